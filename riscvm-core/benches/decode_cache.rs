@@ -0,0 +1,56 @@
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use riscvm_core::cpu::{ExecutionMode, RV64GCRegAbiName::Sp, RV64GC};
+use riscvm_core::ram::MemoryRegion;
+
+/// A recursive `fib(10)`, returning to 0 once it's done, so it runs for as long as we let it
+const FIB: &[u8] = include_bytes!("../../tests/rv64i/fib/fib.bin");
+
+const STACK_TOP: u64 = 0x10_0000;
+const STACK_SIZE: u64 = 0x1_0000;
+
+const INSTRUCTIONS: u64 = 100_000;
+
+fn fib_cpu(cache: bool, mode: ExecutionMode) -> RV64GC {
+    let mut cpu = RV64GC::new();
+    cpu.load_bin(FIB.to_vec());
+    cpu.ram
+        .add_region(MemoryRegion::new(
+            STACK_TOP - STACK_SIZE,
+            STACK_SIZE,
+            vec![0; STACK_SIZE as usize],
+        ))
+        .unwrap();
+    cpu.registers[Sp] = STACK_TOP;
+    cpu.decode_cache.set_enabled(cache);
+    cpu.execution_mode = mode;
+    cpu
+}
+
+fn bench_decode_cache(c: &mut Criterion) {
+    let mut group = c.benchmark_group("fib");
+    group.throughput(criterion::Throughput::Elements(INSTRUCTIONS));
+
+    #[allow(unused_mut)]
+    let mut modes = vec![
+        ("uncached", false, ExecutionMode::Step),
+        ("cached", true, ExecutionMode::Step),
+        ("blocks", true, ExecutionMode::Blocks),
+    ];
+    #[cfg(all(feature = "jit", target_arch = "x86_64"))]
+    modes.push(("jit", true, ExecutionMode::Jit));
+
+    for (name, cache, mode) in modes {
+        group.bench_function(name, |b| {
+            b.iter_batched_ref(
+                || fib_cpu(cache, mode),
+                |cpu| cpu.run(Some(INSTRUCTIONS)),
+                BatchSize::LargeInput,
+            )
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_decode_cache);
+criterion_main!(benches);
@@ -0,0 +1,802 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::cpu::RV64GCRegAbiName::Pc;
+use crate::cpu::{ExitReason, Imm, RV64GCInstruction, Reg, Simm, StepOutcome, RV64GC};
+use crate::exception::{Exception, Trap};
+use crate::sign_extend;
+use crate::sign_extend12;
+#[cfg(all(feature = "jit", target_arch = "x86_64"))]
+use crate::{cpu::ExecutionMode, jit};
+
+const PAGE_SHIFT: u64 = 12;
+
+/// Blocks end after this many instructions even without a branch, so the budget and
+/// `should_quit` are checked every now and then
+const MAX_BLOCK_LEN: usize = 64;
+
+/// Index of the stack pointer, the `*sp` compressed loads and stores are relative to it
+const SP: Reg = 2;
+/// Index of the return address, `c.jalr` links into it
+const RA: Reg = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum LoadKind {
+    Byte,
+    Half,
+    Word,
+    Double,
+    ByteUnsigned,
+    HalfUnsigned,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum StoreKind {
+    Byte,
+    Half,
+    Word,
+    Double,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Cond {
+    Eq,
+    Ne,
+    Lt,
+    Ge,
+    Ltu,
+    Geu,
+}
+
+/// A straight-line instruction with its registers and immediates resolved. Anything that
+/// would write x0 is translated to `Nop`, so x0 stays 0 without being reset every step
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum MicroOp {
+    Nop,
+    LoadImm {
+        rd: Reg,
+        value: u64,
+    },
+    AddImm {
+        rd: Reg,
+        rs1: Reg,
+        imm: i64,
+    },
+    AddImmWord {
+        rd: Reg,
+        rs1: Reg,
+        imm: i32,
+    },
+    XorImm {
+        rd: Reg,
+        rs1: Reg,
+        imm: u64,
+    },
+    OrImm {
+        rd: Reg,
+        rs1: Reg,
+        imm: u64,
+    },
+    AndImm {
+        rd: Reg,
+        rs1: Reg,
+        imm: u64,
+    },
+    SltImm {
+        rd: Reg,
+        rs1: Reg,
+        imm: i64,
+    },
+    ShiftLeftImm {
+        rd: Reg,
+        rs1: Reg,
+        shamt: u32,
+    },
+    ShiftRightImm {
+        rd: Reg,
+        rs1: Reg,
+        shamt: u32,
+    },
+    ShiftRightArithImm {
+        rd: Reg,
+        rs1: Reg,
+        shamt: u32,
+    },
+    Add {
+        rd: Reg,
+        rs1: Reg,
+        rs2: Reg,
+    },
+    Sub {
+        rd: Reg,
+        rs1: Reg,
+        rs2: Reg,
+    },
+    AddWord {
+        rd: Reg,
+        rs1: Reg,
+        rs2: Reg,
+    },
+    SubWord {
+        rd: Reg,
+        rs1: Reg,
+        rs2: Reg,
+    },
+    Xor {
+        rd: Reg,
+        rs1: Reg,
+        rs2: Reg,
+    },
+    Or {
+        rd: Reg,
+        rs1: Reg,
+        rs2: Reg,
+    },
+    And {
+        rd: Reg,
+        rs1: Reg,
+        rs2: Reg,
+    },
+    Slt {
+        rd: Reg,
+        rs1: Reg,
+        rs2: Reg,
+    },
+    Sltu {
+        rd: Reg,
+        rs1: Reg,
+        rs2: Reg,
+    },
+    Load {
+        kind: LoadKind,
+        rd: Reg,
+        rs1: Reg,
+        imm: i64,
+    },
+    Store {
+        kind: StoreKind,
+        rs1: Reg,
+        rs2: Reg,
+        imm: i64,
+    },
+    /// Everything else goes through the interpreter, with pc pointing at it
+    Interpret(RV64GCInstruction),
+}
+
+/// One translated instruction, the pc and bits are kept for traps
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Op {
+    pub(crate) pc: u64,
+    pub(crate) bits: u32,
+    pub(crate) op: MicroOp,
+}
+
+/// How a block ends. Direct exits have a link slot, so the next block is found without a
+/// lookup once it's been run
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum BlockExit {
+    /// The block got too long, or the next instruction has to be stepped
+    Fallthrough {
+        next: u64,
+    },
+    Jump {
+        rd: Reg,
+        link: u64,
+        target: u64,
+    },
+    Branch {
+        cond: Cond,
+        rs1: Reg,
+        rs2: Reg,
+        target: u64,
+        next: u64,
+    },
+    /// `jalr` and friends, the only exit that has to look up the next block
+    Indirect {
+        rd: Reg,
+        rs1: Reg,
+        imm: i64,
+        mask: u64,
+        link: u64,
+    },
+}
+
+/// Where execution goes after a block, and which link slot leads there
+pub(crate) struct Exited {
+    pub(crate) executed: u64,
+    pub(crate) pc: u64,
+    pub(crate) slot: Option<usize>,
+}
+
+#[derive(Debug)]
+pub(crate) struct Block {
+    pub(crate) ops: Vec<Op>,
+    pub(crate) exit: BlockExit,
+    /// The pc of the instruction that ends the block, or where a fallthrough continues
+    pub(crate) end: u64,
+    /// Guest instructions in the block, including the one that ends it
+    pub(crate) len: u64,
+    /// `[taken, not taken]` for branches, jumps and fallthroughs only use the first one
+    links: [Option<u32>; 2],
+    /// How many times the block was run, until it's compiled
+    #[cfg(all(feature = "jit", target_arch = "x86_64"))]
+    runs: u32,
+    #[cfg(all(feature = "jit", target_arch = "x86_64"))]
+    native: Option<jit::NativeBlock>,
+}
+
+/// Basic blocks translated from guest code, for [`ExecutionMode::Blocks`].
+///
+/// Blocks link to each other directly, so the whole cache is dropped when any translated
+/// page is written to or unmapped.
+///
+/// [`ExecutionMode::Blocks`]: crate::cpu::ExecutionMode::Blocks
+#[derive(Debug, Default)]
+pub struct BlockCache {
+    blocks: Vec<Block>,
+    /// Start pc to block, `None` for instructions that are always stepped (like `ecall`)
+    index: HashMap<u64, Option<u32>>,
+    /// Pages any block was translated from
+    pages: HashSet<u64>,
+}
+
+impl BlockCache {
+    pub fn new() -> BlockCache {
+        BlockCache::default()
+    }
+
+    /// Number of translated blocks
+    pub fn len(&self) -> usize {
+        self.blocks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.blocks.is_empty()
+    }
+
+    /// Number of blocks that were compiled by the JIT
+    #[cfg(all(feature = "jit", target_arch = "x86_64"))]
+    pub fn native_blocks(&self) -> usize {
+        self.blocks.iter().filter(|b| b.native.is_some()).count()
+    }
+
+    pub fn clear(&mut self) {
+        self.blocks.clear();
+        self.index.clear();
+        self.pages.clear();
+    }
+
+    /// Drops every block if any of them was translated from `[start, start + len)`
+    pub fn invalidate_range(&mut self, start: u64, len: u64) {
+        if len == 0 {
+            return;
+        }
+
+        let first = start >> PAGE_SHIFT;
+        let last = start.saturating_add(len - 1) >> PAGE_SHIFT;
+        if self.pages.iter().any(|page| (first..=last).contains(page)) {
+            self.clear();
+        }
+    }
+
+    /// Runs the guest a block at a time until it exits, traps, or `budget` instructions
+    /// have been executed
+    pub fn run(&mut self, cpu: &mut RV64GC, budget: Option<u64>) -> ExitReason {
+        let mut executed = 0u64;
+
+        loop {
+            if cpu.should_quit {
+                return cpu.exit_reason();
+            }
+
+            for (start, len) in cpu.ram.code_invalidations.drain(..) {
+                cpu.decode_cache.invalidate_range(start, len);
+                self.invalidate_range(start, len);
+            }
+
+            let pc = cpu.registers[Pc];
+            let mut block = match self.index.get(&pc) {
+                Some(block) => *block,
+                None => self.translate(cpu, pc),
+            };
+
+            // NOTE: Chained blocks run back to back, until an exit that isn't linked yet
+            while let Some(id) = block {
+                let remaining = budget.map_or(u64::MAX, |budget| budget - executed);
+                if self.blocks[id as usize].len > remaining {
+                    break;
+                }
+
+                let exited = match self.execute(cpu, id) {
+                    Ok(exited) => exited,
+                    Err(Trap::Breakpoint { pc }) => return ExitReason::Breakpoint { pc },
+                    Err(trap) => return ExitReason::Trapped(trap),
+                };
+                executed += exited.executed;
+
+                // NOTE: Dropped blocks can't be linked to
+                if !cpu.ram.code_invalidations.is_empty() {
+                    block = None;
+                    break;
+                }
+
+                block = match exited.slot {
+                    Some(slot) => match self.blocks[id as usize].links[slot] {
+                        Some(next) => Some(next),
+                        None => {
+                            let next = self.index.get(&exited.pc).copied().flatten();
+                            self.blocks[id as usize].links[slot] = next;
+                            next
+                        }
+                    },
+                    None => None,
+                };
+            }
+
+            if budget.is_some_and(|budget| executed >= budget) {
+                return ExitReason::BudgetExhausted;
+            }
+
+            // NOTE: Untranslatable instructions, and blocks that don't fit in the budget
+            if block.is_some() || self.index.get(&cpu.registers[Pc]) == Some(&None) {
+                match cpu.step() {
+                    Ok(StepOutcome::Continue) => executed += 1,
+                    Ok(StepOutcome::Halted) => return cpu.exit_reason(),
+                    Err(Trap::Breakpoint { pc }) => return ExitReason::Breakpoint { pc },
+                    Err(trap) => return ExitReason::Trapped(trap),
+                }
+            }
+        }
+    }
+
+    fn execute(&mut self, cpu: &mut RV64GC, id: u32) -> Result<Exited, Trap> {
+        #[cfg(all(feature = "jit", target_arch = "x86_64"))]
+        if cpu.execution_mode == ExecutionMode::Jit {
+            let block = &mut self.blocks[id as usize];
+            if block.native.is_none() && block.runs < jit::HOT_BLOCK_THRESHOLD {
+                block.runs += 1;
+                if block.runs == jit::HOT_BLOCK_THRESHOLD {
+                    block.native = jit::compile(block);
+                }
+            }
+
+            if let Some(native) = &block.native {
+                return jit::execute(native, block, cpu);
+            }
+        }
+
+        let block = &self.blocks[id as usize];
+
+        for (executed, op) in block.ops.iter().enumerate() {
+            if let Err(e) = execute_op(cpu, op) {
+                cpu.registers[Pc] = op.pc;
+                return Err(Trap::from_exception(e, op.pc, op.bits));
+            }
+            cpu.csr.retire();
+
+            // NOTE: The rest of the block may have just been overwritten
+            if matches!(op.op, MicroOp::Store { .. } | MicroOp::Interpret(_))
+                && !cpu.ram.code_invalidations.is_empty()
+            {
+                let next = block.ops.get(executed + 1).map_or(block.end, |op| op.pc);
+                cpu.registers[Pc] = next;
+
+                return Ok(Exited {
+                    executed: executed as u64 + 1,
+                    pc: next,
+                    slot: None,
+                });
+            }
+        }
+
+        let regs = &mut cpu.registers;
+        let (pc, slot) = match &block.exit {
+            BlockExit::Fallthrough { next } => (*next, Some(0)),
+            BlockExit::Jump { rd, link, target } => {
+                if *rd != 0 {
+                    regs[rd] = *link;
+                }
+                (*target, Some(0))
+            }
+            BlockExit::Branch {
+                cond,
+                rs1,
+                rs2,
+                target,
+                next,
+            } => {
+                let (a, b) = (regs[rs1], regs[rs2]);
+                let taken = match cond {
+                    Cond::Eq => a == b,
+                    Cond::Ne => a != b,
+                    Cond::Lt => (a as i64) < (b as i64),
+                    Cond::Ge => (a as i64) >= (b as i64),
+                    Cond::Ltu => a < b,
+                    Cond::Geu => a >= b,
+                };
+
+                if taken {
+                    (*target, Some(0))
+                } else {
+                    (*next, Some(1))
+                }
+            }
+            BlockExit::Indirect {
+                rd,
+                rs1,
+                imm,
+                mask,
+                link,
+            } => {
+                let target = regs[rs1].wrapping_add_signed(*imm) & mask;
+                if *rd != 0 {
+                    regs[rd] = *link;
+                }
+                (target, None)
+            }
+        };
+
+        if !matches!(block.exit, BlockExit::Fallthrough { .. }) {
+            cpu.csr.retire();
+        }
+        cpu.registers[Pc] = pc;
+
+        Ok(Exited {
+            executed: block.len,
+            pc,
+            slot,
+        })
+    }
+
+    /// Translates the block starting at `pc`, `None` if its first instruction can't be
+    /// translated and has to be stepped (or fetching it faults)
+    fn translate(&mut self, cpu: &RV64GC, start: u64) -> Option<u32> {
+        let mut ops = Vec::new();
+        let mut pc = start;
+
+        let exit = loop {
+            if ops.len() == MAX_BLOCK_LEN {
+                break BlockExit::Fallthrough { next: pc };
+            }
+
+            let Ok(bits) = cpu.fetch_at(pc) else {
+                break BlockExit::Fallthrough { next: pc };
+            };
+            let next = pc.wrapping_add(if bits & 0b11 == 0b11 { 4 } else { 2 });
+
+            match translate_instruction(cpu.find_instruction(bits), pc, next) {
+                Translated::Op(op) => ops.push(Op { pc, bits, op }),
+                Translated::Exit(exit) => break exit,
+                Translated::Step => break BlockExit::Fallthrough { next: pc },
+            }
+
+            pc = next;
+        };
+
+        let has_exit = !matches!(exit, BlockExit::Fallthrough { .. });
+        if ops.is_empty() && !has_exit {
+            self.index.insert(start, None);
+            return None;
+        }
+
+        // NOTE: `pc` is where the exit instruction is, which can be up to 4 bytes long
+        self.pages
+            .extend((start >> PAGE_SHIFT)..=(pc.saturating_add(3) >> PAGE_SHIFT));
+
+        let id = self.blocks.len() as u32;
+        self.blocks.push(Block {
+            len: ops.len() as u64 + has_exit as u64,
+            ops,
+            exit,
+            end: pc,
+            links: [None; 2],
+            #[cfg(all(feature = "jit", target_arch = "x86_64"))]
+            runs: 0,
+            #[cfg(all(feature = "jit", target_arch = "x86_64"))]
+            native: None,
+        });
+        self.index.insert(start, Some(id));
+
+        Some(id)
+    }
+}
+
+enum Translated {
+    Op(MicroOp),
+    Exit(BlockExit),
+    /// Ends the block before this instruction, the outer loop steps it
+    Step,
+}
+
+fn translate_instruction(ins: RV64GCInstruction, pc: u64, next: u64) -> Translated {
+    use MicroOp::*;
+    use RV64GCInstruction as I;
+
+    let op = match ins {
+        // NOTE: Control flow ends the block
+        I::Jal(rd, simm) => {
+            return Translated::Exit(BlockExit::Jump {
+                rd,
+                link: next,
+                target: pc.wrapping_add_signed(simm),
+            })
+        }
+        I::Cj(imm) => {
+            return Translated::Exit(BlockExit::Jump {
+                rd: 0,
+                link: next,
+                target: pc.wrapping_add_signed(sign_extend12(imm)),
+            })
+        }
+        I::Jalr(rd, rs1, simm) => {
+            return Translated::Exit(BlockExit::Indirect {
+                rd,
+                rs1,
+                imm: simm,
+                mask: !1,
+                link: next,
+            })
+        }
+        I::Cjr(rs1) => {
+            return Translated::Exit(BlockExit::Indirect {
+                rd: 0,
+                rs1,
+                imm: 0,
+                mask: !0,
+                link: next,
+            })
+        }
+        I::Cjalr(rs1) => {
+            return Translated::Exit(BlockExit::Indirect {
+                rd: RA,
+                rs1,
+                imm: 0,
+                mask: !0,
+                link: next,
+            })
+        }
+        I::Beq(rs1, rs2, imm) => return branch(Cond::Eq, rs1, rs2, imm, 13, pc, next),
+        I::Bne(rs1, rs2, imm) => return branch(Cond::Ne, rs1, rs2, imm, 13, pc, next),
+        I::Blt(rs1, rs2, imm) => return branch(Cond::Lt, rs1, rs2, imm, 13, pc, next),
+        I::Bge(rs1, rs2, imm) => return branch(Cond::Ge, rs1, rs2, imm, 13, pc, next),
+        I::Bltu(rs1, rs2, imm) => return branch(Cond::Ltu, rs1, rs2, imm, 13, pc, next),
+        I::Bgeu(rs1, rs2, imm) => return branch(Cond::Geu, rs1, rs2, imm, 13, pc, next),
+        I::Cbeqz(rs1, imm) => return branch(Cond::Eq, rs1, 0, imm, 9, pc, next),
+        I::Cbnez(rs1, imm) => return branch(Cond::Ne, rs1, 0, imm, 9, pc, next),
+
+        // NOTE: Syscalls, breakpoints and anything that invalidates code goes through `step`
+        I::Ecall | I::Ebreak | I::Cebreak | I::FenceI | I::IllegalInstruction(_) => {
+            return Translated::Step
+        }
+
+        I::Lui(rd, simm) => LoadImm {
+            rd,
+            value: simm as u64,
+        },
+        I::Auipc(rd, simm) => LoadImm {
+            rd,
+            value: pc.wrapping_add_signed(simm),
+        },
+        I::Cli(rd, imm) => LoadImm {
+            rd,
+            value: sign_extend(imm.into(), 6) as u64,
+        },
+        I::Clui(rd, imm) => LoadImm {
+            rd,
+            value: sign_extend(imm.into(), 18) as u64,
+        },
+
+        I::Addi(rd, rs1, imm) => AddImm { rd, rs1, imm },
+        I::Caddi(rd, imm) => AddImm { rd, rs1: rd, imm },
+        I::Caddi16sp(imm) => AddImm {
+            rd: SP,
+            rs1: SP,
+            imm,
+        },
+        I::Caddi4spn(rd, imm) => AddImm {
+            rd,
+            rs1: SP,
+            imm: imm.into(),
+        },
+        I::Addiw(rd, rs1, imm) => AddImmWord {
+            rd,
+            rs1,
+            imm: sign_extend12(imm) as i32,
+        },
+        I::Caddiw(rd, imm) => AddImmWord {
+            rd,
+            rs1: rd,
+            imm: imm as i32,
+        },
+        I::Xori(rd, rs1, imm) => XorImm {
+            rd,
+            rs1,
+            imm: imm as u64,
+        },
+        I::Ori(rd, rs1, imm) => OrImm {
+            rd,
+            rs1,
+            imm: imm as u64,
+        },
+        I::Andi(rd, rs1, imm) => AndImm {
+            rd,
+            rs1,
+            imm: imm as u64,
+        },
+        I::Candi(rd, imm) => AndImm {
+            rd,
+            rs1: rd,
+            imm: imm as u64,
+        },
+        I::Slti(rd, rs1, imm) => SltImm { rd, rs1, imm },
+        I::Slli(rd, rs1, shamt) => ShiftLeftImm { rd, rs1, shamt },
+        I::Srli(rd, rs1, shamt) => ShiftRightImm { rd, rs1, shamt },
+        I::Srai(rd, rs1, shamt) => ShiftRightArithImm { rd, rs1, shamt },
+        I::Cslli(rd, shamt) => ShiftLeftImm { rd, rs1: rd, shamt },
+        I::Csrli(rd, shamt) => ShiftRightImm { rd, rs1: rd, shamt },
+        I::Csrai(rd, shamt) => ShiftRightArithImm { rd, rs1: rd, shamt },
+
+        I::Add(rd, rs1, rs2) => Add { rd, rs1, rs2 },
+        I::Cadd(rd, rs2) => Add { rd, rs1: rd, rs2 },
+        I::Cmv(rd, rs2) => Add { rd, rs1: 0, rs2 },
+        I::Sub(rd, rs1, rs2) => Sub { rd, rs1, rs2 },
+        I::Csub(rd, rs2) => Sub { rd, rs1: rd, rs2 },
+        I::Addw(rd, rs1, rs2) => AddWord { rd, rs1, rs2 },
+        I::Caddw(rd, rs2) => AddWord { rd, rs1: rd, rs2 },
+        I::Subw(rd, rs1, rs2) => SubWord { rd, rs1, rs2 },
+        I::Csubw(rd, rs2) => SubWord { rd, rs1: rd, rs2 },
+        I::Xor(rd, rs1, rs2) => Xor { rd, rs1, rs2 },
+        I::Cxor(rd, rs2) => Xor { rd, rs1: rd, rs2 },
+        I::Or(rd, rs1, rs2) => Or { rd, rs1, rs2 },
+        I::Cor(rd, rs2) => Or { rd, rs1: rd, rs2 },
+        I::And(rd, rs1, rs2) => And { rd, rs1, rs2 },
+        I::Cand(rd, rs2) => And { rd, rs1: rd, rs2 },
+        I::Slt(rd, rs1, rs2) => Slt { rd, rs1, rs2 },
+        I::Sltu(rd, rs1, rs2) => Sltu { rd, rs1, rs2 },
+
+        // NOTE: Loads into x0 still have to fault, the interpreter handles those
+        I::Lb(rd, rs1, imm) if rd != 0 => load(LoadKind::Byte, rd, rs1, imm),
+        I::Lh(rd, rs1, imm) if rd != 0 => load(LoadKind::Half, rd, rs1, imm),
+        I::Lw(rd, rs1, imm) if rd != 0 => load(LoadKind::Word, rd, rs1, imm),
+        I::Ld(rd, rs1, imm) if rd != 0 => load(LoadKind::Double, rd, rs1, imm),
+        I::Lbu(rd, rs1, imm) if rd != 0 => load(LoadKind::ByteUnsigned, rd, rs1, imm),
+        I::Lhu(rd, rs1, imm) if rd != 0 => load(LoadKind::HalfUnsigned, rd, rs1, imm),
+        I::Clw(rd, rs1, imm) => load(LoadKind::Word, rd, rs1, imm.into()),
+        I::Cld(rd, rs1, imm) => load(LoadKind::Double, rd, rs1, imm.into()),
+        I::Clwsp(rd, imm) if rd != 0 => load(LoadKind::Word, rd, SP, imm.into()),
+        I::Cldsp(rd, imm) if rd != 0 => load(LoadKind::Double, rd, SP, imm.into()),
+
+        I::Sb(rs1, rs2, imm) => store(StoreKind::Byte, rs1, rs2, imm),
+        I::Sh(rs1, rs2, imm) => store(StoreKind::Half, rs1, rs2, imm),
+        I::Sw(rs1, rs2, imm) => store(StoreKind::Word, rs1, rs2, imm),
+        I::Sd(rs1, rs2, imm) => store(StoreKind::Double, rs1, rs2, imm),
+        I::Csw(rs1, rs2, imm) => store(StoreKind::Word, rs1, rs2, imm.into()),
+        I::Csd(rs1, rs2, imm) => store(StoreKind::Double, rs1, rs2, imm.into()),
+        I::Cswsp(rs2, imm) => store(StoreKind::Word, SP, rs2, imm.into()),
+        I::Csdsp(rs2, imm) => store(StoreKind::Double, SP, rs2, imm.into()),
+
+        I::Cnop | I::Fence(_, _) => Nop,
+
+        ins => Interpret(ins),
+    };
+
+    Translated::Op(match op {
+        LoadImm { rd: 0, .. }
+        | AddImm { rd: 0, .. }
+        | AddImmWord { rd: 0, .. }
+        | XorImm { rd: 0, .. }
+        | OrImm { rd: 0, .. }
+        | AndImm { rd: 0, .. }
+        | SltImm { rd: 0, .. }
+        | ShiftLeftImm { rd: 0, .. }
+        | ShiftRightImm { rd: 0, .. }
+        | ShiftRightArithImm { rd: 0, .. }
+        | Add { rd: 0, .. }
+        | Sub { rd: 0, .. }
+        | AddWord { rd: 0, .. }
+        | SubWord { rd: 0, .. }
+        | Xor { rd: 0, .. }
+        | Or { rd: 0, .. }
+        | And { rd: 0, .. }
+        | Slt { rd: 0, .. }
+        | Sltu { rd: 0, .. } => Nop,
+        op => op,
+    })
+}
+
+fn branch(cond: Cond, rs1: Reg, rs2: Reg, imm: Imm, bits: u8, pc: u64, next: u64) -> Translated {
+    Translated::Exit(BlockExit::Branch {
+        cond,
+        rs1,
+        rs2,
+        target: pc.wrapping_add_signed(sign_extend(imm.into(), bits)),
+        next,
+    })
+}
+
+fn load(kind: LoadKind, rd: Reg, rs1: Reg, imm: Simm) -> MicroOp {
+    MicroOp::Load { kind, rd, rs1, imm }
+}
+
+fn store(kind: StoreKind, rs1: Reg, rs2: Reg, imm: Simm) -> MicroOp {
+    MicroOp::Store {
+        kind,
+        rs1,
+        rs2,
+        imm,
+    }
+}
+
+pub(crate) fn execute_op(cpu: &mut RV64GC, op: &Op) -> Result<(), Exception> {
+    let regs = &mut cpu.registers;
+
+    match &op.op {
+        MicroOp::Nop => {}
+        MicroOp::LoadImm { rd, value } => regs[rd] = *value,
+        MicroOp::AddImm { rd, rs1, imm } => regs[rd] = regs[rs1].wrapping_add_signed(*imm),
+        MicroOp::AddImmWord { rd, rs1, imm } => {
+            regs[rd] = (regs[rs1] as i32).wrapping_add(*imm) as i64 as u64;
+        }
+        MicroOp::XorImm { rd, rs1, imm } => regs[rd] = regs[rs1] ^ imm,
+        MicroOp::OrImm { rd, rs1, imm } => regs[rd] = regs[rs1] | imm,
+        MicroOp::AndImm { rd, rs1, imm } => regs[rd] = regs[rs1] & imm,
+        MicroOp::SltImm { rd, rs1, imm } => regs[rd] = ((regs[rs1] as i64) < *imm) as u64,
+        MicroOp::ShiftLeftImm { rd, rs1, shamt } => regs[rd] = regs[rs1].wrapping_shl(*shamt),
+        MicroOp::ShiftRightImm { rd, rs1, shamt } => regs[rd] = regs[rs1].wrapping_shr(*shamt),
+        MicroOp::ShiftRightArithImm { rd, rs1, shamt } => {
+            regs[rd] = (regs[rs1] as i64).wrapping_shr(*shamt) as u64;
+        }
+        MicroOp::Add { rd, rs1, rs2 } => regs[rd] = regs[rs1].wrapping_add(regs[rs2]),
+        MicroOp::Sub { rd, rs1, rs2 } => regs[rd] = regs[rs1].wrapping_sub(regs[rs2]),
+        MicroOp::AddWord { rd, rs1, rs2 } => {
+            regs[rd] = (regs[rs1] as i32).wrapping_add(regs[rs2] as i32) as i64 as u64;
+        }
+        MicroOp::SubWord { rd, rs1, rs2 } => {
+            regs[rd] = (regs[rs1] as i32).wrapping_sub(regs[rs2] as i32) as i64 as u64;
+        }
+        MicroOp::Xor { rd, rs1, rs2 } => regs[rd] = regs[rs1] ^ regs[rs2],
+        MicroOp::Or { rd, rs1, rs2 } => regs[rd] = regs[rs1] | regs[rs2],
+        MicroOp::And { rd, rs1, rs2 } => regs[rd] = regs[rs1] & regs[rs2],
+        MicroOp::Slt { rd, rs1, rs2 } => {
+            regs[rd] = ((regs[rs1] as i64) < (regs[rs2] as i64)) as u64;
+        }
+        MicroOp::Sltu { rd, rs1, rs2 } => regs[rd] = (regs[rs1] < regs[rs2]) as u64,
+        MicroOp::Load { kind, rd, rs1, imm } => {
+            let addr = regs[rs1].wrapping_add_signed(*imm);
+            let ram = &cpu.ram;
+            let value = match kind {
+                LoadKind::Byte => ram.read_byte(addr).map(|v| v as i8 as u64),
+                LoadKind::Half => ram.read_halfword(addr).map(|v| v as i16 as u64),
+                LoadKind::Word => ram.read_word(addr).map(|v| v as i32 as u64),
+                LoadKind::Double => ram.read_doubleword(addr),
+                LoadKind::ByteUnsigned => ram.read_byte(addr).map(u64::from),
+                LoadKind::HalfUnsigned => ram.read_halfword(addr),
+            };
+
+            cpu.registers[rd] = value.map_err(Exception::load)?;
+        }
+        MicroOp::Store {
+            kind,
+            rs1,
+            rs2,
+            imm,
+        } => {
+            let addr = regs[rs1].wrapping_add_signed(*imm);
+            let value = regs[rs2];
+            let ram = &mut cpu.ram;
+            match kind {
+                StoreKind::Byte => ram.write_byte(addr, value as u8),
+                StoreKind::Half => ram.write_halfword(addr, value as u16 as u64),
+                StoreKind::Word => ram.write_word(addr, value as u32),
+                StoreKind::Double => ram.write_doubleword(addr, value),
+            }
+            .map_err(Exception::store)?;
+        }
+        MicroOp::Interpret(ins) => {
+            regs[Pc] = op.pc;
+            ins.execute_instruction(cpu)?;
+            cpu.registers[0] = 0;
+        }
+    }
+
+    Ok(())
+}
@@ -1,30 +1,46 @@
 use bit::BitIndex;
 use goblin::elf::Elf;
 use rand::RngCore;
-use tracing::info;
+use tracing::debug;
 use tracing::span;
 use tracing::trace;
 use tracing::Level;
 
-use crate::fcsr::classify_f32;
-use crate::fcsr::round_f32;
+use crate::block::BlockCache;
+use crate::csr;
+use crate::exception::Exception;
+use crate::exception::Trap;
+use crate::fcsr;
+use crate::fcsr::FloatExtends;
 use crate::fcsr::RoundingMode;
 use crate::fcsr::FCSR;
-use crate::opcodes::*;
+use crate::fd::FdTable;
+use crate::icache::DecodeCache;
+use crate::isa::Isa;
+use crate::launch::{LaunchConfig, DEFAULT_STACK_TOP};
+use crate::opcodes;
+use crate::ram::MemoryError;
 use crate::ram::MemoryRegion;
 use crate::ram::Ram;
+use crate::ram::PAGE_SIZE;
 use crate::sign_extend;
 use crate::sign_extend12;
+use crate::snapshot::{Snapshot, SnapshotError};
+use crate::softfloat;
 use crate::syscalls::*;
+use crate::vector::{self, VMem, VOperands, VSrc, VectorState};
+use std::cmp::Ordering;
+use std::collections::BTreeSet;
 use std::fmt::Display;
 use std::ops::{Index, IndexMut};
+use std::sync::OnceLock;
 
 use crate::cpu::RV64GCRegAbiName::*;
 
-type Reg = u8;
-type Imm = u32;
-type Simm = i64;
-type Csr = u16;
+pub(crate) type Reg = u8;
+pub(crate) type Imm = u32;
+pub(crate) type Simm = i64;
+pub(crate) type Csr = u16;
 
 const AT_NULL: u64 = 0; // End of auxv
 #[allow(dead_code)]
@@ -35,34 +51,121 @@ const AT_PHDR: u64 = 3; // Program headers address
 const AT_PHENT: u64 = 4; // Size of program header entries
 const AT_PHNUM: u64 = 5; // Number of program header entries
 const AT_PAGESZ: u64 = 6; // System page size
-#[allow(dead_code)]
 const AT_BASE: u64 = 7; // Interpreter base address
-#[allow(dead_code)]
 const AT_FLAGS: u64 = 8; // Flags
 const AT_ENTRY: u64 = 9; // Program entry point address
 const AT_UID: u64 = 11; // Real user ID
 const AT_EUID: u64 = 12; // Effective user ID
 const AT_GID: u64 = 13; // Real group ID
 const AT_EGID: u64 = 14; // Effective group ID
-#[allow(dead_code)]
 const AT_PLATFORM: u64 = 15; // Platform string address
-#[allow(dead_code)]
 const AT_HWCAP: u64 = 16; // Hardware capabilities
 const AT_CLKTCK: u64 = 17; // Clock ticks per second
 const AT_SECURE: u64 = 23; // Secure mode boolean
 const AT_RANDOM: u64 = 25; // Address of random bytes
 const AT_EXECFN: u64 = 31; // Filename of executed program
+const AT_MINSIGSTKSZ: u64 = 51; // Minimal stack size for signal delivery
+
+/// `MINSIGSTKSZ` from the generic uapi `signal.h`, which riscv uses
+const MINSIGSTKSZ: u64 = 2048;
+
+/// Single letter extensions the hart always implements
+pub const EXTENSIONS: &str = "imafdc";
+
+/// `AT_HWCAP` has bit `letter - 'a'` set for every single letter extension, V included
+/// when `isa` has it
+pub fn hwcap(isa: &Isa) -> u64 {
+    let v = if isa.v { "v" } else { "" };
+    EXTENSIONS
+        .bytes()
+        .chain(v.bytes())
+        .fold(0, |caps, ext| caps | 1 << (ext - b'a'))
+}
+
+/// Where an `ET_DYN` interpreter (`ld.so`) is mapped, well away from the program and the stack
+const INTERP_BASE: u64 = 0x40_0000_0000;
+
+/// What happened after a call to [`RV64GC::step`] that didn't trap
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StepOutcome {
+    /// The instruction retired, and the guest can keep running
+    Continue,
+    /// The guest asked to quit (e.g. through `exit`)
+    Halted,
+}
+
+/// How [`RV64GC::run`] executes the guest
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ExecutionMode {
+    /// One [`RV64GC::step`] per instruction
+    #[default]
+    Step,
+    /// Straight-line code is translated into blocks of micro-ops, which are chained on
+    /// direct jumps and branches. Syscalls and the like still go through [`RV64GC::step`]
+    Blocks,
+    /// Like `Blocks`, with hot blocks compiled to x86_64
+    #[cfg(all(feature = "jit", target_arch = "x86_64"))]
+    Jit,
+}
+
+/// Why [`RV64GC::start`] (or [`RV64GC::run`]) returned
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitReason {
+    /// The guest called `exit` or `exit_group` with this status
+    Exited(i32),
+    /// The guest raised an exception we can't deliver to it
+    Trapped(Trap),
+    /// The instruction budget passed to [`RV64GC::run`] ran out
+    BudgetExhausted,
+    /// The guest executed `ebreak`, the pc is left pointing at it
+    Breakpoint { pc: u64 },
+    /// The host set `should_quit` without the guest exiting
+    Stopped,
+}
 
 #[derive(Debug)]
 pub struct RV64GC {
     pub registers: RV64GCRegisters,
     pub float_registers: RV64GCFloatRegisters,
     pub fcsr: FCSR,
+    /// The vector registers and CSRs, only used when `isa` has V
+    pub vector: VectorState,
+    pub csr: csr::Csr,
     pub ram: Ram,
+    /// Set by `exit`/`exit_group`, or by the host to stop the guest before the next step
     pub should_quit: bool,
+    /// The status the guest passed to `exit`/`exit_group`
+    pub exit_code: Option<i32>,
+    pub launch_config: LaunchConfig,
+    pub fds: FdTable,
+    /// How far the executable was moved from its link address, 0 unless it's a PIE
+    pub load_bias: u64,
+    /// Where the interpreter was loaded, if the executable asked for one
+    pub interp_base: Option<u64>,
+    /// Where the heap starts, the page after the executable's highest segment
+    pub brk_start: u64,
+    /// The current program break, the heap is mapped up to the page it's on
+    pub brk: u64,
+    pub unknown_syscall_policy: UnknownSyscallPolicy,
+    /// Handlers that get a look at syscalls before [`LinuxSyscalls`]
+    pub syscall_overrides: SyscallOverrides,
+    /// The extensions the guest can use on top of RV64GC, set it before the guest runs
+    pub isa: Isa,
+    pub decode_cache: DecodeCache,
+    pub execution_mode: ExecutionMode,
+    /// Blocks translated for [`ExecutionMode::Blocks`]
+    pub block_cache: BlockCache,
+    /// Unknown syscalls that were already logged
+    pub(crate) warned_syscalls: BTreeSet<u64>,
     elf_bin: Vec<u8>,
 }
 
+// NOTE: Forks are meant to run on threads of their own
+const _: () = {
+    const fn assert_send<T: Send>() {}
+    assert_send::<RV64GC>();
+};
+
 impl Default for RV64GC {
     fn default() -> Self {
         Self::new()
@@ -70,19 +173,89 @@ impl Default for RV64GC {
 }
 
 impl RV64GC {
-    fn write_auxv_to_stack(
+    /// Lays out the initial stack the way the Linux ELF loader does, from the top down:
+    /// envp and argv strings, `AT_RANDOM` bytes, then (16 byte aligned) argc, argv, envp
+    /// and auxv.
+    fn initialize_stack(
         &mut self,
-        elf: Elf,
-        phdr_ptr: Option<u64>,
-        rand_ptr: u64,
-        execfn_ptr: u64,
-    ) -> u64 {
-        let mut map = vec![
-            (AT_NULL, 0),
+        elf: &Elf,
+        phdr_addr: Option<u64>,
+        interp_base: Option<u64>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let config = &self.launch_config;
+        let ram = &mut self.ram;
+
+        let stack_start = config
+            .stack_top
+            .checked_sub(config.stack_size)
+            .filter(|&start| start >= config.mmap_top())
+            .ok_or_else(|| {
+                format!(
+                    "A {} byte stack doesn't fit below 0x{:x}",
+                    config.stack_size, config.stack_top
+                )
+            })?;
+        // NOTE: Only the pages the strings and auxv are written to get backed
+        let stack_region = MemoryRegion::new(stack_start, config.stack_size, Vec::new());
+        ram.add_region(stack_region)?;
+
+        // NOTE: Like Linux, keep a NULL word at the very top, so word-at-a-time string
+        // functions reading past the last string stay inside the stack
+        let mut sp = config.stack_top - 8;
+
+        let mut push_str = |sp: &mut u64, s: &str| -> Result<u64, MemoryError> {
+            *sp -= s.len() as u64 + 1;
+            for (i, c) in s.bytes().chain([0]).enumerate() {
+                ram.write_byte(*sp + i as u64, c)?;
+            }
+
+            Ok(*sp)
+        };
+
+        let execfn = config
+            .execfn
+            .as_deref()
+            .or(config.argv.first().map(String::as_str))
+            .unwrap_or("");
+        let execfn_ptr = push_str(&mut sp, execfn)?;
+
+        let mut envp_ptrs = config
+            .envp
+            .iter()
+            .rev()
+            .map(|var| push_str(&mut sp, var))
+            .collect::<Result<Vec<u64>, _>>()?;
+        envp_ptrs.reverse();
+
+        let mut argv_ptrs = config
+            .argv
+            .iter()
+            .rev()
+            .map(|arg| push_str(&mut sp, arg))
+            .collect::<Result<Vec<u64>, _>>()?;
+        argv_ptrs.reverse();
+
+        let platform_ptr = push_str(&mut sp, "riscv64")?;
+
+        let rand_bytes = config.random_bytes.unwrap_or_else(|| {
+            let mut bytes = [0u8; 16];
+            rand::thread_rng().fill_bytes(&mut bytes);
+            bytes
+        });
+
+        sp -= rand_bytes.len() as u64;
+        for (i, b) in rand_bytes.iter().enumerate() {
+            ram.write_byte(sp + i as u64, *b)?;
+        }
+
+        let rand_ptr = sp;
+
+        let mut auxv = vec![
             (AT_PHENT, elf.header.e_phentsize.into()),
             (AT_PHNUM, elf.header.e_phnum.into()),
             (AT_PAGESZ, 4096),
-            (AT_ENTRY, elf.entry),
+            (AT_ENTRY, elf.entry.wrapping_add(self.load_bias)),
+            (AT_FLAGS, 0),
             (AT_UID, 1000),
             (AT_EUID, 1000),
             (AT_GID, 1000),
@@ -91,186 +264,50 @@ impl RV64GC {
             (AT_RANDOM, rand_ptr),
             (AT_CLKTCK, 100),
             (AT_EXECFN, execfn_ptr),
+            (AT_HWCAP, hwcap(&self.isa)),
+            (AT_PLATFORM, platform_ptr),
+            (AT_MINSIGSTKSZ, MINSIGSTKSZ),
         ];
 
-        if let Some(p) = phdr_ptr {
-            map.push((AT_PHDR, p))
-        }
-
-        let mut sp = self.registers[Sp];
-        for (k, v) in map.iter() {
-            sp -= 8;
-            self.ram.write_doubleword(sp, *v).unwrap();
-
-            sp -= 8;
-            self.ram.write_doubleword(sp, *k).unwrap();
-        }
-
-        sp
-    }
-
-    fn initialize_stack_with_ext_lib(&mut self, elf: Elf, phdr_addr: Option<u64>) {
-        use linux_libc_auxv::{AuxVar, AuxVarFlags, InitialLinuxLibcStackLayoutBuilder};
-
-        let stack_top = 0x7FFF_FFFF_FFFF_FFF0;
-        let stack_size: u64 = 8 * 1024 * 1024; // 8 MB
-        let stack_start = stack_top - stack_size;
-        let ram = &mut self.ram;
-        let stack_region = MemoryRegion::new(stack_start, stack_size, vec![0; stack_size as usize]);
-        ram.add_region(stack_region).unwrap();
-
-        let mut builder = InitialLinuxLibcStackLayoutBuilder::new();
-
-        let args_vec = std::env::args().skip(1).collect::<Vec<String>>();
-
-        let prog_name = args_vec.first().unwrap();
-
-        builder.arg_v.push(prog_name);
-        for arg in &args_vec[1..] {
-            builder.arg_v.push(arg);
-        }
-
-        // let envp_vec = std::env::vars()
-        //     .map(|(k, v)| format!("{k}={v}"))
-        //     .collect::<Vec<String>>();
-        // for s in envp_vec.iter() {
-        //     builder.env_v.push(s);
-        // }
-
-        let mut rand_bytes = [0u8; 16];
-        let mut rng = rand::thread_rng();
-        rng.fill_bytes(&mut rand_bytes);
-        let auxv = [
-            AuxVar::Phdr(phdr_addr.unwrap() as *const u8),
-            AuxVar::Phent(elf.header.e_phentsize.into()),
-            AuxVar::Phnum(elf.header.e_phnum.into()),
-            AuxVar::Pagesz(4096),
-            AuxVar::Entry(elf.header.e_entry as *const u8),
-            AuxVar::Uid(1000),
-            AuxVar::Gid(1000),
-            AuxVar::EUid(1000),
-            AuxVar::EGid(1000),
-            AuxVar::Secure(false),
-            AuxVar::Random(rand_bytes),
-            AuxVar::Clktck(100),
-            AuxVar::Flags(AuxVarFlags::empty()),
-            AuxVar::ExecFn(prog_name),
-        ];
-
-        auxv.into_iter().for_each(|e| {
-            builder.aux_v.insert(e);
-        });
-
-        let mut stack_bytes = vec![0u8; builder.total_size()];
-        let low_addr = stack_top - builder.total_size() as u64;
-        unsafe {
-            builder.serialize_into_buf(stack_bytes.as_mut_slice(), low_addr);
+        if let Some(p) = phdr_addr {
+            auxv.push((AT_PHDR, p));
         }
 
-        for (idx, byte) in stack_bytes.into_iter().enumerate() {
-            self.ram.write_byte(low_addr + idx as u64, byte).unwrap();
+        if let Some(base) = interp_base {
+            auxv.push((AT_BASE, base));
         }
-        self.registers[Sp] = low_addr;
-    }
 
-    fn initialize_stack(&mut self, elf: Elf, phdr_addr: Option<u64>) {
-        let ram = &mut self.ram;
-        let args = std::env::args().collect::<Vec<String>>();
-        let mut sp = 0x7FFF_FFFF_FFFF_FFF0;
-        let stack_size: u64 = 8 * 1024 * 1024; // 8 MB
-        let stack_start = sp - stack_size;
-
-        let stack_region = MemoryRegion::new(stack_start, stack_size, vec![0; stack_size as usize]);
-        ram.add_region(stack_region).unwrap();
-
-        let mut rand_bytes = [0u8; 16];
-        let mut rng = rand::thread_rng();
-        rng.fill_bytes(&mut rand_bytes);
-
-        for b in rand_bytes.iter().rev() {
-            sp -= 1;
-            ram.write_byte(sp, *b).unwrap();
-        }
-
-        let rand_ptr = sp;
-
-        let mut envp_ptrs = vec![];
-
-        // Push host environment vars to stack
-        for (k, v) in std::env::vars() {
-            for c in format!("{k}={v}\0").bytes().rev() {
-                sp -= 1;
-                ram.write_byte(sp, c).unwrap();
+        for &(key, value) in &config.auxv {
+            match auxv.iter_mut().find(|(k, _)| *k == key) {
+                Some(entry) => entry.1 = value,
+                None => auxv.push((key, value)),
             }
-
-            envp_ptrs.push(sp);
         }
 
-        sp &= !0xF;
-
-        let mut argv_ptrs = vec![];
-
-        for arg in args.iter().skip(2).rev() {
-            sp -= 1;
-            ram.write_byte(sp, 0).unwrap();
-            for c in arg.bytes().rev() {
-                sp -= 1;
-                ram.write_byte(sp, c).unwrap();
-            }
-
-            argv_ptrs.push(sp);
+        let mut words = vec![argv_ptrs.len() as u64];
+        words.extend(&argv_ptrs);
+        words.push(0);
+        words.extend(&envp_ptrs);
+        words.push(0);
+        for (k, v) in auxv {
+            words.extend([k, v]);
         }
+        words.extend([AT_NULL, 0]);
 
-        let path_name = args.get(1).unwrap().split('/').last().unwrap();
-        sp -= 1;
-        ram.write_byte(sp, 0).unwrap();
-        for c in path_name.bytes().rev() {
-            sp -= 1;
-            ram.write_byte(sp, c).unwrap();
+        // NOTE: The ABI requires sp to be 16 byte aligned on entry
+        sp = (sp - words.len() as u64 * 8) & !0xF;
+        for (i, word) in words.into_iter().enumerate() {
+            ram.write_doubleword(sp + i as u64 * 8, word)?;
         }
 
-        argv_ptrs.push(sp);
-
-        // NOTE: Leave 10kb of extra space for vars
-        sp &= !0xF;
-
-        // NOTE: Let go of the mutable borrow for now
-        let _ = ram;
-
         self.registers[Sp] = sp;
-        sp = self.write_auxv_to_stack(elf, phdr_addr, rand_ptr, *argv_ptrs.last().unwrap());
-
-        let ram = &mut self.ram;
-
-        // Push NULL terminator for envp
-        sp -= 8;
-        ram.write_doubleword(sp, 0).unwrap();
-
-        // FIXME: Throws a 'malloc(): corrupted top size'
-        // for i in envp_ptrs {
-        //     sp -= 8;
-        //     ram.write_doubleword(sp, i).unwrap();
-        // }
-
-        // Push NULL terminator for argv
-        sp -= 8;
-        ram.write_doubleword(sp, 0).unwrap();
-
-        for i in argv_ptrs.iter() {
-            sp -= 8;
-            ram.write_doubleword(sp, *i).unwrap();
-        }
 
-        sp -= 8;
-        // Push argc
-        ram.write_doubleword(sp, argv_ptrs.len() as u64).unwrap();
-
-        self.registers[Sp] = sp;
+        Ok(())
     }
 
     pub fn new() -> RV64GC {
         let mut registers = RV64GCRegisters::new();
-        registers[Sp] = 0x7FFF_FFFF_FFFF_FFF0;
+        registers[Sp] = DEFAULT_STACK_TOP;
 
         let ram = Ram::new();
 
@@ -281,47 +318,169 @@ impl RV64GC {
             float_registers,
             ram,
             fcsr: FCSR::new(),
+            vector: VectorState::new(),
+            csr: csr::Csr::new(),
             should_quit: false,
+            exit_code: None,
+            launch_config: LaunchConfig::default(),
+            fds: FdTable::new(),
+            load_bias: 0,
+            interp_base: None,
+            brk_start: 0,
+            brk: 0,
+            unknown_syscall_policy: UnknownSyscallPolicy::default(),
+            syscall_overrides: SyscallOverrides::default(),
+            isa: Isa::default(),
+            decode_cache: DecodeCache::new(),
+            execution_mode: ExecutionMode::default(),
+            block_cache: BlockCache::new(),
+            warned_syscalls: BTreeSet::new(),
             elf_bin: vec![],
         }
     }
 
     pub fn load_bin(&mut self, bin: Vec<u8>) {
-        let bin_load = MemoryRegion::new(0, bin.len() as u64, bin);
+        // NOTE: Raw binaries are code, and executable so that writes to them reach the
+        // decode cache
+        let flags = (goblin::elf::program_header::PF_R
+            | goblin::elf::program_header::PF_W
+            | goblin::elf::program_header::PF_X) as u64;
+        let end = bin.len() as u64;
+        let bin_load = MemoryRegion::new_with_flags(0, end, bin, flags);
         self.ram.add_region(bin_load).unwrap();
+        self.brk_start = end.next_multiple_of(PAGE_SIZE);
+        self.brk = self.brk_start;
         self.registers[Pc] = 0;
     }
 
-    pub fn load_elf(&mut self, bin: Vec<u8>) -> Result<(), Box<dyn std::error::Error>> {
+    pub fn load_elf(
+        &mut self,
+        bin: Vec<u8>,
+        config: LaunchConfig,
+    ) -> Result<(), Box<dyn std::error::Error>> {
         let span = span!(Level::TRACE, "load_elf");
         let _guard = span.enter();
 
         let elf = goblin::elf::Elf::parse(&bin)?;
-        let entry = elf.entry;
-        self.registers[Pc] = entry;
 
         if elf.header.e_machine != goblin::elf::header::EM_RISCV {
             return Err("Not a RISC-V ELF".into());
         }
 
-        let mut ehdr = None;
+        self.launch_config = config;
+
+        // NOTE: A PIE is linked at (or near) 0, so it is moved to `pie_base` as a whole
+        self.load_bias = if elf.header.e_type == goblin::elf::header::ET_DYN {
+            let lowest_vaddr = elf
+                .program_headers
+                .iter()
+                .filter(|ph| ph.p_type == goblin::elf::program_header::PT_LOAD)
+                .map(|ph| ph.p_vaddr & !0xFFF)
+                .min()
+                .unwrap_or(0);
+
+            self.launch_config
+                .slide(self.launch_config.pie_base)
+                .wrapping_sub(lowest_vaddr)
+        } else {
+            0
+        };
+
+        self.load_segments(&elf, &bin, self.load_bias)?;
+
+        // NOTE: Like Linux, the heap starts right after the executable, not the interpreter
+        self.brk_start = elf
+            .program_headers
+            .iter()
+            .filter(|ph| ph.p_type == goblin::elf::program_header::PT_LOAD)
+            .try_fold(0, |end: u64, ph| {
+                let start = ph.p_vaddr.wrapping_add(self.load_bias);
+                start.checked_add(ph.p_memsz).map(|e| end.max(e))
+            })
+            .and_then(|end| end.checked_next_multiple_of(PAGE_SIZE))
+            .ok_or("The executable ends past the top of the address space")?;
+        self.brk = self.brk_start;
+        let phdr_addr = RV64GC::phdr_addr(&elf, self.load_bias);
+        self.registers[Pc] = elf.entry.wrapping_add(self.load_bias);
+
+        let interp_path = elf
+            .interpreter
+            .map(|interp_path| self.launch_config.resolve_path(interp_path));
+
+        let interp_base = match interp_path {
+            // NOTE: Starting without it would jump to code expecting ld.so's relocations
+            Some(path) if !path.exists() => {
+                return Err(format!(
+                    "The interpreter {} wasn't found, dynamically linked programs need a sysroot \
+                     that has it",
+                    path.display()
+                )
+                .into());
+            }
+            Some(path) => {
+                debug!("loading interpreter from {}", path.display());
+
+                let interp_bin = std::fs::read(&path).map_err(|e| {
+                    format!("Couldn't read the interpreter {}: {e}", path.display())
+                })?;
+                let interp = goblin::elf::Elf::parse(&interp_bin)?;
+
+                if interp.header.e_machine != goblin::elf::header::EM_RISCV {
+                    return Err(format!("{} is not a RISC-V ELF", path.display()).into());
+                }
+
+                let base = if interp.header.e_type == goblin::elf::header::ET_DYN {
+                    self.launch_config.slide(INTERP_BASE)
+                } else {
+                    0
+                };
+
+                self.load_segments(&interp, &interp_bin, base)?;
+                self.registers[Pc] = base.wrapping_add(interp.entry);
+
+                Some(base)
+            }
+            None => None,
+        };
+
+        self.interp_base = interp_base;
+        self.initialize_stack(&elf, phdr_addr, interp_base)?;
+        self.elf_bin = bin;
+
+        trace!("mem regions: {}", self.ram);
 
+        Ok(())
+    }
+
+    /// Maps every `PT_LOAD` segment `bias` bytes above its link address
+    fn load_segments(
+        &mut self,
+        elf: &Elf,
+        bin: &[u8],
+        bias: u64,
+    ) -> Result<(), Box<dyn std::error::Error>> {
         for ph in &elf.program_headers {
             trace!("Reading ph of type: {:#08x}", ph.p_type);
             match ph.p_type {
                 goblin::elf::program_header::PT_LOAD => {
-                    let v_addr = ph.p_vaddr;
-                    if ph.p_offset == 0 {
-                        // HACK: Is it guaranteed to start 64 bytes ahead??!!
-                        ehdr = Some(v_addr + elf.header.e_phoff);
-                    }
+                    let v_addr = ph.p_vaddr.wrapping_add(bias);
                     let mem_size = ph.p_memsz;
 
-                    let mut data = vec![0u8; mem_size as usize];
-
-                    for (i, byte) in bin[ph.file_range()].iter().enumerate() {
-                        data[i] = *byte;
-                    }
+                    // NOTE: Only the file's bytes are copied, the rest of the segment (.bss)
+                    // reads as zeros
+                    let data = ph
+                        .p_offset
+                        .checked_add(ph.p_filesz)
+                        .filter(|_| ph.p_filesz <= mem_size)
+                        .filter(|_| {
+                            v_addr
+                                .checked_add(mem_size)
+                                .and_then(|end| end.checked_next_multiple_of(PAGE_SIZE))
+                                .is_some()
+                        })
+                        .and_then(|end| bin.get(ph.p_offset as usize..end as usize))
+                        .ok_or_else(|| format!("Bad PT_LOAD segment at 0x{:x}", ph.p_vaddr))?
+                        .to_vec();
 
                     let memory_region =
                         MemoryRegion::new_with_flags(v_addr, mem_size, data, ph.p_flags.into());
@@ -339,749 +498,300 @@ impl RV64GC {
             }
         }
 
-        self.initialize_stack_with_ext_lib(elf, ehdr);
-        self.elf_bin = bin;
+        Ok(())
+    }
 
-        trace!("mem regions: {}", self.ram);
+    /// Where the program headers are mapped, from `PT_PHDR` if there is one, otherwise from
+    /// the `PT_LOAD` segment that covers `e_phoff` in the file
+    fn phdr_addr(elf: &Elf, bias: u64) -> Option<u64> {
+        use goblin::elf::program_header::{PT_LOAD, PT_PHDR};
 
-        Ok(())
+        if let Some(ph) = elf.program_headers.iter().find(|ph| ph.p_type == PT_PHDR) {
+            return Some(ph.p_vaddr.wrapping_add(bias));
+        }
+
+        let phoff = elf.header.e_phoff;
+        let phdrs_size = u64::from(elf.header.e_phnum) * u64::from(elf.header.e_phentsize);
+
+        elf.program_headers
+            .iter()
+            .find(|ph| {
+                ph.p_type == PT_LOAD
+                    && ph.p_offset <= phoff
+                    && phoff.saturating_add(phdrs_size) <= ph.p_offset.saturating_add(ph.p_filesz)
+            })
+            .map(|ph| {
+                ph.p_vaddr
+                    .wrapping_add(phoff - ph.p_offset)
+                    .wrapping_add(bias)
+            })
     }
 
     pub fn reset(&mut self) {
         self.registers = RV64GCRegisters::new();
-        self.registers[Sp] = 0x7FFF_FFFF_FFFF_FFF0;
+        self.registers[Sp] = DEFAULT_STACK_TOP;
         self.float_registers = RV64GCFloatRegisters::new();
+        self.fcsr = FCSR::new();
+        self.vector = VectorState::new();
+        self.csr = csr::Csr::new();
         self.ram = Ram::new();
-
-        self.load_elf(self.elf_bin.clone()).unwrap();
+        self.decode_cache.clear();
+        self.block_cache.clear();
+        self.should_quit = false;
+        self.exit_code = None;
+        self.fds = FdTable::new();
+        self.warned_syscalls.clear();
+
+        self.load_elf(self.elf_bin.clone(), self.launch_config.clone())
+            .unwrap();
+
+        // NOTE: Blocks may be running with the block cache taken out of the cpu
+        self.ram.code_invalidations.push((0, u64::MAX));
     }
 
-    // NOTE: Takes mutable reference, to pass down the call stack
-    pub fn start(&mut self) {
-        let span = span!(Level::TRACE, "cpu loop");
-        let _guard = span.enter();
-        // while self.registers[Pc] <= (self.program.len() - 4) as u64 {
-        //     self.step();
-        // }
-
-        while !self.should_quit {
-            self.step();
-        }
+    /// Copies the guest's state, to go back to with [`RV64GC::restore`] as often as needed
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot::capture(self)
     }
 
-    pub fn step(&mut self) {
-        let span = span!(Level::TRACE, "step");
-        let _guard = span.enter();
-
-        trace!("pc: {:08x}", self.registers[Pc]);
-
-        // if self.points_to_break.contains(&self.registers[Pc]) {
-        //     println!("{}", &self.registers);
-        // }
-
-        self.execute();
-        assert_eq!(self.registers[0], 0);
+    /// Puts the guest back where `snapshot` was taken, see [`Snapshot`] for what that covers
+    pub fn restore(&mut self, snapshot: &Snapshot) -> Result<(), SnapshotError> {
+        snapshot.restore_into(self)
     }
 
-    pub fn execute(&mut self) {
-        let current_ins = self.ram.read_word(self.registers[Pc]).unwrap();
-
-        if let Ok(env) = std::env::var("DUMP_OPS") {
-            if env == "1" {
-                trace!("opcode: {current_ins:08x}");
-            }
-        }
-
-        let ins = self.find_instruction(current_ins);
-        ins.execute_instruction(self);
-
-        if let RV64GCInstruction::IllegalInstruction(opcode) = ins {
-            panic!(
-                "instruction not implemented:\n\tpc: {:08x}\n\tinstruction: {opcode:08x}",
-                self.registers[Pc]
-            );
-        }
-
-        if current_ins & 3 == 3 {
-            self.registers[Pc] = self.registers[Pc].wrapping_add(4);
-        } else {
-            self.registers[Pc] = self.registers[Pc].wrapping_add(2);
-        }
+    /// A copy of the guest that carries on independently, sharing memory with this one
+    /// copy-on-write. Open files are opened again with offsets of their own, the caches
+    /// start out empty and syscall overrides aren't carried over (handlers can't be cloned)
+    pub fn fork_state(&self) -> std::io::Result<RV64GC> {
+        Ok(RV64GC {
+            registers: self.registers.clone(),
+            float_registers: self.float_registers.clone(),
+            fcsr: self.fcsr.clone(),
+            vector: self.vector.clone(),
+            csr: self.csr.clone(),
+            ram: self.ram.clone(),
+            should_quit: self.should_quit,
+            exit_code: self.exit_code,
+            launch_config: self.launch_config.clone(),
+            fds: self.fds.try_clone()?,
+            load_bias: self.load_bias,
+            interp_base: self.interp_base,
+            brk_start: self.brk_start,
+            brk: self.brk,
+            unknown_syscall_policy: self.unknown_syscall_policy,
+            syscall_overrides: SyscallOverrides::default(),
+            isa: self.isa,
+            decode_cache: DecodeCache::new(),
+            execution_mode: self.execution_mode,
+            block_cache: BlockCache::new(),
+            warned_syscalls: self.warned_syscalls.clone(),
+            elf_bin: self.elf_bin.clone(),
+        })
     }
 
-    pub fn find_instruction(&self, current_ins: u32) -> RV64GCInstruction {
-        use RV64GCInstruction::*;
-
-        // Default values
-        let rd = current_ins.bit_range(7..12) as Reg;
-        let rs1 = current_ins.bit_range(15..20) as Reg;
-        let rs2 = current_ins.bit_range(20..25) as Reg;
-        let rs3 = current_ins.bit_range(27..32) as Reg;
-        let imm = current_ins.bit_range(20..32) as Imm;
-
-        let rm = current_ins.bit_range(12..15) as Reg;
-
-        if current_ins & 0b11 != 0b11 {
-            let c_ins = current_ins as u16;
-            let c_rs1 = c_ins.bit_range(7..12) as Reg;
-            let x_rs1 = c_ins.bit_range(7..10) as Reg;
-            let x2_rs1 = c_ins.bit_range(2..5) as Reg;
-            let c_rs2 = c_ins.bit_range(2..7) as Reg;
-
-            return match c_ins {
-                i if is_rv64c_nop_instruction(i) => Cnop,
-                i if is_rv64c_ebreak_instruction(i) => Cebreak,
-                i if is_rv64c_jalr_instruction(i) => Cjalr(c_rs1),
-                i if is_rv64c_add_instruction(i) => Cadd(c_rs1, c_rs2),
-
-                i if is_rv64c_jr_instruction(i) => Cjr(c_rs1),
-                i if is_rv64c_mv_instruction(i) => {
-                    trace!("c.mv opcode: {i:04x}");
-                    Cmv(c_rs1, c_rs2)
-                }
-
-                i if is_rv64c_addi_instruction(i) => {
-                    let imm = (i.bit(12) as u32) << 5 | (i.bit_range(2..7) as u32);
-                    let simm = sign_extend(imm.into(), 6);
-
-                    Caddi(c_rs1, simm)
-                }
-
-                i if is_rv64c_addiw_instruction(i) => {
-                    let imm = (i.bit(12) as u32) << 5 | (i.bit_range(2..7) as u32);
-                    let simm = sign_extend(imm.into(), 6);
-
-                    Caddiw(c_rs1, simm)
-                }
-
-                i if is_rv64c_addi16sp_instruction(i) => {
-                    let imm = (i.bit(12) as u16) << 9
-                        | i.bit_range(3..5) << 7
-                        | (i.bit(5) as u16) << 6
-                        | (i.bit(2) as u16) << 5
-                        | (i.bit(6) as u16) << 4;
-
-                    Caddi16sp(sign_extend(imm as u64, 10))
-                }
-
-                i if is_rv64c_lui_instruction(i) => {
-                    let imm = (i.bit(12) as u32) << 17 | u32::from(i.bit_range(2..7)) << 12;
-
-                    trace!("c.lui imm: {}", sign_extend(imm as u64, 18));
-
-                    Clui(c_rs1, imm)
-                }
-
-                i if is_rv64c_andi_instruction(i) => {
-                    let imm = (i.bit(12) as u32) << 5 | u32::from(i.bit_range(2..7));
-                    let simm = sign_extend(imm.into(), 6);
-                    Candi(x_rs1 + 8, simm)
-                }
-
-                i if is_rv64c_ldsp_instruction(i) => {
-                    let imm = (i.bit_range(2..5) as u32) << 6
-                        | (i.bit(12) as u32) << 5
-                        | (i.bit_range(5..7) as u32) << 3;
-
-                    trace!("c.ldsp opcode: {i:04x}");
-
-                    Cldsp(c_rs1, imm)
-                }
-
-                i if is_rv64c_lwsp_instruction(i) => {
-                    let imm = (i.bit_range(2..4) as u32) << 6
-                        | (i.bit(12) as u32) << 5
-                        | (i.bit_range(4..7) as u32) << 2;
-
-                    trace!("c.lwsp opcode: {i:04x}");
-
-                    Clwsp(c_rs1, imm)
-                }
-
-                i if is_rv64c_swsp_instruction(i) => {
-                    let imm = i.bit_range(7..9) << 6 | i.bit_range(9..13) << 2;
-
-                    Cswsp(c_rs2, imm.into())
-                }
-
-                i if is_rv64c_addi4spn_instruction(i) => {
-                    let imm = u32::from(i.bit_range(7..11)) << 6
-                        | u32::from(i.bit_range(11..13)) << 4
-                        | (i.bit(5) as u32) << 3
-                        | (i.bit(6) as u32) << 2;
-
-                    trace!("c.addi4spn opcode: {i:04x}");
-
-                    Caddi4spn(x2_rs1 + 8, imm)
-                }
-
-                i if is_rv64c_li_instruction(i) => {
-                    trace!("c.li instruction: {:04x}", i);
-                    let imm = (i.bit(12) as u32) << 5 | u32::from(i.bit_range(2..7));
-                    Cli(c_rs1, imm)
-                }
-
-                i if is_rv64c_slli_instruction(i) => {
-                    let imm = (i.bit(12) as u32) << 5 | u32::from(i.bit_range(2..7));
-                    Cslli(c_rs1, imm)
-                }
-
-                i if is_rv64c_srli_instruction(i) => {
-                    let imm = (i.bit(12) as u32) << 5 | u32::from(i.bit_range(2..7));
-                    Csrli(x_rs1 + 8, imm)
-                }
-
-                i if is_rv64c_srai_instruction(i) => {
-                    let imm = (i.bit(12) as u32) << 5 | u32::from(i.bit_range(2..7));
-                    Csrai(x_rs1 + 8, imm)
-                }
-
-                i if is_rv64c_sdsp_instruction(i) => {
-                    let imm = (i.bit_range(7..10) as u32) << 6 | (i.bit_range(10..13) as u32) << 3;
-
-                    Csdsp(c_rs2, imm)
-                }
-
-                i if is_rv64c_ld_instruction(i) => {
-                    let imm = (i.bit_range(5..7) as u32) << 6 | (i.bit_range(10..13) as u32) << 3;
-
-                    Cld(x2_rs1 + 8, x_rs1 + 8, imm)
-                }
-
-                i if is_rv64c_beqz_instruction(i) => {
-                    let imm = (i.bit(12) as u32) << 8
-                        | (i.bit_range(5..7) as u32) << 6
-                        | (i.bit(2) as u32) << 5
-                        | (i.bit_range(10..12) as u32) << 3
-                        | (i.bit_range(3..5) as u32) << 1;
-
-                    trace!("beqz imm: {imm}");
-
-                    Cbeqz(x_rs1 + 8, imm)
-                }
-
-                i if is_rv64c_bnez_instruction(i) => {
-                    let imm = (i.bit(12) as u32) << 8
-                        | (i.bit_range(5..7) as u32) << 6
-                        | (i.bit(2) as u32) << 5
-                        | (i.bit_range(10..12) as u32) << 3
-                        | (i.bit_range(3..5) as u32) << 1;
-
-                    Cbnez(x_rs1 + 8, imm)
-                }
-
-                i if is_rv64c_sd_instruction(i) => {
-                    let imm = (i.bit_range(5..7) as u32) << 6 | (i.bit_range(10..13) as u32) << 3;
-
-                    Csd(x_rs1 + 8, x2_rs1 + 8, imm)
-                }
-
-                // Wtf is this bit layout????
-                // [ 11 | 4 | 9 | 8 | 10 | 6 | 7 | 3 | 2 | 1 | 5 ]
-                //   12  11  10   9    8   7   6   5   4   3   2
-                i if is_rv64c_j_instruction(i) => {
-                    let imm = (i.bit(12) as u32) << 11
-                        | (i.bit(8) as u32) << 10
-                        | (i.bit_range(9..11) as u32) << 8
-                        | (i.bit(6) as u32) << 7
-                        | (i.bit(7) as u32) << 6
-                        | (i.bit(2) as u32) << 5
-                        | (i.bit(11) as u32) << 4
-                        | (i.bit_range(3..6) as u32) << 1;
-
-                    Cj(imm)
-                }
-
-                i if is_rv64c_sw_instruction(i) => {
-                    let imm = (i.bit(5) as u32) << 6
-                        | (i.bit_range(10..13) as u32) << 3
-                        | (i.bit(6) as u32) << 2;
-
-                    Csw(x_rs1 + 8, x2_rs1 + 8, imm)
-                }
-
-                i if is_rv64c_lw_instruction(i) => {
-                    let imm = (i.bit(5) as u32) << 6
-                        | (i.bit_range(10..13) as u32) << 3
-                        | (i.bit(6) as u32) << 2;
-
-                    // NOTE: Rd is swapped for some reason
-                    Clw(x2_rs1 + 8, x_rs1 + 8, imm)
-                }
-
-                i if is_rv64c_or_instruction(i) => Cor(x_rs1 + 8, x2_rs1 + 8),
-
-                i if is_rv64c_and_instruction(i) => Cand(x_rs1 + 8, x2_rs1 + 8),
-
-                i if is_rv64c_xor_instruction(i) => Cxor(x_rs1 + 8, x2_rs1 + 8),
-
-                i if is_rv64c_sub_instruction(i) => Csub(x_rs1 + 8, x2_rs1 + 8),
-
-                i if is_rv64c_addw_instruction(i) => Caddw(x_rs1 + 8, x2_rs1 + 8),
-
-                i if is_rv64c_subw_instruction(i) => Csubw(x_rs1 + 8, x2_rs1 + 8),
-
-                i if is_rv64c_fsd_instruction(i) => {
-                    let imm = i.bit_range(5..7) << 6 | i.bit_range(10..13) << 3;
-                    Cfsd(x_rs1 + 8, x2_rs1 + 8, imm.into())
-                }
+    // NOTE: Takes mutable reference, to pass down the call stack
+    pub fn start(&mut self) -> ExitReason {
+        self.run(None)
+    }
 
-                i if is_rv64c_fsdsp_instruction(i) => {
-                    let imm = i.bit_range(7..10) << 6 | i.bit_range(10..13) << 3;
+    /// Runs until the guest exits, traps, or `budget` instructions have been executed
+    pub fn run(&mut self, budget: Option<u64>) -> ExitReason {
+        let span = span!(Level::TRACE, "cpu loop");
+        let _guard = span.enter();
 
-                    Cfsdsp(c_rs2, imm.into())
-                }
+        if self.execution_mode != ExecutionMode::Step {
+            // NOTE: Taken out so blocks can borrow the cpu mutably while they run
+            let mut blocks = std::mem::take(&mut self.block_cache);
+            let reason = blocks.run(self, budget);
+            self.block_cache = blocks;
 
-                _ => IllegalInstruction(c_ins.into()),
-            };
+            return reason;
         }
-        match current_ins {
-            i if is_rv64i_add_instruction(i) => Add(rd, rs1, rs2),
-
-            i if is_rv64i_addi_instruction(i) => Addi(rd, rs1, sign_extend12(imm)),
 
-            i if is_rv64i_auipc_instruction(i) => {
-                let ov_imm = i.bit_range(12..32) << 12;
-                Auipc(rd, sign_extend(ov_imm.into(), 32))
-            }
-
-            i if is_rv64i_lui_instruction(i) => {
-                let ov_imm = i.bit_range(12..32) << 12;
-                Lui(rd, sign_extend(ov_imm.into(), 32))
-            }
-
-            i if is_rv64i_slti_instruction(i) => Slti(rd, rs1, sign_extend12(imm)),
-
-            i if is_rv64i_sltiu_instruction(i) => Sltiu(rd, rs1, imm),
-
-            i if is_rv64i_xori_instruction(i) => Xori(rd, rs1, sign_extend12(imm)),
-
-            i if is_rv64i_ori_instruction(i) => Ori(rd, rs1, sign_extend12(imm)),
-
-            i if is_rv64i_andi_instruction(i) => Andi(rd, rs1, sign_extend12(imm)),
-
-            i if is_rv64i_slli_instruction(i) => {
-                let shamt = i.bit_range(20..26);
-                Slli(rd, rs1, shamt)
-            }
-
-            i if is_rv64i_srli_instruction(i) => {
-                let shamt = i.bit_range(20..26);
-                Srli(rd, rs1, shamt)
-            }
+        let mut executed = 0u64;
 
-            i if is_rv64i_srai_instruction(i) => {
-                let shamt = i.bit_range(20..26);
-                Srai(rd, rs1, shamt)
+        loop {
+            if budget.is_some_and(|budget| executed >= budget) {
+                return ExitReason::BudgetExhausted;
             }
 
-            i if is_rv64i_sll_instruction(i) => Sll(rd, rs1, rs2),
-
-            i if is_rv64i_srl_instruction(i) => Srl(rd, rs1, rs2),
-
-            i if is_rv64i_slt_instruction(i) => Slt(rd, rs1, rs2),
-
-            i if is_rv64i_sltu_instruction(i) => Sltu(rd, rs1, rs2),
-
-            i if is_rv64i_sub_instruction(i) => Sub(rd, rs1, rs2),
-
-            i if is_rv64i_xor_instruction(i) => Xor(rd, rs1, rs2),
-
-            i if is_rv64i_and_instruction(i) => And(rd, rs1, rs2),
-
-            i if is_rv64i_or_instruction(i) => Or(rd, rs1, rs2),
-
-            i if is_rv64i_ecall_instruction(i) => Ecall,
-
-            i if is_rv64i_fence_instruction(i) => {
-                Fence(i.bit_range(20..24) as u8, i.bit_range(24..28) as u8)
+            match self.step() {
+                Ok(StepOutcome::Continue) => executed += 1,
+                Ok(StepOutcome::Halted) => return self.exit_reason(),
+                Err(Trap::Breakpoint { pc }) => return ExitReason::Breakpoint { pc },
+                Err(trap) => return ExitReason::Trapped(trap),
             }
+        }
+    }
 
-            i if is_rv64i_lb_instruction(i) => Lb(rd, rs1, sign_extend12(imm)),
-
-            i if is_rv64i_lbu_instruction(i) => Lbu(rd, rs1, sign_extend12(imm)),
-
-            i if is_rv64i_lhu_instruction(i) => Lhu(rd, rs1, sign_extend12(imm)),
-
-            i if is_rv64i_sb_instruction(i) => {
-                let lo_offset = i.bit_range(7..12);
-                let hi_offset = i.bit_range(25..32);
-
-                let offset = (hi_offset << 5) | lo_offset;
-
-                Sb(rs1, rs2, sign_extend12(offset))
-            }
-
-            i if is_rv64i_sh_instruction(i) => {
-                let lo_offset = i.bit_range(7..12);
-                let hi_offset = i.bit_range(25..32);
-
-                let offset = (hi_offset << 5) | lo_offset;
-
-                Sh(rs1, rs2, sign_extend12(offset))
-            }
-
-            i if is_rv64i_lw_instruction(i) => Lw(rd, rs1, sign_extend12(imm)),
-
-            i if is_rv64i_sw_instruction(i) => {
-                let lo_offset = i.bit_range(7..12);
-                let hi_offset = i.bit_range(25..32);
-
-                let offset = (hi_offset << 5) | lo_offset;
-
-                Sw(rs1, rs2, sign_extend12(offset))
-            }
-
-            i if is_rv64i_ld_instruction(i) => Ld(rd, rs1, sign_extend12(imm)),
-
-            i if is_rv64i_sd_instruction(i) => {
-                let lo_offset = i.bit_range(7..12);
-                let hi_offset = i.bit_range(25..32);
-
-                let offset = (hi_offset << 5) | lo_offset;
-
-                Sd(rs1, rs2, sign_extend12(offset))
-            }
-
-            i if is_rv64i_jal_instruction(i) => {
-                let offset = (i.bit(31) as u32) << 20
-                    | i.bit_range(12..20) << 12
-                    | (i.bit(20) as u32) << 11
-                    | i.bit_range(21..31) << 1;
-
-                let s_offset = sign_extend(offset.into(), 21);
-
-                trace!("offset: {:#020b}", offset);
-                Jal(rd, s_offset)
-            }
-
-            i if is_rv64i_jalr_instruction(i) => Jalr(rd, rs1, sign_extend12(imm)),
-
-            i if is_rv64i_bge_instruction(i) => {
-                let offset = (i.bit(31) as u32) << 12
-                    | (i.bit(7) as u32) << 11
-                    | i.bit_range(25..31) << 5
-                    | i.bit_range(8..12) << 1;
-
-                Bge(rs1, rs2, offset)
-            }
-
-            i if is_rv64i_bgeu_instruction(i) => {
-                let offset = (i.bit(31) as u32) << 12
-                    | (i.bit(7) as u32) << 11
-                    | i.bit_range(25..31) << 5
-                    | i.bit_range(8..12) << 1;
-
-                Bgeu(rs1, rs2, offset)
-            }
-
-            i if is_rv64i_beq_instruction(i) => {
-                let offset = (i.bit(31) as u32) << 12
-                    | (i.bit(7) as u32) << 11
-                    | i.bit_range(25..31) << 5
-                    | i.bit_range(8..12) << 1;
-
-                Beq(rs1, rs2, offset)
-            }
-
-            i if is_rv64i_bne_instruction(i) => {
-                let offset = (i.bit(31) as u32) << 12
-                    | (i.bit(7) as u32) << 11
-                    | i.bit_range(25..31) << 5
-                    | i.bit_range(8..12) << 1;
-
-                Bne(rs1, rs2, offset)
-            }
-
-            i if is_rv64i_blt_instruction(i) => {
-                let offset = (i.bit(31) as u32) << 12
-                    | (i.bit(7) as u32) << 11
-                    | i.bit_range(25..31) << 5
-                    | i.bit_range(8..12) << 1;
-
-                Blt(rs1, rs2, offset)
-            }
-
-            i if is_rv64i_bltu_instruction(i) => {
-                let offset = (i.bit(31) as u32) << 12
-                    | (i.bit(7) as u32) << 11
-                    | i.bit_range(25..31) << 5
-                    | i.bit_range(8..12) << 1;
-
-                Bltu(rs1, rs2, offset)
-            }
-
-            i if is_rv64i_addiw_instruction(i) => Addiw(rd, rs1, imm),
-
-            i if is_rv64i_slliw_instruction(i) => {
-                let shamt = current_ins.bit_range(20..26);
-
-                Slliw(rd, rs1, shamt)
-            }
-
-            i if is_rv64i_srliw_instruction(i) => {
-                let shamt = current_ins.bit_range(20..26);
-
-                Srliw(rd, rs1, shamt)
-            }
-
-            i if is_rv64i_sraiw_instruction(i) => {
-                let shamt = i.bit_range(20..25);
-
-                Sraiw(rd, rs1, shamt)
-            }
-
-            i if is_rv64i_addw_instruction(i) => Addw(rd, rs1, rs2),
-
-            i if is_rv64i_subw_instruction(i) => Subw(rd, rs1, rs2),
-
-            i if is_rv64i_sllw_instruction(i) => Sllw(rd, rs1, rs2),
-
-            i if is_rv64i_srlw_instruction(i) => Srlw(rd, rs1, rs2),
-
-            i if is_rv64i_sraw_instruction(i) => Sraw(rd, rs1, rs2),
-
-            i if is_rv64i_lwu_instruction(i) => Lwu(rd, rs1, imm),
-
-            i if is_rv64m_mul_instruction(i) => Mul(rd, rs1, rs2),
-
-            i if is_rv64m_mulh_instruction(i) => Mulh(rd, rs1, rs2),
-
-            i if is_rv64m_mulhsu_instruction(i) => Mulhsu(rd, rs1, rs2),
-
-            i if is_rv64m_mulhu_instruction(i) => Mulhu(rd, rs1, rs2),
-
-            i if is_rv64m_div_instruction(i) => Div(rd, rs1, rs2),
-
-            i if is_rv64m_divu_instruction(i) => Divu(rd, rs1, rs2),
-
-            i if is_rv64m_rem_instruction(i) => Rem(rd, rs1, rs2),
-
-            i if is_rv64m_remu_instruction(i) => Remu(rd, rs1, rs2),
-
-            i if is_rv64m_mulw_instruction(i) => Mulw(rd, rs1, rs2),
-
-            i if is_rv64m_divw_instruction(i) => Divw(rd, rs1, rs2),
-
-            i if is_rv64m_divuw_instruction(i) => Divuw(rd, rs1, rs2),
-
-            i if is_rv64m_remw_instruction(i) => Remw(rd, rs1, rs2),
-
-            i if is_rv64m_remuw_instruction(i) => Remuw(rd, rs1, rs2),
-
-            i if is_rv64a_lrw_instruction(i) => Lrw(rd, rs1),
-
-            i if is_rv64a_lrd_instruction(i) => Lrd(rd, rs1),
-
-            i if is_rv64a_scw_instruction(i) => Scw(rd, rs1, rs2),
-
-            i if is_rv64a_scd_instruction(i) => Scd(rd, rs1, rs2),
-
-            i if is_rv64a_amoswapw_instruction(i) => Amoswapw(rd, rs1, rs2),
-
-            i if is_rv64a_amoaddw_instruction(i) => Amoaddw(rd, rs1, rs2),
-
-            i if is_rv64a_amoxorw_instruction(i) => Amoxorw(rd, rs1, rs2),
-
-            i if is_rv64a_amoandw_instruction(i) => Amoandw(rd, rs1, rs2),
-
-            i if is_rv64a_amoorw_instruction(i) => Amoorw(rd, rs1, rs2),
-
-            i if is_rv64a_amominw_instruction(i) => Amominw(rd, rs1, rs2),
-
-            i if is_rv64a_amomaxw_instruction(i) => Amomaxw(rd, rs1, rs2),
-
-            i if is_rv64a_amominuw_instruction(i) => Amominuw(rd, rs1, rs2),
-
-            i if is_rv64a_amomaxuw_instruction(i) => Amomaxuw(rd, rs1, rs2),
-
-            i if is_rv64a_amoswapd_instruction(i) => Amoswapd(rd, rs1, rs2),
-
-            i if is_rv64a_amoaddd_instruction(i) => Amoaddd(rd, rs1, rs2),
-
-            i if is_rv64a_amoxord_instruction(i) => Amoxord(rd, rs1, rs2),
-
-            i if is_rv64a_amoandd_instruction(i) => Amoandd(rd, rs1, rs2),
-
-            i if is_rv64a_amoord_instruction(i) => Amoord(rd, rs1, rs2),
-
-            i if is_rv64a_amomind_instruction(i) => Amomind(rd, rs1, rs2),
-
-            i if is_rv64a_amomaxd_instruction(i) => Amomaxd(rd, rs1, rs2),
-
-            i if is_rv64a_amominud_instruction(i) => Amominud(rd, rs1, rs2),
-
-            i if is_rv64a_amomaxud_instruction(i) => Amomaxud(rd, rs1, rs2),
-
-            // RV64F
-            i if is_rv64f_fmadds_instruction(i) => Fmadds(rd, rm, rs1, rs2, rs3),
-            i if is_rv64f_fmsubs_instruction(i) => Fmsubs(rd, rm, rs1, rs2, rs3),
-            i if is_rv64f_fnmadds_instruction(i) => Fnmadds(rd, rm, rs1, rs2, rs3),
-            i if is_rv64f_fnmsubs_instruction(i) => Fnmsubs(rd, rm, rs1, rs2, rs3),
-
-            i if is_rv64f_fadds_instruction(i) => Fadds(rd, rm, rs1, rs2),
-            i if is_rv64f_fsubs_instruction(i) => Fsubs(rd, rm, rs1, rs2),
-            i if is_rv64f_fmuls_instruction(i) => Fmuls(rd, rm, rs1, rs2),
-            i if is_rv64f_fdivs_instruction(i) => Fdivs(rd, rm, rs1, rs2),
-            i if is_rv64f_fsqrts_instruction(i) => Fsqrts(rd, rm, rs1),
-
-            i if is_rv64f_fsgnjs_instruction(i) => Fsgnjs(rd, rs1, rs2),
-            i if is_rv64f_fsgnjns_instruction(i) => Fsgnjs(rd, rs1, rs2),
-            i if is_rv64f_fsgnjxs_instruction(i) => Fsgnjs(rd, rs1, rs2),
-
-            i if is_rv64f_fmins_instruction(i) => Fmins(rd, rs1, rs2),
-            i if is_rv64f_fmaxs_instruction(i) => Fmaxs(rd, rs1, rs2),
-
-            i if is_rv64f_fcvtws_instruction(i) => Fcvtws(rd, rm, rs1),
-            i if is_rv64f_fcvtwus_instruction(i) => Fcvtws(rd, rm, rs1),
-            i if is_rv64f_fmvxw_instruction(i) => Fmvxw(rd, rs1),
-            i if is_rv64f_fmvwx_instruction(i) => Fmvwx(rd, rs1),
-
-            i if is_rv64f_feqs_instruction(i) => Feqs(rd, rs1, rs2),
-            i if is_rv64f_flts_instruction(i) => Flts(rd, rs1, rs2),
-            i if is_rv64f_fles_instruction(i) => Fles(rd, rs1, rs2),
-
-            i if is_rv64f_fclasss_instruction(i) => Fclasss(rd, rs1),
-
-            i if is_rv64f_fcvtsw_instruction(i) => Fcvtsw(rd, rm, rs1),
-            i if is_rv64f_fcvtswu_instruction(i) => Fcvtswu(rd, rm, rs1),
-
-            i if is_rv64f_flw_instruction(i) => {
-                trace!("flw: {i:08x}");
-                Flw(rd, rs1, imm)
-            }
-            i if is_rv64f_fsw_instruction(i) => {
-                let imm = i.bit_range(25..32) << 5 | i.bit_range(7..12);
-                trace!("fsw: {i:08x}");
-                trace!("imm: {}", sign_extend12(imm));
-
-                Fsw(rs1, rs2, imm)
-            }
-
-            // RV64D
-            i if is_rv64f_fsd_instruction(i) => {
-                let imm = i.bit_range(25..32) << 5 | i.bit_range(7..12);
-                let simm = sign_extend12(imm);
-
-                Fsd(rs1, rs2, simm)
-            }
-
-            i if is_rv64f_fsgnjd_instruction(i) => Fsgnjd(rd, rs1, rs2),
-
-            i if is_rv64f_fcvtds_instruction(i) => Fcvtds(rd, rm, rs1),
-
-            i if is_rv64f_fmvxd_instruction(i) => Fmvxd(rd, rs1),
-
-            _ => IllegalInstruction(current_ins),
+    /// Why the guest halted, only meaningful once `should_quit` is set
+    pub fn exit_reason(&self) -> ExitReason {
+        match self.exit_code {
+            Some(code) => ExitReason::Exited(code),
+            None => ExitReason::Stopped,
         }
     }
 
-    pub fn syscall_handler(&mut self) {
-        let span = span!(Level::TRACE, "syscall_handler");
+    /// Executes a single instruction.
+    ///
+    /// On a trap the pc is left pointing at the faulting instruction, and no architectural
+    /// state besides what the instruction already wrote is changed.
+    pub fn step(&mut self) -> Result<StepOutcome, Trap> {
+        let span = span!(Level::TRACE, "step");
         let _guard = span.enter();
 
-        let syscall_id = self.registers[A7];
-        trace!("system call: {syscall_id}");
-
-        match syscall_id {
-            62 => lseek(self),
-
-            63 => read(self),
-
-            64 => write(self),
+        trace!("pc: {:08x}", self.registers[Pc]);
 
-            66 => writev(self),
+        if self.should_quit {
+            return Ok(StepOutcome::Halted);
+        }
 
-            78 => readlink(self),
+        let pc = self.registers[Pc];
 
-            80 => lstat(self),
+        for (start, len) in self.ram.code_invalidations.drain(..) {
+            self.decode_cache.invalidate_range(start, len);
+        }
 
-            93 => {
-                let error_code = self.registers[A0];
-                info!("Program exited with code: {error_code}");
-                self.should_quit = true;
+        let (current_ins, ins) = match self.decode_cache.get(pc) {
+            Some(decoded) => decoded,
+            None => {
+                let current_ins = self.fetch().map_err(|e| Trap::from_exception(e, pc, 0))?;
+                let ins = self.find_instruction(current_ins);
+                self.decode_cache.insert(pc, current_ins, ins);
+                (current_ins, ins)
             }
+        };
 
-            94 => {
-                let error_code = self.registers[A0];
-                info!("Program exited with code: {error_code}");
-                self.should_quit = true;
-            }
+        self.execute_decoded(current_ins, ins)
+            .map_err(|e| Trap::from_exception(e, pc, current_ins))?;
 
-            // NOTE: set_tid
-            96 => {
-                // PID
-                self.registers[A0] = 0;
-            }
+        // NOTE: Instructions write rd unconditionally, so x0 is restored here instead
+        self.registers[0] = 0;
 
-            98 => futex(self),
+        if self.should_quit {
+            Ok(StepOutcome::Halted)
+        } else {
+            Ok(StepOutcome::Continue)
+        }
+    }
 
-            // NOTE: set_robust_list
-            99 => {
-                self.registers[A0] = 0;
-            }
+    /// Fetches the instruction at pc, only reading the upper half if it isn't compressed
+    pub fn fetch(&self) -> Result<u32, Exception> {
+        self.fetch_at(self.registers[Pc])
+    }
 
-            113 => clock_gettime(self),
+    /// Fetches the instruction at `pc`, see [`RV64GC::fetch`]
+    pub fn fetch_at(&self, pc: u64) -> Result<u32, Exception> {
+        if pc & 1 != 0 {
+            return Err(Exception::InstructionAddressMisaligned(pc));
+        }
 
-            131 => tgkill(self),
+        let lo = self.ram.fetch_halfword(pc).map_err(Exception::fetch)? as u32;
+        if lo & 3 != 3 {
+            return Ok(lo);
+        }
 
-            134 => sig_action(self),
+        let hi = self
+            .ram
+            .fetch_halfword(pc.wrapping_add(2))
+            .map_err(Exception::fetch)? as u32;
 
-            135 => rt_sigprocmask(self),
+        Ok(hi << 16 | lo)
+    }
 
-            172 => getpid(self),
-            178 => gettid(self),
+    /// Decodes and executes `current_ins`, as if it was at pc
+    pub fn execute(&mut self, current_ins: u32) -> Result<(), Exception> {
+        let ins = self.find_instruction(current_ins);
+        self.execute_decoded(current_ins, ins)
+    }
 
-            214 => brk(self),
+    /// Executes `ins`, already decoded from `current_ins`, and moves pc past it
+    pub fn execute_decoded(
+        &mut self,
+        current_ins: u32,
+        ins: RV64GCInstruction,
+    ) -> Result<(), Exception> {
+        if dump_ops() {
+            trace!("opcode: {current_ins:08x}");
+        }
 
-            222 => mmap(self),
+        ins.execute_instruction(self)?;
+        self.csr.retire();
+
+        if current_ins & 3 == 3 {
+            self.registers[Pc] = self.registers[Pc].wrapping_add(4);
+        } else {
+            self.registers[Pc] = self.registers[Pc].wrapping_add(2);
+        }
 
-            226 => mprotect(self),
+        Ok(())
+    }
 
-            258 => riscv_hwprobe(self),
+    /// Decodes `current_ins` with the instruction table in [`opcodes`], instructions from
+    /// extensions that aren't enabled in `isa` are illegal
+    pub fn find_instruction(&self, current_ins: u32) -> RV64GCInstruction {
+        let ins = opcodes::decode(current_ins);
+        if self.isa.supports(&ins) {
+            ins
+        } else {
+            RV64GCInstruction::IllegalInstruction(current_ins)
+        }
+    }
 
-            261 => prlimit64(self),
+    pub fn syscall_handler(&mut self) -> Result<(), Exception> {
+        let span = span!(Level::TRACE, "syscall_handler");
+        let _guard = span.enter();
 
-            278 => getrandom(self),
+        let syscall_id = self.registers[A7];
+        trace!(
+            "system call: {syscall_id} ({})",
+            syscall_name(syscall_id).unwrap_or("unknown")
+        );
 
-            // NOTE: Print i64
-            1000 => {
-                let ptr = self.registers[A0] as i64;
-                info!("i64: {}", ptr);
-            }
+        if let Some(mut handler) = self.syscall_overrides.take(syscall_id) {
+            let res = handler.handle(self, syscall_id);
+            self.syscall_overrides.restore(syscall_id, handler);
 
-            // NOTE: Dump registers
-            1001 => {
-                info!("{}", self.registers);
+            if res? == Dispatch::Handled {
+                return Ok(());
             }
+        }
 
-            // NOTE: Print i64 from ptr
-            1100 => {
-                let ptr = self.registers[A0];
-                let val = self.ram.read_doubleword(ptr).unwrap();
-
-                info!("i64: {}", val as i64);
-            }
-            //
-            // NOTE: Print i32 from ptr
-            1101 => {
-                let ptr = self.registers[A0];
-                let val = self.ram.read_word(ptr).unwrap();
+        LinuxSyscalls.handle(self, syscall_id).map(|_| ())
+    }
+}
 
-                info!("i64: {}", val as i32);
-            }
+/// `DUMP_OPS=1` traces every opcode, the environment is only read once
+fn dump_ops() -> bool {
+    static DUMP_OPS: OnceLock<bool> = OnceLock::new();
+    *DUMP_OPS.get_or_init(|| std::env::var("DUMP_OPS").is_ok_and(|env| env == "1"))
+}
 
-            // NOTE: Print float from ptr
-            1110 => {
-                let ptr = self.registers[A0];
-                let value = f32::from_bits(self.ram.read_word(ptr).unwrap());
+/// The full 128 bit carry-less product, Zbc's instructions each take a part of it
+fn clmul(a: u64, b: u64) -> u128 {
+    (0..64)
+        .filter(|i| b >> i & 1 == 1)
+        .fold(0, |product, i| product ^ u128::from(a) << i)
+}
 
-                info!("float: {}", value);
-            }
+/// The rounding mode an F or D instruction uses, reserved ones make it illegal
+fn rounding_mode(cpu: &RV64GC, rm: u8) -> Result<RoundingMode, Exception> {
+    cpu.fcsr
+        .rounding_mode(rm)
+        .ok_or(Exception::IllegalInstruction)
+}
 
-            _ => todo!(),
-        }
+/// LR/SC and AMOs have to be naturally aligned
+fn check_atomic_alignment(
+    addr: u64,
+    size: u64,
+    exception: fn(u64) -> Exception,
+) -> Result<(), Exception> {
+    if !addr.is_multiple_of(size) {
+        return Err(exception(addr));
     }
+
+    Ok(())
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -1113,7 +823,7 @@ pub enum RV64GCInstruction {
     Csrrs(Reg, Reg, Csr),
     Csrrc(Reg, Reg, Csr),
     Csrrwi(Reg, Reg, Csr),
-    Csrrsi(Reg, Imm, Csr),
+    Csrrsi(Reg, Reg, Csr),
     Csrrci(Reg, Reg, Csr),
     Ecall,
     Ebreak,
@@ -1165,6 +875,256 @@ pub enum RV64GCInstruction {
     Remw(Reg, Reg, Reg),
     Remuw(Reg, Reg, Reg),
 
+    // NOTE: Zba
+    Adduw(Reg, Reg, Reg),
+    Sh1add(Reg, Reg, Reg),
+    Sh2add(Reg, Reg, Reg),
+    Sh3add(Reg, Reg, Reg),
+    Sh1adduw(Reg, Reg, Reg),
+    Sh2adduw(Reg, Reg, Reg),
+    Sh3adduw(Reg, Reg, Reg),
+    Slliuw(Reg, Reg, Imm),
+
+    // NOTE: Zbb
+    Andn(Reg, Reg, Reg),
+    Orn(Reg, Reg, Reg),
+    Xnor(Reg, Reg, Reg),
+    Clz(Reg, Reg),
+    Clzw(Reg, Reg),
+    Ctz(Reg, Reg),
+    Ctzw(Reg, Reg),
+    Cpop(Reg, Reg),
+    Cpopw(Reg, Reg),
+    Max(Reg, Reg, Reg),
+    Maxu(Reg, Reg, Reg),
+    Min(Reg, Reg, Reg),
+    Minu(Reg, Reg, Reg),
+    Sextb(Reg, Reg),
+    Sexth(Reg, Reg),
+    Zexth(Reg, Reg),
+    Rol(Reg, Reg, Reg),
+    Rolw(Reg, Reg, Reg),
+    Ror(Reg, Reg, Reg),
+    Rorw(Reg, Reg, Reg),
+    Rori(Reg, Reg, Imm),
+    Roriw(Reg, Reg, Imm),
+    Orcb(Reg, Reg),
+    Rev8(Reg, Reg),
+
+    // NOTE: Zbc
+    Clmul(Reg, Reg, Reg),
+    Clmulh(Reg, Reg, Reg),
+    Clmulr(Reg, Reg, Reg),
+
+    // NOTE: Zbs
+    Bclr(Reg, Reg, Reg),
+    Bclri(Reg, Reg, Imm),
+    Bext(Reg, Reg, Reg),
+    Bexti(Reg, Reg, Imm),
+    Binv(Reg, Reg, Reg),
+    Binvi(Reg, Reg, Imm),
+    Bset(Reg, Reg, Reg),
+    Bseti(Reg, Reg, Imm),
+
+    // NOTE: V, see vector.rs
+    Vsetvli(Reg, Reg, u16),
+    Vsetivli(Reg, u8, u16),
+    Vsetvl(Reg, Reg, Reg),
+    Vle(Reg, Reg, VMem),
+    Vleff(Reg, Reg, VMem),
+    Vlse(Reg, Reg, Reg, VMem),
+    Vluxei(Reg, Reg, Reg, VMem),
+    Vloxei(Reg, Reg, Reg, VMem),
+    Vlr(Reg, Reg, VMem),
+    Vlm(Reg, Reg),
+    Vse(Reg, Reg, VMem),
+    Vsse(Reg, Reg, Reg, VMem),
+    Vsuxei(Reg, Reg, Reg, VMem),
+    Vsoxei(Reg, Reg, Reg, VMem),
+    Vsr(Reg, Reg, VMem),
+    Vsm(Reg, Reg),
+    Vadd(VOperands),
+    Vsub(VOperands),
+    Vrsub(VOperands),
+    Vminu(VOperands),
+    Vmin(VOperands),
+    Vmaxu(VOperands),
+    Vmax(VOperands),
+    Vand(VOperands),
+    Vor(VOperands),
+    Vxor(VOperands),
+    Vadc(VOperands),
+    Vmadc(VOperands),
+    Vsbc(VOperands),
+    Vmsbc(VOperands),
+    Vmv(Reg, VSrc),
+    Vmerge(VOperands),
+    Vmseq(VOperands),
+    Vmsne(VOperands),
+    Vmsltu(VOperands),
+    Vmslt(VOperands),
+    Vmsleu(VOperands),
+    Vmsle(VOperands),
+    Vmsgtu(VOperands),
+    Vmsgt(VOperands),
+    Vsll(VOperands),
+    Vsrl(VOperands),
+    Vsra(VOperands),
+    Vnsrl(VOperands),
+    Vnsra(VOperands),
+    Vzextvf8(Reg, Reg, bool),
+    Vsextvf8(Reg, Reg, bool),
+    Vzextvf4(Reg, Reg, bool),
+    Vsextvf4(Reg, Reg, bool),
+    Vzextvf2(Reg, Reg, bool),
+    Vsextvf2(Reg, Reg, bool),
+    Vdivu(VOperands),
+    Vdiv(VOperands),
+    Vremu(VOperands),
+    Vrem(VOperands),
+    Vmulhu(VOperands),
+    Vmul(VOperands),
+    Vmulhsu(VOperands),
+    Vmulh(VOperands),
+    Vmadd(VOperands),
+    Vnmsub(VOperands),
+    Vmacc(VOperands),
+    Vnmsac(VOperands),
+    Vwaddu(VOperands),
+    Vwadd(VOperands),
+    Vwsubu(VOperands),
+    Vwsub(VOperands),
+    Vwadduw(VOperands),
+    Vwaddw(VOperands),
+    Vwsubuw(VOperands),
+    Vwsubw(VOperands),
+    Vwmulu(VOperands),
+    Vwmulsu(VOperands),
+    Vwmul(VOperands),
+    Vwmaccu(VOperands),
+    Vwmacc(VOperands),
+    Vwmaccus(VOperands),
+    Vwmaccsu(VOperands),
+    Vsaddu(VOperands),
+    Vsadd(VOperands),
+    Vssubu(VOperands),
+    Vssub(VOperands),
+    Vaaddu(VOperands),
+    Vaadd(VOperands),
+    Vasubu(VOperands),
+    Vasub(VOperands),
+    Vsmul(VOperands),
+    Vssrl(VOperands),
+    Vssra(VOperands),
+    Vnclipu(VOperands),
+    Vnclip(VOperands),
+    Vredsum(VOperands),
+    Vredand(VOperands),
+    Vredor(VOperands),
+    Vredxor(VOperands),
+    Vredminu(VOperands),
+    Vredmin(VOperands),
+    Vredmaxu(VOperands),
+    Vredmax(VOperands),
+    Vwredsumu(VOperands),
+    Vwredsum(VOperands),
+    Vfredusum(VOperands),
+    Vfredosum(VOperands),
+    Vfredmin(VOperands),
+    Vfredmax(VOperands),
+    Vfwredusum(VOperands),
+    Vfwredosum(VOperands),
+    Vmandn(Reg, Reg, Reg),
+    Vmand(Reg, Reg, Reg),
+    Vmor(Reg, Reg, Reg),
+    Vmxor(Reg, Reg, Reg),
+    Vmorn(Reg, Reg, Reg),
+    Vmnand(Reg, Reg, Reg),
+    Vmnor(Reg, Reg, Reg),
+    Vmxnor(Reg, Reg, Reg),
+    Vcpop(Reg, Reg, bool),
+    Vfirst(Reg, Reg, bool),
+    Vmsbf(Reg, Reg, bool),
+    Vmsof(Reg, Reg, bool),
+    Vmsif(Reg, Reg, bool),
+    Viota(Reg, Reg, bool),
+    Vid(Reg, bool),
+    Vmvxs(Reg, Reg),
+    Vmvsx(Reg, Reg),
+    Vfmvfs(Reg, Reg),
+    Vfmvsf(Reg, Reg),
+    Vslideup(VOperands),
+    Vslidedown(VOperands),
+    Vslide1up(VOperands),
+    Vslide1down(VOperands),
+    Vfslide1up(VOperands),
+    Vfslide1down(VOperands),
+    Vrgather(VOperands),
+    Vrgatherei16(VOperands),
+    Vcompress(Reg, Reg, Reg),
+    Vmvr(Reg, Reg, u8),
+    Vfadd(VOperands),
+    Vfsub(VOperands),
+    Vfrsub(VOperands),
+    Vfmul(VOperands),
+    Vfdiv(VOperands),
+    Vfrdiv(VOperands),
+    Vfmin(VOperands),
+    Vfmax(VOperands),
+    Vfsgnj(VOperands),
+    Vfsgnjn(VOperands),
+    Vfsgnjx(VOperands),
+    Vfmadd(VOperands),
+    Vfnmadd(VOperands),
+    Vfmsub(VOperands),
+    Vfnmsub(VOperands),
+    Vfmacc(VOperands),
+    Vfnmacc(VOperands),
+    Vfmsac(VOperands),
+    Vfnmsac(VOperands),
+    Vfwadd(VOperands),
+    Vfwsub(VOperands),
+    Vfwaddw(VOperands),
+    Vfwsubw(VOperands),
+    Vfwmul(VOperands),
+    Vfwmacc(VOperands),
+    Vfwnmacc(VOperands),
+    Vfwmsac(VOperands),
+    Vfwnmsac(VOperands),
+    Vmfeq(VOperands),
+    Vmfle(VOperands),
+    Vmflt(VOperands),
+    Vmfne(VOperands),
+    Vmfgt(VOperands),
+    Vmfge(VOperands),
+    Vfmvvf(Reg, Reg),
+    Vfmerge(VOperands),
+    Vfsqrtv(Reg, Reg, bool),
+    Vfrsqrt7v(Reg, Reg, bool),
+    Vfrec7v(Reg, Reg, bool),
+    Vfclassv(Reg, Reg, bool),
+    Vfcvtxufv(Reg, Reg, bool),
+    Vfcvtxfv(Reg, Reg, bool),
+    Vfcvtfxuv(Reg, Reg, bool),
+    Vfcvtfxv(Reg, Reg, bool),
+    Vfcvtrtzxufv(Reg, Reg, bool),
+    Vfcvtrtzxfv(Reg, Reg, bool),
+    Vfwcvtxufv(Reg, Reg, bool),
+    Vfwcvtxfv(Reg, Reg, bool),
+    Vfwcvtfxuv(Reg, Reg, bool),
+    Vfwcvtfxv(Reg, Reg, bool),
+    Vfwcvtffv(Reg, Reg, bool),
+    Vfwcvtrtzxufv(Reg, Reg, bool),
+    Vfwcvtrtzxfv(Reg, Reg, bool),
+    Vfncvtxufw(Reg, Reg, bool),
+    Vfncvtxfw(Reg, Reg, bool),
+    Vfncvtfxuw(Reg, Reg, bool),
+    Vfncvtfxw(Reg, Reg, bool),
+    Vfncvtffw(Reg, Reg, bool),
+    Vfncvtrodffw(Reg, Reg, bool),
+    Vfncvtrtzxufw(Reg, Reg, bool),
+    Vfncvtrtzxfw(Reg, Reg, bool),
+
     // NOTE: RV64A
     Lrw(Reg, Reg),
     Scw(Reg, Reg, Reg),
@@ -1214,6 +1174,10 @@ pub enum RV64GCInstruction {
     Fcvtsw(Reg, Reg, Reg),
     Fcvtswu(Reg, Reg, Reg),
     Fmvwx(Reg, Reg),
+    Fcvtls(Reg, Reg, Reg),
+    Fcvtlus(Reg, Reg, Reg),
+    Fcvtsl(Reg, Reg, Reg),
+    Fcvtslu(Reg, Reg, Reg),
 
     // NOTE: RV64D
     Fmaddd(Reg, Reg, Reg, Reg, Reg),
@@ -1234,17 +1198,22 @@ pub enum RV64GCInstruction {
     Fltd(Reg, Reg, Reg),
     Fled(Reg, Reg, Reg),
     Fclassd(Reg, Reg),
-    Fcvtsd(Reg, Reg),
+    Fcvtsd(Reg, Reg, Reg),
     Fcvtds(Reg, Reg, Reg),
-    Fcvtwd(Reg, Reg),
-    Fcvtwud(Reg, Reg),
-    Fcvtdwu(Reg, Reg),
-    Fcvtdw(Reg, Reg),
+    Fcvtwd(Reg, Reg, Reg),
+    Fcvtwud(Reg, Reg, Reg),
+    Fcvtdwu(Reg, Reg, Reg),
+    Fcvtdw(Reg, Reg, Reg),
     Flw(Reg, Reg, Imm),
     Fsw(Reg, Reg, Imm),
     Fld(Reg, Reg, Imm),
     Fsd(Reg, Reg, Simm),
     Fmvxd(Reg, Reg),
+    Fcvtld(Reg, Reg, Reg),
+    Fcvtlud(Reg, Reg, Reg),
+    Fcvtdl(Reg, Reg, Reg),
+    Fcvtdlu(Reg, Reg, Reg),
+    Fmvdx(Reg, Reg),
 
     // NOTE: RV64C
     Cebreak,
@@ -1263,7 +1232,6 @@ pub enum RV64GCInstruction {
     Clw(Reg, Reg, Imm),
     Cld(Reg, Reg, Imm),
     Cfsd(Reg, Reg, Imm),
-    Cfsw(Reg, Reg, Imm),
     Csd(Reg, Reg, Imm),
     Cnop,
     Caddi(Reg, Simm),
@@ -1281,7 +1249,6 @@ pub enum RV64GCInstruction {
     Cslli(Reg, Imm),
     Cfldsp(Reg, Imm),
     Clwsp(Reg, Imm),
-    Cflwsp(Reg, Imm),
     Cldsp(Reg, Imm),
     Cfsdsp(Reg, Imm),
     Cswsp(Reg, Imm),
@@ -1289,7 +1256,7 @@ pub enum RV64GCInstruction {
 }
 
 impl RV64GCInstruction {
-    pub fn execute_instruction(&self, cpu: &mut RV64GC) {
+    pub fn execute_instruction(&self, cpu: &mut RV64GC) -> Result<(), Exception> {
         use RV64GCInstruction::*;
 
         let span = span!(Level::TRACE, "execute_instruction");
@@ -1297,9 +1264,7 @@ impl RV64GCInstruction {
         trace!("{}", self);
 
         match self {
-            IllegalInstruction(i) => {
-                panic!("Not a valid instruction: 0x{i:08x}");
-            }
+            IllegalInstruction(_) => return Err(Exception::IllegalInstruction),
 
             Add(rd, rs1, rs2) => {
                 cpu.registers[rd] = cpu.registers[rs1].wrapping_add(cpu.registers[rs2]);
@@ -1410,33 +1375,107 @@ impl RV64GCInstruction {
 
             Fence(_, _) => {}
 
-            FenceI => todo!(),
+            // NOTE: There is no icache to flush
+            // NOTE: Stores to code are tracked already, this is for embedders poking at ram.
+            // Everything decoded or translated is dropped before the next instruction
+            FenceI => cpu.ram.code_invalidations.push((0, u64::MAX)),
+
+            // NOTE: Privileged instructions are illegal in U-mode
+            Uret | Sret | Mret | SfenceVma(_, _, _) => return Err(Exception::IllegalInstruction),
 
-            Uret => todo!(),
+            Wfi => {}
 
-            Sret => todo!(),
+            // NOTE: Zicsr
+            // The CSR is only read when rd != x0 for csrrw(i), and only written when
+            // rs1/uimm != 0 for the set/clear variants, as per the spec
+            Csrrw(rd, rs1, csr) => {
+                let value = cpu.registers[rs1];
+                let old = if *rd != 0 {
+                    cpu.csr.read(*csr, &cpu.fcsr, &cpu.vector, &cpu.isa)?
+                } else {
+                    0
+                };
 
-            Mret => todo!(),
+                cpu.csr
+                    .write(*csr, value, &mut cpu.fcsr, &mut cpu.vector, &cpu.isa)?;
+                cpu.registers[rd] = old;
+            }
 
-            Wfi => todo!(),
+            Csrrs(rd, rs1, csr) => {
+                let mask = cpu.registers[rs1];
+                let old = cpu.csr.read(*csr, &cpu.fcsr, &cpu.vector, &cpu.isa)?;
 
-            SfenceVma(_, _, _) => todo!(),
+                if *rs1 != 0 {
+                    cpu.csr
+                        .write(*csr, old | mask, &mut cpu.fcsr, &mut cpu.vector, &cpu.isa)?;
+                }
+                cpu.registers[rd] = old;
+            }
 
-            Csrrw(_, _, _) => todo!(),
-            Csrrs(_, _, _) => todo!(),
-            Csrrc(_, _, _) => todo!(),
-            Csrrwi(_, _, _) => todo!(),
-            Csrrsi(_, _, _) => todo!(),
-            Csrrci(_, _, _) => todo!(),
+            Csrrc(rd, rs1, csr) => {
+                let mask = cpu.registers[rs1];
+                let old = cpu.csr.read(*csr, &cpu.fcsr, &cpu.vector, &cpu.isa)?;
 
-            Ecall => {
-                cpu.syscall_handler();
+                if *rs1 != 0 {
+                    cpu.csr
+                        .write(*csr, old & !mask, &mut cpu.fcsr, &mut cpu.vector, &cpu.isa)?;
+                }
+                cpu.registers[rd] = old;
+            }
+
+            Csrrwi(rd, uimm, csr) => {
+                let old = if *rd != 0 {
+                    cpu.csr.read(*csr, &cpu.fcsr, &cpu.vector, &cpu.isa)?
+                } else {
+                    0
+                };
+
+                cpu.csr.write(
+                    *csr,
+                    u64::from(*uimm),
+                    &mut cpu.fcsr,
+                    &mut cpu.vector,
+                    &cpu.isa,
+                )?;
+                cpu.registers[rd] = old;
+            }
+
+            Csrrsi(rd, uimm, csr) => {
+                let old = cpu.csr.read(*csr, &cpu.fcsr, &cpu.vector, &cpu.isa)?;
+
+                if *uimm != 0 {
+                    cpu.csr.write(
+                        *csr,
+                        old | u64::from(*uimm),
+                        &mut cpu.fcsr,
+                        &mut cpu.vector,
+                        &cpu.isa,
+                    )?;
+                }
+                cpu.registers[rd] = old;
+            }
+
+            Csrrci(rd, uimm, csr) => {
+                let old = cpu.csr.read(*csr, &cpu.fcsr, &cpu.vector, &cpu.isa)?;
+
+                if *uimm != 0 {
+                    cpu.csr.write(
+                        *csr,
+                        old & !u64::from(*uimm),
+                        &mut cpu.fcsr,
+                        &mut cpu.vector,
+                        &cpu.isa,
+                    )?;
+                }
+                cpu.registers[rd] = old;
             }
 
-            Ebreak => {
-                todo!()
+            Ecall => {
+                cpu.syscall_handler()?;
             }
 
+            Ebreak => return Err(Exception::Breakpoint),
+
             // This was previously checking the sign bit at the 4th bit,
             // absolutely stupid...
             Lb(rd, rs1, simm) => {
@@ -1444,11 +1483,7 @@ impl RV64GCInstruction {
                     .ram
                     .read_byte(cpu.registers[rs1].wrapping_add_signed(*simm));
 
-                if let Err(res) = res {
-                    panic!("{}", res);
-                }
-
-                let res = res.unwrap();
+                let res = res.map_err(Exception::load)?;
 
                 cpu.registers[rd] = sign_extend(res.into(), 8) as u64;
             }
@@ -1458,11 +1493,7 @@ impl RV64GCInstruction {
                     .ram
                     .read_byte(cpu.registers[rs1].wrapping_add_signed(*simm));
 
-                if let Err(res) = res {
-                    panic!("{}", res);
-                }
-
-                let res = res.unwrap();
+                let res = res.map_err(Exception::load)?;
                 cpu.registers[rd] = res.into();
             }
 
@@ -1471,11 +1502,7 @@ impl RV64GCInstruction {
                     .ram
                     .read_halfword(cpu.registers[rs1].wrapping_add_signed(*simm));
 
-                if let Err(res) = res {
-                    panic!("{}", res);
-                }
-
-                let res = res.unwrap();
+                let res = res.map_err(Exception::load)?;
 
                 cpu.registers[rd] = res;
             }
@@ -1485,14 +1512,16 @@ impl RV64GCInstruction {
 
                 cpu.ram
                     .write_byte(addr as u64, cpu.registers[rs2] as u8)
-                    .unwrap();
+                    .map_err(Exception::store)?;
             }
 
             Sh(rs1, rs2, simm) => {
                 let addr = (cpu.registers[rs1] as i64).wrapping_add(*simm);
                 let value = cpu.registers[rs2] as u16;
 
-                cpu.ram.write_halfword(addr as u64, value as u64).unwrap();
+                cpu.ram
+                    .write_halfword(addr as u64, value as u64)
+                    .map_err(Exception::store)?;
             }
 
             Lh(rd, rs1, simm) => {
@@ -1500,11 +1529,7 @@ impl RV64GCInstruction {
                     .ram
                     .read_halfword(cpu.registers[rs1].wrapping_add_signed(*simm));
 
-                if let Err(res) = res {
-                    panic!("{}", res);
-                }
-
-                let res = res.unwrap();
+                let res = res.map_err(Exception::load)?;
 
                 cpu.registers[rd] = sign_extend(res, 16) as u64;
             }
@@ -1514,22 +1539,17 @@ impl RV64GCInstruction {
                     .ram
                     .read_word(cpu.registers[rs1].wrapping_add_signed(*simm));
 
-                if let Err(res) = res {
-                    panic!("{}", res);
-                }
-
-                let res = res.unwrap();
+                let res = res.map_err(Exception::load)?;
 
                 cpu.registers[rd] = sign_extend(res.into(), 32) as u64;
             }
 
             Sw(rs1, rs2, simm) => {
-                let addr = cpu.registers[rs1] as i64 + simm;
+                let addr = (cpu.registers[rs1] as i64).wrapping_add(*simm);
 
                 cpu.ram
                     .write_word(addr as u64, cpu.registers[rs2] as u32)
-                    .inspect_err(|e| panic!("{e}"))
-                    .unwrap();
+                    .map_err(Exception::store)?;
             }
 
             Ld(rd, rs1, simm) => {
@@ -1537,11 +1557,7 @@ impl RV64GCInstruction {
 
                 trace!("ld addr: {addr:08x}");
 
-                cpu.registers[rd] = cpu
-                    .ram
-                    .read_doubleword(addr)
-                    .inspect_err(|e| panic!("{e}"))
-                    .unwrap();
+                cpu.registers[rd] = cpu.ram.read_doubleword(addr).map_err(Exception::load)?;
             }
 
             Sd(rs1, rs2, simm) => {
@@ -1551,8 +1567,7 @@ impl RV64GCInstruction {
 
                 cpu.ram
                     .write_doubleword(addr, cpu.registers[rs2])
-                    .inspect_err(|e| panic!("{e}\nAddress: {:08x}", cpu.registers[rs1]))
-                    .unwrap();
+                    .map_err(Exception::store)?;
             }
 
             Jal(rd, simm) => {
@@ -1566,7 +1581,7 @@ impl RV64GCInstruction {
 
                 // Due to adding 4 to the PC every step, we must decrement by 4 to revert this
                 // behaviour
-                cpu.registers[Pc] -= 4;
+                cpu.registers[Pc] = cpu.registers[Pc].wrapping_sub(4);
             }
 
             Jalr(rd, rs1, simm) => {
@@ -1579,28 +1594,28 @@ impl RV64GCInstruction {
 
                 // Due to adding 4 to the PC every step, we must decrement by 4 to revert this
                 // behaviour
-                cpu.registers[Pc] -= 4;
+                cpu.registers[Pc] = cpu.registers[Pc].wrapping_sub(4);
             }
 
             Beq(rs1, rs2, imm) => {
                 if cpu.registers[rs1] == cpu.registers[rs2] {
-                    let simm = sign_extend12(*imm);
+                    let simm = sign_extend((*imm).into(), 13);
                     cpu.registers[Pc] = (cpu.registers[Pc] as i64).wrapping_add(simm) as u64;
 
                     // Due to adding 4 to the PC every step, we must decrement by 4 to revert this
                     // behaviour
-                    cpu.registers[Pc] -= 4;
+                    cpu.registers[Pc] = cpu.registers[Pc].wrapping_sub(4);
                 }
             }
 
             Bne(rs1, rs2, imm) => {
                 if cpu.registers[rs1] != cpu.registers[rs2] {
-                    let simm = sign_extend12(*imm);
+                    let simm = sign_extend((*imm).into(), 13);
                     cpu.registers[Pc] = (cpu.registers[Pc] as i64).wrapping_add(simm) as u64;
 
                     // Due to adding 4 to the PC every step, we must decrement by 4 to revert this
                     // behaviour
-                    cpu.registers[Pc] -= 4;
+                    cpu.registers[Pc] = cpu.registers[Pc].wrapping_sub(4);
                 }
             }
 
@@ -1609,45 +1624,45 @@ impl RV64GCInstruction {
                 let rs2 = cpu.registers[rs2] as i64;
                 if rs1 < rs2 {
                     trace!("blt: {rs1} < {rs2}");
-                    let simm = sign_extend12(*imm);
+                    let simm = sign_extend((*imm).into(), 13);
                     cpu.registers[Pc] = (cpu.registers[Pc] as i64).wrapping_add(simm) as u64;
 
                     // Due to adding 4 to the PC every step, we must decrement by 4 to revert this
                     // behaviour
-                    cpu.registers[Pc] -= 4;
+                    cpu.registers[Pc] = cpu.registers[Pc].wrapping_sub(4);
                 }
             }
 
             Bge(rs1, rs2, imm) => {
                 if (cpu.registers[rs1] as i64) >= (cpu.registers[rs2] as i64) {
-                    let simm = sign_extend12(*imm);
+                    let simm = sign_extend((*imm).into(), 13);
                     cpu.registers[Pc] = (cpu.registers[Pc] as i64).wrapping_add(simm) as u64;
 
                     // Due to adding 4 to the PC every step, we must decrement by 4 to revert this
                     // behaviour
-                    cpu.registers[Pc] -= 4;
+                    cpu.registers[Pc] = cpu.registers[Pc].wrapping_sub(4);
                 }
             }
 
             Bltu(rs1, rs2, imm) => {
                 if cpu.registers[rs1] < cpu.registers[rs2] {
-                    let simm = sign_extend12(*imm);
+                    let simm = sign_extend((*imm).into(), 13);
                     cpu.registers[Pc] = (cpu.registers[Pc] as i64).wrapping_add(simm) as u64;
 
                     // Due to adding 4 to the PC every step, we must decrement by 4 to revert this
                     // behaviour
-                    cpu.registers[Pc] -= 4;
+                    cpu.registers[Pc] = cpu.registers[Pc].wrapping_sub(4);
                 }
             }
 
             Bgeu(rs1, rs2, imm) => {
                 if cpu.registers[rs1] >= cpu.registers[rs2] {
-                    let simm = sign_extend12(*imm);
+                    let simm = sign_extend((*imm).into(), 13);
                     cpu.registers[Pc] = (cpu.registers[Pc] as i64).wrapping_add(simm) as u64;
 
                     // Due to adding 4 to the PC every step, we must decrement by 4 to revert this
                     // behaviour
-                    cpu.registers[Pc] -= 4;
+                    cpu.registers[Pc] = cpu.registers[Pc].wrapping_sub(4);
                 }
             }
 
@@ -1711,40 +1726,34 @@ impl RV64GCInstruction {
 
             Lwu(rd, rs1, offset) => {
                 let addr = (cpu.registers[rs1] as i64).wrapping_add(sign_extend12(*offset));
-                let mem = cpu.ram.read_word(addr as u64).unwrap();
+                let mem = cpu.ram.read_word(addr as u64).map_err(Exception::load)?;
 
                 cpu.registers[rd] = sign_extend(u64::from(mem), 32) as u64;
             }
 
             Mul(rd, rs1, rs2) => {
-                let value = (cpu.registers[rs1] as i64).wrapping_mul(cpu.registers[rs2] as i64);
-
-                cpu.registers[rd] = (value & i32::MAX as i64) as u64;
+                cpu.registers[rd] = cpu.registers[rs1].wrapping_mul(cpu.registers[rs2]);
             }
 
+            // NOTE: The high halves are the upper 64 bits of the full 128 bit product
             Mulh(rd, rs1, rs2) => {
-                let h_multiplicand = (cpu.registers[rs1] >> 32) as i64;
-                let h_multiplier = (cpu.registers[rs2] >> 32) as i64;
+                let product =
+                    i128::from(cpu.registers[rs1] as i64) * i128::from(cpu.registers[rs2] as i64);
 
-                cpu.registers[rd] = h_multiplicand.wrapping_mul(h_multiplier) as u64;
+                cpu.registers[rd] = (product >> 64) as u64;
             }
 
             Mulhsu(rd, rs1, rs2) => {
-                let h_multiplicand = cpu.registers[rs1] >> 32;
-                let h_multiplier = (cpu.registers[rs2] >> 32) as i64;
-                let result: u64 = if h_multiplier.is_negative() {
-                    (h_multiplicand as i64).wrapping_mul(h_multiplier) as u64
-                } else {
-                    h_multiplicand.wrapping_mul(h_multiplier as u64)
-                };
+                let product =
+                    i128::from(cpu.registers[rs1] as i64) * i128::from(cpu.registers[rs2]);
 
-                cpu.registers[rd] = result;
+                cpu.registers[rd] = (product >> 64) as u64;
             }
 
             Mulhu(rd, rs1, rs2) => {
-                let h_multiplicand = cpu.registers[rs1] >> 32;
-                let h_multiplier = cpu.registers[rs2] >> 32;
-                cpu.registers[rd] = h_multiplicand.wrapping_mul(h_multiplier);
+                let product = u128::from(cpu.registers[rs1]) * u128::from(cpu.registers[rs2]);
+
+                cpu.registers[rd] = (product >> 64) as u64;
             }
 
             Div(rd, rs1, rs2) => {
@@ -1752,12 +1761,12 @@ impl RV64GCInstruction {
                 let divisor = cpu.registers[rs2] as i64;
                 if divisor == 0 {
                     cpu.registers[rd] = u64::MAX;
-                    return;
+                    return Ok(());
                 }
 
                 if dividend == i64::MIN && divisor == -1 {
                     cpu.registers[rd] = dividend as u64;
-                    return;
+                    return Ok(());
                 }
 
                 let value = dividend.wrapping_div(divisor);
@@ -1769,7 +1778,7 @@ impl RV64GCInstruction {
                 let divisor = cpu.registers[rs2];
                 if divisor == 0 {
                     cpu.registers[rd] = u64::MAX;
-                    return;
+                    return Ok(());
                 }
 
                 let value = dividend.wrapping_div(divisor);
@@ -1780,13 +1789,13 @@ impl RV64GCInstruction {
                 let dividend = cpu.registers[rs1] as i64;
                 let divisor = cpu.registers[rs2] as i64;
                 if divisor == 0 {
-                    cpu.registers[rd] = u64::MAX;
-                    return;
+                    cpu.registers[rd] = dividend as u64;
+                    return Ok(());
                 }
 
                 if dividend == i64::MIN && divisor == -1 {
                     cpu.registers[rd] = 0;
-                    return;
+                    return Ok(());
                 }
 
                 let value = dividend.wrapping_rem(divisor);
@@ -1797,8 +1806,8 @@ impl RV64GCInstruction {
                 let dividend = cpu.registers[rs1];
                 let divisor = cpu.registers[rs2];
                 if divisor == 0 {
-                    cpu.registers[rd] = u64::MAX;
-                    return;
+                    cpu.registers[rd] = dividend;
+                    return Ok(());
                 }
 
                 let value = dividend.wrapping_rem(divisor);
@@ -1817,7 +1826,7 @@ impl RV64GCInstruction {
 
                 if signed_rs2 == 0 {
                     cpu.registers[rd] = u64::MAX;
-                    return;
+                    return Ok(());
                 }
 
                 cpu.registers[rd] =
@@ -1830,7 +1839,7 @@ impl RV64GCInstruction {
 
                 if unsigned_rs2 == 0 {
                     cpu.registers[rd] = u64::MAX;
-                    return;
+                    return Ok(());
                 }
 
                 cpu.registers[rd] =
@@ -1842,8 +1851,8 @@ impl RV64GCInstruction {
                 let signed_rs2 = (cpu.registers[rs2] & (u32::MAX as u64)) as i32;
 
                 if signed_rs2 == 0 {
-                    cpu.registers[rd] = u64::MAX;
-                    return;
+                    cpu.registers[rd] = i64::from(signed_rs1) as u64;
+                    return Ok(());
                 }
 
                 cpu.registers[rd] =
@@ -1855,144 +1864,348 @@ impl RV64GCInstruction {
                 let unsigned_rs2 = (cpu.registers[rs2] & (u32::MAX as u64)) as u32;
 
                 if unsigned_rs2 == 0 {
-                    cpu.registers[rd] = u64::MAX;
-                    return;
+                    cpu.registers[rd] = sign_extend(unsigned_rs1.into(), 32) as u64;
+                    return Ok(());
                 }
 
                 cpu.registers[rd] =
                     sign_extend(unsigned_rs1.wrapping_rem(unsigned_rs2) as u64, 32) as u64;
             }
 
+            // NOTE: Zba, the .uw forms zero extend the low word of rs1
+            Adduw(rd, rs1, rs2) => {
+                cpu.registers[rd] =
+                    cpu.registers[rs2].wrapping_add(cpu.registers[rs1] as u32 as u64);
+            }
+
+            Sh1add(rd, rs1, rs2) => {
+                cpu.registers[rd] = cpu.registers[rs2].wrapping_add(cpu.registers[rs1] << 1);
+            }
+
+            Sh2add(rd, rs1, rs2) => {
+                cpu.registers[rd] = cpu.registers[rs2].wrapping_add(cpu.registers[rs1] << 2);
+            }
+
+            Sh3add(rd, rs1, rs2) => {
+                cpu.registers[rd] = cpu.registers[rs2].wrapping_add(cpu.registers[rs1] << 3);
+            }
+
+            Sh1adduw(rd, rs1, rs2) => {
+                let rs1 = cpu.registers[rs1] as u32 as u64;
+                cpu.registers[rd] = cpu.registers[rs2].wrapping_add(rs1 << 1);
+            }
+
+            Sh2adduw(rd, rs1, rs2) => {
+                let rs1 = cpu.registers[rs1] as u32 as u64;
+                cpu.registers[rd] = cpu.registers[rs2].wrapping_add(rs1 << 2);
+            }
+
+            Sh3adduw(rd, rs1, rs2) => {
+                let rs1 = cpu.registers[rs1] as u32 as u64;
+                cpu.registers[rd] = cpu.registers[rs2].wrapping_add(rs1 << 3);
+            }
+
+            Slliuw(rd, rs1, shamt) => {
+                cpu.registers[rd] = (cpu.registers[rs1] as u32 as u64) << shamt;
+            }
+
+            // NOTE: Zbb
+            Andn(rd, rs1, rs2) => cpu.registers[rd] = cpu.registers[rs1] & !cpu.registers[rs2],
+
+            Orn(rd, rs1, rs2) => cpu.registers[rd] = cpu.registers[rs1] | !cpu.registers[rs2],
+
+            Xnor(rd, rs1, rs2) => cpu.registers[rd] = !(cpu.registers[rs1] ^ cpu.registers[rs2]),
+
+            Clz(rd, rs1) => cpu.registers[rd] = cpu.registers[rs1].leading_zeros().into(),
+
+            Clzw(rd, rs1) => cpu.registers[rd] = (cpu.registers[rs1] as u32).leading_zeros().into(),
+
+            Ctz(rd, rs1) => cpu.registers[rd] = cpu.registers[rs1].trailing_zeros().into(),
+
+            Ctzw(rd, rs1) => {
+                cpu.registers[rd] = (cpu.registers[rs1] as u32).trailing_zeros().into();
+            }
+
+            Cpop(rd, rs1) => cpu.registers[rd] = cpu.registers[rs1].count_ones().into(),
+
+            Cpopw(rd, rs1) => cpu.registers[rd] = (cpu.registers[rs1] as u32).count_ones().into(),
+
+            Max(rd, rs1, rs2) => {
+                let max = (cpu.registers[rs1] as i64).max(cpu.registers[rs2] as i64);
+                cpu.registers[rd] = max as u64;
+            }
+
+            Maxu(rd, rs1, rs2) => cpu.registers[rd] = cpu.registers[rs1].max(cpu.registers[rs2]),
+
+            Min(rd, rs1, rs2) => {
+                let min = (cpu.registers[rs1] as i64).min(cpu.registers[rs2] as i64);
+                cpu.registers[rd] = min as u64;
+            }
+
+            Minu(rd, rs1, rs2) => cpu.registers[rd] = cpu.registers[rs1].min(cpu.registers[rs2]),
+
+            Sextb(rd, rs1) => cpu.registers[rd] = sign_extend(cpu.registers[rs1], 8) as u64,
+
+            Sexth(rd, rs1) => cpu.registers[rd] = sign_extend(cpu.registers[rs1], 16) as u64,
+
+            Zexth(rd, rs1) => cpu.registers[rd] = cpu.registers[rs1] & 0xFFFF,
+
+            Rol(rd, rs1, rs2) => {
+                let shamt = (cpu.registers[rs2] & 0x3F) as u32;
+                cpu.registers[rd] = cpu.registers[rs1].rotate_left(shamt);
+            }
+
+            Rolw(rd, rs1, rs2) => {
+                let shamt = (cpu.registers[rs2] & 0x1F) as u32;
+                let res = (cpu.registers[rs1] as u32).rotate_left(shamt);
+                cpu.registers[rd] = sign_extend(res.into(), 32) as u64;
+            }
+
+            Ror(rd, rs1, rs2) => {
+                let shamt = (cpu.registers[rs2] & 0x3F) as u32;
+                cpu.registers[rd] = cpu.registers[rs1].rotate_right(shamt);
+            }
+
+            Rorw(rd, rs1, rs2) => {
+                let shamt = (cpu.registers[rs2] & 0x1F) as u32;
+                let res = (cpu.registers[rs1] as u32).rotate_right(shamt);
+                cpu.registers[rd] = sign_extend(res.into(), 32) as u64;
+            }
+
+            Rori(rd, rs1, shamt) => cpu.registers[rd] = cpu.registers[rs1].rotate_right(*shamt),
+
+            Roriw(rd, rs1, shamt) => {
+                let res = (cpu.registers[rs1] as u32).rotate_right(*shamt);
+                cpu.registers[rd] = sign_extend(res.into(), 32) as u64;
+            }
+
+            // NOTE: Every non-zero byte becomes 0xFF
+            Orcb(rd, rs1) => {
+                let bytes = cpu.registers[rs1]
+                    .to_le_bytes()
+                    .map(|b| if b == 0 { 0 } else { 0xFF });
+                cpu.registers[rd] = u64::from_le_bytes(bytes);
+            }
+
+            Rev8(rd, rs1) => cpu.registers[rd] = cpu.registers[rs1].swap_bytes(),
+
+            // NOTE: Zbc, the low, high and "reversed" (bits 126:63) parts of the 128 bit
+            // carry-less product
+            Clmul(rd, rs1, rs2) => {
+                cpu.registers[rd] = clmul(cpu.registers[rs1], cpu.registers[rs2]) as u64;
+            }
+
+            Clmulh(rd, rs1, rs2) => {
+                cpu.registers[rd] = (clmul(cpu.registers[rs1], cpu.registers[rs2]) >> 64) as u64;
+            }
+
+            Clmulr(rd, rs1, rs2) => {
+                cpu.registers[rd] = (clmul(cpu.registers[rs1], cpu.registers[rs2]) >> 63) as u64;
+            }
+
+            // NOTE: Zbs
+            Bclr(rd, rs1, rs2) => {
+                cpu.registers[rd] = cpu.registers[rs1] & !(1 << (cpu.registers[rs2] & 0x3F));
+            }
+
+            Bclri(rd, rs1, shamt) => cpu.registers[rd] = cpu.registers[rs1] & !(1 << shamt),
+
+            Bext(rd, rs1, rs2) => {
+                cpu.registers[rd] = cpu.registers[rs1] >> (cpu.registers[rs2] & 0x3F) & 1;
+            }
+
+            Bexti(rd, rs1, shamt) => cpu.registers[rd] = cpu.registers[rs1] >> shamt & 1,
+
+            Binv(rd, rs1, rs2) => {
+                cpu.registers[rd] = cpu.registers[rs1] ^ 1 << (cpu.registers[rs2] & 0x3F);
+            }
+
+            Binvi(rd, rs1, shamt) => cpu.registers[rd] = cpu.registers[rs1] ^ 1 << shamt,
+
+            Bset(rd, rs1, rs2) => {
+                cpu.registers[rd] = cpu.registers[rs1] | 1 << (cpu.registers[rs2] & 0x3F);
+            }
+
+            Bseti(rd, rs1, shamt) => cpu.registers[rd] = cpu.registers[rs1] | 1 << shamt,
+
             // WARNING: RV64A
             // TODO: Properly implement RV64A for multithreading
             Lrw(rd, rs1) => {
-                cpu.registers[rd] = cpu.ram.read_word(cpu.registers[rs1]).unwrap() as u64;
+                check_atomic_alignment(cpu.registers[rs1], 4, Exception::LoadAddressMisaligned)?;
+                let value = cpu
+                    .ram
+                    .read_word(cpu.registers[rs1])
+                    .map_err(Exception::load)?;
+                cpu.registers[rd] = sign_extend(value.into(), 32) as u64;
             }
 
             // WARNING: Does not check that the previous value was changed!
             Scw(rd, rs1, rs2) => {
+                check_atomic_alignment(cpu.registers[rs1], 4, Exception::StoreAddressMisaligned)?;
                 cpu.ram
                     .write_word(cpu.registers[rs1], cpu.registers[rs2] as u32)
-                    .unwrap();
+                    .map_err(Exception::store)?;
                 cpu.registers[rd] = 0;
             }
 
             Amoswapw(rd, rs1, rs2) => {
-                let rs1_value = cpu.ram.read_word(cpu.registers[rs1]).unwrap() as i32;
+                check_atomic_alignment(cpu.registers[rs1], 4, Exception::StoreAddressMisaligned)?;
+                let rs1_value = cpu
+                    .ram
+                    .read_word(cpu.registers[rs1])
+                    .map_err(Exception::store)? as i32;
                 cpu.ram
                     .write_word(cpu.registers[rs1], cpu.registers[rs2] as u32)
-                    .unwrap();
+                    .map_err(Exception::store)?;
                 if *rd != 0 {
                     cpu.registers[rd] = sign_extend(rs1_value as u64, 32) as u64;
                 }
             }
 
             Amoaddw(rd, rs1, rs2) => {
-                let rs1_value = cpu.ram.read_word(cpu.registers[rs1]).unwrap() as i32;
+                check_atomic_alignment(cpu.registers[rs1], 4, Exception::StoreAddressMisaligned)?;
+                let rs1_value = cpu
+                    .ram
+                    .read_word(cpu.registers[rs1])
+                    .map_err(Exception::store)? as i32;
                 cpu.ram
                     .write_word(
                         cpu.registers[rs1],
                         rs1_value.wrapping_add(cpu.registers[rs2] as i32) as u32,
                     )
-                    .unwrap();
+                    .map_err(Exception::store)?;
                 cpu.registers[rd] = sign_extend(rs1_value as u64, 32) as u64;
             }
 
             Amoxorw(rd, rs1, rs2) => {
-                let rs1_value = cpu.ram.read_word(cpu.registers[rs1]).unwrap() as i32;
+                check_atomic_alignment(cpu.registers[rs1], 4, Exception::StoreAddressMisaligned)?;
+                let rs1_value = cpu
+                    .ram
+                    .read_word(cpu.registers[rs1])
+                    .map_err(Exception::store)? as i32;
                 cpu.ram
                     .write_word(
                         cpu.registers[rs1],
                         (rs1_value ^ (cpu.registers[rs2] as i32)) as u32,
                     )
-                    .unwrap();
+                    .map_err(Exception::store)?;
                 cpu.registers[rd] = sign_extend(rs1_value as u64, 32) as u64;
             }
 
             Amoorw(rd, rs1, rs2) => {
-                let rs1_value = cpu.ram.read_word(cpu.registers[rs1]).unwrap() as i32;
+                check_atomic_alignment(cpu.registers[rs1], 4, Exception::StoreAddressMisaligned)?;
+                let rs1_value = cpu
+                    .ram
+                    .read_word(cpu.registers[rs1])
+                    .map_err(Exception::store)? as i32;
                 cpu.ram
                     .write_word(
                         cpu.registers[rs1],
                         (rs1_value | (cpu.registers[rs2] as i32)) as u32,
                     )
-                    .unwrap();
+                    .map_err(Exception::store)?;
                 cpu.registers[rd] = sign_extend(rs1_value as u64, 32) as u64;
             }
             Amoandw(rd, rs1, rs2) => {
-                let rs1_value = cpu.ram.read_word(cpu.registers[rs1]).unwrap() as i32;
+                check_atomic_alignment(cpu.registers[rs1], 4, Exception::StoreAddressMisaligned)?;
+                let rs1_value = cpu
+                    .ram
+                    .read_word(cpu.registers[rs1])
+                    .map_err(Exception::store)? as i32;
                 cpu.ram
                     .write_word(
                         cpu.registers[rs1],
                         (rs1_value & (cpu.registers[rs2] as i32)) as u32,
                     )
-                    .unwrap();
+                    .map_err(Exception::store)?;
                 cpu.registers[rd] = sign_extend(rs1_value as u64, 32) as u64;
             }
 
             Amominw(rd, rs1, rs2) => {
-                let rs1_value = cpu.ram.read_word(cpu.registers[rs1]).unwrap() as i32;
+                check_atomic_alignment(cpu.registers[rs1], 4, Exception::StoreAddressMisaligned)?;
+                let rs1_value = cpu
+                    .ram
+                    .read_word(cpu.registers[rs1])
+                    .map_err(Exception::store)? as i32;
                 cpu.ram
                     .write_word(
                         cpu.registers[rs1],
                         (rs1_value.min(cpu.registers[rs2] as i32)) as u32,
                     )
-                    .unwrap();
+                    .map_err(Exception::store)?;
                 cpu.registers[rd] = sign_extend(rs1_value as u64, 32) as u64;
             }
 
             Amomaxw(rd, rs1, rs2) => {
-                let rs1_value = cpu.ram.read_word(cpu.registers[rs1]).unwrap() as i32;
+                check_atomic_alignment(cpu.registers[rs1], 4, Exception::StoreAddressMisaligned)?;
+                let rs1_value = cpu
+                    .ram
+                    .read_word(cpu.registers[rs1])
+                    .map_err(Exception::store)? as i32;
                 cpu.ram
                     .write_word(
                         cpu.registers[rs1],
                         (rs1_value.max(cpu.registers[rs2] as i32)) as u32,
                     )
-                    .unwrap();
+                    .map_err(Exception::store)?;
                 cpu.registers[rd] = sign_extend(rs1_value as u64, 32) as u64;
             }
 
             Amominuw(rd, rs1, rs2) => {
-                let rs1_value = cpu.ram.read_word(cpu.registers[rs1]).unwrap();
+                check_atomic_alignment(cpu.registers[rs1], 4, Exception::StoreAddressMisaligned)?;
+                let rs1_value = cpu
+                    .ram
+                    .read_word(cpu.registers[rs1])
+                    .map_err(Exception::store)?;
                 cpu.ram
                     .write_word(
                         cpu.registers[rs1],
                         rs1_value.min((cpu.registers[rs2] & u32::MAX as u64) as u32),
                     )
-                    .unwrap();
+                    .map_err(Exception::store)?;
                 cpu.registers[rd] = sign_extend(rs1_value as u64, 32) as u64;
             }
 
             Amomaxuw(rd, rs1, rs2) => {
-                let rs1_value = cpu.ram.read_word(cpu.registers[rs1]).unwrap();
+                check_atomic_alignment(cpu.registers[rs1], 4, Exception::StoreAddressMisaligned)?;
+                let rs1_value = cpu
+                    .ram
+                    .read_word(cpu.registers[rs1])
+                    .map_err(Exception::store)?;
                 cpu.ram
                     .write_word(
                         cpu.registers[rs1],
                         rs1_value.max((cpu.registers[rs2] & u32::MAX as u64) as u32),
                     )
-                    .unwrap();
+                    .map_err(Exception::store)?;
                 cpu.registers[rd] = sign_extend(rs1_value as u64, 32) as u64;
             }
 
             Lrd(rd, rs1) => {
-                cpu.registers[rd] = cpu.ram.read_doubleword(cpu.registers[rs1]).unwrap();
+                check_atomic_alignment(cpu.registers[rs1], 8, Exception::LoadAddressMisaligned)?;
+                cpu.registers[rd] = cpu
+                    .ram
+                    .read_doubleword(cpu.registers[rs1])
+                    .map_err(Exception::load)?;
             }
 
             Scd(rd, rs1, rs2) => {
+                check_atomic_alignment(cpu.registers[rs1], 8, Exception::StoreAddressMisaligned)?;
                 cpu.ram
                     .write_doubleword(cpu.registers[rs1], cpu.registers[rs2])
-                    .unwrap();
+                    .map_err(Exception::store)?;
 
                 cpu.registers[rd] = 0;
             }
 
             Amoswapd(rd, rs1, rs2) => {
+                check_atomic_alignment(cpu.registers[rs1], 8, Exception::StoreAddressMisaligned)?;
                 let rs1_ptr = cpu.registers[rs1];
-                let rs1_value = cpu.ram.read_doubleword(rs1_ptr).unwrap();
+                let rs1_value = cpu.ram.read_doubleword(rs1_ptr).map_err(Exception::store)?;
 
                 cpu.ram
                     .write_doubleword(rs1_ptr, cpu.registers[rs2])
-                    .unwrap();
+                    .map_err(Exception::store)?;
 
                 if *rd != 0 {
                     cpu.registers[rd] = rs1_value;
@@ -2000,381 +2213,576 @@ impl RV64GCInstruction {
             }
 
             Amoaddd(rd, rs1, rs2) => {
+                check_atomic_alignment(cpu.registers[rs1], 8, Exception::StoreAddressMisaligned)?;
                 let rs1_ptr = cpu.registers[rs1];
-                let rs1_value = cpu.ram.read_doubleword(rs1_ptr).unwrap() as i64;
+                let rs1_value = cpu.ram.read_doubleword(rs1_ptr).map_err(Exception::store)? as i64;
 
                 cpu.ram
                     .write_doubleword(
                         rs1_ptr,
                         rs1_value.wrapping_add(cpu.registers[rs2] as i64) as u64,
                     )
-                    .unwrap();
+                    .map_err(Exception::store)?;
                 cpu.registers[rd] = rs1_value as u64;
             }
 
             Amoandd(rd, rs1, rs2) => {
+                check_atomic_alignment(cpu.registers[rs1], 8, Exception::StoreAddressMisaligned)?;
                 let rs1_ptr = cpu.registers[rs1];
-                let rs1_value = cpu.ram.read_doubleword(rs1_ptr).unwrap() as i64;
+                let rs1_value = cpu.ram.read_doubleword(rs1_ptr).map_err(Exception::store)? as i64;
 
                 cpu.ram
                     .write_doubleword(rs1_ptr, (rs1_value & cpu.registers[rs2] as i64) as u64)
-                    .unwrap();
+                    .map_err(Exception::store)?;
                 cpu.registers[rd] = rs1_value as u64;
             }
 
             Amoxord(rd, rs1, rs2) => {
+                check_atomic_alignment(cpu.registers[rs1], 8, Exception::StoreAddressMisaligned)?;
                 let rs1_ptr = cpu.registers[rs1];
-                let rs1_value = cpu.ram.read_doubleword(rs1_ptr).unwrap() as i64;
+                let rs1_value = cpu.ram.read_doubleword(rs1_ptr).map_err(Exception::store)? as i64;
 
                 cpu.ram
                     .write_doubleword(rs1_ptr, (rs1_value ^ cpu.registers[rs2] as i64) as u64)
-                    .unwrap();
+                    .map_err(Exception::store)?;
                 cpu.registers[rd] = rs1_value as u64;
             }
 
             Amoord(rd, rs1, rs2) => {
+                check_atomic_alignment(cpu.registers[rs1], 8, Exception::StoreAddressMisaligned)?;
                 let rs1_ptr = cpu.registers[rs1];
-                let rs1_value = cpu.ram.read_doubleword(rs1_ptr).unwrap() as i64;
+                let rs1_value = cpu.ram.read_doubleword(rs1_ptr).map_err(Exception::store)? as i64;
 
                 cpu.ram
                     .write_doubleword(rs1_ptr, (rs1_value | cpu.registers[rs2] as i64) as u64)
-                    .unwrap();
+                    .map_err(Exception::store)?;
                 cpu.registers[rd] = rs1_value as u64;
             }
 
             Amomind(rd, rs1, rs2) => {
+                check_atomic_alignment(cpu.registers[rs1], 8, Exception::StoreAddressMisaligned)?;
                 let rs1_ptr = cpu.registers[rs1];
-                let rs1_value = cpu.ram.read_doubleword(rs1_ptr).unwrap() as i64;
+                let rs1_value = cpu.ram.read_doubleword(rs1_ptr).map_err(Exception::store)? as i64;
 
                 cpu.ram
                     .write_doubleword(rs1_ptr, rs1_value.min(cpu.registers[rs2] as i64) as u64)
-                    .unwrap();
+                    .map_err(Exception::store)?;
                 cpu.registers[rd] = rs1_value as u64;
             }
 
             Amominud(rd, rs1, rs2) => {
+                check_atomic_alignment(cpu.registers[rs1], 8, Exception::StoreAddressMisaligned)?;
                 let rs1_ptr = cpu.registers[rs1];
-                let rs1_value = cpu.ram.read_doubleword(rs1_ptr).unwrap();
+                let rs1_value = cpu.ram.read_doubleword(rs1_ptr).map_err(Exception::store)?;
 
                 cpu.ram
                     .write_doubleword(rs1_ptr, rs1_value.min(cpu.registers[rs2]))
-                    .unwrap();
+                    .map_err(Exception::store)?;
                 cpu.registers[rd] = rs1_value;
             }
 
             Amomaxd(rd, rs1, rs2) => {
+                check_atomic_alignment(cpu.registers[rs1], 8, Exception::StoreAddressMisaligned)?;
                 let rs1_ptr = cpu.registers[rs1];
-                let rs1_value = cpu.ram.read_doubleword(rs1_ptr).unwrap() as i64;
+                let rs1_value = cpu.ram.read_doubleword(rs1_ptr).map_err(Exception::store)? as i64;
 
                 cpu.ram
                     .write_doubleword(rs1_ptr, rs1_value.max(cpu.registers[rs2] as i64) as u64)
-                    .unwrap();
+                    .map_err(Exception::store)?;
                 cpu.registers[rd] = rs1_value as u64;
             }
 
             Amomaxud(rd, rs1, rs2) => {
+                check_atomic_alignment(cpu.registers[rs1], 8, Exception::StoreAddressMisaligned)?;
                 let rs1_ptr = cpu.registers[rs1];
-                let rs1_value = cpu.ram.read_doubleword(rs1_ptr).unwrap();
+                let rs1_value = cpu.ram.read_doubleword(rs1_ptr).map_err(Exception::store)?;
 
                 cpu.ram
                     .write_doubleword(rs1_ptr, rs1_value.max(cpu.registers[rs2]))
-                    .unwrap();
+                    .map_err(Exception::store)?;
                 cpu.registers[rd] = rs1_value;
             }
 
             // NOTE: RV64F
             Fmadds(rd, rm, rs1, rs2, rs3) => {
-                let rm = if *rm == 0b111 {
-                    cpu.fcsr.frm
-                } else {
-                    RoundingMode::from(rm)
-                };
+                let rm = rounding_mode(cpu, *rm)?;
+                let rs1 = cpu.float_registers.read_f32(rs1);
+                let rs2 = cpu.float_registers.read_f32(rs2);
+                let rs3 = cpu.float_registers.read_f32(rs3);
 
-                let rs1 = f32::from_bits(cpu.float_registers[rs1] as u32);
-                let rs2 = f32::from_bits(cpu.float_registers[rs2] as u32);
-                let rs3 = f32::from_bits(cpu.float_registers[rs3] as u32);
-
-                cpu.float_registers[rd] = u64::from(round_f32((rs1 * rs2) + rs3, rm).to_bits());
+                let res = softfloat::mul_add(rs1, rs2, rs3, rm, &mut cpu.fcsr);
+                cpu.float_registers.write_f32(rd, res);
             }
 
             Fmsubs(rd, rm, rs1, rs2, rs3) => {
-                let rm = if *rm == 0b111 {
-                    cpu.fcsr.frm
-                } else {
-                    RoundingMode::from(rm)
-                };
+                let rm = rounding_mode(cpu, *rm)?;
+                let rs1 = cpu.float_registers.read_f32(rs1);
+                let rs2 = cpu.float_registers.read_f32(rs2);
+                let rs3 = cpu.float_registers.read_f32(rs3);
+
+                let res = softfloat::mul_sub(rs1, rs2, rs3, rm, &mut cpu.fcsr);
+                cpu.float_registers.write_f32(rd, res);
+            }
 
-                let rs1 = f32::from_bits(cpu.float_registers[rs1] as u32);
-                let rs2 = f32::from_bits(cpu.float_registers[rs2] as u32);
-                let rs3 = f32::from_bits(cpu.float_registers[rs3] as u32);
+            Fnmsubs(rd, rm, rs1, rs2, rs3) => {
+                let rm = rounding_mode(cpu, *rm)?;
+                let rs1 = cpu.float_registers.read_f32(rs1);
+                let rs2 = cpu.float_registers.read_f32(rs2);
+                let rs3 = cpu.float_registers.read_f32(rs3);
 
-                cpu.float_registers[rd] = u64::from(round_f32((rs1 * rs2) - rs3, rm).to_bits());
+                let res = softfloat::neg_mul_add(rs1, rs2, rs3, rm, &mut cpu.fcsr);
+                cpu.float_registers.write_f32(rd, res);
             }
 
             Fnmadds(rd, rm, rs1, rs2, rs3) => {
-                let rm = if *rm == 0b111 {
-                    cpu.fcsr.frm
-                } else {
-                    RoundingMode::from(rm)
-                };
+                let rm = rounding_mode(cpu, *rm)?;
+                let rs1 = cpu.float_registers.read_f32(rs1);
+                let rs2 = cpu.float_registers.read_f32(rs2);
+                let rs3 = cpu.float_registers.read_f32(rs3);
 
-                let rs1 = f32::from_bits(cpu.float_registers[rs1] as u32);
-                let rs2 = f32::from_bits(cpu.float_registers[rs2] as u32);
-                let rs3 = f32::from_bits(cpu.float_registers[rs3] as u32);
+                let res = softfloat::neg_mul_sub(rs1, rs2, rs3, rm, &mut cpu.fcsr);
+                cpu.float_registers.write_f32(rd, res);
+            }
+
+            Fadds(rd, rm, rs1, rs2) => {
+                let rm = rounding_mode(cpu, *rm)?;
+                let rs1 = cpu.float_registers.read_f32(rs1);
+                let rs2 = cpu.float_registers.read_f32(rs2);
 
-                cpu.float_registers[rd] = u64::from(round_f32(-(rs1 * rs2) + rs3, rm).to_bits());
+                let res = softfloat::add(rs1, rs2, rm, &mut cpu.fcsr);
+                cpu.float_registers.write_f32(rd, res);
             }
 
-            Fnmsubs(rd, rm, rs1, rs2, rs3) => {
-                let rm = if *rm == 0b111 {
-                    cpu.fcsr.frm
-                } else {
-                    RoundingMode::from(rm)
-                };
+            Fsubs(rd, rm, rs1, rs2) => {
+                let rm = rounding_mode(cpu, *rm)?;
+                let rs1 = cpu.float_registers.read_f32(rs1);
+                let rs2 = cpu.float_registers.read_f32(rs2);
+
+                let res = softfloat::sub(rs1, rs2, rm, &mut cpu.fcsr);
+                cpu.float_registers.write_f32(rd, res);
+            }
 
-                let rs1 = f32::from_bits(cpu.float_registers[rs1] as u32);
-                let rs2 = f32::from_bits(cpu.float_registers[rs2] as u32);
-                let rs3 = f32::from_bits(cpu.float_registers[rs3] as u32);
+            Fmuls(rd, rm, rs1, rs2) => {
+                let rm = rounding_mode(cpu, *rm)?;
+                let rs1 = cpu.float_registers.read_f32(rs1);
+                let rs2 = cpu.float_registers.read_f32(rs2);
 
-                cpu.float_registers[rd] = u64::from(round_f32(-(rs1 * rs2) - rs3, rm).to_bits());
+                let res = softfloat::mul(rs1, rs2, rm, &mut cpu.fcsr);
+                cpu.float_registers.write_f32(rd, res);
             }
 
-            Fadds(rd, rm, rs1, rs2) => {
-                let rm = if *rm == 0b111 {
-                    cpu.fcsr.frm
-                } else {
-                    RoundingMode::from(rm)
-                };
-                let rs1 = f32::from_bits(cpu.float_registers[rs1] as u32);
-                let rs2 = f32::from_bits(cpu.float_registers[rs2] as u32);
+            Fdivs(rd, rm, rs1, rs2) => {
+                let rm = rounding_mode(cpu, *rm)?;
+                let rs1 = cpu.float_registers.read_f32(rs1);
+                let rs2 = cpu.float_registers.read_f32(rs2);
 
-                let res = round_f32(rs1 + rs2, rm);
+                let res = softfloat::div(rs1, rs2, rm, &mut cpu.fcsr);
+                cpu.float_registers.write_f32(rd, res);
+            }
 
-                trace!("fadds rs1: {rs1}");
+            Fsqrts(rd, rm, rs1) => {
+                let rm = rounding_mode(cpu, *rm)?;
+                let rs1 = cpu.float_registers.read_f32(rs1);
 
-                cpu.float_registers[rd] = u64::from(res.to_bits());
+                let res = softfloat::sqrt(rs1, rm, &mut cpu.fcsr);
+                cpu.float_registers.write_f32(rd, res);
             }
 
-            Fsubs(rd, rm, rs1, rs2) => {
-                let rm = if *rm == 0b111 {
-                    cpu.fcsr.frm
-                } else {
-                    RoundingMode::from(rm)
-                };
-                let rs1 = f32::from_bits(cpu.float_registers[rs1] as u32);
-                let rs2 = f32::from_bits(cpu.float_registers[rs2] as u32);
+            // NOTE: Sign injection only moves bits around, so NaNs keep their payload
+            Fsgnjs(rd, rs1, rs2) => {
+                let rs1 = cpu.float_registers.read_f32(rs1).to_bits();
+                let rs2 = cpu.float_registers.read_f32(rs2).to_bits();
 
-                let res = round_f32(rs1 - rs2, rm);
+                let res = rs1 & !F32_SIGN | rs2 & F32_SIGN;
+                cpu.float_registers.write_f32(rd, f32::from_bits(res));
+            }
+
+            Fsgnjns(rd, rs1, rs2) => {
+                let rs1 = cpu.float_registers.read_f32(rs1).to_bits();
+                let rs2 = cpu.float_registers.read_f32(rs2).to_bits();
 
-                cpu.float_registers[rd] = u64::from(res.to_bits());
+                let res = rs1 & !F32_SIGN | !rs2 & F32_SIGN;
+                cpu.float_registers.write_f32(rd, f32::from_bits(res));
             }
 
-            Fmuls(rd, rm, rs1, rs2) => {
-                let rm = if *rm == 0b111 {
-                    cpu.fcsr.frm
-                } else {
-                    RoundingMode::from(rm)
-                };
-                let rs1 = f32::from_bits(cpu.float_registers[rs1] as u32);
-                let rs2 = f32::from_bits(cpu.float_registers[rs2] as u32);
+            Fsgnjxs(rd, rs1, rs2) => {
+                let rs1 = cpu.float_registers.read_f32(rs1).to_bits();
+                let rs2 = cpu.float_registers.read_f32(rs2).to_bits();
+
+                let res = rs1 ^ rs2 & F32_SIGN;
+                cpu.float_registers.write_f32(rd, f32::from_bits(res));
+            }
+
+            Fmins(rd, rs1, rs2) => {
+                let rs1 = cpu.float_registers.read_f32(rs1);
+                let rs2 = cpu.float_registers.read_f32(rs2);
+
+                let res = fcsr::min_max(rs1, rs2, false, &mut cpu.fcsr);
+                cpu.float_registers.write_f32(rd, res);
+            }
+
+            Fmaxs(rd, rs1, rs2) => {
+                let rs1 = cpu.float_registers.read_f32(rs1);
+                let rs2 = cpu.float_registers.read_f32(rs2);
+
+                let res = fcsr::min_max(rs1, rs2, true, &mut cpu.fcsr);
+                cpu.float_registers.write_f32(rd, res);
+            }
+
+            Fcvtws(rd, rm, rs1) => {
+                let rm = rounding_mode(cpu, *rm)?;
+                let rs1 = cpu.float_registers.read_f32(rs1);
+
+                let (min, max) = (i32::MIN.into(), i32::MAX.into());
+                let int = fcsr::to_integer(rs1.into(), rm, min, max, &mut cpu.fcsr);
+                cpu.registers[rd] = int as i32 as u64;
+            }
+
+            // NOTE: The 32 bit unsigned conversions sign extend their result too
+            Fcvtwus(rd, rm, rs1) => {
+                let rm = rounding_mode(cpu, *rm)?;
+                let rs1 = cpu.float_registers.read_f32(rs1);
+
+                let int = fcsr::to_integer(rs1.into(), rm, 0, u32::MAX.into(), &mut cpu.fcsr);
+                cpu.registers[rd] = int as u32 as i32 as u64;
+            }
+
+            Fcvtls(rd, rm, rs1) => {
+                let rm = rounding_mode(cpu, *rm)?;
+                let rs1 = cpu.float_registers.read_f32(rs1);
+
+                let (min, max) = (i64::MIN.into(), i64::MAX.into());
+                let int = fcsr::to_integer(rs1.into(), rm, min, max, &mut cpu.fcsr);
+                cpu.registers[rd] = int as u64;
+            }
+
+            Fcvtlus(rd, rm, rs1) => {
+                let rm = rounding_mode(cpu, *rm)?;
+                let rs1 = cpu.float_registers.read_f32(rs1);
+
+                let int = fcsr::to_integer(rs1.into(), rm, 0, u64::MAX.into(), &mut cpu.fcsr);
+                cpu.registers[rd] = int as u64;
+            }
+
+            // NOTE: The moves copy the bits as they are, without looking at the NaN-boxing
+            Fmvxw(rd, rs1) => cpu.registers[rd] = cpu.float_registers[rs1] as u32 as i32 as u64,
+
+            Feqs(rd, rs1, rs2) => {
+                let rs1 = cpu.float_registers.read_f32(rs1);
+                let rs2 = cpu.float_registers.read_f32(rs2);
+
+                let ordering = fcsr::compare(rs1, rs2, false, &mut cpu.fcsr);
+                cpu.registers[rd] = u64::from(ordering == Some(Ordering::Equal));
+            }
+
+            Flts(rd, rs1, rs2) => {
+                let rs1 = cpu.float_registers.read_f32(rs1);
+                let rs2 = cpu.float_registers.read_f32(rs2);
+
+                let ordering = fcsr::compare(rs1, rs2, true, &mut cpu.fcsr);
+                cpu.registers[rd] = u64::from(ordering == Some(Ordering::Less));
+            }
+
+            Fles(rd, rs1, rs2) => {
+                let rs1 = cpu.float_registers.read_f32(rs1);
+                let rs2 = cpu.float_registers.read_f32(rs2);
+
+                let ordering = fcsr::compare(rs1, rs2, true, &mut cpu.fcsr);
+                cpu.registers[rd] = u64::from(ordering.is_some_and(Ordering::is_le));
+            }
+
+            Fclasss(rd, rs1) => {
+                let res = fcsr::classify(cpu.float_registers.read_f32(rs1));
+                cpu.registers[rd] = u64::from(res);
+            }
+
+            Fcvtsw(rd, rm, rs1) => {
+                let rm = rounding_mode(cpu, *rm)?;
+                let int = cpu.registers[rs1] as i32;
+                let res = softfloat::from_int(int.into(), rm, &mut cpu.fcsr);
+                cpu.float_registers.write_f32(rd, res);
+            }
+
+            Fcvtswu(rd, rm, rs1) => {
+                let rm = rounding_mode(cpu, *rm)?;
+                let int = cpu.registers[rs1] as u32;
+                let res = softfloat::from_int(int.into(), rm, &mut cpu.fcsr);
+                cpu.float_registers.write_f32(rd, res);
+            }
+
+            Fcvtsl(rd, rm, rs1) => {
+                let rm = rounding_mode(cpu, *rm)?;
+                let int = cpu.registers[rs1] as i64;
+                let res = softfloat::from_int(int.into(), rm, &mut cpu.fcsr);
+                cpu.float_registers.write_f32(rd, res);
+            }
+
+            Fcvtslu(rd, rm, rs1) => {
+                let rm = rounding_mode(cpu, *rm)?;
+                let int = cpu.registers[rs1];
+                let res = softfloat::from_int(int.into(), rm, &mut cpu.fcsr);
+                cpu.float_registers.write_f32(rd, res);
+            }
+
+            Fmvwx(rd, rs1) => {
+                cpu.float_registers
+                    .write_f32(rd, f32::from_bits(cpu.registers[rs1] as u32));
+            }
+
+            Flw(rd, rs1, imm) => {
+                let addr = cpu.registers[rs1].wrapping_add_signed(sign_extend12(*imm));
+                let value = cpu.ram.read_word(addr).map_err(Exception::load)?;
+
+                cpu.float_registers.write_f32(rd, f32::from_bits(value));
+            }
+
+            Fsw(rs1, rs2, imm) => {
+                let value = cpu.float_registers[rs2] as u32;
+                cpu.ram
+                    .write_word(
+                        cpu.registers[rs1].wrapping_add_signed(sign_extend12(*imm)),
+                        value,
+                    )
+                    .map_err(Exception::store)?;
+            }
 
-                let res = round_f32(rs1 * rs2, rm);
+            // NOTE: RV64D
+            Fmaddd(rd, rm, rs1, rs2, rs3) => {
+                let rm = rounding_mode(cpu, *rm)?;
+                let rs1 = cpu.float_registers.read_f64(rs1);
+                let rs2 = cpu.float_registers.read_f64(rs2);
+                let rs3 = cpu.float_registers.read_f64(rs3);
 
-                cpu.float_registers[rd] = u64::from(res.to_bits());
+                let res = softfloat::mul_add(rs1, rs2, rs3, rm, &mut cpu.fcsr);
+                cpu.float_registers.write_f64(rd, res);
             }
 
-            Fdivs(rd, rm, rs1, rs2) => {
-                let rm = if *rm == 0b111 {
-                    cpu.fcsr.frm
-                } else {
-                    RoundingMode::from(rm)
-                };
-                let rs1 = f32::from_bits(cpu.float_registers[rs1] as u32);
-                let rs2 = f32::from_bits(cpu.float_registers[rs2] as u32);
+            Fmsubd(rd, rm, rs1, rs2, rs3) => {
+                let rm = rounding_mode(cpu, *rm)?;
+                let rs1 = cpu.float_registers.read_f64(rs1);
+                let rs2 = cpu.float_registers.read_f64(rs2);
+                let rs3 = cpu.float_registers.read_f64(rs3);
 
-                let res = round_f32(rs1 / rs2, rm);
+                let res = softfloat::mul_sub(rs1, rs2, rs3, rm, &mut cpu.fcsr);
+                cpu.float_registers.write_f64(rd, res);
+            }
+
+            Fnmsubd(rd, rm, rs1, rs2, rs3) => {
+                let rm = rounding_mode(cpu, *rm)?;
+                let rs1 = cpu.float_registers.read_f64(rs1);
+                let rs2 = cpu.float_registers.read_f64(rs2);
+                let rs3 = cpu.float_registers.read_f64(rs3);
 
-                cpu.float_registers[rd] = u64::from(res.to_bits());
+                let res = softfloat::neg_mul_add(rs1, rs2, rs3, rm, &mut cpu.fcsr);
+                cpu.float_registers.write_f64(rd, res);
             }
 
-            Fsqrts(rd, rm, rs1) => {
-                let rm = if *rm == 0b111 {
-                    cpu.fcsr.frm
-                } else {
-                    RoundingMode::from(rm)
-                };
-                let rs1 = f32::from_bits(cpu.float_registers[rs1] as u32);
+            Fnmaddd(rd, rm, rs1, rs2, rs3) => {
+                let rm = rounding_mode(cpu, *rm)?;
+                let rs1 = cpu.float_registers.read_f64(rs1);
+                let rs2 = cpu.float_registers.read_f64(rs2);
+                let rs3 = cpu.float_registers.read_f64(rs3);
+
+                let res = softfloat::neg_mul_sub(rs1, rs2, rs3, rm, &mut cpu.fcsr);
+                cpu.float_registers.write_f64(rd, res);
+            }
 
-                let res = round_f32(rs1.sqrt(), rm);
+            Faddd(rd, rm, rs1, rs2) => {
+                let rm = rounding_mode(cpu, *rm)?;
+                let rs1 = cpu.float_registers.read_f64(rs1);
+                let rs2 = cpu.float_registers.read_f64(rs2);
 
-                cpu.float_registers[rd] = u64::from(res.to_bits());
+                let res = softfloat::add(rs1, rs2, rm, &mut cpu.fcsr);
+                cpu.float_registers.write_f64(rd, res);
             }
 
-            Fsgnjs(rd, rs1, rs2) => {
-                let rs1 = cpu.float_registers[rs1] as u32;
-                let rs2 = cpu.float_registers[rs2] as u32;
+            Fsubd(rd, rm, rs1, rs2) => {
+                let rm = rounding_mode(cpu, *rm)?;
+                let rs1 = cpu.float_registers.read_f64(rs1);
+                let rs2 = cpu.float_registers.read_f64(rs2);
 
-                let sign_bit = rs1.bit(31);
-                let res = *rs2.bit_range(0..31).set_bit(31, sign_bit);
+                let res = softfloat::sub(rs1, rs2, rm, &mut cpu.fcsr);
+                cpu.float_registers.write_f64(rd, res);
+            }
+
+            Fmuld(rd, rm, rs1, rs2) => {
+                let rm = rounding_mode(cpu, *rm)?;
+                let rs1 = cpu.float_registers.read_f64(rs1);
+                let rs2 = cpu.float_registers.read_f64(rs2);
 
-                cpu.float_registers[rd] = u64::from(res);
+                let res = softfloat::mul(rs1, rs2, rm, &mut cpu.fcsr);
+                cpu.float_registers.write_f64(rd, res);
             }
 
-            Fsgnjns(rd, rs1, rs2) => {
-                let rs1 = cpu.float_registers[rs1] as u32;
-                let rs2 = cpu.float_registers[rs2] as u32;
+            Fdivd(rd, rm, rs1, rs2) => {
+                let rm = rounding_mode(cpu, *rm)?;
+                let rs1 = cpu.float_registers.read_f64(rs1);
+                let rs2 = cpu.float_registers.read_f64(rs2);
+
+                let res = softfloat::div(rs1, rs2, rm, &mut cpu.fcsr);
+                cpu.float_registers.write_f64(rd, res);
+            }
 
-                let sign_bit = rs1.bit(31);
-                let res = *rs2.bit_range(0..31).set_bit(31, !sign_bit);
+            Fsqrtd(rd, rm, rs1) => {
+                let rm = rounding_mode(cpu, *rm)?;
+                let rs1 = cpu.float_registers.read_f64(rs1);
 
-                cpu.float_registers[rd] = u64::from(res);
+                let res = softfloat::sqrt(rs1, rm, &mut cpu.fcsr);
+                cpu.float_registers.write_f64(rd, res);
             }
 
-            Fsgnjxs(rd, rs1, rs2) => {
-                let rs1 = cpu.float_registers[rs1] as u32;
-                let rs2 = cpu.float_registers[rs2] as u32;
+            Fsgnjd(rd, rs1, rs2) => {
+                let rs1 = cpu.float_registers[rs1];
+                let rs2 = cpu.float_registers[rs2];
+
+                cpu.float_registers[rd] = rs1 & !F64_SIGN | rs2 & F64_SIGN;
+            }
 
-                let rs1_sign_bit = rs1.bit(31);
-                let rs2_sign_bit = rs2.bit(31);
-                let res = *rs2
-                    .bit_range(0..31)
-                    .set_bit(31, rs1_sign_bit ^ rs2_sign_bit);
+            Fsgnjnd(rd, rs1, rs2) => {
+                let rs1 = cpu.float_registers[rs1];
+                let rs2 = cpu.float_registers[rs2];
 
-                cpu.float_registers[rd] = u64::from(res);
+                cpu.float_registers[rd] = rs1 & !F64_SIGN | !rs2 & F64_SIGN;
             }
 
-            Fmins(rd, rs1, rs2) => {
-                let rs1 = f32::from_bits(cpu.float_registers[rs1] as u32);
-                let rs2 = f32::from_bits(cpu.float_registers[rs2] as u32);
+            Fsgnjxd(rd, rs1, rs2) => {
+                let rs1 = cpu.float_registers[rs1];
+                let rs2 = cpu.float_registers[rs2];
 
-                cpu.float_registers[rd] = u64::from(rs1.min(rs2).to_bits())
+                cpu.float_registers[rd] = rs1 ^ rs2 & F64_SIGN;
             }
 
-            Fmaxs(rd, rs1, rs2) => {
-                let rs1 = f32::from_bits(cpu.float_registers[rs1] as u32);
-                let rs2 = f32::from_bits(cpu.float_registers[rs2] as u32);
+            Fmind(rd, rs1, rs2) => {
+                let rs1 = cpu.float_registers.read_f64(rs1);
+                let rs2 = cpu.float_registers.read_f64(rs2);
 
-                cpu.float_registers[rd] = u64::from(rs1.max(rs2).to_bits())
+                let res = fcsr::min_max(rs1, rs2, false, &mut cpu.fcsr);
+                cpu.float_registers.write_f64(rd, res);
             }
 
-            Fcvtws(rd, rm, rs1) => {
-                let rm = if *rm == 0b111 {
-                    cpu.fcsr.frm
-                } else {
-                    RoundingMode::from(rm)
-                };
-                let rs1 = f32::from_bits(cpu.float_registers[rs1] as u32);
-                let val = round_f32(rs1, rm);
+            Fmaxd(rd, rs1, rs2) => {
+                let rs1 = cpu.float_registers.read_f64(rs1);
+                let rs2 = cpu.float_registers.read_f64(rs2);
 
-                cpu.registers[rd] = val as i64 as u64
+                let res = fcsr::min_max(rs1, rs2, true, &mut cpu.fcsr);
+                cpu.float_registers.write_f64(rd, res);
             }
 
-            Fcvtwus(rd, rm, rs1) => {
-                let rm = if *rm == 0b111 {
-                    cpu.fcsr.frm
-                } else {
-                    RoundingMode::from(rm)
-                };
-                let rs1 = f32::from_bits(cpu.float_registers[rs1] as u32);
-                let val = round_f32(rs1, rm);
+            Fcvtsd(rd, rm, rs1) => {
+                let rm = rounding_mode(cpu, *rm)?;
+                let rs1 = cpu.float_registers.read_f64(rs1);
 
-                cpu.registers[rd] = val as u64
+                let res = softfloat::convert(rs1, rm, &mut cpu.fcsr);
+                cpu.float_registers.write_f32(rd, res);
             }
 
-            Fmvxw(rd, rs1) => cpu.registers[rd] = u64::from(cpu.float_registers[rs1] as u32),
+            // NOTE: Widening is exact, so there's nothing to round, but a reserved rm is
+            // still illegal
+            Fcvtds(rd, rm, rs1) => {
+                rounding_mode(cpu, *rm)?;
+                let rs1 = cpu.float_registers.read_f32(rs1);
 
-            Feqs(rd, rs1, rs2) => {
-                let rs1 = f32::from_bits(cpu.float_registers[rs1] as u32);
-                let rs2 = f32::from_bits(cpu.float_registers[rs2] as u32);
+                let res = softfloat::convert(rs1, RoundingMode::Rne, &mut cpu.fcsr);
+                cpu.float_registers.write_f64(rd, res);
+            }
 
-                if rs1.is_nan() || rs2.is_nan() {
-                    cpu.fcsr.set_flag(FCSR::NV);
-                    cpu.registers[rd] = 0;
-                    return;
-                }
+            Feqd(rd, rs1, rs2) => {
+                let rs1 = cpu.float_registers.read_f64(rs1);
+                let rs2 = cpu.float_registers.read_f64(rs2);
 
-                let res = if rs1 == rs2 { 1 } else { 0 };
+                let ordering = fcsr::compare(rs1, rs2, false, &mut cpu.fcsr);
+                cpu.registers[rd] = u64::from(ordering == Some(Ordering::Equal));
+            }
 
-                cpu.registers[rd] = res;
+            Fltd(rd, rs1, rs2) => {
+                let rs1 = cpu.float_registers.read_f64(rs1);
+                let rs2 = cpu.float_registers.read_f64(rs2);
+
+                let ordering = fcsr::compare(rs1, rs2, true, &mut cpu.fcsr);
+                cpu.registers[rd] = u64::from(ordering == Some(Ordering::Less));
             }
 
-            Flts(rd, rs1, rs2) => {
-                let rs1 = f32::from_bits(cpu.float_registers[rs1] as u32);
-                let rs2 = f32::from_bits(cpu.float_registers[rs2] as u32);
+            Fled(rd, rs1, rs2) => {
+                let rs1 = cpu.float_registers.read_f64(rs1);
+                let rs2 = cpu.float_registers.read_f64(rs2);
 
-                if rs1.is_nan() || rs2.is_nan() {
-                    cpu.fcsr.set_flag(FCSR::NV);
-                    cpu.registers[rd] = 0;
-                    return;
-                }
+                let ordering = fcsr::compare(rs1, rs2, true, &mut cpu.fcsr);
+                cpu.registers[rd] = u64::from(ordering.is_some_and(Ordering::is_le));
+            }
+
+            Fclassd(rd, rs1) => {
+                let res = fcsr::classify(cpu.float_registers.read_f64(rs1));
+                cpu.registers[rd] = u64::from(res);
+            }
 
-                let res = if rs1 < rs2 { 1 } else { 0 };
+            Fcvtwd(rd, rm, rs1) => {
+                let rm = rounding_mode(cpu, *rm)?;
+                let rs1 = cpu.float_registers.read_f64(rs1);
 
-                cpu.registers[rd] = res;
+                let (min, max) = (i32::MIN.into(), i32::MAX.into());
+                let int = fcsr::to_integer(rs1, rm, min, max, &mut cpu.fcsr);
+                cpu.registers[rd] = int as i32 as u64;
             }
 
-            Fles(rd, rs1, rs2) => {
-                let rs1 = f32::from_bits(cpu.float_registers[rs1] as u32);
-                let rs2 = f32::from_bits(cpu.float_registers[rs2] as u32);
+            Fcvtwud(rd, rm, rs1) => {
+                let rm = rounding_mode(cpu, *rm)?;
+                let rs1 = cpu.float_registers.read_f64(rs1);
 
-                if rs1.is_nan() || rs2.is_nan() {
-                    cpu.fcsr.set_flag(FCSR::NV);
-                    cpu.registers[rd] = 0;
-                    return;
-                }
+                let int = fcsr::to_integer(rs1, rm, 0, u32::MAX.into(), &mut cpu.fcsr);
+                cpu.registers[rd] = int as u32 as i32 as u64;
+            }
 
-                let res = if rs1 <= rs2 { 1 } else { 0 };
+            Fcvtld(rd, rm, rs1) => {
+                let rm = rounding_mode(cpu, *rm)?;
+                let rs1 = cpu.float_registers.read_f64(rs1);
 
-                cpu.registers[rd] = res;
+                let (min, max) = (i64::MIN.into(), i64::MAX.into());
+                let int = fcsr::to_integer(rs1, rm, min, max, &mut cpu.fcsr);
+                cpu.registers[rd] = int as u64;
             }
 
-            Fclasss(rd, rs1) => {
-                let res = classify_f32(f32::from_bits(cpu.float_registers[rs1] as u32));
-                cpu.registers[rd] = res as u64;
+            Fcvtlud(rd, rm, rs1) => {
+                let rm = rounding_mode(cpu, *rm)?;
+                let rs1 = cpu.float_registers.read_f64(rs1);
+
+                let int = fcsr::to_integer(rs1, rm, 0, u64::MAX.into(), &mut cpu.fcsr);
+                cpu.registers[rd] = int as u64;
             }
 
-            Fcvtsw(rd, rm, rs1) => {
-                let rm = if *rm == 0b111 {
-                    cpu.fcsr.frm
-                } else {
-                    RoundingMode::from(rm)
-                };
+            // NOTE: Every 32 bit integer fits in a double, these are exact
+            Fcvtdw(rd, rm, rs1) => {
+                rounding_mode(cpu, *rm)?;
                 let int = cpu.registers[rs1] as i32;
-                cpu.float_registers[rd] = u64::from(round_f32(int as f32, rm).to_bits());
+                cpu.float_registers.write_f64(rd, int.into());
             }
 
-            Fcvtswu(rd, rm, rs1) => {
-                let rm = if *rm == 0b111 {
-                    cpu.fcsr.frm
-                } else {
-                    RoundingMode::from(rm)
-                };
+            Fcvtdwu(rd, rm, rs1) => {
+                rounding_mode(cpu, *rm)?;
                 let int = cpu.registers[rs1] as u32;
-                cpu.float_registers[rd] = u64::from(round_f32(int as f32, rm).to_bits());
+                cpu.float_registers.write_f64(rd, int.into());
             }
 
-            Fmvwx(rd, rs1) => cpu.float_registers[rd] = cpu.registers[rs1] & u32::MAX as u64,
+            Fcvtdl(rd, rm, rs1) => {
+                let rm = rounding_mode(cpu, *rm)?;
+                let int = cpu.registers[rs1] as i64;
+                let res = softfloat::from_int(int.into(), rm, &mut cpu.fcsr);
+                cpu.float_registers.write_f64(rd, res);
+            }
 
-            Flw(rd, rs1, imm) => {
-                let simm = sign_extend12(*imm);
-                let addr = (cpu.registers[rs1] as i64).wrapping_add(simm) as u64;
-                trace!("simm: {simm}");
-                let value = f32::from_bits(cpu.ram.read_word(addr).unwrap());
+            Fcvtdlu(rd, rm, rs1) => {
+                let rm = rounding_mode(cpu, *rm)?;
+                let int = cpu.registers[rs1];
+                let res = softfloat::from_int(int.into(), rm, &mut cpu.fcsr);
+                cpu.float_registers.write_f64(rd, res);
+            }
 
-                trace!("flw addr: {addr:08x}");
+            Fmvxd(rd, rs1) => cpu.registers[rd] = cpu.float_registers[rs1],
 
-                cpu.float_registers[rd] = (value as f32).to_bits() as u64;
-            }
+            Fmvdx(rd, rs1) => cpu.float_registers[rd] = cpu.registers[rs1],
 
-            Fsw(rs1, rs2, imm) => {
-                let value = cpu.float_registers[rs2] as u32;
-                trace!("fsw: {value}");
-                cpu.ram
-                    .write_word(
-                        (cpu.registers[rs1] as i64).wrapping_add(sign_extend12(*imm)) as u64,
-                        value,
-                    )
-                    .unwrap();
+            Fld(rd, rs1, imm) => {
+                let addr = cpu.registers[rs1].wrapping_add_signed(sign_extend12(*imm));
+                cpu.float_registers[rd] = cpu.ram.read_doubleword(addr).map_err(Exception::load)?;
             }
 
             Fsd(rs1, rs2, simm) => {
@@ -2382,34 +2790,18 @@ impl RV64GCInstruction {
 
                 cpu.ram
                     .write_doubleword(cpu.registers[rs1].wrapping_add_signed(*simm), value)
-                    .unwrap();
-            }
-
-            Fcvtds(rd, rm, rs1) => {
-                let rm = if *rm == 0b111 {
-                    cpu.fcsr.frm
-                } else {
-                    RoundingMode::from(rm)
-                };
-
-                let dbl_precision = f64::from_bits(cpu.float_registers[rs1]);
-                cpu.float_registers[rd] = (round_f32(dbl_precision as f32, rm)).to_bits() as u64;
-            }
-
-            Fmvxd(rd, rs1) => cpu.registers[rd] = cpu.float_registers[rs1],
-
-            Fsgnjd(rd, rs1, rs2) => {
-                let sign_bit_rem = cpu.float_registers[rs1] & 0x8000000000000000;
-                cpu.float_registers[rd] = sign_bit_rem & cpu.float_registers[rs2];
+                    .map_err(Exception::store)?;
             }
 
             // NOTE: RV64C
-            Cebreak => panic!("c.ebreak not implemented!"),
+            Cebreak => return Err(Exception::Breakpoint),
 
             Cjalr(rs1) => {
+                // NOTE: rs1 can be ra, so it's read before the link is written
+                let target = cpu.registers[rs1];
                 cpu.registers[Ra] = cpu.registers[Pc] + 2;
                 // Subtract 2, since we add 2 after this instruction
-                cpu.registers[Pc] = cpu.registers[rs1].wrapping_sub(2);
+                cpu.registers[Pc] = target.wrapping_sub(2);
             }
 
             Cadd(rd, rs1) => cpu.registers[rd] = cpu.registers[rd].wrapping_add(cpu.registers[rs1]),
@@ -2428,19 +2820,17 @@ impl RV64GCInstruction {
             Cmv(rd, rs1) => cpu.registers[rd] = cpu.registers[rs1],
 
             Cldsp(rd, imm) => {
-                let addr = cpu.registers[Sp] + *imm as u64;
+                let addr = cpu.registers[Sp].wrapping_add(*imm as u64);
                 let res = cpu.ram.read_doubleword(addr);
 
-                if let Err(res) = res {
-                    panic!("{}", res);
-                }
-
-                let res = res.unwrap();
+                let res = res.map_err(Exception::load)?;
 
                 cpu.registers[rd] = res;
             }
 
-            Caddi4spn(rd, imm) => cpu.registers[rd] = cpu.registers[Sp] + u64::from(*imm),
+            Caddi4spn(rd, imm) => {
+                cpu.registers[rd] = cpu.registers[Sp].wrapping_add(u64::from(*imm));
+            }
 
             Caddi16sp(simm) => {
                 cpu.registers[Sp] = cpu.registers[Sp].wrapping_add_signed(*simm);
@@ -2458,9 +2848,7 @@ impl RV64GCInstruction {
                 let offset = cpu.registers[Sp].wrapping_add(*imm as u64);
                 let res = cpu.ram.write_doubleword(offset, cpu.registers[rs1]);
 
-                if let Err(res) = res {
-                    panic!("{}", res);
-                }
+                res.map_err(Exception::store)?;
             }
 
             Cld(rd, rs1, imm) => {
@@ -2468,11 +2856,7 @@ impl RV64GCInstruction {
                     .ram
                     .read_doubleword(cpu.registers[rs1].wrapping_add(*imm as u64));
 
-                if let Err(res) = res {
-                    panic!("{}", res);
-                }
-
-                let res = res.unwrap();
+                let res = res.map_err(Exception::load)?;
 
                 cpu.registers[rd] = res;
             }
@@ -2507,9 +2891,7 @@ impl RV64GCInstruction {
                 let offset = cpu.registers[rs1].wrapping_add(*imm as u64);
                 let res = cpu.ram.write_doubleword(offset, cpu.registers[rs2]);
 
-                if let Err(res) = res {
-                    panic!("{}", res);
-                }
+                res.map_err(Exception::store)?;
             }
 
             Clui(rd, imm) => {
@@ -2532,9 +2914,7 @@ impl RV64GCInstruction {
                 let offset = cpu.registers[rs1].wrapping_add(*imm as u64);
                 let res = cpu.ram.write_word(offset, cpu.registers[rs2] as u32);
 
-                if let Err(res) = res {
-                    panic!("{}", res);
-                }
+                res.map_err(Exception::store)?;
             }
 
             Csrli(rd, imm) => {
@@ -2557,11 +2937,7 @@ impl RV64GCInstruction {
                     .ram
                     .read_word(cpu.registers[Sp].wrapping_add(*imm as u64));
 
-                if let Err(res) = res {
-                    panic!("{}", res);
-                }
-
-                let res = res.unwrap();
+                let res = res.map_err(Exception::load)?;
 
                 let val = sign_extend(res.into(), 32);
                 cpu.registers[rd] = val as u64;
@@ -2577,11 +2953,7 @@ impl RV64GCInstruction {
                 let offset = cpu.registers[rs1].wrapping_add(*imm as u64);
                 let res = cpu.ram.read_word(offset);
 
-                if let Err(res) = res {
-                    panic!("{}", res);
-                }
-
-                let res = res.unwrap();
+                let res = res.map_err(Exception::load)?;
 
                 cpu.registers[rd] = sign_extend(res.into(), 32) as u64;
             }
@@ -2600,110 +2972,54 @@ impl RV64GCInstruction {
                 cpu.registers[rd] = rd_val.wrapping_sub(rs1_val) as i64 as u64;
             }
 
+            Cfld(rd, rs1, imm) => {
+                cpu.float_registers[rd] = cpu
+                    .ram
+                    .read_doubleword(cpu.registers[rs1].wrapping_add(u64::from(*imm)))
+                    .map_err(Exception::load)?;
+            }
+
+            Cfldsp(rd, imm) => {
+                cpu.float_registers[rd] = cpu
+                    .ram
+                    .read_doubleword(cpu.registers[Sp].wrapping_add(u64::from(*imm)))
+                    .map_err(Exception::load)?;
+            }
+
             Cfsd(rs1, rs2, imm) => cpu
                 .ram
                 .write_doubleword(
-                    cpu.registers[rs1] + u64::from(*imm),
+                    cpu.registers[rs1].wrapping_add(u64::from(*imm)),
                     cpu.float_registers[rs2],
                 )
-                .unwrap(),
+                .map_err(Exception::store)?,
 
             Cfsdsp(rs1, imm) => {
                 cpu.ram
                     .write_doubleword(
-                        cpu.registers[Sp] + u64::from(*imm),
+                        cpu.registers[Sp].wrapping_add(u64::from(*imm)),
                         cpu.float_registers[rs1],
                     )
-                    .unwrap();
+                    .map_err(Exception::store)?;
             }
 
             Cswsp(rs1, offset) => cpu
                 .ram
                 .write_word(
-                    cpu.registers[Sp] + *offset as u64,
+                    cpu.registers[Sp].wrapping_add(*offset as u64),
                     cpu.registers[rs1] as u32,
                 )
-                .unwrap(),
+                .map_err(Exception::store)?,
 
-            _ => todo!(),
+            // NOTE: V
+            ins => vector::execute(ins, cpu)?,
         }
-    }
-}
-
-impl Display for RV64GCInstruction {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        use RV64GCInstruction::*;
-        match self {
-            Addi(rd, rs1, simm) => {
-                write!(f, "addi x{rd}, x{rs1}, {}", simm)
-            }
-
-            Auipc(rd, imm) => {
-                let simm = sign_extend(*imm as u64, 32);
-                write!(f, "auipc x{rd}, {simm}")
-            }
-
-            Xori(rd, rs1, imm) => {
-                write!(f, "xori x{rd}, x{rs1}, {imm}")
-            }
-
-            Lui(rd, simm) => {
-                write!(f, "lui x{rd}, {simm}")
-            }
-
-            Srai(rd, rs1, imm) => {
-                write!(f, "srai x{rd}, x{rs1}, {imm}")
-            }
-
-            Add(rd, rs1, rs2) => {
-                write!(f, "add x{rd}, x{rs1}, x{rs2}")
-            }
-
-            Sub(rd, rs1, rs2) => {
-                write!(f, "sub x{rd}, x{rs1}, x{rs2}")
-            }
-
-            Xor(rd, rs1, rs2) => {
-                write!(f, "xor x{rd}, x{rs1}, x{rs2}")
-            }
-
-            Ecall => {
-                write!(f, "ecall")
-            }
-
-            Sd(rs1, rs2, simm) => {
-                write!(f, "sd x{rs2}, {simm}(x{rs1})")
-            }
-
-            Ld(rd, rs1, simm) => {
-                write!(f, "ld x{rd}, {simm}(x{rs1})")
-            }
-
-            Jal(rd, imm) => {
-                let simm = crate::sign_extend(*imm as u64, 20);
-                write!(f, "jal x{rd}, {simm}")
-            }
-
-            Bne(rs1, rs2, imm) => {
-                let simm = crate::sign_extend(*imm as u64, 13);
-                write!(f, "bne x{rs1}, x{rs2}, {simm}")
-            }
-
-            Bge(rs1, rs2, imm) => {
-                let simm = crate::sign_extend(*imm as u64, 13);
-                write!(f, "bge x{rs1}, x{rs2}, {simm}")
-            }
-
-            Lw(rd, rs1, simm) => {
-                write!(f, "lw x{rd}, x{rs1}, {simm}")
-            }
 
-            e => write!(f, "{e:?}"),
-        }
+        Ok(())
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct RV64GCRegisters {
     registers: [u64; 33],
 }
@@ -2774,12 +3090,18 @@ impl RV64GCRegisters {
         }
     }
 
+    /// The registers as one array, pc last, for compiled code
+    #[cfg(all(feature = "jit", target_arch = "x86_64"))]
+    pub(crate) fn as_mut_ptr(&mut self) -> *mut u64 {
+        self.registers.as_mut_ptr()
+    }
+
     pub const fn float_reg(value: u8) -> u8 {
         value + 33
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct RV64GCFloatRegisters {
     registers: [u64; 32],
 }
@@ -2838,8 +3160,38 @@ impl RV64GCFloatRegisters {
             registers: [0u64; 32],
         }
     }
+
+    /// A single precision value, NaN-boxed in the low half. Registers that don't hold a
+    /// properly boxed value read as the canonical NaN
+    pub fn read_f32(&self, index: &u8) -> f32 {
+        let bits = self[index];
+        if bits >> 32 == u64::from(u32::MAX) {
+            f32::from_bits(bits as u32)
+        } else {
+            f32::CANONICAL_NAN
+        }
+    }
+
+    /// Writes a single precision value with the upper half all ones
+    pub fn write_f32(&mut self, index: &u8, value: f32) {
+        self[index] = NAN_BOX | u64::from(value.to_bits());
+    }
+
+    pub fn read_f64(&self, index: &u8) -> f64 {
+        f64::from_bits(self[index])
+    }
+
+    pub fn write_f64(&mut self, index: &u8, value: f64) {
+        self[index] = value.to_bits();
+    }
 }
 
+/// The upper half of a register holding a single precision value
+const NAN_BOX: u64 = 0xFFFF_FFFF_0000_0000;
+
+const F32_SIGN: u32 = 1 << 31;
+const F64_SIGN: u64 = 1 << 63;
+
 #[derive(Debug)]
 pub enum RV64GCRegAbiName {
     Zero = 0,
@@ -2982,3 +3334,648 @@ impl Display for RV64GCRegAbiName {
         write!(f, "{reg}")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::fd::OpenFile;
+    use std::io::Read;
+
+    /// An OP-FP instruction, `rs2` picks the conversion for the `fcvt`s
+    fn op_fp(funct7: u32, rs2: u32, rs1: u32, rm: u32, rd: u32) -> u32 {
+        funct7 << 25 | rs2 << 20 | rs1 << 15 | rm << 12 | rd << 7 | 0x53
+    }
+
+    const RTZ: u32 = 0b001;
+    const DYN: u32 = 0b111;
+
+    fn boxed(bits: u32) -> u64 {
+        0xffff_ffff_0000_0000 | u64::from(bits)
+    }
+
+    #[test]
+    fn test_nan_boxing() {
+        let mut cpu = RV64GC::new();
+
+        // NOTE: 1.0 without the upper ones, every single precision read sees the canonical NaN
+        cpu.float_registers[1] = 0x3f80_0000;
+        cpu.float_registers.write_f32(&2, -1.0);
+        assert_eq!(cpu.float_registers[2], boxed(0xbf80_0000));
+
+        cpu.execute(op_fp(0x00, 2, 1, 0, 3)).unwrap(); // fadd.s f3, f1, f2
+        assert_eq!(cpu.float_registers[3], boxed(0x7fc0_0000));
+        assert_eq!(cpu.fcsr.fflags(), 0);
+
+        cpu.execute(op_fp(0x10, 2, 1, 0, 3)).unwrap(); // fsgnj.s f3, f1, f2
+        assert_eq!(cpu.float_registers[3], boxed(0xffc0_0000));
+
+        cpu.execute(op_fp(0x70, 0, 1, 1, 10)).unwrap(); // fclass.s x10, f1
+        assert_eq!(cpu.registers[10], 1 << 9);
+        cpu.execute(op_fp(0x70, 0, 2, 1, 10)).unwrap(); // fclass.s x10, f2
+        assert_eq!(cpu.registers[10], 1 << 1);
+
+        // NOTE: Writes box the value, whatever was in the upper bits before
+        cpu.registers[11] = 0x1234_5678_3f80_0000;
+        cpu.execute(op_fp(0x78, 0, 11, 0, 4)).unwrap(); // fmv.w.x f4, x11
+        assert_eq!(cpu.float_registers[4], boxed(0x3f80_0000));
+
+        cpu.ram
+            .add_region(MemoryRegion::new(0x10000, PAGE_SIZE, vec![]))
+            .unwrap();
+        cpu.ram.write_word(0x10000, 0x4000_0000).unwrap();
+        cpu.registers[12] = 0x10000;
+        cpu.execute(12 << 15 | 0b010 << 12 | 5 << 7 | 0x07).unwrap(); // flw f5, 0(x12)
+        assert_eq!(cpu.float_registers[5], boxed(0x4000_0000));
+    }
+
+    #[test]
+    fn test_fcvt_saturation() {
+        let (nan_s, inf_s, ninf_s) = (boxed(0x7fc0_0000), boxed(0x7f80_0000), boxed(0xff80_0000));
+        let single = |value: f32| boxed(value.to_bits());
+        let double = |value: f64| value.to_bits();
+        let (nv, nx) = (FCSR::NV, FCSR::NX);
+
+        // NOTE: (funct7, rs2, f1, x10, fflags), all rounding towards zero
+        let cases = [
+            // fcvt.l.s
+            (0x60, 2, nan_s, i64::MAX as u64, nv),
+            (0x60, 2, 0x3f80_0000, i64::MAX as u64, nv),
+            (0x60, 2, inf_s, i64::MAX as u64, nv),
+            (0x60, 2, ninf_s, i64::MIN as u64, nv),
+            (0x60, 2, single(1e30), i64::MAX as u64, nv),
+            (0x60, 2, single(-1e30), i64::MIN as u64, nv),
+            (0x60, 2, single(-1.5), -1i64 as u64, nx),
+            // fcvt.lu.s
+            (0x60, 3, nan_s, u64::MAX, nv),
+            (0x60, 3, inf_s, u64::MAX, nv),
+            (0x60, 3, ninf_s, 0, nv),
+            (0x60, 3, single(1e30), u64::MAX, nv),
+            (0x60, 3, single(-1.0), 0, nv),
+            (0x60, 3, single(-0.5), 0, nx),
+            // fcvt.l.d
+            (0x61, 2, double(f64::NAN), i64::MAX as u64, nv),
+            (0x61, 2, double(f64::INFINITY), i64::MAX as u64, nv),
+            (0x61, 2, double(f64::NEG_INFINITY), i64::MIN as u64, nv),
+            (0x61, 2, double(2f64.powi(63)), i64::MAX as u64, nv),
+            (0x61, 2, double(-(2f64.powi(63))), i64::MIN as u64, 0),
+            // fcvt.lu.d
+            (0x61, 3, double(f64::NAN), u64::MAX, nv),
+            (0x61, 3, double(f64::INFINITY), u64::MAX, nv),
+            (0x61, 3, double(f64::NEG_INFINITY), 0, nv),
+            (0x61, 3, double(2f64.powi(64)), u64::MAX, nv),
+            (0x61, 3, double(2f64.powi(63)), 1 << 63, 0),
+            (0x61, 3, double(-1.0), 0, nv),
+            // NOTE: fcvt.wu.s and fcvt.wu.d sign extend the 32 bit result
+            (0x60, 1, single(3e9), 0xffff_ffff_b2d0_5e00, 0),
+            (0x60, 1, nan_s, u64::MAX, nv),
+            (0x60, 1, single(-1.0), 0, nv),
+            (0x61, 1, double(4_294_967_295.0), u64::MAX, 0),
+            (0x61, 1, double(2f64.powi(32)), u64::MAX, nv),
+            (0x61, 1, double(2_147_483_648.0), 0xffff_ffff_8000_0000, 0),
+        ];
+
+        for (funct7, rs2, input, expected, flags) in cases {
+            let mut cpu = RV64GC::new();
+            cpu.float_registers[1] = input;
+            let ins = op_fp(funct7, rs2, 1, RTZ, 10);
+            cpu.execute(ins).unwrap();
+            assert_eq!(
+                (cpu.registers[10], cpu.fcsr.fflags()),
+                (expected, flags),
+                "{} of {input:#x}",
+                opcodes::decode(ins)
+            );
+        }
+    }
+
+    #[test]
+    fn test_reserved_rounding_modes() {
+        let mut cpu = RV64GC::new();
+        cpu.float_registers[1] = 0.1f64.to_bits();
+        cpu.registers[1] = 1;
+
+        // NOTE: fcvt.d.s, fcvt.d.w and fcvt.d.wu never round, fcvt.s.d does
+        for (funct7, rs2) in [(0x21, 0), (0x69, 0), (0x69, 1), (0x20, 1)] {
+            for rm in [0b101, 0b110] {
+                assert_eq!(
+                    cpu.execute(op_fp(funct7, rs2, 1, rm, 3)),
+                    Err(Exception::IllegalInstruction)
+                );
+            }
+            cpu.execute(op_fp(funct7, rs2, 1, DYN, 3)).unwrap();
+        }
+
+        // NOTE: The dynamic rm uses frm, which can't hold a reserved mode
+        cpu.fcsr.frm = RoundingMode::Rup;
+        cpu.execute(op_fp(0x20, 1, 1, DYN, 3)).unwrap(); // fcvt.s.d f3, f1
+        assert_eq!(cpu.float_registers[3], boxed(0x3dcc_cccd));
+        cpu.execute(op_fp(0x20, 1, 1, RTZ, 3)).unwrap();
+        assert_eq!(cpu.float_registers[3], boxed(0x3dcc_cccc));
+    }
+
+    /// `li a0, 42`, `li a7, 93` and `ecall`, an `exit(42)`
+    const EXIT_42: [u32; 3] = [0x02a0_0513, 0x05d0_0893, 0x0000_0073];
+
+    fn exit_42() -> RV64GC {
+        let mut cpu = RV64GC::new();
+        cpu.load_bin(EXIT_42.iter().flat_map(|ins| ins.to_le_bytes()).collect());
+        cpu
+    }
+
+    #[test]
+    fn test_fork_memory() {
+        let mut parent = RV64GC::new();
+        let (touched, untouched) = (0x10000, 0x11000);
+        parent
+            .ram
+            .add_region(MemoryRegion::new(touched, 2 * PAGE_SIZE, vec![1; 8]))
+            .unwrap();
+
+        let mut first = parent.fork_state().unwrap();
+        let second = parent.fork_state().unwrap();
+
+        // NOTE: Both the page that has a frame and the one that doesn't get copied on write
+        first.ram.write_doubleword(touched, 2).unwrap();
+        first.ram.write_doubleword(untouched, 2).unwrap();
+        for cpu in [&parent, &second] {
+            assert_eq!(cpu.ram.read_doubleword(touched), Ok(0x0101_0101_0101_0101));
+            assert_eq!(cpu.ram.read_doubleword(untouched), Ok(0));
+        }
+
+        parent.ram.write_doubleword(touched, 3).unwrap();
+        assert_eq!(first.ram.read_doubleword(touched), Ok(2));
+        assert_eq!(
+            second.ram.read_doubleword(touched),
+            Ok(0x0101_0101_0101_0101)
+        );
+    }
+
+    #[test]
+    fn test_fork_fds() {
+        let host_path = std::env::temp_dir().join(format!("riscvm-{}-fork", std::process::id()));
+        std::fs::write(&host_path, b"abcdef").unwrap();
+
+        let mut parent = RV64GC::new();
+        let fd = parent.fds.insert(OpenFile {
+            file: std::fs::File::open(&host_path).unwrap(),
+            path: "fork".into(),
+            host_path: host_path.clone(),
+            flags: 0,
+        });
+
+        let read = |cpu: &mut RV64GC| {
+            let mut buf = [0; 2];
+            cpu.fds
+                .get_mut(fd)
+                .unwrap()
+                .file
+                .read_exact(&mut buf)
+                .unwrap();
+            buf
+        };
+        assert_eq!(&read(&mut parent), b"ab");
+
+        // NOTE: The fork starts where the parent was, and then they move on their own
+        let mut child = parent.fork_state().unwrap();
+        assert_eq!(&read(&mut child), b"cd");
+        assert_eq!(&read(&mut child), b"ef");
+        assert_eq!(parent.fds.get(fd).unwrap().offset(), 2);
+        assert_eq!(&read(&mut parent), b"cd");
+
+        std::fs::remove_file(&host_path).unwrap();
+    }
+
+    #[test]
+    fn test_fork_on_another_thread() {
+        let mut parent = exit_42();
+        parent.execution_mode = ExecutionMode::Blocks;
+        parent.run(Some(1));
+
+        let children: Vec<_> = (0..2)
+            .map(|_| {
+                let mut child = parent.fork_state().unwrap();
+                std::thread::spawn(move || child.start())
+            })
+            .collect();
+        for child in children {
+            assert_eq!(child.join().unwrap(), ExitReason::Exited(42));
+        }
+
+        assert_eq!(parent.registers[A7], 0);
+        assert_eq!(parent.start(), ExitReason::Exited(42));
+    }
+
+    /// A Zicsr instruction, `funct3` picks which, `rs1` is the uimm for the immediate ones
+    fn csr_op(csr: u16, rs1: u32, funct3: u32, rd: u32) -> u32 {
+        u32::from(csr) << 20 | rs1 << 15 | funct3 << 12 | rd << 7 | 0x73
+    }
+
+    const CSRRW: u32 = 0b001;
+    const CSRRS: u32 = 0b010;
+    const CSRRC: u32 = 0b011;
+    const CSRRWI: u32 = 0b101;
+    const CSRRSI: u32 = 0b110;
+    const CSRRCI: u32 = 0b111;
+
+    #[test]
+    fn test_counters() {
+        let mut cpu = RV64GC::new();
+        for _ in 0..3 {
+            cpu.execute(0x0000_0013).unwrap(); // nop
+        }
+
+        // NOTE: The reading instruction hasn't retired yet
+        cpu.execute(csr_op(csr::CSR_CYCLE, 0, CSRRS, 10)).unwrap(); // rdcycle x10
+        assert_eq!(cpu.registers[10], 3);
+        cpu.execute(csr_op(csr::CSR_INSTRET, 0, CSRRS, 10)).unwrap(); // rdinstret x10
+        assert_eq!(cpu.registers[10], 4);
+
+        cpu.execute(csr_op(csr::CSR_TIME, 0, CSRRS, 10)).unwrap(); // rdtime x10
+        let before = cpu.registers[10];
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        cpu.execute(csr_op(csr::CSR_TIME, 0, CSRRS, 10)).unwrap();
+        assert!(cpu.registers[10] - before >= csr::TIMEBASE_FREQ / 500);
+
+        // NOTE: The upper halves are RV32 only
+        for addr in [csr::CSR_CYCLEH, csr::CSR_TIMEH, csr::CSR_INSTRETH] {
+            assert_eq!(
+                cpu.execute(csr_op(addr, 0, CSRRS, 10)),
+                Err(Exception::IllegalInstruction)
+            );
+        }
+    }
+
+    #[test]
+    fn test_float_csrs() {
+        let mut cpu = RV64GC::new();
+        let read = |cpu: &mut RV64GC, addr| {
+            cpu.execute(csr_op(addr, 0, CSRRS, 10)).unwrap();
+            cpu.registers[10]
+        };
+
+        cpu.execute(csr_op(csr::CSR_FRM, 0b011, CSRRWI, 0)).unwrap(); // fsrmi 3
+        assert_eq!(cpu.fcsr.frm, RoundingMode::Rup);
+        assert_eq!(read(&mut cpu, csr::CSR_FCSR), 0b011 << 5);
+
+        // NOTE: fcsr is frm and fflags side by side, anything above them is dropped
+        cpu.registers[11] = 0xffff_ff00 | 0b100 << 5 | 0b10101;
+        cpu.execute(csr_op(csr::CSR_FCSR, 11, CSRRW, 10)).unwrap(); // fscsr x10, x11
+        assert_eq!(cpu.registers[10], 0b011 << 5);
+        assert_eq!(read(&mut cpu, csr::CSR_FFLAGS), 0b10101);
+        assert_eq!(read(&mut cpu, csr::CSR_FRM), 0b100);
+        assert_eq!(read(&mut cpu, csr::CSR_FCSR), 0b100 << 5 | 0b10101);
+
+        cpu.execute(csr_op(csr::CSR_FFLAGS, 0b01010, CSRRSI, 10))
+            .unwrap();
+        assert_eq!(cpu.registers[10], 0b10101);
+        assert_eq!(cpu.fcsr.fflags(), 0b11111);
+        cpu.registers[11] = FCSR::NV.into();
+        cpu.execute(csr_op(csr::CSR_FFLAGS, 11, CSRRC, 10)).unwrap();
+        assert_eq!(cpu.fcsr.fflags(), 0b01111);
+        cpu.execute(csr_op(csr::CSR_FFLAGS, 0b11111, CSRRCI, 10))
+            .unwrap();
+        assert_eq!(cpu.fcsr.fflags(), 0);
+        assert_eq!(read(&mut cpu, csr::CSR_FRM), 0b100);
+
+        // NOTE: Reserved rounding modes aren't stored
+        cpu.execute(csr_op(csr::CSR_FRM, 0b101, CSRRWI, 0)).unwrap();
+        assert_eq!(read(&mut cpu, csr::CSR_FRM), 0b100);
+    }
+
+    #[test]
+    fn test_read_only_csrs() {
+        let mut cpu = RV64GC::new();
+        cpu.registers[1] = 1;
+        cpu.registers[10] = 0xdead;
+
+        // NOTE: Only an attempt to write traps, csrrs/csrrc with x0 and the immediate forms
+        // with 0 are plain reads
+        for addr in [csr::CSR_CYCLE, csr::CSR_TIME, csr::CSR_INSTRET] {
+            for ins in [
+                csr_op(addr, 1, CSRRW, 10),
+                csr_op(addr, 1, CSRRW, 0),
+                csr_op(addr, 1, CSRRS, 10),
+                csr_op(addr, 1, CSRRC, 10),
+                csr_op(addr, 0, CSRRWI, 10),
+                csr_op(addr, 1, CSRRSI, 10),
+                csr_op(addr, 1, CSRRCI, 10),
+            ] {
+                assert_eq!(cpu.execute(ins), Err(Exception::IllegalInstruction));
+                assert_eq!((cpu.registers[10], cpu.registers[Pc]), (0xdead, 0));
+            }
+        }
+
+        for ins in [
+            csr_op(csr::CSR_INSTRET, 0, CSRRS, 10),
+            csr_op(csr::CSR_INSTRET, 0, CSRRC, 10),
+            csr_op(csr::CSR_INSTRET, 0, CSRRSI, 10),
+            csr_op(csr::CSR_INSTRET, 0, CSRRCI, 10),
+        ] {
+            cpu.execute(ins).unwrap();
+        }
+        assert_eq!(cpu.registers[10], 3);
+
+        // NOTE: So is one that doesn't exist, or a vector CSR without V
+        assert_eq!(
+            cpu.execute(csr_op(0x7c0, 0, CSRRS, 10)),
+            Err(Exception::IllegalInstruction)
+        );
+        assert_eq!(
+            cpu.execute(csr_op(csr::CSR_VL, 0, CSRRS, 10)),
+            Err(Exception::IllegalInstruction)
+        );
+    }
+
+    #[test]
+    fn test_traps() {
+        let program: [u32; 6] = [
+            0x7c00_2573, // csrr x10, 0x7c0
+            0x0005_b503, // ld x10, 0(x11)
+            0x00a5_b023, // sd x10, 0(x11)
+            0x1005_b52f, // lr.d x10, (x11)
+            0x00c5_b52f, // amoadd.d x10, x12, (x11)
+            0x0010_0073, // ebreak
+        ];
+        let mut cpu = RV64GC::new();
+        cpu.load_bin(program.iter().flat_map(|ins| ins.to_le_bytes()).collect());
+        cpu.registers[10] = 0xdead;
+
+        let unmapped = 0x8000_0000;
+        let cases = [
+            (
+                0,
+                Trap::IllegalInstruction {
+                    pc: 0,
+                    bits: program[0],
+                },
+            ),
+            (
+                unmapped,
+                Trap::LoadAccessFault {
+                    pc: 4,
+                    addr: unmapped,
+                },
+            ),
+            (
+                unmapped,
+                Trap::StoreAccessFault {
+                    pc: 8,
+                    addr: unmapped,
+                },
+            ),
+            (4, Trap::LoadAddressMisaligned { pc: 12, addr: 4 }),
+            (4, Trap::StoreAddressMisaligned { pc: 16, addr: 4 }),
+            (0, Trap::Breakpoint { pc: 20 }),
+            (
+                0,
+                Trap::InstructionAccessFault {
+                    pc: PAGE_SIZE,
+                    addr: PAGE_SIZE,
+                },
+            ),
+        ];
+
+        // NOTE: A trap leaves pc on the instruction and rd as it was
+        for (x11, trap) in cases {
+            cpu.registers[Pc] = trap.pc();
+            cpu.registers[11] = x11;
+            assert_eq!(cpu.step(), Err(trap));
+            assert_eq!((cpu.registers[Pc], cpu.registers[10]), (trap.pc(), 0xdead));
+        }
+    }
+
+    #[test]
+    fn test_run() {
+        for mode in [ExecutionMode::Step, ExecutionMode::Blocks] {
+            let mut cpu = exit_42();
+            cpu.execution_mode = mode;
+
+            // NOTE: Running out of budget leaves the guest where it can carry on
+            assert_eq!(cpu.run(Some(2)), ExitReason::BudgetExhausted, "{mode:?}");
+            assert_eq!(cpu.registers[Pc], 8);
+            assert_eq!((cpu.registers[A0], cpu.registers[A7]), (42, 93));
+            assert_eq!(cpu.run(Some(0)), ExitReason::BudgetExhausted);
+            assert_eq!(cpu.registers[Pc], 8);
+
+            assert_eq!(cpu.run(Some(100)), ExitReason::Exited(42));
+            assert_eq!(cpu.exit_code, Some(42));
+            assert_eq!(cpu.start(), ExitReason::Exited(42));
+
+            let mut cpu = exit_42();
+            cpu.execution_mode = mode;
+            cpu.should_quit = true;
+            assert_eq!(cpu.start(), ExitReason::Stopped);
+
+            // NOTE: ebreak stops the guest without it being a trap
+            let mut cpu = RV64GC::new();
+            cpu.execution_mode = mode;
+            cpu.load_bin([0x13, 0, 0, 0, 0x73, 0, 0x10, 0].to_vec()); // nop, ebreak
+            assert_eq!(cpu.start(), ExitReason::Breakpoint { pc: 4 });
+
+            cpu.registers[Pc] = PAGE_SIZE;
+            assert_eq!(
+                cpu.start(),
+                ExitReason::Trapped(Trap::InstructionAccessFault {
+                    pc: PAGE_SIZE,
+                    addr: PAGE_SIZE
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn test_page_permissions() {
+        let sd = 0x00a5_b023u32.to_le_bytes(); // sd x10, 0(x11)
+        let mut cpu = RV64GC::new();
+        cpu.load_bin(sd.to_vec());
+
+        let (rx, rw) = (0x10000, 0x11000);
+        let flags = (goblin::elf::program_header::PF_R | goblin::elf::program_header::PF_X) as u64;
+        cpu.ram
+            .add_region(MemoryRegion::new_with_flags(rx, PAGE_SIZE, vec![], flags))
+            .unwrap();
+        cpu.ram
+            .add_region(MemoryRegion::new(rw, PAGE_SIZE, sd.to_vec()))
+            .unwrap();
+
+        cpu.registers[11] = rx + 8;
+        assert_eq!(
+            cpu.step(),
+            Err(Trap::StoreAccessFault {
+                pc: 0,
+                addr: rx + 8
+            })
+        );
+        cpu.registers[Pc] = rw;
+        assert_eq!(
+            cpu.step(),
+            Err(Trap::InstructionAccessFault { pc: rw, addr: rw })
+        );
+
+        let mprotect = |cpu: &mut RV64GC, addr, prot| {
+            cpu.registers[A0] = addr;
+            cpu.registers[A1] = PAGE_SIZE;
+            cpu.registers[A2] = prot;
+            super::mprotect(cpu);
+            cpu.registers[A0]
+        };
+
+        // NOTE: PROT_READ | PROT_WRITE, and PROT_READ | PROT_EXEC. The call clobbers x10/x11
+        assert_eq!(mprotect(&mut cpu, rx, 0b011), 0);
+        assert_eq!(mprotect(&mut cpu, rw, 0b101), 0);
+        cpu.registers[Pc] = 0;
+        cpu.registers[10] = 7;
+        cpu.registers[11] = rx + 8;
+        assert_eq!(cpu.step(), Ok(StepOutcome::Continue));
+        assert_eq!(cpu.ram.read_doubleword(rx + 8), Ok(7));
+
+        cpu.registers[Pc] = rw;
+        cpu.registers[11] = rx + 16;
+        assert_eq!(cpu.step(), Ok(StepOutcome::Continue));
+        assert_eq!(cpu.ram.read_doubleword(rx + 16), Ok(7));
+
+        // NOTE: EINVAL for an unaligned address, ENOMEM for one that isn't mapped
+        assert_eq!(mprotect(&mut cpu, rw + 8, 0b011), -22i64 as u64);
+        assert_eq!(mprotect(&mut cpu, 0x20000, 0b011), -12i64 as u64);
+    }
+
+    /// A RISC-V ELF with one R-X `PT_LOAD` linked at `link` that runs [`EXIT_42`], and a
+    /// `PT_INTERP` naming `interp` if there is one
+    fn elf(e_type: u16, link: u64, interp: Option<&str>) -> Vec<u8> {
+        use goblin::elf::program_header::{PF_R, PF_X, PT_INTERP, PT_LOAD};
+
+        let (phoff, phentsize, code) = (64, 56, 0x100);
+        let phnum = 1 + u16::from(interp.is_some());
+        let interp_off = phoff + u64::from(phnum * phentsize);
+
+        let mut bin = vec![0x7f, b'E', b'L', b'F', 2, 1, 1];
+        bin.resize(16, 0);
+        bin.extend(e_type.to_le_bytes());
+        bin.extend(goblin::elf::header::EM_RISCV.to_le_bytes());
+        bin.extend(1u32.to_le_bytes());
+        for word in [link.wrapping_add(code), phoff, 0] {
+            bin.extend(word.to_le_bytes());
+        }
+        bin.extend(0u32.to_le_bytes());
+        for half in [64, phentsize, phnum, 64, 0, 0] {
+            bin.extend(half.to_le_bytes());
+        }
+
+        let len = code + 4 * EXIT_42.len() as u64;
+        let mut phdr = |p_type: u32, p_flags: u32, offset: u64, size: u64| {
+            bin.extend(p_type.to_le_bytes());
+            bin.extend(p_flags.to_le_bytes());
+            let vaddr = link.wrapping_add(offset);
+            for word in [offset, vaddr, vaddr, size, size, 0x1000] {
+                bin.extend(word.to_le_bytes());
+            }
+        };
+        phdr(PT_LOAD, PF_R | PF_X, 0, len);
+        if let Some(interp) = interp {
+            phdr(PT_INTERP, PF_R, interp_off, interp.len() as u64 + 1);
+            bin.extend(interp.bytes().chain([0]));
+        }
+
+        bin.resize(code as usize, 0);
+        bin.extend(EXIT_42.iter().flat_map(|ins| ins.to_le_bytes()));
+        bin
+    }
+
+    #[test]
+    fn test_stack_pages() {
+        let mut cpu = RV64GC::new();
+        let bin = elf(goblin::elf::header::ET_EXEC, 0x10000, None);
+        cpu.load_elf(bin, LaunchConfig::new(vec!["prog".into()]))
+            .unwrap();
+
+        // NOTE: Only the page argv, envp and auxv were written to is backed
+        let config = &cpu.launch_config;
+        let stack_start = config.stack_top - config.stack_size;
+        assert_eq!(cpu.ram.backed_pages(stack_start, config.stack_top).len(), 1);
+        assert_eq!(cpu.ram.read_doubleword(cpu.registers[Sp]), Ok(1));
+        assert_eq!(cpu.ram.read_byte(stack_start), Ok(0));
+
+        // NOTE: A stack bigger than the space below its top is an error, not a wrapped address
+        let mut cpu = RV64GC::new();
+        let mut config = LaunchConfig::new(vec!["prog".into()]);
+        (config.stack_top, config.stack_size) = (0x10_0000, 0x20_0000);
+        let bin = elf(goblin::elf::header::ET_EXEC, 0x10000, None);
+        assert_eq!(
+            cpu.load_elf(bin, config).unwrap_err().to_string(),
+            "A 2097152 byte stack doesn't fit below 0x100000"
+        );
+    }
+
+    /// The auxv the guest starts with, past argv and envp on the stack
+    fn auxv(cpu: &RV64GC) -> Vec<(u64, u64)> {
+        let word = |i: u64| cpu.ram.read_doubleword(cpu.registers[Sp] + 8 * i).unwrap();
+        let mut i = word(0) + 2;
+        while word(i) != 0 {
+            i += 1;
+        }
+
+        (i + 1..)
+            .step_by(2)
+            .map(|i| (word(i), word(i + 1)))
+            .take_while(|&(key, _)| key != AT_NULL)
+            .collect()
+    }
+
+    #[test]
+    fn test_load_elf() {
+        use goblin::elf::header::{ET_DYN, ET_EXEC};
+        use goblin::elf::program_header::PT_LOAD;
+
+        // NOTE: A PIE linked at 0 is moved to pie_base, entry and program headers with it
+        let mut cpu = RV64GC::new();
+        let mut config = LaunchConfig::new(vec!["pie".into()]);
+        config.pie_base = 0x4000_0000;
+        cpu.load_elf(elf(ET_DYN, 0, None), config).unwrap();
+        assert_eq!(cpu.load_bias, 0x4000_0000);
+        assert_eq!(cpu.registers[Pc], 0x4000_0100);
+        assert_eq!(cpu.brk_start, 0x4000_1000);
+
+        let auxv = auxv(&cpu);
+        let at = |key| auxv.iter().find(|&&(k, _)| k == key).map(|&(_, v)| v);
+        assert_eq!(at(AT_ENTRY), Some(0x4000_0100));
+        assert_eq!(at(AT_PHDR), Some(0x4000_0040));
+        assert_eq!(at(AT_PHNUM), Some(1));
+        assert_eq!(at(AT_BASE), None);
+        assert_eq!(cpu.ram.read_word(0x4000_0040), Ok(PT_LOAD));
+        assert_eq!(cpu.run(Some(10)), ExitReason::Exited(42));
+
+        // NOTE: Segments reaching past the last page are an error, not an overflow
+        let mut cpu = RV64GC::new();
+        let config = LaunchConfig::new(vec!["top".into()]);
+        assert_eq!(
+            cpu.load_elf(elf(ET_EXEC, u64::MAX - 0xfff, None), config)
+                .unwrap_err()
+                .to_string(),
+            "Bad PT_LOAD segment at 0xfffffffffffff000"
+        );
+    }
+
+    #[test]
+    fn test_missing_interpreter() {
+        let interp = "/lib/ld-riscvm-missing.so.1";
+        let bin = elf(goblin::elf::header::ET_DYN, 0, Some(interp));
+
+        // NOTE: Reported up front, with or without a sysroot to look in
+        let sysroot = std::env::temp_dir();
+        for sysroot in [None, Some(sysroot.clone())] {
+            let mut cpu = RV64GC::new();
+            let mut config = LaunchConfig::new(vec!["dynamic".into()]);
+            config.sysroot = sysroot;
+            let path = config.resolve_path(interp);
+            assert_eq!(
+                cpu.load_elf(bin.clone(), config).unwrap_err().to_string(),
+                format!(
+                    "The interpreter {} wasn't found, dynamically linked programs need a \
+                     sysroot that has it",
+                    path.display()
+                )
+            );
+        }
+    }
+}
@@ -1,86 +1,192 @@
+use std::time::Instant;
+
+use tracing::warn;
+
 use crate::exception::Exception;
+use crate::fcsr::{RoundingMode, FCSR};
+use crate::isa::Isa;
+use crate::vector::VectorState;
+
+// NOTE: Floating-point CSRs
+pub const CSR_FFLAGS: u16 = 0x001;
+pub const CSR_FRM: u16 = 0x002;
+pub const CSR_FCSR: u16 = 0x003;
+
+// NOTE: Vector CSRs, only there when the hart has V
+pub const CSR_VSTART: u16 = 0x008;
+pub const CSR_VXSAT: u16 = 0x009;
+pub const CSR_VXRM: u16 = 0x00A;
+pub const CSR_VCSR: u16 = 0x00F;
+pub const CSR_VL: u16 = 0xC20;
+pub const CSR_VTYPE: u16 = 0xC21;
+pub const CSR_VLENB: u16 = 0xC22;
+
+// NOTE: Unprivileged counters/timers
+pub const CSR_CYCLE: u16 = 0xC00;
+pub const CSR_TIME: u16 = 0xC01;
+pub const CSR_INSTRET: u16 = 0xC02;
+pub const CSR_CYCLEH: u16 = 0xC80;
+pub const CSR_TIMEH: u16 = 0xC81;
+pub const CSR_INSTRETH: u16 = 0xC82;
 
-const CSR_SSTATUS: u64 = 0x100;
-const CSR_SIE: u64 = 0x104;
-const CSR_STVEC: u64 = 0x105;
-const CSR_SSCRATCH: u64 = 0x140;
-const CSR_SEPC: u64 = 0x141;
-const CSR_SCAUSE: u64 = 0x142;
-const CSR_STVAL: u64 = 0x143;
-const CSR_SATP: u64 = 0x180;
+/// Frequency of the `time` CSR, matches the timebase most RISC-V Linux boards report
+pub const TIMEBASE_FREQ: u64 = 10_000_000;
 
+/// The user-level CSR file.
+///
+/// `fflags`, `frm` and `fcsr` are views into the hart's [`FCSR`], and the vector CSRs into
+/// its [`VectorState`], so they are passed in rather than stored here.
+#[derive(Debug, Clone)]
 pub struct Csr {
-    satp: u64,
-    sstatus: u64,
-    stvec: u64,
-    sepc: u64,
-    scause: u64,
-    stval: u64,
-    sscratch: u64,
+    cycle: u64,
+    instret: u64,
+    boot_time: Instant,
+}
+
+impl Default for Csr {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Csr {
     pub fn new() -> Csr {
         Csr {
-            satp: 0,
-            sstatus: 0,
-            stvec: 0,
-            sepc: 0,
-            scause: 0,
-            stval: 0,
-            sscratch: 0,
+            cycle: 0,
+            instret: 0,
+            boot_time: Instant::now(),
         }
     }
 
-    pub fn read(&self, addr: u64) -> u64 {
+    /// Bumps `cycle` and `instret`, called once per retired instruction
+    pub fn retire(&mut self) {
+        self.cycle = self.cycle.wrapping_add(1);
+        self.instret = self.instret.wrapping_add(1);
+    }
+
+    pub fn instret(&self) -> u64 {
+        self.instret
+    }
+
+    pub fn cycle(&self) -> u64 {
+        self.cycle
+    }
+
+    /// Sets `cycle` and `instret`, when restoring a snapshot
+    pub(crate) fn set_counters(&mut self, cycle: u64, instret: u64) {
+        self.cycle = cycle;
+        self.instret = instret;
+    }
+
+    fn time(&self) -> u64 {
+        let elapsed = self.boot_time.elapsed();
+        (elapsed.as_nanos() * TIMEBASE_FREQ as u128 / 1_000_000_000) as u64
+    }
+
+    /// CSRs with `csr[11:10] == 0b11` are read-only
+    pub const fn is_read_only(addr: u16) -> bool {
+        (addr >> 10) & 0b11 == 0b11
+    }
+
+    /// The vector CSRs need V
+    fn is_vector(addr: u16) -> bool {
+        matches!(
+            addr,
+            CSR_VSTART | CSR_VXSAT | CSR_VXRM | CSR_VCSR | CSR_VL | CSR_VTYPE | CSR_VLENB
+        )
+    }
+
+    pub fn read(
+        &self,
+        addr: u16,
+        fcsr: &FCSR,
+        vector: &VectorState,
+        isa: &Isa,
+    ) -> Result<u64, Exception> {
+        if Csr::is_vector(addr) && !isa.v {
+            return Err(Exception::IllegalInstruction);
+        }
+
         match addr {
-            CSR_SSTATUS => self.sstatus,
-            CSR_STVEC => self.stvec,
-            CSR_SSCRATCH => self.sscratch,
-            CSR_SEPC => self.sepc,
-            CSR_SCAUSE => self.scause,
-            CSR_STVAL => self.stval,
-            CSR_SATP => self.satp,
-
-            _ => todo!(),
+            CSR_FFLAGS => Ok(fcsr.fflags().into()),
+            CSR_FRM => Ok(u8::from(fcsr.frm).into()),
+            CSR_FCSR => Ok(fcsr.read().into()),
+
+            CSR_VSTART => Ok(vector.vstart),
+            CSR_VXSAT => Ok(vector.vxsat.into()),
+            CSR_VXRM => Ok(vector.vxrm.into()),
+            CSR_VCSR => Ok(vector.vcsr()),
+            CSR_VL => Ok(vector.vl),
+            CSR_VTYPE => Ok(vector.vtype),
+            CSR_VLENB => Ok(isa.vlenb() as u64),
+
+            CSR_CYCLE => Ok(self.cycle),
+            CSR_TIME => Ok(self.time()),
+            CSR_INSTRET => Ok(self.instret),
+
+            // NOTE: The upper-half counters only exist on RV32
+            CSR_CYCLEH | CSR_TIMEH | CSR_INSTRETH => Err(Exception::IllegalInstruction),
+
+            _ => Err(Exception::IllegalInstruction),
         }
     }
 
-    pub fn write(&mut self, addr: u64, value: u64) -> Result<(), Exception> {
+    pub fn write(
+        &mut self,
+        addr: u16,
+        value: u64,
+        fcsr: &mut FCSR,
+        vector: &mut VectorState,
+        isa: &Isa,
+    ) -> Result<(), Exception> {
+        if Csr::is_read_only(addr) || (Csr::is_vector(addr) && !isa.v) {
+            return Err(Exception::IllegalInstruction);
+        }
+
         match addr {
-            CSR_SSTATUS => {
-                todo!();
-                // Handle writeable bits; preserve read-only bits
-                // let mask = /* mask of writeable bits */
-                // self.sstatus = (self.sstatus & !mask) | (value & mask);
-                // Ok(())
+            CSR_FFLAGS => {
+                fcsr.set_fflags(value as u8);
+                Ok(())
             }
-            CSR_STVEC => {
-                self.stvec = value;
+            CSR_FRM => {
+                Csr::write_frm(fcsr, value as u8);
                 Ok(())
             }
-            CSR_SSCRATCH => {
-                self.sscratch = value;
+            CSR_FCSR => {
+                fcsr.set_fflags(value as u8);
+                Csr::write_frm(fcsr, (value >> 5) as u8);
                 Ok(())
             }
-            CSR_SEPC => {
-                self.sepc = value;
+
+            // NOTE: vstart only needs enough bits to index a byte of a register
+            CSR_VSTART => {
+                vector.vstart = value & (u64::from(isa.vlen) - 1);
                 Ok(())
             }
-            CSR_SCAUSE => {
-                self.scause = value;
+            CSR_VXSAT => {
+                vector.vxsat = value & 1 == 1;
                 Ok(())
             }
-            CSR_STVAL => {
-                self.stval = value;
+            CSR_VXRM => {
+                vector.vxrm = value as u8 & 0b11;
                 Ok(())
             }
-            CSR_SATP => {
-                self.satp = value;
+            CSR_VCSR => {
+                vector.set_vcsr(value);
                 Ok(())
             }
-            // ... handle other CSRs
+
             _ => Err(Exception::IllegalInstruction),
         }
     }
+
+    fn write_frm(fcsr: &mut FCSR, value: u8) {
+        match RoundingMode::from_bits(value & 0b111) {
+            Some(rm) => fcsr.frm = rm,
+            None => warn!(
+                "ignoring write of reserved rounding mode {:03b} to frm",
+                value & 0b111
+            ),
+        }
+    }
 }
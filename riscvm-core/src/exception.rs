@@ -1,7 +1,177 @@
 use thiserror::Error;
 
-#[derive(Debug, Error)]
+use crate::ram::MemoryError;
+use crate::syscalls::syscall_name;
+
+/// A synchronous exception raised while executing a single instruction.
+///
+/// Instructions don't know where they live, so the pc (and the raw instruction bits)
+/// are attached later, when the exception is turned into a [`Trap`].
+#[derive(Debug, Error, Clone, Copy, PartialEq, Eq)]
 pub enum Exception {
     #[error("Illegal Instruction")]
     IllegalInstruction,
+    #[error("Instruction address misaligned: 0x{0:016x}")]
+    InstructionAddressMisaligned(u64),
+    #[error("Instruction access fault: 0x{0:016x}")]
+    InstructionAccessFault(u64),
+    #[error("Load address misaligned: 0x{0:016x}")]
+    LoadAddressMisaligned(u64),
+    #[error("Load access fault: 0x{0:016x}")]
+    LoadAccessFault(u64),
+    #[error("Store address misaligned: 0x{0:016x}")]
+    StoreAddressMisaligned(u64),
+    #[error("Store access fault: 0x{0:016x}")]
+    StoreAccessFault(u64),
+    #[error("Breakpoint")]
+    Breakpoint,
+    #[error("Unsupported syscall: {0} ({name})", name = syscall_name(*.0).unwrap_or("unknown"))]
+    UnsupportedSyscall(u64),
+}
+
+impl Exception {
+    /// Maps a failed instruction fetch, meant to be used with `map_err`
+    pub fn fetch(err: MemoryError) -> Exception {
+        Exception::InstructionAccessFault(err.addr())
+    }
+
+    /// Maps a failed load, meant to be used with `map_err`
+    pub fn load(err: MemoryError) -> Exception {
+        Exception::LoadAccessFault(err.addr())
+    }
+
+    /// Maps a failed store (or AMO), meant to be used with `map_err`
+    pub fn store(err: MemoryError) -> Exception {
+        Exception::StoreAccessFault(err.addr())
+    }
+}
+
+/// Why the guest stopped executing, handed back to the embedder by `RV64GC::step`.
+#[derive(Debug, Error, Clone, Copy, PartialEq, Eq)]
+pub enum Trap {
+    #[error("Instruction address misaligned: 0x{addr:016x} (pc: 0x{pc:016x})")]
+    InstructionAddressMisaligned { pc: u64, addr: u64 },
+    #[error("Instruction access fault: 0x{addr:016x} (pc: 0x{pc:016x})")]
+    InstructionAccessFault { pc: u64, addr: u64 },
+    #[error("Load address misaligned: 0x{addr:016x} (pc: 0x{pc:016x})")]
+    LoadAddressMisaligned { pc: u64, addr: u64 },
+    #[error("Load access fault: 0x{addr:016x} (pc: 0x{pc:016x})")]
+    LoadAccessFault { pc: u64, addr: u64 },
+    #[error("Store address misaligned: 0x{addr:016x} (pc: 0x{pc:016x})")]
+    StoreAddressMisaligned { pc: u64, addr: u64 },
+    #[error("Store access fault: 0x{addr:016x} (pc: 0x{pc:016x})")]
+    StoreAccessFault { pc: u64, addr: u64 },
+    #[error("Illegal instruction: 0x{bits:08x} (pc: 0x{pc:016x})")]
+    IllegalInstruction { pc: u64, bits: u32 },
+    #[error("Breakpoint (pc: 0x{pc:016x})")]
+    Breakpoint { pc: u64 },
+    #[error(
+        "Unsupported syscall: {number} ({}) (pc: 0x{pc:016x})",
+        syscall_name(*number).unwrap_or("unknown")
+    )]
+    UnsupportedSyscall { pc: u64, number: u64 },
+}
+
+impl Trap {
+    pub fn from_exception(exception: Exception, pc: u64, bits: u32) -> Trap {
+        match exception {
+            Exception::IllegalInstruction => Trap::IllegalInstruction { pc, bits },
+            Exception::InstructionAddressMisaligned(addr) => {
+                Trap::InstructionAddressMisaligned { pc, addr }
+            }
+            Exception::InstructionAccessFault(addr) => Trap::InstructionAccessFault { pc, addr },
+            Exception::LoadAddressMisaligned(addr) => Trap::LoadAddressMisaligned { pc, addr },
+            Exception::LoadAccessFault(addr) => Trap::LoadAccessFault { pc, addr },
+            Exception::StoreAddressMisaligned(addr) => Trap::StoreAddressMisaligned { pc, addr },
+            Exception::StoreAccessFault(addr) => Trap::StoreAccessFault { pc, addr },
+            Exception::Breakpoint => Trap::Breakpoint { pc },
+            Exception::UnsupportedSyscall(number) => Trap::UnsupportedSyscall { pc, number },
+        }
+    }
+
+    /// The pc of the instruction that trapped
+    pub fn pc(&self) -> u64 {
+        match *self {
+            Trap::InstructionAddressMisaligned { pc, .. }
+            | Trap::InstructionAccessFault { pc, .. }
+            | Trap::LoadAddressMisaligned { pc, .. }
+            | Trap::LoadAccessFault { pc, .. }
+            | Trap::StoreAddressMisaligned { pc, .. }
+            | Trap::StoreAccessFault { pc, .. }
+            | Trap::IllegalInstruction { pc, .. }
+            | Trap::Breakpoint { pc }
+            | Trap::UnsupportedSyscall { pc, .. } => pc,
+        }
+    }
+
+    /// The faulting address for access faults and misaligned accesses
+    pub fn fault_addr(&self) -> Option<u64> {
+        match *self {
+            Trap::InstructionAddressMisaligned { addr, .. }
+            | Trap::InstructionAccessFault { addr, .. }
+            | Trap::LoadAddressMisaligned { addr, .. }
+            | Trap::LoadAccessFault { addr, .. }
+            | Trap::StoreAddressMisaligned { addr, .. }
+            | Trap::StoreAccessFault { addr, .. } => Some(addr),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_exception() {
+        let (pc, bits) = (0x1000, 0x0005_b503);
+        let cases = [
+            (
+                Exception::IllegalInstruction,
+                Trap::IllegalInstruction { pc, bits },
+                None,
+            ),
+            (
+                Exception::InstructionAccessFault(0x2000),
+                Trap::InstructionAccessFault { pc, addr: 0x2000 },
+                Some(0x2000),
+            ),
+            (
+                Exception::LoadAddressMisaligned(0x3004),
+                Trap::LoadAddressMisaligned { pc, addr: 0x3004 },
+                Some(0x3004),
+            ),
+            (
+                Exception::LoadAccessFault(0x4000),
+                Trap::LoadAccessFault { pc, addr: 0x4000 },
+                Some(0x4000),
+            ),
+            (
+                Exception::StoreAddressMisaligned(0x5002),
+                Trap::StoreAddressMisaligned { pc, addr: 0x5002 },
+                Some(0x5002),
+            ),
+            (
+                Exception::StoreAccessFault(0x6000),
+                Trap::StoreAccessFault { pc, addr: 0x6000 },
+                Some(0x6000),
+            ),
+            (Exception::Breakpoint, Trap::Breakpoint { pc }, None),
+            (
+                Exception::UnsupportedSyscall(1000),
+                Trap::UnsupportedSyscall { pc, number: 1000 },
+                None,
+            ),
+        ];
+
+        for (exception, trap, addr) in cases {
+            assert_eq!(Trap::from_exception(exception, pc, bits), trap);
+            assert_eq!((trap.pc(), trap.fault_addr()), (pc, addr), "{trap}");
+        }
+
+        assert_eq!(
+            Trap::IllegalInstruction { pc, bits }.to_string(),
+            "Illegal instruction: 0x0005b503 (pc: 0x0000000000001000)"
+        );
+    }
 }
@@ -1,9 +1,15 @@
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct FCSR {
     pub frm: RoundingMode,
     fflags: u8,
 }
 
+impl Default for FCSR {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl FCSR {
     pub fn new() -> Self {
         FCSR {
@@ -22,21 +28,31 @@ impl FCSR {
         self.fflags |= flag;
     }
 
-    pub fn handle_exceptions(&mut self, exact: f64, rounded: f64) {
-        if exact.is_nan() {
-            self.set_flag(FCSR::NV);
-        }
-        if exact.is_infinite() {
-            self.set_flag(FCSR::OF);
-        }
-        if (rounded - exact).abs() > 0.0 {
-            self.set_flag(FCSR::NX);
+    pub fn fflags(&self) -> u8 {
+        self.fflags
+    }
+
+    pub fn set_fflags(&mut self, fflags: u8) {
+        self.fflags = fflags & 0x1F;
+    }
+
+    /// The rounding mode an instruction's `rm` field selects, `frm` for the dynamic one.
+    /// `None` for the reserved encodings, which make the instruction illegal
+    pub fn rounding_mode(&self, rm: u8) -> Option<RoundingMode> {
+        match rm {
+            0b111 => Some(self.frm),
+            rm => RoundingMode::from_bits(rm),
         }
     }
+
+    /// Returns the full `fcsr` value, `frm` in bits 7:5 and `fflags` in bits 4:0
+    pub fn read(&self) -> u32 {
+        u32::from(u8::from(self.frm)) << 5 | u32::from(self.fflags)
+    }
 }
 
 /// Represents the RISC-V floating-point rounding modes.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum RoundingMode {
     Rne, // Round to Nearest, ties to Even
     Rtz, // Round Toward Zero
@@ -45,6 +61,33 @@ pub enum RoundingMode {
     Rmm, // Round to Nearest, ties to Max Magnitude
 }
 
+impl RoundingMode {
+    /// Like the `From<u8>` impl, but returns `None` for the reserved encodings
+    pub fn from_bits(value: u8) -> Option<RoundingMode> {
+        use RoundingMode::*;
+        match value {
+            0b000 => Some(Rne),
+            0b001 => Some(Rtz),
+            0b010 => Some(Rdn),
+            0b011 => Some(Rup),
+            0b100 => Some(Rmm),
+            _ => None,
+        }
+    }
+}
+
+impl From<RoundingMode> for u8 {
+    fn from(value: RoundingMode) -> Self {
+        match value {
+            RoundingMode::Rne => 0b000,
+            RoundingMode::Rtz => 0b001,
+            RoundingMode::Rdn => 0b010,
+            RoundingMode::Rup => 0b011,
+            RoundingMode::Rmm => 0b100,
+        }
+    }
+}
+
 impl From<&u8> for RoundingMode {
     fn from(value: &u8) -> Self {
         use RoundingMode::*;
@@ -73,113 +116,179 @@ impl From<u8> for RoundingMode {
     }
 }
 
-fn fround_rtz(value: f32) -> f32 {
-    value.trunc()
-}
-
-fn fround_rdn(value: f32) -> f32 {
-    value.floor()
-}
-
-fn fround_rup(value: f32) -> f32 {
-    value.ceil()
-}
+/// `fclass`, a one-hot mask of what kind of value it is
+pub fn classify<F: FloatExtends>(value: F) -> u32 {
+    let negative = value.is_sign_negative();
 
-fn fround_rmm(value: f32) -> f32 {
-    let rounded = value.round();
-    let frac_part = value.fract();
-
-    if frac_part.abs() == 0.5 {
-        // Tie case: round away from zero
-        if value > 0.0 {
-            rounded + 1.0
+    let bit = if value.is_nan() {
+        if value.is_snan() {
+            8
+        } else {
+            9
+        }
+    } else if value.is_infinite() {
+        if negative {
+            0
         } else {
-            rounded - 1.0
+            7
         }
+    } else if value.is_zero() {
+        if negative {
+            3
+        } else {
+            4
+        }
+    } else if value.is_subnormal() {
+        if negative {
+            2
+        } else {
+            5
+        }
+    } else if negative {
+        1
     } else {
-        rounded
-    }
+        6
+    };
+
+    1 << bit
 }
 
-fn fround_rne(value: f32) -> f32 {
-    let rounded = value.round();
-    let frac_part = value.fract();
+/// `fmin` and `fmax` return the number if only one operand is a NaN, and order -0.0 below
+/// +0.0. Signaling NaNs are invalid
+pub fn min_max<F: FloatExtends>(a: F, b: F, max: bool, fcsr: &mut FCSR) -> F {
+    if a.is_snan() || b.is_snan() {
+        fcsr.set_flag(FCSR::NV);
+    }
 
-    if frac_part.abs() == 0.5 {
-        // Tie case: round to even
-        if rounded % 2.0 != 0.0 {
-            if value > 0.0 {
-                rounded - 1.0
+    match (a.is_nan(), b.is_nan()) {
+        (true, true) => F::CANONICAL_NAN,
+        (true, false) => b,
+        (false, true) => a,
+        _ if a.is_zero() && b.is_zero() => {
+            if a.is_sign_negative() != max {
+                a
             } else {
-                rounded + 1.0
+                b
             }
-        } else {
-            rounded
         }
-    } else {
-        rounded
+        _ if (a < b) != max => a,
+        _ => b,
     }
 }
 
-pub fn round_f32(value: f32, rounding_mode: RoundingMode) -> f32 {
-    match rounding_mode {
-        RoundingMode::Rne => value,
-        RoundingMode::Rtz => fround_rtz(value),
-        RoundingMode::Rdn => fround_rdn(value),
-        RoundingMode::Rup => fround_rup(value),
-        RoundingMode::Rmm => fround_rmm(value),
+/// Compares like `feq` (quiet, only signaling NaNs are invalid) or `flt`/`fle` (signaling,
+/// any NaN is invalid). NaNs are unordered
+pub fn compare<F: FloatExtends>(
+    a: F,
+    b: F,
+    signaling: bool,
+    fcsr: &mut FCSR,
+) -> Option<std::cmp::Ordering> {
+    if a.is_nan() || b.is_nan() {
+        if signaling || a.is_snan() || b.is_snan() {
+            fcsr.set_flag(FCSR::NV);
+        }
+        return None;
     }
+
+    a.partial_cmp(&b)
 }
 
-pub fn classify_f32(value: f32) -> u32 {
-    let mut result = 0u32;
+/// Rounds to an integral value, the way `rm` says to
+pub fn round_to_integral(value: f64, rm: RoundingMode) -> f64 {
+    match rm {
+        RoundingMode::Rne => value.round_ties_even(),
+        RoundingMode::Rtz => value.trunc(),
+        RoundingMode::Rdn => value.floor(),
+        RoundingMode::Rup => value.ceil(),
+        RoundingMode::Rmm => value.round(),
+    }
+}
 
+/// `fcvt` to an integer in `min..=max`. Out of range values saturate and NaNs become `max`,
+/// both invalid
+pub fn to_integer(value: f64, rm: RoundingMode, min: i128, max: i128, fcsr: &mut FCSR) -> i128 {
     if value.is_nan() {
-        if value.is_snan() {
-            result |= 1 << 8; // Signaling NaN
-        } else {
-            result |= 1 << 9; // Quiet NaN
-        }
-    } else if value.is_infinite() {
-        if value.is_sign_negative() {
-            result |= 1 << 0; // Negative infinity
-        } else {
-            result |= 1 << 7; // Positive infinity
-        }
-    } else if value == 0.0 {
-        if value.is_sign_negative() {
-            result |= 1 << 3; // Negative zero
-        } else {
-            result |= 1 << 4; // Positive zero
-        }
-    } else if value.is_subnormal() {
-        if value.is_sign_negative() {
-            result |= 1 << 2; // Negative subnormal
-        } else {
-            result |= 1 << 5; // Positive subnormal
-        }
-    } else {
-        // Normal number
-        if value.is_sign_negative() {
-            result |= 1 << 1; // Negative normal
-        } else {
-            result |= 1 << 6; // Positive normal
-        }
+        fcsr.set_flag(FCSR::NV);
+        return max;
+    }
+
+    let rounded = round_to_integral(value, rm);
+    // NOTE: The cast saturates, and an i128 fits every 64 bit integer
+    let int = rounded as i128;
+    if int < min || int > max {
+        fcsr.set_flag(FCSR::NV);
+        return int.clamp(min, max);
+    }
+
+    if rounded != value {
+        fcsr.set_flag(FCSR::NX);
     }
 
-    result
+    int
 }
 
-pub trait FloatExtends {
+/// What the F and D instructions need from `f32` and `f64`
+pub trait FloatExtends: Copy + PartialOrd {
+    /// The NaN operations with a NaN result produce
+    const CANONICAL_NAN: Self;
+    /// Widths of the exponent and fraction fields
+    const EXP_BITS: u32;
+    const FRAC_BITS: u32;
+
+    /// The raw bits, zero extended
+    fn to_raw(self) -> u64;
+    fn from_raw(bits: u64) -> Self;
+
     fn is_snan(&self) -> bool;
+    fn is_zero(&self) -> bool;
+    fn is_nan(self) -> bool;
+    fn is_infinite(self) -> bool;
+    fn is_subnormal(self) -> bool;
+    fn is_sign_negative(self) -> bool;
 }
 
-impl FloatExtends for f32 {
-    fn is_snan(&self) -> bool {
-        let bits = self.to_bits();
-        let exponent = (bits >> 23) & 0xFF;
-        let fraction = bits & 0x7FFFFF;
+macro_rules! float_extends {
+    ($float:ty, $bits:ty, $exp_bits:literal, $frac_bits:literal, $canonical_nan:literal) => {
+        impl FloatExtends for $float {
+            const CANONICAL_NAN: $float = <$float>::from_bits($canonical_nan);
+            const EXP_BITS: u32 = $exp_bits;
+            const FRAC_BITS: u32 = $frac_bits;
 
-        exponent == 0xFF && fraction != 0 && (fraction & (1 << 22)) == 0
-    }
+            fn to_raw(self) -> u64 {
+                self.to_bits().into()
+            }
+
+            fn from_raw(bits: u64) -> Self {
+                <$float>::from_bits(bits as $bits)
+            }
+
+            fn is_snan(&self) -> bool {
+                <$float>::is_nan(*self) && self.to_bits() >> ($frac_bits - 1) & 1 == 0
+            }
+
+            fn is_zero(&self) -> bool {
+                *self == 0.0
+            }
+
+            fn is_nan(self) -> bool {
+                <$float>::is_nan(self)
+            }
+
+            fn is_infinite(self) -> bool {
+                <$float>::is_infinite(self)
+            }
+
+            fn is_subnormal(self) -> bool {
+                <$float>::is_subnormal(self)
+            }
+
+            fn is_sign_negative(self) -> bool {
+                <$float>::is_sign_negative(self)
+            }
+        }
+    };
 }
+
+float_extends!(f32, u32, 8, 23, 0x7FC0_0000);
+float_extends!(f64, u64, 11, 52, 0x7FF8_0000_0000_0000);
@@ -0,0 +1,114 @@
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io;
+use std::io::Seek;
+use std::io::SeekFrom;
+use std::path::PathBuf;
+
+use crate::syscalls::reopen_options;
+
+/// 0, 1 and 2 always refer to the host's stdio
+const FIRST_GUEST_FD: i32 = 3;
+
+/// A file (or directory) the guest opened
+#[derive(Debug)]
+pub struct OpenFile {
+    pub file: File,
+    /// The guest path it was opened by, `*at` calls resolve relative paths against it
+    pub path: PathBuf,
+    /// Where it is on the host, and the `openat` flags it was opened with. Restoring a
+    /// snapshot (or forking) opens it again from these
+    pub host_path: PathBuf,
+    pub flags: u64,
+}
+
+impl OpenFile {
+    /// Opens `host_path` again like `openat` did (without creating or truncating it), with
+    /// an offset of its own starting at `offset`
+    pub(crate) fn reopen(
+        path: PathBuf,
+        host_path: PathBuf,
+        flags: u64,
+        offset: u64,
+    ) -> io::Result<OpenFile> {
+        let mut file = reopen_options(flags).open(&host_path)?;
+        file.seek(SeekFrom::Start(offset))?;
+
+        Ok(OpenFile {
+            file,
+            path,
+            host_path,
+            flags,
+        })
+    }
+
+    pub fn offset(&self) -> u64 {
+        (&self.file).stream_position().unwrap_or(0)
+    }
+}
+
+/// Files the guest has opened, indexed by the fd it was handed
+#[derive(Debug, Default)]
+pub struct FdTable {
+    files: BTreeMap<i32, OpenFile>,
+}
+
+impl FdTable {
+    pub fn new() -> FdTable {
+        FdTable {
+            files: BTreeMap::new(),
+        }
+    }
+
+    /// Stores `file` under the lowest free fd, like `open` does
+    pub fn insert(&mut self, file: OpenFile) -> i32 {
+        let mut fd = FIRST_GUEST_FD;
+        for used in self.files.keys() {
+            if *used != fd {
+                break;
+            }
+            fd += 1;
+        }
+
+        self.files.insert(fd, file);
+        fd
+    }
+
+    pub fn get(&self, fd: i32) -> Option<&OpenFile> {
+        self.files.get(&fd)
+    }
+
+    pub fn get_mut(&mut self, fd: i32) -> Option<&mut OpenFile> {
+        self.files.get_mut(&fd)
+    }
+
+    pub fn remove(&mut self, fd: i32) -> Option<OpenFile> {
+        self.files.remove(&fd)
+    }
+
+    /// Stores `file` under `fd`, replacing whatever was there
+    pub(crate) fn insert_at(&mut self, fd: i32, file: OpenFile) {
+        self.files.insert(fd, file);
+    }
+
+    /// Opens every file again, at the same offset but without sharing it
+    pub fn try_clone(&self) -> io::Result<FdTable> {
+        let mut files = BTreeMap::new();
+        for (&fd, open) in &self.files {
+            let copy = OpenFile::reopen(
+                open.path.clone(),
+                open.host_path.clone(),
+                open.flags,
+                open.offset(),
+            )
+            .map_err(|e| io::Error::new(e.kind(), format!("fd {fd}: {e}")))?;
+            files.insert(fd, copy);
+        }
+
+        Ok(FdTable { files })
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (i32, &OpenFile)> {
+        self.files.iter().map(|(fd, file)| (*fd, file))
+    }
+}
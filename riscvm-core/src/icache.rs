@@ -0,0 +1,170 @@
+use std::collections::HashMap;
+
+use crate::cpu::RV64GCInstruction;
+
+const PAGE_SHIFT: u64 = 12;
+
+/// Instructions are at least 2 byte aligned, so a page has this many possible pcs
+const SLOTS_PER_PAGE: usize = 1 << (PAGE_SHIFT - 1);
+
+type CachePage = [Option<(u32, RV64GCInstruction)>; SLOTS_PER_PAGE];
+
+/// Decoded instructions (and their raw bits), grouped by the page their pc is in.
+///
+/// Stale entries are dropped through [`DecodeCache::invalidate_page`] when code is written
+/// or unmapped, and all of them with [`DecodeCache::clear`] on `fence.i`.
+#[derive(Debug)]
+pub struct DecodeCache {
+    pages: Vec<Box<CachePage>>,
+    /// Page number to index in `pages`
+    index: HashMap<u64, usize>,
+    /// The last page looked up, straight line code stays on one page for a while
+    last: Option<(u64, usize)>,
+    enabled: bool,
+}
+
+impl Default for DecodeCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DecodeCache {
+    pub fn new() -> DecodeCache {
+        DecodeCache {
+            pages: Vec::new(),
+            index: HashMap::new(),
+            last: None,
+            enabled: true,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Turning the cache off makes every step decode again, which is only useful to compare
+    /// against
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        self.clear();
+    }
+
+    pub fn get(&mut self, pc: u64) -> Option<(u32, RV64GCInstruction)> {
+        let page = self.page_index(pc >> PAGE_SHIFT)?;
+        self.pages[page][Self::slot(pc)]
+    }
+
+    pub fn insert(&mut self, pc: u64, bits: u32, ins: RV64GCInstruction) {
+        if !self.enabled {
+            return;
+        }
+
+        let page_number = pc >> PAGE_SHIFT;
+        let page = match self.page_index(page_number) {
+            Some(page) => page,
+            None => {
+                self.pages.push(Box::new([None; SLOTS_PER_PAGE]));
+                let page = self.pages.len() - 1;
+                self.index.insert(page_number, page);
+                page
+            }
+        };
+
+        self.pages[page][Self::slot(pc)] = Some((bits, ins));
+    }
+
+    /// Forgets everything decoded from the page `addr` is in
+    pub fn invalidate_page(&mut self, addr: u64) {
+        let page_number = addr >> PAGE_SHIFT;
+        if let Some(page) = self.page_index(page_number) {
+            self.pages[page].fill(None);
+        }
+
+        // NOTE: A 4 byte instruction at the very end of the previous page reads 2 bytes of
+        // this one
+        if let Some(page) = page_number
+            .checked_sub(1)
+            .and_then(|prev| self.page_index(prev))
+        {
+            self.pages[page][SLOTS_PER_PAGE - 1] = None;
+        }
+    }
+
+    /// Forgets every page in `[start, start + len)`
+    pub fn invalidate_range(&mut self, start: u64, len: u64) {
+        if len == 0 {
+            return;
+        }
+
+        let first = start >> PAGE_SHIFT;
+        let last = start.saturating_add(len - 1) >> PAGE_SHIFT;
+        if last - first >= self.pages.len() as u64 {
+            self.clear();
+            return;
+        }
+
+        for page_number in first..=last {
+            self.invalidate_page(page_number << PAGE_SHIFT);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.pages.clear();
+        self.index.clear();
+        self.last = None;
+    }
+
+    fn page_index(&mut self, page_number: u64) -> Option<usize> {
+        if let Some((number, page)) = self.last {
+            if number == page_number {
+                return Some(page);
+            }
+        }
+
+        let page = *self.index.get(&page_number)?;
+        self.last = Some((page_number, page));
+        Some(page)
+    }
+
+    fn slot(pc: u64) -> usize {
+        ((pc & ((1 << PAGE_SHIFT) - 1)) >> 1) as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cpu::RV64GCRegAbiName::*;
+    use crate::cpu::{ExecutionMode, ExitReason, RV64GC};
+
+    #[rustfmt::skip]
+    const SELF_MODIFYING: [u32; 8] = [
+        0x0010_0513, // 0:  addi a0, zero, 1
+        0x0004_1a63, // 4:  bnez s0, 24
+        0x0010_0413, // 8:  addi s0, zero, 1
+        0x0060_2023, // 12: sw t1, 0(zero)
+        0x0000_100f, // 16: fence.i
+        0xfedf_f06f, // 20: j 0
+        0x05d0_0893, // 24: addi a7, zero, 93
+        0x0000_0073, // 28: ecall
+    ];
+
+    #[test]
+    fn test_self_modifying_code() {
+        for mode in [ExecutionMode::Step, ExecutionMode::Blocks] {
+            let mut cpu = RV64GC::new();
+            cpu.execution_mode = mode;
+            cpu.load_bin(
+                SELF_MODIFYING
+                    .iter()
+                    .flat_map(|ins| ins.to_le_bytes())
+                    .collect(),
+            );
+
+            // NOTE: The first pass decodes `addi a0, zero, 1` and replaces it, the second
+            // has to run what was stored instead
+            cpu.registers[T1] = 0x0020_0513; // addi a0, zero, 2
+            assert_eq!(cpu.run(Some(100)), ExitReason::Exited(2), "{mode:?}");
+        }
+    }
+}
@@ -0,0 +1,198 @@
+use std::str::FromStr;
+
+use thiserror::Error;
+
+use crate::cpu::RV64GCInstruction;
+use crate::vector::MAX_VLEN;
+
+/// The optional extensions the hart implements on top of RV64GC, which is always there.
+/// Instructions from a disabled extension decode as illegal, and `riscv_hwprobe` only
+/// reports the enabled ones
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Isa {
+    /// Address generation: `sh1add`, `add.uw`, `slli.uw` and friends
+    pub zba: bool,
+    /// Basic bit manipulation: `andn`, `clz`, `cpop`, `rev8`, `orc.b`, rotates, min/max...
+    pub zbb: bool,
+    /// Carry-less multiplication: `clmul`, `clmulh` and `clmulr`
+    pub zbc: bool,
+    /// Single bit instructions: `bset`, `bclr`, `binv`, `bext` and their immediate forms
+    pub zbs: bool,
+    /// The vector extension, see [`crate::vector`]
+    pub v: bool,
+    /// Bits in a vector register, a power of two from 128 to [`MAX_VLEN`]
+    pub vlen: u32,
+}
+
+/// VLEN unless a `zvl<N>b` extension asks for more, the smallest V allows
+pub const DEFAULT_VLEN: u32 = 128;
+
+impl Default for Isa {
+    fn default() -> Self {
+        Isa::RV64GC
+    }
+}
+
+/// Extensions an instruction can belong to, besides the base ISA
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Extension {
+    Zba,
+    Zbb,
+    Zbc,
+    Zbs,
+    V,
+}
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum IsaError {
+    #[error("ISA string {0:?} doesn't start with rv64gc(v) (or rv64imafdc(v))")]
+    Base(String),
+    #[error("Unknown extension {0:?}, known are zba, zbb, zbc, zbs and zvl<N>b")]
+    UnknownExtension(String),
+    #[error("VLEN in {0:?} has to be a power of two from 128 to 1024")]
+    Vlen(String),
+}
+
+impl Isa {
+    /// Plain RV64GC, no bit manipulation or vectors, the default
+    pub const RV64GC: Isa = Isa {
+        zba: false,
+        zbb: false,
+        zbc: false,
+        zbs: false,
+        v: false,
+        vlen: DEFAULT_VLEN,
+    };
+
+    /// RV64GC with every bit manipulation extension
+    pub const RV64GC_ZB: Isa = Isa {
+        zba: true,
+        zbb: true,
+        zbc: true,
+        zbs: true,
+        v: false,
+        vlen: DEFAULT_VLEN,
+    };
+
+    /// [`Isa::RV64GC_ZB`] with V
+    pub const RV64GCV_ZB: Isa = Isa {
+        v: true,
+        ..Isa::RV64GC_ZB
+    };
+
+    pub fn has(&self, extension: Extension) -> bool {
+        match extension {
+            Extension::Zba => self.zba,
+            Extension::Zbb => self.zbb,
+            Extension::Zbc => self.zbc,
+            Extension::Zbs => self.zbs,
+            Extension::V => self.v,
+        }
+    }
+
+    /// VLEN in bytes
+    pub fn vlenb(&self) -> usize {
+        self.vlen as usize / 8
+    }
+
+    /// Whether `ins` is in the base ISA or an enabled extension
+    pub fn supports(&self, ins: &RV64GCInstruction) -> bool {
+        ins.extension().is_none_or(|extension| self.has(extension))
+    }
+}
+
+/// Parses ISA strings like `rv64gc_zba_zbb` or `rv64gcv_zvl256b`, the extensions after the
+/// base can be in any order
+impl FromStr for Isa {
+    type Err = IsaError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lower = s.to_ascii_lowercase();
+        let mut parts = lower.split('_');
+
+        let mut isa = Isa::RV64GC;
+        match parts.next() {
+            Some("rv64gc" | "rv64imafdc") => {}
+            Some("rv64gcv" | "rv64imafdcv") => isa.v = true,
+            _ => return Err(IsaError::Base(s.to_string())),
+        }
+
+        for part in parts {
+            match part {
+                "zba" => isa.zba = true,
+                "zbb" => isa.zbb = true,
+                "zbc" => isa.zbc = true,
+                "zbs" => isa.zbs = true,
+                _ => match part.strip_prefix("zvl").and_then(|n| n.strip_suffix('b')) {
+                    Some(vlen) => {
+                        isa.vlen = vlen
+                            .parse()
+                            .ok()
+                            .filter(|vlen: &u32| {
+                                vlen.is_power_of_two() && (DEFAULT_VLEN..=MAX_VLEN).contains(vlen)
+                            })
+                            .ok_or_else(|| IsaError::Vlen(part.to_string()))?;
+                    }
+                    None => return Err(IsaError::UnknownExtension(part.to_string())),
+                },
+            }
+        }
+
+        Ok(isa)
+    }
+}
+
+impl RV64GCInstruction {
+    /// The optional extension this instruction is from, `None` for RV64GC ones
+    pub fn extension(&self) -> Option<Extension> {
+        use RV64GCInstruction::*;
+
+        match self {
+            Adduw(..) | Sh1add(..) | Sh2add(..) | Sh3add(..) | Sh1adduw(..) | Sh2adduw(..)
+            | Sh3adduw(..) | Slliuw(..) => Some(Extension::Zba),
+            Andn(..) | Orn(..) | Xnor(..) | Clz(..) | Clzw(..) | Ctz(..) | Ctzw(..) | Cpop(..)
+            | Cpopw(..) | Max(..) | Maxu(..) | Min(..) | Minu(..) | Sextb(..) | Sexth(..)
+            | Zexth(..) | Rol(..) | Rolw(..) | Ror(..) | Rori(..) | Roriw(..) | Rorw(..)
+            | Orcb(..) | Rev8(..) => Some(Extension::Zbb),
+            Clmul(..) | Clmulh(..) | Clmulr(..) => Some(Extension::Zbc),
+            Bclr(..) | Bclri(..) | Bext(..) | Bexti(..) | Binv(..) | Binvi(..) | Bset(..)
+            | Bseti(..) => Some(Extension::Zbs),
+            Vsetvli(..) | Vsetivli(..) | Vsetvl(..) | Vle(..) | Vleff(..) | Vlse(..)
+            | Vluxei(..) | Vloxei(..) | Vlr(..) | Vlm(..) | Vse(..) | Vsse(..) | Vsuxei(..)
+            | Vsoxei(..) | Vsr(..) | Vsm(..) | Vadd(..) | Vsub(..) | Vrsub(..) | Vminu(..)
+            | Vmin(..) | Vmaxu(..) | Vmax(..) | Vand(..) | Vor(..) | Vxor(..) | Vadc(..)
+            | Vmadc(..) | Vsbc(..) | Vmsbc(..) | Vmv(..) | Vmerge(..) | Vmseq(..) | Vmsne(..)
+            | Vmsltu(..) | Vmslt(..) | Vmsleu(..) | Vmsle(..) | Vmsgtu(..) | Vmsgt(..)
+            | Vsll(..) | Vsrl(..) | Vsra(..) | Vnsrl(..) | Vnsra(..) | Vzextvf8(..)
+            | Vsextvf8(..) | Vzextvf4(..) | Vsextvf4(..) | Vzextvf2(..) | Vsextvf2(..)
+            | Vdivu(..) | Vdiv(..) | Vremu(..) | Vrem(..) | Vmulhu(..) | Vmul(..) | Vmulhsu(..)
+            | Vmulh(..) | Vmadd(..) | Vnmsub(..) | Vmacc(..) | Vnmsac(..) | Vwaddu(..)
+            | Vwadd(..) | Vwsubu(..) | Vwsub(..) | Vwadduw(..) | Vwaddw(..) | Vwsubuw(..)
+            | Vwsubw(..) | Vwmulu(..) | Vwmulsu(..) | Vwmul(..) | Vwmaccu(..) | Vwmacc(..)
+            | Vwmaccus(..) | Vwmaccsu(..) | Vsaddu(..) | Vsadd(..) | Vssubu(..) | Vssub(..)
+            | Vaaddu(..) | Vaadd(..) | Vasubu(..) | Vasub(..) | Vsmul(..) | Vssrl(..)
+            | Vssra(..) | Vnclipu(..) | Vnclip(..) | Vredsum(..) | Vredand(..) | Vredor(..)
+            | Vredxor(..) | Vredminu(..) | Vredmin(..) | Vredmaxu(..) | Vredmax(..)
+            | Vwredsumu(..) | Vwredsum(..) | Vfredusum(..) | Vfredosum(..) | Vfredmin(..)
+            | Vfredmax(..) | Vfwredusum(..) | Vfwredosum(..) | Vmandn(..) | Vmand(..)
+            | Vmor(..) | Vmxor(..) | Vmorn(..) | Vmnand(..) | Vmnor(..) | Vmxnor(..)
+            | Vcpop(..) | Vfirst(..) | Vmsbf(..) | Vmsof(..) | Vmsif(..) | Viota(..) | Vid(..)
+            | Vmvxs(..) | Vmvsx(..) | Vfmvfs(..) | Vfmvsf(..) | Vslideup(..) | Vslidedown(..)
+            | Vslide1up(..) | Vslide1down(..) | Vfslide1up(..) | Vfslide1down(..)
+            | Vrgather(..) | Vrgatherei16(..) | Vcompress(..) | Vmvr(..) | Vfadd(..)
+            | Vfsub(..) | Vfrsub(..) | Vfmul(..) | Vfdiv(..) | Vfrdiv(..) | Vfmin(..)
+            | Vfmax(..) | Vfsgnj(..) | Vfsgnjn(..) | Vfsgnjx(..) | Vfmadd(..) | Vfnmadd(..)
+            | Vfmsub(..) | Vfnmsub(..) | Vfmacc(..) | Vfnmacc(..) | Vfmsac(..) | Vfnmsac(..)
+            | Vfwadd(..) | Vfwsub(..) | Vfwaddw(..) | Vfwsubw(..) | Vfwmul(..) | Vfwmacc(..)
+            | Vfwnmacc(..) | Vfwmsac(..) | Vfwnmsac(..) | Vmfeq(..) | Vmfle(..) | Vmflt(..)
+            | Vmfne(..) | Vmfgt(..) | Vmfge(..) | Vfmvvf(..) | Vfmerge(..) | Vfsqrtv(..)
+            | Vfrsqrt7v(..) | Vfrec7v(..) | Vfclassv(..) | Vfcvtxufv(..) | Vfcvtxfv(..)
+            | Vfcvtfxuv(..) | Vfcvtfxv(..) | Vfcvtrtzxufv(..) | Vfcvtrtzxfv(..)
+            | Vfwcvtxufv(..) | Vfwcvtxfv(..) | Vfwcvtfxuv(..) | Vfwcvtfxv(..) | Vfwcvtffv(..)
+            | Vfwcvtrtzxufv(..) | Vfwcvtrtzxfv(..) | Vfncvtxufw(..) | Vfncvtxfw(..)
+            | Vfncvtfxuw(..) | Vfncvtfxw(..) | Vfncvtffw(..) | Vfncvtrodffw(..)
+            | Vfncvtrtzxufw(..) | Vfncvtrtzxfw(..) => Some(Extension::V),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,834 @@
+use std::ptr::NonNull;
+
+use thiserror::Error;
+
+use crate::block::{Block, BlockExit, Cond, Exited, MicroOp, Op};
+use crate::cpu::RV64GCRegAbiName::Pc;
+use crate::cpu::{ExecutionMode, ExitReason, RV64GCInstruction, Reg, RV64GC};
+use crate::exception::{Exception, Trap};
+
+/// A block is compiled once it has run this many times
+pub const HOT_BLOCK_THRESHOLD: u32 = 32;
+
+/// How many instructions [`Lockstep`] runs between register comparisons by default. Blocks
+/// only run compiled when they fit in what's left of an interval
+pub const DEFAULT_LOCKSTEP_INTERVAL: u64 = 1000;
+
+// NOTE: What compiled code returns, the index of the op it stopped at is above these
+const EXIT_TAKEN: u64 = 0;
+const EXIT_NOT_TAKEN: u64 = 1;
+const FAULT: u64 = 2;
+const STOPPED: u64 = 3;
+const STATUS_BITS: u64 = 2;
+
+/// `regs` is the [`RV64GCRegisters`] array, the pc is stored in it before returning
+///
+/// [`RV64GCRegisters`]: crate::cpu::RV64GCRegisters
+type Entry = unsafe extern "sysv64" fn(regs: *mut u64, ctx: *mut Context) -> u64;
+
+/// Passed to compiled code, which hands it to [`execute_op`] for anything it calls out for
+struct Context {
+    cpu: *mut RV64GC,
+    /// Ops retired so far, native ones are only counted when something else needs it
+    retired: u64,
+    fault: Option<Exception>,
+}
+
+impl Context {
+    fn retire_until(&mut self, cpu: &mut RV64GC, index: u64) {
+        for _ in self.retired..index {
+            cpu.csr.retire();
+        }
+        self.retired = self.retired.max(index);
+    }
+}
+
+/// A block compiled to x86_64, in its own read-only executable mapping
+pub(crate) struct NativeBlock {
+    code: NonNull<u8>,
+    len: usize,
+}
+
+// SAFETY: The mapping is never written after it's created
+unsafe impl Send for NativeBlock {}
+
+impl std::fmt::Debug for NativeBlock {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "NativeBlock({:p}, {} bytes)", self.code, self.len)
+    }
+}
+
+impl NativeBlock {
+    fn new(code: &[u8]) -> Option<NativeBlock> {
+        let len = code.len().next_multiple_of(4096);
+
+        // SAFETY: A fresh anonymous mapping, which is only made executable once it's been
+        // written, and never writable again
+        unsafe {
+            let ptr = libc::mmap(
+                std::ptr::null_mut(),
+                len,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+                -1,
+                0,
+            );
+            if ptr == libc::MAP_FAILED {
+                return None;
+            }
+
+            std::ptr::copy_nonoverlapping(code.as_ptr(), ptr.cast(), code.len());
+            if libc::mprotect(ptr, len, libc::PROT_READ | libc::PROT_EXEC) != 0 {
+                libc::munmap(ptr, len);
+                return None;
+            }
+
+            Some(NativeBlock {
+                code: NonNull::new_unchecked(ptr.cast()),
+                len,
+            })
+        }
+    }
+
+    fn entry(&self) -> Entry {
+        // SAFETY: The mapping starts with a function generated by `compile`
+        unsafe { std::mem::transmute::<*mut u8, Entry>(self.code.as_ptr()) }
+    }
+}
+
+impl Drop for NativeBlock {
+    fn drop(&mut self) {
+        // SAFETY: Mapped in `new`, and nothing runs it anymore
+        unsafe {
+            libc::munmap(self.code.as_ptr().cast(), self.len);
+        }
+    }
+}
+
+/// Compiles `block`, `None` if it couldn't be mapped. Integer ops (RV64I and M) are
+/// generated inline, loads, stores and everything else call back into [`execute_op`]
+pub(crate) fn compile(block: &Block) -> Option<NativeBlock> {
+    let mut asm = Assembler::new();
+    asm.prologue();
+
+    for (index, op) in block.ops.iter().enumerate() {
+        if !asm.op(&op.op) {
+            asm.call_out(op, index as u64);
+        }
+    }
+
+    asm.exit(&block.exit);
+    asm.epilogue();
+
+    NativeBlock::new(&asm.finish())
+}
+
+/// Runs a compiled block, like [`Block`]s are interpreted
+pub(crate) fn execute(
+    native: &NativeBlock,
+    block: &Block,
+    cpu: &mut RV64GC,
+) -> Result<Exited, Trap> {
+    let mut ctx = Context {
+        cpu,
+        retired: 0,
+        fault: None,
+    };
+
+    // SAFETY: The registers array and the context outlive the call, and compiled code
+    // only touches the cpu through them
+    let status = unsafe {
+        let regs = (*ctx.cpu).registers.as_mut_ptr();
+        native.entry()(regs, &mut ctx)
+    };
+
+    let index = status >> STATUS_BITS;
+    match status & ((1 << STATUS_BITS) - 1) {
+        FAULT => {
+            let op = &block.ops[index as usize];
+            ctx.retire_until(cpu, index);
+            cpu.registers[Pc] = op.pc;
+
+            let fault = ctx
+                .fault
+                .expect("compiled code faulted without an exception");
+            Err(Trap::from_exception(fault, op.pc, op.bits))
+        }
+        STOPPED => {
+            let next = block
+                .ops
+                .get(index as usize + 1)
+                .map_or(block.end, |op| op.pc);
+            cpu.registers[Pc] = next;
+
+            Ok(Exited {
+                executed: index + 1,
+                pc: next,
+                slot: None,
+            })
+        }
+        taken => {
+            ctx.retire_until(cpu, block.len);
+
+            let slot = match block.exit {
+                BlockExit::Indirect { .. } => None,
+                _ if taken == EXIT_NOT_TAKEN => Some(1),
+                _ => Some(0),
+            };
+
+            Ok(Exited {
+                executed: block.len,
+                pc: cpu.registers[Pc],
+                slot,
+            })
+        }
+    }
+}
+
+/// Called by compiled code for ops it doesn't generate itself
+unsafe extern "sysv64" fn execute_op(ctx: *mut Context, op: *const Op, index: u64) -> u64 {
+    // SAFETY: Both come from `execute`, which is still running
+    let (ctx, op) = unsafe { (&mut *ctx, &*op) };
+    let cpu = unsafe { &mut *ctx.cpu };
+
+    ctx.retire_until(cpu, index);
+
+    if let Err(e) = crate::block::execute_op(cpu, op) {
+        ctx.fault = Some(e);
+        return FAULT;
+    }
+
+    cpu.csr.retire();
+    ctx.retired = index + 1;
+
+    if cpu.ram.code_invalidations.is_empty() {
+        EXIT_TAKEN
+    } else {
+        STOPPED
+    }
+}
+
+#[derive(Clone, Copy)]
+#[repr(u8)]
+enum X86 {
+    Rax = 0,
+    Rcx = 1,
+    Rdx = 2,
+    Rbx = 3,
+    Rsi = 6,
+    Rdi = 7,
+    R12 = 12,
+}
+
+use X86::*;
+
+/// `add`, `or`, `and`, `sub`, `xor` and `cmp` opcodes, with `r/m` as the destination
+#[derive(Clone, Copy)]
+enum Alu {
+    Add = 0x01,
+    Or = 0x09,
+    And = 0x21,
+    Sub = 0x29,
+    Xor = 0x31,
+    Cmp = 0x39,
+}
+
+/// `F7` group opcodes
+#[derive(Clone, Copy)]
+enum Unary {
+    Neg = 3,
+    Mul = 4,
+    Imul = 5,
+    Div = 6,
+    Idiv = 7,
+}
+
+/// `C1` group opcodes
+#[derive(Clone, Copy)]
+enum Shift {
+    Shl = 4,
+    Shr = 5,
+    Sar = 7,
+}
+
+/// Just enough of an x86_64 assembler for blocks. Guest registers live in memory at `rbx`,
+/// the context pointer is kept in `r12`, and `rax`, `rcx` and `rdx` are scratch
+struct Assembler {
+    code: Vec<u8>,
+    /// `rel32`s that jump to the epilogue
+    to_epilogue: Vec<usize>,
+}
+
+impl Assembler {
+    fn new() -> Assembler {
+        Assembler {
+            code: Vec::new(),
+            to_epilogue: Vec::new(),
+        }
+    }
+
+    fn finish(self) -> Vec<u8> {
+        self.code
+    }
+
+    fn emit(&mut self, bytes: &[u8]) {
+        self.code.extend_from_slice(bytes);
+    }
+
+    fn rex(&mut self, wide: bool, reg: X86, rm: X86) {
+        let rex = 0x40 | (wide as u8) << 3 | (reg as u8 >> 3) << 2 | rm as u8 >> 3;
+        if rex != 0x40 {
+            self.emit(&[rex]);
+        }
+    }
+
+    fn modrm(&mut self, mode: u8, reg: u8, rm: u8) {
+        self.emit(&[mode << 6 | (reg & 7) << 3 | rm & 7]);
+    }
+
+    /// `rbx` relative addressing of guest register `reg`
+    fn guest(&mut self, reg: u8, guest: Reg) {
+        self.modrm(0b10, reg, Rbx as u8);
+        self.emit(&(guest as i32 * 8).to_le_bytes());
+    }
+
+    fn load(&mut self, dst: X86, guest: Reg) {
+        self.load_sized(true, dst, guest);
+    }
+
+    /// Only the low 32 bits of `guest` when not `wide`
+    fn load_sized(&mut self, wide: bool, dst: X86, guest: Reg) {
+        self.rex(wide, dst, Rbx);
+        self.emit(&[0x8B]);
+        self.guest(dst as u8, guest);
+    }
+
+    fn store(&mut self, guest: Reg, src: X86) {
+        self.rex(true, src, Rbx);
+        self.emit(&[0x89]);
+        self.guest(src as u8, guest);
+    }
+
+    fn mov_imm(&mut self, dst: X86, imm: u64) {
+        self.rex(true, Rax, dst);
+        self.emit(&[0xB8 + (dst as u8 & 7)]);
+        self.emit(&imm.to_le_bytes());
+    }
+
+    fn mov(&mut self, dst: X86, src: X86) {
+        self.rex(true, src, dst);
+        self.emit(&[0x89]);
+        self.modrm(0b11, src as u8, dst as u8);
+    }
+
+    fn alu(&mut self, op: Alu, wide: bool, dst: X86, src: X86) {
+        self.rex(wide, src, dst);
+        self.emit(&[op as u8]);
+        self.modrm(0b11, src as u8, dst as u8);
+    }
+
+    /// `cmp dst, -1`
+    fn cmp_minus_one(&mut self, wide: bool, dst: X86) {
+        self.rex(wide, Rax, dst);
+        self.emit(&[0x83]);
+        self.modrm(0b11, 7, dst as u8);
+        self.emit(&[0xFF]);
+    }
+
+    fn test(&mut self, wide: bool, reg: X86) {
+        self.rex(wide, reg, reg);
+        self.emit(&[0x85]);
+        self.modrm(0b11, reg as u8, reg as u8);
+    }
+
+    fn unary(&mut self, op: Unary, wide: bool, reg: X86) {
+        self.rex(wide, Rax, reg);
+        self.emit(&[0xF7]);
+        self.modrm(0b11, op as u8, reg as u8);
+    }
+
+    /// `imul dst, src`
+    fn imul(&mut self, wide: bool, dst: X86, src: X86) {
+        self.rex(wide, dst, src);
+        self.emit(&[0x0F, 0xAF]);
+        self.modrm(0b11, dst as u8, src as u8);
+    }
+
+    fn shift(&mut self, op: Shift, reg: X86, amount: u32) {
+        self.rex(true, Rax, reg);
+        self.emit(&[0xC1]);
+        self.modrm(0b11, op as u8, reg as u8);
+        self.emit(&[(amount & 63) as u8]);
+    }
+
+    /// Sign extends `eax` to `rax`, for word results
+    fn sign_extend_eax(&mut self) {
+        self.emit(&[0x48, 0x63, 0xC0]);
+    }
+
+    /// Sets `rax` to whether the flags match condition code `cc`
+    fn set(&mut self, cc: u8) {
+        self.emit(&[0x0F, 0x90 | cc, 0xC0, 0x0F, 0xB6, 0xC0]);
+    }
+
+    /// A forward jump (conditional on `cc`, if any), to be [`patch`]ed
+    ///
+    /// [`patch`]: Assembler::patch
+    fn jump(&mut self, cc: Option<u8>) -> usize {
+        match cc {
+            Some(cc) => self.emit(&[0x0F, 0x80 | cc]),
+            None => self.emit(&[0xE9]),
+        }
+        self.emit(&[0; 4]);
+        self.code.len() - 4
+    }
+
+    /// Points the jump at `at` to the current position
+    fn patch(&mut self, at: usize) {
+        let rel = (self.code.len() - (at + 4)) as i32;
+        self.code[at..at + 4].copy_from_slice(&rel.to_le_bytes());
+    }
+
+    fn prologue(&mut self) {
+        // NOTE: push rbx; push r12; push r13 (for alignment); mov rbx, rdi; mov r12, rsi
+        self.emit(&[0x53, 0x41, 0x54, 0x41, 0x55]);
+        self.mov(Rbx, Rdi);
+        self.mov(R12, Rsi);
+    }
+
+    fn epilogue(&mut self) {
+        for at in std::mem::take(&mut self.to_epilogue) {
+            self.patch(at);
+        }
+
+        // NOTE: pop r13; pop r12; pop rbx; ret
+        self.emit(&[0x41, 0x5D, 0x41, 0x5C, 0x5B, 0xC3]);
+    }
+
+    /// Returns `rax` (or'd with `status`, if it's set)
+    fn ret(&mut self, status: Option<u64>) {
+        if let Some(status) = status {
+            self.mov_imm(Rcx, status);
+            self.alu(Alu::Or, true, Rax, Rcx);
+        }
+
+        let at = self.jump(None);
+        self.to_epilogue.push(at);
+    }
+
+    /// `false` if `op` has to be called out for
+    fn op(&mut self, op: &MicroOp) -> bool {
+        match *op {
+            MicroOp::Nop => {}
+            MicroOp::LoadImm { rd, value } => {
+                self.mov_imm(Rax, value);
+                self.store(rd, Rax);
+            }
+            MicroOp::AddImm { rd, rs1, imm } => self.alu_imm(Alu::Add, rd, rs1, imm as u64),
+            MicroOp::AddImmWord { rd, rs1, imm } => {
+                self.load_sized(false, Rax, rs1);
+                self.mov_imm(Rcx, imm as u32 as u64);
+                self.alu(Alu::Add, false, Rax, Rcx);
+                self.sign_extend_eax();
+                self.store(rd, Rax);
+            }
+            MicroOp::XorImm { rd, rs1, imm } => self.alu_imm(Alu::Xor, rd, rs1, imm),
+            MicroOp::OrImm { rd, rs1, imm } => self.alu_imm(Alu::Or, rd, rs1, imm),
+            MicroOp::AndImm { rd, rs1, imm } => self.alu_imm(Alu::And, rd, rs1, imm),
+            MicroOp::SltImm { rd, rs1, imm } => {
+                self.load(Rax, rs1);
+                self.mov_imm(Rcx, imm as u64);
+                self.compare(rd, 0xC);
+            }
+            MicroOp::ShiftLeftImm { rd, rs1, shamt } => self.shift_imm(Shift::Shl, rd, rs1, shamt),
+            MicroOp::ShiftRightImm { rd, rs1, shamt } => self.shift_imm(Shift::Shr, rd, rs1, shamt),
+            MicroOp::ShiftRightArithImm { rd, rs1, shamt } => {
+                self.shift_imm(Shift::Sar, rd, rs1, shamt)
+            }
+            MicroOp::Add { rd, rs1, rs2 } => self.alu_reg(Alu::Add, true, rd, rs1, rs2),
+            MicroOp::Sub { rd, rs1, rs2 } => self.alu_reg(Alu::Sub, true, rd, rs1, rs2),
+            MicroOp::AddWord { rd, rs1, rs2 } => self.alu_reg(Alu::Add, false, rd, rs1, rs2),
+            MicroOp::SubWord { rd, rs1, rs2 } => self.alu_reg(Alu::Sub, false, rd, rs1, rs2),
+            MicroOp::Xor { rd, rs1, rs2 } => self.alu_reg(Alu::Xor, true, rd, rs1, rs2),
+            MicroOp::Or { rd, rs1, rs2 } => self.alu_reg(Alu::Or, true, rd, rs1, rs2),
+            MicroOp::And { rd, rs1, rs2 } => self.alu_reg(Alu::And, true, rd, rs1, rs2),
+            MicroOp::Slt { rd, rs1, rs2 } => {
+                self.operands(rs1, rs2);
+                self.compare(rd, 0xC);
+            }
+            MicroOp::Sltu { rd, rs1, rs2 } => {
+                self.operands(rs1, rs2);
+                self.compare(rd, 0x2);
+            }
+            MicroOp::Interpret(ins) => return self.multiply_divide(ins),
+            MicroOp::Load { .. } | MicroOp::Store { .. } => return false,
+        }
+
+        true
+    }
+
+    /// RV64M, which the block interpreter leaves to `execute_instruction`
+    fn multiply_divide(&mut self, ins: RV64GCInstruction) -> bool {
+        use RV64GCInstruction::*;
+
+        let (rd, rs1, rs2) = match ins {
+            Mul(rd, rs1, rs2)
+            | Mulh(rd, rs1, rs2)
+            | Mulhsu(rd, rs1, rs2)
+            | Mulhu(rd, rs1, rs2)
+            | Div(rd, rs1, rs2)
+            | Divu(rd, rs1, rs2)
+            | Rem(rd, rs1, rs2)
+            | Remu(rd, rs1, rs2)
+            | Mulw(rd, rs1, rs2)
+            | Divw(rd, rs1, rs2)
+            | Divuw(rd, rs1, rs2)
+            | Remw(rd, rs1, rs2)
+            | Remuw(rd, rs1, rs2) => (rd, rs1, rs2),
+            _ => return false,
+        };
+
+        // NOTE: None of these can trap, so writes to x0 are simply dropped
+        if rd == 0 {
+            return true;
+        }
+
+        let wide = !matches!(ins, Mulw(..) | Divw(..) | Divuw(..) | Remw(..) | Remuw(..));
+        self.load_sized(wide, Rax, rs1);
+        self.load_sized(wide, Rcx, rs2);
+
+        match ins {
+            Mul(..) | Mulw(..) => self.imul(wide, Rax, Rcx),
+            Mulh(..) => {
+                self.unary(Unary::Imul, true, Rcx);
+                self.mov(Rax, Rdx);
+            }
+            Mulhu(..) => {
+                self.unary(Unary::Mul, true, Rcx);
+                self.mov(Rax, Rdx);
+            }
+            Mulhsu(..) => {
+                // NOTE: The unsigned high half, minus rs2 if rs1 is negative
+                self.unary(Unary::Mul, true, Rcx);
+                self.load(Rax, rs1);
+                self.shift(Shift::Sar, Rax, 63);
+                self.alu(Alu::And, true, Rax, Rcx);
+                self.alu(Alu::Sub, true, Rdx, Rax);
+                self.mov(Rax, Rdx);
+            }
+            Div(..) | Divw(..) => self.divide(wide, true, false),
+            Rem(..) | Remw(..) => self.divide(wide, true, true),
+            Divu(..) | Divuw(..) => self.divide(wide, false, false),
+            Remu(..) | Remuw(..) => self.divide(wide, false, true),
+            _ => unreachable!(),
+        }
+
+        if !wide {
+            self.sign_extend_eax();
+        }
+        self.store(rd, Rax);
+
+        true
+    }
+
+    /// `rax / rcx` (or `%`) into `rax`, without the x86 exceptions for dividing by zero
+    /// and overflowing
+    fn divide(&mut self, wide: bool, signed: bool, remainder: bool) {
+        self.test(wide, Rcx);
+        let by_zero = self.jump(Some(0x4));
+
+        let overflow = signed.then(|| {
+            self.cmp_minus_one(wide, Rcx);
+            self.jump(Some(0x4))
+        });
+
+        if signed {
+            // NOTE: cqo / cdq
+            self.rex(wide, Rax, Rax);
+            self.emit(&[0x99]);
+        } else {
+            self.alu(Alu::Xor, false, Rdx, Rdx);
+        }
+        self.unary(if signed { Unary::Idiv } else { Unary::Div }, wide, Rcx);
+        if remainder {
+            self.mov(Rax, Rdx);
+        }
+        let done = self.jump(None);
+
+        // NOTE: Dividing by -1 negates, the remainder is 0 (even for the most negative
+        // dividend)
+        if let Some(overflow) = overflow {
+            self.patch(overflow);
+            if remainder {
+                self.alu(Alu::Xor, false, Rax, Rax);
+            } else {
+                self.unary(Unary::Neg, wide, Rax);
+            }
+        }
+        let overflow_done = overflow.map(|_| self.jump(None));
+
+        // NOTE: Dividing by 0 gives all ones, the remainder is the dividend
+        self.patch(by_zero);
+        if !remainder {
+            self.mov_imm(Rax, u64::MAX);
+        }
+
+        self.patch(done);
+        if let Some(at) = overflow_done {
+            self.patch(at);
+        }
+    }
+
+    fn operands(&mut self, rs1: Reg, rs2: Reg) {
+        self.load(Rax, rs1);
+        self.load(Rcx, rs2);
+    }
+
+    /// Sets `rd` to whether `rax` and `rcx` compare with condition code `cc`
+    fn compare(&mut self, rd: Reg, cc: u8) {
+        self.alu(Alu::Cmp, true, Rax, Rcx);
+        self.set(cc);
+        self.store(rd, Rax);
+    }
+
+    fn alu_imm(&mut self, op: Alu, rd: Reg, rs1: Reg, imm: u64) {
+        self.load(Rax, rs1);
+        self.mov_imm(Rcx, imm);
+        self.alu(op, true, Rax, Rcx);
+        self.store(rd, Rax);
+    }
+
+    fn alu_reg(&mut self, op: Alu, wide: bool, rd: Reg, rs1: Reg, rs2: Reg) {
+        self.operands(rs1, rs2);
+        self.alu(op, wide, Rax, Rcx);
+        if !wide {
+            self.sign_extend_eax();
+        }
+        self.store(rd, Rax);
+    }
+
+    fn shift_imm(&mut self, op: Shift, rd: Reg, rs1: Reg, shamt: u32) {
+        self.load(Rax, rs1);
+        self.shift(op, Rax, shamt);
+        self.store(rd, Rax);
+    }
+
+    /// Calls [`execute_op`] for `op`, returning from the block if it faulted or code was
+    /// overwritten
+    fn call_out(&mut self, op: &Op, index: u64) {
+        self.mov(Rdi, R12);
+        self.mov_imm(Rsi, op as *const Op as u64);
+        self.mov_imm(Rdx, index);
+        self.mov_imm(Rax, execute_op as *const () as u64);
+        self.emit(&[0xFF, 0xD0]);
+
+        self.test(true, Rax);
+        let ok = self.jump(Some(0x4));
+        self.ret(Some(index << STATUS_BITS));
+        self.patch(ok);
+    }
+
+    fn exit(&mut self, exit: &BlockExit) {
+        match *exit {
+            BlockExit::Fallthrough { next } => self.leave(next, EXIT_TAKEN),
+            BlockExit::Jump { rd, link, target } => {
+                if rd != 0 {
+                    self.mov_imm(Rax, link);
+                    self.store(rd, Rax);
+                }
+                self.leave(target, EXIT_TAKEN);
+            }
+            BlockExit::Branch {
+                cond,
+                rs1,
+                rs2,
+                target,
+                next,
+            } => {
+                let cc = match cond {
+                    Cond::Eq => 0x4,
+                    Cond::Ne => 0x5,
+                    Cond::Lt => 0xC,
+                    Cond::Ge => 0xD,
+                    Cond::Ltu => 0x2,
+                    Cond::Geu => 0x3,
+                };
+
+                self.operands(rs1, rs2);
+                self.alu(Alu::Cmp, true, Rax, Rcx);
+                let taken = self.jump(Some(cc));
+                self.leave(next, EXIT_NOT_TAKEN);
+                self.patch(taken);
+                self.leave(target, EXIT_TAKEN);
+            }
+            BlockExit::Indirect {
+                rd,
+                rs1,
+                imm,
+                mask,
+                link,
+            } => {
+                self.load(Rax, rs1);
+                self.mov_imm(Rcx, imm as u64);
+                self.alu(Alu::Add, true, Rax, Rcx);
+                self.mov_imm(Rcx, mask);
+                self.alu(Alu::And, true, Rax, Rcx);
+                if rd != 0 {
+                    self.mov_imm(Rcx, link);
+                    self.store(rd, Rcx);
+                }
+                self.store(Pc as Reg, Rax);
+                self.mov_imm(Rax, EXIT_TAKEN);
+                self.ret(None);
+            }
+        }
+    }
+
+    /// Sets the pc to `pc` and returns `status`
+    fn leave(&mut self, pc: u64, status: u64) {
+        self.mov_imm(Rax, pc);
+        self.store(Pc as Reg, Rax);
+        self.mov_imm(Rax, status);
+        self.ret(None);
+    }
+}
+
+/// The first place the JIT and the interpreter disagreed, see [`Lockstep`]
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum Divergence {
+    #[error("{register} is 0x{jit:016x} with the JIT but 0x{reference:016x} in the interpreter (after {executed} instructions)")]
+    Register {
+        register: String,
+        jit: u64,
+        reference: u64,
+        executed: u64,
+    },
+    #[error("The JIT stopped with {jit:?} but the interpreter with {reference:?} (after {executed} instructions)")]
+    ExitReason {
+        jit: ExitReason,
+        reference: ExitReason,
+        executed: u64,
+    },
+}
+
+/// Runs the same guest with [`ExecutionMode::Jit`] and [`ExecutionMode::Step`], comparing
+/// the registers of both every `interval` instructions.
+///
+/// Both cpus have to be loaded the same way, with a fixed [`LaunchConfig::random_bytes`]
+/// for ELFs. Syscalls are made by both of them, so output is written twice.
+///
+/// [`LaunchConfig::random_bytes`]: crate::launch::LaunchConfig::random_bytes
+pub struct Lockstep {
+    pub jit: RV64GC,
+    pub reference: RV64GC,
+    pub interval: u64,
+    executed: u64,
+}
+
+impl Lockstep {
+    pub fn new(mut jit: RV64GC, mut reference: RV64GC) -> Lockstep {
+        jit.execution_mode = ExecutionMode::Jit;
+        reference.execution_mode = ExecutionMode::Step;
+
+        Lockstep {
+            jit,
+            reference,
+            interval: DEFAULT_LOCKSTEP_INTERVAL,
+            executed: 0,
+        }
+    }
+
+    /// Instructions both cpus have executed
+    pub fn executed(&self) -> u64 {
+        self.executed
+    }
+
+    /// Runs both until the guest exits or traps (the same way in both), `budget`
+    /// instructions have been executed, or they diverge
+    pub fn run(&mut self, budget: Option<u64>) -> Result<ExitReason, Divergence> {
+        let mut executed = 0u64;
+
+        loop {
+            let chunk = budget.map_or(self.interval, |budget| self.interval.min(budget - executed));
+            if chunk == 0 {
+                return Ok(ExitReason::BudgetExhausted);
+            }
+
+            let jit = self.jit.run(Some(chunk));
+            let reference = self.reference.run(Some(chunk));
+            self.compare()?;
+
+            if jit != reference {
+                return Err(Divergence::ExitReason {
+                    jit,
+                    reference,
+                    executed: self.executed,
+                });
+            }
+
+            if jit != ExitReason::BudgetExhausted {
+                return Ok(jit);
+            }
+
+            executed += chunk;
+            self.executed += chunk;
+        }
+    }
+
+    fn compare(&self) -> Result<(), Divergence> {
+        let (jit, reference) = (&self.jit, &self.reference);
+
+        let integer = (0..=32).map(|i| {
+            let name = if i == Pc as usize {
+                "pc".to_string()
+            } else {
+                format!("x{i}")
+            };
+            (name, jit.registers[i], reference.registers[i])
+        });
+        let float = (0..32).map(|i| {
+            (
+                format!("f{i}"),
+                jit.float_registers[i],
+                reference.float_registers[i],
+            )
+        });
+
+        match integer.chain(float).find(|(_, a, b)| a != b) {
+            Some((register, jit, reference)) => Err(Divergence::Register {
+                register,
+                jit,
+                reference,
+                executed: self.executed,
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cpu::RV64GCRegAbiName::Sp;
+    use crate::ram::MemoryRegion;
+
+    fn fib() -> RV64GC {
+        let mut cpu = RV64GC::new();
+        cpu.load_bin(include_bytes!("../../tests/rv64i/fib/fib.bin").to_vec());
+        cpu.ram
+            .add_region(MemoryRegion::new(0xF_0000, 0x1_0000, vec![0; 0x1_0000]))
+            .unwrap();
+        cpu.registers[Sp] = 0x10_0000;
+        cpu
+    }
+
+    #[test]
+    fn jit_matches_interpreter() {
+        let mut lockstep = Lockstep::new(fib(), fib());
+        assert_eq!(lockstep.run(Some(20_000)), Ok(ExitReason::BudgetExhausted));
+
+        lockstep.interval = 77;
+        assert_eq!(lockstep.run(Some(100_000)), Ok(ExitReason::BudgetExhausted));
+        assert!(lockstep.jit.block_cache.native_blocks() > 0);
+    }
+}
@@ -0,0 +1,157 @@
+use std::path::{Component, Path, PathBuf};
+
+use rand::Rng;
+
+/// Top of the guest stack, the stack grows down from here
+pub const DEFAULT_STACK_TOP: u64 = 0x7FFF_FFFF_FFFF_FFF0;
+
+/// 8 MB, the usual `ulimit -s` on Linux
+pub const DEFAULT_STACK_SIZE: u64 = 8 * 1024 * 1024;
+
+/// 1 GB, how far the program break can grow by default
+pub const DEFAULT_HEAP_LIMIT: u64 = 1024 * 1024 * 1024;
+
+/// Where the lowest segment of an `ET_DYN` executable (a PIE) is loaded by default
+pub const DEFAULT_PIE_BASE: u64 = 0x5555_5555_4000;
+
+/// The stack gets at least this much room below its top before mappings start, like on Linux
+const STACK_GAP: u64 = 128 * 1024 * 1024;
+
+/// Bases are moved by up to this many pages when randomized, what Linux uses for sv39
+const ASLR_PAGES: u64 = 1 << 18;
+
+/// How the guest is started (its initial stack and where its files come from), passed to
+/// [`RV64GC::load_elf`].
+///
+/// [`RV64GC::load_elf`]: crate::cpu::RV64GC::load_elf
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LaunchConfig {
+    pub argv: Vec<String>,
+    /// `KEY=VALUE` strings, nothing from the host is passed unless it's listed here
+    pub envp: Vec<String>,
+    /// The path the executable was started from, reported as `AT_EXECFN`, `argv[0]` if unset
+    pub execfn: Option<String>,
+    /// `(AT_*, value)` pairs, replacing the default entry with the same key or adding a new one
+    pub auxv: Vec<(u64, u64)>,
+    pub stack_size: u64,
+    pub stack_top: u64,
+    /// How far `brk` can move the program break past its start, like `RLIMIT_DATA`
+    pub heap_limit: u64,
+    /// Directory absolute guest paths (including `PT_INTERP`) are looked up in first
+    pub sysroot: Option<PathBuf>,
+    /// Confines every path the guest opens to this directory, like a chroot, `PT_INTERP`
+    /// included. The sysroot isn't used when this is set
+    pub root: Option<PathBuf>,
+    /// Refuse anything that would modify the host file system with `EROFS`
+    pub read_only: bool,
+    /// Where a PIE's lowest segment is mapped, must be page aligned
+    pub pie_base: u64,
+    /// Slide the PIE and interpreter bases by a random number of pages, like ASLR
+    pub randomize_base: bool,
+    /// The 16 bytes `AT_RANDOM` points to, fresh ones from the host if unset
+    pub random_bytes: Option<[u8; 16]>,
+}
+
+impl Default for LaunchConfig {
+    fn default() -> Self {
+        LaunchConfig {
+            argv: Vec::new(),
+            envp: Vec::new(),
+            execfn: None,
+            auxv: Vec::new(),
+            stack_size: DEFAULT_STACK_SIZE,
+            stack_top: DEFAULT_STACK_TOP,
+            heap_limit: DEFAULT_HEAP_LIMIT,
+            sysroot: None,
+            root: None,
+            read_only: false,
+            pie_base: DEFAULT_PIE_BASE,
+            randomize_base: false,
+            random_bytes: None,
+        }
+    }
+}
+
+impl LaunchConfig {
+    pub fn new(argv: Vec<String>) -> LaunchConfig {
+        LaunchConfig {
+            argv,
+            ..Default::default()
+        }
+    }
+
+    /// Sets `key` to `value`, replacing any previous definition
+    pub fn set_env(&mut self, key: &str, value: &str) {
+        self.remove_env(key);
+        self.envp.push(format!("{key}={value}"));
+    }
+
+    /// The address mappings without a (usable) hint are placed below, highest first
+    pub fn mmap_top(&self) -> u64 {
+        let stack_start = self
+            .stack_top
+            .saturating_sub(self.stack_size.max(STACK_GAP));
+        stack_start & !0xfff
+    }
+
+    /// `base`, slid up by a random number of pages if `randomize_base` is set
+    pub fn slide(&self, base: u64) -> u64 {
+        if self.randomize_base {
+            base + rand::thread_rng().gen_range(0..ASLR_PAGES) * 4096
+        } else {
+            base
+        }
+    }
+
+    /// Maps an absolute guest path into the root or the sysroot, falling back to the host
+    /// path when there is no sysroot or it doesn't contain the file
+    pub fn resolve_path(&self, path: &str) -> PathBuf {
+        if let (Some(root), Some(relative)) = (&self.root, path.strip_prefix('/')) {
+            return root.join(relative);
+        }
+
+        if let (Some(sysroot), Some(relative)) = (&self.sysroot, path.strip_prefix('/')) {
+            let in_sysroot = sysroot.join(relative);
+            if in_sysroot.exists() {
+                return in_sysroot;
+            }
+        }
+
+        Path::new(path).to_path_buf()
+    }
+
+    /// Where the guest's `path` is on the host. With a `root`, `..` stops at the root and
+    /// `None` is returned for paths that symlinks would lead out of it
+    pub fn host_path(&self, path: &Path) -> Option<PathBuf> {
+        let Some(root) = &self.root else {
+            return Some(self.resolve_path(&path.to_string_lossy()));
+        };
+
+        let mut inside = PathBuf::new();
+        for component in path.components() {
+            match component {
+                Component::Normal(name) => inside.push(name),
+                Component::ParentDir => {
+                    inside.pop();
+                }
+                _ => {}
+            }
+        }
+
+        let host = root.join(inside);
+
+        // NOTE: Symlinks are followed by the host, so the deepest thing that exists has to
+        // resolve to somewhere in the root. Absolute links inside the root are refused too
+        let root = root.canonicalize().ok()?;
+        let existing = host.ancestors().find(|p| p.symlink_metadata().is_ok())?;
+        existing
+            .canonicalize()
+            .is_ok_and(|p| p.starts_with(&root))
+            .then_some(host)
+    }
+
+    pub fn remove_env(&mut self, key: &str) {
+        self.envp
+            .retain(|var| var.split_once('=').map_or(var.as_str(), |(k, _)| k) != key);
+    }
+}
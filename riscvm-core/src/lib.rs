@@ -1,11 +1,21 @@
+pub mod block;
 pub mod cpu;
 pub mod csr;
 pub mod exception;
 pub mod fcsr;
+pub mod fd;
+pub mod icache;
+pub mod isa;
+#[cfg(all(feature = "jit", target_arch = "x86_64"))]
+pub mod jit;
+pub mod launch;
 pub mod mmu;
 pub mod opcodes;
 pub mod ram;
+pub mod snapshot;
+pub mod softfloat;
 pub mod syscalls;
+pub mod vector;
 
 pub fn sign_extend12(n: u32) -> i64 {
     sign_extend(n.into(), 12)
@@ -1,3194 +1,2532 @@
-// RV64I Base Integer Instructions
+// RV64GC instruction encodings
+//
+// Every instruction the decoder knows about is one row of the table at the bottom of this
+// file: its mnemonic, which bits identify it, and the operand format it's decoded with.
+// Decoding, `Display` for `RV64GCInstruction` and the tests are all generated from it.
 
-use bit::BitIndex;
-
-pub const fn is_rv64i_add_instruction(ins: u32) -> bool {
-    let add_format: u32 = 0b0000_0000_0000_0000_0000_0000_0011_0011;
-    let mask: u32 = 0b1111_1110_0000_0000_0111_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == add_format
-}
-
-pub const fn is_rv64i_addi_instruction(ins: u32) -> bool {
-    let addi_format: u32 = 0b0000_0000_0000_0000_0000_0000_0001_0011;
-    let mask: u32 = 0b0000_0000_0000_0000_0111_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == addi_format
-}
-
-pub const fn is_rv64i_auipc_instruction(ins: u32) -> bool {
-    let auipc_format: u32 = 0b0000_0000_0000_0000_0000_0000_0001_0111;
-    let mask: u32 = 0b0000_0000_0000_0000_0000_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == auipc_format
-}
-
-pub const fn is_rv64i_lui_instruction(ins: u32) -> bool {
-    let lui_format: u32 = 0b0000_0000_0000_0000_0000_0000_0011_0111;
-    let mask: u32 = 0b0000_0000_0000_0000_0000_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == lui_format
-}
-
-pub const fn is_rv64i_slti_instruction(ins: u32) -> bool {
-    let slti_format: u32 = 0b0000_0000_0000_0000_0010_0000_0001_0011;
-    let mask: u32 = 0b0000_0000_0000_0000_0111_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == slti_format
-}
+use std::fmt::Display;
+use std::sync::OnceLock;
 
-pub const fn is_rv64i_sltiu_instruction(ins: u32) -> bool {
-    let sltiu_format: u32 = 0b0000_0000_0000_0000_0011_0000_0001_0011;
-    let mask: u32 = 0b0000_0000_0000_0000_0111_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == sltiu_format
-}
-
-pub const fn is_rv64i_xori_instruction(ins: u32) -> bool {
-    let xori_format: u32 = 0b0000_0000_0000_0000_0100_0000_0001_0011;
-    let mask: u32 = 0b0000_0000_0000_0000_0111_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == xori_format
-}
+use bit::BitIndex;
 
-pub const fn is_rv64i_ori_instruction(ins: u32) -> bool {
-    let ori_format: u32 = 0b0000_0000_0000_0000_0110_0000_0001_0011;
-    let mask: u32 = 0b0000_0000_0000_0000_0111_0000_0111_1111;
+use crate::cpu::{Csr, Imm, RV64GCInstruction, Reg, Simm};
+use crate::sign_extend;
+use crate::sign_extend12;
+use crate::vector::{format_vtype, VMem, VOperands, VSrc};
+
+/// One row of the instruction table
+#[derive(Debug, Clone, Copy)]
+pub struct Encoding {
+    /// The assembler mnemonic, also what the instruction is displayed as
+    pub name: &'static str,
+    /// Bits that identify the instruction, everything else is an operand
+    pub mask: u32,
+    /// What the masked bits are for this instruction
+    pub value: u32,
+    pub decode: fn(u32) -> RV64GCInstruction,
+    /// A reserved encoding carved out of a later row, these decode as illegal
+    pub reserved: bool,
+}
+
+impl Encoding {
+    pub const fn matches(&self, ins: u32) -> bool {
+        ins & self.mask == self.value
+    }
 
-    let extracted = ins & mask;
-    extracted == ori_format
+    pub const fn is_compressed(&self) -> bool {
+        self.value & 0b11 != 0b11
+    }
 }
 
-pub const fn is_rv64i_andi_instruction(ins: u32) -> bool {
-    let andi_format: u32 = 0b0000_0000_0000_0000_0111_0000_0001_0011;
-    let mask: u32 = 0b0000_0000_0000_0000_0111_0000_0111_1111;
+/// Bits the dispatch table is indexed by: opcode, funct3 and funct7
+const KEY_MASK: u32 = 0xFE00_707F;
 
-    let extracted = ins & mask;
-    extracted == andi_format
-}
+/// Bits compressed instructions are dispatched on: quadrant and funct3
+const C_KEY_MASK: u32 = 0xE003;
 
-pub const fn is_rv64i_slli_instruction(ins: u32) -> bool {
-    let slli_format: u32 = 0b0000_0000_0000_0000_0001_0000_0001_0011;
-    let mask: u32 = 0b1111_1000_0000_0000_0111_0000_0111_1111;
+/// Compressed keys come after the 2^15 keys of 32 bit instructions
+const C_KEY_BASE: usize = 1 << 15;
 
-    let extracted = ins & mask;
-    extracted == slli_format
+const fn dispatch_key(ins: u32) -> usize {
+    if ins & 0b11 == 0b11 {
+        ((ins >> 2) & 0x1F | ((ins >> 12) & 0x7) << 5 | (ins >> 25) << 8) as usize
+    } else {
+        C_KEY_BASE + (ins & 0b11 | ((ins >> 13) & 0x7) << 2) as usize
+    }
 }
 
-pub const fn is_rv64i_srli_instruction(ins: u32) -> bool {
-    let srli_format: u32 = 0b0000_0000_0000_0000_0101_0000_0001_0011;
-    let mask: u32 = 0b1111_1000_0000_0000_0111_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == srli_format
+/// The instruction bits a key was made from, the inverse of [`dispatch_key`]
+const fn key_bits(key: usize) -> u32 {
+    let key = key as u32;
+    if key < C_KEY_BASE as u32 {
+        (key & 0x1F) << 2 | 0b11 | ((key >> 5) & 0x7) << 12 | (key >> 8) << 25
+    } else {
+        let key = key - C_KEY_BASE as u32;
+        key & 0b11 | (key >> 2) << 13
+    }
 }
 
-pub const fn is_rv64i_srai_instruction(ins: u32) -> bool {
-    let srai_format: u32 = 0b0100_0000_0000_0000_0101_0000_0001_0011;
-    let mask: u32 = 0b1111_1100_0000_0000_0111_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == srai_format
+/// Indices into [`ENCODINGS`] of the rows that can match each dispatch key, in table order
+struct DispatchTable {
+    /// `(start, len)` in `rows` per key
+    buckets: Vec<(u16, u16)>,
+    rows: Vec<u16>,
 }
 
-pub const fn is_rv64i_sub_instruction(ins: u32) -> bool {
-    let sub_format: u32 = 0b0100_0000_0000_0000_0000_0000_0011_0011;
-    let mask: u32 = 0b1111_1110_0000_0000_0111_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == sub_format
-}
+impl DispatchTable {
+    fn build() -> DispatchTable {
+        let mut buckets = Vec::with_capacity(C_KEY_BASE + 32);
+        let mut rows = Vec::new();
 
-pub const fn is_rv64i_sll_instruction(ins: u32) -> bool {
-    let sll_format: u32 = 0b0000_0000_0000_0000_0001_0000_0011_0011;
-    let mask: u32 = 0b1111_1110_0000_0000_0111_0000_0111_1111;
+        for key in 0..C_KEY_BASE + 32 {
+            let bits = key_bits(key);
+            let key_mask = if key < C_KEY_BASE {
+                KEY_MASK
+            } else {
+                C_KEY_MASK
+            };
 
-    let extracted = ins & mask;
-    extracted == sll_format
-}
+            let start = rows.len();
+            for (index, encoding) in ENCODINGS.iter().enumerate() {
+                let compressed = key >= C_KEY_BASE;
+                if encoding.is_compressed() == compressed
+                    && (bits ^ encoding.value) & encoding.mask & key_mask == 0
+                {
+                    rows.push(index as u16);
+                }
+            }
 
-pub const fn is_rv64i_slt_instruction(ins: u32) -> bool {
-    let slt_format: u32 = 0b0000_0000_0000_0000_0010_0000_0011_0011;
-    let mask: u32 = 0b1111_1110_0000_0000_0111_0000_0011_0011;
+            buckets.push((start as u16, (rows.len() - start) as u16));
+        }
 
-    let extracted = ins & mask;
-    extracted == slt_format
+        DispatchTable { buckets, rows }
+    }
 }
 
-pub const fn is_rv64i_sltu_instruction(ins: u32) -> bool {
-    let sltu_format: u32 = 0b0000_0000_0000_0000_0011_0000_0011_0011;
-    let mask: u32 = 0b1111_1110_0000_0000_0111_0000_0011_0011;
-
-    let extracted = ins & mask;
-    extracted == sltu_format
+fn dispatch_table() -> &'static DispatchTable {
+    static TABLE: OnceLock<DispatchTable> = OnceLock::new();
+    TABLE.get_or_init(DispatchTable::build)
 }
 
-pub const fn is_rv64i_xor_instruction(ins: u32) -> bool {
-    let xor_format: u32 = 0b0000_0000_0000_0000_0100_0000_0011_0011;
-    let mask: u32 = 0b1111_1110_0000_0000_0111_0000_0011_0011;
+/// The first row of [`ENCODINGS`] that matches `ins`, `None` if that's a reserved encoding.
+/// Only the upper half of a compressed instruction is ignored
+pub fn find_encoding(ins: u32) -> Option<&'static Encoding> {
+    let ins = if ins & 0b11 == 0b11 {
+        ins
+    } else {
+        ins & 0xFFFF
+    };
 
-    let extracted = ins & mask;
-    extracted == xor_format
+    let table = dispatch_table();
+    let (start, len) = table.buckets[dispatch_key(ins)];
+    table.rows[start as usize..(start + len) as usize]
+        .iter()
+        .map(|&index| &ENCODINGS[index as usize])
+        .find(|encoding| encoding.matches(ins))
+        .filter(|encoding| !encoding.reserved)
 }
 
-pub const fn is_rv64i_srl_instruction(ins: u32) -> bool {
-    let srl_format: u32 = 0b0000_0000_0000_0000_0101_0000_0011_0011;
-    let mask: u32 = 0b1111_1110_0000_0000_0111_0000_0011_0011;
+pub fn decode(ins: u32) -> RV64GCInstruction {
+    let ins = if ins & 0b11 == 0b11 {
+        ins
+    } else {
+        ins & 0xFFFF
+    };
 
-    let extracted = ins & mask;
-    extracted == srl_format
+    match find_encoding(ins) {
+        Some(encoding) => (encoding.decode)(ins),
+        None => RV64GCInstruction::IllegalInstruction(ins),
+    }
 }
 
-pub const fn is_rv64i_sra_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0100_0000_0000_0000_0101_0000_0011_0011;
-    let mask: u32 = 0b1111_1110_0000_0000_0111_0000_0011_0011;
+// NOTE: Operand fields, named like in the spec
 
-    let extracted = ins & mask;
-    extracted == format
+fn rd(ins: u32) -> Reg {
+    ins.bit_range(7..12) as Reg
 }
 
-pub const fn is_rv64i_or_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0000_0000_0000_0000_0110_0000_0011_0011;
-    let mask: u32 = 0b1111_1110_0000_0000_0111_0000_0011_0011;
-
-    let extracted = ins & mask;
-    extracted == format
+fn rs1(ins: u32) -> Reg {
+    ins.bit_range(15..20) as Reg
 }
 
-pub const fn is_rv64i_and_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0000_0000_0000_0000_0111_0000_0011_0011;
-    let mask: u32 = 0b1111_1110_0000_0000_0111_0000_0011_0011;
-
-    let extracted = ins & mask;
-    extracted == format
+fn rs2(ins: u32) -> Reg {
+    ins.bit_range(20..25) as Reg
 }
 
-pub const fn is_rv64i_ecall_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0000_0000_0000_0000_0000_0000_0111_0011;
-    let mask: u32 = 0xFFFFFFFF;
-
-    let extracted = ins & mask;
-    extracted == format
+fn rs3(ins: u32) -> Reg {
+    ins.bit_range(27..32) as Reg
 }
 
-pub const fn is_rv64i_ebreak_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0000_0000_0001_0000_0000_0000_0111_0011;
-    let mask: u32 = 0xFFFFFFFF;
-
-    let extracted = ins & mask;
-    extracted == format
+fn rm(ins: u32) -> Reg {
+    ins.bit_range(12..15) as Reg
 }
 
-pub const fn is_rv64i_fence_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0000_0000_0000_0000_0000_0000_0000_1111;
-    let mask: u32 = 0b1111_0000_0000_1111_1111_1111_1111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
+fn csr(ins: u32) -> Csr {
+    ins.bit_range(20..32) as Csr
 }
 
-pub const fn is_rv64i_fencei_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0000_0000_0010_0000_0001_0000_0011_0011;
-    let mask: u32 = 0b1111_1111_1111_1111_1111_1111_1111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
+fn imm_i(ins: u32) -> Imm {
+    ins.bit_range(20..32)
 }
 
-pub const fn is_rv64i_csrrw_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0000_0000_0010_0000_0001_0000_0111_0011;
-    let mask: u32 = 0b0000_0000_0000_0000_0111_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
+fn imm_s(ins: u32) -> Imm {
+    ins.bit_range(25..32) << 5 | ins.bit_range(7..12)
 }
 
-pub const fn is_rv64i_csrrs_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0000_0000_0010_0000_0010_0000_0111_0011;
-    let mask: u32 = 0b0000_0000_0000_0000_0111_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
+fn imm_b(ins: u32) -> Imm {
+    (ins.bit(31) as u32) << 12
+        | (ins.bit(7) as u32) << 11
+        | ins.bit_range(25..31) << 5
+        | ins.bit_range(8..12) << 1
 }
 
-pub const fn is_rv64i_csrrc_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0000_0000_0010_0000_0011_0000_0111_0011;
-    let mask: u32 = 0b0000_0000_0000_0000_0111_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
+fn imm_u(ins: u32) -> Simm {
+    sign_extend((ins.bit_range(12..32) << 12).into(), 32)
 }
 
-pub const fn is_rv64i_csrrwi_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0000_0000_0010_0000_0101_0000_0111_0011;
-    let mask: u32 = 0b0000_0000_0000_0000_0111_0000_0111_1111;
+fn imm_j(ins: u32) -> Simm {
+    let offset = (ins.bit(31) as u32) << 20
+        | ins.bit_range(12..20) << 12
+        | (ins.bit(20) as u32) << 11
+        | ins.bit_range(21..31) << 1;
 
-    let extracted = ins & mask;
-    extracted == format
+    sign_extend(offset.into(), 21)
 }
 
-pub const fn is_rv64i_csrrsi_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0000_0000_0010_0000_0110_0000_0111_0011;
-    let mask: u32 = 0b0000_0000_0000_0000_0111_0000_0111_1111;
+// NOTE: Compressed operand fields, the primed registers are x8-x15
 
-    let extracted = ins & mask;
-    extracted == format
+fn c_rd(ins: u32) -> Reg {
+    ins.bit_range(7..12) as Reg
 }
 
-pub const fn is_rv64i_csrrci_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0000_0000_0010_0000_0111_0000_0111_0011;
-    let mask: u32 = 0b0000_0000_0000_0000_0111_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
+fn c_rs2(ins: u32) -> Reg {
+    ins.bit_range(2..7) as Reg
 }
 
-pub const fn is_rv64i_uret_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0000_0000_0010_0000_0000_0000_0111_0011;
-    let mask: u32 = 0xFFFFFFFF;
-
-    let extracted = ins & mask;
-    extracted == format
+fn c_rd_prime(ins: u32) -> Reg {
+    ins.bit_range(7..10) as Reg + 8
 }
 
-pub const fn is_rv64i_lb_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0000_0000_0000_0000_0000_0000_0000_0011;
-    let mask: u32 = 0b0000_0000_0000_0000_0111_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
+fn c_rs2_prime(ins: u32) -> Reg {
+    ins.bit_range(2..5) as Reg + 8
 }
 
-pub const fn is_rv64i_lh_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0000_0000_0000_0000_0001_0000_0000_0011;
-    let mask: u32 = 0b0000_0000_0000_0000_0111_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
+fn c_imm6(ins: u32) -> Imm {
+    (ins.bit(12) as u32) << 5 | ins.bit_range(2..7)
 }
 
-pub const fn is_rv64i_lw_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0000_0000_0000_0000_0010_0000_0000_0011;
-    let mask: u32 = 0b0000_0000_0000_0000_0111_0000_0111_1111;
+fn c_addi16sp_imm(ins: u32) -> Simm {
+    let imm = (ins.bit(12) as u32) << 9
+        | ins.bit_range(3..5) << 7
+        | (ins.bit(5) as u32) << 6
+        | (ins.bit(2) as u32) << 5
+        | (ins.bit(6) as u32) << 4;
 
-    let extracted = ins & mask;
-    extracted == format
+    sign_extend(imm.into(), 10)
 }
 
-pub const fn is_rv64i_lbu_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0000_0000_0000_0000_0100_0000_0000_0011;
-    let mask: u32 = 0b0000_0000_0000_0000_0111_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
+fn c_lui_imm(ins: u32) -> Imm {
+    (ins.bit(12) as u32) << 17 | ins.bit_range(2..7) << 12
 }
 
-pub const fn is_rv64i_lhu_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0000_0000_0000_0000_0101_0000_0000_0011;
-    let mask: u32 = 0b0000_0000_0000_0000_0111_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
+fn c_ldsp_imm(ins: u32) -> Imm {
+    ins.bit_range(2..5) << 6 | (ins.bit(12) as u32) << 5 | ins.bit_range(5..7) << 3
 }
 
-pub const fn is_rv64i_sb_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0000_0000_0000_0000_0000_0000_0010_0011;
-    let mask: u32 = 0b0000_0000_0000_0000_0111_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
+fn c_lwsp_imm(ins: u32) -> Imm {
+    ins.bit_range(2..4) << 6 | (ins.bit(12) as u32) << 5 | ins.bit_range(4..7) << 2
 }
 
-pub const fn is_rv64i_sh_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0000_0000_0000_0000_0001_0000_0010_0011;
-    let mask: u32 = 0b0000_0000_0000_0000_0111_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
+fn c_sdsp_imm(ins: u32) -> Imm {
+    ins.bit_range(7..10) << 6 | ins.bit_range(10..13) << 3
 }
 
-pub const fn is_rv64i_sw_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0000_0000_0000_0000_0010_0000_0010_0011;
-    let mask: u32 = 0b0000_0000_0000_0000_0111_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
+fn c_swsp_imm(ins: u32) -> Imm {
+    ins.bit_range(7..9) << 6 | ins.bit_range(9..13) << 2
 }
 
-pub const fn is_rv64i_jal_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0000_0000_0000_0000_0000_0000_0110_1111;
-    let mask: u32 = 0b0000_0000_0000_0000_0000_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
+fn c_addi4spn_imm(ins: u32) -> Imm {
+    ins.bit_range(7..11) << 6
+        | ins.bit_range(11..13) << 4
+        | (ins.bit(5) as u32) << 3
+        | (ins.bit(6) as u32) << 2
 }
 
-pub const fn is_rv64i_jalr_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0000_0000_0000_0000_0000_0000_0110_0111;
-    let mask: u32 = 0b0000_0000_0000_0000_0000_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
+fn c_d_imm(ins: u32) -> Imm {
+    ins.bit_range(5..7) << 6 | ins.bit_range(10..13) << 3
 }
 
-pub const fn is_rv64i_beq_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0000_0000_0000_0000_0000_0000_0110_0011;
-    let mask: u32 = 0b0000_0000_0000_0000_0111_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
+fn c_w_imm(ins: u32) -> Imm {
+    (ins.bit(5) as u32) << 6 | ins.bit_range(10..13) << 3 | (ins.bit(6) as u32) << 2
 }
 
-pub const fn is_rv64i_bne_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0000_0000_0000_0000_0001_0000_0110_0011;
-    let mask: u32 = 0b0000_0000_0000_0000_0111_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
+fn c_b_imm(ins: u32) -> Imm {
+    (ins.bit(12) as u32) << 8
+        | ins.bit_range(5..7) << 6
+        | (ins.bit(2) as u32) << 5
+        | ins.bit_range(10..12) << 3
+        | ins.bit_range(3..5) << 1
 }
 
-pub const fn is_rv64i_blt_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0000_0000_0000_0000_0100_0000_0110_0011;
-    let mask: u32 = 0b0000_0000_0000_0000_0111_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
+// Wtf is this bit layout????
+// [ 11 | 4 | 9 | 8 | 10 | 6 | 7 | 3 | 2 | 1 | 5 ]
+//   12  11  10   9    8   7   6   5   4   3   2
+fn c_j_imm(ins: u32) -> Imm {
+    (ins.bit(12) as u32) << 11
+        | (ins.bit(8) as u32) << 10
+        | ins.bit_range(9..11) << 8
+        | (ins.bit(6) as u32) << 7
+        | (ins.bit(7) as u32) << 6
+        | (ins.bit(2) as u32) << 5
+        | (ins.bit(11) as u32) << 4
+        | ins.bit_range(3..6) << 1
 }
 
-pub const fn is_rv64i_bge_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0000_0000_0000_0000_0101_0000_0110_0011;
-    let mask: u32 = 0b0000_0000_0000_0000_0111_0000_0111_1111;
+/// The `iorw` set of a fence's predecessor or successor field, `0` when it's empty
+fn fence_set(set: Reg) -> String {
+    if set & 0b1111 == 0 {
+        return "0".to_string();
+    }
 
-    let extracted = ins & mask;
-    extracted == format
+    "iorw"
+        .chars()
+        .enumerate()
+        .filter(|(i, _)| set & (0b1000 >> i) != 0)
+        .map(|(_, c)| c)
+        .collect()
 }
 
-pub const fn is_rv64i_bltu_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0000_0000_0000_0000_0110_0000_0110_0011;
-    let mask: u32 = 0b0000_0000_0000_0000_0111_0000_0111_1111;
+// NOTE: Vector operand fields
 
-    let extracted = ins & mask;
-    extracted == format
+fn v_operands(ins: u32, src: VSrc) -> VOperands {
+    VOperands {
+        vd: rd(ins),
+        vs2: rs2(ins),
+        src,
+        vm: ins.bit(25),
+    }
 }
 
-pub const fn is_rv64i_bgeu_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0000_0000_0000_0000_0111_0000_0110_0011;
-    let mask: u32 = 0b0000_0000_0000_0000_0111_0000_0111_1111;
+fn v_mem(ins: u32) -> VMem {
+    let eew = match rm(ins) {
+        0b000 => 8,
+        0b101 => 16,
+        0b110 => 32,
+        _ => 64,
+    };
 
-    let extracted = ins & mask;
-    extracted == format
+    VMem {
+        eew,
+        nf: ins.bit_range(29..32) as u8 + 1,
+        vm: ins.bit(25),
+    }
 }
 
-pub const fn is_rv64i_ld_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0000_0000_0000_0000_0011_0000_0000_0011;
-    let mask: u32 = 0b0000_0000_0000_0000_0111_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
+/// `, v0.t` after a masked vector instruction
+fn masked(vm: bool) -> &'static str {
+    if vm {
+        ""
+    } else {
+        ", v0.t"
+    }
 }
 
-pub const fn is_rv64i_sd_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0000_0000_0000_0000_0011_0000_0010_0011;
-    let mask: u32 = 0b0000_0000_0000_0000_0111_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
-}
+/// The mnemonic of a vector load or store from the one of its row, `vle8.v` can be
+/// `vle32.v` or `vlseg4e32.v` too
+fn v_mem_name(name: &str, mem: &VMem) -> String {
+    let digits = name
+        .find(|c: char| c.is_ascii_digit())
+        .unwrap_or(name.len());
+    let suffix = name[digits..].trim_start_matches(|c: char| c.is_ascii_digit());
+    let mut prefix = name[..digits].to_string();
 
-pub const fn is_rv64i_addiw_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0000_0000_0000_0000_0000_0000_0001_1011;
-    let mask: u32 = 0b0000_0000_0000_0000_0111_0000_0111_1111;
+    if mem.nf > 1 {
+        let kind = if prefix.ends_with("ei") { "ei" } else { "e" };
+        prefix.truncate(prefix.len() - kind.len());
+        prefix = format!("{prefix}seg{}{kind}", mem.nf);
+    }
 
-    let extracted = ins & mask;
-    extracted == format
-}
+    format!("{prefix}{}{suffix}", mem.eew)
+}
+
+/// How each operand format is decoded and displayed. `decode` builds the variant from the
+/// instruction bits, `display` prints it if `$ins` is that variant. Register classes (`x`
+/// or `f`) are only used for displaying
+macro_rules! operands {
+    // NOTE: 32 bit formats
+    (decode none, $i:ident, $v:ident) => {{
+        let _ = $i;
+        RV64GCInstruction::$v
+    }};
+    (display none (), $s:expr, $f:ident, $n:literal, $v:ident) => {
+        if let RV64GCInstruction::$v = $s {
+            return write!($f, $n);
+        }
+    };
 
-pub const fn is_rv64i_sraiw_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0100_0000_0000_0000_0101_0000_0001_1011;
-    let mask: u32 = 0b1111_1110_0000_0000_0111_0000_0111_1111;
+    (decode r, $i:ident, $v:ident) => { RV64GCInstruction::$v(rd($i), rs1($i), rs2($i)) };
+    (display r ($a:ident $b:ident $c:ident), $s:expr, $f:ident, $n:literal, $v:ident) => {
+        if let RV64GCInstruction::$v(rd, rs1, rs2) = $s {
+            let (a, b, c) = (stringify!($a), stringify!($b), stringify!($c));
+            return write!($f, "{} {a}{rd}, {b}{rs1}, {c}{rs2}", $n);
+        }
+    };
 
-    let extracted = ins & mask;
-    extracted == format
-}
+    (decode r2, $i:ident, $v:ident) => { RV64GCInstruction::$v(rd($i), rs1($i)) };
+    (display r2 ($a:ident $b:ident), $s:expr, $f:ident, $n:literal, $v:ident) => {
+        if let RV64GCInstruction::$v(rd, rs1) = $s {
+            let (a, b) = (stringify!($a), stringify!($b));
+            return write!($f, "{} {a}{rd}, {b}{rs1}", $n);
+        }
+    };
+
+    (decode rrm, $i:ident, $v:ident) => {
+        RV64GCInstruction::$v(rd($i), rm($i), rs1($i), rs2($i))
+    };
+    (display rrm ($a:ident $b:ident $c:ident), $s:expr, $f:ident, $n:literal, $v:ident) => {
+        if let RV64GCInstruction::$v(rd, _, rs1, rs2) = $s {
+            let (a, b, c) = (stringify!($a), stringify!($b), stringify!($c));
+            return write!($f, "{} {a}{rd}, {b}{rs1}, {c}{rs2}", $n);
+        }
+    };
 
-pub const fn is_rv64i_addw_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0000_0000_0000_0000_0000_0000_0011_1011;
-    let mask: u32 = 0b1111_1110_0000_0000_0111_0000_0111_1111;
+    (decode r2rm, $i:ident, $v:ident) => { RV64GCInstruction::$v(rd($i), rm($i), rs1($i)) };
+    (display r2rm ($a:ident $b:ident), $s:expr, $f:ident, $n:literal, $v:ident) => {
+        if let RV64GCInstruction::$v(rd, _, rs1) = $s {
+            let (a, b) = (stringify!($a), stringify!($b));
+            return write!($f, "{} {a}{rd}, {b}{rs1}", $n);
+        }
+    };
+
+    (decode r4, $i:ident, $v:ident) => {
+        RV64GCInstruction::$v(rd($i), rm($i), rs1($i), rs2($i), rs3($i))
+    };
+    (display r4 (), $s:expr, $f:ident, $n:literal, $v:ident) => {
+        if let RV64GCInstruction::$v(rd, _, rs1, rs2, rs3) = $s {
+            return write!($f, "{} f{rd}, f{rs1}, f{rs2}, f{rs3}", $n);
+        }
+    };
 
-    let extracted = ins & mask;
-    extracted == format
-}
+    (decode lr, $i:ident, $v:ident) => { RV64GCInstruction::$v(rd($i), rs1($i)) };
+    (display lr (), $s:expr, $f:ident, $n:literal, $v:ident) => {
+        if let RV64GCInstruction::$v(rd, rs1) = $s {
+            return write!($f, "{} x{rd}, (x{rs1})", $n);
+        }
+    };
 
-pub const fn is_rv64i_subw_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0100_0000_0000_0000_0000_0000_0011_1011;
-    let mask: u32 = 0b1111_1110_0000_0000_0111_0000_0111_1111;
+    (decode amo, $i:ident, $v:ident) => { RV64GCInstruction::$v(rd($i), rs1($i), rs2($i)) };
+    (display amo (), $s:expr, $f:ident, $n:literal, $v:ident) => {
+        if let RV64GCInstruction::$v(rd, rs1, rs2) = $s {
+            return write!($f, "{} x{rd}, x{rs2}, (x{rs1})", $n);
+        }
+    };
+
+    (decode i, $i:ident, $v:ident) => {
+        RV64GCInstruction::$v(rd($i), rs1($i), sign_extend12(imm_i($i)))
+    };
+    (display i (), $s:expr, $f:ident, $n:literal, $v:ident) => {
+        if let RV64GCInstruction::$v(rd, rs1, simm) = $s {
+            return write!($f, "{} x{rd}, x{rs1}, {simm}", $n);
+        }
+    };
 
-    let extracted = ins & mask;
-    extracted == format
-}
+    // NOTE: The immediate is kept as is, it's sign extended when executed
+    (decode iu, $i:ident, $v:ident) => { RV64GCInstruction::$v(rd($i), rs1($i), imm_i($i)) };
+    (display iu (), $s:expr, $f:ident, $n:literal, $v:ident) => {
+        if let RV64GCInstruction::$v(rd, rs1, imm) = $s {
+            return write!($f, "{} x{rd}, x{rs1}, {}", $n, sign_extend12(*imm));
+        }
+    };
+
+    (decode shamt, $i:ident, $v:ident) => {
+        RV64GCInstruction::$v(rd($i), rs1($i), $i.bit_range(20..26))
+    };
+    (decode shamtw, $i:ident, $v:ident) => {
+        RV64GCInstruction::$v(rd($i), rs1($i), $i.bit_range(20..25))
+    };
+    (display shamt (), $s:expr, $f:ident, $n:literal, $v:ident) => {
+        if let RV64GCInstruction::$v(rd, rs1, shamt) = $s {
+            return write!($f, "{} x{rd}, x{rs1}, {shamt}", $n);
+        }
+    };
+    (display shamtw (), $s:expr, $f:ident, $n:literal, $v:ident) => {
+        operands!(display shamt (), $s, $f, $n, $v)
+    };
+
+    (decode load, $i:ident, $v:ident) => {
+        RV64GCInstruction::$v(rd($i), rs1($i), sign_extend12(imm_i($i)))
+    };
+    (display load ($a:ident), $s:expr, $f:ident, $n:literal, $v:ident) => {
+        if let RV64GCInstruction::$v(rd, rs1, simm) = $s {
+            let a = stringify!($a);
+            return write!($f, "{} {a}{rd}, {simm}(x{rs1})", $n);
+        }
+    };
 
-pub const fn is_rv64i_slliw_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0000_0000_0000_0000_0001_0000_0001_1011;
-    let mask: u32 = 0b1111_1110_0000_0000_0111_0000_0111_1111;
+    (decode loadu, $i:ident, $v:ident) => { RV64GCInstruction::$v(rd($i), rs1($i), imm_i($i)) };
+    (display loadu ($a:ident), $s:expr, $f:ident, $n:literal, $v:ident) => {
+        if let RV64GCInstruction::$v(rd, rs1, imm) = $s {
+            let a = stringify!($a);
+            return write!($f, "{} {a}{rd}, {}(x{rs1})", $n, sign_extend12(*imm));
+        }
+    };
+
+    (decode store, $i:ident, $v:ident) => {
+        RV64GCInstruction::$v(rs1($i), rs2($i), sign_extend12(imm_s($i)))
+    };
+    (display store ($a:ident), $s:expr, $f:ident, $n:literal, $v:ident) => {
+        if let RV64GCInstruction::$v(rs1, rs2, simm) = $s {
+            let a = stringify!($a);
+            return write!($f, "{} {a}{rs2}, {simm}(x{rs1})", $n);
+        }
+    };
 
-    let extracted = ins & mask;
-    extracted == format
-}
+    (decode storeu, $i:ident, $v:ident) => { RV64GCInstruction::$v(rs1($i), rs2($i), imm_s($i)) };
+    (display storeu ($a:ident), $s:expr, $f:ident, $n:literal, $v:ident) => {
+        if let RV64GCInstruction::$v(rs1, rs2, imm) = $s {
+            let a = stringify!($a);
+            return write!($f, "{} {a}{rs2}, {}(x{rs1})", $n, sign_extend12(*imm));
+        }
+    };
 
-pub const fn is_rv64i_srliw_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0000_0000_0000_0000_0101_0000_0001_1011;
-    let mask: u32 = 0b1111_1110_0000_0000_0111_0000_0111_1111;
+    (decode branch, $i:ident, $v:ident) => { RV64GCInstruction::$v(rs1($i), rs2($i), imm_b($i)) };
+    (display branch (), $s:expr, $f:ident, $n:literal, $v:ident) => {
+        if let RV64GCInstruction::$v(rs1, rs2, imm) = $s {
+            let offset = sign_extend((*imm).into(), 13);
+            return write!($f, "{} x{rs1}, x{rs2}, {offset}", $n);
+        }
+    };
 
-    let extracted = ins & mask;
-    extracted == format
-}
+    (decode u, $i:ident, $v:ident) => { RV64GCInstruction::$v(rd($i), imm_u($i)) };
+    (display u (), $s:expr, $f:ident, $n:literal, $v:ident) => {
+        if let RV64GCInstruction::$v(rd, simm) = $s {
+            return write!($f, "{} x{rd}, {:#x}", $n, (simm >> 12) & 0xF_FFFF);
+        }
+    };
 
-pub const fn is_rv64i_sllw_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0000_0000_0000_0000_0001_0000_0011_1011;
-    let mask: u32 = 0b1111_1110_0000_0000_0111_0000_0111_1111;
+    (decode jal, $i:ident, $v:ident) => { RV64GCInstruction::$v(rd($i), imm_j($i)) };
+    (display jal (), $s:expr, $f:ident, $n:literal, $v:ident) => {
+        if let RV64GCInstruction::$v(rd, simm) = $s {
+            return write!($f, "{} x{rd}, {simm}", $n);
+        }
+    };
+
+    (decode fence, $i:ident, $v:ident) => {
+        RV64GCInstruction::$v($i.bit_range(20..24) as Reg, $i.bit_range(24..28) as Reg)
+    };
+    (display fence (), $s:expr, $f:ident, $n:literal, $v:ident) => {
+        if let RV64GCInstruction::$v(succ, pred) = $s {
+            return write!($f, "{} {}, {}", $n, fence_set(*pred), fence_set(*succ));
+        }
+    };
 
-    let extracted = ins & mask;
-    extracted == format
-}
+    (decode csr, $i:ident, $v:ident) => { RV64GCInstruction::$v(rd($i), rs1($i), csr($i)) };
+    (display csr (), $s:expr, $f:ident, $n:literal, $v:ident) => {
+        if let RV64GCInstruction::$v(rd, rs1, csr) = $s {
+            return write!($f, "{} x{rd}, {csr:#x}, x{rs1}", $n);
+        }
+    };
 
-pub const fn is_rv64i_srlw_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0000_0000_0000_0000_0101_0000_0011_1011;
-    let mask: u32 = 0b1111_1110_0000_0000_0111_0000_0111_1111;
+    (decode csri, $i:ident, $v:ident) => { RV64GCInstruction::$v(rd($i), rs1($i), csr($i)) };
+    (display csri (), $s:expr, $f:ident, $n:literal, $v:ident) => {
+        if let RV64GCInstruction::$v(rd, uimm, csr) = $s {
+            return write!($f, "{} x{rd}, {csr:#x}, {uimm}", $n);
+        }
+    };
 
-    let extracted = ins & mask;
-    extracted == format
-}
+    // NOTE: Compressed formats
+    (decode cr, $i:ident, $v:ident) => { RV64GCInstruction::$v(c_rd($i), c_rs2($i)) };
+    (display cr (), $s:expr, $f:ident, $n:literal, $v:ident) => {
+        if let RV64GCInstruction::$v(rd, rs2) = $s {
+            return write!($f, "{} x{rd}, x{rs2}", $n);
+        }
+    };
 
-pub const fn is_rv64i_sraw_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0100_0000_0000_0000_0101_0000_0011_1011;
-    let mask: u32 = 0b1111_1110_0000_0000_0111_0000_0111_1111;
+    (decode cjr, $i:ident, $v:ident) => { RV64GCInstruction::$v(c_rd($i)) };
+    (display cjr (), $s:expr, $f:ident, $n:literal, $v:ident) => {
+        if let RV64GCInstruction::$v(rs1) = $s {
+            return write!($f, "{} x{rs1}", $n);
+        }
+    };
+
+    (decode ci, $i:ident, $v:ident) => {
+        RV64GCInstruction::$v(c_rd($i), sign_extend(c_imm6($i).into(), 6))
+    };
+    (display ci (), $s:expr, $f:ident, $n:literal, $v:ident) => {
+        if let RV64GCInstruction::$v(rd, simm) = $s {
+            return write!($f, "{} x{rd}, {simm}", $n);
+        }
+    };
 
-    let extracted = ins & mask;
-    extracted == format
-}
+    // NOTE: c.li's immediate is sign extended when executed
+    (decode cli, $i:ident, $v:ident) => { RV64GCInstruction::$v(c_rd($i), c_imm6($i)) };
+    (display cli (), $s:expr, $f:ident, $n:literal, $v:ident) => {
+        if let RV64GCInstruction::$v(rd, imm) = $s {
+            return write!($f, "{} x{rd}, {}", $n, sign_extend((*imm).into(), 6));
+        }
+    };
 
-pub const fn is_rv64i_lwu_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0000_0000_0000_0000_0110_0000_0000_0011;
-    let mask: u32 = 0b0000_0000_0000_0000_0111_0000_0111_1111;
+    (decode cslli, $i:ident, $v:ident) => { RV64GCInstruction::$v(c_rd($i), c_imm6($i)) };
+    (display cslli (), $s:expr, $f:ident, $n:literal, $v:ident) => {
+        if let RV64GCInstruction::$v(rd, shamt) = $s {
+            return write!($f, "{} x{rd}, {shamt}", $n);
+        }
+    };
+
+    (decode cshift, $i:ident, $v:ident) => { RV64GCInstruction::$v(c_rd_prime($i), c_imm6($i)) };
+    (display cshift (), $s:expr, $f:ident, $n:literal, $v:ident) => {
+        operands!(display cslli (), $s, $f, $n, $v)
+    };
+
+    (decode candi, $i:ident, $v:ident) => {
+        RV64GCInstruction::$v(c_rd_prime($i), sign_extend(c_imm6($i).into(), 6))
+    };
+    (display candi (), $s:expr, $f:ident, $n:literal, $v:ident) => {
+        operands!(display ci (), $s, $f, $n, $v)
+    };
+
+    (decode caddi16sp, $i:ident, $v:ident) => { RV64GCInstruction::$v(c_addi16sp_imm($i)) };
+    (display caddi16sp (), $s:expr, $f:ident, $n:literal, $v:ident) => {
+        if let RV64GCInstruction::$v(simm) = $s {
+            return write!($f, "{} x2, {simm}", $n);
+        }
+    };
 
-    let extracted = ins & mask;
-    extracted == format
-}
+    (decode clui, $i:ident, $v:ident) => { RV64GCInstruction::$v(c_rd($i), c_lui_imm($i)) };
+    (display clui (), $s:expr, $f:ident, $n:literal, $v:ident) => {
+        if let RV64GCInstruction::$v(rd, imm) = $s {
+            let imm = (sign_extend((*imm).into(), 18) >> 12) & 0xF_FFFF;
+            return write!($f, "{} x{rd}, {imm:#x}", $n);
+        }
+    };
+
+    (decode cldsp, $i:ident, $v:ident) => { RV64GCInstruction::$v(c_rd($i), c_ldsp_imm($i)) };
+    (decode clwsp, $i:ident, $v:ident) => { RV64GCInstruction::$v(c_rd($i), c_lwsp_imm($i)) };
+    (display cldsp ($a:ident), $s:expr, $f:ident, $n:literal, $v:ident) => {
+        if let RV64GCInstruction::$v(rd, imm) = $s {
+            let a = stringify!($a);
+            return write!($f, "{} {a}{rd}, {imm}(x2)", $n);
+        }
+    };
+    (display clwsp ($a:ident), $s:expr, $f:ident, $n:literal, $v:ident) => {
+        operands!(display cldsp ($a), $s, $f, $n, $v)
+    };
+
+    (decode csdsp, $i:ident, $v:ident) => { RV64GCInstruction::$v(c_rs2($i), c_sdsp_imm($i)) };
+    (decode cswsp, $i:ident, $v:ident) => { RV64GCInstruction::$v(c_rs2($i), c_swsp_imm($i)) };
+    (display csdsp ($a:ident), $s:expr, $f:ident, $n:literal, $v:ident) => {
+        if let RV64GCInstruction::$v(rs2, imm) = $s {
+            let a = stringify!($a);
+            return write!($f, "{} {a}{rs2}, {imm}(x2)", $n);
+        }
+    };
+    (display cswsp ($a:ident), $s:expr, $f:ident, $n:literal, $v:ident) => {
+        operands!(display csdsp ($a), $s, $f, $n, $v)
+    };
+
+    (decode caddi4spn, $i:ident, $v:ident) => {
+        RV64GCInstruction::$v(c_rs2_prime($i), c_addi4spn_imm($i))
+    };
+    (display caddi4spn (), $s:expr, $f:ident, $n:literal, $v:ident) => {
+        if let RV64GCInstruction::$v(rd, imm) = $s {
+            return write!($f, "{} x{rd}, x2, {imm}", $n);
+        }
+    };
+
+    // NOTE: Loads are (rd', rs1', imm), stores (rs1', rs2', imm)
+    (decode cld, $i:ident, $v:ident) => {
+        RV64GCInstruction::$v(c_rs2_prime($i), c_rd_prime($i), c_d_imm($i))
+    };
+    (decode clw, $i:ident, $v:ident) => {
+        RV64GCInstruction::$v(c_rs2_prime($i), c_rd_prime($i), c_w_imm($i))
+    };
+    (display cld ($a:ident), $s:expr, $f:ident, $n:literal, $v:ident) => {
+        if let RV64GCInstruction::$v(rd, rs1, imm) = $s {
+            let a = stringify!($a);
+            return write!($f, "{} {a}{rd}, {imm}(x{rs1})", $n);
+        }
+    };
+    (display clw ($a:ident), $s:expr, $f:ident, $n:literal, $v:ident) => {
+        operands!(display cld ($a), $s, $f, $n, $v)
+    };
+
+    (decode csd, $i:ident, $v:ident) => {
+        RV64GCInstruction::$v(c_rd_prime($i), c_rs2_prime($i), c_d_imm($i))
+    };
+    (decode csw, $i:ident, $v:ident) => {
+        RV64GCInstruction::$v(c_rd_prime($i), c_rs2_prime($i), c_w_imm($i))
+    };
+    (display csd ($a:ident), $s:expr, $f:ident, $n:literal, $v:ident) => {
+        if let RV64GCInstruction::$v(rs1, rs2, imm) = $s {
+            let a = stringify!($a);
+            return write!($f, "{} {a}{rs2}, {imm}(x{rs1})", $n);
+        }
+    };
+    (display csw ($a:ident), $s:expr, $f:ident, $n:literal, $v:ident) => {
+        operands!(display csd ($a), $s, $f, $n, $v)
+    };
+
+    (decode cb, $i:ident, $v:ident) => { RV64GCInstruction::$v(c_rd_prime($i), c_b_imm($i)) };
+    (display cb (), $s:expr, $f:ident, $n:literal, $v:ident) => {
+        if let RV64GCInstruction::$v(rs1, imm) = $s {
+            return write!($f, "{} x{rs1}, {}", $n, sign_extend((*imm).into(), 9));
+        }
+    };
 
-pub const fn is_rv64m_mul_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0000_0010_0000_0000_0000_0000_0011_0011;
-    let mask: u32 = 0b1111_1110_0000_0000_0111_0000_0111_1111;
+    (decode cj, $i:ident, $v:ident) => { RV64GCInstruction::$v(c_j_imm($i)) };
+    (display cj (), $s:expr, $f:ident, $n:literal, $v:ident) => {
+        if let RV64GCInstruction::$v(imm) = $s {
+            return write!($f, "{} {}", $n, sign_extend((*imm).into(), 12));
+        }
+    };
+
+    (decode ca, $i:ident, $v:ident) => { RV64GCInstruction::$v(c_rd_prime($i), c_rs2_prime($i)) };
+    (display ca (), $s:expr, $f:ident, $n:literal, $v:ident) => {
+        operands!(display cr (), $s, $f, $n, $v)
+    };
+
+    // NOTE: Vector formats. Most take `vd, vs2, vs1/rs1/imm` and print a trailing `v0.t`
+    // when they're masked
+    (decode vv, $i:ident, $v:ident) => { RV64GCInstruction::$v(v_operands($i, VSrc::V(rs1($i)))) };
+    (decode vx, $i:ident, $v:ident) => { RV64GCInstruction::$v(v_operands($i, VSrc::X(rs1($i)))) };
+    (decode vf, $i:ident, $v:ident) => { RV64GCInstruction::$v(v_operands($i, VSrc::F(rs1($i)))) };
+    (decode vi, $i:ident, $v:ident) => {
+        RV64GCInstruction::$v(v_operands($i, VSrc::I(sign_extend(rs1($i).into(), 5) as i8)))
+    };
+    (decode vui, $i:ident, $v:ident) => {
+        RV64GCInstruction::$v(v_operands($i, VSrc::I(rs1($i) as i8)))
+    };
+    (display vv (), $s:expr, $f:ident, $n:literal, $v:ident) => {
+        if let RV64GCInstruction::$v(VOperands { vd, vs2, src: VSrc::V(vs1), vm }) = $s {
+            return write!($f, "{} v{vd}, v{vs2}, v{vs1}{}", $n, masked(*vm));
+        }
+    };
+    (display vx (), $s:expr, $f:ident, $n:literal, $v:ident) => {
+        if let RV64GCInstruction::$v(VOperands { vd, vs2, src: VSrc::X(rs1), vm }) = $s {
+            return write!($f, "{} v{vd}, v{vs2}, x{rs1}{}", $n, masked(*vm));
+        }
+    };
+    (display vf (), $s:expr, $f:ident, $n:literal, $v:ident) => {
+        if let RV64GCInstruction::$v(VOperands { vd, vs2, src: VSrc::F(rs1), vm }) = $s {
+            return write!($f, "{} v{vd}, v{vs2}, f{rs1}{}", $n, masked(*vm));
+        }
+    };
+    (display vi (), $s:expr, $f:ident, $n:literal, $v:ident) => {
+        if let RV64GCInstruction::$v(VOperands { vd, vs2, src: VSrc::I(imm), vm }) = $s {
+            return write!($f, "{} v{vd}, v{vs2}, {imm}{}", $n, masked(*vm));
+        }
+    };
+    (display vui (), $s:expr, $f:ident, $n:literal, $v:ident) => {
+        operands!(display vi (), $s, $f, $n, $v)
+    };
+
+    // NOTE: vadc, vmerge and friends, which always take v0
+    (decode vvm, $i:ident, $v:ident) => { operands!(decode vv, $i, $v) };
+    (decode vxm, $i:ident, $v:ident) => { operands!(decode vx, $i, $v) };
+    (decode vim, $i:ident, $v:ident) => { operands!(decode vi, $i, $v) };
+    (decode vfm, $i:ident, $v:ident) => { operands!(decode vf, $i, $v) };
+    (display vvm (), $s:expr, $f:ident, $n:literal, $v:ident) => {
+        if let RV64GCInstruction::$v(VOperands { vd, vs2, src: VSrc::V(vs1), vm: false }) = $s {
+            return write!($f, "{} v{vd}, v{vs2}, v{vs1}, v0", $n);
+        }
+    };
+    (display vxm (), $s:expr, $f:ident, $n:literal, $v:ident) => {
+        if let RV64GCInstruction::$v(VOperands { vd, vs2, src: VSrc::X(rs1), vm: false }) = $s {
+            return write!($f, "{} v{vd}, v{vs2}, x{rs1}, v0", $n);
+        }
+    };
+    (display vim (), $s:expr, $f:ident, $n:literal, $v:ident) => {
+        if let RV64GCInstruction::$v(VOperands { vd, vs2, src: VSrc::I(imm), vm: false }) = $s {
+            return write!($f, "{} v{vd}, v{vs2}, {imm}, v0", $n);
+        }
+    };
+    (display vfm (), $s:expr, $f:ident, $n:literal, $v:ident) => {
+        if let RV64GCInstruction::$v(VOperands { vd, vs2, src: VSrc::F(rs1), vm: false }) = $s {
+            return write!($f, "{} v{vd}, v{vs2}, f{rs1}, v0", $n);
+        }
+    };
+
+    // NOTE: The multiply-adds are written `vd, vs1/rs1, vs2`
+    (decode mvv, $i:ident, $v:ident) => { operands!(decode vv, $i, $v) };
+    (decode mvx, $i:ident, $v:ident) => { operands!(decode vx, $i, $v) };
+    (decode mvf, $i:ident, $v:ident) => { operands!(decode vf, $i, $v) };
+    (display mvv (), $s:expr, $f:ident, $n:literal, $v:ident) => {
+        if let RV64GCInstruction::$v(VOperands { vd, vs2, src: VSrc::V(vs1), vm }) = $s {
+            return write!($f, "{} v{vd}, v{vs1}, v{vs2}{}", $n, masked(*vm));
+        }
+    };
+    (display mvx (), $s:expr, $f:ident, $n:literal, $v:ident) => {
+        if let RV64GCInstruction::$v(VOperands { vd, vs2, src: VSrc::X(rs1), vm }) = $s {
+            return write!($f, "{} v{vd}, x{rs1}, v{vs2}{}", $n, masked(*vm));
+        }
+    };
+    (display mvf (), $s:expr, $f:ident, $n:literal, $v:ident) => {
+        if let RV64GCInstruction::$v(VOperands { vd, vs2, src: VSrc::F(rs1), vm }) = $s {
+            return write!($f, "{} v{vd}, f{rs1}, v{vs2}{}", $n, masked(*vm));
+        }
+    };
+
+    (decode vmvv, $i:ident, $v:ident) => { RV64GCInstruction::$v(rd($i), VSrc::V(rs1($i))) };
+    (decode vmvx, $i:ident, $v:ident) => { RV64GCInstruction::$v(rd($i), VSrc::X(rs1($i))) };
+    (decode vmvi, $i:ident, $v:ident) => {
+        RV64GCInstruction::$v(rd($i), VSrc::I(sign_extend(rs1($i).into(), 5) as i8))
+    };
+    (display vmvv (), $s:expr, $f:ident, $n:literal, $v:ident) => {
+        if let RV64GCInstruction::$v(vd, VSrc::V(vs1)) = $s {
+            return write!($f, "{} v{vd}, v{vs1}", $n);
+        }
+    };
+    (display vmvx (), $s:expr, $f:ident, $n:literal, $v:ident) => {
+        if let RV64GCInstruction::$v(vd, VSrc::X(rs1)) = $s {
+            return write!($f, "{} v{vd}, x{rs1}", $n);
+        }
+    };
+    (display vmvi (), $s:expr, $f:ident, $n:literal, $v:ident) => {
+        if let RV64GCInstruction::$v(vd, VSrc::I(imm)) = $s {
+            return write!($f, "{} v{vd}, {imm}", $n);
+        }
+    };
 
-    let extracted = ins & mask;
-    extracted == format
-}
+    // NOTE: Unary instructions, the vs1 field picks the operation
+    (decode v2, $i:ident, $v:ident) => { RV64GCInstruction::$v(rd($i), rs2($i), $i.bit(25)) };
+    (display v2 (), $s:expr, $f:ident, $n:literal, $v:ident) => {
+        if let RV64GCInstruction::$v(vd, vs2, vm) = $s {
+            return write!($f, "{} v{vd}, v{vs2}{}", $n, masked(*vm));
+        }
+    };
 
-pub const fn is_rv64m_mulh_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0000_0010_0000_0000_0001_0000_0011_0011;
-    let mask: u32 = 0b1111_1110_0000_0000_0111_0000_0111_1111;
+    (decode xvm, $i:ident, $v:ident) => { operands!(decode v2, $i, $v) };
+    (display xvm (), $s:expr, $f:ident, $n:literal, $v:ident) => {
+        if let RV64GCInstruction::$v(rd, vs2, vm) = $s {
+            return write!($f, "{} x{rd}, v{vs2}{}", $n, masked(*vm));
+        }
+    };
 
-    let extracted = ins & mask;
-    extracted == format
-}
+    (decode vid, $i:ident, $v:ident) => { RV64GCInstruction::$v(rd($i), $i.bit(25)) };
+    (display vid (), $s:expr, $f:ident, $n:literal, $v:ident) => {
+        if let RV64GCInstruction::$v(vd, vm) = $s {
+            return write!($f, "{} v{vd}{}", $n, masked(*vm));
+        }
+    };
+
+    // NOTE: Element 0 to a scalar register
+    (decode r2v, $i:ident, $v:ident) => { RV64GCInstruction::$v(rd($i), rs2($i)) };
+    (display r2v ($a:ident), $s:expr, $f:ident, $n:literal, $v:ident) => {
+        if let RV64GCInstruction::$v(rd, vs2) = $s {
+            let a = stringify!($a);
+            return write!($f, "{} {a}{rd}, v{vs2}", $n);
+        }
+    };
 
-pub const fn is_rv64m_mulhsu_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0000_0010_0000_0000_0010_0000_0011_0011;
-    let mask: u32 = 0b1111_1110_0000_0000_0111_0000_0111_1111;
+    // NOTE: Mask instructions are `vd, vs2, vs1`, and never masked
+    (decode mm, $i:ident, $v:ident) => { RV64GCInstruction::$v(rd($i), rs2($i), rs1($i)) };
+    (display mm (), $s:expr, $f:ident, $n:literal, $v:ident) => {
+        if let RV64GCInstruction::$v(vd, vs2, vs1) = $s {
+            return write!($f, "{} v{vd}, v{vs2}, v{vs1}", $n);
+        }
+    };
 
-    let extracted = ins & mask;
-    extracted == format
-}
+    // NOTE: vmv<nr>r.v keeps nr - 1 in the vs1 field
+    (decode vmvr, $i:ident, $v:ident) => { RV64GCInstruction::$v(rd($i), rs2($i), rs1($i) + 1) };
+    (display vmvr (), $s:expr, $f:ident, $n:literal, $v:ident) => {
+        if let RV64GCInstruction::$v(vd, vs2, nregs) = $s {
+            return write!($f, "vmv{nregs}r.v v{vd}, v{vs2}");
+        }
+    };
+
+    (decode vsetvli, $i:ident, $v:ident) => {
+        RV64GCInstruction::$v(rd($i), rs1($i), $i.bit_range(20..31) as u16)
+    };
+    (display vsetvli (), $s:expr, $f:ident, $n:literal, $v:ident) => {
+        if let RV64GCInstruction::$v(rd, rs1, vtypei) = $s {
+            return write!($f, "{} x{rd}, x{rs1}, {}", $n, format_vtype((*vtypei).into()));
+        }
+    };
+
+    (decode vsetivli, $i:ident, $v:ident) => {
+        RV64GCInstruction::$v(rd($i), rs1($i), $i.bit_range(20..30) as u16)
+    };
+    (display vsetivli (), $s:expr, $f:ident, $n:literal, $v:ident) => {
+        if let RV64GCInstruction::$v(rd, uimm, vtypei) = $s {
+            return write!($f, "{} x{rd}, {uimm}, {}", $n, format_vtype((*vtypei).into()));
+        }
+    };
+
+    // NOTE: One row per width covers every number of fields, the mnemonic is worked out from
+    // both
+    (decode vmem, $i:ident, $v:ident) => { RV64GCInstruction::$v(rd($i), rs1($i), v_mem($i)) };
+    (display vmem (), $s:expr, $f:ident, $n:literal, $v:ident) => {
+        if let RV64GCInstruction::$v(vd, rs1, mem) = $s {
+            return write!($f, "{} v{vd}, (x{rs1}){}", v_mem_name($n, mem), masked(mem.vm));
+        }
+    };
+
+    (decode vmems, $i:ident, $v:ident) => {
+        RV64GCInstruction::$v(rd($i), rs1($i), rs2($i), v_mem($i))
+    };
+    (display vmems (), $s:expr, $f:ident, $n:literal, $v:ident) => {
+        if let RV64GCInstruction::$v(vd, rs1, rs2, mem) = $s {
+            let name = v_mem_name($n, mem);
+            return write!($f, "{name} v{vd}, (x{rs1}), x{rs2}{}", masked(mem.vm));
+        }
+    };
 
-pub const fn is_rv64m_mulhu_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0000_0010_0000_0000_0011_0000_0011_0011;
-    let mask: u32 = 0b1111_1110_0000_0000_0111_0000_0111_1111;
+    (decode vmemx, $i:ident, $v:ident) => { operands!(decode vmems, $i, $v) };
+    (display vmemx (), $s:expr, $f:ident, $n:literal, $v:ident) => {
+        if let RV64GCInstruction::$v(vd, rs1, vs2, mem) = $s {
+            let name = v_mem_name($n, mem);
+            return write!($f, "{name} v{vd}, (x{rs1}), v{vs2}{}", masked(mem.vm));
+        }
+    };
 
-    let extracted = ins & mask;
-    extracted == format
-}
+    (decode vlr, $i:ident, $v:ident) => { operands!(decode vmem, $i, $v) };
+    (display vlr (), $s:expr, $f:ident, $n:literal, $v:ident) => {
+        if let RV64GCInstruction::$v(vd, rs1, mem) = $s {
+            return write!($f, "vl{}re{}.v v{vd}, (x{rs1})", mem.nf, mem.eew);
+        }
+    };
 
-pub const fn is_rv64m_div_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0000_0010_0000_0000_0100_0000_0011_0011;
-    let mask: u32 = 0b1111_1110_0000_0000_0111_0000_0111_1111;
+    (decode vsr, $i:ident, $v:ident) => { operands!(decode vmem, $i, $v) };
+    (display vsr (), $s:expr, $f:ident, $n:literal, $v:ident) => {
+        if let RV64GCInstruction::$v(vs3, rs1, mem) = $s {
+            return write!($f, "vs{}r.v v{vs3}, (x{rs1})", mem.nf);
+        }
+    };
 
-    let extracted = ins & mask;
-    extracted == format
+    (decode vlm, $i:ident, $v:ident) => { RV64GCInstruction::$v(rd($i), rs1($i)) };
+    (display vlm (), $s:expr, $f:ident, $n:literal, $v:ident) => {
+        if let RV64GCInstruction::$v(vd, rs1) = $s {
+            return write!($f, "{} v{vd}, (x{rs1})", $n);
+        }
+    };
+
+    // NOTE: Reserved encodings, named after the instruction they're carved out of
+    (decode reserved, $i:ident, $v:ident) => { RV64GCInstruction::$v($i) };
+    (display reserved (), $s:expr, $f:ident, $n:literal, $v:ident) => {};
+    (reserved reserved) => { true };
+    (reserved $format:ident) => { false };
+}
+
+/// Generates [`ENCODINGS`] and `Display` for [`RV64GCInstruction`] from the rows of the
+/// instruction table: `name, mask, value, format (register classes) => Variant`
+macro_rules! instructions {
+    ($(
+        $name:literal, $mask:literal, $value:literal,
+        $format:ident $(($($class:ident)*))? => $variant:ident;
+    )*) => {
+        /// Every instruction the decoder knows about. When several rows match, the first one
+        /// wins, so more specific encodings come first
+        pub static ENCODINGS: &[Encoding] = &[$(
+            Encoding {
+                name: $name,
+                mask: $mask,
+                value: $value,
+                decode: |ins| operands!(decode $format, ins, $variant),
+                reserved: operands!(reserved $format),
+            },
+        )*];
+
+        impl Display for RV64GCInstruction {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                $( operands!(display $format ($($($class)*)?), self, f, $name, $variant); )*
+
+                write!(f, "{self:?}")
+            }
+        }
+    };
+}
+
+instructions! {
+    // NOTE: RV64I
+    "lui",        0x0000_007F, 0x0000_0037, u             => Lui;
+    "auipc",      0x0000_007F, 0x0000_0017, u             => Auipc;
+    "jal",        0x0000_007F, 0x0000_006F, jal           => Jal;
+    "jalr",       0x0000_007F, 0x0000_0067, load(x)       => Jalr;
+    "beq",        0x0000_707F, 0x0000_0063, branch        => Beq;
+    "bne",        0x0000_707F, 0x0000_1063, branch        => Bne;
+    "blt",        0x0000_707F, 0x0000_4063, branch        => Blt;
+    "bge",        0x0000_707F, 0x0000_5063, branch        => Bge;
+    "bltu",       0x0000_707F, 0x0000_6063, branch        => Bltu;
+    "bgeu",       0x0000_707F, 0x0000_7063, branch        => Bgeu;
+    "lb",         0x0000_707F, 0x0000_0003, load(x)       => Lb;
+    "lh",         0x0000_707F, 0x0000_1003, load(x)       => Lh;
+    "lw",         0x0000_707F, 0x0000_2003, load(x)       => Lw;
+    "lbu",        0x0000_707F, 0x0000_4003, load(x)       => Lbu;
+    "lhu",        0x0000_707F, 0x0000_5003, load(x)       => Lhu;
+    "sb",         0x0000_707F, 0x0000_0023, store(x)      => Sb;
+    "sh",         0x0000_707F, 0x0000_1023, store(x)      => Sh;
+    "sw",         0x0000_707F, 0x0000_2023, store(x)      => Sw;
+    "addi",       0x0000_707F, 0x0000_0013, i             => Addi;
+    "slti",       0x0000_707F, 0x0000_2013, i             => Slti;
+    "sltiu",      0x0000_707F, 0x0000_3013, iu            => Sltiu;
+    "xori",       0x0000_707F, 0x0000_4013, i             => Xori;
+    "ori",        0x0000_707F, 0x0000_6013, i             => Ori;
+    "andi",       0x0000_707F, 0x0000_7013, i             => Andi;
+    "slli",       0xF800_707F, 0x0000_1013, shamt         => Slli;
+    "srli",       0xF800_707F, 0x0000_5013, shamt         => Srli;
+    "srai",       0xFC00_707F, 0x4000_5013, shamt         => Srai;
+    "add",        0xFE00_707F, 0x0000_0033, r(x x x)      => Add;
+    "sub",        0xFE00_707F, 0x4000_0033, r(x x x)      => Sub;
+    "sll",        0xFE00_707F, 0x0000_1033, r(x x x)      => Sll;
+    "slt",        0xFE00_707F, 0x0000_2033, r(x x x)      => Slt;
+    "sltu",       0xFE00_707F, 0x0000_3033, r(x x x)      => Sltu;
+    "xor",        0xFE00_707F, 0x0000_4033, r(x x x)      => Xor;
+    "srl",        0xFE00_707F, 0x0000_5033, r(x x x)      => Srl;
+    "sra",        0xFE00_707F, 0x4000_5033, r(x x x)      => Sra;
+    "or",         0xFE00_707F, 0x0000_6033, r(x x x)      => Or;
+    "and",        0xFE00_707F, 0x0000_7033, r(x x x)      => And;
+    "fence",      0x0000_707F, 0x0000_000F, fence         => Fence;
+    "ecall",      0xFFFF_FFFF, 0x0000_0073, none          => Ecall;
+    "ebreak",     0xFFFF_FFFF, 0x0010_0073, none          => Ebreak;
+
+    // NOTE: RV64I, word and doubleword instructions
+    "lwu",        0x0000_707F, 0x0000_6003, loadu(x)      => Lwu;
+    "ld",         0x0000_707F, 0x0000_3003, load(x)       => Ld;
+    "sd",         0x0000_707F, 0x0000_3023, store(x)      => Sd;
+    "addiw",      0x0000_707F, 0x0000_001B, iu            => Addiw;
+    "slliw",      0xFE00_707F, 0x0000_101B, shamtw        => Slliw;
+    "srliw",      0xFE00_707F, 0x0000_501B, shamtw        => Srliw;
+    "sraiw",      0xFE00_707F, 0x4000_501B, shamtw        => Sraiw;
+    "addw",       0xFE00_707F, 0x0000_003B, r(x x x)      => Addw;
+    "subw",       0xFE00_707F, 0x4000_003B, r(x x x)      => Subw;
+    "sllw",       0xFE00_707F, 0x0000_103B, r(x x x)      => Sllw;
+    "srlw",       0xFE00_707F, 0x0000_503B, r(x x x)      => Srlw;
+    "sraw",       0xFE00_707F, 0x4000_503B, r(x x x)      => Sraw;
+
+    // NOTE: Zifencei
+    "fence.i",    0x0000_707F, 0x0000_100F, none          => FenceI;
+
+    // NOTE: Zicsr
+    "csrrw",      0x0000_707F, 0x0000_1073, csr           => Csrrw;
+    "csrrs",      0x0000_707F, 0x0000_2073, csr           => Csrrs;
+    "csrrc",      0x0000_707F, 0x0000_3073, csr           => Csrrc;
+    "csrrwi",     0x0000_707F, 0x0000_5073, csri          => Csrrwi;
+    "csrrsi",     0x0000_707F, 0x0000_6073, csri          => Csrrsi;
+    "csrrci",     0x0000_707F, 0x0000_7073, csri          => Csrrci;
+
+    // NOTE: Privileged, illegal in U-mode except for wfi
+    "uret",       0xFFFF_FFFF, 0x0020_0073, none          => Uret;
+    "sret",       0xFFFF_FFFF, 0x1020_0073, none          => Sret;
+    "mret",       0xFFFF_FFFF, 0x3020_0073, none          => Mret;
+    "wfi",        0xFFFF_FFFF, 0x1050_0073, none          => Wfi;
+    "sfence.vma", 0xFE00_7FFF, 0x1200_0073, r(x x x)      => SfenceVma;
+
+    // NOTE: RV64M
+    "mul",        0xFE00_707F, 0x0200_0033, r(x x x)      => Mul;
+    "mulh",       0xFE00_707F, 0x0200_1033, r(x x x)      => Mulh;
+    "mulhsu",     0xFE00_707F, 0x0200_2033, r(x x x)      => Mulhsu;
+    "mulhu",      0xFE00_707F, 0x0200_3033, r(x x x)      => Mulhu;
+    "div",        0xFE00_707F, 0x0200_4033, r(x x x)      => Div;
+    "divu",       0xFE00_707F, 0x0200_5033, r(x x x)      => Divu;
+    "rem",        0xFE00_707F, 0x0200_6033, r(x x x)      => Rem;
+    "remu",       0xFE00_707F, 0x0200_7033, r(x x x)      => Remu;
+    "mulw",       0xFE00_707F, 0x0200_003B, r(x x x)      => Mulw;
+    "divw",       0xFE00_707F, 0x0200_403B, r(x x x)      => Divw;
+    "divuw",      0xFE00_707F, 0x0200_503B, r(x x x)      => Divuw;
+    "remw",       0xFE00_707F, 0x0200_603B, r(x x x)      => Remw;
+    "remuw",      0xFE00_707F, 0x0200_703B, r(x x x)      => Remuw;
+
+    // NOTE: Zba
+    "add.uw",     0xFE00_707F, 0x0800_003B, r(x x x)      => Adduw;
+    "sh1add",     0xFE00_707F, 0x2000_2033, r(x x x)      => Sh1add;
+    "sh2add",     0xFE00_707F, 0x2000_4033, r(x x x)      => Sh2add;
+    "sh3add",     0xFE00_707F, 0x2000_6033, r(x x x)      => Sh3add;
+    "sh1add.uw",  0xFE00_707F, 0x2000_203B, r(x x x)      => Sh1adduw;
+    "sh2add.uw",  0xFE00_707F, 0x2000_403B, r(x x x)      => Sh2adduw;
+    "sh3add.uw",  0xFE00_707F, 0x2000_603B, r(x x x)      => Sh3adduw;
+    "slli.uw",    0xFC00_707F, 0x0800_101B, shamt         => Slliuw;
+
+    // NOTE: Zbb. zext.h is packw with rs2 = x0, which isn't in Zbb otherwise
+    "andn",       0xFE00_707F, 0x4000_7033, r(x x x)      => Andn;
+    "orn",        0xFE00_707F, 0x4000_6033, r(x x x)      => Orn;
+    "xnor",       0xFE00_707F, 0x4000_4033, r(x x x)      => Xnor;
+    "clz",        0xFFF0_707F, 0x6000_1013, r2(x x)       => Clz;
+    "clzw",       0xFFF0_707F, 0x6000_101B, r2(x x)       => Clzw;
+    "ctz",        0xFFF0_707F, 0x6010_1013, r2(x x)       => Ctz;
+    "ctzw",       0xFFF0_707F, 0x6010_101B, r2(x x)       => Ctzw;
+    "cpop",       0xFFF0_707F, 0x6020_1013, r2(x x)       => Cpop;
+    "cpopw",      0xFFF0_707F, 0x6020_101B, r2(x x)       => Cpopw;
+    "max",        0xFE00_707F, 0x0A00_6033, r(x x x)      => Max;
+    "maxu",       0xFE00_707F, 0x0A00_7033, r(x x x)      => Maxu;
+    "min",        0xFE00_707F, 0x0A00_4033, r(x x x)      => Min;
+    "minu",       0xFE00_707F, 0x0A00_5033, r(x x x)      => Minu;
+    "sext.b",     0xFFF0_707F, 0x6040_1013, r2(x x)       => Sextb;
+    "sext.h",     0xFFF0_707F, 0x6050_1013, r2(x x)       => Sexth;
+    "zext.h",     0xFFF0_707F, 0x0800_403B, r2(x x)       => Zexth;
+    "rol",        0xFE00_707F, 0x6000_1033, r(x x x)      => Rol;
+    "rolw",       0xFE00_707F, 0x6000_103B, r(x x x)      => Rolw;
+    "ror",        0xFE00_707F, 0x6000_5033, r(x x x)      => Ror;
+    "rorw",       0xFE00_707F, 0x6000_503B, r(x x x)      => Rorw;
+    "rori",       0xFC00_707F, 0x6000_5013, shamt         => Rori;
+    "roriw",      0xFE00_707F, 0x6000_501B, shamtw        => Roriw;
+    "orc.b",      0xFFF0_707F, 0x2870_5013, r2(x x)       => Orcb;
+    "rev8",       0xFFF0_707F, 0x6B80_5013, r2(x x)       => Rev8;
+
+    // NOTE: Zbc
+    "clmul",      0xFE00_707F, 0x0A00_1033, r(x x x)      => Clmul;
+    "clmulh",     0xFE00_707F, 0x0A00_3033, r(x x x)      => Clmulh;
+    "clmulr",     0xFE00_707F, 0x0A00_2033, r(x x x)      => Clmulr;
+
+    // NOTE: Zbs
+    "bclr",       0xFE00_707F, 0x4800_1033, r(x x x)      => Bclr;
+    "bclri",      0xFC00_707F, 0x4800_1013, shamt         => Bclri;
+    "bext",       0xFE00_707F, 0x4800_5033, r(x x x)      => Bext;
+    "bexti",      0xFC00_707F, 0x4800_5013, shamt         => Bexti;
+    "binv",       0xFE00_707F, 0x6800_1033, r(x x x)      => Binv;
+    "binvi",      0xFC00_707F, 0x6800_1013, shamt         => Binvi;
+    "bset",       0xFE00_707F, 0x2800_1033, r(x x x)      => Bset;
+    "bseti",      0xFC00_707F, 0x2800_1013, shamt         => Bseti;
+
+    // NOTE: V, configuration
+    "vsetvli",    0x8000_707F, 0x0000_7057, vsetvli       => Vsetvli;
+    "vsetivli",   0xC000_707F, 0xC000_7057, vsetivli      => Vsetivli;
+    "vsetvl",     0xFE00_707F, 0x8000_7057, r(x x x)      => Vsetvl;
+
+    // NOTE: V, loads and stores
+    "vle8.v",     0x1DF0_707F, 0x0000_0007, vmem          => Vle;
+    "vle8ff.v",   0x1DF0_707F, 0x0100_0007, vmem          => Vleff;
+    "vlse8.v",    0x1C00_707F, 0x0800_0007, vmems         => Vlse;
+    "vluxei8.v",  0x1C00_707F, 0x0400_0007, vmemx         => Vluxei;
+    "vloxei8.v",  0x1C00_707F, 0x0C00_0007, vmemx         => Vloxei;
+    "vl1re8.v",   0x1FF0_707F, 0x0280_0007, vlr           => Vlr;
+    "vle16.v",    0x1DF0_707F, 0x0000_5007, vmem          => Vle;
+    "vle16ff.v",  0x1DF0_707F, 0x0100_5007, vmem          => Vleff;
+    "vlse16.v",   0x1C00_707F, 0x0800_5007, vmems         => Vlse;
+    "vluxei16.v", 0x1C00_707F, 0x0400_5007, vmemx         => Vluxei;
+    "vloxei16.v", 0x1C00_707F, 0x0C00_5007, vmemx         => Vloxei;
+    "vl1re16.v",  0x1FF0_707F, 0x0280_5007, vlr           => Vlr;
+    "vle32.v",    0x1DF0_707F, 0x0000_6007, vmem          => Vle;
+    "vle32ff.v",  0x1DF0_707F, 0x0100_6007, vmem          => Vleff;
+    "vlse32.v",   0x1C00_707F, 0x0800_6007, vmems         => Vlse;
+    "vluxei32.v", 0x1C00_707F, 0x0400_6007, vmemx         => Vluxei;
+    "vloxei32.v", 0x1C00_707F, 0x0C00_6007, vmemx         => Vloxei;
+    "vl1re32.v",  0x1FF0_707F, 0x0280_6007, vlr           => Vlr;
+    "vle64.v",    0x1DF0_707F, 0x0000_7007, vmem          => Vle;
+    "vle64ff.v",  0x1DF0_707F, 0x0100_7007, vmem          => Vleff;
+    "vlse64.v",   0x1C00_707F, 0x0800_7007, vmems         => Vlse;
+    "vluxei64.v", 0x1C00_707F, 0x0400_7007, vmemx         => Vluxei;
+    "vloxei64.v", 0x1C00_707F, 0x0C00_7007, vmemx         => Vloxei;
+    "vl1re64.v",  0x1FF0_707F, 0x0280_7007, vlr           => Vlr;
+    "vlm.v",      0xFFF0_707F, 0x02B0_0007, vlm           => Vlm;
+    "vse8.v",     0x1DF0_707F, 0x0000_0027, vmem          => Vse;
+    "vsse8.v",    0x1C00_707F, 0x0800_0027, vmems         => Vsse;
+    "vsuxei8.v",  0x1C00_707F, 0x0400_0027, vmemx         => Vsuxei;
+    "vsoxei8.v",  0x1C00_707F, 0x0C00_0027, vmemx         => Vsoxei;
+    "vse16.v",    0x1DF0_707F, 0x0000_5027, vmem          => Vse;
+    "vsse16.v",   0x1C00_707F, 0x0800_5027, vmems         => Vsse;
+    "vsuxei16.v", 0x1C00_707F, 0x0400_5027, vmemx         => Vsuxei;
+    "vsoxei16.v", 0x1C00_707F, 0x0C00_5027, vmemx         => Vsoxei;
+    "vse32.v",    0x1DF0_707F, 0x0000_6027, vmem          => Vse;
+    "vsse32.v",   0x1C00_707F, 0x0800_6027, vmems         => Vsse;
+    "vsuxei32.v", 0x1C00_707F, 0x0400_6027, vmemx         => Vsuxei;
+    "vsoxei32.v", 0x1C00_707F, 0x0C00_6027, vmemx         => Vsoxei;
+    "vse64.v",    0x1DF0_707F, 0x0000_7027, vmem          => Vse;
+    "vsse64.v",   0x1C00_707F, 0x0800_7027, vmems         => Vsse;
+    "vsuxei64.v", 0x1C00_707F, 0x0400_7027, vmemx         => Vsuxei;
+    "vsoxei64.v", 0x1C00_707F, 0x0C00_7027, vmemx         => Vsoxei;
+    "vs1r.v",     0x1FF0_707F, 0x0280_0027, vsr           => Vsr;
+    "vsm.v",      0xFFF0_707F, 0x02B0_0027, vlm           => Vsm;
+
+    // NOTE: V, integer arithmetic
+    "vadd.vv",    0xFC00_707F, 0x0000_0057, vv            => Vadd;
+    "vadd.vx",    0xFC00_707F, 0x0000_4057, vx            => Vadd;
+    "vadd.vi",    0xFC00_707F, 0x0000_3057, vi            => Vadd;
+    "vsub.vv",    0xFC00_707F, 0x0800_0057, vv            => Vsub;
+    "vsub.vx",    0xFC00_707F, 0x0800_4057, vx            => Vsub;
+    "vrsub.vx",   0xFC00_707F, 0x0C00_4057, vx            => Vrsub;
+    "vrsub.vi",   0xFC00_707F, 0x0C00_3057, vi            => Vrsub;
+    "vminu.vv",   0xFC00_707F, 0x1000_0057, vv            => Vminu;
+    "vminu.vx",   0xFC00_707F, 0x1000_4057, vx            => Vminu;
+    "vmin.vv",    0xFC00_707F, 0x1400_0057, vv            => Vmin;
+    "vmin.vx",    0xFC00_707F, 0x1400_4057, vx            => Vmin;
+    "vmaxu.vv",   0xFC00_707F, 0x1800_0057, vv            => Vmaxu;
+    "vmaxu.vx",   0xFC00_707F, 0x1800_4057, vx            => Vmaxu;
+    "vmax.vv",    0xFC00_707F, 0x1C00_0057, vv            => Vmax;
+    "vmax.vx",    0xFC00_707F, 0x1C00_4057, vx            => Vmax;
+    "vand.vv",    0xFC00_707F, 0x2400_0057, vv            => Vand;
+    "vand.vx",    0xFC00_707F, 0x2400_4057, vx            => Vand;
+    "vand.vi",    0xFC00_707F, 0x2400_3057, vi            => Vand;
+    "vor.vv",     0xFC00_707F, 0x2800_0057, vv            => Vor;
+    "vor.vx",     0xFC00_707F, 0x2800_4057, vx            => Vor;
+    "vor.vi",     0xFC00_707F, 0x2800_3057, vi            => Vor;
+    "vxor.vv",    0xFC00_707F, 0x2C00_0057, vv            => Vxor;
+    "vxor.vx",    0xFC00_707F, 0x2C00_4057, vx            => Vxor;
+    "vxor.vi",    0xFC00_707F, 0x2C00_3057, vi            => Vxor;
+    "vadc.vvm",   0xFE00_707F, 0x4000_0057, vvm           => Vadc;
+    "vadc.vxm",   0xFE00_707F, 0x4000_4057, vxm           => Vadc;
+    "vadc.vim",   0xFE00_707F, 0x4000_3057, vim           => Vadc;
+    "vmadc.vvm",  0xFE00_707F, 0x4400_0057, vvm           => Vmadc;
+    "vmadc.vxm",  0xFE00_707F, 0x4400_4057, vxm           => Vmadc;
+    "vmadc.vim",  0xFE00_707F, 0x4400_3057, vim           => Vmadc;
+    "vmadc.vv",   0xFE00_707F, 0x4600_0057, vv            => Vmadc;
+    "vmadc.vx",   0xFE00_707F, 0x4600_4057, vx            => Vmadc;
+    "vmadc.vi",   0xFE00_707F, 0x4600_3057, vi            => Vmadc;
+    "vsbc.vvm",   0xFE00_707F, 0x4800_0057, vvm           => Vsbc;
+    "vsbc.vxm",   0xFE00_707F, 0x4800_4057, vxm           => Vsbc;
+    "vmsbc.vvm",  0xFE00_707F, 0x4C00_0057, vvm           => Vmsbc;
+    "vmsbc.vxm",  0xFE00_707F, 0x4C00_4057, vxm           => Vmsbc;
+    "vmsbc.vv",   0xFE00_707F, 0x4E00_0057, vv            => Vmsbc;
+    "vmsbc.vx",   0xFE00_707F, 0x4E00_4057, vx            => Vmsbc;
+    "vmv.v.v",    0xFFF0_707F, 0x5E00_0057, vmvv          => Vmv;
+    "vmv.v.x",    0xFFF0_707F, 0x5E00_4057, vmvx          => Vmv;
+    "vmv.v.i",    0xFFF0_707F, 0x5E00_3057, vmvi          => Vmv;
+    "vmerge.vvm", 0xFE00_707F, 0x5C00_0057, vvm           => Vmerge;
+    "vmerge.vxm", 0xFE00_707F, 0x5C00_4057, vxm           => Vmerge;
+    "vmerge.vim", 0xFE00_707F, 0x5C00_3057, vim           => Vmerge;
+    "vmseq.vv",   0xFC00_707F, 0x6000_0057, vv            => Vmseq;
+    "vmseq.vx",   0xFC00_707F, 0x6000_4057, vx            => Vmseq;
+    "vmseq.vi",   0xFC00_707F, 0x6000_3057, vi            => Vmseq;
+    "vmsne.vv",   0xFC00_707F, 0x6400_0057, vv            => Vmsne;
+    "vmsne.vx",   0xFC00_707F, 0x6400_4057, vx            => Vmsne;
+    "vmsne.vi",   0xFC00_707F, 0x6400_3057, vi            => Vmsne;
+    "vmsltu.vv",  0xFC00_707F, 0x6800_0057, vv            => Vmsltu;
+    "vmsltu.vx",  0xFC00_707F, 0x6800_4057, vx            => Vmsltu;
+    "vmslt.vv",   0xFC00_707F, 0x6C00_0057, vv            => Vmslt;
+    "vmslt.vx",   0xFC00_707F, 0x6C00_4057, vx            => Vmslt;
+    "vmsleu.vv",  0xFC00_707F, 0x7000_0057, vv            => Vmsleu;
+    "vmsleu.vx",  0xFC00_707F, 0x7000_4057, vx            => Vmsleu;
+    "vmsleu.vi",  0xFC00_707F, 0x7000_3057, vi            => Vmsleu;
+    "vmsle.vv",   0xFC00_707F, 0x7400_0057, vv            => Vmsle;
+    "vmsle.vx",   0xFC00_707F, 0x7400_4057, vx            => Vmsle;
+    "vmsle.vi",   0xFC00_707F, 0x7400_3057, vi            => Vmsle;
+    "vmsgtu.vx",  0xFC00_707F, 0x7800_4057, vx            => Vmsgtu;
+    "vmsgtu.vi",  0xFC00_707F, 0x7800_3057, vi            => Vmsgtu;
+    "vmsgt.vx",   0xFC00_707F, 0x7C00_4057, vx            => Vmsgt;
+    "vmsgt.vi",   0xFC00_707F, 0x7C00_3057, vi            => Vmsgt;
+    "vsll.vv",    0xFC00_707F, 0x9400_0057, vv            => Vsll;
+    "vsll.vx",    0xFC00_707F, 0x9400_4057, vx            => Vsll;
+    "vsll.vi",    0xFC00_707F, 0x9400_3057, vui           => Vsll;
+    "vsrl.vv",    0xFC00_707F, 0xA000_0057, vv            => Vsrl;
+    "vsrl.vx",    0xFC00_707F, 0xA000_4057, vx            => Vsrl;
+    "vsrl.vi",    0xFC00_707F, 0xA000_3057, vui           => Vsrl;
+    "vsra.vv",    0xFC00_707F, 0xA400_0057, vv            => Vsra;
+    "vsra.vx",    0xFC00_707F, 0xA400_4057, vx            => Vsra;
+    "vsra.vi",    0xFC00_707F, 0xA400_3057, vui           => Vsra;
+    "vnsrl.wv",   0xFC00_707F, 0xB000_0057, vv            => Vnsrl;
+    "vnsrl.wx",   0xFC00_707F, 0xB000_4057, vx            => Vnsrl;
+    "vnsrl.wi",   0xFC00_707F, 0xB000_3057, vui           => Vnsrl;
+    "vnsra.wv",   0xFC00_707F, 0xB400_0057, vv            => Vnsra;
+    "vnsra.wx",   0xFC00_707F, 0xB400_4057, vx            => Vnsra;
+    "vnsra.wi",   0xFC00_707F, 0xB400_3057, vui           => Vnsra;
+    "vzext.vf8",  0xFC0F_F07F, 0x4801_2057, v2            => Vzextvf8;
+    "vsext.vf8",  0xFC0F_F07F, 0x4801_A057, v2            => Vsextvf8;
+    "vzext.vf4",  0xFC0F_F07F, 0x4802_2057, v2            => Vzextvf4;
+    "vsext.vf4",  0xFC0F_F07F, 0x4802_A057, v2            => Vsextvf4;
+    "vzext.vf2",  0xFC0F_F07F, 0x4803_2057, v2            => Vzextvf2;
+    "vsext.vf2",  0xFC0F_F07F, 0x4803_A057, v2            => Vsextvf2;
+    "vdivu.vv",   0xFC00_707F, 0x8000_2057, vv            => Vdivu;
+    "vdivu.vx",   0xFC00_707F, 0x8000_6057, vx            => Vdivu;
+    "vdiv.vv",    0xFC00_707F, 0x8400_2057, vv            => Vdiv;
+    "vdiv.vx",    0xFC00_707F, 0x8400_6057, vx            => Vdiv;
+    "vremu.vv",   0xFC00_707F, 0x8800_2057, vv            => Vremu;
+    "vremu.vx",   0xFC00_707F, 0x8800_6057, vx            => Vremu;
+    "vrem.vv",    0xFC00_707F, 0x8C00_2057, vv            => Vrem;
+    "vrem.vx",    0xFC00_707F, 0x8C00_6057, vx            => Vrem;
+    "vmulhu.vv",  0xFC00_707F, 0x9000_2057, vv            => Vmulhu;
+    "vmulhu.vx",  0xFC00_707F, 0x9000_6057, vx            => Vmulhu;
+    "vmul.vv",    0xFC00_707F, 0x9400_2057, vv            => Vmul;
+    "vmul.vx",    0xFC00_707F, 0x9400_6057, vx            => Vmul;
+    "vmulhsu.vv", 0xFC00_707F, 0x9800_2057, vv            => Vmulhsu;
+    "vmulhsu.vx", 0xFC00_707F, 0x9800_6057, vx            => Vmulhsu;
+    "vmulh.vv",   0xFC00_707F, 0x9C00_2057, vv            => Vmulh;
+    "vmulh.vx",   0xFC00_707F, 0x9C00_6057, vx            => Vmulh;
+    "vmadd.vv",   0xFC00_707F, 0xA400_2057, mvv           => Vmadd;
+    "vmadd.vx",   0xFC00_707F, 0xA400_6057, mvx           => Vmadd;
+    "vnmsub.vv",  0xFC00_707F, 0xAC00_2057, mvv           => Vnmsub;
+    "vnmsub.vx",  0xFC00_707F, 0xAC00_6057, mvx           => Vnmsub;
+    "vmacc.vv",   0xFC00_707F, 0xB400_2057, mvv           => Vmacc;
+    "vmacc.vx",   0xFC00_707F, 0xB400_6057, mvx           => Vmacc;
+    "vnmsac.vv",  0xFC00_707F, 0xBC00_2057, mvv           => Vnmsac;
+    "vnmsac.vx",  0xFC00_707F, 0xBC00_6057, mvx           => Vnmsac;
+    "vwaddu.vv",  0xFC00_707F, 0xC000_2057, vv            => Vwaddu;
+    "vwaddu.vx",  0xFC00_707F, 0xC000_6057, vx            => Vwaddu;
+    "vwadd.vv",   0xFC00_707F, 0xC400_2057, vv            => Vwadd;
+    "vwadd.vx",   0xFC00_707F, 0xC400_6057, vx            => Vwadd;
+    "vwsubu.vv",  0xFC00_707F, 0xC800_2057, vv            => Vwsubu;
+    "vwsubu.vx",  0xFC00_707F, 0xC800_6057, vx            => Vwsubu;
+    "vwsub.vv",   0xFC00_707F, 0xCC00_2057, vv            => Vwsub;
+    "vwsub.vx",   0xFC00_707F, 0xCC00_6057, vx            => Vwsub;
+    "vwaddu.wv",  0xFC00_707F, 0xD000_2057, vv            => Vwadduw;
+    "vwaddu.wx",  0xFC00_707F, 0xD000_6057, vx            => Vwadduw;
+    "vwadd.wv",   0xFC00_707F, 0xD400_2057, vv            => Vwaddw;
+    "vwadd.wx",   0xFC00_707F, 0xD400_6057, vx            => Vwaddw;
+    "vwsubu.wv",  0xFC00_707F, 0xD800_2057, vv            => Vwsubuw;
+    "vwsubu.wx",  0xFC00_707F, 0xD800_6057, vx            => Vwsubuw;
+    "vwsub.wv",   0xFC00_707F, 0xDC00_2057, vv            => Vwsubw;
+    "vwsub.wx",   0xFC00_707F, 0xDC00_6057, vx            => Vwsubw;
+    "vwmulu.vv",  0xFC00_707F, 0xE000_2057, vv            => Vwmulu;
+    "vwmulu.vx",  0xFC00_707F, 0xE000_6057, vx            => Vwmulu;
+    "vwmulsu.vv", 0xFC00_707F, 0xE800_2057, vv            => Vwmulsu;
+    "vwmulsu.vx", 0xFC00_707F, 0xE800_6057, vx            => Vwmulsu;
+    "vwmul.vv",   0xFC00_707F, 0xEC00_2057, vv            => Vwmul;
+    "vwmul.vx",   0xFC00_707F, 0xEC00_6057, vx            => Vwmul;
+    "vwmaccu.vv", 0xFC00_707F, 0xF000_2057, mvv           => Vwmaccu;
+    "vwmaccu.vx", 0xFC00_707F, 0xF000_6057, mvx           => Vwmaccu;
+    "vwmacc.vv",  0xFC00_707F, 0xF400_2057, mvv           => Vwmacc;
+    "vwmacc.vx",  0xFC00_707F, 0xF400_6057, mvx           => Vwmacc;
+    "vwmaccus.vx", 0xFC00_707F, 0xF800_6057, mvx           => Vwmaccus;
+    "vwmaccsu.vv", 0xFC00_707F, 0xFC00_2057, mvv           => Vwmaccsu;
+    "vwmaccsu.vx", 0xFC00_707F, 0xFC00_6057, mvx           => Vwmaccsu;
+
+    // NOTE: V, fixed point
+    "vsaddu.vv",  0xFC00_707F, 0x8000_0057, vv            => Vsaddu;
+    "vsaddu.vx",  0xFC00_707F, 0x8000_4057, vx            => Vsaddu;
+    "vsaddu.vi",  0xFC00_707F, 0x8000_3057, vi            => Vsaddu;
+    "vsadd.vv",   0xFC00_707F, 0x8400_0057, vv            => Vsadd;
+    "vsadd.vx",   0xFC00_707F, 0x8400_4057, vx            => Vsadd;
+    "vsadd.vi",   0xFC00_707F, 0x8400_3057, vi            => Vsadd;
+    "vssubu.vv",  0xFC00_707F, 0x8800_0057, vv            => Vssubu;
+    "vssubu.vx",  0xFC00_707F, 0x8800_4057, vx            => Vssubu;
+    "vssub.vv",   0xFC00_707F, 0x8C00_0057, vv            => Vssub;
+    "vssub.vx",   0xFC00_707F, 0x8C00_4057, vx            => Vssub;
+    "vaaddu.vv",  0xFC00_707F, 0x2000_2057, vv            => Vaaddu;
+    "vaaddu.vx",  0xFC00_707F, 0x2000_6057, vx            => Vaaddu;
+    "vaadd.vv",   0xFC00_707F, 0x2400_2057, vv            => Vaadd;
+    "vaadd.vx",   0xFC00_707F, 0x2400_6057, vx            => Vaadd;
+    "vasubu.vv",  0xFC00_707F, 0x2800_2057, vv            => Vasubu;
+    "vasubu.vx",  0xFC00_707F, 0x2800_6057, vx            => Vasubu;
+    "vasub.vv",   0xFC00_707F, 0x2C00_2057, vv            => Vasub;
+    "vasub.vx",   0xFC00_707F, 0x2C00_6057, vx            => Vasub;
+    "vsmul.vv",   0xFC00_707F, 0x9C00_0057, vv            => Vsmul;
+    "vsmul.vx",   0xFC00_707F, 0x9C00_4057, vx            => Vsmul;
+    "vssrl.vv",   0xFC00_707F, 0xA800_0057, vv            => Vssrl;
+    "vssrl.vx",   0xFC00_707F, 0xA800_4057, vx            => Vssrl;
+    "vssrl.vi",   0xFC00_707F, 0xA800_3057, vui           => Vssrl;
+    "vssra.vv",   0xFC00_707F, 0xAC00_0057, vv            => Vssra;
+    "vssra.vx",   0xFC00_707F, 0xAC00_4057, vx            => Vssra;
+    "vssra.vi",   0xFC00_707F, 0xAC00_3057, vui           => Vssra;
+    "vnclipu.wv", 0xFC00_707F, 0xB800_0057, vv            => Vnclipu;
+    "vnclipu.wx", 0xFC00_707F, 0xB800_4057, vx            => Vnclipu;
+    "vnclipu.wi", 0xFC00_707F, 0xB800_3057, vui           => Vnclipu;
+    "vnclip.wv",  0xFC00_707F, 0xBC00_0057, vv            => Vnclip;
+    "vnclip.wx",  0xFC00_707F, 0xBC00_4057, vx            => Vnclip;
+    "vnclip.wi",  0xFC00_707F, 0xBC00_3057, vui           => Vnclip;
+
+    // NOTE: V, reductions
+    "vredsum.vs", 0xFC00_707F, 0x0000_2057, vv            => Vredsum;
+    "vredand.vs", 0xFC00_707F, 0x0400_2057, vv            => Vredand;
+    "vredor.vs",  0xFC00_707F, 0x0800_2057, vv            => Vredor;
+    "vredxor.vs", 0xFC00_707F, 0x0C00_2057, vv            => Vredxor;
+    "vredminu.vs", 0xFC00_707F, 0x1000_2057, vv            => Vredminu;
+    "vredmin.vs", 0xFC00_707F, 0x1400_2057, vv            => Vredmin;
+    "vredmaxu.vs", 0xFC00_707F, 0x1800_2057, vv            => Vredmaxu;
+    "vredmax.vs", 0xFC00_707F, 0x1C00_2057, vv            => Vredmax;
+    "vwredsumu.vs", 0xFC00_707F, 0xC000_0057, vv            => Vwredsumu;
+    "vwredsum.vs", 0xFC00_707F, 0xC400_0057, vv            => Vwredsum;
+    "vfredusum.vs", 0xFC00_707F, 0x0400_1057, vv            => Vfredusum;
+    "vfredosum.vs", 0xFC00_707F, 0x0C00_1057, vv            => Vfredosum;
+    "vfredmin.vs", 0xFC00_707F, 0x1400_1057, vv            => Vfredmin;
+    "vfredmax.vs", 0xFC00_707F, 0x1C00_1057, vv            => Vfredmax;
+    "vfwredusum.vs", 0xFC00_707F, 0xC400_1057, vv            => Vfwredusum;
+    "vfwredosum.vs", 0xFC00_707F, 0xCC00_1057, vv            => Vfwredosum;
+
+    // NOTE: V, masks
+    "vmandn.mm",  0xFE00_707F, 0x6200_2057, mm            => Vmandn;
+    "vmand.mm",   0xFE00_707F, 0x6600_2057, mm            => Vmand;
+    "vmor.mm",    0xFE00_707F, 0x6A00_2057, mm            => Vmor;
+    "vmxor.mm",   0xFE00_707F, 0x6E00_2057, mm            => Vmxor;
+    "vmorn.mm",   0xFE00_707F, 0x7200_2057, mm            => Vmorn;
+    "vmnand.mm",  0xFE00_707F, 0x7600_2057, mm            => Vmnand;
+    "vmnor.mm",   0xFE00_707F, 0x7A00_2057, mm            => Vmnor;
+    "vmxnor.mm",  0xFE00_707F, 0x7E00_2057, mm            => Vmxnor;
+    "vcpop.m",    0xFC0F_F07F, 0x4008_2057, xvm           => Vcpop;
+    "vfirst.m",   0xFC0F_F07F, 0x4008_A057, xvm           => Vfirst;
+    "vmsbf.m",    0xFC0F_F07F, 0x5000_A057, v2            => Vmsbf;
+    "vmsof.m",    0xFC0F_F07F, 0x5001_2057, v2            => Vmsof;
+    "vmsif.m",    0xFC0F_F07F, 0x5001_A057, v2            => Vmsif;
+    "viota.m",    0xFC0F_F07F, 0x5008_2057, v2            => Viota;
+    "vid.v",      0xFDFF_F07F, 0x5008_A057, vid           => Vid;
+
+    // NOTE: V, permutations
+    "vmv.x.s",    0xFE0F_F07F, 0x4200_2057, r2v(x)        => Vmvxs;
+    "vmv.s.x",    0xFFF0_707F, 0x4200_6057, r2(v x)       => Vmvsx;
+    "vfmv.f.s",   0xFE0F_F07F, 0x4200_1057, r2v(f)        => Vfmvfs;
+    "vfmv.s.f",   0xFFF0_707F, 0x4200_5057, r2(v f)       => Vfmvsf;
+    "vslideup.vx", 0xFC00_707F, 0x3800_4057, vx            => Vslideup;
+    "vslideup.vi", 0xFC00_707F, 0x3800_3057, vui           => Vslideup;
+    "vslidedown.vx", 0xFC00_707F, 0x3C00_4057, vx            => Vslidedown;
+    "vslidedown.vi", 0xFC00_707F, 0x3C00_3057, vui           => Vslidedown;
+    "vslide1up.vx", 0xFC00_707F, 0x3800_6057, vx            => Vslide1up;
+    "vslide1down.vx", 0xFC00_707F, 0x3C00_6057, vx            => Vslide1down;
+    "vfslide1up.vf", 0xFC00_707F, 0x3800_5057, vf            => Vfslide1up;
+    "vfslide1down.vf", 0xFC00_707F, 0x3C00_5057, vf            => Vfslide1down;
+    "vrgather.vv", 0xFC00_707F, 0x3000_0057, vv            => Vrgather;
+    "vrgather.vx", 0xFC00_707F, 0x3000_4057, vx            => Vrgather;
+    "vrgather.vi", 0xFC00_707F, 0x3000_3057, vui           => Vrgather;
+    "vrgatherei16.vv", 0xFC00_707F, 0x3800_0057, vv            => Vrgatherei16;
+    "vcompress.vm", 0xFE00_707F, 0x5E00_2057, mm            => Vcompress;
+    "vmv1r.v",    0xFE0F_F07F, 0x9E00_3057, vmvr          => Vmvr;
+    "vmv2r.v",    0xFE0F_F07F, 0x9E00_B057, vmvr          => Vmvr;
+    "vmv4r.v",    0xFE0F_F07F, 0x9E01_B057, vmvr          => Vmvr;
+    "vmv8r.v",    0xFE0F_F07F, 0x9E03_B057, vmvr          => Vmvr;
+
+    // NOTE: V, floating point
+    "vfadd.vv",   0xFC00_707F, 0x0000_1057, vv            => Vfadd;
+    "vfadd.vf",   0xFC00_707F, 0x0000_5057, vf            => Vfadd;
+    "vfsub.vv",   0xFC00_707F, 0x0800_1057, vv            => Vfsub;
+    "vfsub.vf",   0xFC00_707F, 0x0800_5057, vf            => Vfsub;
+    "vfrsub.vf",  0xFC00_707F, 0x9C00_5057, vf            => Vfrsub;
+    "vfmul.vv",   0xFC00_707F, 0x9000_1057, vv            => Vfmul;
+    "vfmul.vf",   0xFC00_707F, 0x9000_5057, vf            => Vfmul;
+    "vfdiv.vv",   0xFC00_707F, 0x8000_1057, vv            => Vfdiv;
+    "vfdiv.vf",   0xFC00_707F, 0x8000_5057, vf            => Vfdiv;
+    "vfrdiv.vf",  0xFC00_707F, 0x8400_5057, vf            => Vfrdiv;
+    "vfmin.vv",   0xFC00_707F, 0x1000_1057, vv            => Vfmin;
+    "vfmin.vf",   0xFC00_707F, 0x1000_5057, vf            => Vfmin;
+    "vfmax.vv",   0xFC00_707F, 0x1800_1057, vv            => Vfmax;
+    "vfmax.vf",   0xFC00_707F, 0x1800_5057, vf            => Vfmax;
+    "vfsgnj.vv",  0xFC00_707F, 0x2000_1057, vv            => Vfsgnj;
+    "vfsgnj.vf",  0xFC00_707F, 0x2000_5057, vf            => Vfsgnj;
+    "vfsgnjn.vv", 0xFC00_707F, 0x2400_1057, vv            => Vfsgnjn;
+    "vfsgnjn.vf", 0xFC00_707F, 0x2400_5057, vf            => Vfsgnjn;
+    "vfsgnjx.vv", 0xFC00_707F, 0x2800_1057, vv            => Vfsgnjx;
+    "vfsgnjx.vf", 0xFC00_707F, 0x2800_5057, vf            => Vfsgnjx;
+    "vfmadd.vv",  0xFC00_707F, 0xA000_1057, mvv           => Vfmadd;
+    "vfmadd.vf",  0xFC00_707F, 0xA000_5057, mvf           => Vfmadd;
+    "vfnmadd.vv", 0xFC00_707F, 0xA400_1057, mvv           => Vfnmadd;
+    "vfnmadd.vf", 0xFC00_707F, 0xA400_5057, mvf           => Vfnmadd;
+    "vfmsub.vv",  0xFC00_707F, 0xA800_1057, mvv           => Vfmsub;
+    "vfmsub.vf",  0xFC00_707F, 0xA800_5057, mvf           => Vfmsub;
+    "vfnmsub.vv", 0xFC00_707F, 0xAC00_1057, mvv           => Vfnmsub;
+    "vfnmsub.vf", 0xFC00_707F, 0xAC00_5057, mvf           => Vfnmsub;
+    "vfmacc.vv",  0xFC00_707F, 0xB000_1057, mvv           => Vfmacc;
+    "vfmacc.vf",  0xFC00_707F, 0xB000_5057, mvf           => Vfmacc;
+    "vfnmacc.vv", 0xFC00_707F, 0xB400_1057, mvv           => Vfnmacc;
+    "vfnmacc.vf", 0xFC00_707F, 0xB400_5057, mvf           => Vfnmacc;
+    "vfmsac.vv",  0xFC00_707F, 0xB800_1057, mvv           => Vfmsac;
+    "vfmsac.vf",  0xFC00_707F, 0xB800_5057, mvf           => Vfmsac;
+    "vfnmsac.vv", 0xFC00_707F, 0xBC00_1057, mvv           => Vfnmsac;
+    "vfnmsac.vf", 0xFC00_707F, 0xBC00_5057, mvf           => Vfnmsac;
+    "vfwadd.vv",  0xFC00_707F, 0xC000_1057, vv            => Vfwadd;
+    "vfwadd.vf",  0xFC00_707F, 0xC000_5057, vf            => Vfwadd;
+    "vfwsub.vv",  0xFC00_707F, 0xC800_1057, vv            => Vfwsub;
+    "vfwsub.vf",  0xFC00_707F, 0xC800_5057, vf            => Vfwsub;
+    "vfwadd.wv",  0xFC00_707F, 0xD000_1057, vv            => Vfwaddw;
+    "vfwadd.wf",  0xFC00_707F, 0xD000_5057, vf            => Vfwaddw;
+    "vfwsub.wv",  0xFC00_707F, 0xD800_1057, vv            => Vfwsubw;
+    "vfwsub.wf",  0xFC00_707F, 0xD800_5057, vf            => Vfwsubw;
+    "vfwmul.vv",  0xFC00_707F, 0xE000_1057, vv            => Vfwmul;
+    "vfwmul.vf",  0xFC00_707F, 0xE000_5057, vf            => Vfwmul;
+    "vfwmacc.vv", 0xFC00_707F, 0xF000_1057, mvv           => Vfwmacc;
+    "vfwmacc.vf", 0xFC00_707F, 0xF000_5057, mvf           => Vfwmacc;
+    "vfwnmacc.vv", 0xFC00_707F, 0xF400_1057, mvv           => Vfwnmacc;
+    "vfwnmacc.vf", 0xFC00_707F, 0xF400_5057, mvf           => Vfwnmacc;
+    "vfwmsac.vv", 0xFC00_707F, 0xF800_1057, mvv           => Vfwmsac;
+    "vfwmsac.vf", 0xFC00_707F, 0xF800_5057, mvf           => Vfwmsac;
+    "vfwnmsac.vv", 0xFC00_707F, 0xFC00_1057, mvv           => Vfwnmsac;
+    "vfwnmsac.vf", 0xFC00_707F, 0xFC00_5057, mvf           => Vfwnmsac;
+    "vmfeq.vv",   0xFC00_707F, 0x6000_1057, vv            => Vmfeq;
+    "vmfeq.vf",   0xFC00_707F, 0x6000_5057, vf            => Vmfeq;
+    "vmfle.vv",   0xFC00_707F, 0x6400_1057, vv            => Vmfle;
+    "vmfle.vf",   0xFC00_707F, 0x6400_5057, vf            => Vmfle;
+    "vmflt.vv",   0xFC00_707F, 0x6C00_1057, vv            => Vmflt;
+    "vmflt.vf",   0xFC00_707F, 0x6C00_5057, vf            => Vmflt;
+    "vmfne.vv",   0xFC00_707F, 0x7000_1057, vv            => Vmfne;
+    "vmfne.vf",   0xFC00_707F, 0x7000_5057, vf            => Vmfne;
+    "vmfgt.vf",   0xFC00_707F, 0x7400_5057, vf            => Vmfgt;
+    "vmfge.vf",   0xFC00_707F, 0x7C00_5057, vf            => Vmfge;
+    "vfmv.v.f",   0xFFF0_707F, 0x5E00_5057, r2(v f)       => Vfmvvf;
+    "vfmerge.vfm", 0xFE00_707F, 0x5C00_5057, vfm           => Vfmerge;
+    "vfsqrt.v",   0xFC0F_F07F, 0x4C00_1057, v2            => Vfsqrtv;
+    "vfrsqrt7.v", 0xFC0F_F07F, 0x4C02_1057, v2            => Vfrsqrt7v;
+    "vfrec7.v",   0xFC0F_F07F, 0x4C02_9057, v2            => Vfrec7v;
+    "vfclass.v",  0xFC0F_F07F, 0x4C08_1057, v2            => Vfclassv;
+    "vfcvt.xu.f.v", 0xFC0F_F07F, 0x4800_1057, v2            => Vfcvtxufv;
+    "vfcvt.x.f.v", 0xFC0F_F07F, 0x4800_9057, v2            => Vfcvtxfv;
+    "vfcvt.f.xu.v", 0xFC0F_F07F, 0x4801_1057, v2            => Vfcvtfxuv;
+    "vfcvt.f.x.v", 0xFC0F_F07F, 0x4801_9057, v2            => Vfcvtfxv;
+    "vfcvt.rtz.xu.f.v", 0xFC0F_F07F, 0x4803_1057, v2            => Vfcvtrtzxufv;
+    "vfcvt.rtz.x.f.v", 0xFC0F_F07F, 0x4803_9057, v2            => Vfcvtrtzxfv;
+    "vfwcvt.xu.f.v", 0xFC0F_F07F, 0x4804_1057, v2            => Vfwcvtxufv;
+    "vfwcvt.x.f.v", 0xFC0F_F07F, 0x4804_9057, v2            => Vfwcvtxfv;
+    "vfwcvt.f.xu.v", 0xFC0F_F07F, 0x4805_1057, v2            => Vfwcvtfxuv;
+    "vfwcvt.f.x.v", 0xFC0F_F07F, 0x4805_9057, v2            => Vfwcvtfxv;
+    "vfwcvt.f.f.v", 0xFC0F_F07F, 0x4806_1057, v2            => Vfwcvtffv;
+    "vfwcvt.rtz.xu.f.v", 0xFC0F_F07F, 0x4807_1057, v2            => Vfwcvtrtzxufv;
+    "vfwcvt.rtz.x.f.v", 0xFC0F_F07F, 0x4807_9057, v2            => Vfwcvtrtzxfv;
+    "vfncvt.xu.f.w", 0xFC0F_F07F, 0x4808_1057, v2            => Vfncvtxufw;
+    "vfncvt.x.f.w", 0xFC0F_F07F, 0x4808_9057, v2            => Vfncvtxfw;
+    "vfncvt.f.xu.w", 0xFC0F_F07F, 0x4809_1057, v2            => Vfncvtfxuw;
+    "vfncvt.f.x.w", 0xFC0F_F07F, 0x4809_9057, v2            => Vfncvtfxw;
+    "vfncvt.f.f.w", 0xFC0F_F07F, 0x480A_1057, v2            => Vfncvtffw;
+    "vfncvt.rod.f.f.w", 0xFC0F_F07F, 0x480A_9057, v2            => Vfncvtrodffw;
+    "vfncvt.rtz.xu.f.w", 0xFC0F_F07F, 0x480B_1057, v2            => Vfncvtrtzxufw;
+    "vfncvt.rtz.x.f.w", 0xFC0F_F07F, 0x480B_9057, v2            => Vfncvtrtzxfw;
+
+    // NOTE: RV64A
+    "lr.w",       0xF9F0_707F, 0x1000_202F, lr            => Lrw;
+    "sc.w",       0xF800_707F, 0x1800_202F, amo           => Scw;
+    "amoswap.w",  0xF800_707F, 0x0800_202F, amo           => Amoswapw;
+    "amoadd.w",   0xF800_707F, 0x0000_202F, amo           => Amoaddw;
+    "amoxor.w",   0xF800_707F, 0x2000_202F, amo           => Amoxorw;
+    "amoand.w",   0xF800_707F, 0x6000_202F, amo           => Amoandw;
+    "amoor.w",    0xF800_707F, 0x4000_202F, amo           => Amoorw;
+    "amomin.w",   0xF800_707F, 0x8000_202F, amo           => Amominw;
+    "amomax.w",   0xF800_707F, 0xA000_202F, amo           => Amomaxw;
+    "amominu.w",  0xF800_707F, 0xC000_202F, amo           => Amominuw;
+    "amomaxu.w",  0xF800_707F, 0xE000_202F, amo           => Amomaxuw;
+    "lr.d",       0xF800_707F, 0x1000_302F, lr            => Lrd;
+    "sc.d",       0xF800_707F, 0x1800_302F, amo           => Scd;
+    "amoswap.d",  0xF800_707F, 0x0800_302F, amo           => Amoswapd;
+    "amoadd.d",   0xF800_707F, 0x0000_302F, amo           => Amoaddd;
+    "amoxor.d",   0xF800_707F, 0x2000_302F, amo           => Amoxord;
+    "amoand.d",   0xF800_707F, 0x6000_302F, amo           => Amoandd;
+    "amoor.d",    0xF800_707F, 0x4000_302F, amo           => Amoord;
+    "amomin.d",   0xF800_707F, 0x8000_302F, amo           => Amomind;
+    "amomax.d",   0xF800_707F, 0xA000_302F, amo           => Amomaxd;
+    "amominu.d",  0xF800_707F, 0xC000_302F, amo           => Amominud;
+    "amomaxu.d",  0xF800_707F, 0xE000_302F, amo           => Amomaxud;
+
+    // NOTE: RV64F
+    "flw",        0x0000_707F, 0x0000_2007, loadu(f)      => Flw;
+    "fsw",        0x0000_707F, 0x0000_2027, storeu(f)     => Fsw;
+    "fmadd.s",    0x0600_007F, 0x0000_0043, r4            => Fmadds;
+    "fmsub.s",    0x0600_007F, 0x0000_0047, r4            => Fmsubs;
+    "fnmsub.s",   0x0600_007F, 0x0000_004B, r4            => Fnmsubs;
+    "fnmadd.s",   0x0600_007F, 0x0000_004F, r4            => Fnmadds;
+    "fadd.s",     0xFE00_007F, 0x0000_0053, rrm(f f f)    => Fadds;
+    "fsub.s",     0xFE00_007F, 0x0800_0053, rrm(f f f)    => Fsubs;
+    "fmul.s",     0xFE00_007F, 0x1000_0053, rrm(f f f)    => Fmuls;
+    "fdiv.s",     0xFE00_007F, 0x1800_0053, rrm(f f f)    => Fdivs;
+    "fsqrt.s",    0xFFF0_007F, 0x5800_0053, r2rm(f f)     => Fsqrts;
+    "fsgnj.s",    0xFE00_707F, 0x2000_0053, r(f f f)      => Fsgnjs;
+    "fsgnjn.s",   0xFE00_707F, 0x2000_1053, r(f f f)      => Fsgnjns;
+    "fsgnjx.s",   0xFE00_707F, 0x2000_2053, r(f f f)      => Fsgnjxs;
+    "fmin.s",     0xFE00_707F, 0x2800_0053, r(f f f)      => Fmins;
+    "fmax.s",     0xFE00_707F, 0x2800_1053, r(f f f)      => Fmaxs;
+    "fcvt.w.s",   0xFFF0_007F, 0xC000_0053, r2rm(x f)     => Fcvtws;
+    "fcvt.wu.s",  0xFFF0_007F, 0xC010_0053, r2rm(x f)     => Fcvtwus;
+    "fmv.x.w",    0xFFF0_707F, 0xE000_0053, r2(x f)       => Fmvxw;
+    "feq.s",      0xFE00_707F, 0xA000_2053, r(x f f)      => Feqs;
+    "flt.s",      0xFE00_707F, 0xA000_1053, r(x f f)      => Flts;
+    "fle.s",      0xFE00_707F, 0xA000_0053, r(x f f)      => Fles;
+    "fclass.s",   0xFFF0_707F, 0xE000_1053, r2(x f)       => Fclasss;
+    "fcvt.s.w",   0xFFF0_007F, 0xD000_0053, r2rm(f x)     => Fcvtsw;
+    "fcvt.s.wu",  0xFFF0_007F, 0xD010_0053, r2rm(f x)     => Fcvtswu;
+    "fmv.w.x",    0xFFF0_707F, 0xF000_0053, r2(f x)       => Fmvwx;
+    "fcvt.l.s",   0xFFF0_007F, 0xC020_0053, r2rm(x f)     => Fcvtls;
+    "fcvt.lu.s",  0xFFF0_007F, 0xC030_0053, r2rm(x f)     => Fcvtlus;
+    "fcvt.s.l",   0xFFF0_007F, 0xD020_0053, r2rm(f x)     => Fcvtsl;
+    "fcvt.s.lu",  0xFFF0_007F, 0xD030_0053, r2rm(f x)     => Fcvtslu;
+
+    // NOTE: RV64D
+    "fld",        0x0000_707F, 0x0000_3007, loadu(f)      => Fld;
+    "fsd",        0x0000_707F, 0x0000_3027, store(f)      => Fsd;
+    "fmadd.d",    0x0600_007F, 0x0200_0043, r4            => Fmaddd;
+    "fmsub.d",    0x0600_007F, 0x0200_0047, r4            => Fmsubd;
+    "fnmsub.d",   0x0600_007F, 0x0200_004B, r4            => Fnmsubd;
+    "fnmadd.d",   0x0600_007F, 0x0200_004F, r4            => Fnmaddd;
+    "fadd.d",     0xFE00_007F, 0x0200_0053, rrm(f f f)    => Faddd;
+    "fsub.d",     0xFE00_007F, 0x0A00_0053, rrm(f f f)    => Fsubd;
+    "fmul.d",     0xFE00_007F, 0x1200_0053, rrm(f f f)    => Fmuld;
+    "fdiv.d",     0xFE00_007F, 0x1A00_0053, rrm(f f f)    => Fdivd;
+    "fsqrt.d",    0xFFF0_007F, 0x5A00_0053, r2rm(f f)     => Fsqrtd;
+    "fsgnj.d",    0xFE00_707F, 0x2200_0053, r(f f f)      => Fsgnjd;
+    "fsgnjn.d",   0xFE00_707F, 0x2200_1053, r(f f f)      => Fsgnjnd;
+    "fsgnjx.d",   0xFE00_707F, 0x2200_2053, r(f f f)      => Fsgnjxd;
+    "fmin.d",     0xFE00_707F, 0x2A00_0053, r(f f f)      => Fmind;
+    "fmax.d",     0xFE00_707F, 0x2A00_1053, r(f f f)      => Fmaxd;
+    "fcvt.s.d",   0xFFF0_007F, 0x4010_0053, r2rm(f f)     => Fcvtsd;
+    "fcvt.d.s",   0xFFF0_007F, 0x4200_0053, r2rm(f f)     => Fcvtds;
+    "feq.d",      0xFE00_707F, 0xA200_2053, r(x f f)      => Feqd;
+    "flt.d",      0xFE00_707F, 0xA200_1053, r(x f f)      => Fltd;
+    "fle.d",      0xFE00_707F, 0xA200_0053, r(x f f)      => Fled;
+    "fclass.d",   0xFFF0_707F, 0xE200_1053, r2(x f)       => Fclassd;
+    "fcvt.w.d",   0xFFF0_007F, 0xC200_0053, r2rm(x f)     => Fcvtwd;
+    "fcvt.wu.d",  0xFFF0_007F, 0xC210_0053, r2rm(x f)     => Fcvtwud;
+    "fcvt.d.w",   0xFFF0_007F, 0xD200_0053, r2rm(f x)     => Fcvtdw;
+    "fcvt.d.wu",  0xFFF0_007F, 0xD210_0053, r2rm(f x)     => Fcvtdwu;
+    "fmv.x.d",    0xFFF0_707F, 0xE200_0053, r2(x f)       => Fmvxd;
+    "fcvt.l.d",   0xFFF0_007F, 0xC220_0053, r2rm(x f)     => Fcvtld;
+    "fcvt.lu.d",  0xFFF0_007F, 0xC230_0053, r2rm(x f)     => Fcvtlud;
+    "fcvt.d.l",   0xFFF0_007F, 0xD220_0053, r2rm(f x)     => Fcvtdl;
+    "fcvt.d.lu",  0xFFF0_007F, 0xD230_0053, r2rm(f x)     => Fcvtdlu;
+    "fmv.d.x",    0xFFF0_707F, 0xF200_0053, r2(f x)       => Fmvdx;
+
+    // NOTE: RV64C, quadrant 0. A zero immediate is reserved, which makes 0x0000 illegal
+    "c.addi4spn", 0xFFE3, 0x0000, reserved      => IllegalInstruction;
+    "c.addi4spn", 0xE003, 0x0000, caddi4spn     => Caddi4spn;
+    "c.fld",      0xE003, 0x2000, cld(f)        => Cfld;
+    "c.lw",       0xE003, 0x4000, clw(x)        => Clw;
+    "c.ld",       0xE003, 0x6000, cld(x)        => Cld;
+    "c.fsd",      0xE003, 0xA000, csd(f)        => Cfsd;
+    "c.sw",       0xE003, 0xC000, csw(x)        => Csw;
+    "c.sd",       0xE003, 0xE000, csd(x)        => Csd;
+
+    // NOTE: RV64C, quadrant 1
+    "c.nop",      0xFFFF, 0x0001, none          => Cnop;
+    "c.addi",     0xE003, 0x0001, ci            => Caddi;
+    "c.addiw",    0xEF83, 0x2001, reserved      => IllegalInstruction;
+    "c.addiw",    0xE003, 0x2001, ci            => Caddiw;
+    "c.li",       0xE003, 0x4001, cli           => Cli;
+    "c.addi16sp", 0xFFFF, 0x6101, reserved      => IllegalInstruction;
+    "c.addi16sp", 0xEF83, 0x6101, caddi16sp     => Caddi16sp;
+    "c.lui",      0xF07F, 0x6001, reserved      => IllegalInstruction;
+    "c.lui",      0xE003, 0x6001, clui          => Clui;
+    "c.srli",     0xEC03, 0x8001, cshift        => Csrli;
+    "c.srai",     0xEC03, 0x8401, cshift        => Csrai;
+    "c.andi",     0xEC03, 0x8801, candi         => Candi;
+    "c.sub",      0xFC63, 0x8C01, ca            => Csub;
+    "c.xor",      0xFC63, 0x8C21, ca            => Cxor;
+    "c.or",       0xFC63, 0x8C41, ca            => Cor;
+    "c.and",      0xFC63, 0x8C61, ca            => Cand;
+    "c.subw",     0xFC63, 0x9C01, ca            => Csubw;
+    "c.addw",     0xFC63, 0x9C21, ca            => Caddw;
+    "c.j",        0xE003, 0xA001, cj            => Cj;
+    "c.beqz",     0xE003, 0xC001, cb            => Cbeqz;
+    "c.bnez",     0xE003, 0xE001, cb            => Cbnez;
+
+    // NOTE: RV64C, quadrant 2
+    "c.slli",     0xE003, 0x0002, cslli         => Cslli;
+    "c.fldsp",    0xE003, 0x2002, cldsp(f)      => Cfldsp;
+    "c.lwsp",     0xEF83, 0x4002, reserved      => IllegalInstruction;
+    "c.lwsp",     0xE003, 0x4002, clwsp(x)      => Clwsp;
+    "c.ldsp",     0xEF83, 0x6002, reserved      => IllegalInstruction;
+    "c.ldsp",     0xE003, 0x6002, cldsp(x)      => Cldsp;
+    "c.jr",       0xFFFF, 0x8002, reserved      => IllegalInstruction;
+    "c.jr",       0xF07F, 0x8002, cjr           => Cjr;
+    "c.mv",       0xF003, 0x8002, cr            => Cmv;
+    "c.ebreak",   0xFFFF, 0x9002, none          => Cebreak;
+    "c.jalr",     0xF07F, 0x9002, cjr           => Cjalr;
+    "c.add",      0xF003, 0x9002, cr            => Cadd;
+    "c.fsdsp",    0xE003, 0xA002, csdsp(f)      => Cfsdsp;
+    "c.swsp",     0xE003, 0xC002, cswsp(x)      => Cswsp;
+    "c.sdsp",     0xE003, 0xE002, csdsp(x)      => Csdsp;
 }
 
-pub const fn is_rv64m_divu_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0000_0010_0000_0000_0101_0000_0011_0011;
-    let mask: u32 = 0b1111_1110_0000_0000_0111_0000_0111_1111;
+#[cfg(test)]
+pub mod tests {
+    use super::*;
 
-    let extracted = ins & mask;
-    extracted == format
-}
+    #[test]
+    #[allow(clippy::vec_init_then_push)]
+    fn test_all_instructions() -> Result<(), Box<dyn std::error::Error>> {
+        let mut map: Vec<(&str, u32)> = Vec::new();
+        map.push(("add", 0x00a282b3));
+        map.push(("addi", 0x00500013));
+        map.push(("auipc", 0x00123297));
+        map.push(("lui", 0x000122b7));
+        map.push(("slti", 0x0052a293));
+        map.push(("sltiu", 0x0052b293));
+        map.push(("xori", 0x0052c293));
+        map.push(("ori", 0x2142e293));
+        map.push(("andi", 0x0051fb93));
+        map.push(("slli", 0x02d61393));
+        map.push(("srli", 0x02d65393));
+        map.push(("srai", 0x42d65393));
+        map.push(("sub", 0x405302b3));
+        map.push(("sll", 0x019192b3));
+        map.push(("sll", 0x00f696b3));
+        map.push(("slt", 0x0191a2b3));
+        map.push(("sltu", 0x0191b2b3));
+        map.push(("xor", 0x001d4fb3));
+        map.push(("srl", 0x0191d2b3));
+        map.push(("sra", 0x4191d2b3));
+        map.push(("or", 0x0191e2b3));
+        map.push(("and", 0x0191f2b3));
+        map.push(("ecall", 0x00000073));
+        map.push(("ebreak", 0x00100073));
+        map.push(("fence", 0x0ff0000f));
+        map.push(("fence.i", 0x0000100f));
+        map.push(("csrrw", 0x003312f3));
+        map.push(("csrrs", 0xc00322f3));
+        map.push(("csrrc", 0x001332f3));
+        map.push(("csrrwi", 0x002352f3));
+        map.push(("csrrsi", 0x001362f3));
+        map.push(("csrrci", 0x001372f3));
+        map.push(("uret", 0x00200073));
+        map.push(("sret", 0x10200073));
+        map.push(("mret", 0x30200073));
+        map.push(("wfi", 0x10500073));
+        map.push(("sfence.vma", 0x12000073));
+        map.push(("lb", 0x000e0283));
+        map.push(("lh", 0x000e1283));
+        map.push(("lw", 0x000e2283));
+        map.push(("lbu", 0x000e4283));
+        map.push(("lhu", 0x000e5283));
+        map.push(("sb", 0x005e0023));
+        map.push(("sh", 0x005e1023));
+        map.push(("sw", 0x005e2023));
+        map.push(("jal", 0x00400c6f));
+        map.push(("jalr", 0x00428c67));
+        map.push(("beq", 0x005c0263));
+        map.push(("bne", 0x005c1263));
+        map.push(("bge", 0x005c5263));
+        map.push(("bgeu", 0x005c7263));
+        map.push(("blt", 0x005c4263));
+        map.push(("bltu", 0x005c6263));
+        map.push(("ld", 0x00043283));
+        map.push(("sd", 0x00543023));
+        map.push(("addiw", 0x0007879b));
+        map.push(("slliw", 0x0052929b));
+        map.push(("srliw", 0x0052d29b));
+        map.push(("sraiw", 0x41f7d79b));
+        map.push(("subw", 0x40f007bb));
+        map.push(("addw", 0x00f707bb));
+        map.push(("sllw", 0x005292bb));
+        map.push(("srlw", 0x0052d2bb));
+        map.push(("sraw", 0x4052d2bb));
+        map.push(("lwu", 0x0002e283));
+        map.push(("mul", 0x025282b3));
+        map.push(("mulh", 0x025292b3));
+        map.push(("mulhu", 0x0252b2b3));
+        map.push(("mulhsu", 0x0252a2b3));
+        map.push(("div", 0x0252c2b3));
+        map.push(("divu", 0x0252d2b3));
+        map.push(("rem", 0x0252e2b3));
+        map.push(("remu", 0x0252f2b3));
+        map.push(("mulw", 0x035282bb));
+        map.push(("divw", 0x0352c2bb));
+        map.push(("divuw", 0x0352d2bb));
+        map.push(("remw", 0x0352e2bb));
+        map.push(("remuw", 0x0352f2bb));
+        map.push(("add.uw", 0x08c5853b));
+        map.push(("sh1add", 0x20c5a533));
+        map.push(("sh2add", 0x2062c4b3));
+        map.push(("sh3add", 0x20d767b3));
+        map.push(("sh1add.uw", 0x20c5a53b));
+        map.push(("sh2add.uw", 0x207342bb));
+        map.push(("sh3add.uw", 0x20c5e53b));
+        map.push(("slli.uw", 0x0a35951b));
+        map.push(("andn", 0x40c5f533));
+        map.push(("orn", 0x407362b3));
+        map.push(("xnor", 0x40c5c533));
+        map.push(("clz", 0x60059513));
+        map.push(("clzw", 0x6003129b));
+        map.push(("ctz", 0x60159513));
+        map.push(("ctzw", 0x6015951b));
+        map.push(("cpop", 0x60259513));
+        map.push(("cpopw", 0x6025951b));
+        map.push(("max", 0x0ac5e533));
+        map.push(("maxu", 0x0ac5f533));
+        map.push(("min", 0x0ac5c533));
+        map.push(("minu", 0x0ac5d533));
+        map.push(("sext.b", 0x60459513));
+        map.push(("sext.h", 0x60559513));
+        map.push(("zext.h", 0x0805c53b));
+        map.push(("rol", 0x60c59533));
+        map.push(("rolw", 0x60c5953b));
+        map.push(("ror", 0x60c5d533));
+        map.push(("rorw", 0x60c5d53b));
+        map.push(("rori", 0x62d5d513));
+        map.push(("roriw", 0x60d5d51b));
+        map.push(("orc.b", 0x2875d513));
+        map.push(("rev8", 0x6b85d513));
+        map.push(("clmul", 0x0ac59533));
+        map.push(("clmulh", 0x0ac5b533));
+        map.push(("clmulr", 0x0ac5a533));
+        map.push(("bclr", 0x48c59533));
+        map.push(("bclri", 0x4bf59513));
+        map.push(("bext", 0x48c5d533));
+        map.push(("bexti", 0x4a15d513));
+        map.push(("binv", 0x68c59533));
+        map.push(("binvi", 0x68759513));
+        map.push(("bset", 0x28c59533));
+        map.push(("bseti", 0x2a859513));
+        map.push(("vsetvli", 0x0515f557));
+        map.push(("vsetivli", 0xc002f557));
+        map.push(("vsetvl", 0x80c5f557));
+        map.push(("vle8.v", 0x02050207));
+        map.push(("vle8ff.v", 0x03050207));
+        map.push(("vlse8.v", 0x0ab50207));
+        map.push(("vluxei8.v", 0x06850207));
+        map.push(("vloxei8.v", 0x0e850207));
+        map.push(("vl1re8.v", 0x02850207));
+        map.push(("vle16.v", 0x02055207));
+        map.push(("vle16ff.v", 0x03055207));
+        map.push(("vlse16.v", 0x0ab55207));
+        map.push(("vluxei16.v", 0x06855207));
+        map.push(("vloxei16.v", 0x0e855207));
+        map.push(("vl1re16.v", 0x02855207));
+        map.push(("vle32.v", 0x02056207));
+        map.push(("vle32ff.v", 0x03056207));
+        map.push(("vlse32.v", 0x0ab56207));
+        map.push(("vluxei32.v", 0x06856207));
+        map.push(("vloxei32.v", 0x0e856207));
+        map.push(("vl1re32.v", 0x02856207));
+        map.push(("vle64.v", 0x02057207));
+        map.push(("vle64ff.v", 0x03057207));
+        map.push(("vlse64.v", 0x0ab57207));
+        map.push(("vluxei64.v", 0x06857207));
+        map.push(("vloxei64.v", 0x0e857207));
+        map.push(("vl1re64.v", 0x02857207));
+        map.push(("vlm.v", 0x02b50207));
+        map.push(("vse8.v", 0x02050227));
+        map.push(("vsse8.v", 0x0ab50227));
+        map.push(("vsuxei8.v", 0x06850227));
+        map.push(("vsoxei8.v", 0x0e850227));
+        map.push(("vse16.v", 0x02055227));
+        map.push(("vsse16.v", 0x0ab55227));
+        map.push(("vsuxei16.v", 0x06855227));
+        map.push(("vsoxei16.v", 0x0e855227));
+        map.push(("vse32.v", 0x02056227));
+        map.push(("vsse32.v", 0x0ab56227));
+        map.push(("vsuxei32.v", 0x06856227));
+        map.push(("vsoxei32.v", 0x0e856227));
+        map.push(("vse64.v", 0x02057227));
+        map.push(("vsse64.v", 0x0ab57227));
+        map.push(("vsuxei64.v", 0x06857227));
+        map.push(("vsoxei64.v", 0x0e857227));
+        map.push(("vs1r.v", 0x02850227));
+        map.push(("vsm.v", 0x02b50227));
+        map.push(("vadd.vv", 0x02860257));
+        map.push(("vadd.vx", 0x02854257));
+        map.push(("vadd.vi", 0x028db257));
+        map.push(("vsub.vv", 0x0a860257));
+        map.push(("vsub.vx", 0x0a854257));
+        map.push(("vrsub.vx", 0x0e854257));
+        map.push(("vrsub.vi", 0x0e8db257));
+        map.push(("vminu.vv", 0x12860257));
+        map.push(("vminu.vx", 0x12854257));
+        map.push(("vmin.vv", 0x16860257));
+        map.push(("vmin.vx", 0x16854257));
+        map.push(("vmaxu.vv", 0x1a860257));
+        map.push(("vmaxu.vx", 0x1a854257));
+        map.push(("vmax.vv", 0x1e860257));
+        map.push(("vmax.vx", 0x1e854257));
+        map.push(("vand.vv", 0x26860257));
+        map.push(("vand.vx", 0x26854257));
+        map.push(("vand.vi", 0x268db257));
+        map.push(("vor.vv", 0x2a860257));
+        map.push(("vor.vx", 0x2a854257));
+        map.push(("vor.vi", 0x2a8db257));
+        map.push(("vxor.vv", 0x2e860257));
+        map.push(("vxor.vx", 0x2e854257));
+        map.push(("vxor.vi", 0x2e8db257));
+        map.push(("vadc.vvm", 0x40860257));
+        map.push(("vadc.vxm", 0x40854257));
+        map.push(("vadc.vim", 0x408db257));
+        map.push(("vmadc.vvm", 0x44860257));
+        map.push(("vmadc.vxm", 0x44854257));
+        map.push(("vmadc.vim", 0x448db257));
+        map.push(("vmadc.vv", 0x46860257));
+        map.push(("vmadc.vx", 0x46854257));
+        map.push(("vmadc.vi", 0x468db257));
+        map.push(("vsbc.vvm", 0x48860257));
+        map.push(("vsbc.vxm", 0x48854257));
+        map.push(("vmsbc.vvm", 0x4c860257));
+        map.push(("vmsbc.vxm", 0x4c854257));
+        map.push(("vmsbc.vv", 0x4e860257));
+        map.push(("vmsbc.vx", 0x4e854257));
+        map.push(("vmv.v.v", 0x5e040257));
+        map.push(("vmv.v.x", 0x5e054257));
+        map.push(("vmv.v.i", 0x5e0db257));
+        map.push(("vmerge.vvm", 0x5c860257));
+        map.push(("vmerge.vxm", 0x5c854257));
+        map.push(("vmerge.vim", 0x5c8db257));
+        map.push(("vmseq.vv", 0x62860257));
+        map.push(("vmseq.vx", 0x62854257));
+        map.push(("vmseq.vi", 0x628db257));
+        map.push(("vmsne.vv", 0x66860257));
+        map.push(("vmsne.vx", 0x66854257));
+        map.push(("vmsne.vi", 0x668db257));
+        map.push(("vmsltu.vv", 0x6a860257));
+        map.push(("vmsltu.vx", 0x6a854257));
+        map.push(("vmslt.vv", 0x6e860257));
+        map.push(("vmslt.vx", 0x6e854257));
+        map.push(("vmsleu.vv", 0x72860257));
+        map.push(("vmsleu.vx", 0x72854257));
+        map.push(("vmsleu.vi", 0x728db257));
+        map.push(("vmsle.vv", 0x76860257));
+        map.push(("vmsle.vx", 0x76854257));
+        map.push(("vmsle.vi", 0x768db257));
+        map.push(("vmsgtu.vx", 0x7a854257));
+        map.push(("vmsgtu.vi", 0x7a8db257));
+        map.push(("vmsgt.vx", 0x7e854257));
+        map.push(("vmsgt.vi", 0x7e8db257));
+        map.push(("vsll.vv", 0x96860257));
+        map.push(("vsll.vx", 0x96854257));
+        map.push(("vsll.vi", 0x9682b257));
+        map.push(("vsrl.vv", 0xa2860257));
+        map.push(("vsrl.vx", 0xa2854257));
+        map.push(("vsrl.vi", 0xa282b257));
+        map.push(("vsra.vv", 0xa6860257));
+        map.push(("vsra.vx", 0xa6854257));
+        map.push(("vsra.vi", 0xa682b257));
+        map.push(("vnsrl.wv", 0xb2860257));
+        map.push(("vnsrl.wx", 0xb2854257));
+        map.push(("vnsrl.wi", 0xb282b257));
+        map.push(("vnsra.wv", 0xb6860257));
+        map.push(("vnsra.wx", 0xb6854257));
+        map.push(("vnsra.wi", 0xb682b257));
+        map.push(("vzext.vf8", 0x4a812257));
+        map.push(("vsext.vf8", 0x4a81a257));
+        map.push(("vzext.vf4", 0x4a822257));
+        map.push(("vsext.vf4", 0x4a82a257));
+        map.push(("vzext.vf2", 0x4a832257));
+        map.push(("vsext.vf2", 0x4a83a257));
+        map.push(("vdivu.vv", 0x82862257));
+        map.push(("vdivu.vx", 0x82856257));
+        map.push(("vdiv.vv", 0x86862257));
+        map.push(("vdiv.vx", 0x86856257));
+        map.push(("vremu.vv", 0x8a862257));
+        map.push(("vremu.vx", 0x8a856257));
+        map.push(("vrem.vv", 0x8e862257));
+        map.push(("vrem.vx", 0x8e856257));
+        map.push(("vmulhu.vv", 0x92862257));
+        map.push(("vmulhu.vx", 0x92856257));
+        map.push(("vmul.vv", 0x96862257));
+        map.push(("vmul.vx", 0x96856257));
+        map.push(("vmulhsu.vv", 0x9a862257));
+        map.push(("vmulhsu.vx", 0x9a856257));
+        map.push(("vmulh.vv", 0x9e862257));
+        map.push(("vmulh.vx", 0x9e856257));
+        map.push(("vmadd.vv", 0xa6862257));
+        map.push(("vmadd.vx", 0xa6856257));
+        map.push(("vnmsub.vv", 0xae862257));
+        map.push(("vnmsub.vx", 0xae856257));
+        map.push(("vmacc.vv", 0xb6862257));
+        map.push(("vmacc.vx", 0xb6856257));
+        map.push(("vnmsac.vv", 0xbe862257));
+        map.push(("vnmsac.vx", 0xbe856257));
+        map.push(("vwaddu.vv", 0xc2862257));
+        map.push(("vwaddu.vx", 0xc2856257));
+        map.push(("vwadd.vv", 0xc6862257));
+        map.push(("vwadd.vx", 0xc6856257));
+        map.push(("vwsubu.vv", 0xca862257));
+        map.push(("vwsubu.vx", 0xca856257));
+        map.push(("vwsub.vv", 0xce862257));
+        map.push(("vwsub.vx", 0xce856257));
+        map.push(("vwaddu.wv", 0xd2862257));
+        map.push(("vwaddu.wx", 0xd2856257));
+        map.push(("vwadd.wv", 0xd6862257));
+        map.push(("vwadd.wx", 0xd6856257));
+        map.push(("vwsubu.wv", 0xda862257));
+        map.push(("vwsubu.wx", 0xda856257));
+        map.push(("vwsub.wv", 0xde862257));
+        map.push(("vwsub.wx", 0xde856257));
+        map.push(("vwmulu.vv", 0xe2862257));
+        map.push(("vwmulu.vx", 0xe2856257));
+        map.push(("vwmulsu.vv", 0xea862257));
+        map.push(("vwmulsu.vx", 0xea856257));
+        map.push(("vwmul.vv", 0xee862257));
+        map.push(("vwmul.vx", 0xee856257));
+        map.push(("vwmaccu.vv", 0xf2862257));
+        map.push(("vwmaccu.vx", 0xf2856257));
+        map.push(("vwmacc.vv", 0xf6862257));
+        map.push(("vwmacc.vx", 0xf6856257));
+        map.push(("vwmaccus.vx", 0xfa856257));
+        map.push(("vwmaccsu.vv", 0xfe862257));
+        map.push(("vwmaccsu.vx", 0xfe856257));
+        map.push(("vsaddu.vv", 0x82860257));
+        map.push(("vsaddu.vx", 0x82854257));
+        map.push(("vsaddu.vi", 0x828db257));
+        map.push(("vsadd.vv", 0x86860257));
+        map.push(("vsadd.vx", 0x86854257));
+        map.push(("vsadd.vi", 0x868db257));
+        map.push(("vssubu.vv", 0x8a860257));
+        map.push(("vssubu.vx", 0x8a854257));
+        map.push(("vssub.vv", 0x8e860257));
+        map.push(("vssub.vx", 0x8e854257));
+        map.push(("vaaddu.vv", 0x22862257));
+        map.push(("vaaddu.vx", 0x22856257));
+        map.push(("vaadd.vv", 0x26862257));
+        map.push(("vaadd.vx", 0x26856257));
+        map.push(("vasubu.vv", 0x2a862257));
+        map.push(("vasubu.vx", 0x2a856257));
+        map.push(("vasub.vv", 0x2e862257));
+        map.push(("vasub.vx", 0x2e856257));
+        map.push(("vsmul.vv", 0x9e860257));
+        map.push(("vsmul.vx", 0x9e854257));
+        map.push(("vssrl.vv", 0xaa860257));
+        map.push(("vssrl.vx", 0xaa854257));
+        map.push(("vssrl.vi", 0xaa82b257));
+        map.push(("vssra.vv", 0xae860257));
+        map.push(("vssra.vx", 0xae854257));
+        map.push(("vssra.vi", 0xae82b257));
+        map.push(("vnclipu.wv", 0xba860257));
+        map.push(("vnclipu.wx", 0xba854257));
+        map.push(("vnclipu.wi", 0xba82b257));
+        map.push(("vnclip.wv", 0xbe860257));
+        map.push(("vnclip.wx", 0xbe854257));
+        map.push(("vnclip.wi", 0xbe82b257));
+        map.push(("vredsum.vs", 0x02862257));
+        map.push(("vredand.vs", 0x06862257));
+        map.push(("vredor.vs", 0x0a862257));
+        map.push(("vredxor.vs", 0x0e862257));
+        map.push(("vredminu.vs", 0x12862257));
+        map.push(("vredmin.vs", 0x16862257));
+        map.push(("vredmaxu.vs", 0x1a862257));
+        map.push(("vredmax.vs", 0x1e862257));
+        map.push(("vwredsumu.vs", 0xc2860257));
+        map.push(("vwredsum.vs", 0xc6860257));
+        map.push(("vfredusum.vs", 0x06861257));
+        map.push(("vfredosum.vs", 0x0e861257));
+        map.push(("vfredmin.vs", 0x16861257));
+        map.push(("vfredmax.vs", 0x1e861257));
+        map.push(("vfwredusum.vs", 0xc6861257));
+        map.push(("vfwredosum.vs", 0xce861257));
+        map.push(("vmandn.mm", 0x62862257));
+        map.push(("vmand.mm", 0x66862257));
+        map.push(("vmor.mm", 0x6a862257));
+        map.push(("vmxor.mm", 0x6e862257));
+        map.push(("vmorn.mm", 0x72862257));
+        map.push(("vmnand.mm", 0x76862257));
+        map.push(("vmnor.mm", 0x7a862257));
+        map.push(("vmxnor.mm", 0x7e862257));
+        map.push(("vcpop.m", 0x42882557));
+        map.push(("vfirst.m", 0x4288a557));
+        map.push(("vmsbf.m", 0x5280a257));
+        map.push(("vmsof.m", 0x52812257));
+        map.push(("vmsif.m", 0x5281a257));
+        map.push(("viota.m", 0x52882257));
+        map.push(("vid.v", 0x5208a257));
+        map.push(("vmv.x.s", 0x42802557));
+        map.push(("vmv.s.x", 0x42056257));
+        map.push(("vfmv.f.s", 0x42801557));
+        map.push(("vfmv.s.f", 0x42055257));
+        map.push(("vslideup.vx", 0x3a854257));
+        map.push(("vslideup.vi", 0x3a82b257));
+        map.push(("vslidedown.vx", 0x3e854257));
+        map.push(("vslidedown.vi", 0x3e82b257));
+        map.push(("vslide1up.vx", 0x3a856257));
+        map.push(("vslide1down.vx", 0x3e856257));
+        map.push(("vfslide1up.vf", 0x3a855257));
+        map.push(("vfslide1down.vf", 0x3e855257));
+        map.push(("vrgather.vv", 0x32860257));
+        map.push(("vrgather.vx", 0x32854257));
+        map.push(("vrgather.vi", 0x3282b257));
+        map.push(("vrgatherei16.vv", 0x3a860257));
+        map.push(("vcompress.vm", 0x5e862257));
+        map.push(("vmv1r.v", 0x9f003457));
+        map.push(("vmv2r.v", 0x9f00b457));
+        map.push(("vmv4r.v", 0x9f01b457));
+        map.push(("vmv8r.v", 0x9f03b457));
+        map.push(("vfadd.vv", 0x02861257));
+        map.push(("vfadd.vf", 0x02855257));
+        map.push(("vfsub.vv", 0x0a861257));
+        map.push(("vfsub.vf", 0x0a855257));
+        map.push(("vfrsub.vf", 0x9e855257));
+        map.push(("vfmul.vv", 0x92861257));
+        map.push(("vfmul.vf", 0x92855257));
+        map.push(("vfdiv.vv", 0x82861257));
+        map.push(("vfdiv.vf", 0x82855257));
+        map.push(("vfrdiv.vf", 0x86855257));
+        map.push(("vfmin.vv", 0x12861257));
+        map.push(("vfmin.vf", 0x12855257));
+        map.push(("vfmax.vv", 0x1a861257));
+        map.push(("vfmax.vf", 0x1a855257));
+        map.push(("vfsgnj.vv", 0x22861257));
+        map.push(("vfsgnj.vf", 0x22855257));
+        map.push(("vfsgnjn.vv", 0x26861257));
+        map.push(("vfsgnjn.vf", 0x26855257));
+        map.push(("vfsgnjx.vv", 0x2a861257));
+        map.push(("vfsgnjx.vf", 0x2a855257));
+        map.push(("vfmadd.vv", 0xa2861257));
+        map.push(("vfmadd.vf", 0xa2855257));
+        map.push(("vfnmadd.vv", 0xa6861257));
+        map.push(("vfnmadd.vf", 0xa6855257));
+        map.push(("vfmsub.vv", 0xaa861257));
+        map.push(("vfmsub.vf", 0xaa855257));
+        map.push(("vfnmsub.vv", 0xae861257));
+        map.push(("vfnmsub.vf", 0xae855257));
+        map.push(("vfmacc.vv", 0xb2861257));
+        map.push(("vfmacc.vf", 0xb2855257));
+        map.push(("vfnmacc.vv", 0xb6861257));
+        map.push(("vfnmacc.vf", 0xb6855257));
+        map.push(("vfmsac.vv", 0xba861257));
+        map.push(("vfmsac.vf", 0xba855257));
+        map.push(("vfnmsac.vv", 0xbe861257));
+        map.push(("vfnmsac.vf", 0xbe855257));
+        map.push(("vfwadd.vv", 0xc2861257));
+        map.push(("vfwadd.vf", 0xc2855257));
+        map.push(("vfwsub.vv", 0xca861257));
+        map.push(("vfwsub.vf", 0xca855257));
+        map.push(("vfwadd.wv", 0xd2861257));
+        map.push(("vfwadd.wf", 0xd2855257));
+        map.push(("vfwsub.wv", 0xda861257));
+        map.push(("vfwsub.wf", 0xda855257));
+        map.push(("vfwmul.vv", 0xe2861257));
+        map.push(("vfwmul.vf", 0xe2855257));
+        map.push(("vfwmacc.vv", 0xf2861257));
+        map.push(("vfwmacc.vf", 0xf2855257));
+        map.push(("vfwnmacc.vv", 0xf6861257));
+        map.push(("vfwnmacc.vf", 0xf6855257));
+        map.push(("vfwmsac.vv", 0xfa861257));
+        map.push(("vfwmsac.vf", 0xfa855257));
+        map.push(("vfwnmsac.vv", 0xfe861257));
+        map.push(("vfwnmsac.vf", 0xfe855257));
+        map.push(("vmfeq.vv", 0x62861257));
+        map.push(("vmfeq.vf", 0x62855257));
+        map.push(("vmfle.vv", 0x66861257));
+        map.push(("vmfle.vf", 0x66855257));
+        map.push(("vmflt.vv", 0x6e861257));
+        map.push(("vmflt.vf", 0x6e855257));
+        map.push(("vmfne.vv", 0x72861257));
+        map.push(("vmfne.vf", 0x72855257));
+        map.push(("vmfgt.vf", 0x76855257));
+        map.push(("vmfge.vf", 0x7e855257));
+        map.push(("vfmv.v.f", 0x5e055257));
+        map.push(("vfmerge.vfm", 0x5c855257));
+        map.push(("vfsqrt.v", 0x4e801257));
+        map.push(("vfrsqrt7.v", 0x4e821257));
+        map.push(("vfrec7.v", 0x4e829257));
+        map.push(("vfclass.v", 0x4e881257));
+        map.push(("vfcvt.xu.f.v", 0x4a801257));
+        map.push(("vfcvt.x.f.v", 0x4a809257));
+        map.push(("vfcvt.f.xu.v", 0x4a811257));
+        map.push(("vfcvt.f.x.v", 0x4a819257));
+        map.push(("vfcvt.rtz.xu.f.v", 0x4a831257));
+        map.push(("vfcvt.rtz.x.f.v", 0x4a839257));
+        map.push(("vfwcvt.xu.f.v", 0x4a841257));
+        map.push(("vfwcvt.x.f.v", 0x4a849257));
+        map.push(("vfwcvt.f.xu.v", 0x4a851257));
+        map.push(("vfwcvt.f.x.v", 0x4a859257));
+        map.push(("vfwcvt.f.f.v", 0x4a861257));
+        map.push(("vfwcvt.rtz.xu.f.v", 0x4a871257));
+        map.push(("vfwcvt.rtz.x.f.v", 0x4a879257));
+        map.push(("vfncvt.xu.f.w", 0x4a881257));
+        map.push(("vfncvt.x.f.w", 0x4a889257));
+        map.push(("vfncvt.f.xu.w", 0x4a891257));
+        map.push(("vfncvt.f.x.w", 0x4a899257));
+        map.push(("vfncvt.f.f.w", 0x4a8a1257));
+        map.push(("vfncvt.rod.f.f.w", 0x4a8a9257));
+        map.push(("vfncvt.rtz.xu.f.w", 0x4a8b1257));
+        map.push(("vfncvt.rtz.x.f.w", 0x4a8b9257));
+        map.push(("lr.w", 0x1002a2af));
+        map.push(("sc.w", 0x1852a2af));
+        map.push(("amoswap.w", 0x0852a2af));
+        map.push(("amoadd.w", 0x0052a2af));
+        map.push(("amoxor.w", 0x2052a2af));
+        map.push(("amoor.w", 0x4052a2af));
+        map.push(("amoand.w", 0x6052a2af));
+        map.push(("amomin.w", 0x8052a2af));
+        map.push(("amominu.w", 0xc052a2af));
+        map.push(("amomax.w", 0xa052a2af));
+        map.push(("amomaxu.w", 0xe052a2af));
+        map.push(("lr.d", 0x1002b2af));
+        map.push(("sc.d", 0x185032af));
+        map.push(("amoswap.d", 0x085332af));
+        map.push(("amoand.d", 0x605332af));
+        map.push(("amoadd.d", 0x005332af));
+        map.push(("amoxor.d", 0x205332af));
+        map.push(("amoor.d", 0x405332af));
+        map.push(("amomin.d", 0x805332af));
+        map.push(("amominu.d", 0xc05332af));
+        map.push(("amomax.d", 0xa05332af));
+        map.push(("amomaxu.d", 0xe05332af));
+        map.push(("fmadd.s", 0x1852f343));
+        map.push(("fmsub.s", 0x1852f347));
+        map.push(("fnmsub.s", 0x1852f34b));
+        map.push(("fnmadd.s", 0x1852f34f));
+        map.push(("fsub.s", 0x0852f353));
+        map.push(("fadd.s", 0x0010f053));
+        map.push(("fmul.s", 0x1052f353));
+        map.push(("fdiv.s", 0x1852f353));
+        map.push(("fsqrt.s", 0x5802f353));
+        map.push(("fsgnj.s", 0x205302d3));
+        map.push(("fsgnjn.s", 0x205312d3));
+        map.push(("fsgnjx.s", 0x205322d3));
+        map.push(("fmin.s", 0x285302d3));
+        map.push(("fmax.s", 0x285312d3));
+        map.push(("fcvt.w.s", 0xc00372d3));
+        map.push(("fcvt.wu.s", 0xc01372d3));
+        map.push(("fmv.w.x", 0xf00082d3));
+        map.push(("feq.s", 0xa07322d3));
+        map.push(("flt.s", 0xa07312d3));
+        map.push(("fle.s", 0xa07302d3));
+        map.push(("fclass.s", 0xe00312d3));
+        map.push(("fcvt.s.w", 0xd002f1d3));
+        map.push(("fcvt.s.wu", 0xd012f1d3));
+        map.push(("fmv.x.w", 0xe00280d3));
+        map.push(("fcvt.l.s", 0xc0257553));
+        map.push(("fcvt.lu.s", 0xc0351553));
+        map.push(("fcvt.s.l", 0xd0257553));
+        map.push(("fcvt.s.lu", 0xd0357553));
 
-pub const fn is_rv64m_rem_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0000_0010_0000_0000_0110_0000_0011_0011;
-    let mask: u32 = 0b1111_1110_0000_0000_0111_0000_0111_1111;
+        // NOTE: RV64D
+        map.push(("fmadd.d", 0x2252f343));
+        map.push(("fmsub.d", 0x2252f347));
+        map.push(("fnmsub.d", 0x2252f34b));
+        map.push(("fnmadd.d", 0x2252f34f));
+        map.push(("fadd.d", 0x0252f353));
+        map.push(("fsub.d", 0x0a52f353));
+        map.push(("fmul.d", 0x1252f353));
+        map.push(("fdiv.d", 0x1a52f353));
+        map.push(("fsqrt.d", 0x5a02f353));
+        map.push(("fsgnj.d", 0x22628353));
+        map.push(("fsgnjn.d", 0x22629353));
+        map.push(("fsgnjx.d", 0x2262a353));
+        map.push(("fmin.d", 0x2a628353));
+        map.push(("fmax.d", 0x2a629353));
+        map.push(("fcvt.s.d", 0x4012f353));
+        map.push(("fcvt.d.s", 0x4202f353));
+        map.push(("feq.d", 0xa2522353));
+        map.push(("fle.d", 0xa2520353));
+        map.push(("flt.d", 0xa2521353));
+        map.push(("fclass.d", 0xe2021353));
+        map.push(("fcvt.w.d", 0xc20272d3));
+        map.push(("fcvt.wu.d", 0xc21272d3));
+        map.push(("fcvt.d.wu", 0xd21272d3));
+        map.push(("fcvt.d.w", 0xd20272d3));
+        map.push(("fmv.x.d", 0xe20282d3));
+        map.push(("fcvt.l.d", 0xc2251553));
+        map.push(("fcvt.lu.d", 0xc2351553));
+        map.push(("fcvt.d.l", 0xd2257553));
+        map.push(("fcvt.d.lu", 0xd2357553));
+        map.push(("fmv.d.x", 0xf2050553));
+        map.push(("flw", 0x00002287));
+        map.push(("fsw", 0x00502027));
+        map.push(("fld", 0x00003287));
+        map.push(("fsd", 0x0052b227));
 
-    let extracted = ins & mask;
-    extracted == format
-}
+        // NOTE: RV64C
+        map.push(("c.addi4spn", 0x0020));
+        map.push(("c.fld", 0x2008));
+        map.push(("c.lw", 0x4008));
+        map.push(("c.ld", 0x6008));
+        map.push(("c.fsd", 0xa008));
+        map.push(("c.sw", 0xc008));
+        map.push(("c.sd", 0xe008));
+        map.push(("c.nop", 0x0001));
+        map.push(("c.addi", 0x0515));
+        map.push(("c.addiw", 0x2515));
+        map.push(("c.li", 0x4515));
+        map.push(("c.addi16sp", 0x6141));
+        map.push(("c.lui", 0x6521));
+        map.push(("c.srli", 0x8121));
+        map.push(("c.srai", 0x8521));
+        map.push(("c.andi", 0x8921));
+        map.push(("c.sub", 0x8d01));
+        map.push(("c.xor", 0x8d21));
+        map.push(("c.or", 0x8d41));
+        map.push(("c.and", 0x8d61));
+        map.push(("c.subw", 0x9d01));
+        map.push(("c.addw", 0x9d21));
+        map.push(("c.beqz", 0xc801));
+        map.push(("c.bnez", 0xe801));
+        map.push(("c.j", 0xa001));
+        map.push(("c.slli", 0x0442));
+        map.push(("c.fldsp", 0x2442));
+        map.push(("c.lwsp", 0x4442));
+        map.push(("c.ldsp", 0x6442));
+        map.push(("c.jr", 0x8402));
+        map.push(("c.mv", 0x843a));
+        map.push(("c.ebreak", 0x9002));
+        map.push(("c.jalr", 0x9402));
+        map.push(("c.add", 0x942a));
+        map.push(("c.fsdsp", 0xa42a));
+        map.push(("c.swsp", 0xc42a));
+        map.push(("c.sdsp", 0xe416));
+
+        for (name, ins) in &map {
+            let Some(encoding) = find_encoding(*ins) else {
+                return Err(format!("{name}: {ins:#08x}, is not decoded at all!").into());
+            };
+
+            if encoding.name != *name {
+                return Err(format!(
+                    "{name}: {ins:#08x}, is decoded as a {} instruction!",
+                    encoding.name
+                )
+                .into());
+            }
+        }
 
-pub const fn is_rv64m_remu_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0000_0010_0000_0000_0111_0000_0011_0011;
-    let mask: u32 = 0b1111_1110_0000_0000_0111_0000_0111_1111;
+        // NOTE: Every row of the table needs an example
+        for encoding in ENCODINGS {
+            if !map.iter().any(|(name, _)| *name == encoding.name) {
+                return Err(format!("{} has no example instruction!", encoding.name).into());
+            }
+        }
 
-    let extracted = ins & mask;
-    extracted == format
-}
+        Ok(())
+    }
 
-pub const fn is_rv64m_mulw_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0000_0010_0000_0000_0000_0000_0011_1011;
-    let mask: u32 = 0b1111_1110_0000_0000_0111_0000_0111_1111;
+    #[test]
+    fn test_dispatch_matches_table_order() {
+        let linear = |ins: u32| {
+            let ins = if ins & 0b11 == 0b11 {
+                ins
+            } else {
+                ins & 0xFFFF
+            };
+            ENCODINGS
+                .iter()
+                .find(|encoding| encoding.matches(ins))
+                .filter(|encoding| !encoding.reserved)
+        };
+
+        let mut state: u32 = 0x9E37_79B9;
+        let random = (0..1_000_000).map(|_| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            state
+        });
+
+        for ins in (0..=u16::MAX as u32).chain(random) {
+            let found = find_encoding(ins).map(|encoding| encoding.name);
+            assert_eq!(
+                found,
+                linear(ins).map(|encoding| encoding.name),
+                "{ins:#010x}"
+            );
+        }
+    }
 
-    let extracted = ins & mask;
-    extracted == format
-}
+    /// An immediate spread over the instruction from bit `top` down, its bits listed in the
+    /// order the spec gives them
+    fn scattered(ins: u32, top: u32, order: &[u32]) -> u32 {
+        order
+            .iter()
+            .zip((0..=top).rev())
+            .fold(0, |imm, (bit, from)| imm | ((ins >> from) & 1) << bit)
+    }
 
-pub const fn is_rv64m_divw_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0000_0010_0000_0000_0100_0000_0011_1011;
-    let mask: u32 = 0b1111_1110_0000_0000_0111_0000_0111_1111;
+    fn sext(value: u32, bits: u32) -> i64 {
+        (i64::from(value) << (64 - bits)) >> (64 - bits)
+    }
 
-    let extracted = ins & mask;
-    extracted == format
-}
+    /// What a 16 bit instruction is according to the RV64C tables in the spec, written out
+    /// independently of the decoder. HINTs are the instruction they're encoded as, reserved
+    /// encodings are `None`
+    fn spec_rvc(ins: u32) -> Option<String> {
+        let funct3 = ins >> 13;
+        let rd = (ins >> 7) & 0x1F;
+        let rs2 = (ins >> 2) & 0x1F;
+        let rd_p = 8 + ((ins >> 7) & 0b111);
+        let rs2_p = 8 + ((ins >> 2) & 0b111);
+        let imm6 = scattered(ins, 12, &[5]) | scattered(ins, 6, &[4, 3, 2, 1, 0]);
+        let uimm_d = scattered(ins, 12, &[5, 4, 3]) | scattered(ins, 6, &[7, 6]);
+        let uimm_w = scattered(ins, 12, &[5, 4, 3]) | scattered(ins, 6, &[2, 6]);
+
+        let text = match (ins & 0b11, funct3) {
+            (0b00, 0b000) => {
+                let nzuimm = scattered(ins, 12, &[5, 4, 9, 8, 7, 6, 2, 3]);
+                if nzuimm == 0 {
+                    return None;
+                }
+                format!("c.addi4spn x{rs2_p}, x2, {nzuimm}")
+            }
+            (0b00, 0b001) => format!("c.fld f{rs2_p}, {uimm_d}(x{rd_p})"),
+            (0b00, 0b010) => format!("c.lw x{rs2_p}, {uimm_w}(x{rd_p})"),
+            (0b00, 0b011) => format!("c.ld x{rs2_p}, {uimm_d}(x{rd_p})"),
+            (0b00, 0b100) => return None,
+            (0b00, 0b101) => format!("c.fsd f{rs2_p}, {uimm_d}(x{rd_p})"),
+            (0b00, 0b110) => format!("c.sw x{rs2_p}, {uimm_w}(x{rd_p})"),
+            (0b00, 0b111) => format!("c.sd x{rs2_p}, {uimm_d}(x{rd_p})"),
+
+            (0b01, 0b000) if ins == 0x0001 => "c.nop".to_string(),
+            (0b01, 0b000) => format!("c.addi x{rd}, {}", sext(imm6, 6)),
+            (0b01, 0b001) if rd == 0 => return None,
+            (0b01, 0b001) => format!("c.addiw x{rd}, {}", sext(imm6, 6)),
+            (0b01, 0b010) => format!("c.li x{rd}, {}", sext(imm6, 6)),
+            (0b01, 0b011) if rd == 2 => {
+                let nzimm = scattered(ins, 12, &[9]) | scattered(ins, 6, &[4, 6, 8, 7, 5]);
+                if nzimm == 0 {
+                    return None;
+                }
+                format!("c.addi16sp x2, {}", sext(nzimm, 10))
+            }
+            (0b01, 0b011) => {
+                let nzimm = scattered(ins, 12, &[17]) | scattered(ins, 6, &[16, 15, 14, 13, 12]);
+                if nzimm == 0 {
+                    return None;
+                }
+                format!("c.lui x{rd}, {:#x}", (sext(nzimm, 18) >> 12) & 0xF_FFFF)
+            }
+            (0b01, 0b100) => match ((ins >> 10) & 0b11, (ins >> 12) & 1, (ins >> 5) & 0b11) {
+                (0b00, _, _) => format!("c.srli x{rd_p}, {imm6}"),
+                (0b01, _, _) => format!("c.srai x{rd_p}, {imm6}"),
+                (0b10, _, _) => format!("c.andi x{rd_p}, {}", sext(imm6, 6)),
+                (_, 0, 0b00) => format!("c.sub x{rd_p}, x{rs2_p}"),
+                (_, 0, 0b01) => format!("c.xor x{rd_p}, x{rs2_p}"),
+                (_, 0, 0b10) => format!("c.or x{rd_p}, x{rs2_p}"),
+                (_, 0, _) => format!("c.and x{rd_p}, x{rs2_p}"),
+                (_, _, 0b00) => format!("c.subw x{rd_p}, x{rs2_p}"),
+                (_, _, 0b01) => format!("c.addw x{rd_p}, x{rs2_p}"),
+                _ => return None,
+            },
+            (0b01, 0b101) => {
+                let offset = scattered(ins, 12, &[11, 4, 9, 8, 10, 6, 7, 3, 2, 1, 5]);
+                format!("c.j {}", sext(offset, 12))
+            }
+            (0b01, _) => {
+                let offset = scattered(ins, 12, &[8, 4, 3]) | scattered(ins, 6, &[7, 6, 2, 1, 5]);
+                let name = if funct3 == 0b110 { "c.beqz" } else { "c.bnez" };
+                format!("{name} x{rd_p}, {}", sext(offset, 9))
+            }
+
+            (0b10, 0b000) => format!("c.slli x{rd}, {imm6}"),
+            (0b10, 0b001) => {
+                let uimm = scattered(ins, 12, &[5]) | scattered(ins, 6, &[4, 3, 8, 7, 6]);
+                format!("c.fldsp f{rd}, {uimm}(x2)")
+            }
+            (0b10, 0b010 | 0b011) if rd == 0 => return None,
+            (0b10, 0b010) => {
+                let uimm = scattered(ins, 12, &[5]) | scattered(ins, 6, &[4, 3, 2, 7, 6]);
+                format!("c.lwsp x{rd}, {uimm}(x2)")
+            }
+            (0b10, 0b011) => {
+                let uimm = scattered(ins, 12, &[5]) | scattered(ins, 6, &[4, 3, 8, 7, 6]);
+                format!("c.ldsp x{rd}, {uimm}(x2)")
+            }
+            (0b10, 0b100) => match ((ins >> 12) & 1, rd, rs2) {
+                (0, 0, 0) => return None,
+                (0, _, 0) => format!("c.jr x{rd}"),
+                (0, _, _) => format!("c.mv x{rd}, x{rs2}"),
+                (_, 0, 0) => "c.ebreak".to_string(),
+                (_, _, 0) => format!("c.jalr x{rd}"),
+                _ => format!("c.add x{rd}, x{rs2}"),
+            },
+            (0b10, 0b101) => {
+                let uimm = scattered(ins, 12, &[5, 4, 3, 8, 7, 6]);
+                format!("c.fsdsp f{rs2}, {uimm}(x2)")
+            }
+            (0b10, 0b110) => {
+                let uimm = scattered(ins, 12, &[5, 4, 3, 2, 7, 6]);
+                format!("c.swsp x{rs2}, {uimm}(x2)")
+            }
+            (0b10, _) => {
+                let uimm = scattered(ins, 12, &[5, 4, 3, 8, 7, 6]);
+                format!("c.sdsp x{rs2}, {uimm}(x2)")
+            }
+
+            _ => unreachable!("{ins:#06x} isn't compressed"),
+        };
+
+        Some(text)
+    }
 
-pub const fn is_rv64m_divuw_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0000_0010_0000_0000_0101_0000_0011_1011;
-    let mask: u32 = 0b1111_1110_0000_0000_0111_0000_0111_1111;
+    #[test]
+    fn test_every_compressed_encoding() {
+        for ins in (0..=u16::MAX as u32).filter(|ins| ins & 0b11 != 0b11) {
+            let decoded = decode(ins);
+            let found = find_encoding(ins).map(|_| decoded.to_string());
+            assert_eq!(found, spec_rvc(ins), "{ins:#06x}");
+
+            if found.is_none() {
+                assert!(
+                    matches!(decoded, RV64GCInstruction::IllegalInstruction(bits) if bits == ins),
+                    "{ins:#06x} decodes as {decoded:?}"
+                );
+            }
+        }
+    }
 
-    let extracted = ins & mask;
-    extracted == format
-}
+    #[test]
+    fn test_bit_manipulation() {
+        // NOTE: The immediate forms take a 6 bit shift amount, the word ones 5 bits
+        let cases = [
+            (0x0a35951b, "slli.uw x10, x11, 35"),
+            (0x62d5d513, "rori x10, x11, 45"),
+            (0x60d5d51b, "roriw x10, x11, 13"),
+            (0x4bf59513, "bclri x10, x11, 63"),
+            (0x0805c53b, "zext.h x10, x11"),
+            (0x2062c4b3, "sh2add x9, x5, x6"),
+        ];
+
+        for (ins, text) in cases {
+            assert_eq!(decode(ins).to_string(), text);
+        }
 
-pub const fn is_rv64m_remw_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0000_0010_0000_0000_0110_0000_0011_1011;
-    let mask: u32 = 0b1111_1110_0000_0000_0111_0000_0111_1111;
+        // NOTE: The table always decodes them, the cpu rejects disabled extensions, which
+        // is all of them by default
+        let mut cpu = crate::cpu::RV64GC::new();
+        let (ror, add_uw) = (0x60c5d533, 0x08c5853b);
+        assert!(matches!(
+            cpu.find_instruction(ror),
+            RV64GCInstruction::IllegalInstruction(bits) if bits == ror
+        ));
+
+        cpu.isa = crate::isa::Isa::RV64GC_ZB;
+        assert!(matches!(
+            cpu.find_instruction(ror),
+            RV64GCInstruction::Ror(10, 11, 12)
+        ));
+
+        cpu.isa = "rv64gc_zba".parse().unwrap();
+        assert!(matches!(
+            cpu.find_instruction(ror),
+            RV64GCInstruction::IllegalInstruction(bits) if bits == ror
+        ));
+        assert!(matches!(
+            cpu.find_instruction(add_uw),
+            RV64GCInstruction::Adduw(10, 11, 12)
+        ));
+
+        assert_eq!(
+            "rv64gc_zbq".parse::<crate::isa::Isa>(),
+            Err(crate::isa::IsaError::UnknownExtension("zbq".to_string()))
+        );
+    }
 
-    let extracted = ins & mask;
-    extracted == format
-}
+    #[test]
+    fn test_vector() {
+        // NOTE: Loads and stores have a row per width, the fields come from the encoding
+        let cases = [
+            (0x0515f557, "vsetvli x10, x11, e32, m2, ta, mu"),
+            (0xc872f557, "vsetivli x10, 5, e8, mf2, tu, ma"),
+            (0x008db257, "vadd.vi v4, v8, -5, v0.t"),
+            (0x968fb257, "vsll.vi v4, v8, 31"),
+            (0xb6856257, "vmacc.vx v4, x10, v8"),
+            (0x44860257, "vmadc.vvm v4, v8, v12, v0"),
+            (0x46860257, "vmadc.vv v4, v8, v12"),
+            (0x40055207, "vlseg3e16.v v4, (x10), v0.t"),
+            (0x26856207, "vluxseg2ei32.v v4, (x10), v8"),
+            (0x2ab57227, "vssseg2e64.v v4, (x10), x11"),
+            (0x03050207, "vle8ff.v v4, (x10)"),
+            (0x62856207, "vl4re32.v v4, (x10)"),
+            (0xe2850427, "vs8r.v v8, (x10)"),
+            (0x9f01b457, "vmv4r.v v8, v16"),
+            (0x42801557, "vfmv.f.s f10, v8"),
+            (0x40882557, "vcpop.m x10, v8, v0.t"),
+            (0x4a861257, "vfwcvt.f.f.v v4, v8"),
+        ];
+
+        for (ins, text) in cases {
+            assert_eq!(decode(ins).to_string(), text);
+        }
 
-pub const fn is_rv64m_remuw_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0000_0010_0000_0000_0111_0000_0011_1011;
-    let mask: u32 = 0b1111_1110_0000_0000_0111_0000_0111_1111;
+        let mut cpu = crate::cpu::RV64GC::new();
+        assert!(matches!(
+            cpu.find_instruction(0x008db257),
+            RV64GCInstruction::IllegalInstruction(_)
+        ));
+        cpu.isa = "rv64gcv".parse().unwrap();
+        assert!(matches!(
+            cpu.find_instruction(0x008db257),
+            RV64GCInstruction::Vadd(_)
+        ));
+        cpu.isa = "rv64gc_zba".parse().unwrap();
+        assert!(matches!(
+            cpu.find_instruction(0x008db257),
+            RV64GCInstruction::IllegalInstruction(_)
+        ));
+
+        let isa: crate::isa::Isa = "rv64gcv_zvl512b".parse().unwrap();
+        assert!(isa.v);
+        assert_eq!(isa.vlenb(), 64);
+        assert_eq!(
+            "rv64gcv_zvl96b".parse::<crate::isa::Isa>(),
+            Err(crate::isa::IsaError::Vlen("zvl96b".to_string()))
+        );
+    }
 
-    let extracted = ins & mask;
-    extracted == format
-}
-
-// RV64A
-
-pub const fn is_rv64a_lrw_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0001_0000_0000_0000_0010_0000_0010_1111;
-    let mask: u32 = 0b1111_1001_1111_0000_0111_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64a_scw_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0001_1000_0000_0000_0010_0000_0010_1111;
-    let mask: u32 = 0b1111_1000_0000_0000_0111_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64a_amoswapw_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0000_1000_0000_0000_0010_0000_0010_1111;
-    let mask: u32 = 0b1111_1000_0000_0000_0111_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64a_amoaddw_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0000_0000_0000_0000_0010_0000_0010_1111;
-    let mask: u32 = 0b1111_1000_0000_0000_0111_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64a_amoxorw_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0010_0000_0000_0000_0010_0000_0010_1111;
-    let mask: u32 = 0b1111_1000_0000_0000_0111_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64a_amoandw_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0110_0000_0000_0000_0010_0000_0010_1111;
-    let mask: u32 = 0b1111_1000_0000_0000_0111_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64a_amoorw_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0100_0000_0000_0000_0010_0000_0010_1111;
-    let mask: u32 = 0b1111_1000_0000_0000_0111_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64a_amominw_instruction(ins: u32) -> bool {
-    let format: u32 = 0b1000_0000_0000_0000_0010_0000_0010_1111;
-    let mask: u32 = 0b1111_1000_0000_0000_0111_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64a_amomaxw_instruction(ins: u32) -> bool {
-    let format: u32 = 0b1010_0000_0000_0000_0010_0000_0010_1111;
-    let mask: u32 = 0b1111_1000_0000_0000_0111_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64a_amominuw_instruction(ins: u32) -> bool {
-    let format: u32 = 0b1100_0000_0000_0000_0010_0000_0010_1111;
-    let mask: u32 = 0b1111_1000_0000_0000_0111_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64a_amomaxuw_instruction(ins: u32) -> bool {
-    let format: u32 = 0b1110_0000_0000_0000_0010_0000_0010_1111;
-    let mask: u32 = 0b1111_1000_0000_0000_0111_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64a_lrd_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0001_0000_0000_0000_0011_0000_0010_1111;
-    let mask: u32 = 0b1111_1000_0000_0000_0111_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64a_scd_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0001_1000_0000_0000_0011_0000_0010_1111;
-    let mask: u32 = 0b1111_1000_0000_0000_0111_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64a_amoswapd_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0000_1000_0000_0000_0011_0000_0010_1111;
-    let mask: u32 = 0b1111_1000_0000_0000_0111_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64a_amoaddd_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0000_0000_0000_0000_0011_0000_0010_1111;
-    let mask: u32 = 0b1111_1000_0000_0000_0111_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64a_amoxord_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0010_0000_0000_0000_0011_0000_0010_1111;
-    let mask: u32 = 0b1111_1000_0000_0000_0111_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64a_amoandd_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0110_0000_0000_0000_0011_0000_0010_1111;
-    let mask: u32 = 0b1111_1000_0000_0000_0111_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64a_amoord_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0100_0000_0000_0000_0011_0000_0010_1111;
-    let mask: u32 = 0b1111_1000_0000_0000_0111_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64a_amomind_instruction(ins: u32) -> bool {
-    let format: u32 = 0b1000_0000_0000_0000_0011_0000_0010_1111;
-    let mask: u32 = 0b1111_1000_0000_0000_0111_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64a_amomaxd_instruction(ins: u32) -> bool {
-    let format: u32 = 0b1010_0000_0000_0000_0011_0000_0010_1111;
-    let mask: u32 = 0b1111_1000_0000_0000_0111_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64a_amominud_instruction(ins: u32) -> bool {
-    let format: u32 = 0b1100_0000_0000_0000_0011_0000_0010_1111;
-    let mask: u32 = 0b1111_1000_0000_0000_0111_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64a_amomaxud_instruction(ins: u32) -> bool {
-    let format: u32 = 0b1110_0000_0000_0000_0011_0000_0010_1111;
-    let mask: u32 = 0b1111_1000_0000_0000_0111_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-// RV64FD
-
-pub const fn is_rv64f_fmadds_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0000_0000_0000_0000_0000_0000_0100_0011;
-    let mask: u32 = 0b0000_0110_0000_0000_0000_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64f_fmsubs_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0000_0000_0000_0000_0000_0000_0100_0111;
-    let mask: u32 = 0b0000_0110_0000_0000_0000_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64f_fnmsubs_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0000_0000_0000_0000_0000_0000_0100_1011;
-    let mask: u32 = 0b0000_0110_0000_0000_0000_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64f_fnmadds_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0000_0000_0000_0000_0000_0000_0100_1111;
-    let mask: u32 = 0b0000_0110_0000_0000_0000_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64f_fadds_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0000_0000_0000_0000_0000_0000_0101_0011;
-    let mask: u32 = 0b1111_1110_0000_0000_0000_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64f_fsubs_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0000_1000_0000_0000_0000_0000_0101_0011;
-    let mask: u32 = 0b1111_1110_0000_0000_0000_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64f_fmuls_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0001_0000_0000_0000_0000_0000_0101_0011;
-    let mask: u32 = 0b1111_1110_0000_0000_0000_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64f_fdivs_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0001_1000_0000_0000_0000_0000_0101_0011;
-    let mask: u32 = 0b1111_1110_0000_0000_0000_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64f_fsqrts_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0101_1000_0000_0000_0000_0000_0101_0011;
-    let mask: u32 = 0b1111_1110_0000_0000_0000_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64f_fsgnjs_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0010_0000_0000_0000_0000_0000_0101_0011;
-    let mask: u32 = 0b1111_1110_0000_0000_0111_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64f_fsgnjns_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0010_0000_0000_0000_0001_0000_0101_0011;
-    let mask: u32 = 0b1111_1110_0000_0000_0111_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64f_fsgnjxs_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0010_0000_0000_0000_0010_0000_0101_0011;
-    let mask: u32 = 0b1111_1110_0000_0000_0111_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64f_fmins_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0010_1000_0000_0000_0000_0000_0101_0011;
-    let mask: u32 = 0b1111_1110_0000_0000_0111_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64f_fmaxs_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0010_1000_0000_0000_0001_0000_0101_0011;
-    let mask: u32 = 0b1111_1110_0000_0000_0111_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64f_fcvtws_instruction(ins: u32) -> bool {
-    let format: u32 = 0b1100_0000_0000_0000_0000_0000_0101_0011;
-    let mask: u32 = 0b1111_1111_1111_0000_0000_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64f_fcvtwus_instruction(ins: u32) -> bool {
-    let format: u32 = 0b1100_0000_0001_0000_0000_0000_0101_0011;
-    let mask: u32 = 0b1111_1111_1111_0000_0000_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64f_fmvxw_instruction(ins: u32) -> bool {
-    let format: u32 = 0b1110_0000_0000_0000_0000_0000_0101_0011;
-    let mask: u32 = 0b1111_1111_1111_0000_0111_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64f_feqs_instruction(ins: u32) -> bool {
-    let format: u32 = 0b1010_0000_0000_0000_0010_0000_0101_0011;
-    let mask: u32 = 0b1111_1110_0000_0000_0111_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64f_flts_instruction(ins: u32) -> bool {
-    let format: u32 = 0b1010_0000_0000_0000_0001_0000_0101_0011;
-    let mask: u32 = 0b1111_1110_0000_0000_0111_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64f_fles_instruction(ins: u32) -> bool {
-    let format: u32 = 0b1010_0000_0000_0000_0000_0000_0101_0011;
-    let mask: u32 = 0b1111_1110_0000_0000_0111_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64f_fclasss_instruction(ins: u32) -> bool {
-    let format: u32 = 0b1110_0000_0000_0000_0001_0000_0101_0011;
-    let mask: u32 = 0b1111_1111_1111_0000_0111_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64f_fcvtsw_instruction(ins: u32) -> bool {
-    let format: u32 = 0b1101_0000_0000_0000_0000_0000_0101_0011;
-    let mask: u32 = 0b1111_1111_1111_0000_0000_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64f_fcvtswu_instruction(ins: u32) -> bool {
-    let format: u32 = 0b1101_0000_0001_0000_0000_0000_0101_0011;
-    let mask: u32 = 0b1111_1111_1111_0000_0000_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64f_fmvwx_instruction(ins: u32) -> bool {
-    let format: u32 = 0b1111_0000_0000_0000_0000_0000_0101_0011;
-    let mask: u32 = 0b1111_1111_1111_0000_0111_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-// RV64D
-
-pub const fn is_rv64f_fmaddd_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0000_0010_0000_0000_0000_0000_0100_0011;
-    let mask: u32 = 0b0000_0110_0000_0000_0000_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64f_fmsubd_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0000_0010_0000_0000_0000_0000_0100_0111;
-    let mask: u32 = 0b0000_0110_0000_0000_0000_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64f_fnmsubd_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0000_0010_0000_0000_0000_0000_0100_1011;
-    let mask: u32 = 0b0000_0110_0000_0000_0000_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64f_fnmaddd_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0000_0010_0000_0000_0000_0000_0100_1111;
-    let mask: u32 = 0b0000_0110_0000_0000_0000_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64f_faddd_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0000_0010_0000_0000_0000_0000_0101_0011;
-    let mask: u32 = 0b1111_1110_0000_0000_0000_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64f_fsubd_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0000_1010_0000_0000_0000_0000_0101_0011;
-    let mask: u32 = 0b1111_1110_0000_0000_0000_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64f_fmuld_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0001_0010_0000_0000_0000_0000_0101_0011;
-    let mask: u32 = 0b1111_1110_0000_0000_0000_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64f_fdivd_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0001_1010_0000_0000_0000_0000_0101_0011;
-    let mask: u32 = 0b1111_1110_0000_0000_0000_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64f_fsqrtd_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0101_1010_0000_0000_0000_0000_0101_0011;
-    let mask: u32 = 0b1111_1110_0000_0000_0000_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64f_fsgnjd_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0010_0010_0000_0000_0000_0000_0101_0011;
-    let mask: u32 = 0b1111_1110_0000_0000_0111_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64f_fsgnjnd_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0010_0010_0000_0000_0001_0000_0101_0011;
-    let mask: u32 = 0b1111_1110_0000_0000_0111_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64f_fsgnjxd_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0010_0010_0000_0000_0010_0000_0101_0011;
-    let mask: u32 = 0b1111_1110_0000_0000_0111_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64f_fmind_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0010_1010_0000_0000_0000_0000_0101_0011;
-    let mask: u32 = 0b1111_1110_0000_0000_0111_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64f_fmaxd_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0010_1010_0000_0000_0001_0000_0101_0011;
-    let mask: u32 = 0b1111_1110_0000_0000_0111_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64f_fcvtwd_instruction(ins: u32) -> bool {
-    let format: u32 = 0b1100_0010_0000_0000_0000_0000_0101_0011;
-    let mask: u32 = 0b1111_1111_1111_0000_0000_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64f_fcvtwud_instruction(ins: u32) -> bool {
-    let format: u32 = 0b1100_0010_0001_0000_0000_0000_0101_0011;
-    let mask: u32 = 0b1111_1111_1111_0000_0000_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64f_fcvtdw_instruction(ins: u32) -> bool {
-    let format: u32 = 0b1101_0010_0000_0000_0000_0000_0101_0011;
-    let mask: u32 = 0b1111_1111_1111_0000_0000_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64f_fcvtdwu_instruction(ins: u32) -> bool {
-    let format: u32 = 0b1101_0010_0001_0000_0000_0000_0101_0011;
-    let mask: u32 = 0b1111_1111_1111_0000_0000_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64f_fmvxd_instruction(ins: u32) -> bool {
-    let format: u32 = 0b1110_0010_0000_0000_0000_0000_0101_0011;
-    let mask: u32 = 0b1111_1111_1111_0000_0111_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64f_feqd_instruction(ins: u32) -> bool {
-    let format: u32 = 0b1010_0010_0000_0000_0010_0000_0101_0011;
-    let mask: u32 = 0b1111_1110_0000_0000_0111_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64f_fltd_instruction(ins: u32) -> bool {
-    let format: u32 = 0b1010_0010_0000_0000_0001_0000_0101_0011;
-    let mask: u32 = 0b1111_1110_0000_0000_0111_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64f_fled_instruction(ins: u32) -> bool {
-    let format: u32 = 0b1010_0010_0000_0000_0000_0000_0101_0011;
-    let mask: u32 = 0b1111_1110_0000_0000_0111_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64f_fclassd_instruction(ins: u32) -> bool {
-    let format: u32 = 0b1110_0010_0000_0000_0001_0000_0101_0011;
-    let mask: u32 = 0b1111_1111_1111_0000_0111_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64f_fcvtsd_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0100_0000_0001_0000_0000_0000_0101_0011;
-    let mask: u32 = 0b1111_1111_1111_0000_0000_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64f_fcvtds_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0100_0010_0000_0000_0000_0000_0101_0011;
-    let mask: u32 = 0b1111_1111_1111_0000_0000_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64f_flw_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0000_0000_0000_0000_0010_0000_0000_0111;
-    let mask: u32 = 0b0000_0000_0000_0000_0111_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64f_fsw_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0000_0000_0000_0000_0010_0000_0010_0111;
-    let mask: u32 = 0b0000_0000_0000_0000_0111_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64f_fld_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0000_0000_0000_0000_0011_0000_0000_0111;
-    let mask: u32 = 0b0000_0000_0000_0000_0111_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64f_fsd_instruction(ins: u32) -> bool {
-    let format: u32 = 0b0000_0000_0000_0000_0011_0000_0010_0111;
-    let mask: u32 = 0b0000_0000_0000_0000_0111_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-// NOTE: RV64C
-pub const fn is_rv64c_addi4spn_instruction(ins: u16) -> bool {
-    let format: u16 = 0b0000_0000_0000_0000;
-    let mask: u16 = 0b1110_0000_0000_0011;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-pub const fn is_rv64c_fld_instruction(ins: u16) -> bool {
-    let format: u16 = 0b0010_0000_0000_0000;
-    let mask: u16 = 0b1110_0000_0000_0011;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-pub const fn is_rv64c_lw_instruction(ins: u16) -> bool {
-    let format: u16 = 0b0100_0000_0000_0000;
-    let mask: u16 = 0b1110_0000_0000_0011;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-pub const fn is_rv64c_ld_instruction(ins: u16) -> bool {
-    let format: u16 = 0b0110_0000_0000_0000;
-    let mask: u16 = 0b1110_0000_0000_0011;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-pub const fn is_rv64c_fsd_instruction(ins: u16) -> bool {
-    let format: u16 = 0b1010_0000_0000_0000;
-    let mask: u16 = 0b1110_0000_0000_0011;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-pub const fn is_rv64c_sw_instruction(ins: u16) -> bool {
-    let format: u16 = 0b1100_0000_0000_0000;
-    let mask: u16 = 0b1110_0000_0000_0011;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-pub const fn is_rv64c_sd_instruction(ins: u16) -> bool {
-    let format: u16 = 0b1110_0000_0000_0000;
-    let mask: u16 = 0b1110_0000_0000_0011;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64c_nop_instruction(ins: u16) -> bool {
-    let format: u16 = 0b0000_0000_0000_0001;
-    let mask: u16 = u16::MAX;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub fn is_rv64c_addi_instruction(ins: u16) -> bool {
-    let format: u16 = 0b0000_0000_0000_0001;
-    let mask: u16 = 0b1110_0000_0000_0011;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64c_addiw_instruction(ins: u16) -> bool {
-    let format: u16 = 0b0010_0000_0000_0001;
-    let mask: u16 = 0b1110_0000_0000_0011;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64c_li_instruction(ins: u16) -> bool {
-    let format: u16 = 0b0100_0000_0000_0001;
-    let mask: u16 = 0b1110_0000_0000_0011;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64c_addi16sp_instruction(ins: u16) -> bool {
-    let format: u16 = 0b0110_0001_0000_0001;
-    let mask: u16 = 0b1110_1111_1000_0011;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-// WARNING: This must come before matching c.addi16sp
-pub const fn is_rv64c_lui_instruction(ins: u16) -> bool {
-    let format: u16 = 0b0110_0000_0000_0001;
-    let mask: u16 = 0b1110_0000_0000_0011;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64c_srli_instruction(ins: u16) -> bool {
-    let format: u16 = 0b1000_0000_0000_0001;
-    let mask: u16 = 0b1110_1100_0000_0011;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64c_srai_instruction(ins: u16) -> bool {
-    let format: u16 = 0b1000_0100_0000_0001;
-    let mask: u16 = 0b1110_1100_0000_0011;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64c_andi_instruction(ins: u16) -> bool {
-    let format: u16 = 0b1000_1000_0000_0001;
-    let mask: u16 = 0b1110_1100_0000_0011;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64c_sub_instruction(ins: u16) -> bool {
-    let format: u16 = 0b1000_1100_0000_0001;
-    let mask: u16 = 0b1111_1100_0110_0011;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64c_xor_instruction(ins: u16) -> bool {
-    let format: u16 = 0b1000_1100_0010_0001;
-    let mask: u16 = 0b1111_1100_0110_0011;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-pub const fn is_rv64c_or_instruction(ins: u16) -> bool {
-    let format: u16 = 0b1000_1100_0100_0001;
-    let mask: u16 = 0b1111_1100_0110_0011;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64c_and_instruction(ins: u16) -> bool {
-    let format: u16 = 0b1000_1100_0110_0001;
-    let mask: u16 = 0b1111_1100_0110_0011;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64c_subw_instruction(ins: u16) -> bool {
-    let format: u16 = 0b1001_1100_0000_0001;
-    let mask: u16 = 0b1111_1100_0110_0011;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64c_addw_instruction(ins: u16) -> bool {
-    let format: u16 = 0b1001_1100_0010_0001;
-    let mask: u16 = 0b1111_1100_0110_0011;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64c_j_instruction(ins: u16) -> bool {
-    let format: u16 = 0b1010_0000_0000_0001;
-    let mask: u16 = 0b1110_0000_0000_0011;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-pub const fn is_rv64c_beqz_instruction(ins: u16) -> bool {
-    let format: u16 = 0b1100_0000_0000_0001;
-    let mask: u16 = 0b1110_0000_0000_0011;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-pub const fn is_rv64c_bnez_instruction(ins: u16) -> bool {
-    let format: u16 = 0b1110_0000_0000_0001;
-    let mask: u16 = 0b1110_0000_0000_0011;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-pub const fn is_rv64c_slli_instruction(ins: u16) -> bool {
-    let format: u16 = 0b0000_0000_0000_0010;
-    let mask: u16 = 0b1110_0000_0000_0011;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64c_fldsp_instruction(ins: u16) -> bool {
-    let format: u16 = 0b0010_0000_0000_0010;
-    let mask: u16 = 0b1110_0000_0000_0011;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-pub const fn is_rv64c_lwsp_instruction(ins: u16) -> bool {
-    let format: u16 = 0b0100_0000_0000_0010;
-    let mask: u16 = 0b1110_0000_0000_0011;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64c_ldsp_instruction(ins: u16) -> bool {
-    let format: u16 = 0b0110_0000_0000_0010;
-    let mask: u16 = 0b1110_0000_0000_0011;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-// WARNING: Must be checked before c.mv
-pub const fn is_rv64c_jr_instruction(ins: u16) -> bool {
-    let format: u16 = 0b1000_0000_0000_0010;
-    let mask: u16 = 0b1111_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-pub const fn is_rv64c_mv_instruction(ins: u16) -> bool {
-    let format: u16 = 0b1000_0000_0000_0010;
-    let mask: u16 = 0b1111_0000_0000_0011;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-pub const fn is_rv64c_ebreak_instruction(ins: u16) -> bool {
-    let format: u16 = 0b1001_0000_0000_0010;
-    let mask: u16 = 0b1111_1111_1111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-pub const fn is_rv64c_jalr_instruction(ins: u16) -> bool {
-    let format: u16 = 0b1001_0000_0000_0010;
-    let mask: u16 = 0b1111_0000_0111_1111;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-pub const fn is_rv64c_add_instruction(ins: u16) -> bool {
-    let format: u16 = 0b1001_0000_0000_0010;
-    let mask: u16 = 0b1111_0000_0000_0011;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-pub const fn is_rv64c_fsdsp_instruction(ins: u16) -> bool {
-    let format: u16 = 0b1010_0000_0000_0010;
-    let mask: u16 = 0b1110_0000_0000_0011;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-pub const fn is_rv64c_swsp_instruction(ins: u16) -> bool {
-    let format: u16 = 0b1100_0000_0000_0010;
-    let mask: u16 = 0b1110_0000_0000_0011;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-pub const fn is_rv64c_sdsp_instruction(ins: u16) -> bool {
-    let format: u16 = 0b1110_0000_0000_0010;
-    let mask: u16 = 0b1110_0000_0000_0011;
-
-    let extracted = ins & mask;
-    extracted == format
-}
-
-#[cfg(test)]
-pub mod tests {
-    use super::*;
-
-    #[test]
-    #[allow(clippy::vec_init_then_push)]
-    fn test_all_instructions() -> Result<(), Box<dyn std::error::Error>> {
-        let mut map: Vec<(&str, u32)> = Vec::new();
-        map.push(("add", 0x00a282b3));
-        map.push(("addi", 0x00500013));
-        map.push(("auipc", 0x00123297));
-        map.push(("slti", 0x0052a293));
-        map.push(("sltiu", 0x0052b293));
-        map.push(("xori", 0x0052c293));
-        map.push(("ori", 0x2142e293));
-        map.push(("andi", 0x0051fb93));
-        map.push(("slli", 0x02d61393));
-        map.push(("srli", 0x02d65393));
-        map.push(("srai", 0x42d65393));
-        map.push(("sub", 0x405302b3));
-        map.push(("sll", 0x019192b3));
-        map.push(("sll", 0x00f696b3));
-        map.push(("slt", 0x0191a2b3));
-        map.push(("sltu", 0x0191b2b3));
-        map.push(("xor", 0x001d4fb3));
-        map.push(("srl", 0x0191d2b3));
-        map.push(("sra", 0x4191d2b3));
-        map.push(("or", 0x0191e2b3));
-        map.push(("and", 0x0191f2b3));
-        map.push(("ecall", 0x00000073));
-        map.push(("ebreak", 0x00100073));
-        map.push(("lb", 0x000e0283));
-        map.push(("lh", 0x000e1283));
-        map.push(("lw", 0x000e2283));
-        map.push(("lbu", 0x000e4283));
-        map.push(("lhu", 0x000e5283));
-        map.push(("sb", 0x005e0023));
-        map.push(("sh", 0x005e1023));
-        map.push(("sw", 0x005e2023));
-        map.push(("jal", 0x00400c6f));
-        map.push(("jalr", 0x00428c67));
-        map.push(("beq", 0x005c0263));
-        map.push(("bne", 0x005c1263));
-        map.push(("bge", 0x005c5263));
-        map.push(("bgeu", 0x005c7263));
-        map.push(("blt", 0x005c4263));
-        map.push(("bltu", 0x005c6263));
-        map.push(("ld", 0x00043283));
-        map.push(("sd", 0x00543023));
-        map.push(("addiw", 0x0007879b));
-        map.push(("slliw", 0x0052929b));
-        map.push(("srliw", 0x0052d29b));
-        map.push(("sraiw", 0x41f7d79b));
-        map.push(("subw", 0x40f007bb));
-        map.push(("addw", 0x00f707bb));
-        map.push(("sllw", 0x005292bb));
-        map.push(("srlw", 0x0052d2bb));
-        map.push(("sraw", 0x4052d2bb));
-        map.push(("lwu", 0x0002e283));
-        map.push(("mul", 0x025282b3));
-        map.push(("mulh", 0x025292b3));
-        map.push(("mulhu", 0x0252b2b3));
-        map.push(("mulhsu", 0x0252a2b3));
-        map.push(("div", 0x0252c2b3));
-        map.push(("divu", 0x0252d2b3));
-        map.push(("rem", 0x0252e2b3));
-        map.push(("remu", 0x0252f2b3));
-        map.push(("mulw", 0x035282bb));
-        map.push(("divw", 0x0352c2bb));
-        map.push(("divuw", 0x0352d2bb));
-        map.push(("remw", 0x0352e2bb));
-        map.push(("remuw", 0x0352f2bb));
-        map.push(("lrw", 0x1002a2af));
-        map.push(("scw", 0x1852a2af));
-        map.push(("amoswapw", 0x0852a2af));
-        map.push(("amoaddw", 0x0052a2af));
-        map.push(("amoxorw", 0x2052a2af));
-        map.push(("amoorw", 0x4052a2af));
-        map.push(("amoandw", 0x6052a2af));
-        map.push(("amominw", 0x8052a2af));
-        map.push(("amominuw", 0xc052a2af));
-        map.push(("amomaxw", 0xa052a2af));
-        map.push(("amomaxuw", 0xe052a2af));
-        map.push(("lrd", 0x1002b2af));
-        map.push(("scd", 0x185032af));
-        map.push(("amoswapd", 0x085332af));
-        map.push(("amoandd", 0x605332af));
-        map.push(("amoaddd", 0x005332af));
-        map.push(("amoxord", 0x205332af));
-        map.push(("amoord", 0x405332af));
-        map.push(("amomind", 0x805332af));
-        map.push(("amominud", 0xc05332af));
-        map.push(("amomaxd", 0xa05332af));
-        map.push(("amomaxud", 0xe05332af));
-        map.push(("fmadds", 0x1852f343));
-        map.push(("fmsubs", 0x1852f347));
-        map.push(("fnmsubs", 0x1852f34b));
-        map.push(("fnmadds", 0x1852f34f));
-        map.push(("fsubs", 0x0852f353));
-        map.push(("fadds", 0x0010f053));
-        map.push(("fmuls", 0x1052f353));
-        map.push(("fdivs", 0x1852f353));
-        map.push(("fsqrts", 0x5802f353));
-        map.push(("fsgnjs", 0x205302d3));
-        map.push(("fsgnjns", 0x205312d3));
-        map.push(("fsgnjxs", 0x205322d3));
-        map.push(("fmins", 0x285302d3));
-        map.push(("fmaxs", 0x285312d3));
-        map.push(("fcvtws", 0xc00372d3));
-        map.push(("fcvtwus", 0xc01372d3));
-        map.push(("fmvwx", 0xf00082d3));
-        map.push(("feqs", 0xa07322d3));
-        map.push(("flts", 0xa07312d3));
-        map.push(("fles", 0xa07302d3));
-        map.push(("fclasss", 0xe00312d3));
-        map.push(("fcvtsw", 0xd002f1d3));
-        map.push(("fcvtswu", 0xd012f1d3));
-        map.push(("fmvxw", 0xe00280d3));
-
-        // NOTE: RV64D
-        map.push(("fmaddd", 0x2252f343));
-        map.push(("fmsubd", 0x2252f347));
-        map.push(("fnmsubd", 0x2252f34b));
-        map.push(("fnmaddd", 0x2252f34f));
-        map.push(("faddd", 0x0252f353));
-        map.push(("fsubd", 0x0a52f353));
-        map.push(("fmuld", 0x1252f353));
-        map.push(("fdivd", 0x1a52f353));
-        map.push(("fsqrtd", 0x5a02f353));
-        map.push(("fsgnjd", 0x22628353));
-        map.push(("fsgnjnd", 0x22629353));
-        map.push(("fsgnjxd", 0x2262a353));
-        map.push(("fmind", 0x2a628353));
-        map.push(("fmaxd", 0x2a629353));
-        map.push(("fcvtsd", 0x4012f353));
-        map.push(("fcvtds", 0x4202f353));
-        map.push(("feqd", 0xa2522353));
-        map.push(("fled", 0xa2520353));
-        map.push(("fltd", 0xa2521353));
-        map.push(("fclassd", 0xe2021353));
-        map.push(("fcvtwd", 0xc20272d3));
-        map.push(("fcvtwud", 0xc21272d3));
-        map.push(("fcvtdwu", 0xd21272d3));
-        map.push(("fcvtdw", 0xd20272d3));
-        map.push(("flw", 0x00002287));
-        map.push(("fsw", 0x00502027));
-        map.push(("fld", 0x00003287));
-        map.push(("fsd", 0x0052b227));
-
-        // NOTE: RV64C
-        map.push(("caddi4spn", 0x0020));
-        map.push(("cfld", 0x2008));
-        map.push(("clw", 0x4008));
-        map.push(("cld", 0x6008));
-        map.push(("cfsd", 0xa008));
-        map.push(("csw", 0xc008));
-        map.push(("csd", 0xe008));
-        map.push(("cnop", 0x0001));
-        map.push(("caddi", 0x0515));
-        map.push(("caddiw", 0x2515));
-        map.push(("cli", 0x4515));
-        map.push(("caddi16sp", 0x6141));
-        map.push(("clui", 0x6521));
-        map.push(("csrli", 0x8121));
-        map.push(("csrai", 0x8521));
-        map.push(("candi", 0x8921));
-        map.push(("csub", 0x8d01));
-        map.push(("cxor", 0x8d21));
-        map.push(("cor", 0x8d41));
-        map.push(("cand", 0x8d61));
-        map.push(("csubw", 0x9d01));
-        map.push(("caddw", 0x9d21));
-        map.push(("cbeqz", 0xc801));
-        map.push(("cbnez", 0xe801));
-        map.push(("cslli", 0x0442));
-        map.push(("cfldsp", 0x2442));
-        map.push(("clwsp", 0x4442));
-        map.push(("cldsp", 0x6442));
-        map.push(("cjr", 0x8402));
-        map.push(("cmv", 0x843a));
-        map.push(("cebreak", 0x9002));
-        map.push(("cjalr", 0x9402));
-        map.push(("cadd", 0x942a));
-        map.push(("cfsdsp", 0xa42a));
-        map.push(("cswsp", 0xc42a));
-        map.push(("csdsp", 0xe416));
-
-        for (name, ins) in map {
-            if (is_rv64i_add_instruction(ins) && name != "add")
-                || (!is_rv64i_add_instruction(ins) && name == "add")
-            {
-                return Err(format!("{name}: {ins:#08x}, is not an add instruction!").into());
-            }
-
-            if is_rv64i_addi_instruction(ins) && name != "addi"
-                || (!is_rv64i_addi_instruction(ins) && name == "addi")
-            {
-                return Err(format!("{name}: {ins:#08x}, is not an addi instruction!").into());
-            }
-
-            if is_rv64i_auipc_instruction(ins) && name != "auipc"
-                || (!is_rv64i_auipc_instruction(ins) && name == "auipc")
-            {
-                return Err(format!("{name}: {ins:#08x}, is not an auipc instruction!").into());
-            }
-
-            if is_rv64i_slti_instruction(ins) && name != "slti"
-                || (!is_rv64i_slti_instruction(ins) && name == "slti")
-            {
-                return Err(format!("{name}: {ins:#08x}, is not an sltui instruction!").into());
-            }
-
-            if is_rv64i_sltiu_instruction(ins) && name != "sltiu"
-                || (!is_rv64i_sltiu_instruction(ins) && name == "sltiu")
-            {
-                return Err(format!("{name}: {ins:#08x}, is not an sltui instruction!").into());
-            }
-
-            if is_rv64i_xori_instruction(ins) && name != "xori"
-                || (!is_rv64i_xori_instruction(ins) && name == "xori")
-            {
-                return Err(format!("{name}: {ins:#08x}, is not an xori instruction!").into());
-            }
-
-            if is_rv64i_ori_instruction(ins) && name != "ori"
-                || (!is_rv64i_ori_instruction(ins) && name == "ori")
-            {
-                return Err(format!("{name}: {ins:#08x}, is not an ori instruction!").into());
-            }
-
-            if is_rv64i_andi_instruction(ins) && name != "andi"
-                || (!is_rv64i_andi_instruction(ins) && name == "andi")
-            {
-                return Err(format!("{name}: {ins:#08x}, is not an andi instruction!").into());
-            }
-
-            if is_rv64i_slli_instruction(ins) && name != "slli"
-                || (!is_rv64i_slli_instruction(ins) && name == "slli")
-            {
-                return Err(format!("{name}: {ins:#08x}, is not an slli instruction!").into());
-            }
-
-            if is_rv64i_srli_instruction(ins) && name != "srli"
-                || (!is_rv64i_srli_instruction(ins) && name == "srli")
-            {
-                return Err(format!("{name}: {ins:#08x}, is not an srli instruction!").into());
-            }
-
-            if is_rv64i_srai_instruction(ins) && name != "srai"
-                || (!is_rv64i_srai_instruction(ins) && name == "srai")
-            {
-                return Err(format!("{name}: {ins:#08x}, is not an srai instruction!").into());
-            }
-
-            if is_rv64i_sub_instruction(ins) && name != "sub"
-                || (!is_rv64i_sub_instruction(ins) && name == "sub")
-            {
-                return Err(format!("{name}: {ins:#08x}, is not an sub instruction!").into());
-            }
-
-            if is_rv64i_sll_instruction(ins) && name != "sll"
-                || (!is_rv64i_sll_instruction(ins) && name == "sll")
-            {
-                return Err(format!("{name}: {ins:#08x}, is not an sll instruction!").into());
-            }
-
-            if is_rv64i_slt_instruction(ins) && name != "slt"
-                || (!is_rv64i_slt_instruction(ins) && name == "slt")
-            {
-                return Err(format!("{name}: {ins:#08x}, is not an slt instruction!").into());
-            }
-
-            if is_rv64i_sltu_instruction(ins) && name != "sltu"
-                || (!is_rv64i_sltu_instruction(ins) && name == "sltu")
-            {
-                return Err(format!("{name}: {ins:#08x}, is not an sltu instruction!").into());
-            }
-
-            if is_rv64i_xor_instruction(ins) && name != "xor"
-                || (!is_rv64i_xor_instruction(ins) && name == "xor")
-            {
-                return Err(format!("{name}: {ins:#08x}, is not an xor instruction!").into());
-            }
-
-            if is_rv64i_and_instruction(ins) && name != "and"
-                || (!is_rv64i_and_instruction(ins) && name == "and")
-            {
-                return Err(format!("{name}: {ins:#08x}, is not an and instruction!").into());
-            }
-
-            if is_rv64i_ecall_instruction(ins) && name != "ecall"
-                || (!is_rv64i_ecall_instruction(ins) && name == "ecall")
-            {
-                return Err(format!("{name}: {ins:#08x}, is not an ecall instruction!").into());
-            }
-
-            if is_rv64i_ebreak_instruction(ins) && name != "ebreak"
-                || (!is_rv64i_ebreak_instruction(ins) && name == "ebreak")
-            {
-                return Err(format!("{name}: {ins:#08x}, is not an ebreak instruction!").into());
-            }
-
-            if is_rv64i_lb_instruction(ins) && name != "lb"
-                || (!is_rv64i_lb_instruction(ins) && name == "lb")
-            {
-                return Err(format!("{name}: {ins:#08x}, is not an lb instruction!").into());
-            }
-
-            if is_rv64i_lh_instruction(ins) && name != "lh"
-                || (!is_rv64i_lh_instruction(ins) && name == "lh")
-            {
-                return Err(format!("{name}: {ins:#08x}, is not an lh instruction!").into());
-            }
-
-            if is_rv64i_lw_instruction(ins) && name != "lw"
-                || (!is_rv64i_lw_instruction(ins) && name == "lw")
-            {
-                return Err(format!("{name}: {ins:#08x}, is not an lw instruction!").into());
-            }
-
-            if is_rv64i_lbu_instruction(ins) && name != "lbu"
-                || (!is_rv64i_lbu_instruction(ins) && name == "lbu")
-            {
-                return Err(format!("{name}: {ins:#08x}, is not an lbu instruction!").into());
-            }
-
-            if is_rv64i_lhu_instruction(ins) && name != "lhu"
-                || (!is_rv64i_lhu_instruction(ins) && name == "lhu")
-            {
-                return Err(format!("{name}: {ins:#08x}, is not an ebreak instruction!").into());
-            }
-
-            if is_rv64i_sb_instruction(ins) && name != "sb"
-                || (!is_rv64i_sb_instruction(ins) && name == "sb")
-            {
-                return Err(format!("{name}: {ins:#08x}, is not an sb instruction!").into());
-            }
-
-            if is_rv64i_sh_instruction(ins) && name != "sh"
-                || (!is_rv64i_sh_instruction(ins) && name == "sh")
-            {
-                return Err(format!("{name}: {ins:#08x}, is not an sh instruction!").into());
-            }
-
-            if is_rv64i_sw_instruction(ins) && name != "sw"
-                || (!is_rv64i_sw_instruction(ins) && name == "sw")
-            {
-                return Err(format!("{name}: {ins:#08x}, is not an sw instruction!").into());
-            }
-
-            if is_rv64i_jal_instruction(ins) && name != "jal"
-                || (!is_rv64i_jal_instruction(ins) && name == "jal")
-            {
-                return Err(format!("{name}: {ins:#08x}, is not an jal instruction!").into());
-            }
-
-            if is_rv64i_jalr_instruction(ins) && name != "jalr"
-                || (!is_rv64i_jalr_instruction(ins) && name == "jalr")
-            {
-                return Err(format!("{name}: {ins:#08x}, is not an jalr instruction!").into());
-            }
-
-            if is_rv64i_beq_instruction(ins) && name != "beq"
-                || (!is_rv64i_beq_instruction(ins) && name == "beq")
-            {
-                return Err(format!("{name}: {ins:#08x}, is not an beq instruction!").into());
-            }
-
-            if is_rv64i_bne_instruction(ins) && name != "bne"
-                || (!is_rv64i_bne_instruction(ins) && name == "bne")
-            {
-                return Err(format!("{name}: {ins:#08x}, is not an bne instruction!").into());
-            }
-
-            if is_rv64i_blt_instruction(ins) && name != "blt"
-                || (!is_rv64i_blt_instruction(ins) && name == "blt")
-            {
-                return Err(format!("{name}: {ins:#08x}, is not an blt instruction!").into());
-            }
-
-            if is_rv64i_bge_instruction(ins) && name != "bge"
-                || (!is_rv64i_bge_instruction(ins) && name == "bge")
-            {
-                return Err(format!("{name}: {ins:#08x}, is not an bge instruction!").into());
-            }
-
-            if is_rv64i_bltu_instruction(ins) && name != "bltu"
-                || (!is_rv64i_bltu_instruction(ins) && name == "bltu")
-            {
-                return Err(format!("{name}: {ins:#08x}, is not an bltu instruction!").into());
-            }
-
-            if is_rv64i_bgeu_instruction(ins) && name != "bgeu"
-                || (!is_rv64i_bgeu_instruction(ins) && name == "bgeu")
-            {
-                return Err(format!("{name}: {ins:#08x}, is not an bgeu instruction!").into());
-            }
-
-            if is_rv64i_ld_instruction(ins) && name != "ld"
-                || (!is_rv64i_ld_instruction(ins) && name == "ld")
-            {
-                return Err(format!("{name}: {ins:#08x}, is not an ld instruction!").into());
-            }
-
-            if is_rv64i_sd_instruction(ins) && name != "sd"
-                || (!is_rv64i_sd_instruction(ins) && name == "sd")
-            {
-                return Err(format!("{name}: {ins:#08x}, is not an sd instruction!").into());
-            }
-
-            if is_rv64i_addiw_instruction(ins) && name != "addiw"
-                || (!is_rv64i_addiw_instruction(ins) && name == "addiw")
-            {
-                return Err(format!("{name}: {ins:#08x}, is not an addiw instruction!").into());
-            }
-
-            if is_rv64i_slliw_instruction(ins) && name != "slliw"
-                || (!is_rv64i_slliw_instruction(ins) && name == "slliw")
-            {
-                return Err(format!("{name}: {ins:#08x}, is not an slliw instruction!").into());
-            }
-
-            if is_rv64i_srliw_instruction(ins) && name != "srliw"
-                || (!is_rv64i_srliw_instruction(ins) && name == "srliw")
-            {
-                return Err(format!("{name}: {ins:#08x}, is not an srliw instruction!").into());
-            }
-
-            if is_rv64i_sraiw_instruction(ins) && name != "sraiw"
-                || (!is_rv64i_sraiw_instruction(ins) && name == "sraiw")
-            {
-                return Err(format!("{name}: {ins:#08x}, is not an sraiw instruction!").into());
-            }
-
-            if is_rv64i_addw_instruction(ins) && name != "addw"
-                || (!is_rv64i_addw_instruction(ins) && name == "addw")
-            {
-                return Err(format!("{name}: {ins:#08x}, is not an addw instruction!").into());
-            }
-
-            if is_rv64i_subw_instruction(ins) && name != "subw"
-                || (!is_rv64i_subw_instruction(ins) && name == "subw")
-            {
-                return Err(format!("{name}: {ins:#08x}, is not an subw instruction!").into());
-            }
-
-            if is_rv64i_sllw_instruction(ins) && name != "sllw"
-                || (!is_rv64i_sllw_instruction(ins) && name == "sllw")
-            {
-                return Err(format!("{name}: {ins:#08x}, is not an sllw instruction!").into());
-            }
-
-            if is_rv64i_srlw_instruction(ins) && name != "srlw"
-                || (!is_rv64i_srlw_instruction(ins) && name == "srlw")
-            {
-                return Err(format!("{name}: {ins:#08x}, is not an srlw instruction!").into());
-            }
-
-            if is_rv64i_sraw_instruction(ins) && name != "sraw"
-                || (!is_rv64i_sraw_instruction(ins) && name == "sraw")
-            {
-                return Err(format!("{name}: {ins:#08x}, is not an sraw instruction!").into());
-            }
-
-            if is_rv64i_lwu_instruction(ins) && name != "lwu"
-                || (!is_rv64i_lwu_instruction(ins) && name == "lwu")
-            {
-                return Err(format!("{name}: {ins:#08x}, is not an srlw instruction!").into());
-            }
-
-            if is_rv64m_mul_instruction(ins) && name != "mul"
-                || (!is_rv64m_mul_instruction(ins) && name == "mul")
-            {
-                return Err(format!("{name}: {ins:#08x}, is not an mul instruction!").into());
-            }
-
-            if is_rv64m_mulh_instruction(ins) && name != "mulh"
-                || (!is_rv64m_mulh_instruction(ins) && name == "mulh")
-            {
-                return Err(format!("{name}: {ins:#08x}, is not an mulh instruction!").into());
-            }
-
-            if is_rv64m_mulhu_instruction(ins) && name != "mulhu"
-                || (!is_rv64m_mulhu_instruction(ins) && name == "mulhu")
-            {
-                return Err(format!("{name}: {ins:#08x}, is not an mulhu instruction!").into());
-            }
-
-            if is_rv64m_mulhsu_instruction(ins) && name != "mulhsu"
-                || (!is_rv64m_mulhsu_instruction(ins) && name == "mulhsu")
-            {
-                return Err(format!("{name}: {ins:#08x}, is not an mulhsu instruction!").into());
-            }
-
-            if is_rv64m_div_instruction(ins) && name != "div"
-                || (!is_rv64m_div_instruction(ins) && name == "div")
-            {
-                return Err(format!("{name}: {ins:#08x}, is not an div instruction!").into());
-            }
-
-            if is_rv64m_divu_instruction(ins) && name != "divu"
-                || (!is_rv64m_divu_instruction(ins) && name == "divu")
-            {
-                return Err(format!("{name}: {ins:#08x}, is not an divu instruction!").into());
-            }
-
-            if is_rv64m_rem_instruction(ins) && name != "rem"
-                || (!is_rv64m_rem_instruction(ins) && name == "rem")
-            {
-                return Err(format!("{name}: {ins:#08x}, is not an rem instruction!").into());
-            }
-
-            if is_rv64m_remu_instruction(ins) && name != "remu"
-                || (!is_rv64m_remu_instruction(ins) && name == "remu")
-            {
-                return Err(format!("{name}: {ins:#08x}, is not an remu instruction!").into());
-            }
-
-            {
-                let value = is_rv64m_mulw_instruction(ins);
-                let ins_name = "mulw";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-
-            {
-                let value = is_rv64m_divw_instruction(ins);
-                let ins_name = "divw";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-
-            {
-                let value = is_rv64m_divuw_instruction(ins);
-                let ins_name = "divuw";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-
-            {
-                let value = is_rv64m_remw_instruction(ins);
-                let ins_name = "remw";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-
-            {
-                let value = is_rv64m_remuw_instruction(ins);
-                let ins_name = "remuw";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-
-            // RV64A
-
-            {
-                let value = is_rv64a_lrw_instruction(ins);
-                let ins_name = "lrw";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-
-            {
-                let value = is_rv64a_scw_instruction(ins);
-                let ins_name = "scw";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-
-            {
-                let value = is_rv64a_amoaddw_instruction(ins);
-                let ins_name = "amoaddw";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-
-            {
-                let value = is_rv64a_amoandw_instruction(ins);
-                let ins_name = "amoandw";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-
-            {
-                let value = is_rv64a_amoxorw_instruction(ins);
-                let ins_name = "amoxorw";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-
-            {
-                let value = is_rv64a_amoorw_instruction(ins);
-                let ins_name = "amoorw";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-
-            {
-                let value = is_rv64a_amoswapw_instruction(ins);
-                let ins_name = "amoswapw";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-
-            {
-                let value = is_rv64a_amominw_instruction(ins);
-                let ins_name = "amominw";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-
-            {
-                let value = is_rv64a_amominuw_instruction(ins);
-                let ins_name = "amominuw";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-            {
-                let value = is_rv64a_amomaxw_instruction(ins);
-                let ins_name = "amomaxw";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-            {
-                let value = is_rv64a_amomaxuw_instruction(ins);
-                let ins_name = "amomaxuw";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-            {
-                let value = is_rv64a_lrd_instruction(ins);
-                let ins_name = "lrd";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-            {
-                let value = is_rv64a_scd_instruction(ins);
-                let ins_name = "scd";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-            {
-                let value = is_rv64a_amoandd_instruction(ins);
-                let ins_name = "amoandd";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-            {
-                let value = is_rv64a_amoaddd_instruction(ins);
-                let ins_name = "amoaddd";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-            {
-                let value = is_rv64a_amoswapd_instruction(ins);
-                let ins_name = "amoswapd";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-            {
-                let value = is_rv64a_amoxord_instruction(ins);
-                let ins_name = "amoxord";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-            {
-                let value = is_rv64a_amoord_instruction(ins);
-                let ins_name = "amoord";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-            {
-                let value = is_rv64a_amomaxd_instruction(ins);
-                let ins_name = "amomaxd";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-            {
-                let value = is_rv64a_amomaxud_instruction(ins);
-                let ins_name = "amomaxud";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-            {
-                let value = is_rv64a_amomind_instruction(ins);
-                let ins_name = "amomind";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-            {
-                let value = is_rv64a_amominud_instruction(ins);
-                let ins_name = "amominud";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-
-            // RV64F
-
-            {
-                let value = is_rv64f_fmadds_instruction(ins);
-                let ins_name = "fmadds";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-
-            {
-                let value = is_rv64f_fmsubs_instruction(ins);
-                let ins_name = "fmsubs";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-
-            {
-                let value = is_rv64f_fnmadds_instruction(ins);
-                let ins_name = "fnmadds";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-            {
-                let value = is_rv64f_fnmsubs_instruction(ins);
-                let ins_name = "fnmsubs";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-
-            {
-                let value = is_rv64f_fadds_instruction(ins);
-                let ins_name = "fadds";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-
-            {
-                let value = is_rv64f_fsubs_instruction(ins);
-                let ins_name = "fsubs";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-            {
-                let value = is_rv64f_fmuls_instruction(ins);
-                let ins_name = "fmuls";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-            {
-                let value = is_rv64f_fdivs_instruction(ins);
-                let ins_name = "fdivs";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-            {
-                let value = is_rv64f_fsqrts_instruction(ins);
-                let ins_name = "fsqrts";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-            {
-                let value = is_rv64f_fsgnjs_instruction(ins);
-                let ins_name = "fsgnjs";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-            {
-                let value = is_rv64f_fsgnjns_instruction(ins);
-                let ins_name = "fsgnjns";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-            {
-                let value = is_rv64f_fsgnjxs_instruction(ins);
-                let ins_name = "fsgnjxs";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-            {
-                let value = is_rv64f_fmins_instruction(ins);
-                let ins_name = "fmins";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-            {
-                let value = is_rv64f_fmaxs_instruction(ins);
-                let ins_name = "fmaxs";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-            {
-                let value = is_rv64f_fcvtws_instruction(ins);
-                let ins_name = "fcvtws";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-            {
-                let value = is_rv64f_fcvtwus_instruction(ins);
-                let ins_name = "fcvtwus";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-            {
-                let value = is_rv64f_fmvxw_instruction(ins);
-                let ins_name = "fmvxw";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-            {
-                let value = is_rv64f_feqs_instruction(ins);
-                let ins_name = "feqs";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-            {
-                let value = is_rv64f_flts_instruction(ins);
-                let ins_name = "flts";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-            {
-                let value = is_rv64f_fles_instruction(ins);
-                let ins_name = "fles";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-            {
-                let value = is_rv64f_fclasss_instruction(ins);
-                let ins_name = "fclasss";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-            {
-                let value = is_rv64f_fcvtsw_instruction(ins);
-                let ins_name = "fcvtsw";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-            {
-                let value = is_rv64f_fcvtswu_instruction(ins);
-                let ins_name = "fcvtswu";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-            {
-                let value = is_rv64f_fmvwx_instruction(ins);
-                let ins_name = "fmvwx";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-
-            // NOTE: RV64D
-
-            {
-                let value = is_rv64f_fmaddd_instruction(ins);
-                let ins_name = "fmaddd";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-
-            {
-                let value = is_rv64f_fmsubd_instruction(ins);
-                let ins_name = "fmsubd";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-
-            {
-                let value = is_rv64f_fnmaddd_instruction(ins);
-                let ins_name = "fnmaddd";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-            {
-                let value = is_rv64f_fnmsubd_instruction(ins);
-                let ins_name = "fnmsubd";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-
-            {
-                let value = is_rv64f_faddd_instruction(ins);
-                let ins_name = "faddd";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-
-            {
-                let value = is_rv64f_fsubd_instruction(ins);
-                let ins_name = "fsubd";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-            {
-                let value = is_rv64f_fmuld_instruction(ins);
-                let ins_name = "fmuld";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-            {
-                let value = is_rv64f_fdivd_instruction(ins);
-                let ins_name = "fdivd";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-            {
-                let value = is_rv64f_fsqrtd_instruction(ins);
-                let ins_name = "fsqrtd";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-            {
-                let value = is_rv64f_fsgnjd_instruction(ins);
-                let ins_name = "fsgnjd";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-            {
-                let value = is_rv64f_fsgnjnd_instruction(ins);
-                let ins_name = "fsgnjnd";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-            {
-                let value = is_rv64f_fsgnjxd_instruction(ins);
-                let ins_name = "fsgnjxd";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-            {
-                let value = is_rv64f_fmind_instruction(ins);
-                let ins_name = "fmind";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-            {
-                let value = is_rv64f_fmaxd_instruction(ins);
-                let ins_name = "fmaxd";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-            {
-                let value = is_rv64f_fcvtwd_instruction(ins);
-                let ins_name = "fcvtwd";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-            {
-                let value = is_rv64f_fcvtwud_instruction(ins);
-                let ins_name = "fcvtwud";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-            {
-                let value = is_rv64f_feqd_instruction(ins);
-                let ins_name = "feqd";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-            {
-                let value = is_rv64f_fltd_instruction(ins);
-                let ins_name = "fltd";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-            {
-                let value = is_rv64f_fled_instruction(ins);
-                let ins_name = "fled";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-            {
-                let value = is_rv64f_fclassd_instruction(ins);
-                let ins_name = "fclassd";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-            {
-                let value = is_rv64f_fcvtsd_instruction(ins);
-                let ins_name = "fcvtsd";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-            {
-                let value = is_rv64f_fcvtds_instruction(ins);
-                let ins_name = "fcvtds";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-            {
-                let value = is_rv64f_fcvtdw_instruction(ins);
-                let ins_name = "fcvtdw";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-            {
-                let value = is_rv64f_fcvtwd_instruction(ins);
-                let ins_name = "fcvtwd";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-            {
-                let value = is_rv64f_flw_instruction(ins);
-                let ins_name = "flw";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-            {
-                let value = is_rv64f_fsw_instruction(ins);
-                let ins_name = "fsw";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-            {
-                let value = is_rv64f_fld_instruction(ins);
-                let ins_name = "fld";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-            {
-                let value = is_rv64f_fsd_instruction(ins);
-                let ins_name = "fsd";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-
-            let c_ins = ins as u16;
-
-            // NOTE: RV64C
-            {
-                let value = is_rv64c_addi4spn_instruction(c_ins);
-                let ins_name = "caddi4spn";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {c_ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-            {
-                let value = is_rv64c_fld_instruction(c_ins);
-                let ins_name = "cfld";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {c_ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-            {
-                let value = is_rv64c_lw_instruction(c_ins);
-                let ins_name = "clw";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {c_ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-            {
-                let value = is_rv64c_ld_instruction(c_ins);
-                let ins_name = "cld";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {c_ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-            {
-                let value = is_rv64c_fsd_instruction(c_ins);
-                let ins_name = "cfsd";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {c_ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-            {
-                let value = is_rv64c_sw_instruction(c_ins);
-                let ins_name = "csw";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {c_ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-            {
-                let value = is_rv64c_sd_instruction(c_ins);
-                let ins_name = "csd";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {c_ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-            {
-                let value = is_rv64c_nop_instruction(c_ins);
-                let ins_name = "cnop";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {c_ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-            if name != "cnop" {
-                {
-                    let value = is_rv64c_addi_instruction(c_ins);
-                    let ins_name = "caddi";
-
-                    if (value && name != ins_name) || (!value && name == ins_name) {
-                        return Err(format!(
-                            "{name}: {c_ins:#08x}, is not an {ins_name} instruction!"
-                        )
-                        .into());
-                    }
-                }
-            }
-            {
-                let value = is_rv64c_addiw_instruction(c_ins);
-                let ins_name = "caddiw";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {c_ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-            {
-                let value = is_rv64c_li_instruction(c_ins);
-                let ins_name = "cli";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {c_ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-            {
-                let value = is_rv64c_addi16sp_instruction(c_ins);
-                let ins_name = "caddi16sp";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {c_ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-
-            // These two ops overlap, caddi16sp will always match clui.
-            // Only way to differentiate is to match addi16sp first.
-            if name != "caddi16sp" {
-                {
-                    let value = is_rv64c_lui_instruction(c_ins);
-                    let ins_name = "clui";
-
-                    if (value && name != ins_name) || (!value && name == ins_name) {
-                        return Err(format!(
-                            "{name}: {c_ins:#08x}, is not an {ins_name} instruction!"
-                        )
-                        .into());
-                    }
-                }
-            }
-
-            {
-                let value = is_rv64c_srli_instruction(c_ins);
-                let ins_name = "csrli";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {c_ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-            {
-                let value = is_rv64c_srai_instruction(c_ins);
-                let ins_name = "csrai";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {c_ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-            {
-                let value = is_rv64c_andi_instruction(c_ins);
-                let ins_name = "candi";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {c_ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-            {
-                let value = is_rv64c_sub_instruction(c_ins);
-                let ins_name = "csub";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {c_ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-            {
-                let value = is_rv64c_xor_instruction(c_ins);
-                let ins_name = "cxor";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {c_ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-            {
-                let value = is_rv64c_or_instruction(c_ins);
-                let ins_name = "cor";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {c_ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-            {
-                let value = is_rv64c_subw_instruction(c_ins);
-                let ins_name = "csubw";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {c_ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-            {
-                let value = is_rv64c_addw_instruction(c_ins);
-                let ins_name = "caddw";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {c_ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-            {
-                let value = is_rv64c_beqz_instruction(c_ins);
-                let ins_name = "cbeqz";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {c_ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-            {
-                let value = is_rv64c_bnez_instruction(c_ins);
-                let ins_name = "cbnez";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {c_ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-            {
-                let value = is_rv64c_slli_instruction(c_ins);
-                let ins_name = "cslli";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {c_ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-            {
-                let value = is_rv64c_fldsp_instruction(c_ins);
-                let ins_name = "cfldsp";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {c_ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-            {
-                let value = is_rv64c_lwsp_instruction(c_ins);
-                let ins_name = "clwsp";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {c_ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-
-            {
-                let value = is_rv64c_ldsp_instruction(c_ins);
-                let ins_name = "cldsp";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {c_ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-            {
-                let value = is_rv64c_jr_instruction(c_ins);
-                let ins_name = "cjr";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {c_ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-
-            if name != "cjr" {
-                {
-                    let value = is_rv64c_mv_instruction(c_ins);
-                    let ins_name = "cmv";
-
-                    if (value && name != ins_name) || (!value && name == ins_name) {
-                        return Err(format!(
-                            "{name}: {c_ins:#08x}, is not an {ins_name} instruction!"
-                        )
-                        .into());
-                    }
-                }
-            }
-            {
-                let value = is_rv64c_ebreak_instruction(c_ins);
-                let ins_name = "cebreak";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {c_ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-
-            if name != "cebreak" {
-                {
-                    let value = is_rv64c_jalr_instruction(c_ins);
-                    let ins_name = "cjalr";
-
-                    if (value && name != ins_name) || (!value && name == ins_name) {
-                        return Err(format!(
-                            "{name}: {c_ins:#08x}, is not an {ins_name} instruction!"
-                        )
-                        .into());
-                    }
-                }
-            }
-            if name != "cjalr" && name != "cebreak" {
-                {
-                    let value = is_rv64c_add_instruction(c_ins);
-                    let ins_name = "cadd";
-
-                    if (value && name != ins_name) || (!value && name == ins_name) {
-                        return Err(format!(
-                            "{name}: {c_ins:#08x}, is not an {ins_name} instruction!"
-                        )
-                        .into());
-                    }
-                }
-            }
-            {
-                let value = is_rv64c_fsdsp_instruction(c_ins);
-                let ins_name = "cfsdsp";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {c_ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-            {
-                let value = is_rv64c_swsp_instruction(c_ins);
-                let ins_name = "cswsp";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {c_ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
-            {
-                let value = is_rv64c_sdsp_instruction(c_ins);
-                let ins_name = "csdsp";
-
-                if (value && name != ins_name) || (!value && name == ins_name) {
-                    return Err(
-                        format!("{name}: {c_ins:#08x}, is not an {ins_name} instruction!").into(),
-                    );
-                }
-            }
+    #[test]
+    fn test_fence() {
+        // NOTE: fm, rs1 and rd are reserved, so fence.tso (fm 1000) and fences with them
+        // set act as a plain fence
+        let cases = [
+            (0x0ff0_000f, "fence iorw, iorw"),
+            (0x8330_000f, "fence rw, rw"),
+            (0x0100_000f, "fence w, 0"),
+            (0x0335_0f8f, "fence rw, rw"),
+        ];
+
+        let mut cpu = crate::cpu::RV64GC::new();
+        for (ins, text) in cases {
+            assert_eq!(decode(ins).to_string(), text, "{ins:#010x}");
+            assert!(matches!(
+                cpu.find_instruction(ins),
+                RV64GCInstruction::Fence(..)
+            ));
+            cpu.execute(ins).unwrap();
         }
-
-        Ok(())
+        assert!(matches!(
+            decode(0x8330_000f),
+            RV64GCInstruction::Fence(0b0011, 0b0011)
+        ));
     }
 }
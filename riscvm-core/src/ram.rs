@@ -1,12 +1,120 @@
+use std::cell::Cell;
+use std::collections::HashMap;
 use std::fmt::Display;
+use std::sync::Arc;
 
+use goblin::elf::program_header::{PF_R, PF_W, PF_X};
 use thiserror::Error;
-use tracing::trace;
 
-#[derive(Debug)]
+pub const PAGE_SIZE: u64 = 4096;
+const PAGE_SHIFT: u64 = 12;
+const PAGE_MASK: u64 = PAGE_SIZE - 1;
+
+/// Entries in the software TLB, which is direct mapped by page number
+const TLB_ENTRIES: usize = 64;
+
+pub(crate) type Page = [u8; PAGE_SIZE as usize];
+
+/// What every page without a frame reads as
+static ZERO_PAGE: Page = [0; PAGE_SIZE as usize];
+
+/// What an access needs the page to allow
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    Read,
+    Write,
+    Execute,
+}
+
+impl Access {
+    /// The ELF `PF_*` bit allowing this access
+    fn flag(self) -> u64 {
+        match self {
+            Access::Read => PF_R as u64,
+            Access::Write => PF_W as u64,
+            Access::Execute => PF_X as u64,
+        }
+    }
+}
+
+impl Display for Access {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Access::Read => write!(f, "read"),
+            Access::Write => write!(f, "write"),
+            Access::Execute => write!(f, "execute"),
+        }
+    }
+}
+
+#[derive(Clone)]
+struct Frame {
+    /// Shared with forks (and snapshots) until one of them writes to it
+    data: Arc<Page>,
+    /// `PF_*` flags of the regions on this page. Writes to executable pages have to
+    /// invalidate decoded code
+    flags: u64,
+}
+
+/// A copy of everything mapped in a [`Ram`], see [`crate::snapshot`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct RamImage {
+    /// `(start, size, flags)`
+    pub(crate) regions: Vec<(u64, u64, u64)>,
+    /// `(page number, flags, data)`
+    pub(crate) pages: Vec<(u64, u64, Arc<Page>)>,
+    pub(crate) lowest_unalloced_addr: u64,
+}
+
+/// A cached page table lookup
+#[derive(Debug, Clone, Copy)]
+struct TlbEntry {
+    page: u64,
+    frame: u32,
+}
+
+/// Page numbers are at most 52 bits, so this never matches
+const TLB_EMPTY: TlbEntry = TlbEntry {
+    page: u64::MAX,
+    frame: 0,
+};
+
+/// Guest memory, stored a page at a time.
+///
+/// [`MemoryRegion`]s describe what's mapped (and with which flags), the bytes themselves
+/// live in page sized frames. Accesses that stay on one page are a TLB (or page table)
+/// lookup and a copy, only accesses straddling two pages go a byte at a time.
+///
+/// Permissions are page granular too. Loads need the page to be readable, stores writable
+/// and fetches executable, or they fail with [`MemoryError::PermissionDenied`].
+///
+/// Pages nothing was ever written to don't get a frame, they read as zeros with the flags
+/// of their region until the first write. Reserving a large range only costs the region.
+///
+/// Clones share pages copy-on-write, a page is only copied once either side writes to it.
+#[derive(Clone)]
 pub struct Ram {
     regions: Vec<MemoryRegion>,
+    /// Guest page number to index in `frames`, for the pages that were written to
+    page_table: HashMap<u64, u32>,
+    frames: Vec<Option<Frame>>,
+    /// Indices in `frames` that were unmapped, reused before growing it
+    free_frames: Vec<u32>,
+    tlb: [Cell<TlbEntry>; TLB_ENTRIES],
     pub lowest_unalloced_addr: u64,
+    /// `(start, len)` ranges that may hold stale decoded instructions, written executable
+    /// memory and anything unmapped. The cpu drains these into its decode cache
+    pub(crate) code_invalidations: Vec<(u64, u64)>,
+}
+
+impl std::fmt::Debug for Ram {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Ram")
+            .field("regions", &self.regions)
+            .field("pages", &self.page_table.len())
+            .field("lowest_unalloced_addr", &self.lowest_unalloced_addr)
+            .finish_non_exhaustive()
+    }
 }
 
 impl Default for Ram {
@@ -19,16 +127,70 @@ impl Ram {
     pub fn new() -> Ram {
         Ram {
             regions: Vec::new(),
+            page_table: HashMap::new(),
+            frames: Vec::new(),
+            free_frames: Vec::new(),
+            tlb: std::array::from_fn(|_| Cell::new(TLB_EMPTY)),
             lowest_unalloced_addr: 0,
+            code_invalidations: Vec::new(),
         }
     }
 
-    pub fn add_region(&mut self, region: MemoryRegion) -> Result<(), MemoryError> {
+    pub(crate) fn image(&self) -> RamImage {
+        let mut pages: Vec<_> = self
+            .page_table
+            .iter()
+            .map(|(&page, &frame)| {
+                let frame = self.frames[frame as usize].as_ref().unwrap();
+                (page, frame.flags, frame.data.clone())
+            })
+            .collect();
+        pages.sort_by_key(|(page, ..)| *page);
+
+        RamImage {
+            regions: self
+                .regions
+                .iter()
+                .map(|r| (r.start, r.size, r.flags))
+                .collect(),
+            pages,
+            lowest_unalloced_addr: self.lowest_unalloced_addr,
+        }
+    }
+
+    /// Rebuilds the ram [`Ram::image`] copied
+    pub(crate) fn from_image(image: &RamImage) -> Ram {
+        let mut ram = Ram::new();
+        ram.regions = image
+            .regions
+            .iter()
+            .map(|&(start, size, flags)| {
+                MemoryRegion::new_with_flags(start, size, Vec::new(), flags)
+            })
+            .collect();
+
+        for (page, flags, data) in &image.pages {
+            ram.frames.push(Some(Frame {
+                data: data.clone(),
+                flags: *flags,
+            }));
+            ram.page_table.insert(*page, (ram.frames.len() - 1) as u32);
+        }
+
+        ram.lowest_unalloced_addr = image.lowest_unalloced_addr;
+        ram
+    }
+
+    /// Maps `region`, copying its data into pages
+    pub fn add_region(&mut self, mut region: MemoryRegion) -> Result<(), MemoryError> {
         // Check for overlaps
         if let Some(overlap) = self.find_overlap(&region) {
             return Err(MemoryError::RegionOverlap(overlap.start));
         }
 
+        let data = std::mem::take(&mut region.data);
+        self.map(region.start, region.size, &data, region.flags);
+
         // Find the insertion index
         let index = self
             .regions
@@ -45,93 +207,392 @@ impl Ram {
         Ok(())
     }
 
+    /// Grows the region `addr` is in by `addition` zeroed bytes, if nothing is mapped there
     pub fn extend_region(&mut self, addr: u64, addition: u64) -> Result<(), MemoryError> {
-        let region = self
-            .find_region_mut(addr)
-            .ok_or(MemoryError::InvalidAddress(addr));
+        let index = self
+            .find_region_index(addr)
+            .ok_or(MemoryError::InvalidAddress(addr))?;
 
-        let Ok(region) = region else {
-            return region.map(|_| ());
-        };
+        let region = &self.regions[index];
+        let end = region.start + region.size;
+        let flags = region.flags;
 
-        region.extend(addition);
+        let new_end = end
+            .checked_add(addition)
+            .ok_or(MemoryError::InvalidAddress(end))?;
+        if !self.is_range_free(end, addition) {
+            return Err(MemoryError::RegionOverlap(end));
+        }
+
+        self.map(end, addition, &[], flags);
+        self.regions[index].size += addition;
+
+        if new_end > self.lowest_unalloced_addr {
+            self.lowest_unalloced_addr = new_end;
+        }
 
         Ok(())
     }
 
-    pub fn find_end_of_text_region(&self) -> u64 {
-        let mut iter = self.regions.iter();
+    /// Whether nothing is mapped in `[start, start + len)`
+    pub fn is_range_free(&self, start: u64, len: u64) -> bool {
+        let end = start.saturating_add(len);
+        !self
+            .regions
+            .iter()
+            .any(|r| r.start < end && start < r.start + r.size)
+    }
 
-        // There has to be at least 1 region
-        let mut biggest_reg = iter.next().unwrap();
-        for reg in iter {
-            if !reg.is_execute() {
-                continue;
+    /// The flags of the region `addr` is in
+    pub fn region_flags(&self, addr: u64) -> Option<u64> {
+        self.find_region_index(addr).map(|i| self.regions[i].flags)
+    }
+
+    /// Whether every page in `[start, start + len)` has a region on it
+    pub fn is_range_mapped(&self, start: u64, len: u64) -> bool {
+        start
+            .checked_add(len)
+            .is_some_and(|end| self.first_unmapped(start, end).is_none())
+    }
+
+    /// The first address in `[start, end)` on a page no region is on, walking the regions
+    /// rather than the pages
+    fn first_unmapped(&self, start: u64, end: u64) -> Option<u64> {
+        let mut addr = start;
+        let first = self
+            .regions
+            .partition_point(|r| (r.start + r.size).next_multiple_of(PAGE_SIZE) <= start);
+
+        for region in &self.regions[first..] {
+            if addr >= end {
+                break;
             }
-            if reg.is_execute() && !biggest_reg.is_execute() {
-                biggest_reg = reg;
+            if region.start & !PAGE_MASK > addr {
+                return Some(addr);
+            }
+            addr = addr.max((region.start + region.size).next_multiple_of(PAGE_SIZE));
+        }
+
+        (addr < end).then_some(addr)
+    }
+
+    /// Unmaps `[start, start + len)`, splitting any region that only partially overlaps it
+    pub fn remove_range(&mut self, start: u64, len: u64) {
+        let end = start.saturating_add(len);
+        self.code_invalidations.push((start, len));
+
+        let mut kept = Vec::with_capacity(self.regions.len());
+        for mut region in std::mem::take(&mut self.regions) {
+            let region_end = region.start + region.size;
+            if region_end <= start || region.start >= end {
+                kept.push(region);
                 continue;
             }
 
-            if reg.start > biggest_reg.start {
-                biggest_reg = reg;
+            if region_end > end {
+                kept.push(MemoryRegion::new_with_flags(
+                    end,
+                    region_end - end,
+                    Vec::new(),
+                    region.flags,
+                ));
+            }
+
+            if region.start < start {
+                region.size = start - region.start;
+                kept.push(region);
             }
         }
 
-        biggest_reg.start + biggest_reg.size
+        kept.sort_by_key(|r| r.start);
+        self.regions = kept;
+        self.unmap_unused(start, end);
+        self.lowest_unalloced_addr = self
+            .regions
+            .iter()
+            .map(|r| r.start + r.size)
+            .max()
+            .unwrap_or(0);
+    }
+
+    pub fn remove_region(&mut self, addr: u64) -> Result<(), MemoryError> {
+        let index = self
+            .find_region_index(addr)
+            .ok_or(MemoryError::InvalidAddress(addr))?;
+
+        let removed = self.regions.remove(index);
+        let end = removed.start + removed.size;
+        self.unmap_unused(removed.start, end);
+
+        if end == self.lowest_unalloced_addr {
+            self.lowest_unalloced_addr = self.regions.last().map(|i| i.start + i.size).unwrap_or(0);
+        }
+
+        self.code_invalidations.push((removed.start, removed.size));
+
+        Ok(())
     }
 
-    pub fn extend_text_region_to(&mut self, addr: u64) -> Result<(), MemoryError> {
-        let mut iter = self.regions.iter_mut();
+    /// The highest page aligned address a `len` byte mapping fits at without overlapping
+    /// anything, ending at or below `top` and starting at or above `bottom`
+    pub fn find_free_range(&self, len: u64, bottom: u64, top: u64) -> Option<u64> {
+        let len = len.next_multiple_of(PAGE_SIZE);
+        let mut end = top & !PAGE_MASK;
 
-        // There has to be at least 1 region
-        let mut biggest_reg = iter.next().unwrap();
-        for reg in iter {
-            if !reg.is_execute() {
+        // NOTE: Regions don't overlap, so sorting them by start sorts them by end too
+        for region in self.regions.iter().rev() {
+            let region_start = region.start & !PAGE_MASK;
+            let region_end = (region.start + region.size).next_multiple_of(PAGE_SIZE);
+            if region_start >= end {
                 continue;
             }
-            if reg.is_execute() && !biggest_reg.is_execute() {
-                biggest_reg = reg;
+
+            if region_end <= end && end - region_end >= len {
+                break;
+            }
+            end = region_start;
+        }
+
+        end.checked_sub(len).filter(|&start| start >= bottom)
+    }
+
+    /// Moves the page aligned `[old, old + old_len)` to `[new, new + new_len)`, truncating
+    /// it or zero filling what's past its old end. The old range has to be part of one
+    /// region, the new one unmapped
+    pub fn remap(
+        &mut self,
+        old: u64,
+        old_len: u64,
+        new: u64,
+        new_len: u64,
+    ) -> Result<(), MemoryError> {
+        let index = self
+            .find_region_index(old)
+            .ok_or(MemoryError::InvalidAddress(old))?;
+        let region = &self.regions[index];
+        if old + old_len > region.start + region.size {
+            return Err(MemoryError::InvalidAddress(region.start + region.size));
+        }
+
+        self.add_region(MemoryRegion::new_with_flags(
+            new,
+            new_len,
+            Vec::new(),
+            region.flags,
+        ))?;
+
+        // NOTE: Pages without a frame are zeros, which the new ones are already
+        for page in self.backed_pages(old, old + old_len.min(new_len)) {
+            let from = self.page_table[&page];
+            let data = self.frames[from as usize].as_ref().unwrap().data.clone();
+
+            let to = self.materialize(page - (old >> PAGE_SHIFT) + (new >> PAGE_SHIFT));
+            self.frames[to].as_mut().unwrap().data = data;
+        }
+
+        self.remove_range(old, old_len);
+
+        Ok(())
+    }
+
+    /// Changes the flags of the page aligned range `[start, start + len)`, splitting any
+    /// region that only partially overlaps it. Fails without changing anything if part of
+    /// the range isn't mapped
+    pub fn protect(&mut self, start: u64, len: u64, flags: u64) -> Result<(), MemoryError> {
+        if start & PAGE_MASK != 0 {
+            return Err(MemoryError::InvalidAddress(start));
+        }
+
+        let end = start
+            .checked_add(len.next_multiple_of(PAGE_SIZE))
+            .ok_or(MemoryError::InvalidAddress(start))?;
+        if let Some(addr) = self.first_unmapped(start, end) {
+            return Err(MemoryError::InvalidAddress(addr));
+        }
+
+        let mut regions = Vec::with_capacity(self.regions.len() + 2);
+        for region in std::mem::take(&mut self.regions) {
+            let region_end = region.start + region.size;
+            if region_end <= start || region.start >= end {
+                regions.push(region);
                 continue;
             }
 
-            if reg.start > biggest_reg.start {
-                biggest_reg = reg;
+            let middle_start = region.start.max(start);
+            let middle_end = region_end.min(end);
+            if region.start < start {
+                regions.push(MemoryRegion::new_with_flags(
+                    region.start,
+                    start - region.start,
+                    Vec::new(),
+                    region.flags,
+                ));
+            }
+            regions.push(MemoryRegion::new_with_flags(
+                middle_start,
+                middle_end - middle_start,
+                Vec::new(),
+                flags,
+            ));
+            if region_end > end {
+                regions.push(MemoryRegion::new_with_flags(
+                    end,
+                    region_end - end,
+                    Vec::new(),
+                    region.flags,
+                ));
             }
         }
+        self.regions = regions;
 
-        let Some(offset) = addr.checked_sub(biggest_reg.start + biggest_reg.size) else {
-            return Err(MemoryError::InvalidAddress(u64::MAX));
-        };
+        // NOTE: The range is whole pages, so every region on them now has `flags`. Pages
+        // without a frame take theirs from the regions
+        for page in self.backed_pages(start, end) {
+            let frame = self.page_table[&page];
+            self.frames[frame as usize].as_mut().unwrap().flags = flags;
+        }
 
-        biggest_reg.extend(offset);
+        // Decoded instructions were fetched with the old permissions
+        self.code_invalidations.push((start, end - start));
 
         Ok(())
     }
 
-    pub fn remove_region(&mut self, addr: u64) -> Result<(), MemoryError> {
-        let region = self
-            .find_region(addr)
-            .ok_or(MemoryError::InvalidAddress(addr));
+    /// Fills `[start, start + len)` with `data` (zeros past its end). Pages past the end of
+    /// `data` that don't have a frame already read as zeros, so they're left without one
+    fn map(&mut self, start: u64, len: u64, data: &[u8], flags: u64) {
+        let end = start + len;
+        let data_end = start + (data.len() as u64).min(len);
 
-        let Ok(region) = region else {
-            return region.map(|_| ());
+        let mut pages: Vec<u64> = if start < data_end {
+            ((start >> PAGE_SHIFT)..=((data_end - 1) >> PAGE_SHIFT)).collect()
+        } else {
+            Vec::new()
         };
+        let last = pages.last().copied();
+        pages.extend(
+            self.backed_pages(data_end, end)
+                .into_iter()
+                .filter(|&page| Some(page) > last),
+        );
 
-        let index = self
-            .regions
-            .binary_search_by_key(&region.start, |r| r.start)
-            .unwrap();
+        for page in pages {
+            let page_start = page << PAGE_SHIFT;
+            let low = start.max(page_start);
+            let high = end.min(page_start + PAGE_SIZE);
+            let offset = (low & PAGE_MASK) as usize;
+            let chunk = (high - low) as usize;
 
-        if region.start + region.size == self.lowest_unalloced_addr {
-            self.lowest_unalloced_addr = self.regions.last().map(|i| i.start + i.size).unwrap_or(0);
+            let from = ((low - start) as usize).min(data.len());
+            let to = ((high - start) as usize).min(data.len());
+
+            let frame = self.materialize(page);
+            let frame = self.frames[frame].as_mut().unwrap();
+            frame.flags |= flags;
+
+            let bytes = &mut Arc::make_mut(&mut frame.data)[offset..offset + chunk];
+            bytes[..to - from].copy_from_slice(&data[from..to]);
+            bytes[to - from..].fill(0);
         }
+    }
 
-        self.regions.remove(index);
+    /// Copies `data` to `address` without checking permissions, like the kernel filling in
+    /// a file mapping. Fails without copying anything if part of the range isn't mapped
+    pub(crate) fn fill(&mut self, address: u64, data: &[u8]) -> Result<(), MemoryError> {
+        let len = data.len() as u64;
+        if let Some(addr) = address
+            .checked_add(len)
+            .and_then(|end| self.first_unmapped(address, end))
+        {
+            return Err(MemoryError::InvalidAddress(addr));
+        }
 
+        self.map(address, len, data, 0);
         Ok(())
     }
 
+    /// Backs `page` with a frame (if it isn't yet) carrying the flags of the regions on it,
+    /// returning its index in `frames`
+    fn materialize(&mut self, page: u64) -> usize {
+        if let Some(&frame) = self.page_table.get(&page) {
+            return frame as usize;
+        }
+
+        let start = page << PAGE_SHIFT;
+        let first = self.regions.partition_point(|r| r.start + r.size <= start);
+        let flags = self.regions[first..]
+            .iter()
+            .take_while(|r| r.start < start + PAGE_SIZE)
+            .fold(0, |flags, r| flags | r.flags);
+
+        let frame = self.allocate_frame(flags);
+        self.page_table.insert(page, frame);
+        frame as usize
+    }
+
+    /// Page numbers in `[start, end)` backed by a frame, walking whichever of the range
+    /// and the page table is smaller
+    pub(crate) fn backed_pages(&self, start: u64, end: u64) -> Vec<u64> {
+        if start >= end {
+            return Vec::new();
+        }
+
+        let pages = (start >> PAGE_SHIFT)..=((end - 1) >> PAGE_SHIFT);
+        if pages.end() - pages.start() < self.page_table.len() as u64 {
+            return pages
+                .filter(|page| self.page_table.contains_key(page))
+                .collect();
+        }
+
+        let mut backed: Vec<u64> = self
+            .page_table
+            .keys()
+            .copied()
+            .filter(|page| pages.contains(page))
+            .collect();
+        backed.sort_unstable();
+        backed
+    }
+
+    fn allocate_frame(&mut self, flags: u64) -> u32 {
+        let frame = Frame {
+            data: Arc::new([0; PAGE_SIZE as usize]),
+            flags,
+        };
+
+        match self.free_frames.pop() {
+            Some(index) => {
+                self.frames[index as usize] = Some(frame);
+                index
+            }
+            None => {
+                self.frames.push(Some(frame));
+                (self.frames.len() - 1) as u32
+            }
+        }
+    }
+
+    /// Frees the pages in `[start, end)` no region is on anymore
+    fn unmap_unused(&mut self, start: u64, end: u64) {
+        if start >= end {
+            return;
+        }
+
+        for page in self.backed_pages(start, end) {
+            if !self.is_range_free(page << PAGE_SHIFT, PAGE_SIZE) {
+                continue;
+            }
+
+            let frame = self.page_table.remove(&page).unwrap();
+            self.frames[frame as usize] = None;
+            self.free_frames.push(frame);
+        }
+
+        for entry in &self.tlb {
+            entry.set(TLB_EMPTY);
+        }
+    }
+
     fn find_overlap(&self, new_region: &MemoryRegion) -> Option<&MemoryRegion> {
         // Check for overlap with existing regions
         self.regions
@@ -139,7 +600,7 @@ impl Ram {
             .find(|&region| Ram::regions_overlap(region, new_region))
     }
 
-    fn find_region(&self, address: u64) -> Option<&MemoryRegion> {
+    fn find_region_index(&self, address: u64) -> Option<usize> {
         let mut low = 0;
         let mut high = self.regions.len();
 
@@ -151,98 +612,226 @@ impl Ram {
             } else if address >= region.start + region.size {
                 low = mid + 1;
             } else {
-                return Some(region);
+                return Some(mid);
             }
         }
         None
     }
 
-    fn find_region_mut(&mut self, address: u64) -> Option<&mut MemoryRegion> {
-        let mut low = 0;
-        let mut high = self.regions.len();
+    /// Index in `frames` of the page `address` is on, through the TLB
+    #[inline]
+    fn frame_index(&self, address: u64) -> Option<usize> {
+        let page = address >> PAGE_SHIFT;
+        let entry = &self.tlb[page as usize % TLB_ENTRIES];
 
-        while low < high {
-            let mid = (low + high) / 2;
-            let region_start = self.regions[mid].start;
-            let region_end = region_start + self.regions[mid].size;
-            if address < region_start {
-                high = mid;
-            } else if address >= region_end {
-                low = mid + 1;
-            } else {
-                return Some(&mut self.regions[mid]);
+        let cached = entry.get();
+        if cached.page == page {
+            return Some(cached.frame as usize);
+        }
+
+        let frame = *self.page_table.get(&page)?;
+        entry.set(TlbEntry { page, frame });
+        Some(frame as usize)
+    }
+
+    /// The bytes of the page `address` is on, if it allows `access`. Pages without a frame
+    /// are the zero page with the flags of the region `address` is in
+    #[inline]
+    fn page(&self, address: u64, access: Access) -> Result<&Page, MemoryError> {
+        let (data, flags) = match self.frame_index(address) {
+            Some(index) => {
+                let frame = self.frames[index].as_ref().unwrap();
+                (&*frame.data, frame.flags)
+            }
+            None => {
+                let index = self
+                    .find_region_index(address)
+                    .ok_or(MemoryError::InvalidAddress(address))?;
+                (&ZERO_PAGE, self.regions[index].flags)
             }
+        };
+
+        if flags & access.flag() == 0 {
+            return Err(MemoryError::PermissionDenied(address, access));
         }
-        None
+
+        Ok(data)
+    }
+
+    /// The frame of the page `address` is on, giving it one on the first write
+    #[inline]
+    fn frame_mut(&mut self, address: u64) -> Result<&mut Frame, MemoryError> {
+        self.page(address, Access::Write)?;
+
+        let index = match self.frame_index(address) {
+            Some(index) => index,
+            None => self.materialize(address >> PAGE_SHIFT),
+        };
+        Ok(self.frames[index].as_mut().unwrap())
+    }
+
+    /// Reads `N` bytes, with a single lookup unless they straddle two pages
+    #[inline]
+    fn read<const N: usize>(&self, address: u64, access: Access) -> Result<[u8; N], MemoryError> {
+        let offset = (address & PAGE_MASK) as usize;
+        if offset + N <= PAGE_SIZE as usize {
+            let page = self.page(address, access)?;
+            return Ok(page[offset..offset + N].try_into().unwrap());
+        }
+
+        let mut bytes = [0; N];
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            let address = address.wrapping_add(i as u64);
+            *byte = self.page(address, access)?[(address & PAGE_MASK) as usize];
+        }
+
+        Ok(bytes)
+    }
+
+    /// Writes `bytes`, with a single lookup unless they straddle two pages. Nothing is
+    /// written if any of them isn't mapped writable
+    #[inline]
+    fn write<const N: usize>(&mut self, address: u64, bytes: [u8; N]) -> Result<(), MemoryError> {
+        let offset = (address & PAGE_MASK) as usize;
+        if offset + N <= PAGE_SIZE as usize {
+            let frame = self.frame_mut(address)?;
+            Arc::make_mut(&mut frame.data)[offset..offset + N].copy_from_slice(&bytes);
+
+            if frame.flags & PF_X as u64 != 0 {
+                self.note_code_write(address);
+            }
+            return Ok(());
+        }
+
+        self.page(address.wrapping_add(N as u64 - 1), Access::Write)?;
+        for (i, byte) in bytes.into_iter().enumerate() {
+            self.write_byte(address.wrapping_add(i as u64), byte)?;
+        }
+
+        Ok(())
     }
 
     pub fn read_byte(&self, address: u64) -> Result<u8, MemoryError> {
-        let region = self
-            .find_region(address)
-            .ok_or(MemoryError::InvalidAddress(address))?;
-        let offset = (address - region.start) as usize;
-        Ok(region.data[offset])
+        self.read(address, Access::Read).map(|[byte]| byte)
     }
 
     pub fn write_byte(&mut self, address: u64, value: u8) -> Result<(), MemoryError> {
-        let region = self
-            .find_region_mut(address)
+        self.write(address, [value])
+    }
+
+    /// Fails with the first address in `[address, address + len)` that doesn't allow
+    /// `access`, checking a page at a time
+    pub fn check_access(&self, address: u64, len: u64, access: Access) -> Result<(), MemoryError> {
+        if len == 0 {
+            return Ok(());
+        }
+
+        let last = address
+            .checked_add(len - 1)
             .ok_or(MemoryError::InvalidAddress(address))?;
-        let offset = (address - region.start) as usize;
-        region.data[offset] = value;
+        for page in (address >> PAGE_SHIFT)..=(last >> PAGE_SHIFT) {
+            self.page((page << PAGE_SHIFT).max(address), access)?;
+        }
+
+        Ok(())
+    }
+
+    /// Fills `buf` from `address` a page at a time. Nothing is read unless all of it is
+    /// mapped readable
+    pub fn read_bytes(&self, address: u64, buf: &mut [u8]) -> Result<(), MemoryError> {
+        self.check_access(address, buf.len() as u64, Access::Read)?;
+
+        let mut done = 0;
+        while done < buf.len() {
+            let addr = address + done as u64;
+            let offset = (addr & PAGE_MASK) as usize;
+            let chunk = (PAGE_SIZE as usize - offset).min(buf.len() - done);
+
+            let page = self.page(addr, Access::Read)?;
+            buf[done..done + chunk].copy_from_slice(&page[offset..offset + chunk]);
+
+            done += chunk;
+        }
+
         Ok(())
     }
 
+    /// Copies `bytes` to `address` a page at a time. Nothing is written unless all of it is
+    /// mapped writable
+    pub fn write_bytes(&mut self, address: u64, bytes: &[u8]) -> Result<(), MemoryError> {
+        self.check_access(address, bytes.len() as u64, Access::Write)?;
+
+        let mut done = 0;
+        while done < bytes.len() {
+            let addr = address + done as u64;
+            let offset = (addr & PAGE_MASK) as usize;
+            let chunk = (PAGE_SIZE as usize - offset).min(bytes.len() - done);
+
+            let frame = self.frame_mut(addr)?;
+            Arc::make_mut(&mut frame.data)[offset..offset + chunk]
+                .copy_from_slice(&bytes[done..done + chunk]);
+            if frame.flags & PF_X as u64 != 0 {
+                self.note_code_write(addr);
+            }
+
+            done += chunk;
+        }
+
+        Ok(())
+    }
+
+    fn note_code_write(&mut self, address: u64) {
+        // NOTE: One entry per page is enough for runs of stores
+        let page = address & !PAGE_MASK;
+        if self.code_invalidations.last() != Some(&(page, PAGE_SIZE)) {
+            self.code_invalidations.push((page, PAGE_SIZE));
+        }
+    }
+
+    /// Reads a halfword of code, which needs the page to be executable rather than readable
+    pub fn fetch_halfword(&self, address: u64) -> Result<u64, MemoryError> {
+        self.read(address, Access::Execute)
+            .map(|bytes| u16::from_le_bytes(bytes).into())
+    }
+
     pub fn read_halfword(&self, address: u64) -> Result<u64, MemoryError> {
-        self.read_nbytes(address, 2)
+        self.read(address, Access::Read)
+            .map(|bytes| u16::from_le_bytes(bytes).into())
     }
 
     pub fn write_halfword(&mut self, address: u64, value: u64) -> Result<(), MemoryError> {
-        self.write_nbytes(address, value, 2)
+        self.write(address, (value as u16).to_le_bytes())
     }
 
     pub fn read_doubleword(&self, address: u64) -> Result<u64, MemoryError> {
-        self.read_nbytes(address, 8)
+        self.read(address, Access::Read).map(u64::from_le_bytes)
     }
 
     pub fn write_doubleword(&mut self, address: u64, value: u64) -> Result<(), MemoryError> {
-        self.write_nbytes(address, value, 8)
+        self.write(address, value.to_le_bytes())
     }
 
     pub fn read_nbytes(&self, address: u64, len: u64) -> Result<u64, MemoryError> {
-        let mut result = Vec::new();
-        for addr in address..address + len {
-            result.push(self.read_byte(addr)?)
-        }
-
-        Ok(result
-            .iter()
-            .enumerate()
-            .fold(0u64, |res, (idx, val)| res + (u64::from(*val) << (8 * idx))))
+        (0..len).try_fold(0u64, |res, idx| {
+            let byte = self.read_byte(address.wrapping_add(idx))?;
+            Ok(res | u64::from(byte) << (8 * idx))
+        })
     }
 
     pub fn write_nbytes(&mut self, address: u64, value: u64, len: u64) -> Result<(), MemoryError> {
-        for (idx, addr) in (address..address + len).enumerate() {
-            self.write_byte(addr, ((value >> (8 * idx)) & 0xFF) as u8)?;
+        for idx in 0..len {
+            self.write_byte(address.wrapping_add(idx), (value >> (8 * idx)) as u8)?;
         }
 
         Ok(())
     }
 
     pub fn read_word(&self, address: u64) -> Result<u32, MemoryError> {
-        let b0 = self.read_byte(address)? as u32;
-        let b1 = self.read_byte(address + 1)? as u32;
-        let b2 = self.read_byte(address + 2)? as u32;
-        let b3 = self.read_byte(address + 3)? as u32;
-        Ok(b0 | (b1 << 8) | (b2 << 16) | (b3 << 24))
+        self.read(address, Access::Read).map(u32::from_le_bytes)
     }
 
     pub fn write_word(&mut self, address: u64, value: u32) -> Result<(), MemoryError> {
-        self.write_byte(address, (value & 0xFF) as u8)?;
-        self.write_byte(address + 1, ((value >> 8) & 0xFF) as u8)?;
-        self.write_byte(address + 2, ((value >> 16) & 0xFF) as u8)?;
-        self.write_byte(address + 3, ((value >> 24) & 0xFF) as u8)?;
-        Ok(())
+        self.write(address, value.to_le_bytes())
     }
 
     #[inline]
@@ -263,7 +852,11 @@ impl Display for Ram {
     }
 }
 
-#[derive(Debug)]
+/// A mapping, `flags` uses the ELF `PF_*` bits.
+///
+/// `data` is what the region starts out with (zeros past its end), it's copied into the
+/// ram's pages when the region is added.
+#[derive(Debug, Clone)]
 pub struct MemoryRegion {
     start: u64,
     size: u64,
@@ -282,12 +875,13 @@ impl Display for MemoryRegion {
 }
 
 impl MemoryRegion {
+    /// A readable and writable (but not executable) region
     pub fn new(start: u64, size: u64, data: Vec<u8>) -> Self {
         MemoryRegion {
             start,
             size,
             data,
-            flags: 0,
+            flags: (PF_R | PF_W) as u64,
         }
     }
 
@@ -301,7 +895,7 @@ impl MemoryRegion {
     }
 
     pub fn is_execute(&self) -> bool {
-        self.flags & 1 == 1
+        self.flags & PF_X as u64 != 0
     }
 
     pub fn extend(&mut self, addition: u64) {
@@ -310,12 +904,182 @@ impl MemoryRegion {
     }
 }
 
-#[derive(Debug, Error)]
+#[derive(Debug, Error, Clone, Copy, PartialEq, Eq)]
 pub enum MemoryError {
     #[error("Invalid address: 0x{0:016x}")]
     InvalidAddress(u64),
-    #[error("Permission denied at address: 0x{0:X}")]
-    PermissionDenied(u64),
+    #[error("Permission denied for {1} at address: 0x{0:X}")]
+    PermissionDenied(u64, Access),
     #[error("Region overlap at address: 0x{0:X}")]
     RegionOverlap(u64),
 }
+
+impl MemoryError {
+    pub fn addr(&self) -> u64 {
+        match *self {
+            MemoryError::InvalidAddress(addr)
+            | MemoryError::PermissionDenied(addr, _)
+            | MemoryError::RegionOverlap(addr) => addr,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RW: u64 = (PF_R | PF_W) as u64;
+
+    #[test]
+    fn test_remove_range() {
+        let mut ram = Ram::new();
+        ram.add_region(MemoryRegion::new_with_flags(
+            0x10000,
+            3 * PAGE_SIZE,
+            vec![1; 3 * PAGE_SIZE as usize],
+            RW,
+        ))
+        .unwrap();
+        ram.add_region(MemoryRegion::new_with_flags(
+            0x20000,
+            PAGE_SIZE,
+            vec![2],
+            RW,
+        ))
+        .unwrap();
+
+        // NOTE: Only the page left without a region loses its frame
+        ram.remove_range(0x11000, PAGE_SIZE);
+        assert_eq!(ram.page_table.len(), 3);
+        assert_eq!(ram.read_byte(0x10000), Ok(1));
+        assert_eq!(ram.read_byte(0x12000), Ok(1));
+        assert_eq!(
+            ram.read_byte(0x11000),
+            Err(MemoryError::InvalidAddress(0x11000))
+        );
+
+        // NOTE: This walks the few pages there are, not the 2^35 in the range
+        ram.remove_range(0, 1 << 47);
+        assert!(ram.page_table.is_empty());
+        assert_eq!(ram.free_frames.len(), 4);
+        assert_eq!(ram.lowest_unalloced_addr, 0);
+    }
+
+    #[test]
+    fn test_untouched_pages() {
+        let mut ram = Ram::new();
+
+        // NOTE: 64 GiB, which would never fit if every page got a frame
+        let start = 0x10_0000_0000;
+        let len = 1 << 36;
+        ram.add_region(MemoryRegion::new_with_flags(start, len, vec![], RW))
+            .unwrap();
+        assert!(ram.page_table.is_empty());
+        assert_eq!(ram.read_doubleword(start + len - 8), Ok(0));
+
+        ram.write_word(start + 0x1234, 0xdead_beef).unwrap();
+        assert_eq!(ram.page_table.len(), 1);
+        assert_eq!(ram.read_word(start + 0x1234), Ok(0xdead_beef));
+        assert_eq!(ram.read_word(start + 0x2234), Ok(0));
+
+        assert!(ram.is_range_mapped(start, len));
+        assert!(!ram.is_range_mapped(start, len + PAGE_SIZE));
+        assert!(!ram.is_range_mapped(u64::MAX - PAGE_SIZE + 1, 2 * PAGE_SIZE));
+
+        // NOTE: Moving the range only copies the page that was written
+        let new = 0x20_0000_0000;
+        ram.remap(start, len, new, len).unwrap();
+        assert_eq!(ram.page_table.len(), 1);
+        assert_eq!(ram.read_word(new + 0x1234), Ok(0xdead_beef));
+        assert_eq!(
+            ram.read_word(start + 0x1234),
+            Err(MemoryError::InvalidAddress(start + 0x1234))
+        );
+
+        // NOTE: Filling ignores permissions, like the kernel reading in a file mapping
+        ram.protect(new, PAGE_SIZE, PF_R as u64).unwrap();
+        ram.fill(new + 0x10, b"riscvm").unwrap();
+        assert_eq!(ram.read_nbytes(new + 0x10, 6), Ok(0x6d76_6373_6972));
+        assert_eq!(
+            ram.fill(new - 8, &[1; 16]),
+            Err(MemoryError::InvalidAddress(new - 8))
+        );
+    }
+
+    #[test]
+    fn test_permissions() {
+        let mut ram = Ram::new();
+        let (rx, rw) = (0x10000, 0x11000);
+        let code = [0x13, 0, 0, 0];
+        ram.add_region(MemoryRegion::new_with_flags(
+            rx,
+            PAGE_SIZE,
+            code.to_vec(),
+            (PF_R | PF_X) as u64,
+        ))
+        .unwrap();
+        ram.add_region(MemoryRegion::new_with_flags(rw, PAGE_SIZE, vec![], RW))
+            .unwrap();
+        ram.add_region(MemoryRegion::new_with_flags(0x12000, PAGE_SIZE, vec![], 0))
+            .unwrap();
+
+        assert_eq!(ram.fetch_halfword(rx), Ok(0x13));
+        assert_eq!(ram.read_word(rx), Ok(0x13));
+        assert_eq!(
+            ram.write_word(rx + 8, 1),
+            Err(MemoryError::PermissionDenied(rx + 8, Access::Write))
+        );
+        ram.write_word(rw, 1).unwrap();
+        assert_eq!(
+            ram.fetch_halfword(rw + 2),
+            Err(MemoryError::PermissionDenied(rw + 2, Access::Execute))
+        );
+        assert_eq!(
+            ram.read_byte(0x12000),
+            Err(MemoryError::PermissionDenied(0x12000, Access::Read))
+        );
+        assert_eq!(
+            ram.check_access(rw + PAGE_SIZE - 4, 8, Access::Write),
+            Err(MemoryError::PermissionDenied(rw + PAGE_SIZE, Access::Write))
+        );
+
+        // NOTE: Swapping the two around, after the accesses above filled the TLB
+        ram.protect(rx, PAGE_SIZE, RW).unwrap();
+        ram.protect(rw, PAGE_SIZE, (PF_R | PF_X) as u64).unwrap();
+        assert_eq!(
+            ram.fetch_halfword(rx),
+            Err(MemoryError::PermissionDenied(rx, Access::Execute))
+        );
+        ram.write_word(rx + 8, 2).unwrap();
+        assert_eq!(ram.read_word(rx), Ok(0x13));
+        assert_eq!(ram.fetch_halfword(rw), Ok(1));
+        assert_eq!(
+            ram.write_word(rw, 2),
+            Err(MemoryError::PermissionDenied(rw, Access::Write))
+        );
+
+        // NOTE: A range that isn't all mapped is left alone
+        assert_eq!(
+            ram.protect(rx, 4 * PAGE_SIZE, RW),
+            Err(MemoryError::InvalidAddress(0x13000))
+        );
+        assert_eq!(
+            ram.write_word(rw, 2),
+            Err(MemoryError::PermissionDenied(rw, Access::Write))
+        );
+
+        // NOTE: Protecting part of a region splits it
+        ram.protect(0x12000, PAGE_SIZE, RW).unwrap();
+        ram.protect(rx, 3 * PAGE_SIZE, PF_R as u64).unwrap();
+        ram.protect(rw, PAGE_SIZE, RW).unwrap();
+        ram.write_byte(rw, 3).unwrap();
+        assert_eq!(
+            ram.write_byte(rw - 1, 3),
+            Err(MemoryError::PermissionDenied(rw - 1, Access::Write))
+        );
+        assert_eq!(
+            ram.write_byte(rw + PAGE_SIZE, 3),
+            Err(MemoryError::PermissionDenied(rw + PAGE_SIZE, Access::Write))
+        );
+    }
+}
@@ -0,0 +1,550 @@
+use std::ffi::OsStr;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use thiserror::Error;
+
+use crate::cpu::RV64GC;
+use crate::fcsr::RoundingMode;
+use crate::fd::{FdTable, OpenFile};
+use crate::ram::{Page, Ram, RamImage, PAGE_SIZE};
+use crate::vector::VectorState;
+
+const MAGIC: &[u8; 8] = b"RVMSNAP\0";
+const VERSION: u32 = 2;
+
+/// Pages in a snapshot file are either all zeroes (and left out) or stored as is
+const PAGE_ZERO: u8 = 0;
+const PAGE_DATA: u8 = 1;
+
+#[derive(Debug, Error)]
+pub enum SnapshotError {
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+    #[error("Not a riscvm snapshot")]
+    BadMagic,
+    #[error("Unsupported snapshot version: {0}")]
+    UnsupportedVersion(u32),
+    #[error("Corrupt snapshot: {0}")]
+    Corrupt(&'static str),
+    #[error("Couldn't reopen fd {fd} ({}): {source}", path.display())]
+    Reopen {
+        fd: i32,
+        path: PathBuf,
+        source: io::Error,
+    },
+}
+
+/// A file the guest had open, opened again (and seeked back) on restore
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct FileState {
+    fd: i32,
+    path: PathBuf,
+    host_path: PathBuf,
+    flags: u64,
+    offset: u64,
+}
+
+/// Everything the guest needs to carry on from where [`RV64GC::snapshot`] was taken:
+/// registers, `fcsr`, the vector state, the counters, memory, open files and the program break.
+///
+/// The host side setup (the launch config, execution mode, syscall overrides) isn't part
+/// of it, restoring keeps whatever the cpu has. Files are reopened by their host path, so
+/// they have to still be there. Memory pages are shared with the cpu copy-on-write, which
+/// keeps taking and restoring snapshots cheap.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snapshot {
+    registers: [u64; 33],
+    float_registers: [u64; 32],
+    fcsr: u32,
+    vector: VectorState,
+    cycle: u64,
+    instret: u64,
+    ram: RamImage,
+    files: Vec<FileState>,
+    brk_start: u64,
+    brk: u64,
+    load_bias: u64,
+    interp_base: Option<u64>,
+}
+
+impl Snapshot {
+    pub(crate) fn capture(cpu: &RV64GC) -> Snapshot {
+        let files = cpu
+            .fds
+            .iter()
+            .map(|(fd, open)| FileState {
+                fd,
+                path: open.path.clone(),
+                host_path: open.host_path.clone(),
+                flags: open.flags,
+                offset: open.offset(),
+            })
+            .collect();
+
+        Snapshot {
+            registers: std::array::from_fn(|i| cpu.registers[i]),
+            float_registers: std::array::from_fn(|i| cpu.float_registers[&(i as u8)]),
+            fcsr: cpu.fcsr.read(),
+            vector: cpu.vector.clone(),
+            cycle: cpu.csr.cycle(),
+            instret: cpu.csr.instret(),
+            ram: cpu.ram.image(),
+            files,
+            brk_start: cpu.brk_start,
+            brk: cpu.brk,
+            load_bias: cpu.load_bias,
+            interp_base: cpu.interp_base,
+        }
+    }
+
+    /// Puts `cpu` back in the captured state. Nothing changes if a file can't be reopened
+    pub(crate) fn restore_into(&self, cpu: &mut RV64GC) -> Result<(), SnapshotError> {
+        let frm = RoundingMode::from_bits((self.fcsr >> 5) as u8 & 0b111)
+            .ok_or(SnapshotError::Corrupt("reserved rounding mode"))?;
+
+        let mut fds = FdTable::new();
+        for state in &self.files {
+            let file = OpenFile::reopen(
+                state.path.clone(),
+                state.host_path.clone(),
+                state.flags,
+                state.offset,
+            )
+            .map_err(|source| SnapshotError::Reopen {
+                fd: state.fd,
+                path: state.host_path.clone(),
+                source,
+            })?;
+
+            fds.insert_at(state.fd, file);
+        }
+
+        for (i, value) in self.registers.iter().enumerate() {
+            cpu.registers[i] = *value;
+        }
+        for (i, value) in self.float_registers.iter().enumerate() {
+            cpu.float_registers[&(i as u8)] = *value;
+        }
+        cpu.fcsr.frm = frm;
+        cpu.fcsr.set_fflags(self.fcsr as u8);
+        cpu.vector = self.vector.clone();
+        cpu.csr.set_counters(self.cycle, self.instret);
+
+        cpu.ram = Ram::from_image(&self.ram);
+        // NOTE: While blocks run the block cache is taken out of the cpu, so clearing it
+        // below isn't enough on its own, this makes it drop everything too
+        cpu.ram.code_invalidations.push((0, u64::MAX));
+        cpu.fds = fds;
+        cpu.brk_start = self.brk_start;
+        cpu.brk = self.brk;
+        cpu.load_bias = self.load_bias;
+        cpu.interp_base = self.interp_base;
+
+        cpu.should_quit = false;
+        cpu.exit_code = None;
+        cpu.decode_cache.clear();
+        cpu.block_cache.clear();
+
+        Ok(())
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let mut file = BufWriter::new(File::create(path)?);
+        self.write_to(&mut file)?;
+        file.flush()
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Snapshot, SnapshotError> {
+        Snapshot::read_from(BufReader::new(File::open(path)?))
+    }
+
+    /// Writes the snapshot out, everything little endian
+    pub fn write_to(&self, mut w: impl Write) -> io::Result<()> {
+        let w = &mut w;
+        w.write_all(MAGIC)?;
+        w.write_all(&VERSION.to_le_bytes())?;
+
+        for value in self.registers.iter().chain(&self.float_registers) {
+            put_u64(w, *value)?;
+        }
+        w.write_all(&self.fcsr.to_le_bytes())?;
+        let vector = &self.vector;
+        for value in [vector.vl, vector.vtype, vector.vstart, vector.vcsr()] {
+            put_u64(w, value)?;
+        }
+        w.write_all(&vector.registers)?;
+        for value in [
+            self.cycle,
+            self.instret,
+            self.brk_start,
+            self.brk,
+            self.load_bias,
+        ] {
+            put_u64(w, value)?;
+        }
+        put_u64(w, self.interp_base.is_some().into())?;
+        put_u64(w, self.interp_base.unwrap_or(0))?;
+
+        put_u64(w, self.ram.lowest_unalloced_addr)?;
+        put_u64(w, self.ram.regions.len() as u64)?;
+        for (start, size, flags) in &self.ram.regions {
+            put_u64(w, *start)?;
+            put_u64(w, *size)?;
+            put_u64(w, *flags)?;
+        }
+
+        put_u64(w, self.ram.pages.len() as u64)?;
+        for (page, flags, data) in &self.ram.pages {
+            put_u64(w, *page)?;
+            put_u64(w, *flags)?;
+            if data.iter().all(|&b| b == 0) {
+                w.write_all(&[PAGE_ZERO])?;
+            } else {
+                w.write_all(&[PAGE_DATA])?;
+                w.write_all(&data[..])?;
+            }
+        }
+
+        put_u64(w, self.files.len() as u64)?;
+        for file in &self.files {
+            put_u64(w, file.fd as u64)?;
+            put_u64(w, file.flags)?;
+            put_u64(w, file.offset)?;
+            put_path(w, &file.path)?;
+            put_path(w, &file.host_path)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn read_from(mut r: impl Read) -> Result<Snapshot, SnapshotError> {
+        let r = &mut r;
+
+        let mut magic = [0; 8];
+        r.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(SnapshotError::BadMagic);
+        }
+
+        let mut version = [0; 4];
+        r.read_exact(&mut version)?;
+        let version = u32::from_le_bytes(version);
+        if version != VERSION {
+            return Err(SnapshotError::UnsupportedVersion(version));
+        }
+
+        let mut registers = [0; 33];
+        for value in &mut registers {
+            *value = get_u64(r)?;
+        }
+        let mut float_registers = [0; 32];
+        for value in &mut float_registers {
+            *value = get_u64(r)?;
+        }
+        let mut fcsr = [0; 4];
+        r.read_exact(&mut fcsr)?;
+        let fcsr = u32::from_le_bytes(fcsr);
+
+        let mut vector = VectorState::new();
+        vector.vl = get_u64(r)?;
+        vector.vtype = get_u64(r)?;
+        vector.vstart = get_u64(r)?;
+        vector.set_vcsr(get_u64(r)?);
+        r.read_exact(&mut vector.registers)?;
+
+        let cycle = get_u64(r)?;
+        let instret = get_u64(r)?;
+        let brk_start = get_u64(r)?;
+        let brk = get_u64(r)?;
+        let load_bias = get_u64(r)?;
+        let has_interp = get_u64(r)? != 0;
+        let interp_base = Some(get_u64(r)?).filter(|_| has_interp);
+
+        let lowest_unalloced_addr = get_u64(r)?;
+        let count = get_u64(r)?;
+        let mut regions: Vec<(u64, u64, u64)> = Vec::with_capacity(count.min(1024) as usize);
+        for _ in 0..count {
+            let (start, size, flags) = (get_u64(r)?, get_u64(r)?, get_u64(r)?);
+
+            // NOTE: Regions are stored sorted, and can't overlap or wrap around
+            if start.checked_add(size).is_none() {
+                return Err(SnapshotError::Corrupt("region wraps around"));
+            }
+            if regions.last().is_some_and(|&(s, size, _)| s + size > start) {
+                return Err(SnapshotError::Corrupt("overlapping regions"));
+            }
+            regions.push((start, size, flags));
+        }
+
+        let count = get_u64(r)?;
+        let mut pages: Vec<(u64, u64, Arc<Page>)> = Vec::with_capacity(count.min(1024) as usize);
+        for _ in 0..count {
+            let page = get_u64(r)?;
+            let flags = get_u64(r)?;
+            if pages.last().is_some_and(|&(last, ..)| last >= page) || page > u64::MAX / PAGE_SIZE {
+                return Err(SnapshotError::Corrupt("page numbers out of order"));
+            }
+
+            let mut kind = [0];
+            r.read_exact(&mut kind)?;
+            let mut data: Page = [0; PAGE_SIZE as usize];
+            match kind[0] {
+                PAGE_ZERO => {}
+                PAGE_DATA => r.read_exact(&mut data[..])?,
+                _ => return Err(SnapshotError::Corrupt("unknown page kind")),
+            }
+
+            pages.push((page, flags, Arc::new(data)));
+        }
+
+        let count = get_u64(r)?;
+        let mut files = Vec::with_capacity(count.min(1024) as usize);
+        for _ in 0..count {
+            files.push(FileState {
+                fd: get_u64(r)? as i32,
+                flags: get_u64(r)?,
+                offset: get_u64(r)?,
+                path: get_path(r)?,
+                host_path: get_path(r)?,
+            });
+        }
+
+        Ok(Snapshot {
+            registers,
+            float_registers,
+            fcsr,
+            vector,
+            cycle,
+            instret,
+            ram: RamImage {
+                regions,
+                pages,
+                lowest_unalloced_addr,
+            },
+            files,
+            brk_start,
+            brk,
+            load_bias,
+            interp_base,
+        })
+    }
+}
+
+fn put_u64(w: &mut impl Write, value: u64) -> io::Result<()> {
+    w.write_all(&value.to_le_bytes())
+}
+
+fn put_path(w: &mut impl Write, path: &Path) -> io::Result<()> {
+    let bytes = path.as_os_str().as_bytes();
+    put_u64(w, bytes.len() as u64)?;
+    w.write_all(bytes)
+}
+
+fn get_u64(r: &mut impl Read) -> io::Result<u64> {
+    let mut bytes = [0; 8];
+    r.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+fn get_path(r: &mut impl Read) -> Result<PathBuf, SnapshotError> {
+    let len = get_u64(r)?;
+    if len > 4096 {
+        return Err(SnapshotError::Corrupt("path too long"));
+    }
+
+    let mut bytes = vec![0; len as usize];
+    r.read_exact(&mut bytes)?;
+    Ok(PathBuf::from(OsStr::from_bytes(&bytes)))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Seek, SeekFrom};
+
+    use goblin::elf::program_header::{PF_R, PF_W, PF_X};
+
+    use super::*;
+    use crate::cpu::{ExecutionMode, ExitReason, RV64GCRegAbiName::*};
+    use crate::ram::MemoryRegion;
+    use crate::syscalls::Dispatch;
+
+    const DATA: u64 = 0x10000;
+
+    fn cpu_with_file(name: &str) -> (RV64GC, PathBuf) {
+        let host_path = std::env::temp_dir().join(format!("riscvm-{}-{name}", std::process::id()));
+        std::fs::write(&host_path, b"0123456789").unwrap();
+
+        let mut cpu = RV64GC::new();
+        cpu.ram
+            .add_region(MemoryRegion::new(DATA, 2 * PAGE_SIZE, b"snapshot".to_vec()))
+            .unwrap();
+
+        let mut file = File::open(&host_path).unwrap();
+        file.seek(SeekFrom::Start(3)).unwrap();
+        cpu.fds.insert(OpenFile {
+            file,
+            path: PathBuf::from("/data"),
+            host_path: host_path.clone(),
+            flags: 0,
+        });
+
+        cpu.registers[A0] = 7;
+        cpu.float_registers[&3] = 2.5f64.to_bits();
+        cpu.brk_start = 0x40000;
+        cpu.brk = 0x40123;
+        (cpu, host_path)
+    }
+
+    /// Changes everything [`cpu_with_file`] set up
+    fn scribble(cpu: &mut RV64GC) {
+        cpu.registers[A0] = 8;
+        cpu.registers[Pc] = 0x1234;
+        cpu.float_registers[&3] = 0;
+        cpu.ram.write_doubleword(DATA, 0).unwrap();
+        cpu.ram.write_byte(DATA + PAGE_SIZE, 1).unwrap();
+        cpu.fds
+            .get_mut(3)
+            .unwrap()
+            .file
+            .seek(SeekFrom::Start(9))
+            .unwrap();
+        cpu.brk = 0x41000;
+    }
+
+    fn assert_restored(cpu: &RV64GC) {
+        assert_eq!(cpu.registers[A0], 7);
+        assert_eq!(cpu.registers[Pc], 0);
+        assert_eq!(cpu.float_registers[&3], 2.5f64.to_bits());
+        assert_eq!(
+            cpu.ram.read_nbytes(DATA, 8),
+            Ok(u64::from_le_bytes(*b"snapshot"))
+        );
+        assert_eq!(cpu.ram.read_byte(DATA + PAGE_SIZE), Ok(0));
+        assert_eq!(cpu.fds.get(3).unwrap().offset(), 3);
+        assert_eq!((cpu.brk_start, cpu.brk), (0x40000, 0x40123));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let (mut cpu, host_path) = cpu_with_file("round-trip");
+        let snapshot = cpu.snapshot();
+
+        scribble(&mut cpu);
+        cpu.restore(&snapshot).unwrap();
+        assert_restored(&cpu);
+
+        let mut bytes = Vec::new();
+        snapshot.write_to(&mut bytes).unwrap();
+        assert_eq!(Snapshot::read_from(&bytes[..]).unwrap(), snapshot);
+
+        let path = host_path.with_extension("snap");
+        snapshot.save(&path).unwrap();
+        let loaded = Snapshot::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded, snapshot);
+
+        scribble(&mut cpu);
+        cpu.restore(&loaded).unwrap();
+        assert_restored(&cpu);
+
+        let mut fresh = RV64GC::new();
+        fresh.restore(&loaded).unwrap();
+        assert_restored(&fresh);
+        std::fs::remove_file(&host_path).unwrap();
+    }
+
+    #[test]
+    fn test_corrupt_input() {
+        let (cpu, host_path) = cpu_with_file("corrupt");
+        let mut bytes = Vec::new();
+        cpu.snapshot().write_to(&mut bytes).unwrap();
+        std::fs::remove_file(&host_path).unwrap();
+
+        for len in [0, 8, 12, 100, bytes.len() / 2, bytes.len() - 1] {
+            assert!(matches!(
+                Snapshot::read_from(&bytes[..len]),
+                Err(SnapshotError::Io(_))
+            ));
+        }
+
+        let mut bad = bytes.clone();
+        bad[0] = b'X';
+        assert!(matches!(
+            Snapshot::read_from(&bad[..]),
+            Err(SnapshotError::BadMagic)
+        ));
+
+        let mut bad = bytes.clone();
+        bad[8] = 9;
+        assert!(matches!(
+            Snapshot::read_from(&bad[..]),
+            Err(SnapshotError::UnsupportedVersion(9))
+        ));
+
+        // NOTE: The one region is right after the register file, vector state and scalars
+        let regions = 12 + 65 * 8 + 4 + 4 * 8 + VectorState::new().registers.len() + 7 * 8 + 16;
+        let mut bad = bytes.clone();
+        bad[regions + 8..regions + 16].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert!(matches!(
+            Snapshot::read_from(&bad[..]),
+            Err(SnapshotError::Corrupt(_))
+        ));
+
+        // NOTE: Then the page count, and the first page's number, flags and kind
+        let kind = regions + 24 + 8 + 16;
+        let mut bad = bytes.clone();
+        bad[kind] = 7;
+        assert!(matches!(
+            Snapshot::read_from(&bad[..]),
+            Err(SnapshotError::Corrupt(_))
+        ));
+    }
+
+    #[test]
+    fn test_restore_while_running() {
+        // NOTE: addi a1, a1, 1; addi a0, a0, 100; ecall
+        let code = [0x00158593u32, 0x06450513, 0x00000073];
+        let flags = (PF_R | PF_W | PF_X) as u64;
+
+        for mode in [ExecutionMode::Step, ExecutionMode::Blocks] {
+            let mut cpu = RV64GC::new();
+            cpu.execution_mode = mode;
+            let bytes = code.iter().flat_map(|i| i.to_le_bytes()).collect();
+            cpu.ram
+                .add_region(MemoryRegion::new_with_flags(
+                    0x1000, PAGE_SIZE, bytes, flags,
+                ))
+                .unwrap();
+            cpu.registers[A7] = 500;
+
+            // NOTE: The ecall moves pc past itself after the restore, to 0x1000
+            cpu.registers[Pc] = 0xffc;
+            let snapshot = cpu.snapshot();
+
+            // NOTE: Runs addi a0, a0, 1 first, the restore has to drop its translation
+            cpu.ram.write_word(0x1004, 0x00150513).unwrap();
+            cpu.registers[Pc] = 0x1000;
+
+            let mut calls = 0;
+            cpu.syscall_overrides
+                .register(500, move |cpu: &mut RV64GC, _| {
+                    calls += 1;
+                    if calls == 1 {
+                        cpu.restore(&snapshot).unwrap();
+                    } else {
+                        cpu.should_quit = true;
+                        cpu.exit_code = Some(0);
+                    }
+                    Ok(Dispatch::Handled)
+                });
+
+            assert_eq!(cpu.run(Some(100)), ExitReason::Exited(0));
+            assert_eq!(cpu.registers[A0], 100, "{mode:?}");
+            assert_eq!(cpu.registers[A1], 1);
+        }
+    }
+}
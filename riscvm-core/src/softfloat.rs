@@ -0,0 +1,1277 @@
+// IEEE 754 binary32 and binary64 arithmetic in software
+//
+// The host only rounds to nearest and doesn't tell us which exceptions happened, so every F
+// and D operation that can round goes through here instead. Operands are taken apart into an
+// integer significand and an exponent, the exact result (or enough of it, plus a sticky bit)
+// is computed with `u128`s and `round` packs it back the way `rm` says, raising the flags
+// RISC-V wants. Tininess is detected after rounding, and NaN results are always canonical.
+
+use std::cmp::Ordering;
+
+use crate::fcsr::{FloatExtends, RoundingMode, FCSR};
+
+/// A float taken apart
+#[derive(Debug, Clone, Copy)]
+enum Unpacked {
+    Nan {
+        signaling: bool,
+    },
+    Inf {
+        sign: bool,
+    },
+    Zero {
+        sign: bool,
+    },
+    /// `sig * 2^exp`, `sig` isn't zero
+    Finite {
+        sign: bool,
+        exp: i32,
+        sig: u128,
+    },
+}
+
+impl Unpacked {
+    fn sign(&self) -> bool {
+        match *self {
+            Unpacked::Nan { .. } => false,
+            Unpacked::Inf { sign } | Unpacked::Zero { sign } | Unpacked::Finite { sign, .. } => {
+                sign
+            }
+        }
+    }
+}
+
+const fn bias<F: FloatExtends>() -> i32 {
+    (1 << (F::EXP_BITS - 1)) - 1
+}
+
+const fn sign_bit<F: FloatExtends>() -> u64 {
+    1 << (F::EXP_BITS + F::FRAC_BITS)
+}
+
+/// Index of the highest set bit
+fn msb(value: u128) -> i32 {
+    127 - value.leading_zeros() as i32
+}
+
+/// Shifts right, ORing everything shifted out into the lowest bit
+fn shift_right_jam(value: u128, shift: i32) -> u128 {
+    if shift >= 128 {
+        u128::from(value != 0)
+    } else {
+        value >> shift | u128::from(value & ((1 << shift) - 1) != 0)
+    }
+}
+
+fn unpack<F: FloatExtends>(value: F) -> Unpacked {
+    let bits = value.to_raw();
+    let sign = bits & sign_bit::<F>() != 0;
+    let biased = (bits >> F::FRAC_BITS) & ((1 << F::EXP_BITS) - 1);
+    let frac = bits & ((1 << F::FRAC_BITS) - 1);
+
+    if biased == (1 << F::EXP_BITS) - 1 {
+        if frac == 0 {
+            Unpacked::Inf { sign }
+        } else {
+            Unpacked::Nan {
+                signaling: frac >> (F::FRAC_BITS - 1) == 0,
+            }
+        }
+    } else if biased == 0 {
+        if frac == 0 {
+            Unpacked::Zero { sign }
+        } else {
+            Unpacked::Finite {
+                sign,
+                exp: 1 - bias::<F>() - F::FRAC_BITS as i32,
+                sig: frac.into(),
+            }
+        }
+    } else {
+        Unpacked::Finite {
+            sign,
+            exp: biased as i32 - bias::<F>() - F::FRAC_BITS as i32,
+            sig: (frac | 1 << F::FRAC_BITS).into(),
+        }
+    }
+}
+
+fn pack<F: FloatExtends>(sign: bool, biased: u64, frac: u64) -> F {
+    let sign = if sign { sign_bit::<F>() } else { 0 };
+    F::from_raw(sign | biased << F::FRAC_BITS | frac)
+}
+
+fn zero<F: FloatExtends>(sign: bool) -> F {
+    pack(sign, 0, 0)
+}
+
+fn infinity<F: FloatExtends>(sign: bool) -> F {
+    pack(sign, (1 << F::EXP_BITS) - 1, 0)
+}
+
+fn invalid<F: FloatExtends>(fcsr: &mut FCSR) -> F {
+    fcsr.set_flag(FCSR::NV);
+    F::CANONICAL_NAN
+}
+
+/// The canonical NaN if any operand is a NaN, signaling ones are invalid
+fn propagate_nan<F: FloatExtends>(operands: &[Unpacked], fcsr: &mut FCSR) -> Option<F> {
+    if operands
+        .iter()
+        .any(|operand| matches!(operand, Unpacked::Nan { signaling: true }))
+    {
+        fcsr.set_flag(FCSR::NV);
+    }
+
+    operands
+        .iter()
+        .any(|operand| matches!(operand, Unpacked::Nan { .. }))
+        .then_some(F::CANONICAL_NAN)
+}
+
+/// Rounds `sig * 2^exp` (plus a bit more if `sticky`) to a multiple of `2^ulp`. Returns the
+/// multiple and whether anything was lost. With `sticky`, `ulp` has to be above `exp`
+fn round_at(
+    sign: bool,
+    exp: i32,
+    sig: u128,
+    sticky: bool,
+    ulp: i32,
+    rm: RoundingMode,
+) -> (u128, bool) {
+    let shift = ulp - exp;
+    if shift <= 0 {
+        debug_assert!(!sticky, "not enough bits to round");
+        return (sig << -shift, false);
+    }
+
+    // NOTE: How what's shifted out compares to half an ulp
+    let (kept, half, inexact) = if shift >= 128 {
+        (0, Ordering::Less, true)
+    } else {
+        let rest = sig & ((1 << shift) - 1);
+        let half = rest.cmp(&(1 << (shift - 1))).then(if sticky {
+            Ordering::Greater
+        } else {
+            Ordering::Equal
+        });
+
+        (sig >> shift, half, rest != 0 || sticky)
+    };
+
+    let up = match rm {
+        RoundingMode::Rne => half == Ordering::Greater || half == Ordering::Equal && kept & 1 == 1,
+        RoundingMode::Rmm => half != Ordering::Less,
+        RoundingMode::Rtz => false,
+        RoundingMode::Rdn => sign && inexact,
+        RoundingMode::Rup => !sign && inexact,
+    };
+
+    (kept + u128::from(up), inexact)
+}
+
+/// Packs the exact value `sig * 2^exp` (plus a bit more if `sticky`) into an `F`, rounding it
+/// the way `rm` says and raising OF, UF and NX
+fn round<F: FloatExtends>(
+    sign: bool,
+    exp: i32,
+    sig: u128,
+    sticky: bool,
+    rm: RoundingMode,
+    fcsr: &mut FCSR,
+) -> F {
+    debug_assert!(sig != 0);
+
+    let frac_bits = F::FRAC_BITS as i32;
+    let emin = 1 - bias::<F>();
+    let top = exp + msb(sig);
+
+    // NOTE: Subnormals keep fewer bits, their ulp is fixed
+    let mut ulp = top.max(emin) - frac_bits;
+    let (mut kept, inexact) = round_at(sign, exp, sig, sticky, ulp, rm);
+    if kept >> (frac_bits + 1) != 0 {
+        kept >>= 1;
+        ulp += 1;
+    }
+
+    if inexact {
+        fcsr.set_flag(FCSR::NX);
+
+        // NOTE: Tininess is detected after rounding, as if the exponent range was unbounded.
+        // Only a value right below the smallest normal can round up out of being tiny
+        if top < emin {
+            let (unbounded, _) = round_at(sign, exp, sig, sticky, top - frac_bits, rm);
+            if top != emin - 1 || unbounded >> (frac_bits + 1) == 0 {
+                fcsr.set_flag(FCSR::UF);
+            }
+        }
+    }
+
+    if kept == 0 {
+        return zero(sign);
+    }
+
+    if ulp + frac_bits > bias::<F>() {
+        fcsr.set_flag(FCSR::OF | FCSR::NX);
+
+        let to_infinity = match rm {
+            RoundingMode::Rne | RoundingMode::Rmm => true,
+            RoundingMode::Rtz => false,
+            RoundingMode::Rdn => sign,
+            RoundingMode::Rup => !sign,
+        };
+
+        return if to_infinity {
+            infinity(sign)
+        } else {
+            pack(sign, (1 << F::EXP_BITS) - 2, (1 << F::FRAC_BITS) - 1)
+        };
+    }
+
+    let frac = kept as u64 & ((1 << F::FRAC_BITS) - 1);
+    if kept >> frac_bits == 0 {
+        pack(sign, 0, frac)
+    } else {
+        pack(sign, (ulp + frac_bits + bias::<F>()) as u64, frac)
+    }
+}
+
+/// The exact product, `None` for infinity times zero. NaNs have to be handled already
+fn product(a: Unpacked, b: Unpacked) -> Option<Unpacked> {
+    let sign = a.sign() != b.sign();
+
+    match (a, b) {
+        (Unpacked::Inf { .. }, Unpacked::Zero { .. })
+        | (Unpacked::Zero { .. }, Unpacked::Inf { .. }) => None,
+        (Unpacked::Inf { .. }, _) | (_, Unpacked::Inf { .. }) => Some(Unpacked::Inf { sign }),
+        (Unpacked::Zero { .. }, _) | (_, Unpacked::Zero { .. }) => Some(Unpacked::Zero { sign }),
+        (
+            Unpacked::Finite {
+                exp: a_exp,
+                sig: a_sig,
+                ..
+            },
+            Unpacked::Finite {
+                exp: b_exp,
+                sig: b_sig,
+                ..
+            },
+        ) => Some(Unpacked::Finite {
+            sign,
+            exp: a_exp + b_exp,
+            sig: a_sig * b_sig,
+        }),
+        (Unpacked::Nan { .. }, _) | (_, Unpacked::Nan { .. }) => unreachable!(),
+    }
+}
+
+/// `a + b` for operands that aren't NaNs. `a` can be an exact product, so its significand
+/// may be twice as wide as an `F`'s
+fn sum<F: FloatExtends>(a: Unpacked, b: Unpacked, rm: RoundingMode, fcsr: &mut FCSR) -> F {
+    match (a, b) {
+        (Unpacked::Inf { sign: a_sign }, Unpacked::Inf { sign: b_sign }) if a_sign != b_sign => {
+            invalid(fcsr)
+        }
+        (Unpacked::Inf { sign }, _) | (_, Unpacked::Inf { sign }) => infinity(sign),
+        // NOTE: Zeros of opposite signs add up to +0, except when rounding down
+        (Unpacked::Zero { sign: a_sign }, Unpacked::Zero { sign: b_sign }) => {
+            zero(if a_sign == b_sign {
+                a_sign
+            } else {
+                rm == RoundingMode::Rdn
+            })
+        }
+        (Unpacked::Zero { .. }, Unpacked::Finite { sign, exp, sig })
+        | (Unpacked::Finite { sign, exp, sig }, Unpacked::Zero { .. }) => {
+            round(sign, exp, sig, false, rm, fcsr)
+        }
+        (
+            Unpacked::Finite {
+                sign: a_sign,
+                exp: a_exp,
+                sig: a_sig,
+            },
+            Unpacked::Finite {
+                sign: b_sign,
+                exp: b_exp,
+                sig: b_sig,
+            },
+        ) => {
+            // NOTE: The larger operand goes to the top of a u128, the other is lined up with
+            // it and whatever falls off the bottom sticks to its lowest bit. That's far below
+            // where the result gets rounded, even when the two nearly cancel out
+            let ((big_sign, big_exp, big_sig), (small_sign, small_exp, small_sig)) =
+                if a_exp + msb(a_sig) >= b_exp + msb(b_sig) {
+                    ((a_sign, a_exp, a_sig), (b_sign, b_exp, b_sig))
+                } else {
+                    ((b_sign, b_exp, b_sig), (a_sign, a_exp, a_sig))
+                };
+
+            let shift = 125 - msb(big_sig);
+            let exp = big_exp - shift;
+            let big_sig = big_sig << shift;
+            let small_sig = if small_exp >= exp {
+                small_sig << (small_exp - exp)
+            } else {
+                shift_right_jam(small_sig, exp - small_exp)
+            };
+
+            let (sign, sig) = if big_sign == small_sign {
+                (big_sign, big_sig + small_sig)
+            } else if big_sig >= small_sig {
+                (big_sign, big_sig - small_sig)
+            } else {
+                (small_sign, small_sig - big_sig)
+            };
+
+            if sig == 0 {
+                return zero(rm == RoundingMode::Rdn);
+            }
+
+            round(sign, exp, sig, false, rm, fcsr)
+        }
+        (Unpacked::Nan { .. }, _) | (_, Unpacked::Nan { .. }) => unreachable!(),
+    }
+}
+
+fn negate<F: FloatExtends>(value: F) -> F {
+    F::from_raw(value.to_raw() ^ sign_bit::<F>())
+}
+
+pub fn add<F: FloatExtends>(a: F, b: F, rm: RoundingMode, fcsr: &mut FCSR) -> F {
+    let (a, b) = (unpack(a), unpack(b));
+    if let Some(nan) = propagate_nan(&[a, b], fcsr) {
+        return nan;
+    }
+
+    sum(a, b, rm, fcsr)
+}
+
+pub fn sub<F: FloatExtends>(a: F, b: F, rm: RoundingMode, fcsr: &mut FCSR) -> F {
+    add(a, negate(b), rm, fcsr)
+}
+
+pub fn mul<F: FloatExtends>(a: F, b: F, rm: RoundingMode, fcsr: &mut FCSR) -> F {
+    let (a, b) = (unpack(a), unpack(b));
+    if let Some(nan) = propagate_nan(&[a, b], fcsr) {
+        return nan;
+    }
+
+    match product(a, b) {
+        None => invalid(fcsr),
+        Some(Unpacked::Inf { sign }) => infinity(sign),
+        Some(Unpacked::Zero { sign }) => zero(sign),
+        Some(Unpacked::Finite { sign, exp, sig }) => round(sign, exp, sig, false, rm, fcsr),
+        Some(Unpacked::Nan { .. }) => unreachable!(),
+    }
+}
+
+/// `a * b + c`, rounded once. `fmsub` and friends negate operands first
+pub fn mul_add<F: FloatExtends>(a: F, b: F, c: F, rm: RoundingMode, fcsr: &mut FCSR) -> F {
+    let (a, b, c) = (unpack(a), unpack(b), unpack(c));
+
+    // NOTE: Infinity times zero is invalid even when the addend is a quiet NaN
+    if let Some(nan) = propagate_nan(&[a, b, c], fcsr) {
+        if matches!(
+            (a, b),
+            (Unpacked::Inf { .. }, Unpacked::Zero { .. })
+                | (Unpacked::Zero { .. }, Unpacked::Inf { .. })
+        ) {
+            fcsr.set_flag(FCSR::NV);
+        }
+        return nan;
+    }
+
+    match product(a, b) {
+        None => invalid(fcsr),
+        Some(product) => sum(product, c, rm, fcsr),
+    }
+}
+
+pub fn neg_mul_add<F: FloatExtends>(a: F, b: F, c: F, rm: RoundingMode, fcsr: &mut FCSR) -> F {
+    mul_add(negate(a), b, c, rm, fcsr)
+}
+
+pub fn mul_sub<F: FloatExtends>(a: F, b: F, c: F, rm: RoundingMode, fcsr: &mut FCSR) -> F {
+    mul_add(a, b, negate(c), rm, fcsr)
+}
+
+pub fn neg_mul_sub<F: FloatExtends>(a: F, b: F, c: F, rm: RoundingMode, fcsr: &mut FCSR) -> F {
+    mul_add(negate(a), b, negate(c), rm, fcsr)
+}
+
+pub fn div<F: FloatExtends>(a: F, b: F, rm: RoundingMode, fcsr: &mut FCSR) -> F {
+    let (a, b) = (unpack(a), unpack(b));
+    if let Some(nan) = propagate_nan(&[a, b], fcsr) {
+        return nan;
+    }
+
+    let sign = a.sign() != b.sign();
+    match (a, b) {
+        (Unpacked::Inf { .. }, Unpacked::Inf { .. })
+        | (Unpacked::Zero { .. }, Unpacked::Zero { .. }) => invalid(fcsr),
+        (Unpacked::Inf { .. }, _) => infinity(sign),
+        (_, Unpacked::Zero { .. }) => {
+            fcsr.set_flag(FCSR::DZ);
+            infinity(sign)
+        }
+        (Unpacked::Zero { .. }, _) | (_, Unpacked::Inf { .. }) => zero(sign),
+        (
+            Unpacked::Finite {
+                exp: a_exp,
+                sig: a_sig,
+                ..
+            },
+            Unpacked::Finite {
+                exp: b_exp,
+                sig: b_sig,
+                ..
+            },
+        ) => {
+            // NOTE: With the dividend at the top, the quotient has over 70 bits
+            let shift = 126 - msb(a_sig);
+            let dividend = a_sig << shift;
+            let quotient = dividend / b_sig;
+            let sticky = !dividend.is_multiple_of(b_sig);
+
+            round(sign, a_exp - b_exp - shift, quotient, sticky, rm, fcsr)
+        }
+        (Unpacked::Nan { .. }, _) | (_, Unpacked::Nan { .. }) => unreachable!(),
+    }
+}
+
+pub fn sqrt<F: FloatExtends>(a: F, rm: RoundingMode, fcsr: &mut FCSR) -> F {
+    let a = unpack(a);
+    if let Some(nan) = propagate_nan(&[a], fcsr) {
+        return nan;
+    }
+
+    match a {
+        Unpacked::Zero { sign } => zero(sign),
+        Unpacked::Inf { sign: false } => infinity(false),
+        Unpacked::Inf { sign: true } | Unpacked::Finite { sign: true, .. } => invalid(fcsr),
+        Unpacked::Finite { exp, sig, .. } => {
+            // NOTE: Scaled by an even power of two, so the root's exponent is exact, and far
+            // enough that the root has over 60 bits
+            let mut shift = 126 - msb(sig);
+            if (exp - shift) % 2 != 0 {
+                shift -= 1;
+            }
+
+            let radicand = sig << shift;
+            let root = radicand.isqrt();
+
+            round(
+                false,
+                (exp - shift) / 2,
+                root,
+                root * root != radicand,
+                rm,
+                fcsr,
+            )
+        }
+        Unpacked::Nan { .. } => unreachable!(),
+    }
+}
+
+/// `fcvt` between the float formats, only narrowing can round
+pub fn convert<F: FloatExtends, T: FloatExtends>(value: F, rm: RoundingMode, fcsr: &mut FCSR) -> T {
+    match unpack(value) {
+        Unpacked::Nan { signaling } => {
+            if signaling {
+                fcsr.set_flag(FCSR::NV);
+            }
+            T::CANONICAL_NAN
+        }
+        Unpacked::Inf { sign } => infinity(sign),
+        Unpacked::Zero { sign } => zero(sign),
+        Unpacked::Finite { sign, exp, sig } => round(sign, exp, sig, false, rm, fcsr),
+    }
+}
+
+/// `fcvt` from an integer, which rounds when it has more bits than the significand
+pub fn from_int<F: FloatExtends>(value: i128, rm: RoundingMode, fcsr: &mut FCSR) -> F {
+    if value == 0 {
+        return zero(false);
+    }
+
+    round(value < 0, 0, value.unsigned_abs(), false, rm, fcsr)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rand::{Rng, SeedableRng};
+    use RoundingMode::*;
+
+    fn run(op: impl FnOnce(&mut FCSR) -> f64) -> (f64, u8) {
+        let mut fcsr = FCSR::new();
+        let res = op(&mut fcsr);
+        (res, fcsr.fflags())
+    }
+
+    #[test]
+    fn test_rounding_modes() {
+        let third = |rm| run(|fcsr| div(1.0, 3.0, rm, fcsr));
+        let (nearest, flags) = third(Rne);
+        assert_eq!((nearest, flags), (1.0 / 3.0, FCSR::NX));
+        assert_eq!(third(Rtz).0, nearest);
+        assert_eq!(third(Rdn).0, nearest);
+        assert_eq!(third(Rup).0, f64::from_bits(nearest.to_bits() + 1));
+        assert_eq!(third(Rmm).0, nearest);
+
+        // NOTE: 1 + 2^-53 is a tie, Rne keeps the even 1.0 and Rmm rounds away from it
+        let tie = |rm| run(|fcsr| add(1.0, f64::EPSILON / 2.0, rm, fcsr)).0;
+        assert_eq!(tie(Rne), 1.0);
+        assert_eq!(tie(Rmm), 1.0 + f64::EPSILON);
+        assert_eq!(tie(Rup), 1.0 + f64::EPSILON);
+
+        let mut fcsr = FCSR::new();
+        assert_eq!(from_int::<f32>(16_777_217, Rne, &mut fcsr), 16_777_216.0);
+        assert_eq!(from_int::<f32>(16_777_217, Rup, &mut fcsr), 16_777_218.0);
+        assert_eq!(from_int::<f32>(-16_777_217, Rdn, &mut fcsr), -16_777_218.0);
+        assert_eq!(fcsr.fflags(), FCSR::NX);
+    }
+
+    #[test]
+    fn test_exception_flags() {
+        assert_eq!(run(|fcsr| add(1.0, 2.0, Rne, fcsr)), (3.0, 0));
+        assert_eq!(
+            run(|fcsr| div(1.0, 0.0, Rne, fcsr)),
+            (f64::INFINITY, FCSR::DZ)
+        );
+        assert_eq!(run(|fcsr| sqrt(-1.0, Rne, fcsr)).1, FCSR::NV);
+        assert_eq!(
+            run(|fcsr| sqrt(-0.0, Rne, fcsr)).0.to_bits(),
+            (-0.0f64).to_bits()
+        );
+        assert_eq!(
+            run(|fcsr| mul(f64::MAX, 2.0, Rtz, fcsr)),
+            (f64::MAX, FCSR::OF | FCSR::NX)
+        );
+        assert_eq!(
+            run(|fcsr| mul(f64::MIN_POSITIVE, 0.5, Rne, fcsr)),
+            (f64::MIN_POSITIVE / 2.0, 0)
+        );
+        assert_eq!(
+            run(|fcsr| div(f64::MIN_POSITIVE, 3.0, Rne, fcsr)).1,
+            FCSR::UF | FCSR::NX
+        );
+        assert_eq!(
+            run(|fcsr| mul_add(f64::INFINITY, 0.0, f64::NAN, Rne, fcsr)).1,
+            FCSR::NV
+        );
+        assert_eq!(
+            run(|fcsr| sub(f64::INFINITY, f64::INFINITY, Rne, fcsr))
+                .0
+                .to_bits(),
+            f64::CANONICAL_NAN.to_bits()
+        );
+        assert_eq!(
+            run(|fcsr| sub(1.0, 1.0, Rdn, fcsr)).0.to_bits(),
+            (-0.0f64).to_bits()
+        );
+
+        // NOTE: Just below the smallest normal, tiny before rounding but not after, unless
+        // it's rounded down
+        let largest_subnormal = f64::MIN_POSITIVE - f64::from_bits(1);
+        let below = |rm| run(|fcsr| mul(largest_subnormal, 1.0 + f64::EPSILON, rm, fcsr));
+        assert_eq!(below(Rne), (f64::MIN_POSITIVE, FCSR::NX));
+        assert_eq!(below(Rtz), (largest_subnormal, FCSR::UF | FCSR::NX));
+    }
+
+    #[derive(Debug, Clone, Copy)]
+    enum Op {
+        Add,
+        Sub,
+        Mul,
+        Div,
+        Sqrt,
+        Fmadd,
+        Fmsub,
+        Fnmsub,
+        Fnmadd,
+    }
+
+    use Op::*;
+
+    impl Op {
+        fn apply<F: FloatExtends>(self, x: &[F], rm: RoundingMode, fcsr: &mut FCSR) -> F {
+            match self {
+                Add => add(x[0], x[1], rm, fcsr),
+                Sub => sub(x[0], x[1], rm, fcsr),
+                Mul => mul(x[0], x[1], rm, fcsr),
+                Div => div(x[0], x[1], rm, fcsr),
+                Sqrt => sqrt(x[0], rm, fcsr),
+                Fmadd => mul_add(x[0], x[1], x[2], rm, fcsr),
+                Fmsub => mul_sub(x[0], x[1], x[2], rm, fcsr),
+                // NOTE: -(a * b) + c and -(a * b) - c, the way the instructions call them
+                Fnmsub => neg_mul_add(x[0], x[1], x[2], rm, fcsr),
+                Fnmadd => neg_mul_sub(x[0], x[1], x[2], rm, fcsr),
+            }
+        }
+    }
+
+    const MODES: [RoundingMode; 5] = [Rne, Rtz, Rdn, Rup, Rmm];
+
+    /// An operation, the bits of its operands, and the result and flags for each of [`MODES`]
+    type Case = (Op, &'static [u64], [(u64, u8); 5]);
+
+    fn check<F: FloatExtends>(cases: &[Case]) {
+        for &(op, operands, expected) in cases {
+            let operands: Vec<F> = operands.iter().map(|&bits| F::from_raw(bits)).collect();
+            for (rm, (bits, flags)) in MODES.into_iter().zip(expected) {
+                let mut fcsr = FCSR::new();
+                let res = op.apply(&operands, rm, &mut fcsr);
+                assert_eq!(
+                    (res.to_raw(), fcsr.fflags()),
+                    (bits, flags),
+                    "{op:?} {:x?} {rm:?}",
+                    operands.iter().map(|x| x.to_raw()).collect::<Vec<_>>()
+                );
+            }
+        }
+    }
+
+    // NOTE: The expected values are the exact rational results rounded independently of this
+    // module, the round to nearest ones agree with the host's
+    #[rustfmt::skip]
+    const F32_CASES: &[Case] = &[
+        // 1 + eps/2 ties to even
+        (Add, &[0x3f80_0000, 0x3380_0000], [
+            (0x3f80_0000, FCSR::NX),
+            (0x3f80_0000, FCSR::NX),
+            (0x3f80_0000, FCSR::NX),
+            (0x3f80_0001, FCSR::NX),
+            (0x3f80_0001, FCSR::NX),
+        ]),
+        // 1 + 3/4 eps
+        (Add, &[0x3f80_0000, 0x33c0_0000], [
+            (0x3f80_0001, FCSR::NX),
+            (0x3f80_0000, FCSR::NX),
+            (0x3f80_0000, FCSR::NX),
+            (0x3f80_0001, FCSR::NX),
+            (0x3f80_0001, FCSR::NX),
+        ]),
+        // -1 - eps/2
+        (Add, &[0xbf80_0000, 0xb380_0000], [
+            (0xbf80_0000, FCSR::NX),
+            (0xbf80_0000, FCSR::NX),
+            (0xbf80_0001, FCSR::NX),
+            (0xbf80_0000, FCSR::NX),
+            (0xbf80_0001, FCSR::NX),
+        ]),
+        // exact zero is -0 only when rounding down
+        (Sub, &[0x3f80_0000, 0x3f80_0000], [
+            (0x0000_0000, 0),
+            (0x0000_0000, 0),
+            (0x8000_0000, 0),
+            (0x0000_0000, 0),
+            (0x0000_0000, 0),
+        ]),
+        // overflow
+        (Mul, &[0x7f7f_ffff, 0x4000_0000], [
+            (0x7f80_0000, FCSR::OF | FCSR::NX),
+            (0x7f7f_ffff, FCSR::OF | FCSR::NX),
+            (0x7f7f_ffff, FCSR::OF | FCSR::NX),
+            (0x7f80_0000, FCSR::OF | FCSR::NX),
+            (0x7f80_0000, FCSR::OF | FCSR::NX),
+        ]),
+        // negative overflow
+        (Mul, &[0xff7f_ffff, 0x4000_0000], [
+            (0xff80_0000, FCSR::OF | FCSR::NX),
+            (0xff7f_ffff, FCSR::OF | FCSR::NX),
+            (0xff80_0000, FCSR::OF | FCSR::NX),
+            (0xff7f_ffff, FCSR::OF | FCSR::NX),
+            (0xff80_0000, FCSR::OF | FCSR::NX),
+        ]),
+        // half the smallest subnormal ties to zero
+        (Mul, &[0x0000_0001, 0x3f00_0000], [
+            (0x0000_0000, FCSR::UF | FCSR::NX),
+            (0x0000_0000, FCSR::UF | FCSR::NX),
+            (0x0000_0000, FCSR::UF | FCSR::NX),
+            (0x0000_0001, FCSR::UF | FCSR::NX),
+            (0x0000_0001, FCSR::UF | FCSR::NX),
+        ]),
+        // exact subnormal
+        (Mul, &[0x0080_0000, 0x3f00_0000], [
+            (0x0040_0000, 0),
+            (0x0040_0000, 0),
+            (0x0040_0000, 0),
+            (0x0040_0000, 0),
+            (0x0040_0000, 0),
+        ]),
+        // tiny before rounding, not after
+        (Mul, &[0x007f_ffff, 0x3f80_0001], [
+            (0x0080_0000, FCSR::NX),
+            (0x007f_ffff, FCSR::UF | FCSR::NX),
+            (0x007f_ffff, FCSR::UF | FCSR::NX),
+            (0x0080_0000, FCSR::NX),
+            (0x0080_0000, FCSR::NX),
+        ]),
+        // 1 / 3
+        (Div, &[0x3f80_0000, 0x4040_0000], [
+            (0x3eaa_aaab, FCSR::NX),
+            (0x3eaa_aaaa, FCSR::NX),
+            (0x3eaa_aaaa, FCSR::NX),
+            (0x3eaa_aaab, FCSR::NX),
+            (0x3eaa_aaab, FCSR::NX),
+        ]),
+        // the smallest normal / 3
+        (Div, &[0x0080_0000, 0x4040_0000], [
+            (0x002a_aaab, FCSR::UF | FCSR::NX),
+            (0x002a_aaaa, FCSR::UF | FCSR::NX),
+            (0x002a_aaaa, FCSR::UF | FCSR::NX),
+            (0x002a_aaab, FCSR::UF | FCSR::NX),
+            (0x002a_aaab, FCSR::UF | FCSR::NX),
+        ]),
+        // (1 + eps)(1 - eps) - 1 is exact only when fused
+        (Fmadd, &[0x3f80_0001, 0x3f7f_fffe, 0xbf80_0000], [
+            (0xa880_0000, 0),
+            (0xa880_0000, 0),
+            (0xa880_0000, 0),
+            (0xa880_0000, 0),
+            (0xa880_0000, 0),
+        ]),
+        // (1 + eps)^2 - 1 ties
+        (Fmadd, &[0x3f80_0001, 0x3f80_0001, 0xbf80_0000], [
+            (0x3480_0000, FCSR::NX),
+            (0x3480_0000, FCSR::NX),
+            (0x3480_0000, FCSR::NX),
+            (0x3480_0001, FCSR::NX),
+            (0x3480_0001, FCSR::NX),
+        ]),
+        // 1 * 1 - 1
+        (Fmadd, &[0x3f80_0000, 0x3f80_0000, 0xbf80_0000], [
+            (0x0000_0000, 0),
+            (0x0000_0000, 0),
+            (0x8000_0000, 0),
+            (0x0000_0000, 0),
+            (0x0000_0000, 0),
+        ]),
+        // fmsub
+        (Fmsub, &[0x4000_0000, 0x4040_0000, 0x3f80_0000], [
+            (0x40a0_0000, 0),
+            (0x40a0_0000, 0),
+            (0x40a0_0000, 0),
+            (0x40a0_0000, 0),
+            (0x40a0_0000, 0),
+        ]),
+        // fnmsub negates the product
+        (Fnmsub, &[0x4000_0000, 0x4040_0000, 0x3f80_0000], [
+            (0xc0a0_0000, 0),
+            (0xc0a0_0000, 0),
+            (0xc0a0_0000, 0),
+            (0xc0a0_0000, 0),
+            (0xc0a0_0000, 0),
+        ]),
+        // fnmadd negates the product and the addend
+        (Fnmadd, &[0x4000_0000, 0x4040_0000, 0x3f80_0000], [
+            (0xc0e0_0000, 0),
+            (0xc0e0_0000, 0),
+            (0xc0e0_0000, 0),
+            (0xc0e0_0000, 0),
+            (0xc0e0_0000, 0),
+        ]),
+        // fnmsub rounds after negating
+        (Fnmsub, &[0x3f80_0001, 0x3f80_0001, 0x3f80_0000], [
+            (0xb480_0000, FCSR::NX),
+            (0xb480_0000, FCSR::NX),
+            (0xb480_0001, FCSR::NX),
+            (0xb480_0000, FCSR::NX),
+            (0xb480_0001, FCSR::NX),
+        ]),
+        // fnmadd rounds after negating
+        (Fnmadd, &[0x3f80_0001, 0x3f80_0001, 0xbf80_0000], [
+            (0xb480_0000, FCSR::NX),
+            (0xb480_0000, FCSR::NX),
+            (0xb480_0001, FCSR::NX),
+            (0xb480_0000, FCSR::NX),
+            (0xb480_0001, FCSR::NX),
+        ]),
+        // fnmadd of zeros
+        (Fnmadd, &[0x3f80_0000, 0x0000_0000, 0x0000_0000], [
+            (0x8000_0000, 0),
+            (0x8000_0000, 0),
+            (0x8000_0000, 0),
+            (0x8000_0000, 0),
+            (0x8000_0000, 0),
+        ]),
+        // fnmsub of zeros
+        (Fnmsub, &[0x3f80_0000, 0x0000_0000, 0x0000_0000], [
+            (0x0000_0000, 0),
+            (0x0000_0000, 0),
+            (0x8000_0000, 0),
+            (0x0000_0000, 0),
+            (0x0000_0000, 0),
+        ]),
+        // fmsub of zeros
+        (Fmsub, &[0x3f80_0000, 0x0000_0000, 0x0000_0000], [
+            (0x0000_0000, 0),
+            (0x0000_0000, 0),
+            (0x8000_0000, 0),
+            (0x0000_0000, 0),
+            (0x0000_0000, 0),
+        ]),
+        // sqrt(2)
+        (Sqrt, &[0x4000_0000], [
+            (0x3fb5_04f3, FCSR::NX),
+            (0x3fb5_04f3, FCSR::NX),
+            (0x3fb5_04f3, FCSR::NX),
+            (0x3fb5_04f4, FCSR::NX),
+            (0x3fb5_04f3, FCSR::NX),
+        ]),
+        // sqrt(4)
+        (Sqrt, &[0x4080_0000], [
+            (0x4000_0000, 0),
+            (0x4000_0000, 0),
+            (0x4000_0000, 0),
+            (0x4000_0000, 0),
+            (0x4000_0000, 0),
+        ]),
+        // sqrt(-0)
+        (Sqrt, &[0x8000_0000], [
+            (0x8000_0000, 0),
+            (0x8000_0000, 0),
+            (0x8000_0000, 0),
+            (0x8000_0000, 0),
+            (0x8000_0000, 0),
+        ]),
+        // sqrt of the smallest subnormal
+        (Sqrt, &[0x0000_0001], [
+            (0x1a35_04f3, FCSR::NX),
+            (0x1a35_04f3, FCSR::NX),
+            (0x1a35_04f3, FCSR::NX),
+            (0x1a35_04f4, FCSR::NX),
+            (0x1a35_04f3, FCSR::NX),
+        ]),
+        // sqrt of the largest subnormal
+        (Sqrt, &[0x007f_ffff], [
+            (0x1fff_ffff, FCSR::NX),
+            (0x1fff_fffe, FCSR::NX),
+            (0x1fff_fffe, FCSR::NX),
+            (0x1fff_ffff, FCSR::NX),
+            (0x1fff_ffff, FCSR::NX),
+        ]),
+    ];
+
+    #[rustfmt::skip]
+    const F64_CASES: &[Case] = &[
+        // 1 + eps/2 ties to even
+        (Add, &[0x3ff0_0000_0000_0000, 0x3ca0_0000_0000_0000], [
+            (0x3ff0_0000_0000_0000, FCSR::NX),
+            (0x3ff0_0000_0000_0000, FCSR::NX),
+            (0x3ff0_0000_0000_0000, FCSR::NX),
+            (0x3ff0_0000_0000_0001, FCSR::NX),
+            (0x3ff0_0000_0000_0001, FCSR::NX),
+        ]),
+        // 1 + 3/4 eps
+        (Add, &[0x3ff0_0000_0000_0000, 0x3ca8_0000_0000_0000], [
+            (0x3ff0_0000_0000_0001, FCSR::NX),
+            (0x3ff0_0000_0000_0000, FCSR::NX),
+            (0x3ff0_0000_0000_0000, FCSR::NX),
+            (0x3ff0_0000_0000_0001, FCSR::NX),
+            (0x3ff0_0000_0000_0001, FCSR::NX),
+        ]),
+        // -1 - eps/2
+        (Add, &[0xbff0_0000_0000_0000, 0xbca0_0000_0000_0000], [
+            (0xbff0_0000_0000_0000, FCSR::NX),
+            (0xbff0_0000_0000_0000, FCSR::NX),
+            (0xbff0_0000_0000_0001, FCSR::NX),
+            (0xbff0_0000_0000_0000, FCSR::NX),
+            (0xbff0_0000_0000_0001, FCSR::NX),
+        ]),
+        // exact zero is -0 only when rounding down
+        (Sub, &[0x3ff0_0000_0000_0000, 0x3ff0_0000_0000_0000], [
+            (0x0000_0000_0000_0000, 0),
+            (0x0000_0000_0000_0000, 0),
+            (0x8000_0000_0000_0000, 0),
+            (0x0000_0000_0000_0000, 0),
+            (0x0000_0000_0000_0000, 0),
+        ]),
+        // overflow
+        (Mul, &[0x7fef_ffff_ffff_ffff, 0x4000_0000_0000_0000], [
+            (0x7ff0_0000_0000_0000, FCSR::OF | FCSR::NX),
+            (0x7fef_ffff_ffff_ffff, FCSR::OF | FCSR::NX),
+            (0x7fef_ffff_ffff_ffff, FCSR::OF | FCSR::NX),
+            (0x7ff0_0000_0000_0000, FCSR::OF | FCSR::NX),
+            (0x7ff0_0000_0000_0000, FCSR::OF | FCSR::NX),
+        ]),
+        // negative overflow
+        (Mul, &[0xffef_ffff_ffff_ffff, 0x4000_0000_0000_0000], [
+            (0xfff0_0000_0000_0000, FCSR::OF | FCSR::NX),
+            (0xffef_ffff_ffff_ffff, FCSR::OF | FCSR::NX),
+            (0xfff0_0000_0000_0000, FCSR::OF | FCSR::NX),
+            (0xffef_ffff_ffff_ffff, FCSR::OF | FCSR::NX),
+            (0xfff0_0000_0000_0000, FCSR::OF | FCSR::NX),
+        ]),
+        // half the smallest subnormal ties to zero
+        (Mul, &[0x0000_0000_0000_0001, 0x3fe0_0000_0000_0000], [
+            (0x0000_0000_0000_0000, FCSR::UF | FCSR::NX),
+            (0x0000_0000_0000_0000, FCSR::UF | FCSR::NX),
+            (0x0000_0000_0000_0000, FCSR::UF | FCSR::NX),
+            (0x0000_0000_0000_0001, FCSR::UF | FCSR::NX),
+            (0x0000_0000_0000_0001, FCSR::UF | FCSR::NX),
+        ]),
+        // exact subnormal
+        (Mul, &[0x0010_0000_0000_0000, 0x3fe0_0000_0000_0000], [
+            (0x0008_0000_0000_0000, 0),
+            (0x0008_0000_0000_0000, 0),
+            (0x0008_0000_0000_0000, 0),
+            (0x0008_0000_0000_0000, 0),
+            (0x0008_0000_0000_0000, 0),
+        ]),
+        // tiny before rounding, not after
+        (Mul, &[0x000f_ffff_ffff_ffff, 0x3ff0_0000_0000_0001], [
+            (0x0010_0000_0000_0000, FCSR::NX),
+            (0x000f_ffff_ffff_ffff, FCSR::UF | FCSR::NX),
+            (0x000f_ffff_ffff_ffff, FCSR::UF | FCSR::NX),
+            (0x0010_0000_0000_0000, FCSR::NX),
+            (0x0010_0000_0000_0000, FCSR::NX),
+        ]),
+        // 1 / 3
+        (Div, &[0x3ff0_0000_0000_0000, 0x4008_0000_0000_0000], [
+            (0x3fd5_5555_5555_5555, FCSR::NX),
+            (0x3fd5_5555_5555_5555, FCSR::NX),
+            (0x3fd5_5555_5555_5555, FCSR::NX),
+            (0x3fd5_5555_5555_5556, FCSR::NX),
+            (0x3fd5_5555_5555_5555, FCSR::NX),
+        ]),
+        // the smallest normal / 3
+        (Div, &[0x0010_0000_0000_0000, 0x4008_0000_0000_0000], [
+            (0x0005_5555_5555_5555, FCSR::UF | FCSR::NX),
+            (0x0005_5555_5555_5555, FCSR::UF | FCSR::NX),
+            (0x0005_5555_5555_5555, FCSR::UF | FCSR::NX),
+            (0x0005_5555_5555_5556, FCSR::UF | FCSR::NX),
+            (0x0005_5555_5555_5555, FCSR::UF | FCSR::NX),
+        ]),
+        // (1 + eps)(1 - eps) - 1 is exact only when fused
+        (Fmadd, &[0x3ff0_0000_0000_0001, 0x3fef_ffff_ffff_fffe, 0xbff0_0000_0000_0000], [
+            (0xb970_0000_0000_0000, 0),
+            (0xb970_0000_0000_0000, 0),
+            (0xb970_0000_0000_0000, 0),
+            (0xb970_0000_0000_0000, 0),
+            (0xb970_0000_0000_0000, 0),
+        ]),
+        // (1 + eps)^2 - 1 ties
+        (Fmadd, &[0x3ff0_0000_0000_0001, 0x3ff0_0000_0000_0001, 0xbff0_0000_0000_0000], [
+            (0x3cc0_0000_0000_0000, FCSR::NX),
+            (0x3cc0_0000_0000_0000, FCSR::NX),
+            (0x3cc0_0000_0000_0000, FCSR::NX),
+            (0x3cc0_0000_0000_0001, FCSR::NX),
+            (0x3cc0_0000_0000_0001, FCSR::NX),
+        ]),
+        // 1 * 1 - 1
+        (Fmadd, &[0x3ff0_0000_0000_0000, 0x3ff0_0000_0000_0000, 0xbff0_0000_0000_0000], [
+            (0x0000_0000_0000_0000, 0),
+            (0x0000_0000_0000_0000, 0),
+            (0x8000_0000_0000_0000, 0),
+            (0x0000_0000_0000_0000, 0),
+            (0x0000_0000_0000_0000, 0),
+        ]),
+        // fmsub
+        (Fmsub, &[0x4000_0000_0000_0000, 0x4008_0000_0000_0000, 0x3ff0_0000_0000_0000], [
+            (0x4014_0000_0000_0000, 0),
+            (0x4014_0000_0000_0000, 0),
+            (0x4014_0000_0000_0000, 0),
+            (0x4014_0000_0000_0000, 0),
+            (0x4014_0000_0000_0000, 0),
+        ]),
+        // fnmsub negates the product
+        (Fnmsub, &[0x4000_0000_0000_0000, 0x4008_0000_0000_0000, 0x3ff0_0000_0000_0000], [
+            (0xc014_0000_0000_0000, 0),
+            (0xc014_0000_0000_0000, 0),
+            (0xc014_0000_0000_0000, 0),
+            (0xc014_0000_0000_0000, 0),
+            (0xc014_0000_0000_0000, 0),
+        ]),
+        // fnmadd negates the product and the addend
+        (Fnmadd, &[0x4000_0000_0000_0000, 0x4008_0000_0000_0000, 0x3ff0_0000_0000_0000], [
+            (0xc01c_0000_0000_0000, 0),
+            (0xc01c_0000_0000_0000, 0),
+            (0xc01c_0000_0000_0000, 0),
+            (0xc01c_0000_0000_0000, 0),
+            (0xc01c_0000_0000_0000, 0),
+        ]),
+        // fnmsub rounds after negating
+        (Fnmsub, &[0x3ff0_0000_0000_0001, 0x3ff0_0000_0000_0001, 0x3ff0_0000_0000_0000], [
+            (0xbcc0_0000_0000_0000, FCSR::NX),
+            (0xbcc0_0000_0000_0000, FCSR::NX),
+            (0xbcc0_0000_0000_0001, FCSR::NX),
+            (0xbcc0_0000_0000_0000, FCSR::NX),
+            (0xbcc0_0000_0000_0001, FCSR::NX),
+        ]),
+        // fnmadd rounds after negating
+        (Fnmadd, &[0x3ff0_0000_0000_0001, 0x3ff0_0000_0000_0001, 0xbff0_0000_0000_0000], [
+            (0xbcc0_0000_0000_0000, FCSR::NX),
+            (0xbcc0_0000_0000_0000, FCSR::NX),
+            (0xbcc0_0000_0000_0001, FCSR::NX),
+            (0xbcc0_0000_0000_0000, FCSR::NX),
+            (0xbcc0_0000_0000_0001, FCSR::NX),
+        ]),
+        // fnmadd of zeros
+        (Fnmadd, &[0x3ff0_0000_0000_0000, 0x0000_0000_0000_0000, 0x0000_0000_0000_0000], [
+            (0x8000_0000_0000_0000, 0),
+            (0x8000_0000_0000_0000, 0),
+            (0x8000_0000_0000_0000, 0),
+            (0x8000_0000_0000_0000, 0),
+            (0x8000_0000_0000_0000, 0),
+        ]),
+        // fnmsub of zeros
+        (Fnmsub, &[0x3ff0_0000_0000_0000, 0x0000_0000_0000_0000, 0x0000_0000_0000_0000], [
+            (0x0000_0000_0000_0000, 0),
+            (0x0000_0000_0000_0000, 0),
+            (0x8000_0000_0000_0000, 0),
+            (0x0000_0000_0000_0000, 0),
+            (0x0000_0000_0000_0000, 0),
+        ]),
+        // fmsub of zeros
+        (Fmsub, &[0x3ff0_0000_0000_0000, 0x0000_0000_0000_0000, 0x0000_0000_0000_0000], [
+            (0x0000_0000_0000_0000, 0),
+            (0x0000_0000_0000_0000, 0),
+            (0x8000_0000_0000_0000, 0),
+            (0x0000_0000_0000_0000, 0),
+            (0x0000_0000_0000_0000, 0),
+        ]),
+        // sqrt(2)
+        (Sqrt, &[0x4000_0000_0000_0000], [
+            (0x3ff6_a09e_667f_3bcd, FCSR::NX),
+            (0x3ff6_a09e_667f_3bcc, FCSR::NX),
+            (0x3ff6_a09e_667f_3bcc, FCSR::NX),
+            (0x3ff6_a09e_667f_3bcd, FCSR::NX),
+            (0x3ff6_a09e_667f_3bcd, FCSR::NX),
+        ]),
+        // sqrt(4)
+        (Sqrt, &[0x4010_0000_0000_0000], [
+            (0x4000_0000_0000_0000, 0),
+            (0x4000_0000_0000_0000, 0),
+            (0x4000_0000_0000_0000, 0),
+            (0x4000_0000_0000_0000, 0),
+            (0x4000_0000_0000_0000, 0),
+        ]),
+        // sqrt(-0)
+        (Sqrt, &[0x8000_0000_0000_0000], [
+            (0x8000_0000_0000_0000, 0),
+            (0x8000_0000_0000_0000, 0),
+            (0x8000_0000_0000_0000, 0),
+            (0x8000_0000_0000_0000, 0),
+            (0x8000_0000_0000_0000, 0),
+        ]),
+        // sqrt of the smallest subnormal
+        (Sqrt, &[0x0000_0000_0000_0001], [
+            (0x1e60_0000_0000_0000, 0),
+            (0x1e60_0000_0000_0000, 0),
+            (0x1e60_0000_0000_0000, 0),
+            (0x1e60_0000_0000_0000, 0),
+            (0x1e60_0000_0000_0000, 0),
+        ]),
+        // sqrt of the largest subnormal
+        (Sqrt, &[0x000f_ffff_ffff_ffff], [
+            (0x1fff_ffff_ffff_ffff, FCSR::NX),
+            (0x1fff_ffff_ffff_fffe, FCSR::NX),
+            (0x1fff_ffff_ffff_fffe, FCSR::NX),
+            (0x1fff_ffff_ffff_ffff, FCSR::NX),
+            (0x1fff_ffff_ffff_ffff, FCSR::NX),
+        ]),
+    ];
+
+    /// `fcvt.s.d` inputs, and the result and flags for each of [`MODES`]
+    #[rustfmt::skip]
+    const NARROWING_CASES: &[(u64, [(u64, u8); 5])] = &[
+        // 1 + 2^-24 ties to even
+        (0x3ff0_0000_1000_0000, [
+            (0x3f80_0000, FCSR::NX),
+            (0x3f80_0000, FCSR::NX),
+            (0x3f80_0000, FCSR::NX),
+            (0x3f80_0001, FCSR::NX),
+            (0x3f80_0001, FCSR::NX),
+        ]),
+        // -(1 + 3 * 2^-25)
+        (0xbff0_0000_1800_0000, [
+            (0xbf80_0001, FCSR::NX),
+            (0xbf80_0000, FCSR::NX),
+            (0xbf80_0001, FCSR::NX),
+            (0xbf80_0000, FCSR::NX),
+            (0xbf80_0001, FCSR::NX),
+        ]),
+        // overflow
+        (0x7e37_e43c_8800_759c, [
+            (0x7f80_0000, FCSR::OF | FCSR::NX),
+            (0x7f7f_ffff, FCSR::OF | FCSR::NX),
+            (0x7f7f_ffff, FCSR::OF | FCSR::NX),
+            (0x7f80_0000, FCSR::OF | FCSR::NX),
+            (0x7f80_0000, FCSR::OF | FCSR::NX),
+        ]),
+        // negative overflow
+        (0xfe37_e43c_8800_759c, [
+            (0xff80_0000, FCSR::OF | FCSR::NX),
+            (0xff7f_ffff, FCSR::OF | FCSR::NX),
+            (0xff80_0000, FCSR::OF | FCSR::NX),
+            (0xff7f_ffff, FCSR::OF | FCSR::NX),
+            (0xff80_0000, FCSR::OF | FCSR::NX),
+        ]),
+        // just below the smallest normal
+        (0x380f_ffff_f000_0000, [
+            (0x0080_0000, FCSR::NX),
+            (0x007f_ffff, FCSR::UF | FCSR::NX),
+            (0x007f_ffff, FCSR::UF | FCSR::NX),
+            (0x0080_0000, FCSR::NX),
+            (0x0080_0000, FCSR::NX),
+        ]),
+        // half the smallest subnormal
+        (0x3690_0000_0000_0000, [
+            (0x0000_0000, FCSR::UF | FCSR::NX),
+            (0x0000_0000, FCSR::UF | FCSR::NX),
+            (0x0000_0000, FCSR::UF | FCSR::NX),
+            (0x0000_0001, FCSR::UF | FCSR::NX),
+            (0x0000_0001, FCSR::UF | FCSR::NX),
+        ]),
+        // 3/4 of the smallest subnormal
+        (0x3698_0000_0000_0000, [
+            (0x0000_0001, FCSR::UF | FCSR::NX),
+            (0x0000_0000, FCSR::UF | FCSR::NX),
+            (0x0000_0000, FCSR::UF | FCSR::NX),
+            (0x0000_0001, FCSR::UF | FCSR::NX),
+            (0x0000_0001, FCSR::UF | FCSR::NX),
+        ]),
+        // exact subnormal
+        (0x36a0_0000_0000_0000, [
+            (0x0000_0001, 0),
+            (0x0000_0001, 0),
+            (0x0000_0001, 0),
+            (0x0000_0001, 0),
+            (0x0000_0001, 0),
+        ]),
+        // 0.1
+        (0x3fb9_9999_9999_999a, [
+            (0x3dcc_cccd, FCSR::NX),
+            (0x3dcc_cccc, FCSR::NX),
+            (0x3dcc_cccc, FCSR::NX),
+            (0x3dcc_cccd, FCSR::NX),
+            (0x3dcc_cccd, FCSR::NX),
+        ]),
+    ];
+
+    #[test]
+    fn test_f32_cases() {
+        check::<f32>(F32_CASES);
+    }
+
+    #[test]
+    fn test_f64_cases() {
+        check::<f64>(F64_CASES);
+    }
+
+    #[test]
+    fn test_narrowing() {
+        for &(input, expected) in NARROWING_CASES {
+            for (rm, (bits, flags)) in MODES.into_iter().zip(expected) {
+                let mut fcsr = FCSR::new();
+                let res: f32 = convert(f64::from_bits(input), rm, &mut fcsr);
+                assert_eq!(
+                    (res.to_raw(), fcsr.fflags()),
+                    (bits, flags),
+                    "{input:#x} {rm:?}"
+                );
+            }
+        }
+
+        // NOTE: Widening is always exact, even from a subnormal
+        let mut fcsr = FCSR::new();
+        let wide: f64 = convert(f32::from_bits(1), Rup, &mut fcsr);
+        assert_eq!((wide, fcsr.fflags()), (2f64.powi(-149), 0));
+
+        let narrow: f32 = convert(f64::from_bits(0x7ff0_0000_0000_0001), Rne, &mut fcsr);
+        assert_eq!(narrow.to_bits(), f32::CANONICAL_NAN.to_bits());
+        assert_eq!(fcsr.fflags(), FCSR::NV);
+    }
+
+    #[test]
+    fn test_negated_nan() {
+        // NOTE: Negating the operands doesn't leak into the canonical NaN's sign
+        let mut fcsr = FCSR::new();
+        let nan = neg_mul_sub(f64::NAN, 1.0, 1.0, Rne, &mut fcsr);
+        assert_eq!(nan.to_bits(), f64::CANONICAL_NAN.to_bits());
+        let nan = neg_mul_add(1.0f32, 1.0, -f32::NAN, Rne, &mut fcsr);
+        assert_eq!(nan.to_bits(), f32::CANONICAL_NAN.to_bits());
+        assert_eq!(fcsr.fflags(), 0);
+    }
+
+    /// Random bits with the exponent mostly near 1.0's, so operands overlap and cancel, and
+    /// otherwise anywhere, subnormals, infinities and NaNs included
+    fn operand(rng: &mut impl Rng, exp_bits: u32, mant_bits: u32) -> u64 {
+        let max_exp = (1 << exp_bits) - 1;
+        let exp = match rng.gen_range(0..4) {
+            0 => rng.gen_range(0..=max_exp),
+            1 => 0,
+            _ => (max_exp >> 1) + rng.gen_range(0..16) - 8,
+        };
+
+        let sign = rng.gen_range(0..2u64);
+        sign << (exp_bits + mant_bits) | exp << mant_bits | rng.gen_range(0..1 << mant_bits)
+    }
+
+    #[test]
+    fn test_against_host() {
+        // NOTE: Not the riscv-arch-test vectors, which aren't vendored here. The host's FPU is
+        // an IEEE 754 implementation of its own for round to nearest, so random operands are
+        // checked against it, NaNs by being canonical since the host keeps payloads
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0x5eed);
+        let mut fcsr = FCSR::new();
+
+        let check64 = |ours: f64, host: f64| match host.is_nan() {
+            true => ours.to_bits() == f64::CANONICAL_NAN.to_bits(),
+            false => ours.to_bits() == host.to_bits(),
+        };
+        for _ in 0..20_000 {
+            let [a, b, c] = [(); 3].map(|_| f64::from_bits(operand(&mut rng, 11, 52)));
+            let i = rng.gen::<i64>() >> rng.gen_range(0..64);
+
+            #[rustfmt::skip]
+            let results = [
+                ("add", add(a, b, Rne, &mut fcsr), a + b),
+                ("sub", sub(a, b, Rne, &mut fcsr), a - b),
+                ("mul", mul(a, b, Rne, &mut fcsr), a * b),
+                ("div", div(a, b, Rne, &mut fcsr), a / b),
+                ("sqrt", sqrt(a, Rne, &mut fcsr), a.sqrt()),
+                ("mul_add", mul_add(a, b, c, Rne, &mut fcsr), a.mul_add(b, c)),
+                ("from_int", from_int(i.into(), Rne, &mut fcsr), i as f64),
+            ];
+            for (op, ours, host) in results {
+                assert!(
+                    check64(ours, host),
+                    "{op} {a:e} {b:e} {c:e} {i}: {ours:e} != {host:e}"
+                );
+            }
+        }
+
+        let check32 = |ours: f32, host: f32| match host.is_nan() {
+            true => ours.to_bits() == f32::CANONICAL_NAN.to_bits(),
+            false => ours.to_bits() == host.to_bits(),
+        };
+        for _ in 0..20_000 {
+            let [a, b, c] = [(); 3].map(|_| f32::from_bits(operand(&mut rng, 8, 23) as u32));
+            let wide = f64::from_bits(operand(&mut rng, 11, 52));
+            let i = rng.gen::<i64>() >> rng.gen_range(0..64);
+
+            #[rustfmt::skip]
+            let results = [
+                ("add", add(a, b, Rne, &mut fcsr), a + b),
+                ("sub", sub(a, b, Rne, &mut fcsr), a - b),
+                ("mul", mul(a, b, Rne, &mut fcsr), a * b),
+                ("div", div(a, b, Rne, &mut fcsr), a / b),
+                ("sqrt", sqrt(a, Rne, &mut fcsr), a.sqrt()),
+                ("mul_add", mul_add(a, b, c, Rne, &mut fcsr), a.mul_add(b, c)),
+                ("from_int", from_int(i.into(), Rne, &mut fcsr), i as f32),
+                ("convert", convert(wide, Rne, &mut fcsr), wide as f32),
+            ];
+            for (op, ours, host) in results {
+                assert!(
+                    check32(ours, host),
+                    "{op} {a:e} {b:e} {c:e} {wide:e} {i}: {ours:e} != {host:e}"
+                );
+            }
+        }
+    }
+}
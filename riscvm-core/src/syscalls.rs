@@ -1,13 +1,28 @@
+use std::collections::BTreeMap;
+use std::fs::Metadata;
 use std::io;
 use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
 use std::io::Write;
+use std::os::fd::AsFd;
+use std::os::unix::fs::DirBuilderExt;
+use std::os::unix::fs::FileExt;
+use std::os::unix::fs::MetadataExt;
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::Path;
+use std::path::PathBuf;
 
+use crate::cpu::RV64GCRegAbiName;
 use crate::cpu::RV64GCRegAbiName::*;
 use crate::cpu::RV64GC;
-use crate::ram::MemoryRegion;
+use crate::exception::Exception;
+use crate::fd::{FdTable, OpenFile};
+use crate::isa::Isa;
+use crate::ram::{Access, MemoryError, MemoryRegion, Ram};
+use goblin::elf::program_header::{PF_R, PF_W, PF_X};
 use rand::Rng;
 use tracing::debug;
-use tracing::error;
 use tracing::info;
 use tracing::span;
 use tracing::trace;
@@ -15,6 +30,7 @@ use tracing::warn;
 use tracing::Level;
 
 #[allow(unused, clippy::upper_case_acronyms)]
+#[derive(Debug)]
 enum Errno {
     EPERM = 1,
     ENOENT = 2,
@@ -26,6 +42,21 @@ enum Errno {
     ENOMEM = 12,
     EACCCES = 13,
     EFAULT = 14,
+    EBUSY = 16,
+    EEXIST = 17,
+    EXDEV = 18,
+    ENOTDIR = 20,
+    EISDIR = 21,
+    EINVAL = 22,
+    EMFILE = 24,
+    EFBIG = 27,
+    ENOSPC = 28,
+    ESPIPE = 29,
+    EROFS = 30,
+    ENAMETOOLONG = 36,
+    ENOSYS = 38,
+    ENOTEMPTY = 39,
+    ELOOP = 40,
 }
 
 impl From<Errno> for i64 {
@@ -41,6 +72,663 @@ impl Errno {
     }
 }
 
+impl Errno {
+    /// The host's errno values are the same as the guest's, both are Linux
+    fn from_raw(errno: i32) -> Option<Errno> {
+        Some(match errno {
+            1 => Errno::EPERM,
+            2 => Errno::ENOENT,
+            3 => Errno::ESRCH,
+            4 => Errno::EINTR,
+            5 => Errno::EIO,
+            9 => Errno::EBADF,
+            11 => Errno::EAGAIN,
+            12 => Errno::ENOMEM,
+            13 => Errno::EACCCES,
+            14 => Errno::EFAULT,
+            16 => Errno::EBUSY,
+            17 => Errno::EEXIST,
+            18 => Errno::EXDEV,
+            20 => Errno::ENOTDIR,
+            21 => Errno::EISDIR,
+            22 => Errno::EINVAL,
+            24 => Errno::EMFILE,
+            27 => Errno::EFBIG,
+            28 => Errno::ENOSPC,
+            29 => Errno::ESPIPE,
+            30 => Errno::EROFS,
+            36 => Errno::ENAMETOOLONG,
+            38 => Errno::ENOSYS,
+            39 => Errno::ENOTEMPTY,
+            40 => Errno::ELOOP,
+            _ => return None,
+        })
+    }
+}
+
+impl From<io::Error> for Errno {
+    fn from(err: io::Error) -> Self {
+        if let Some(errno) = err.raw_os_error().and_then(Errno::from_raw) {
+            return errno;
+        }
+
+        match err.kind() {
+            io::ErrorKind::NotFound => Errno::ENOENT,
+            io::ErrorKind::PermissionDenied => Errno::EACCCES,
+            io::ErrorKind::AlreadyExists => Errno::EEXIST,
+            io::ErrorKind::NotADirectory => Errno::ENOTDIR,
+            io::ErrorKind::IsADirectory => Errno::EISDIR,
+            io::ErrorKind::InvalidInput => Errno::EINVAL,
+            io::ErrorKind::Interrupted => Errno::EINTR,
+            _ => Errno::EIO,
+        }
+    }
+}
+
+const AT_FDCWD: i64 = -100;
+const AT_SYMLINK_NOFOLLOW: u64 = 0x100;
+const AT_EMPTY_PATH: u64 = 0x1000;
+const AT_REMOVEDIR: u64 = 0x200;
+
+const W_OK: u64 = 2;
+
+const O_ACCMODE: u64 = 0o3;
+const O_WRONLY: u64 = 0o1;
+const O_RDWR: u64 = 0o2;
+const O_CREAT: u64 = 0o100;
+const O_EXCL: u64 = 0o200;
+const O_TRUNC: u64 = 0o1000;
+const O_APPEND: u64 = 0o2000;
+const O_DIRECTORY: u64 = 0o200000;
+
+const RENAME_NOREPLACE: u64 = 1;
+
+const MAP_SHARED: u64 = 0x01;
+const MAP_PRIVATE: u64 = 0x02;
+const MAP_FIXED: u64 = 0x10;
+const MAP_ANONYMOUS: u64 = 0x20;
+const MAP_FIXED_NOREPLACE: u64 = 0x100000;
+
+const MREMAP_MAYMOVE: u64 = 1;
+const MREMAP_FIXED: u64 = 2;
+
+/// Like Linux's default `vm.mmap_min_addr`, hints below it are ignored
+const MMAP_MIN_ADDR: u64 = 0x10000;
+/// Lengths past this can't be mapped anywhere, and would overflow when rounded up
+const MMAP_MAX_LEN: u64 = 1 << 47;
+
+const PROT_READ: u64 = 0x1;
+const PROT_WRITE: u64 = 0x2;
+const PROT_EXEC: u64 = 0x4;
+
+const PAGE_SIZE: u64 = 4096;
+
+/// How much reads and writes copy between guest memory and the host at a time
+const IO_CHUNK: u64 = 64 * 1024;
+/// Linux caps a single read or write at this many bytes
+const MAX_RW_COUNT: u64 = 0x7fff_f000;
+/// Most iovecs `writev` takes, `IOV_MAX` in the guest's libc
+const UIO_MAXIOV: u64 = 1024;
+
+const RISCV_HWPROBE_KEY_MVENDORID: i64 = 0;
+const RISCV_HWPROBE_KEY_MARCHID: i64 = 1;
+const RISCV_HWPROBE_KEY_MIMPID: i64 = 2;
+const RISCV_HWPROBE_KEY_BASE_BEHAVIOR: i64 = 3;
+const RISCV_HWPROBE_KEY_IMA_EXT_0: i64 = 4;
+const RISCV_HWPROBE_KEY_CPUPERF_0: i64 = 5;
+
+const RISCV_HWPROBE_BASE_BEHAVIOR_IMA: u64 = 1 << 0;
+const RISCV_HWPROBE_IMA_FD: u64 = 1 << 0;
+const RISCV_HWPROBE_IMA_C: u64 = 1 << 1;
+const RISCV_HWPROBE_IMA_V: u64 = 1 << 2;
+const RISCV_HWPROBE_EXT_ZBA: u64 = 1 << 3;
+const RISCV_HWPROBE_EXT_ZBB: u64 = 1 << 4;
+const RISCV_HWPROBE_EXT_ZBS: u64 = 1 << 5;
+const RISCV_HWPROBE_EXT_ZBC: u64 = 1 << 7;
+const RISCV_HWPROBE_MISALIGNED_UNKNOWN: u64 = 0;
+
+/// Names of the generic (asm-generic/unistd.h) syscalls riscv64 uses, indexed by number.
+/// 244 to 257 are reserved for the architecture
+#[rustfmt::skip]
+const SYSCALL_NAMES: [&str; 295] = [
+    "io_setup", "io_destroy", "io_submit", "io_cancel", "io_getevents", "setxattr", "lsetxattr",
+    "fsetxattr", "getxattr", "lgetxattr", "fgetxattr", "listxattr", "llistxattr", "flistxattr",
+    "removexattr", "lremovexattr", "fremovexattr", "getcwd", "lookup_dcookie", "eventfd2",
+    "epoll_create1", "epoll_ctl", "epoll_pwait", "dup", "dup3", "fcntl", "inotify_init1",
+    "inotify_add_watch", "inotify_rm_watch", "ioctl", "ioprio_set", "ioprio_get", "flock",
+    "mknodat", "mkdirat", "unlinkat", "symlinkat", "linkat", "renameat", "umount2", "mount",
+    "pivot_root", "nfsservctl", "statfs", "fstatfs", "truncate", "ftruncate", "fallocate",
+    "faccessat", "chdir", "fchdir", "chroot", "fchmod", "fchmodat", "fchownat", "fchown",
+    "openat", "close", "vhangup", "pipe2", "quotactl", "getdents64", "lseek", "read", "write",
+    "readv", "writev", "pread64", "pwrite64", "preadv", "pwritev", "sendfile", "pselect6",
+    "ppoll", "signalfd4", "vmsplice", "splice", "tee", "readlinkat", "newfstatat", "fstat",
+    "sync", "fsync", "fdatasync", "sync_file_range", "timerfd_create", "timerfd_settime",
+    "timerfd_gettime", "utimensat", "acct", "capget", "capset", "personality", "exit",
+    "exit_group", "waitid", "set_tid_address", "unshare", "futex", "set_robust_list",
+    "get_robust_list", "nanosleep", "getitimer", "setitimer", "kexec_load", "init_module",
+    "delete_module", "timer_create", "timer_gettime", "timer_getoverrun", "timer_settime",
+    "timer_delete", "clock_settime", "clock_gettime", "clock_getres", "clock_nanosleep",
+    "syslog", "ptrace", "sched_setparam", "sched_setscheduler", "sched_getscheduler",
+    "sched_getparam", "sched_setaffinity", "sched_getaffinity", "sched_yield",
+    "sched_get_priority_max", "sched_get_priority_min", "sched_rr_get_interval",
+    "restart_syscall", "kill", "tkill", "tgkill", "sigaltstack", "rt_sigsuspend",
+    "rt_sigaction", "rt_sigprocmask", "rt_sigpending", "rt_sigtimedwait", "rt_sigqueueinfo",
+    "rt_sigreturn", "setpriority", "getpriority", "reboot", "setregid", "setgid", "setreuid",
+    "setuid", "setresuid", "getresuid", "setresgid", "getresgid", "setfsuid", "setfsgid",
+    "times", "setpgid", "getpgid", "getsid", "setsid", "getgroups", "setgroups", "uname",
+    "sethostname", "setdomainname", "getrlimit", "setrlimit", "getrusage", "umask", "prctl",
+    "getcpu", "gettimeofday", "settimeofday", "adjtimex", "getpid", "getppid", "getuid",
+    "geteuid", "getgid", "getegid", "gettid", "sysinfo", "mq_open", "mq_unlink", "mq_timedsend",
+    "mq_timedreceive", "mq_notify", "mq_getsetattr", "msgget", "msgctl", "msgrcv", "msgsnd",
+    "semget", "semctl", "semtimedop", "semop", "shmget", "shmctl", "shmat", "shmdt", "socket",
+    "socketpair", "bind", "listen", "accept", "connect", "getsockname", "getpeername", "sendto",
+    "recvfrom", "setsockopt", "getsockopt", "shutdown", "sendmsg", "recvmsg", "readahead",
+    "brk", "munmap", "mremap", "add_key", "request_key", "keyctl", "clone", "execve", "mmap",
+    "fadvise64", "swapon", "swapoff", "mprotect", "msync", "mlock", "munlock", "mlockall",
+    "munlockall", "mincore", "madvise", "remap_file_pages", "mbind", "get_mempolicy",
+    "set_mempolicy", "migrate_pages", "move_pages", "rt_tgsigqueueinfo", "perf_event_open",
+    "accept4", "recvmmsg", "", "", "", "", "", "", "", "", "", "", "", "", "", "",
+    "riscv_hwprobe", "riscv_flush_icache", "wait4", "prlimit64", "fanotify_init",
+    "fanotify_mark", "name_to_handle_at", "open_by_handle_at", "clock_adjtime", "syncfs",
+    "setns", "sendmmsg", "process_vm_readv", "process_vm_writev", "kcmp", "finit_module",
+    "sched_setattr", "sched_getattr", "renameat2", "seccomp", "getrandom", "memfd_create",
+    "bpf", "execveat", "userfaultfd", "membarrier", "mlock2", "copy_file_range", "preadv2",
+    "pwritev2", "pkey_mprotect", "pkey_alloc", "pkey_free", "statx", "io_pgetevents", "rseq",
+    "kexec_file_load",
+];
+
+/// Syscalls from 424 on, numbered the same on every architecture
+#[rustfmt::skip]
+const NEWER_SYSCALL_NAMES: [&str; 39] = [
+    "pidfd_send_signal", "io_uring_setup", "io_uring_enter", "io_uring_register", "open_tree",
+    "move_mount", "fsopen", "fsconfig", "fsmount", "fspick", "pidfd_open", "clone3",
+    "close_range", "openat2", "pidfd_getfd", "faccessat2", "process_madvise", "epoll_pwait2",
+    "mount_setattr", "quotactl_fd", "landlock_create_ruleset", "landlock_add_rule",
+    "landlock_restrict_self", "memfd_secret", "process_mrelease", "futex_waitv",
+    "set_mempolicy_home_node", "cachestat", "fchmodat2", "map_shadow_stack", "futex_wake",
+    "futex_wait", "futex_requeue", "statmount", "listmount", "lsm_get_self_attr",
+    "lsm_set_self_attr", "lsm_list_modules", "mseal",
+];
+
+/// The name of syscall `number`, for logs and errors
+pub fn syscall_name(number: u64) -> Option<&'static str> {
+    let name = match number {
+        0..=294 => SYSCALL_NAMES[number as usize],
+        424..=462 => NEWER_SYSCALL_NAMES[(number - 424) as usize],
+        _ => return None,
+    };
+
+    (!name.is_empty()).then_some(name)
+}
+
+/// Whether a [`SyscallHandler`] dealt with a syscall
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dispatch {
+    Handled,
+    /// Pass the syscall on to the next handler
+    Fallthrough,
+}
+
+/// Something that can service the guest's `ecall`s. The number is in a7 and the arguments in
+/// a0 to a5, the result goes in a0
+///
+/// Closures taking `(&mut RV64GC, u64)` are handlers too. Handlers are `Send` so the cpu
+/// can move to another thread.
+pub trait SyscallHandler: Send {
+    fn handle(&mut self, cpu: &mut RV64GC, number: u64) -> Result<Dispatch, Exception>;
+}
+
+impl<F> SyscallHandler for F
+where
+    F: FnMut(&mut RV64GC, u64) -> Result<Dispatch, Exception> + Send,
+{
+    fn handle(&mut self, cpu: &mut RV64GC, number: u64) -> Result<Dispatch, Exception> {
+        self(cpu, number)
+    }
+}
+
+/// Per syscall number handlers that run before [`LinuxSyscalls`], which handles anything
+/// they pass on
+#[derive(Default)]
+pub struct SyscallOverrides {
+    handlers: BTreeMap<u64, Box<dyn SyscallHandler>>,
+}
+
+impl SyscallOverrides {
+    /// Routes syscall `number` to `handler`, returning the handler it replaces
+    pub fn register(
+        &mut self,
+        number: u64,
+        handler: impl SyscallHandler + 'static,
+    ) -> Option<Box<dyn SyscallHandler>> {
+        self.handlers.insert(number, Box::new(handler))
+    }
+
+    pub fn unregister(&mut self, number: u64) -> Option<Box<dyn SyscallHandler>> {
+        self.handlers.remove(&number)
+    }
+
+    /// Takes the handler out while it runs, it needs the cpu this is a part of
+    pub(crate) fn take(&mut self, number: u64) -> Option<Box<dyn SyscallHandler>> {
+        self.handlers.remove(&number)
+    }
+
+    /// Puts a handler back after [`SyscallOverrides::take`], unless it registered a
+    /// replacement for itself while it ran
+    pub(crate) fn restore(&mut self, number: u64, handler: Box<dyn SyscallHandler>) {
+        self.handlers.entry(number).or_insert(handler);
+    }
+}
+
+impl std::fmt::Debug for SyscallOverrides {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self.handlers.keys()).finish()
+    }
+}
+
+/// The syscalls a Linux guest expects, plus riscvm's own debugging calls from 1000 on
+#[derive(Debug, Default, Clone, Copy)]
+pub struct LinuxSyscalls;
+
+impl SyscallHandler for LinuxSyscalls {
+    fn handle(&mut self, cpu: &mut RV64GC, number: u64) -> Result<Dispatch, Exception> {
+        match number {
+            34 => mkdirat(cpu),
+
+            35 => unlinkat(cpu),
+
+            48 => faccessat(cpu),
+
+            56 => openat(cpu),
+
+            57 => close(cpu),
+
+            62 => lseek(cpu),
+
+            63 => read(cpu),
+
+            64 => write(cpu),
+
+            66 => writev(cpu),
+
+            67 => pread64(cpu),
+
+            68 => pwrite64(cpu),
+
+            78 => readlink(cpu),
+
+            79 => newfstatat(cpu),
+
+            80 => fstat(cpu),
+
+            // NOTE: exit and exit_group, there is only ever one thread
+            93 | 94 => {
+                let error_code = cpu.registers[A0] as i32;
+                info!("Program exited with code: {error_code}");
+                cpu.exit_code = Some(error_code);
+                cpu.should_quit = true;
+            }
+
+            // NOTE: set_tid
+            96 => {
+                // PID
+                cpu.registers[A0] = 0;
+            }
+
+            98 => futex(cpu),
+
+            // NOTE: set_robust_list
+            99 => {
+                cpu.registers[A0] = 0;
+            }
+
+            113 => clock_gettime(cpu),
+
+            131 => tgkill(cpu),
+
+            134 => sig_action(cpu),
+
+            135 => rt_sigprocmask(cpu),
+
+            172 => getpid(cpu),
+            178 => gettid(cpu),
+
+            214 => brk(cpu),
+
+            215 => munmap(cpu),
+
+            216 => mremap(cpu),
+
+            222 => mmap(cpu),
+
+            226 => mprotect(cpu),
+
+            258 => riscv_hwprobe(cpu),
+
+            261 => prlimit64(cpu),
+
+            276 => renameat2(cpu),
+
+            278 => getrandom(cpu),
+
+            // NOTE: Print i64
+            1000 => {
+                let ptr = cpu.registers[A0] as i64;
+                info!("i64: {}", ptr);
+            }
+
+            // NOTE: Dump registers
+            1001 => {
+                info!("{}", cpu.registers);
+            }
+
+            // NOTE: Print i64 from ptr
+            1100 => {
+                let ptr = cpu.registers[A0];
+                let val = cpu.ram.read_doubleword(ptr).map_err(Exception::load)?;
+
+                info!("i64: {}", val as i64);
+            }
+            //
+            // NOTE: Print i32 from ptr
+            1101 => {
+                let ptr = cpu.registers[A0];
+                let val = cpu.ram.read_word(ptr).map_err(Exception::load)?;
+
+                info!("i64: {}", val as i32);
+            }
+
+            // NOTE: Print float from ptr
+            1110 => {
+                let ptr = cpu.registers[A0];
+                let value = f32::from_bits(cpu.ram.read_word(ptr).map_err(Exception::load)?);
+
+                info!("float: {}", value);
+            }
+
+            _ => return unknown_syscall(cpu, number).map(|()| Dispatch::Handled),
+        }
+
+        Ok(Dispatch::Handled)
+    }
+}
+
+/// What to do when the guest makes a syscall we don't implement
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum UnknownSyscallPolicy {
+    /// Panic, for finding out what a program needs
+    Panic,
+    /// Return `-ENOSYS` and log a warning the first time each number is seen
+    #[default]
+    Enosys,
+    /// Stop with [`Trap::UnsupportedSyscall`] and let the embedder handle it
+    ///
+    /// [`Trap::UnsupportedSyscall`]: crate::exception::Trap::UnsupportedSyscall
+    Trap,
+}
+
+/// Called for syscall numbers [`LinuxSyscalls`] doesn't know
+pub fn unknown_syscall(cpu: &mut RV64GC, number: u64) -> Result<(), Exception> {
+    let name = syscall_name(number).unwrap_or("unknown");
+
+    match cpu.unknown_syscall_policy {
+        UnknownSyscallPolicy::Panic => panic!("unimplemented syscall {number} ({name})"),
+        UnknownSyscallPolicy::Enosys => {
+            if cpu.warned_syscalls.insert(number) {
+                warn!("unimplemented syscall {number} ({name}), returning ENOSYS");
+            }
+
+            cpu.registers[A0] = Errno::ENOSYS.into_err();
+            Ok(())
+        }
+        UnknownSyscallPolicy::Trap => Err(Exception::UnsupportedSyscall(number)),
+    }
+}
+
+/// Reads a NUL terminated string out of guest memory
+fn read_c_string(cpu: &RV64GC, ptr: u64) -> Result<String, Errno> {
+    let mut bytes = vec![];
+    let mut addr = ptr;
+    loop {
+        let b = cpu.ram.read_byte(addr).map_err(|_| Errno::EFAULT)?;
+        if b == 0 {
+            break;
+        }
+
+        bytes.push(b);
+        addr = addr.wrapping_add(1);
+    }
+
+    String::from_utf8(bytes).map_err(|_| Errno::EINVAL)
+}
+
+/// The guest path `path` names when looked up relative to `dirfd`
+fn guest_path(cpu: &RV64GC, dirfd: i64, path: &str) -> Result<PathBuf, Errno> {
+    if path.is_empty() {
+        return Err(Errno::ENOENT);
+    }
+
+    if path.starts_with('/') {
+        return Ok(PathBuf::from(path));
+    }
+
+    if dirfd == AT_FDCWD {
+        // NOTE: The guest's working directory is the root when it has one, the host's otherwise
+        return Ok(match cpu.launch_config.root {
+            Some(_) => Path::new("/").join(path),
+            None => PathBuf::from(path),
+        });
+    }
+
+    let dir = cpu.fds.get(dirfd as i32).ok_or(Errno::EBADF)?;
+    if !dir.file.metadata()?.is_dir() {
+        return Err(Errno::ENOTDIR);
+    }
+
+    Ok(dir.path.join(path))
+}
+
+/// Looks up a path relative to `dirfd` on the host
+fn resolve_at(cpu: &RV64GC, dirfd: i64, path: &str) -> Result<PathBuf, Errno> {
+    let guest = guest_path(cpu, dirfd, path)?;
+    cpu.launch_config.host_path(&guest).ok_or_else(|| {
+        warn!("{} leads outside of the root", guest.display());
+        Errno::EACCCES
+    })
+}
+
+/// Reads the path at `cpu.registers[path_reg]` and resolves it against `cpu.registers[dirfd_reg]`
+fn path_arg(
+    cpu: &RV64GC,
+    dirfd_reg: RV64GCRegAbiName,
+    path_reg: RV64GCRegAbiName,
+) -> Result<PathBuf, Errno> {
+    let path = read_c_string(cpu, cpu.registers[path_reg])?;
+    debug!("path: {path}");
+    resolve_at(cpu, cpu.registers[dirfd_reg] as i64, &path)
+}
+
+fn check_writable(cpu: &RV64GC) -> Result<(), Errno> {
+    if cpu.launch_config.read_only {
+        return Err(Errno::EROFS);
+    }
+
+    Ok(())
+}
+
+/// Reads up to `count` bytes into guest memory at `buf` with `read`, which gets a chunk to
+/// fill and how much was read so far. Goes a bounded chunk at a time, so the host never
+/// needs a buffer as big as `count`, and stops at the first short read like a single
+/// `read` would. Bytes that made it to the guest before a fault or error are returned
+fn read_to_guest(
+    ram: &mut Ram,
+    buf: u64,
+    count: u64,
+    mut read: impl FnMut(&mut [u8], u64) -> io::Result<usize>,
+) -> Result<u64, Errno> {
+    let count = count.min(MAX_RW_COUNT);
+    let mut chunk = vec![0u8; count.min(IO_CHUNK) as usize];
+    let mut done = 0;
+
+    while done < count {
+        let addr = buf.wrapping_add(done);
+        let mut want = (count - done).min(IO_CHUNK);
+
+        // NOTE: Checked first, so nothing is read that can't be stored. Like Linux, the
+        // read stops short at the first page that isn't writable
+        if let Err(e) = ram.check_access(addr, want, Access::Write) {
+            want = e.addr().saturating_sub(addr);
+            if want == 0 {
+                return if done == 0 {
+                    Err(Errno::EFAULT)
+                } else {
+                    Ok(done)
+                };
+            }
+        }
+
+        let n = match read(&mut chunk[..want as usize], done) {
+            Ok(n) => n,
+            Err(e) if done == 0 => return Err(e.into()),
+            Err(_) => break,
+        };
+
+        ram.write_bytes(addr, &chunk[..n])
+            .map_err(|_| Errno::EFAULT)?;
+        done += n as u64;
+        if (n as u64) < want {
+            break;
+        }
+    }
+
+    Ok(done)
+}
+
+/// Writes up to `count` bytes of guest memory at `buf` with `write`, which gets a chunk and
+/// how much was written so far. The counterpart of [`read_to_guest`], a bounded chunk at a
+/// time, stopping at the first short write or the first page that isn't readable
+fn write_from_guest(
+    ram: &Ram,
+    buf: u64,
+    count: u64,
+    mut write: impl FnMut(&[u8], u64) -> Result<usize, Errno>,
+) -> Result<u64, Errno> {
+    // NOTE: Like Linux, a range that wraps is a fault before the count is clamped
+    if buf.checked_add(count).is_none() {
+        return Err(Errno::EFAULT);
+    }
+
+    let count = count.min(MAX_RW_COUNT);
+    let mut chunk = vec![0u8; count.min(IO_CHUNK) as usize];
+    let mut done = 0;
+
+    while done < count {
+        let addr = buf + done;
+        let mut want = (count - done).min(IO_CHUNK);
+
+        if let Err(e) = ram.check_access(addr, want, Access::Read) {
+            want = e.addr().saturating_sub(addr);
+            if want == 0 {
+                return if done == 0 {
+                    Err(Errno::EFAULT)
+                } else {
+                    Ok(done)
+                };
+            }
+        }
+
+        let chunk = &mut chunk[..want as usize];
+        ram.read_bytes(addr, chunk).map_err(|_| Errno::EFAULT)?;
+
+        let n = match write(chunk, done) {
+            Ok(n) => n,
+            Err(e) if done == 0 => return Err(e),
+            Err(_) => break,
+        };
+
+        done += n as u64;
+        if (n as u64) < want {
+            break;
+        }
+    }
+
+    Ok(done)
+}
+
+/// Writes `bytes` to the host's stdout or stderr, or a file the guest opened
+fn write_fd(fds: &mut FdTable, fd: i64, bytes: &[u8]) -> Result<usize, Errno> {
+    match fd {
+        1 => {
+            let mut stdout = std::io::stdout();
+            stdout.write_all(bytes)?;
+            let _ = stdout.flush();
+            Ok(bytes.len())
+        }
+        2 => {
+            eprint!("\x1b[31m{}\x1b[0m", String::from_utf8_lossy(bytes));
+            Ok(bytes.len())
+        }
+        _ => {
+            let open = fds.get_mut(fd as i32).ok_or(Errno::EBADF)?;
+            Ok(open.file.write(bytes)?)
+        }
+    }
+}
+
+/// `fstat` for a file the guest opened, or the host's stdio. Whether that's a terminal, a
+/// pipe or a file decides how libc buffers it
+fn fd_metadata(cpu: &RV64GC, fd: i64) -> Result<Metadata, Errno> {
+    let stdio = match fd {
+        0 => std::io::stdin().as_fd().try_clone_to_owned(),
+        1 => std::io::stdout().as_fd().try_clone_to_owned(),
+        2 => std::io::stderr().as_fd().try_clone_to_owned(),
+        _ => {
+            let open = cpu.fds.get(fd as i32).ok_or(Errno::EBADF)?;
+            return Ok(open.file.metadata()?);
+        }
+    };
+
+    Ok(std::fs::File::from(stdio?).metadata()?)
+}
+
+/// Writes `meta` out as the generic (riscv64) `struct stat`
+fn write_stat(cpu: &mut RV64GC, addr: u64, meta: &Metadata) -> Result<(), Errno> {
+    let fields: [(u64, u64, u64); 15] = [
+        (0, meta.dev(), 8),
+        (8, meta.ino(), 8),
+        (16, meta.mode().into(), 4),
+        (20, meta.nlink(), 4),
+        (24, meta.uid().into(), 4),
+        (28, meta.gid().into(), 4),
+        (32, meta.rdev(), 8),
+        (48, meta.size(), 8),
+        (56, meta.blksize(), 4),
+        (64, meta.blocks(), 8),
+        (72, meta.atime() as u64, 8),
+        (80, meta.atime_nsec() as u64, 8),
+        (88, meta.mtime() as u64, 8),
+        (96, meta.mtime_nsec() as u64, 8),
+        (104, meta.ctime() as u64, 8),
+    ];
+
+    // NOTE: struct stat is 128 bytes, clear the padding and ctime_nsec first
+    for offset in (0..128).step_by(8) {
+        cpu.ram
+            .write_doubleword(addr.wrapping_add(offset), 0)
+            .map_err(|_| Errno::EFAULT)?;
+    }
+
+    for (offset, value, len) in fields {
+        cpu.ram
+            .write_nbytes(addr.wrapping_add(offset), value, len)
+            .map_err(|_| Errno::EFAULT)?;
+    }
+
+    cpu.ram
+        .write_doubleword(addr.wrapping_add(112), meta.ctime_nsec() as u64)
+        .map_err(|_| Errno::EFAULT)
+}
+
 #[derive(Debug)]
 enum SeekMode {
     Set = 0,
@@ -48,13 +736,15 @@ enum SeekMode {
     End = 2,
 }
 
-impl From<i64> for SeekMode {
-    fn from(value: i64) -> Self {
+impl TryFrom<i64> for SeekMode {
+    type Error = Errno;
+
+    fn try_from(value: i64) -> Result<Self, Self::Error> {
         match value {
-            0 => SeekMode::Set,
-            1 => SeekMode::Cur,
-            2 => SeekMode::End,
-            _ => panic!("invalid seek mode!"),
+            0 => Ok(SeekMode::Set),
+            1 => Ok(SeekMode::Cur),
+            2 => Ok(SeekMode::End),
+            _ => Err(Errno::EINVAL),
         }
     }
 }
@@ -78,41 +768,178 @@ pub fn read(cpu: &mut RV64GC) {
     debug!("buf: 0x{buf:08x}");
     debug!("count: {count}");
 
-    if count == 0 {
-        cpu.registers[A0] = 0;
+    let res = if fd == 0 {
+        read_to_guest(&mut cpu.ram, buf, count, |chunk, _| {
+            std::io::stdin().read(chunk)
+        })
+    } else if let Some(open) = cpu.fds.get_mut(fd as i32) {
+        read_to_guest(&mut cpu.ram, buf, count, |chunk, _| open.file.read(chunk))
+    } else {
+        warn!("read on unknown fd {fd}");
+        Err(Errno::EBADF)
+    };
+
+    cpu.registers[A0] = match res {
+        Ok(n) => n,
+        Err(e) => e.into_err(),
+    };
+}
+
+// 67
+pub fn pread64(cpu: &mut RV64GC) {
+    let span = span!(Level::TRACE, "syscall_pread64");
+    let _guard = span.enter();
+
+    let fd = cpu.registers[A0] as i64;
+    let buf = cpu.registers[A1];
+    let count = cpu.registers[A2];
+    let offset = cpu.registers[A3];
+
+    debug!("fd: {fd}, count: {count}, offset: {offset}");
+
+    let Some(open) = cpu.fds.get(fd as i32) else {
+        cpu.registers[A0] = if (0..=2).contains(&fd) {
+            Errno::ESPIPE.into_err()
+        } else {
+            Errno::EBADF.into_err()
+        };
         return;
-    }
+    };
 
-    if fd != 0 {
-        panic!("riscvm only supports the read syscall with the file descriptor 0 (stdin)");
+    if (offset as i64) < 0 {
+        cpu.registers[A0] = Errno::EINVAL.into_err();
+        return;
     }
 
-    let mut buffer = vec![0u8; count as usize];
+    let res = read_to_guest(&mut cpu.ram, buf, count, |chunk, done| {
+        open.file.read_at(chunk, offset + done)
+    });
 
-    if fd == 0 {
-        let bytes_read = match std::io::stdin().read(&mut buffer) {
-            Ok(n) => n,
-            Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {
-                // Read was interrupted
-                cpu.registers[A0] = Errno::EINTR.into_err();
-                return;
-            }
-            Err(_) => {
-                // Other I/O error
-                cpu.registers[A0] = Errno::EIO.into_err();
-                return;
-            }
-        };
+    cpu.registers[A0] = match res {
+        Ok(n) => n,
+        Err(e) => e.into_err(),
+    };
+}
 
-        for (idx, b) in buffer.into_iter().enumerate() {
-            if cpu.ram.write_byte(buf + idx as u64, b).is_err() {
-                cpu.registers[A0] = Errno::EFAULT.into_err();
-                return;
-            }
+// 48
+pub fn faccessat(cpu: &mut RV64GC) {
+    let mode = cpu.registers[A2];
+
+    // NOTE: Only existence is checked, the guest runs as whoever runs riscvm
+    let res = path_arg(cpu, A0, A1).and_then(|p| {
+        std::fs::symlink_metadata(p)?;
+        if mode & W_OK != 0 {
+            check_writable(cpu)?;
+        }
+
+        Ok(())
+    });
+
+    cpu.registers[A0] = match res {
+        Ok(()) => 0,
+        Err(e) => e.into_err(),
+    };
+}
+
+// 56
+pub fn openat(cpu: &mut RV64GC) {
+    let span = span!(Level::TRACE, "syscall_openat");
+    let _guard = span.enter();
+
+    let dirfd = cpu.registers[A0] as i64;
+    let flags = cpu.registers[A2];
+    let mode = cpu.registers[A3] as u32;
+
+    let path = match read_c_string(cpu, cpu.registers[A1]) {
+        Ok(path) => path,
+        Err(e) => {
+            cpu.registers[A0] = e.into_err();
+            return;
         }
+    };
 
-        cpu.registers[A0] = bytes_read as u64;
+    let (guest_path, host_path) = match guest_path(cpu, dirfd, &path)
+        .and_then(|guest| Ok((guest, resolve_at(cpu, dirfd, &path)?)))
+    {
+        Ok(paths) => paths,
+        Err(e) => {
+            cpu.registers[A0] = e.into_err();
+            return;
+        }
+    };
+
+    debug!("openat: {path} -> {}", host_path.display());
+
+    let writes = flags & O_ACCMODE != 0 || flags & (O_CREAT | O_TRUNC) != 0;
+    if writes && cpu.launch_config.read_only {
+        cpu.registers[A0] = Errno::EROFS.into_err();
+        return;
     }
+
+    let file = open_options(flags, mode)
+        .open(&host_path)
+        .map_err(Errno::from)
+        .and_then(|file| {
+            if flags & O_DIRECTORY != 0 && !file.metadata()?.is_dir() {
+                return Err(Errno::ENOTDIR);
+            }
+
+            Ok(file)
+        });
+
+    cpu.registers[A0] = match file {
+        Ok(file) => cpu.fds.insert(OpenFile {
+            file,
+            path: guest_path,
+            host_path,
+            flags,
+        }) as u64,
+        Err(e) => e.into_err(),
+    };
+}
+
+/// How `openat` opens a file with `flags`
+fn open_options(flags: u64, mode: u32) -> std::fs::OpenOptions {
+    let mut options = std::fs::OpenOptions::new();
+    match flags & O_ACCMODE {
+        O_WRONLY => options.write(true),
+        O_RDWR => options.read(true).write(true),
+        _ => options.read(true),
+    };
+
+    options
+        .append(flags & O_APPEND != 0)
+        .truncate(flags & O_TRUNC != 0)
+        .mode(mode);
+
+    if flags & O_CREAT != 0 {
+        if flags & O_EXCL != 0 {
+            options.create_new(true);
+        } else {
+            options.create(true);
+        }
+    }
+
+    options
+}
+
+/// How to open a file `openat` already opened with `flags` again, without creating or
+/// truncating it
+pub(crate) fn reopen_options(flags: u64) -> std::fs::OpenOptions {
+    open_options(flags & !(O_CREAT | O_EXCL | O_TRUNC), 0)
+}
+
+// 57
+pub fn close(cpu: &mut RV64GC) {
+    let fd = cpu.registers[A0] as i64;
+    debug!("close: {fd}");
+
+    // NOTE: Never close the host's stdio
+    cpu.registers[A0] = if (0..=2).contains(&fd) || cpu.fds.remove(fd as i32).is_some() {
+        0
+    } else {
+        Errno::EBADF.into_err()
+    };
 }
 
 // 64
@@ -120,101 +947,109 @@ pub fn write(cpu: &mut RV64GC) {
     let span = span!(Level::TRACE, "syscall_write");
     let _guard = span.enter();
 
-    debug!("write");
-
+    let fd = cpu.registers[A0] as i64;
     let ptr = cpu.registers[A1];
     let len = cpu.registers[A2];
 
+    trace!("fd: {fd}");
     trace!("ptr: {ptr:#08x}");
     trace!("len: {len}");
 
-    let msg = if len != 0 {
-        (ptr..ptr + len)
-            .map(|addr| {
-                cpu.ram
-                    .read_byte(addr)
-                    .inspect_err(|e| panic!("{e}"))
-                    .unwrap() as char
-            })
-            .collect::<String>()
-    } else {
-        let mut i = 0;
-        let mut string = String::new();
-        while let Ok(c) = cpu.ram.read_byte(i).map(|i| i as char) {
-            if c == '\0' {
-                break;
-            }
+    let res = write_from_guest(&cpu.ram, ptr, len, |chunk, _| {
+        write_fd(&mut cpu.fds, fd, chunk)
+    });
+    cpu.registers[A0] = match res {
+        Ok(n) => n,
+        Err(e) => e.into_err(),
+    };
+}
 
-            string.push(c);
-            i += 1;
-        }
+// 68
+pub fn pwrite64(cpu: &mut RV64GC) {
+    let span = span!(Level::TRACE, "syscall_pwrite64");
+    let _guard = span.enter();
 
-        string
-    };
+    let fd = cpu.registers[A0] as i64;
+    let ptr = cpu.registers[A1];
+    let len = cpu.registers[A2];
+    let offset = cpu.registers[A3];
 
-    print!("{msg}");
+    debug!("fd: {fd}, count: {len}, offset: {offset}");
+
+    let res = match cpu.fds.get(fd as i32) {
+        Some(open) => write_from_guest(&cpu.ram, ptr, len, |chunk, done| {
+            Ok(open.file.write_at(chunk, offset.wrapping_add(done))?)
+        }),
+        None if (0..=2).contains(&fd) => Err(Errno::ESPIPE),
+        None => Err(Errno::EBADF),
+    };
 
-    std::io::stdout().flush().unwrap();
-    cpu.registers[A0] = len;
+    cpu.registers[A0] = match res {
+        Ok(n) => n,
+        Err(e) => e.into_err(),
+    };
 }
 
 // 66
 pub fn writev(cpu: &mut RV64GC) {
-    #[derive(Debug)]
-    struct Iovec {
-        iov_base: u64,
-        iov_len: u64,
-    }
-
     debug!("writev");
 
-    let mut total_bytes_written = 0;
-    let fd = cpu.registers[A0];
+    let fd = cpu.registers[A0] as i64;
     trace!("fd: {fd}");
     let iovec_ptr = cpu.registers[A1];
     let iovec_cnt = cpu.registers[A2];
 
+    if iovec_cnt > UIO_MAXIOV {
+        cpu.registers[A0] = Errno::EINVAL.into_err();
+        return;
+    }
+
+    // NOTE: Every iovec is read before anything is written, so a bad one fails the call
+    let mut iovecs = Vec::with_capacity(iovec_cnt as usize);
     for i in 0..iovec_cnt {
-        let lptr = iovec_ptr + (16 * i);
-        let iov_base = cpu.ram.read_doubleword(lptr).unwrap();
-        let iov_len = cpu.ram.read_doubleword(lptr + 8).unwrap();
-        let iov = Iovec { iov_base, iov_len };
+        let lptr = iovec_ptr.wrapping_add(16 * i);
+        let (Ok(iov_base), Ok(iov_len)) = (
+            cpu.ram.read_doubleword(lptr),
+            cpu.ram.read_doubleword(lptr.wrapping_add(8)),
+        ) else {
+            cpu.registers[A0] = Errno::EFAULT.into_err();
+            return;
+        };
 
-        let mut val = vec![];
+        if (iov_len as i64) < 0 {
+            cpu.registers[A0] = Errno::EINVAL.into_err();
+            return;
+        }
 
-        if iov_len > 0 {
-            for j in iov.iov_base..iov.iov_base + iov.iov_len {
-                val.push(cpu.ram.read_byte(j).unwrap());
-                total_bytes_written += 1;
-            }
-        } else {
-            let mut base_addr = iov.iov_base;
-            while let Ok(b) = cpu.ram.read_byte(base_addr) {
-                if b != 0 {
-                    val.push(b);
-                    base_addr += 1;
-                } else {
+        iovecs.push((iov_base, iov_len));
+    }
+
+    // NOTE: Like Linux, each iovec and the total are clamped, and the call returns at the
+    // first short write
+    let mut total = 0;
+    for (iov_base, iov_len) in iovecs {
+        let want = iov_len.min(MAX_RW_COUNT - total);
+        let res = write_from_guest(&cpu.ram, iov_base, want, |chunk, _| {
+            write_fd(&mut cpu.fds, fd, chunk)
+        });
+
+        match res {
+            Ok(n) => {
+                total += n;
+                if n < iov_len {
                     break;
                 }
             }
-        }
-
-        if let Ok(s) = std::str::from_utf8(&val) {
-            match fd {
-                2 => eprint!("\x1b[31m{s}\x1b[0m"),
-                _ => {
-                    print!("{s}");
-                }
+            Err(e) if total == 0 => {
+                cpu.registers[A0] = e.into_err();
+                return;
             }
-        } else {
-            panic!("writev not a string!");
+            Err(_) => break,
         }
     }
 
-    trace!("wrote {total_bytes_written} bytes");
-
-    std::io::stdout().flush().unwrap();
-    cpu.registers[A0] = total_bytes_written
+    trace!("wrote {total} bytes");
+    cpu.registers[A0] = total;
 }
 
 pub fn mmap(cpu: &mut RV64GC) {
@@ -223,50 +1058,255 @@ pub fn mmap(cpu: &mut RV64GC) {
 
     let addr = cpu.registers[A0];
     let len = cpu.registers[A1];
-    let prot = cpu.registers[A2] as i64;
-    let flags = cpu.registers[A3] as i64;
+    let prot = cpu.registers[A2];
+    let flags = cpu.registers[A3];
     let fd = cpu.registers[A4] as i64;
     let offset = cpu.registers[A5];
 
     debug!("mmap");
     trace!("mmap\n\taddr: {addr}\n\tlen: {len}\n\tprot: {prot}\n\tflags: {flags}\n\tfd: {fd}\n\toffset: {offset}");
 
-    if len == 0 {
-        panic!("mmap called with no len!")
+    let fixed = flags & (MAP_FIXED | MAP_FIXED_NOREPLACE) != 0;
+    if len == 0
+        || len > MMAP_MAX_LEN
+        || !offset.is_multiple_of(PAGE_SIZE)
+        || (fixed && !addr.is_multiple_of(PAGE_SIZE))
+        || (fixed && addr.checked_add(len).is_none())
+        || flags & (MAP_SHARED | MAP_PRIVATE) == 0
+    {
+        cpu.registers[A0] = Errno::EINVAL.into_err();
+        return;
     }
+    let len = len.next_multiple_of(PAGE_SIZE);
 
-    let mmap_addr = if addr == 0 {
-        cpu.ram.lowest_unalloced_addr + 16
-    } else {
+    // NOTE: Some callers leave out MAP_ANONYMOUS and only pass fd = -1
+    let file_backed = flags & MAP_ANONYMOUS == 0 && fd != -1;
+    if file_backed {
+        if cpu.fds.get(fd as i32).is_none() {
+            cpu.registers[A0] = Errno::EBADF.into_err();
+            return;
+        }
+
+        // NOTE: Shared mappings are private copies as well, writes don't reach the file
+        if flags & MAP_SHARED != 0 && prot & PROT_WRITE != 0 {
+            warn!("mmap: writes to shared file mapping of fd {fd} won't reach the file");
+        }
+    }
+
+    let mmap_addr = if flags & MAP_FIXED != 0 {
+        cpu.ram.remove_range(addr, len);
         addr
+    } else if flags & MAP_FIXED_NOREPLACE != 0 {
+        if !cpu.ram.is_range_free(addr, len) {
+            cpu.registers[A0] = Errno::EEXIST.into_err();
+            return;
+        }
+        addr
+    } else {
+        let hint = addr & !(PAGE_SIZE - 1);
+        if hint >= MMAP_MIN_ADDR
+            && hint.checked_add(len).is_some()
+            && cpu.ram.is_range_free(hint, len)
+        {
+            hint
+        } else {
+            let top = cpu.launch_config.mmap_top();
+            match cpu.ram.find_free_range(len, MMAP_MIN_ADDR, top) {
+                Some(addr) => addr,
+                None => {
+                    cpu.registers[A0] = Errno::ENOMEM.into_err();
+                    return;
+                }
+            }
+        }
     };
 
-    let map = MemoryRegion::new(mmap_addr, len, vec![0u8; len as usize]);
-    let res = cpu.ram.add_region(map);
+    let map = MemoryRegion::new_with_flags(mmap_addr, len, Vec::new(), prot_to_flags(prot));
+    if cpu.ram.add_region(map).is_err() {
+        cpu.registers[A0] = Errno::ENOMEM.into_err();
+        return;
+    }
 
     trace!("mmap_addr: {mmap_addr:08x}");
 
-    if res.is_ok() {
-        cpu.registers[A0] = mmap_addr;
-    } else {
-        cpu.registers[A0] = u64::MAX;
+    // NOTE: Read a page at a time, past the end of the file the mapping reads as zeroes
+    if file_backed {
+        let open = cpu.fds.get(fd as i32).unwrap();
+        let mut page = [0u8; PAGE_SIZE as usize];
+        let mut filled = 0;
+        while filled < len {
+            let want = (len - filled).min(PAGE_SIZE) as usize;
+            let n = match open.file.read_at(&mut page[..want], offset + filled) {
+                Ok(0) => break,
+                Ok(n) => n,
+                Err(e) => {
+                    cpu.ram.remove_range(mmap_addr, len);
+                    cpu.registers[A0] = Errno::from(e).into_err();
+                    return;
+                }
+            };
+
+            cpu.ram.fill(mmap_addr + filled, &page[..n]).unwrap();
+            filled += n as u64;
+        }
     }
+
+    cpu.registers[A0] = mmap_addr;
 }
 
-pub fn brk(cpu: &mut RV64GC) {
-    debug!("brk");
+// 215
+pub fn munmap(cpu: &mut RV64GC) {
     let addr = cpu.registers[A0];
-    if addr == 0 {
-        cpu.registers[A0] = cpu.ram.find_end_of_text_region();
+    let len = cpu.registers[A1];
+
+    debug!("munmap: {addr:08x} {len}");
+
+    if len == 0 || !addr.is_multiple_of(PAGE_SIZE) || len > MMAP_MAX_LEN {
+        cpu.registers[A0] = Errno::EINVAL.into_err();
         return;
     }
 
-    if cpu.ram.extend_text_region_to(addr).is_ok() {
-        cpu.registers[A0] = 0;
-    } else {
-        error!("brk failed: 0x:{addr:08x}");
+    // NOTE: Unmapping what isn't mapped isn't an error
+    cpu.ram.remove_range(addr, len.next_multiple_of(PAGE_SIZE));
+    cpu.registers[A0] = 0;
+}
+
+// 216
+pub fn mremap(cpu: &mut RV64GC) {
+    let old_addr = cpu.registers[A0];
+    let old_len = cpu.registers[A1];
+    let new_len = cpu.registers[A2];
+    let flags = cpu.registers[A3];
+    let new_addr = cpu.registers[A4];
+
+    debug!("mremap: {old_addr:08x} {old_len} -> {new_len} (flags: {flags:x})");
+
+    let may_move = flags & MREMAP_MAYMOVE != 0;
+    let fixed = flags & MREMAP_FIXED != 0;
+    if !old_addr.is_multiple_of(PAGE_SIZE)
+        || new_len == 0
+        || new_len > MMAP_MAX_LEN
+        || old_len > MMAP_MAX_LEN
+        || flags & !(MREMAP_MAYMOVE | MREMAP_FIXED) != 0
+        || (fixed && (!may_move || !new_addr.is_multiple_of(PAGE_SIZE)))
+    {
+        cpu.registers[A0] = Errno::EINVAL.into_err();
+        return;
+    }
+
+    // NOTE: An old length of 0 duplicates shared mappings, there are none
+    if old_len == 0 {
+        cpu.registers[A0] = Errno::EINVAL.into_err();
+        return;
+    }
+
+    let old_len = old_len.next_multiple_of(PAGE_SIZE);
+    let new_len = new_len.next_multiple_of(PAGE_SIZE);
+
+    // NOTE: new_addr only means something with MREMAP_FIXED
+    let (Some(old_end), Some(new_end)) = (
+        old_addr.checked_add(old_len),
+        new_addr.checked_add(if fixed { new_len } else { 0 }),
+    ) else {
+        cpu.registers[A0] = Errno::EINVAL.into_err();
+        return;
+    };
+
+    // Everything being remapped has to be mapped
+    if !cpu.ram.is_range_mapped(old_addr, old_len) {
+        cpu.registers[A0] = Errno::EFAULT.into_err();
+        return;
+    }
+
+    if fixed {
+        if new_addr < old_end && old_addr < new_end {
+            cpu.registers[A0] = Errno::EINVAL.into_err();
+            return;
+        }
+
+        cpu.ram.remove_range(new_addr, new_len);
+        cpu.registers[A0] = match cpu.ram.remap(old_addr, old_len, new_addr, new_len) {
+            Ok(()) => new_addr,
+            Err(_) => Errno::EFAULT.into_err(),
+        };
+        return;
+    }
+
+    if new_len <= old_len {
+        cpu.ram.remove_range(old_addr + new_len, old_len - new_len);
+        cpu.registers[A0] = old_addr;
+        return;
+    }
+
+    // Grow in place when the range ends its region and the pages after it are free
+    let addition = new_len - old_len;
+    if cpu.ram.is_range_free(old_end, addition)
+        && cpu.ram.extend_region(old_end - 1, addition).is_ok()
+    {
+        cpu.registers[A0] = old_addr;
+        return;
+    }
+
+    if !may_move {
+        cpu.registers[A0] = Errno::ENOMEM.into_err();
+        return;
+    }
+
+    let top = cpu.launch_config.mmap_top();
+    let Some(new_addr) = cpu.ram.find_free_range(new_len, MMAP_MIN_ADDR, top) else {
         cpu.registers[A0] = Errno::ENOMEM.into_err();
+        return;
+    };
+
+    cpu.registers[A0] = match cpu.ram.remap(old_addr, old_len, new_addr, new_len) {
+        Ok(()) => new_addr,
+        Err(_) => Errno::EFAULT.into_err(),
+    };
+}
+
+/// Moves the program break, returning where it ends up. Like Linux, a break that can't
+/// be set leaves the old one in place (and returns it) instead of failing
+pub fn brk(cpu: &mut RV64GC) {
+    let addr = cpu.registers[A0];
+    debug!("brk: {addr:08x}");
+
+    let start = cpu.brk_start;
+    let old_end = cpu.brk.next_multiple_of(PAGE_SIZE);
+
+    let fits = addr
+        .checked_sub(start)
+        .is_some_and(|size| size <= cpu.launch_config.heap_limit);
+    if !fits {
+        cpu.registers[A0] = cpu.brk;
+        return;
+    }
+
+    let new_end = addr.next_multiple_of(PAGE_SIZE);
+    if new_end < old_end {
+        cpu.ram.remove_range(new_end, old_end - new_end);
+    } else if new_end > old_end {
+        // NOTE: mprotect can split the heap, so grow whatever ends at the break,
+        // and only if it's still plain heap
+        let addition = new_end - old_end;
+        let heap = (PF_R | PF_W) as u64;
+        let grown = if !cpu.ram.is_range_free(old_end, addition) {
+            Err(MemoryError::RegionOverlap(old_end))
+        } else if old_end > start && cpu.ram.region_flags(old_end - 1) == Some(heap) {
+            cpu.ram.extend_region(old_end - 1, addition)
+        } else {
+            cpu.ram
+                .add_region(MemoryRegion::new(old_end, addition, Vec::new()))
+        };
+
+        if let Err(e) = grown {
+            debug!("brk: can't grow the heap to 0x{addr:08x}: {e}");
+            cpu.registers[A0] = cpu.brk;
+            return;
+        }
     }
+
+    cpu.brk = addr;
+    cpu.registers[A0] = addr;
 }
 
 // 278
@@ -329,17 +1369,203 @@ pub fn readlink(cpu: &mut RV64GC) {
 
 // 226
 pub fn mprotect(cpu: &mut RV64GC) {
-    cpu.registers[A0] = 0;
+    let addr = cpu.registers[A0];
+    let len = cpu.registers[A1];
+    let prot = cpu.registers[A2];
+
+    debug!("mprotect: {addr:08x} {len} {prot:x}");
+
+    if !addr.is_multiple_of(PAGE_SIZE) {
+        cpu.registers[A0] = Errno::EINVAL.into_err();
+        return;
+    }
+
+    cpu.registers[A0] = match cpu.ram.protect(addr, len, prot_to_flags(prot)) {
+        Ok(()) => 0,
+        Err(_) => Errno::ENOMEM.into_err(),
+    };
+}
+
+/// Maps `PROT_*` bits to the ELF `PF_*` ones regions use
+fn prot_to_flags(prot: u64) -> u64 {
+    let mut flags = 0;
+    if prot & PROT_READ != 0 {
+        flags |= PF_R;
+    }
+    if prot & PROT_WRITE != 0 {
+        flags |= PF_W;
+    }
+    if prot & PROT_EXEC != 0 {
+        flags |= PF_X;
+    }
+
+    flags.into()
+}
+
+// 79
+pub fn newfstatat(cpu: &mut RV64GC) {
+    let dirfd = cpu.registers[A0] as i64;
+    let statbuf = cpu.registers[A2];
+    let flags = cpu.registers[A3];
+
+    let path = match read_c_string(cpu, cpu.registers[A1]) {
+        Ok(path) => path,
+        Err(e) => {
+            cpu.registers[A0] = e.into_err();
+            return;
+        }
+    };
+
+    debug!("newfstatat: {dirfd} {path}");
+
+    let meta = if path.is_empty() && flags & AT_EMPTY_PATH != 0 {
+        fd_metadata(cpu, dirfd)
+    } else {
+        resolve_at(cpu, dirfd, &path).and_then(|p| {
+            let meta = if flags & AT_SYMLINK_NOFOLLOW != 0 {
+                std::fs::symlink_metadata(p)
+            } else {
+                std::fs::metadata(p)
+            };
+
+            meta.map_err(Errno::from)
+        })
+    };
+
+    cpu.registers[A0] = match meta.and_then(|meta| write_stat(cpu, statbuf, &meta)) {
+        Ok(()) => 0,
+        Err(e) => e.into_err(),
+    };
 }
 
 // 80
-pub fn lstat(cpu: &mut RV64GC) {
-    cpu.registers[A0] = u64::MAX;
+pub fn fstat(cpu: &mut RV64GC) {
+    let fd = cpu.registers[A0] as i64;
+    let statbuf = cpu.registers[A1];
+
+    debug!("fstat: {fd}");
+
+    cpu.registers[A0] = match fd_metadata(cpu, fd).and_then(|meta| write_stat(cpu, statbuf, &meta))
+    {
+        Ok(()) => 0,
+        Err(e) => e.into_err(),
+    };
+}
+
+// 34
+pub fn mkdirat(cpu: &mut RV64GC) {
+    let mode = cpu.registers[A2] as u32;
+
+    let res = check_writable(cpu)
+        .and_then(|()| path_arg(cpu, A0, A1))
+        .and_then(|p| Ok(std::fs::DirBuilder::new().mode(mode).create(p)?));
+
+    cpu.registers[A0] = match res {
+        Ok(()) => 0,
+        Err(e) => e.into_err(),
+    };
+}
+
+// 35
+pub fn unlinkat(cpu: &mut RV64GC) {
+    let flags = cpu.registers[A2];
+
+    let res = check_writable(cpu)
+        .and_then(|()| path_arg(cpu, A0, A1))
+        .and_then(|p| {
+            if flags & AT_REMOVEDIR != 0 {
+                Ok(std::fs::remove_dir(p)?)
+            } else {
+                Ok(std::fs::remove_file(p)?)
+            }
+        });
+
+    cpu.registers[A0] = match res {
+        Ok(()) => 0,
+        Err(e) => e.into_err(),
+    };
+}
+
+// 276
+pub fn renameat2(cpu: &mut RV64GC) {
+    let flags = cpu.registers[A4];
+
+    // NOTE: RENAME_EXCHANGE and RENAME_WHITEOUT can't be done with std
+    if flags & !RENAME_NOREPLACE != 0 {
+        cpu.registers[A0] = Errno::EINVAL.into_err();
+        return;
+    }
+
+    let res = check_writable(cpu)
+        .and_then(|()| Ok((path_arg(cpu, A0, A1)?, path_arg(cpu, A2, A3)?)))
+        .and_then(|(from, to)| {
+            if flags & RENAME_NOREPLACE != 0 && to.symlink_metadata().is_ok() {
+                return Err(Errno::EEXIST);
+            }
+
+            Ok(std::fs::rename(from, to)?)
+        });
+
+    cpu.registers[A0] = match res {
+        Ok(()) => 0,
+        Err(e) => e.into_err(),
+    };
 }
 
 // 258
+// https://docs.kernel.org/arch/riscv/hwprobe.html
 pub fn riscv_hwprobe(cpu: &mut RV64GC) {
-    cpu.registers[A0] = u64::MAX;
+    let pairs = cpu.registers[A0];
+    let pair_count = cpu.registers[A1];
+    let flags = cpu.registers[A4];
+
+    // NOTE: There's a single hart, so the cpu set doesn't matter. RISCV_HWPROBE_WHICH_CPUS
+    // (the only flag) isn't supported
+    if flags != 0 {
+        cpu.registers[A0] = Errno::EINVAL.into_err();
+        return;
+    }
+
+    let res = (0..pair_count).try_for_each(|i| {
+        let pair = pairs.wrapping_add(i * 16);
+        let key = cpu.ram.read_doubleword(pair).map_err(|_| Errno::EFAULT)? as i64;
+
+        // NOTE: Unknown keys are set to -1, with a value of 0
+        let (key, value) = match key {
+            RISCV_HWPROBE_KEY_MVENDORID | RISCV_HWPROBE_KEY_MARCHID | RISCV_HWPROBE_KEY_MIMPID => {
+                (key, 0)
+            }
+            RISCV_HWPROBE_KEY_BASE_BEHAVIOR => (key, RISCV_HWPROBE_BASE_BEHAVIOR_IMA),
+            RISCV_HWPROBE_KEY_IMA_EXT_0 => (key, hwprobe_ima_ext_0(&cpu.isa)),
+            RISCV_HWPROBE_KEY_CPUPERF_0 => (key, RISCV_HWPROBE_MISALIGNED_UNKNOWN),
+            _ => (-1, 0),
+        };
+
+        cpu.ram
+            .write_doubleword(pair, key as u64)
+            .and_then(|()| cpu.ram.write_doubleword(pair.wrapping_add(8), value))
+            .map_err(|_| Errno::EFAULT)
+    });
+
+    cpu.registers[A0] = match res {
+        Ok(()) => 0,
+        Err(e) => e.into_err(),
+    };
+}
+
+/// `RISCV_HWPROBE_KEY_IMA_EXT_0`, F, D and C are always there
+fn hwprobe_ima_ext_0(isa: &Isa) -> u64 {
+    [
+        (true, RISCV_HWPROBE_IMA_FD | RISCV_HWPROBE_IMA_C),
+        (isa.v, RISCV_HWPROBE_IMA_V),
+        (isa.zba, RISCV_HWPROBE_EXT_ZBA),
+        (isa.zbb, RISCV_HWPROBE_EXT_ZBB),
+        (isa.zbc, RISCV_HWPROBE_EXT_ZBC),
+        (isa.zbs, RISCV_HWPROBE_EXT_ZBS),
+    ]
+    .into_iter()
+    .filter(|(enabled, _)| *enabled)
+    .fold(0, |bits, (_, bit)| bits | bit)
 }
 
 // 62
@@ -350,20 +1576,42 @@ pub fn lseek(cpu: &mut RV64GC) {
     let fd = cpu.registers[A0] as i64;
     let offset = cpu.registers[A1] as i64;
     let whence = cpu.registers[A2] as i64;
-    let seek_mode = SeekMode::from(whence);
+    let seek_mode = match SeekMode::try_from(whence) {
+        Ok(mode) => mode,
+        Err(e) => {
+            cpu.registers[A0] = e.into_err();
+            return;
+        }
+    };
 
     trace!("lseek");
     trace!("fd: {fd}");
     trace!("offset: {offset}");
     trace!("seek mode: {seek_mode:?}");
 
-    if fd == 0 {
-        warn!("lseek on stdin is not supported!");
-        cpu.registers[A0] = Errno::EBADF.into_err();
-    } else {
-        warn!("lseek is not yet implemented!");
-        cpu.registers[A0] = Errno::EBADF.into_err();
-    }
+    let Some(open) = cpu.fds.get_mut(fd as i32) else {
+        cpu.registers[A0] = if (0..=2).contains(&fd) {
+            Errno::ESPIPE.into_err()
+        } else {
+            Errno::EBADF.into_err()
+        };
+        return;
+    };
+
+    let pos = match seek_mode {
+        SeekMode::Set if offset < 0 => {
+            cpu.registers[A0] = Errno::EINVAL.into_err();
+            return;
+        }
+        SeekMode::Set => SeekFrom::Start(offset as u64),
+        SeekMode::Cur => SeekFrom::Current(offset),
+        SeekMode::End => SeekFrom::End(offset),
+    };
+
+    cpu.registers[A0] = match open.file.seek(pos) {
+        Ok(new_offset) => new_offset,
+        Err(e) => Errno::from(e).into_err(),
+    };
 }
 
 // 98
@@ -372,3 +1620,491 @@ pub fn lseek(cpu: &mut RV64GC) {
 pub fn futex(cpu: &mut RV64GC) {
     cpu.registers[A0] = 0;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::{Arc, Mutex};
+
+    /// Runs `syscall` with `args` in a0..a5, returning a0
+    fn call(cpu: &mut RV64GC, syscall: fn(&mut RV64GC), args: &[u64]) -> u64 {
+        for (reg, &arg) in [A0, A1, A2, A3, A4, A5].into_iter().zip(args) {
+            cpu.registers[reg] = arg;
+        }
+        syscall(cpu);
+        cpu.registers[A0]
+    }
+
+    /// Runs an `ecall` for syscall `number`, overrides and all, returning a0
+    fn ecall(cpu: &mut RV64GC, number: u64, args: &[u64]) -> Result<u64, Exception> {
+        for (reg, &arg) in [A0, A1, A2, A3, A4, A5].into_iter().zip(args) {
+            cpu.registers[reg] = arg;
+        }
+        cpu.registers[A7] = number;
+        cpu.execute(0x0000_0073)?;
+        Ok(cpu.registers[A0])
+    }
+
+    fn temp_file(name: &str, contents: &[u8]) -> OpenFile {
+        let host_path = std::env::temp_dir().join(format!("riscvm-{}-{name}", std::process::id()));
+        std::fs::write(&host_path, contents).unwrap();
+
+        OpenFile {
+            file: std::fs::File::open(&host_path).unwrap(),
+            path: PathBuf::from(name),
+            host_path,
+            flags: 0,
+        }
+    }
+
+    #[test]
+    fn test_mmap() {
+        let mut cpu = RV64GC::new();
+        let anonymous = MAP_PRIVATE | MAP_ANONYMOUS;
+
+        // NOTE: A reservation like the ones language runtimes make, 1 TiB
+        let reserved = call(&mut cpu, mmap, &[0, 1 << 40, 0, anonymous, u64::MAX, 0]);
+        assert_eq!(reserved % PAGE_SIZE, 0);
+        let rw = PROT_READ | PROT_WRITE;
+        assert_eq!(call(&mut cpu, mprotect, &[reserved, 1 << 20, rw]), 0);
+        cpu.ram.write_doubleword(reserved + 0x8000, 42).unwrap();
+        assert_eq!(call(&mut cpu, munmap, &[reserved, 1 << 40]), 0);
+
+        // NOTE: The file is read a page at a time, what's past its end reads as zeros
+        let contents: Vec<u8> = (0..5000u32).map(|i| i as u8).collect();
+        let fd = cpu.fds.insert(temp_file("mmap", &contents)) as u64;
+        let mapped = call(
+            &mut cpu,
+            mmap,
+            &[0, 3 * PAGE_SIZE, PROT_READ, MAP_PRIVATE, fd, 0],
+        );
+        assert_eq!(cpu.ram.read_byte(mapped + 4999), Ok(4999u32 as u8));
+        assert_eq!(cpu.ram.read_byte(mapped + 5000), Ok(0));
+        assert!(cpu.ram.write_byte(mapped, 1).is_err());
+        std::fs::remove_file(&cpu.fds.get(fd as i32).unwrap().host_path).unwrap();
+
+        let fixed = MAP_PRIVATE | MAP_ANONYMOUS | MAP_FIXED;
+        let top = u64::MAX - PAGE_SIZE + 1;
+        assert_eq!(
+            call(
+                &mut cpu,
+                mmap,
+                &[top, 2 * PAGE_SIZE, rw, fixed, u64::MAX, 0]
+            ),
+            Errno::EINVAL.into_err()
+        );
+    }
+
+    #[test]
+    fn test_read() {
+        let mut cpu = RV64GC::new();
+        let contents: Vec<u8> = (0..200_000u32).map(|i| (i % 251) as u8).collect();
+        let fd = cpu.fds.insert(temp_file("read", &contents)) as u64;
+        let rw = PROT_READ | PROT_WRITE;
+        let buf = call(
+            &mut cpu,
+            mmap,
+            &[0, 256 * 1024, rw, MAP_PRIVATE | MAP_ANONYMOUS, u64::MAX, 0],
+        );
+
+        // NOTE: A count far bigger than the host could allocate only reads what's there
+        assert_eq!(
+            call(&mut cpu, pread64, &[fd, buf, u64::MAX >> 1, 10]),
+            199_990
+        );
+        assert_eq!(cpu.ram.read_byte(buf + 150_000), Ok(contents[150_010]));
+        assert_eq!(cpu.fds.get(fd as i32).unwrap().offset(), 0);
+
+        // NOTE: Reads stop at the first page that isn't mapped, and fault if that's the first
+        let end = buf + 256 * 1024;
+        assert_eq!(call(&mut cpu, pread64, &[fd, end - 100, 1000, 0]), 100);
+        assert_eq!(cpu.ram.read_byte(end - 1), Ok(contents[99]));
+        assert_eq!(
+            call(&mut cpu, pread64, &[fd, end, 1000, 0]),
+            Errno::EFAULT.into_err()
+        );
+        assert_eq!(
+            call(&mut cpu, pread64, &[fd, buf, 10, u64::MAX]),
+            Errno::EINVAL.into_err()
+        );
+
+        assert_eq!(call(&mut cpu, read, &[fd, buf, 70_000]), 70_000);
+        assert_eq!(call(&mut cpu, read, &[fd, buf, u64::MAX]), 130_000);
+        assert_eq!(cpu.ram.read_byte(buf + 129_999), Ok(contents[199_999]));
+        assert_eq!(call(&mut cpu, read, &[fd, buf, u64::MAX]), 0);
+        assert_eq!(call(&mut cpu, read, &[99, buf, 1]), Errno::EBADF.into_err());
+
+        std::fs::remove_file(&cpu.fds.get(fd as i32).unwrap().host_path).unwrap();
+    }
+
+    #[test]
+    fn test_write() {
+        let mut cpu = RV64GC::new();
+        let contents: Vec<u8> = (0..256 * 1024u32).map(|i| (i % 251) as u8).collect();
+        let mut open = temp_file("write", b"");
+        open.file = std::fs::OpenOptions::new()
+            .write(true)
+            .open(&open.host_path)
+            .unwrap();
+        let host_path = open.host_path.clone();
+        let fd = cpu.fds.insert(open) as u64;
+        let rw = PROT_READ | PROT_WRITE;
+        let buf = call(
+            &mut cpu,
+            mmap,
+            &[0, 256 * 1024, rw, MAP_PRIVATE | MAP_ANONYMOUS, u64::MAX, 0],
+        );
+        cpu.ram.write_bytes(buf, &contents).unwrap();
+
+        // NOTE: A count far bigger than the host could allocate only writes what's mapped
+        let end = buf + 256 * 1024;
+        assert_eq!(call(&mut cpu, write, &[fd, buf, u64::MAX >> 1]), 256 * 1024);
+        assert_eq!(std::fs::read(&host_path).unwrap(), contents);
+
+        // NOTE: Writes stop at the first page that isn't mapped, and fault if that's the
+        // first, or if the range wraps around
+        assert_eq!(call(&mut cpu, pwrite64, &[fd, end - 100, 1000, 4]), 100);
+        assert_eq!(
+            call(&mut cpu, write, &[fd, end, 10]),
+            Errno::EFAULT.into_err()
+        );
+        assert_eq!(
+            call(&mut cpu, write, &[fd, u64::MAX - 10, 100]),
+            Errno::EFAULT.into_err()
+        );
+        assert_eq!(
+            call(&mut cpu, pwrite64, &[fd, u64::MAX - 10, 100, 0]),
+            Errno::EFAULT.into_err()
+        );
+        assert_eq!(
+            call(&mut cpu, write, &[99, buf, 1]),
+            Errno::EBADF.into_err()
+        );
+        assert_eq!(
+            call(&mut cpu, pwrite64, &[1, buf, 1, 0]),
+            Errno::ESPIPE.into_err()
+        );
+
+        let host = std::fs::read(&host_path).unwrap();
+        assert_eq!(host[4..104], contents[contents.len() - 100..]);
+        assert_eq!(host.len(), contents.len());
+
+        // NOTE: writev stops at the first short iovec, and rejects bad ones up front
+        let iovecs = buf + 1000;
+        for (i, word) in [buf, 3, end - 2, 10, buf, 4].into_iter().enumerate() {
+            cpu.ram
+                .write_doubleword(iovecs + 8 * i as u64, word)
+                .unwrap();
+        }
+        assert_eq!(call(&mut cpu, writev, &[fd, iovecs, 3]), 5);
+        assert_eq!(
+            call(&mut cpu, writev, &[fd, iovecs, UIO_MAXIOV + 1]),
+            Errno::EINVAL.into_err()
+        );
+        cpu.ram.write_doubleword(iovecs + 40, u64::MAX).unwrap();
+        assert_eq!(
+            call(&mut cpu, writev, &[fd, iovecs, 3]),
+            Errno::EINVAL.into_err()
+        );
+        cpu.ram
+            .write_doubleword(iovecs + 32, u64::MAX - 10)
+            .unwrap();
+        cpu.ram.write_doubleword(iovecs + 40, 100).unwrap();
+        assert_eq!(
+            call(&mut cpu, writev, &[fd, iovecs + 32, 1]),
+            Errno::EFAULT.into_err()
+        );
+
+        let host = std::fs::read(&host_path).unwrap();
+        assert_eq!(host.len(), contents.len() + 5);
+        let tail = &contents[contents.len() - 2..];
+        assert_eq!(host[contents.len()..], [&contents[..3], tail].concat());
+        std::fs::remove_file(&host_path).unwrap();
+    }
+
+    #[test]
+    fn test_fstat() {
+        let mut cpu = RV64GC::new();
+        let rw = PROT_READ | PROT_WRITE;
+        let buf = call(
+            &mut cpu,
+            mmap,
+            &[0, PAGE_SIZE, rw, MAP_PRIVATE | MAP_ANONYMOUS, u64::MAX, 0],
+        );
+
+        // NOTE: The host's stdio is open, whatever it is while the tests run
+        for fd in 0..=2 {
+            let host = fd_metadata(&cpu, fd).unwrap();
+            assert_eq!(call(&mut cpu, fstat, &[fd as u64, buf]), 0);
+            assert_eq!(cpu.ram.read_word(buf + 16), Ok(host.mode()));
+            assert_eq!(cpu.ram.read_doubleword(buf + 8), Ok(host.ino()));
+        }
+
+        let fd = cpu.fds.insert(temp_file("fstat", b"12345")) as u64;
+        assert_eq!(call(&mut cpu, fstat, &[fd, buf]), 0);
+        assert_eq!(cpu.ram.read_doubleword(buf + 48), Ok(5));
+        std::fs::remove_file(&cpu.fds.get(fd as i32).unwrap().host_path).unwrap();
+
+        assert_eq!(call(&mut cpu, fstat, &[99, buf]), Errno::EBADF.into_err());
+    }
+
+    #[test]
+    fn test_mremap() {
+        let mut cpu = RV64GC::new();
+        let anonymous = MAP_PRIVATE | MAP_ANONYMOUS;
+        let rw = PROT_READ | PROT_WRITE;
+        let addr = call(
+            &mut cpu,
+            mmap,
+            &[0, 2 * PAGE_SIZE, rw, anonymous, u64::MAX, 0],
+        );
+        cpu.ram.write_word(addr + 4, 7).unwrap();
+
+        // NOTE: Ranges that wrap around are rejected rather than taken as empty
+        let top = u64::MAX - PAGE_SIZE + 1;
+        assert_eq!(
+            call(&mut cpu, mremap, &[top, 2 * PAGE_SIZE, PAGE_SIZE, 0, 0]),
+            Errno::EINVAL.into_err()
+        );
+        let fixed = MREMAP_MAYMOVE | MREMAP_FIXED;
+        assert_eq!(
+            call(
+                &mut cpu,
+                mremap,
+                &[addr, PAGE_SIZE, 2 * PAGE_SIZE, fixed, top]
+            ),
+            Errno::EINVAL.into_err()
+        );
+
+        // NOTE: Part of the range isn't mapped
+        assert_eq!(
+            call(&mut cpu, mremap, &[addr, 3 * PAGE_SIZE, PAGE_SIZE, 0, 0]),
+            Errno::EFAULT.into_err()
+        );
+
+        let moved = call(
+            &mut cpu,
+            mremap,
+            &[addr, 2 * PAGE_SIZE, 1 << 32, MREMAP_MAYMOVE, 0],
+        );
+        assert_eq!(cpu.ram.read_word(moved + 4), Ok(7));
+        assert_eq!(cpu.ram.read_word(moved + (1 << 32) - 4), Ok(0));
+
+        // NOTE: A prefix of a larger mapping can't grow in place, it has to move
+        let addr = 0x4000_0000;
+        let end = addr + 3 * PAGE_SIZE;
+        assert!(cpu.ram.is_range_free(addr, 4 * PAGE_SIZE));
+        assert_eq!(
+            call(
+                &mut cpu,
+                mmap,
+                &[addr, 3 * PAGE_SIZE, rw, anonymous | MAP_FIXED, u64::MAX, 0],
+            ),
+            addr
+        );
+        cpu.ram.write_word(addr, 9).unwrap();
+        assert_eq!(
+            call(&mut cpu, mremap, &[addr, PAGE_SIZE, 2 * PAGE_SIZE, 0, 0]),
+            Errno::ENOMEM.into_err()
+        );
+        assert_eq!(
+            cpu.ram.read_byte(end),
+            Err(MemoryError::InvalidAddress(end))
+        );
+
+        let moved = call(
+            &mut cpu,
+            mremap,
+            &[addr, PAGE_SIZE, 2 * PAGE_SIZE, MREMAP_MAYMOVE, 0],
+        );
+        assert_ne!(moved, addr);
+        assert_eq!(cpu.ram.read_word(moved), Ok(9));
+        assert_eq!(
+            cpu.ram.read_byte(addr),
+            Err(MemoryError::InvalidAddress(addr))
+        );
+        assert_eq!(cpu.ram.read_byte(addr + PAGE_SIZE), Ok(0));
+        assert_eq!(
+            cpu.ram.read_byte(end),
+            Err(MemoryError::InvalidAddress(end))
+        );
+    }
+
+    #[test]
+    fn test_overrides() {
+        let mut cpu = RV64GC::new();
+        let rw = PROT_READ | PROT_WRITE;
+        let buf = call(
+            &mut cpu,
+            mmap,
+            &[0, PAGE_SIZE, rw, MAP_PRIVATE | MAP_ANONYMOUS, u64::MAX, 0],
+        );
+        cpu.ram.write_bytes(buf, b"hello").unwrap();
+
+        // NOTE: Captures what the guest writes to stdout, other fds go on to LinuxSyscalls
+        let stdout = Arc::new(Mutex::new(Vec::new()));
+        let captured = stdout.clone();
+        cpu.syscall_overrides
+            .register(64, move |cpu: &mut RV64GC, _| {
+                if cpu.registers[A0] != 1 {
+                    return Ok(Dispatch::Fallthrough);
+                }
+
+                let (ptr, len) = (cpu.registers[A1], cpu.registers[A2]);
+                let mut bytes = vec![0; len as usize];
+                cpu.ram
+                    .read_bytes(ptr, &mut bytes)
+                    .map_err(|_| Exception::LoadAccessFault(ptr))?;
+                captured.lock().unwrap().extend_from_slice(&bytes);
+                cpu.registers[A0] = len;
+                Ok(Dispatch::Handled)
+            });
+
+        assert_eq!(ecall(&mut cpu, 64, &[1, buf, 5]), Ok(5));
+        assert_eq!(ecall(&mut cpu, 64, &[1, buf, 2]), Ok(2));
+        assert_eq!(*stdout.lock().unwrap(), b"hellohe");
+
+        let mut open = temp_file("overrides", b"");
+        open.file = std::fs::OpenOptions::new()
+            .write(true)
+            .open(&open.host_path)
+            .unwrap();
+        let host_path = open.host_path.clone();
+        let fd = cpu.fds.insert(open) as u64;
+        assert_eq!(ecall(&mut cpu, 64, &[fd, buf, 5]), Ok(5));
+        assert_eq!(
+            ecall(&mut cpu, 64, &[99, buf, 5]),
+            Ok(Errno::EBADF.into_err())
+        );
+        assert_eq!(std::fs::read(&host_path).unwrap(), b"hello");
+        assert_eq!(stdout.lock().unwrap().len(), 7);
+        std::fs::remove_file(&host_path).unwrap();
+
+        // NOTE: Numbers LinuxSyscalls doesn't know are free for calls into the host
+        let host_call = 1234;
+        let enosys = Ok(Errno::ENOSYS.into_err());
+        assert_eq!(ecall(&mut cpu, host_call, &[21]), enosys);
+        cpu.syscall_overrides
+            .register(host_call, |cpu: &mut RV64GC, _| {
+                cpu.registers[A0] *= 2;
+                Ok(Dispatch::Handled)
+            });
+        assert_eq!(ecall(&mut cpu, host_call, &[21]), Ok(42));
+
+        let replaced = cpu
+            .syscall_overrides
+            .register(host_call, |cpu: &mut RV64GC, _| {
+                cpu.registers[A0] *= 3;
+                Ok(Dispatch::Handled)
+            });
+        assert!(replaced.is_some());
+        assert_eq!(ecall(&mut cpu, host_call, &[21]), Ok(63));
+        assert!(cpu.syscall_overrides.unregister(host_call).is_some());
+        assert_eq!(ecall(&mut cpu, host_call, &[21]), enosys);
+
+        // NOTE: So are riscvm's own debugging calls, and an error is the ecall's exception
+        cpu.syscall_overrides
+            .register(1000, |cpu: &mut RV64GC, number| {
+                cpu.registers[A0] = number;
+                Ok(Dispatch::Handled)
+            });
+        assert_eq!(ecall(&mut cpu, 1000, &[5]), Ok(1000));
+
+        cpu.syscall_overrides.register(1100, |cpu: &mut RV64GC, _| {
+            Err(Exception::LoadAccessFault(cpu.registers[A0]))
+        });
+        for _ in 0..2 {
+            assert_eq!(
+                ecall(&mut cpu, 1100, &[8]),
+                Err(Exception::LoadAccessFault(8))
+            );
+        }
+    }
+
+    #[test]
+    fn test_brk() {
+        let mut cpu = RV64GC::new();
+        let start = 0x10_0000;
+        (cpu.brk_start, cpu.brk) = (start, start);
+        cpu.launch_config.heap_limit = 4 * PAGE_SIZE;
+
+        assert_eq!(call(&mut cpu, brk, &[0]), start);
+        assert_eq!(
+            cpu.ram.read_byte(start),
+            Err(MemoryError::InvalidAddress(start))
+        );
+
+        // NOTE: The heap is whole pages, however far into the last one the break is
+        assert_eq!(call(&mut cpu, brk, &[start + 100]), start + 100);
+        cpu.ram.write_byte(start + PAGE_SIZE - 1, 1).unwrap();
+        assert_eq!(
+            call(&mut cpu, brk, &[start + 3 * PAGE_SIZE]),
+            start + 3 * PAGE_SIZE
+        );
+        cpu.ram.write_word(start + PAGE_SIZE + 8, 2).unwrap();
+        assert_eq!(cpu.ram.read_byte(start + PAGE_SIZE - 1), Ok(1));
+
+        // NOTE: Past the limit, or below the start, the break stays where it was
+        let limit = start + 4 * PAGE_SIZE;
+        assert_eq!(call(&mut cpu, brk, &[limit + 1]), start + 3 * PAGE_SIZE);
+        assert_eq!(call(&mut cpu, brk, &[start - 1]), start + 3 * PAGE_SIZE);
+        assert_eq!(cpu.brk, start + 3 * PAGE_SIZE);
+
+        // NOTE: Shrinking unmaps the pages, and growing back gives zeroed ones
+        assert_eq!(call(&mut cpu, brk, &[start + PAGE_SIZE]), start + PAGE_SIZE);
+        assert_eq!(
+            cpu.ram.read_byte(start + PAGE_SIZE),
+            Err(MemoryError::InvalidAddress(start + PAGE_SIZE))
+        );
+        assert_eq!(cpu.ram.read_byte(start + PAGE_SIZE - 1), Ok(1));
+        assert_eq!(call(&mut cpu, brk, &[limit]), limit);
+        assert_eq!(cpu.ram.read_word(start + PAGE_SIZE + 8), Ok(0));
+
+        assert_eq!(call(&mut cpu, brk, &[start]), start);
+        assert_eq!(
+            cpu.ram.read_byte(start),
+            Err(MemoryError::InvalidAddress(start))
+        );
+
+        // NOTE: Growing into a mapping fails the same way
+        let rw = PROT_READ | PROT_WRITE;
+        let fixed = MAP_PRIVATE | MAP_ANONYMOUS | MAP_FIXED;
+        let mapped = start + 2 * PAGE_SIZE;
+        assert_eq!(
+            call(&mut cpu, mmap, &[mapped, PAGE_SIZE, rw, fixed, u64::MAX, 0]),
+            mapped
+        );
+        assert_eq!(call(&mut cpu, brk, &[start + PAGE_SIZE]), start + PAGE_SIZE);
+        assert_eq!(call(&mut cpu, brk, &[limit]), start + PAGE_SIZE);
+        assert_eq!(
+            cpu.ram.read_byte(start + PAGE_SIZE),
+            Err(MemoryError::InvalidAddress(start + PAGE_SIZE))
+        );
+        assert_eq!(call(&mut cpu, munmap, &[mapped, PAGE_SIZE]), 0);
+
+        // NOTE: Growing past a heap mprotect split gives writable pages after the break
+        assert_eq!(
+            call(&mut cpu, brk, &[start + 2 * PAGE_SIZE]),
+            start + 2 * PAGE_SIZE
+        );
+        assert_eq!(
+            call(
+                &mut cpu,
+                mprotect,
+                &[start + PAGE_SIZE, PAGE_SIZE, PROT_READ]
+            ),
+            0
+        );
+        assert_eq!(call(&mut cpu, brk, &[limit]), limit);
+        cpu.ram.write_byte(start, 3).unwrap();
+        cpu.ram.write_byte(limit - 1, 4).unwrap();
+        assert_eq!(
+            cpu.ram.write_byte(start + PAGE_SIZE, 5),
+            Err(MemoryError::PermissionDenied(
+                start + PAGE_SIZE,
+                Access::Write
+            ))
+        );
+    }
+}
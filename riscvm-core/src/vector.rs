@@ -0,0 +1,2292 @@
+// RVV 1.0, the vector extension
+//
+// The register file and `vl`/`vtype`/`vstart`/`vxrm`/`vxsat` live in [`VectorState`], VLEN
+// comes from the hart's [`Isa`](crate::isa::Isa). Every vector instruction ends up in
+// [`execute`], which works one element at a time: elements are read out of the register
+// file as `u64`s (the raw bits for floats) and written back truncated to their width.
+// Tail and inactive elements are always left undisturbed, which the agnostic policies
+// allow too. Half precision (Zvfh) isn't supported, so float elements are 32 or 64 bits.
+
+use std::mem::size_of;
+use std::ops::Range;
+
+use crate::cpu::{RV64GCInstruction, Reg, RV64GC};
+use crate::exception::Exception;
+use crate::fcsr::{self, FloatExtends, RoundingMode, FCSR};
+use crate::sign_extend;
+use crate::softfloat;
+
+/// The largest VLEN [`Isa`](crate::isa::Isa) accepts, the register file always has room
+/// for it
+pub const MAX_VLEN: u32 = 1024;
+
+const MAX_VLENB: usize = MAX_VLEN as usize / 8;
+
+/// `vtype.vill`, set when `vsetvl{i}` asks for a configuration the hart doesn't support
+pub const VTYPE_VILL: u64 = 1 << 63;
+
+/// ELEN, the widest element, in bytes
+const ELEN: usize = 8;
+
+/// The last source of a vector arithmetic instruction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VSrc {
+    /// `vs1`
+    V(Reg),
+    /// An integer register, `rs1`
+    X(Reg),
+    /// A float register, `rs1`
+    F(Reg),
+    /// The 5 bit immediate, already sign extended for the instructions that take it signed
+    I(i8),
+}
+
+/// `vd, vs2, vs1/rs1/imm` and `vm`, which most arithmetic instructions take
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VOperands {
+    pub vd: Reg,
+    pub vs2: Reg,
+    pub src: VSrc,
+    /// Unmasked, `v0.t` is clear in the encoding
+    pub vm: bool,
+}
+
+/// The parts of a load or store besides its registers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VMem {
+    /// The `width` field in bits: of the data for unit-stride, strided and whole register
+    /// accesses, of the indices for indexed ones
+    pub eew: u8,
+    /// Fields per segment, or registers for whole register accesses
+    pub nf: u8,
+    pub vm: bool,
+}
+
+/// The vector registers and CSRs of a hart
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VectorState {
+    /// `v0` to `v31`, `MAX_VLENB` bytes apart whatever VLEN is
+    pub(crate) registers: Vec<u8>,
+    pub vl: u64,
+    pub vtype: u64,
+    pub vstart: u64,
+    /// The fixed point rounding mode
+    pub vxrm: u8,
+    /// Set when a fixed point instruction saturated
+    pub vxsat: bool,
+}
+
+impl Default for VectorState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl VectorState {
+    pub fn new() -> VectorState {
+        VectorState {
+            registers: vec![0; 32 * MAX_VLENB],
+            vl: 0,
+            vtype: VTYPE_VILL,
+            vstart: 0,
+            vxrm: 0,
+            vxsat: false,
+        }
+    }
+
+    /// The bytes of `reg`, VLEN is `vlenb * 8`
+    pub fn register(&self, reg: Reg, vlenb: usize) -> &[u8] {
+        &self.registers[usize::from(reg) * MAX_VLENB..][..vlenb]
+    }
+
+    pub fn register_mut(&mut self, reg: Reg, vlenb: usize) -> &mut [u8] {
+        &mut self.registers[usize::from(reg) * MAX_VLENB..][..vlenb]
+    }
+
+    /// `vcsr`, `vxrm` in bits 2:1 and `vxsat` in bit 0
+    pub fn vcsr(&self) -> u64 {
+        u64::from(self.vxrm) << 1 | u64::from(self.vxsat)
+    }
+
+    pub fn set_vcsr(&mut self, value: u64) {
+        self.vxrm = (value >> 1) as u8 & 0b11;
+        self.vxsat = value & 1 == 1;
+    }
+
+    /// Where byte `byte` of the register group starting at `reg` is
+    fn offset(reg: Reg, byte: usize, vlenb: usize) -> usize {
+        // NOTE: Groups are checked to fit before they're used, wrapping only keeps a bad one
+        // from indexing out of bounds
+        (usize::from(reg) + byte / vlenb) % 32 * MAX_VLENB + byte % vlenb
+    }
+
+    /// Element `index` of the group starting at `reg`, elements are `eew` bytes wide
+    pub fn element(&self, vlenb: usize, reg: Reg, index: usize, eew: usize) -> u64 {
+        let start = Self::offset(reg, index * eew, vlenb);
+        let mut bytes = [0; 8];
+        bytes[..eew].copy_from_slice(&self.registers[start..start + eew]);
+        u64::from_le_bytes(bytes)
+    }
+
+    pub fn set_element(&mut self, vlenb: usize, reg: Reg, index: usize, eew: usize, value: u64) {
+        let start = Self::offset(reg, index * eew, vlenb);
+        self.registers[start..start + eew].copy_from_slice(&value.to_le_bytes()[..eew]);
+    }
+
+    /// Bit `index` of the mask in `reg`
+    pub fn mask_bit(&self, reg: Reg, index: usize) -> bool {
+        self.registers[usize::from(reg) * MAX_VLENB + index / 8] >> (index % 8) & 1 == 1
+    }
+
+    pub fn set_mask_bit(&mut self, reg: Reg, index: usize, bit: bool) {
+        let byte = &mut self.registers[usize::from(reg) * MAX_VLENB + index / 8];
+        *byte = *byte & !(1 << (index % 8)) | u8::from(bit) << (index % 8);
+    }
+}
+
+/// SEW in bytes and log2(LMUL) of `vtype`, `None` if the hart doesn't support it
+fn decode_vtype(vtype: u64) -> Option<(usize, i32)> {
+    // NOTE: Bits 62:8 are reserved, and vill is bit 63
+    if vtype >> 8 != 0 {
+        return None;
+    }
+
+    let vsew = (vtype >> 3) & 0b111;
+    if vsew > 3 {
+        return None;
+    }
+
+    let sew = 1 << vsew;
+    let lmul = match vtype & 0b111 {
+        0b100 => return None,
+        vlmul @ 0..=3 => vlmul as i32,
+        vlmul => vlmul as i32 - 8,
+    };
+
+    // NOTE: Fractional LMULs need SEW <= ELEN * LMUL
+    if lmul < 0 && sew > ELEN >> -lmul {
+        return None;
+    }
+
+    Some((sew, lmul))
+}
+
+/// Prints `vtype` the way assemblers take it, `e32, m2, ta, mu`, or as a number when it's
+/// reserved
+pub fn format_vtype(vtype: u64) -> String {
+    let vsew = (vtype >> 3) & 0b111;
+    let vlmul = vtype & 0b111;
+    if vtype >> 8 != 0 || vsew > 3 || vlmul == 0b100 {
+        return vtype.to_string();
+    }
+
+    let lmul = match vlmul {
+        0..=3 => format!("m{}", 1 << vlmul),
+        _ => format!("mf{}", 1 << (8 - vlmul)),
+    };
+    let ta = if vtype >> 6 & 1 == 1 { "ta" } else { "tu" };
+    let ma = if vtype >> 7 & 1 == 1 { "ma" } else { "mu" };
+
+    format!("e{}, {lmul}, {ta}, {ma}", 8 << vsew)
+}
+
+/// Elements of `eew` bytes in a group of 2^`emul` registers
+fn vlmax(vlenb: usize, eew: usize, emul: i32) -> usize {
+    if emul >= 0 {
+        (vlenb / eew) << emul
+    } else {
+        (vlenb / eew) >> -emul
+    }
+}
+
+/// `vtype` and `vl` as an instruction sees them
+#[derive(Debug, Clone, Copy)]
+struct Config {
+    vlenb: usize,
+    /// SEW in bytes
+    sew: usize,
+    /// log2(LMUL)
+    lmul: i32,
+    vl: usize,
+    vstart: usize,
+}
+
+impl Config {
+    /// The current configuration, instructions that depend on it are illegal when `vill` is
+    /// set
+    fn current(cpu: &RV64GC) -> Result<Config, Exception> {
+        let (sew, lmul) = decode_vtype(cpu.vector.vtype).ok_or(Exception::IllegalInstruction)?;
+        let vlenb = cpu.isa.vlenb();
+        let vl = (cpu.vector.vl as usize).min(vlmax(vlenb, sew, lmul));
+
+        Ok(Config {
+            vlenb,
+            sew,
+            lmul,
+            vl,
+            vstart: cpu.vector.vstart as usize,
+        })
+    }
+
+    fn vlmax(&self) -> usize {
+        vlmax(self.vlenb, self.sew, self.lmul)
+    }
+
+    /// log2(EMUL) of a group of `eew` byte elements, which have as many elements as SEW ones
+    fn emul(&self, eew: usize) -> i32 {
+        self.lmul + eew.trailing_zeros() as i32 - self.sew.trailing_zeros() as i32
+    }
+
+    /// 2*SEW, widening and narrowing instructions need it to fit ELEN
+    fn wide_sew(&self) -> Result<usize, Exception> {
+        if self.sew * 2 > ELEN {
+            return Err(Exception::IllegalInstruction);
+        }
+
+        Ok(self.sew * 2)
+    }
+
+    /// [`Config::wide_sew`], for instructions that also have a 2*LMUL group
+    fn wide(&self) -> Result<usize, Exception> {
+        if self.lmul >= 3 {
+            return Err(Exception::IllegalInstruction);
+        }
+
+        self.wide_sew()
+    }
+
+    /// The elements an instruction works on, tail ones are never touched
+    fn body(&self) -> Range<usize> {
+        self.vstart..self.vl
+    }
+
+    fn get(&self, cpu: &RV64GC, reg: Reg, index: usize, eew: usize) -> u64 {
+        cpu.vector.element(self.vlenb, reg, index, eew)
+    }
+
+    fn set(&self, cpu: &mut RV64GC, reg: Reg, index: usize, eew: usize, value: u64) {
+        cpu.vector.set_element(self.vlenb, reg, index, eew, value);
+    }
+
+    /// Element `index` of `src`, scalars are the same for every element and get truncated to
+    /// `eew` bytes
+    fn operand(&self, cpu: &RV64GC, src: VSrc, index: usize, eew: usize) -> u64 {
+        match src {
+            VSrc::V(vs1) => self.get(cpu, vs1, index, eew),
+            VSrc::X(rs1) => cpu.registers[&rs1] & ones(eew),
+            // NOTE: Singles have to be NaN-boxed like for the F instructions
+            VSrc::F(rs1) if eew == 4 => cpu.float_registers.read_f32(&rs1).to_raw(),
+            VSrc::F(rs1) => cpu.float_registers[&rs1] & ones(eew),
+            VSrc::I(imm) => imm as u64 & ones(eew),
+        }
+    }
+
+    fn lane(&self, cpu: &RV64GC) -> Lane {
+        Lane {
+            sew: self.sew as u32 * 8,
+            vxrm: cpu.vector.vxrm,
+            saturated: false,
+        }
+    }
+}
+
+/// Masked instructions skip the elements whose bit in v0 is clear
+fn active(cpu: &RV64GC, vm: bool, index: usize) -> bool {
+    vm || cpu.vector.mask_bit(0, index)
+}
+
+/// The low `bytes` bytes set
+fn ones(bytes: usize) -> u64 {
+    u64::MAX >> (64 - 8 * bytes)
+}
+
+/// A group of 2^`emul` registers has to start at a multiple of its size
+fn check_group(reg: Reg, emul: i32) -> Result<(), Exception> {
+    if !(-3..=3).contains(&emul) || (emul > 0 && !reg.is_multiple_of(1 << emul)) {
+        return Err(Exception::IllegalInstruction);
+    }
+
+    Ok(())
+}
+
+/// `vd`, `vs2` and `vs1` have to be aligned to their EMULs, and a masked instruction can't
+/// write v0 unless its result is a mask
+fn check_operands(
+    ops: &VOperands,
+    vd: i32,
+    vs2: i32,
+    vs1: i32,
+    mask_result: bool,
+) -> Result<(), Exception> {
+    check_group(ops.vd, vd)?;
+    check_group(ops.vs2, vs2)?;
+    if let VSrc::V(reg) = ops.src {
+        check_group(reg, vs1)?;
+    }
+
+    if !ops.vm && ops.vd == 0 && !mask_result {
+        return Err(Exception::IllegalInstruction);
+    }
+
+    Ok(())
+}
+
+/// Runs `f` on every active body element, then writes the results to `vd`, `eew` bytes
+/// wide. Everything is read before anything is written, so sources can overlap `vd`
+fn elementwise(
+    cpu: &mut RV64GC,
+    cfg: &Config,
+    vd: Reg,
+    eew: usize,
+    vm: bool,
+    mut f: impl FnMut(&mut RV64GC, usize) -> u64,
+) {
+    let mut results = Vec::with_capacity(cfg.vl);
+    for index in cfg.body() {
+        if active(cpu, vm, index) {
+            results.push((index, f(cpu, index)));
+        }
+    }
+
+    for (index, value) in results {
+        cfg.set(cpu, vd, index, eew, value);
+    }
+}
+
+/// [`elementwise`] for instructions that produce a mask
+fn maskwise(
+    cpu: &mut RV64GC,
+    cfg: &Config,
+    vd: Reg,
+    vm: bool,
+    mut f: impl FnMut(&mut RV64GC, usize) -> bool,
+) {
+    let mut results = Vec::with_capacity(cfg.vl);
+    for index in cfg.body() {
+        if active(cpu, vm, index) {
+            results.push((index, f(cpu, index)));
+        }
+    }
+
+    for (index, bit) in results {
+        cpu.vector.set_mask_bit(vd, index, bit);
+    }
+}
+
+/// Executes a vector instruction, [`RV64GCInstruction::execute_instruction`] hands every
+/// one of them over
+pub(crate) fn execute(ins: &RV64GCInstruction, cpu: &mut RV64GC) -> Result<(), Exception> {
+    use RV64GCInstruction::*;
+
+    match *ins {
+        Vsetvli(rd, rs1, vtypei) => {
+            let avl = avl(cpu, rd, rs1);
+            cpu.registers[&rd] = configure(cpu, avl, vtypei.into());
+        }
+
+        Vsetivli(rd, uimm, vtypei) => {
+            cpu.registers[&rd] = configure(cpu, uimm.into(), vtypei.into());
+        }
+
+        Vsetvl(rd, rs1, rs2) => {
+            let avl = avl(cpu, rd, rs1);
+            let vtype = cpu.registers[&rs2];
+            cpu.registers[&rd] = configure(cpu, avl, vtype);
+        }
+
+        // NOTE: Whole register instructions don't depend on vtype, they work even with vill
+        Vlr(vd, rs1, mem) => load_whole(cpu, vd, rs1, mem)?,
+        Vsr(vs3, rs1, mem) => store_whole(cpu, vs3, rs1, mem)?,
+        Vmvr(vd, vs2, nregs) => {
+            if !nregs.is_power_of_two() || !vd.is_multiple_of(nregs) || !vs2.is_multiple_of(nregs) {
+                return Err(Exception::IllegalInstruction);
+            }
+
+            let vlenb = cpu.isa.vlenb();
+            for reg in 0..nregs {
+                let bytes = cpu.vector.register(vs2 + reg, vlenb).to_vec();
+                cpu.vector
+                    .register_mut(vd + reg, vlenb)
+                    .copy_from_slice(&bytes);
+            }
+        }
+
+        _ => {
+            let cfg = Config::current(cpu)?;
+            execute_configured(ins, cpu, &cfg)?;
+        }
+    }
+
+    cpu.vector.vstart = 0;
+    Ok(())
+}
+
+/// The AVL `vsetvli` and `vsetvl` ask for: `rs1`, VLMAX when only `rd` is x0, or the
+/// current `vl` when both are
+fn avl(cpu: &RV64GC, rd: Reg, rs1: Reg) -> u64 {
+    match (rd, rs1) {
+        (0, 0) => cpu.vector.vl,
+        (_, 0) => u64::MAX,
+        _ => cpu.registers[&rs1],
+    }
+}
+
+/// Sets `vtype`, and `vl` to `min(avl, VLMAX)`, which it returns
+fn configure(cpu: &mut RV64GC, avl: u64, vtype: u64) -> u64 {
+    let vlenb = cpu.isa.vlenb();
+    let vector = &mut cpu.vector;
+
+    match decode_vtype(vtype) {
+        Some((sew, lmul)) => {
+            vector.vtype = vtype;
+            vector.vl = avl.min(vlmax(vlenb, sew, lmul) as u64);
+        }
+        None => {
+            vector.vtype = VTYPE_VILL;
+            vector.vl = 0;
+        }
+    }
+
+    vector.vl
+}
+
+/// Calls `$f::<F>` with `F` the float type of SEW, half precision isn't supported
+macro_rules! float_sew {
+    ($cfg:expr, $f:ident($($arg:expr),* $(,)?)) => {
+        match $cfg.sew {
+            4 => $f::<f32>($($arg),*),
+            8 => $f::<f64>($($arg),*),
+            _ => Err(Exception::IllegalInstruction),
+        }
+    };
+}
+
+fn execute_configured(
+    ins: &RV64GCInstruction,
+    cpu: &mut RV64GC,
+    cfg: &Config,
+) -> Result<(), Exception> {
+    use Num::*;
+    use RV64GCInstruction::*;
+    use Rounding::*;
+
+    match *ins {
+        // NOTE: Loads and stores
+        Vle(vd, rs1, mem) => load(cpu, cfg, vd, rs1, mem, Addressing::Unit, false),
+        Vleff(vd, rs1, mem) => load(cpu, cfg, vd, rs1, mem, Addressing::Unit, true),
+        Vlse(vd, rs1, rs2, mem) => {
+            let stride = cpu.registers[&rs2];
+            load(cpu, cfg, vd, rs1, mem, Addressing::Strided(stride), false)
+        }
+        Vluxei(vd, rs1, vs2, mem) | Vloxei(vd, rs1, vs2, mem) => {
+            load(cpu, cfg, vd, rs1, mem, Addressing::Indexed(vs2), false)
+        }
+        Vse(vs3, rs1, mem) => store(cpu, cfg, vs3, rs1, mem, Addressing::Unit),
+        Vsse(vs3, rs1, rs2, mem) => {
+            let stride = cpu.registers[&rs2];
+            store(cpu, cfg, vs3, rs1, mem, Addressing::Strided(stride))
+        }
+        Vsuxei(vs3, rs1, vs2, mem) | Vsoxei(vs3, rs1, vs2, mem) => {
+            store(cpu, cfg, vs3, rs1, mem, Addressing::Indexed(vs2))
+        }
+        Vlm(vd, rs1) => load_mask(cpu, cfg, vd, rs1),
+        Vsm(vs3, rs1) => store_mask(cpu, cfg, vs3, rs1),
+
+        // NOTE: Integer arithmetic
+        Vadd(ops) => binary(cpu, cfg, ops, |a, b, _| a.wrapping_add(b)),
+        Vsub(ops) => binary(cpu, cfg, ops, |a, b, _| a.wrapping_sub(b)),
+        Vrsub(ops) => binary(cpu, cfg, ops, |a, b, _| b.wrapping_sub(a)),
+        Vminu(ops) => binary(cpu, cfg, ops, |a, b, _| a.min(b)),
+        Vmin(ops) => binary(cpu, cfg, ops, |a, b, l| {
+            if signed(a, l.sew) < signed(b, l.sew) {
+                a
+            } else {
+                b
+            }
+        }),
+        Vmaxu(ops) => binary(cpu, cfg, ops, |a, b, _| a.max(b)),
+        Vmax(ops) => binary(cpu, cfg, ops, |a, b, l| {
+            if signed(a, l.sew) > signed(b, l.sew) {
+                a
+            } else {
+                b
+            }
+        }),
+        Vand(ops) => binary(cpu, cfg, ops, |a, b, _| a & b),
+        Vor(ops) => binary(cpu, cfg, ops, |a, b, _| a | b),
+        Vxor(ops) => binary(cpu, cfg, ops, |a, b, _| a ^ b),
+        Vsll(ops) => binary(cpu, cfg, ops, |a, b, l| a << (b % u64::from(l.sew))),
+        Vsrl(ops) => binary(cpu, cfg, ops, |a, b, l| a >> (b % u64::from(l.sew))),
+        Vsra(ops) => binary(cpu, cfg, ops, |a, b, l| {
+            (signed(a, l.sew) >> (b % u64::from(l.sew))) as u64
+        }),
+        Vmul(ops) => binary(cpu, cfg, ops, |a, b, _| a.wrapping_mul(b)),
+        Vmulh(ops) => binary(cpu, cfg, ops, |a, b, l| {
+            ((i128::from(signed(a, l.sew)) * i128::from(signed(b, l.sew))) >> l.sew) as u64
+        }),
+        Vmulhu(ops) => binary(cpu, cfg, ops, |a, b, l| {
+            ((u128::from(a) * u128::from(b)) >> l.sew) as u64
+        }),
+        Vmulhsu(ops) => binary(cpu, cfg, ops, |a, b, l| {
+            ((i128::from(signed(a, l.sew)) * i128::from(b)) >> l.sew) as u64
+        }),
+        // NOTE: Division by zero and overflow don't trap, like for the M instructions
+        Vdivu(ops) => binary(cpu, cfg, ops, |a, b, _| {
+            a.checked_div(b).unwrap_or(u64::MAX)
+        }),
+        Vdiv(ops) => binary(cpu, cfg, ops, |a, b, l| match signed(b, l.sew) {
+            0 => u64::MAX,
+            b => signed(a, l.sew).wrapping_div(b) as u64,
+        }),
+        Vremu(ops) => binary(cpu, cfg, ops, |a, b, _| a.checked_rem(b).unwrap_or(a)),
+        Vrem(ops) => binary(cpu, cfg, ops, |a, b, l| match signed(b, l.sew) {
+            0 => a,
+            b => signed(a, l.sew).wrapping_rem(b) as u64,
+        }),
+
+        Vadc(ops) => with_carry(cpu, cfg, ops, false),
+        Vsbc(ops) => with_carry(cpu, cfg, ops, true),
+        Vmadc(ops) => carry_out(cpu, cfg, ops, false),
+        Vmsbc(ops) => carry_out(cpu, cfg, ops, true),
+
+        Vmerge(ops) => {
+            check_operands(&ops, cfg.lmul, cfg.lmul, cfg.lmul, false)?;
+            elementwise(cpu, cfg, ops.vd, cfg.sew, true, |cpu, i| {
+                if cpu.vector.mask_bit(0, i) {
+                    cfg.operand(cpu, ops.src, i, cfg.sew)
+                } else {
+                    cfg.get(cpu, ops.vs2, i, cfg.sew)
+                }
+            });
+            Ok(())
+        }
+        Vmv(vd, src) => {
+            check_group(vd, cfg.lmul)?;
+            if let VSrc::V(vs1) = src {
+                check_group(vs1, cfg.lmul)?;
+            }
+
+            elementwise(cpu, cfg, vd, cfg.sew, true, |cpu, i| {
+                cfg.operand(cpu, src, i, cfg.sew)
+            });
+            Ok(())
+        }
+
+        Vmseq(ops) => compare(cpu, cfg, ops, |a, b, _| a == b),
+        Vmsne(ops) => compare(cpu, cfg, ops, |a, b, _| a != b),
+        Vmsltu(ops) => compare(cpu, cfg, ops, |a, b, _| a < b),
+        Vmslt(ops) => compare(cpu, cfg, ops, |a, b, sew| signed(a, sew) < signed(b, sew)),
+        Vmsleu(ops) => compare(cpu, cfg, ops, |a, b, _| a <= b),
+        Vmsle(ops) => compare(cpu, cfg, ops, |a, b, sew| signed(a, sew) <= signed(b, sew)),
+        Vmsgtu(ops) => compare(cpu, cfg, ops, |a, b, _| a > b),
+        Vmsgt(ops) => compare(cpu, cfg, ops, |a, b, sew| signed(a, sew) > signed(b, sew)),
+
+        Vzextvf2(vd, vs2, vm) => extend(cpu, cfg, vd, vs2, vm, 2, false),
+        Vzextvf4(vd, vs2, vm) => extend(cpu, cfg, vd, vs2, vm, 4, false),
+        Vzextvf8(vd, vs2, vm) => extend(cpu, cfg, vd, vs2, vm, 8, false),
+        Vsextvf2(vd, vs2, vm) => extend(cpu, cfg, vd, vs2, vm, 2, true),
+        Vsextvf4(vd, vs2, vm) => extend(cpu, cfg, vd, vs2, vm, 4, true),
+        Vsextvf8(vd, vs2, vm) => extend(cpu, cfg, vd, vs2, vm, 8, true),
+
+        // NOTE: The multiply-adds are `op(vs1/rs1, vs2, vd)`
+        Vmacc(ops) => multiply_add(cpu, cfg, ops, |b, a, d, _| {
+            b.wrapping_mul(a).wrapping_add(d)
+        }),
+        Vnmsac(ops) => multiply_add(cpu, cfg, ops, |b, a, d, _| {
+            d.wrapping_sub(b.wrapping_mul(a))
+        }),
+        Vmadd(ops) => multiply_add(cpu, cfg, ops, |b, a, d, _| {
+            b.wrapping_mul(d).wrapping_add(a)
+        }),
+        Vnmsub(ops) => multiply_add(cpu, cfg, ops, |b, a, d, _| {
+            a.wrapping_sub(b.wrapping_mul(d))
+        }),
+
+        // NOTE: Widening instructions, `l.sew` is the narrow width
+        Vwaddu(ops) => widening(cpu, cfg, ops, false, |a, b, _| a.wrapping_add(b)),
+        Vwadd(ops) => widening(cpu, cfg, ops, false, |a, b, l| {
+            signed(a, l.sew).wrapping_add(signed(b, l.sew)) as u64
+        }),
+        Vwsubu(ops) => widening(cpu, cfg, ops, false, |a, b, _| a.wrapping_sub(b)),
+        Vwsub(ops) => widening(cpu, cfg, ops, false, |a, b, l| {
+            signed(a, l.sew).wrapping_sub(signed(b, l.sew)) as u64
+        }),
+        Vwadduw(ops) => widening(cpu, cfg, ops, true, |a, b, _| a.wrapping_add(b)),
+        Vwaddw(ops) => widening(cpu, cfg, ops, true, |a, b, l| {
+            a.wrapping_add_signed(signed(b, l.sew))
+        }),
+        Vwsubuw(ops) => widening(cpu, cfg, ops, true, |a, b, _| a.wrapping_sub(b)),
+        Vwsubw(ops) => widening(cpu, cfg, ops, true, |a, b, l| {
+            a.wrapping_add_signed(signed(b, l.sew).wrapping_neg())
+        }),
+        Vwmulu(ops) => widening(cpu, cfg, ops, false, |a, b, _| a.wrapping_mul(b)),
+        Vwmulsu(ops) => widening(cpu, cfg, ops, false, |a, b, l| {
+            signed(a, l.sew).wrapping_mul(b as i64) as u64
+        }),
+        Vwmul(ops) => widening(cpu, cfg, ops, false, |a, b, l| {
+            signed(a, l.sew).wrapping_mul(signed(b, l.sew)) as u64
+        }),
+        Vwmaccu(ops) => widening_multiply_add(cpu, cfg, ops, |b, a, d, _| {
+            b.wrapping_mul(a).wrapping_add(d)
+        }),
+        Vwmacc(ops) => widening_multiply_add(cpu, cfg, ops, |b, a, d, l| {
+            d.wrapping_add(signed(b, l.sew).wrapping_mul(signed(a, l.sew)) as u64)
+        }),
+        Vwmaccsu(ops) => widening_multiply_add(cpu, cfg, ops, |b, a, d, l| {
+            d.wrapping_add(signed(b, l.sew).wrapping_mul(a as i64) as u64)
+        }),
+        Vwmaccus(ops) => widening_multiply_add(cpu, cfg, ops, |b, a, d, l| {
+            d.wrapping_add((b as i64).wrapping_mul(signed(a, l.sew)) as u64)
+        }),
+
+        // NOTE: Narrowing shifts take the shift amount modulo 2*SEW
+        Vnsrl(ops) => narrowing(cpu, cfg, ops, |a, b, l| a >> (b % u64::from(2 * l.sew))),
+        Vnsra(ops) => narrowing(cpu, cfg, ops, |a, b, l| {
+            (signed(a, 2 * l.sew) >> (b % u64::from(2 * l.sew))) as u64
+        }),
+
+        // NOTE: Fixed point
+        Vsaddu(ops) => binary(cpu, cfg, ops, |a, b, l| {
+            l.clip_unsigned(i128::from(a) + i128::from(b))
+        }),
+        Vsadd(ops) => binary(cpu, cfg, ops, |a, b, l| {
+            l.clip_signed(i128::from(signed(a, l.sew)) + i128::from(signed(b, l.sew)))
+        }),
+        Vssubu(ops) => binary(cpu, cfg, ops, |a, b, l| {
+            l.clip_unsigned(i128::from(a) - i128::from(b))
+        }),
+        Vssub(ops) => binary(cpu, cfg, ops, |a, b, l| {
+            l.clip_signed(i128::from(signed(a, l.sew)) - i128::from(signed(b, l.sew)))
+        }),
+        Vaaddu(ops) => binary(cpu, cfg, ops, |a, b, l| {
+            l.round(i128::from(a) + i128::from(b), 1) as u64
+        }),
+        Vaadd(ops) => binary(cpu, cfg, ops, |a, b, l| {
+            l.round(
+                i128::from(signed(a, l.sew)) + i128::from(signed(b, l.sew)),
+                1,
+            ) as u64
+        }),
+        Vasubu(ops) => binary(cpu, cfg, ops, |a, b, l| {
+            l.round(i128::from(a) - i128::from(b), 1) as u64
+        }),
+        Vasub(ops) => binary(cpu, cfg, ops, |a, b, l| {
+            l.round(
+                i128::from(signed(a, l.sew)) - i128::from(signed(b, l.sew)),
+                1,
+            ) as u64
+        }),
+        Vsmul(ops) => binary(cpu, cfg, ops, |a, b, l| {
+            let product = i128::from(signed(a, l.sew)) * i128::from(signed(b, l.sew));
+            let rounded = l.round(product, u64::from(l.sew) - 1);
+            l.clip_signed(rounded)
+        }),
+        Vssrl(ops) => binary(cpu, cfg, ops, |a, b, l| {
+            l.round(i128::from(a), b % u64::from(l.sew)) as u64
+        }),
+        Vssra(ops) => binary(cpu, cfg, ops, |a, b, l| {
+            l.round(i128::from(signed(a, l.sew)), b % u64::from(l.sew)) as u64
+        }),
+        Vnclipu(ops) => narrowing(cpu, cfg, ops, |a, b, l| {
+            let rounded = l.round(i128::from(a), b % u64::from(2 * l.sew));
+            l.clip_unsigned(rounded)
+        }),
+        Vnclip(ops) => narrowing(cpu, cfg, ops, |a, b, l| {
+            let rounded = l.round(signed(a, 2 * l.sew).into(), b % u64::from(2 * l.sew));
+            l.clip_signed(rounded)
+        }),
+
+        // NOTE: Reductions, the accumulator starts as vs1[0]
+        Vredsum(ops) => reduce(cpu, cfg, ops, cfg.sew, |acc, e, _| acc.wrapping_add(e)),
+        Vredand(ops) => reduce(cpu, cfg, ops, cfg.sew, |acc, e, _| acc & e),
+        Vredor(ops) => reduce(cpu, cfg, ops, cfg.sew, |acc, e, _| acc | e),
+        Vredxor(ops) => reduce(cpu, cfg, ops, cfg.sew, |acc, e, _| acc ^ e),
+        Vredminu(ops) => reduce(cpu, cfg, ops, cfg.sew, |acc, e, _| acc.min(e)),
+        Vredmin(ops) => reduce(cpu, cfg, ops, cfg.sew, |acc, e, l| {
+            signed(acc, l.sew).min(signed(e, l.sew)) as u64
+        }),
+        Vredmaxu(ops) => reduce(cpu, cfg, ops, cfg.sew, |acc, e, _| acc.max(e)),
+        Vredmax(ops) => reduce(cpu, cfg, ops, cfg.sew, |acc, e, l| {
+            signed(acc, l.sew).max(signed(e, l.sew)) as u64
+        }),
+        Vwredsumu(ops) => reduce(cpu, cfg, ops, cfg.wide_sew()?, |acc, e, _| {
+            acc.wrapping_add(e)
+        }),
+        Vwredsum(ops) => reduce(cpu, cfg, ops, cfg.wide_sew()?, |acc, e, l| {
+            acc.wrapping_add_signed(signed(e, l.sew))
+        }),
+        Vfredusum(ops) | Vfredosum(ops) => {
+            float_sew!(cfg, float_reduce(cpu, cfg, ops, FloatOp::Add))
+        }
+        Vfredmin(ops) => float_sew!(cfg, float_reduce(cpu, cfg, ops, FloatOp::Min)),
+        Vfredmax(ops) => float_sew!(cfg, float_reduce(cpu, cfg, ops, FloatOp::Max)),
+        Vfwredusum(ops) | Vfwredosum(ops) => float_widening_reduce(cpu, cfg, ops),
+
+        // NOTE: Masks
+        Vmand(vd, vs2, vs1) => mask_logical(cpu, cfg, vd, vs2, vs1, |a, b| a & b),
+        Vmnand(vd, vs2, vs1) => mask_logical(cpu, cfg, vd, vs2, vs1, |a, b| !(a & b)),
+        Vmandn(vd, vs2, vs1) => mask_logical(cpu, cfg, vd, vs2, vs1, |a, b| a & !b),
+        Vmxor(vd, vs2, vs1) => mask_logical(cpu, cfg, vd, vs2, vs1, |a, b| a ^ b),
+        Vmor(vd, vs2, vs1) => mask_logical(cpu, cfg, vd, vs2, vs1, |a, b| a | b),
+        Vmnor(vd, vs2, vs1) => mask_logical(cpu, cfg, vd, vs2, vs1, |a, b| !(a | b)),
+        Vmorn(vd, vs2, vs1) => mask_logical(cpu, cfg, vd, vs2, vs1, |a, b| a | !b),
+        Vmxnor(vd, vs2, vs1) => mask_logical(cpu, cfg, vd, vs2, vs1, |a, b| !(a ^ b)),
+
+        Vcpop(rd, vs2, vm) => {
+            check_vstart(cfg)?;
+            let count = (0..cfg.vl)
+                .filter(|&i| active(cpu, vm, i) && cpu.vector.mask_bit(vs2, i))
+                .count();
+            cpu.registers[&rd] = count as u64;
+            Ok(())
+        }
+        Vfirst(rd, vs2, vm) => {
+            check_vstart(cfg)?;
+            let first = (0..cfg.vl).find(|&i| active(cpu, vm, i) && cpu.vector.mask_bit(vs2, i));
+            cpu.registers[&rd] = first.map_or(u64::MAX, |i| i as u64);
+            Ok(())
+        }
+        Vmsbf(vd, vs2, vm) => set_first(cpu, cfg, vd, vs2, vm, |before, first| before && !first),
+        Vmsif(vd, vs2, vm) => set_first(cpu, cfg, vd, vs2, vm, |before, _| before),
+        Vmsof(vd, vs2, vm) => set_first(cpu, cfg, vd, vs2, vm, |_, first| first),
+        Viota(vd, vs2, vm) => {
+            check_vstart(cfg)?;
+            check_group(vd, cfg.lmul)?;
+            check_mask(vd, vm)?;
+
+            let mut count = 0;
+            elementwise(cpu, cfg, vd, cfg.sew, vm, |cpu, i| {
+                let index = count;
+                count += u64::from(cpu.vector.mask_bit(vs2, i));
+                index
+            });
+            Ok(())
+        }
+        Vid(vd, vm) => {
+            check_group(vd, cfg.lmul)?;
+            check_mask(vd, vm)?;
+            elementwise(cpu, cfg, vd, cfg.sew, vm, |_, i| i as u64);
+            Ok(())
+        }
+
+        // NOTE: Permutations. The scalar moves ignore LMUL, and the ones to a scalar ignore vl
+        Vmvxs(rd, vs2) => {
+            let value = cfg.get(cpu, vs2, 0, cfg.sew);
+            cpu.registers[&rd] = sign_extend(value, cfg.sew as u8 * 8) as u64;
+            Ok(())
+        }
+        Vmvsx(vd, rs1) => {
+            if cfg.vstart < cfg.vl {
+                let value = cpu.registers[&rs1];
+                cfg.set(cpu, vd, 0, cfg.sew, value);
+            }
+            Ok(())
+        }
+        Vfmvfs(rd, vs2) => match cfg.sew {
+            4 => {
+                let value = f32::from_raw(cfg.get(cpu, vs2, 0, 4));
+                cpu.float_registers.write_f32(&rd, value);
+                Ok(())
+            }
+            8 => {
+                cpu.float_registers[&rd] = cfg.get(cpu, vs2, 0, 8);
+                Ok(())
+            }
+            _ => Err(Exception::IllegalInstruction),
+        },
+        Vfmvsf(vd, rs1) => {
+            check_float(cfg)?;
+            if cfg.vstart < cfg.vl {
+                let value = cfg.operand(cpu, VSrc::F(rs1), 0, cfg.sew);
+                cfg.set(cpu, vd, 0, cfg.sew, value);
+            }
+            Ok(())
+        }
+        Vfmvvf(vd, rs1) => {
+            check_float(cfg)?;
+            check_group(vd, cfg.lmul)?;
+            let value = cfg.operand(cpu, VSrc::F(rs1), 0, cfg.sew);
+            elementwise(cpu, cfg, vd, cfg.sew, true, |_, _| value);
+            Ok(())
+        }
+        Vfmerge(ops) => {
+            check_float(cfg)?;
+            execute_configured(&Vmerge(ops), cpu, cfg)
+        }
+
+        Vslideup(ops) => slide(cpu, cfg, ops, true),
+        Vslidedown(ops) => slide(cpu, cfg, ops, false),
+        Vslide1up(ops) => slide1(cpu, cfg, ops, true),
+        Vslide1down(ops) => slide1(cpu, cfg, ops, false),
+        Vfslide1up(ops) => {
+            check_float(cfg)?;
+            slide1(cpu, cfg, ops, true)
+        }
+        Vfslide1down(ops) => {
+            check_float(cfg)?;
+            slide1(cpu, cfg, ops, false)
+        }
+        Vrgather(ops) => gather(cpu, cfg, ops, cfg.sew),
+        Vrgatherei16(ops) => gather(cpu, cfg, ops, 2),
+        Vcompress(vd, vs2, vs1) => {
+            check_vstart(cfg)?;
+            check_group(vd, cfg.lmul)?;
+            check_group(vs2, cfg.lmul)?;
+
+            let packed: Vec<u64> = (0..cfg.vl)
+                .filter(|&i| cpu.vector.mask_bit(vs1, i))
+                .map(|i| cfg.get(cpu, vs2, i, cfg.sew))
+                .collect();
+            for (i, value) in packed.into_iter().enumerate() {
+                cfg.set(cpu, vd, i, cfg.sew, value);
+            }
+            Ok(())
+        }
+
+        // NOTE: Floating point, with the dynamic rounding mode
+        Vfadd(ops) => float_sew!(cfg, float_binary(cpu, cfg, ops, FloatOp::Add)),
+        Vfsub(ops) => float_sew!(cfg, float_binary(cpu, cfg, ops, FloatOp::Sub)),
+        Vfrsub(ops) => float_sew!(cfg, float_binary(cpu, cfg, ops, FloatOp::Rsub)),
+        Vfmul(ops) => float_sew!(cfg, float_binary(cpu, cfg, ops, FloatOp::Mul)),
+        Vfdiv(ops) => float_sew!(cfg, float_binary(cpu, cfg, ops, FloatOp::Div)),
+        Vfrdiv(ops) => float_sew!(cfg, float_binary(cpu, cfg, ops, FloatOp::Rdiv)),
+        Vfmin(ops) => float_sew!(cfg, float_binary(cpu, cfg, ops, FloatOp::Min)),
+        Vfmax(ops) => float_sew!(cfg, float_binary(cpu, cfg, ops, FloatOp::Max)),
+        Vfsgnj(ops) => float_sew!(cfg, float_binary(cpu, cfg, ops, FloatOp::Sgnj)),
+        Vfsgnjn(ops) => float_sew!(cfg, float_binary(cpu, cfg, ops, FloatOp::Sgnjn)),
+        Vfsgnjx(ops) => float_sew!(cfg, float_binary(cpu, cfg, ops, FloatOp::Sgnjx)),
+
+        Vfmacc(ops) => float_sew!(cfg, float_multiply_add(cpu, cfg, ops, FmaOp::Macc)),
+        Vfnmacc(ops) => float_sew!(cfg, float_multiply_add(cpu, cfg, ops, FmaOp::Nmacc)),
+        Vfmsac(ops) => float_sew!(cfg, float_multiply_add(cpu, cfg, ops, FmaOp::Msac)),
+        Vfnmsac(ops) => float_sew!(cfg, float_multiply_add(cpu, cfg, ops, FmaOp::Nmsac)),
+        Vfmadd(ops) => float_sew!(cfg, float_multiply_add(cpu, cfg, ops, FmaOp::Madd)),
+        Vfnmadd(ops) => float_sew!(cfg, float_multiply_add(cpu, cfg, ops, FmaOp::Nmadd)),
+        Vfmsub(ops) => float_sew!(cfg, float_multiply_add(cpu, cfg, ops, FmaOp::Msub)),
+        Vfnmsub(ops) => float_sew!(cfg, float_multiply_add(cpu, cfg, ops, FmaOp::Nmsub)),
+
+        Vfwadd(ops) => float_widening(cpu, cfg, ops, false, FloatOp::Add),
+        Vfwsub(ops) => float_widening(cpu, cfg, ops, false, FloatOp::Sub),
+        Vfwaddw(ops) => float_widening(cpu, cfg, ops, true, FloatOp::Add),
+        Vfwsubw(ops) => float_widening(cpu, cfg, ops, true, FloatOp::Sub),
+        Vfwmul(ops) => float_widening(cpu, cfg, ops, false, FloatOp::Mul),
+        Vfwmacc(ops) => float_widening_multiply_add(cpu, cfg, ops, FmaOp::Macc),
+        Vfwnmacc(ops) => float_widening_multiply_add(cpu, cfg, ops, FmaOp::Nmacc),
+        Vfwmsac(ops) => float_widening_multiply_add(cpu, cfg, ops, FmaOp::Msac),
+        Vfwnmsac(ops) => float_widening_multiply_add(cpu, cfg, ops, FmaOp::Nmsac),
+
+        Vmfeq(ops) => float_sew!(cfg, float_compare(cpu, cfg, ops, FloatCmp::Eq)),
+        Vmfne(ops) => float_sew!(cfg, float_compare(cpu, cfg, ops, FloatCmp::Ne)),
+        Vmflt(ops) => float_sew!(cfg, float_compare(cpu, cfg, ops, FloatCmp::Lt)),
+        Vmfle(ops) => float_sew!(cfg, float_compare(cpu, cfg, ops, FloatCmp::Le)),
+        Vmfgt(ops) => float_sew!(cfg, float_compare(cpu, cfg, ops, FloatCmp::Gt)),
+        Vmfge(ops) => float_sew!(cfg, float_compare(cpu, cfg, ops, FloatCmp::Ge)),
+
+        Vfsqrtv(vd, vs2, vm) => float_sew!(cfg, float_unary(cpu, cfg, vd, vs2, vm, Unary::Sqrt)),
+        Vfrsqrt7v(vd, vs2, vm) => {
+            float_sew!(cfg, float_unary(cpu, cfg, vd, vs2, vm, Unary::Rsqrt7))
+        }
+        Vfrec7v(vd, vs2, vm) => float_sew!(cfg, float_unary(cpu, cfg, vd, vs2, vm, Unary::Rec7)),
+        Vfclassv(vd, vs2, vm) => float_sew!(cfg, float_unary(cpu, cfg, vd, vs2, vm, Unary::Class)),
+
+        Vfcvtxufv(vd, vs2, vm) => convert(cpu, cfg, vd, vs2, vm, Conversion(Float, Uint, 0, Frm)),
+        Vfcvtxfv(vd, vs2, vm) => convert(cpu, cfg, vd, vs2, vm, Conversion(Float, Int, 0, Frm)),
+        Vfcvtfxuv(vd, vs2, vm) => convert(cpu, cfg, vd, vs2, vm, Conversion(Uint, Float, 0, Frm)),
+        Vfcvtfxv(vd, vs2, vm) => convert(cpu, cfg, vd, vs2, vm, Conversion(Int, Float, 0, Frm)),
+        Vfcvtrtzxufv(vd, vs2, vm) => {
+            convert(cpu, cfg, vd, vs2, vm, Conversion(Float, Uint, 0, Rtz))
+        }
+        Vfcvtrtzxfv(vd, vs2, vm) => convert(cpu, cfg, vd, vs2, vm, Conversion(Float, Int, 0, Rtz)),
+        Vfwcvtxufv(vd, vs2, vm) => convert(cpu, cfg, vd, vs2, vm, Conversion(Float, Uint, 1, Frm)),
+        Vfwcvtxfv(vd, vs2, vm) => convert(cpu, cfg, vd, vs2, vm, Conversion(Float, Int, 1, Frm)),
+        Vfwcvtfxuv(vd, vs2, vm) => convert(cpu, cfg, vd, vs2, vm, Conversion(Uint, Float, 1, Frm)),
+        Vfwcvtfxv(vd, vs2, vm) => convert(cpu, cfg, vd, vs2, vm, Conversion(Int, Float, 1, Frm)),
+        Vfwcvtffv(vd, vs2, vm) => convert(cpu, cfg, vd, vs2, vm, Conversion(Float, Float, 1, Frm)),
+        Vfwcvtrtzxufv(vd, vs2, vm) => {
+            convert(cpu, cfg, vd, vs2, vm, Conversion(Float, Uint, 1, Rtz))
+        }
+        Vfwcvtrtzxfv(vd, vs2, vm) => convert(cpu, cfg, vd, vs2, vm, Conversion(Float, Int, 1, Rtz)),
+        Vfncvtxufw(vd, vs2, vm) => convert(cpu, cfg, vd, vs2, vm, Conversion(Float, Uint, -1, Frm)),
+        Vfncvtxfw(vd, vs2, vm) => convert(cpu, cfg, vd, vs2, vm, Conversion(Float, Int, -1, Frm)),
+        Vfncvtfxuw(vd, vs2, vm) => convert(cpu, cfg, vd, vs2, vm, Conversion(Uint, Float, -1, Frm)),
+        Vfncvtfxw(vd, vs2, vm) => convert(cpu, cfg, vd, vs2, vm, Conversion(Int, Float, -1, Frm)),
+        Vfncvtffw(vd, vs2, vm) => convert(cpu, cfg, vd, vs2, vm, Conversion(Float, Float, -1, Frm)),
+        Vfncvtrodffw(vd, vs2, vm) => {
+            convert(cpu, cfg, vd, vs2, vm, Conversion(Float, Float, -1, Odd))
+        }
+        Vfncvtrtzxufw(vd, vs2, vm) => {
+            convert(cpu, cfg, vd, vs2, vm, Conversion(Float, Uint, -1, Rtz))
+        }
+        Vfncvtrtzxfw(vd, vs2, vm) => {
+            convert(cpu, cfg, vd, vs2, vm, Conversion(Float, Int, -1, Rtz))
+        }
+
+        _ => unreachable!("{ins:?} isn't a vector instruction"),
+    }
+}
+
+/// Reductions, `vcpop`, `vfirst`, `viota`, `vcompress` and `vmsbf` and friends can't be
+/// resumed, they need `vstart` to be 0
+fn check_vstart(cfg: &Config) -> Result<(), Exception> {
+    if cfg.vstart != 0 {
+        return Err(Exception::IllegalInstruction);
+    }
+
+    Ok(())
+}
+
+/// Masked instructions can't write v0 unless their result is a mask
+fn check_mask(vd: Reg, vm: bool) -> Result<(), Exception> {
+    if !vm && vd == 0 {
+        return Err(Exception::IllegalInstruction);
+    }
+
+    Ok(())
+}
+
+/// Float instructions need SEW to be 32 or 64
+fn check_float(cfg: &Config) -> Result<(), Exception> {
+    if !matches!(cfg.sew, 4 | 8) {
+        return Err(Exception::IllegalInstruction);
+    }
+
+    Ok(())
+}
+
+/// `value`'s low `bits` bits as a signed number
+fn signed(value: u64, bits: u32) -> i64 {
+    sign_extend(value, bits as u8)
+}
+
+/// What integer element operations get besides their operands
+struct Lane {
+    /// SEW in bits
+    sew: u32,
+    vxrm: u8,
+    /// Set when a fixed point result saturated, `vxsat` gets it afterwards
+    saturated: bool,
+}
+
+impl Lane {
+    /// Shifts `value` right by `shift` bits, rounding the way `vxrm` says
+    fn round(&self, value: i128, shift: u64) -> i128 {
+        if shift == 0 {
+            return value;
+        }
+
+        let bit = |n: u64| value >> n & 1 == 1;
+        let below = |n: u64| value & ((1 << n) - 1) != 0;
+        let increment = match self.vxrm {
+            // NOTE: rnu, rne, rdn and rod
+            0 => bit(shift - 1),
+            1 => bit(shift - 1) && (below(shift - 1) || bit(shift)),
+            2 => false,
+            _ => !bit(shift) && below(shift),
+        };
+
+        (value >> shift) + i128::from(increment)
+    }
+
+    fn clip_unsigned(&mut self, value: i128) -> u64 {
+        let max = (1 << self.sew) - 1;
+        self.clip(value, 0, max)
+    }
+
+    fn clip_signed(&mut self, value: i128) -> u64 {
+        let max = (1 << (self.sew - 1)) - 1;
+        self.clip(value, -max - 1, max)
+    }
+
+    fn clip(&mut self, value: i128, min: i128, max: i128) -> u64 {
+        if value < min || value > max {
+            self.saturated = true;
+        }
+
+        value.clamp(min, max) as u64
+    }
+}
+
+type IntOp = fn(u64, u64, &mut Lane) -> u64;
+
+/// `op(vs1/rs1, vs2, vd)`
+type MultiplyAddOp = fn(u64, u64, u64, &mut Lane) -> u64;
+
+/// `vd[i] = op(vs2[i], src[i])`, all SEW wide
+fn binary(cpu: &mut RV64GC, cfg: &Config, ops: VOperands, op: IntOp) -> Result<(), Exception> {
+    check_operands(&ops, cfg.lmul, cfg.lmul, cfg.lmul, false)?;
+
+    let mut lane = cfg.lane(cpu);
+    elementwise(cpu, cfg, ops.vd, cfg.sew, ops.vm, |cpu, i| {
+        let a = cfg.get(cpu, ops.vs2, i, cfg.sew);
+        op(a, cfg.operand(cpu, ops.src, i, cfg.sew), &mut lane)
+    });
+    cpu.vector.vxsat |= lane.saturated;
+
+    Ok(())
+}
+
+/// `vd.mask[i] = pred(vs2[i], src[i], SEW in bits)`
+fn compare(
+    cpu: &mut RV64GC,
+    cfg: &Config,
+    ops: VOperands,
+    pred: fn(u64, u64, u32) -> bool,
+) -> Result<(), Exception> {
+    check_operands(&ops, 0, cfg.lmul, cfg.lmul, true)?;
+
+    let sew = cfg.sew as u32 * 8;
+    maskwise(cpu, cfg, ops.vd, ops.vm, |cpu, i| {
+        let a = cfg.get(cpu, ops.vs2, i, cfg.sew);
+        pred(a, cfg.operand(cpu, ops.src, i, cfg.sew), sew)
+    });
+
+    Ok(())
+}
+
+/// `vadc`/`vsbc`, every body element gets its bit in v0 as the carry or borrow in
+fn with_carry(
+    cpu: &mut RV64GC,
+    cfg: &Config,
+    ops: VOperands,
+    subtract: bool,
+) -> Result<(), Exception> {
+    check_operands(&ops, cfg.lmul, cfg.lmul, cfg.lmul, false)?;
+
+    elementwise(cpu, cfg, ops.vd, cfg.sew, true, |cpu, i| {
+        let a = cfg.get(cpu, ops.vs2, i, cfg.sew);
+        let b = cfg.operand(cpu, ops.src, i, cfg.sew);
+        let carry = u64::from(cpu.vector.mask_bit(0, i));
+        if subtract {
+            a.wrapping_sub(b).wrapping_sub(carry)
+        } else {
+            a.wrapping_add(b).wrapping_add(carry)
+        }
+    });
+
+    Ok(())
+}
+
+/// `vmadc`/`vmsbc`, the carry or borrow out as a mask. v0 is the carry in, unless the
+/// instruction is "unmasked"
+fn carry_out(
+    cpu: &mut RV64GC,
+    cfg: &Config,
+    ops: VOperands,
+    subtract: bool,
+) -> Result<(), Exception> {
+    check_operands(&ops, 0, cfg.lmul, cfg.lmul, true)?;
+
+    let sew = cfg.sew * 8;
+    maskwise(cpu, cfg, ops.vd, true, |cpu, i| {
+        let a = u128::from(cfg.get(cpu, ops.vs2, i, cfg.sew));
+        let b = u128::from(cfg.operand(cpu, ops.src, i, cfg.sew));
+        let carry = u128::from(!ops.vm && cpu.vector.mask_bit(0, i));
+        if subtract {
+            a < b + carry
+        } else {
+            (a + b + carry) >> sew != 0
+        }
+    });
+
+    Ok(())
+}
+
+/// `vzext`/`vsext`, `vd[i]` is `vs2[i]` extended from SEW/`factor` bits
+fn extend(
+    cpu: &mut RV64GC,
+    cfg: &Config,
+    vd: Reg,
+    vs2: Reg,
+    vm: bool,
+    factor: usize,
+    sign: bool,
+) -> Result<(), Exception> {
+    let eew = cfg.sew / factor;
+    if eew == 0 {
+        return Err(Exception::IllegalInstruction);
+    }
+
+    check_group(vd, cfg.lmul)?;
+    check_group(vs2, cfg.emul(eew))?;
+    check_mask(vd, vm)?;
+
+    elementwise(cpu, cfg, vd, cfg.sew, vm, |cpu, i| {
+        let value = cfg.get(cpu, vs2, i, eew);
+        if sign {
+            sign_extend(value, eew as u8 * 8) as u64
+        } else {
+            value
+        }
+    });
+
+    Ok(())
+}
+
+/// `vd[i] = op(src[i], vs2[i], vd[i])`
+fn multiply_add(
+    cpu: &mut RV64GC,
+    cfg: &Config,
+    ops: VOperands,
+    op: MultiplyAddOp,
+) -> Result<(), Exception> {
+    check_operands(&ops, cfg.lmul, cfg.lmul, cfg.lmul, false)?;
+
+    let mut lane = cfg.lane(cpu);
+    elementwise(cpu, cfg, ops.vd, cfg.sew, ops.vm, |cpu, i| {
+        let b = cfg.operand(cpu, ops.src, i, cfg.sew);
+        let a = cfg.get(cpu, ops.vs2, i, cfg.sew);
+        op(b, a, cfg.get(cpu, ops.vd, i, cfg.sew), &mut lane)
+    });
+
+    Ok(())
+}
+
+/// `vd[i] = op(vs2[i], src[i])` into 2*SEW elements, `vs2` is 2*SEW wide too for the `.w`
+/// forms
+fn widening(
+    cpu: &mut RV64GC,
+    cfg: &Config,
+    ops: VOperands,
+    wide_vs2: bool,
+    op: IntOp,
+) -> Result<(), Exception> {
+    let wide = cfg.wide()?;
+    let vs2_eew = if wide_vs2 { wide } else { cfg.sew };
+    check_operands(&ops, cfg.lmul + 1, cfg.emul(vs2_eew), cfg.lmul, false)?;
+
+    let mut lane = cfg.lane(cpu);
+    elementwise(cpu, cfg, ops.vd, wide, ops.vm, |cpu, i| {
+        let a = cfg.get(cpu, ops.vs2, i, vs2_eew);
+        op(a, cfg.operand(cpu, ops.src, i, cfg.sew), &mut lane)
+    });
+
+    Ok(())
+}
+
+/// `vd[i] = op(src[i], vs2[i], vd[i])` with a 2*SEW `vd`
+fn widening_multiply_add(
+    cpu: &mut RV64GC,
+    cfg: &Config,
+    ops: VOperands,
+    op: MultiplyAddOp,
+) -> Result<(), Exception> {
+    let wide = cfg.wide()?;
+    check_operands(&ops, cfg.lmul + 1, cfg.lmul, cfg.lmul, false)?;
+
+    let mut lane = cfg.lane(cpu);
+    elementwise(cpu, cfg, ops.vd, wide, ops.vm, |cpu, i| {
+        let b = cfg.operand(cpu, ops.src, i, cfg.sew);
+        let a = cfg.get(cpu, ops.vs2, i, cfg.sew);
+        op(b, a, cfg.get(cpu, ops.vd, i, wide), &mut lane)
+    });
+
+    Ok(())
+}
+
+/// `vd[i] = op(vs2[i], src[i])` from 2*SEW `vs2` elements into SEW ones
+fn narrowing(cpu: &mut RV64GC, cfg: &Config, ops: VOperands, op: IntOp) -> Result<(), Exception> {
+    let wide = cfg.wide()?;
+    check_operands(&ops, cfg.lmul, cfg.lmul + 1, cfg.lmul, false)?;
+
+    let mut lane = cfg.lane(cpu);
+    elementwise(cpu, cfg, ops.vd, cfg.sew, ops.vm, |cpu, i| {
+        let a = cfg.get(cpu, ops.vs2, i, wide);
+        op(a, cfg.operand(cpu, ops.src, i, cfg.sew), &mut lane)
+    });
+    cpu.vector.vxsat |= lane.saturated;
+
+    Ok(())
+}
+
+/// `vd[0] = op(...op(op(vs1[0], vs2[0]), vs2[1])..., vs2[vl - 1])` over the active
+/// elements, `vd[0]` and `vs1[0]` are `eew` bytes wide
+fn reduce(
+    cpu: &mut RV64GC,
+    cfg: &Config,
+    ops: VOperands,
+    eew: usize,
+    op: IntOp,
+) -> Result<(), Exception> {
+    check_vstart(cfg)?;
+    check_group(ops.vs2, cfg.lmul)?;
+    let VSrc::V(vs1) = ops.src else {
+        return Err(Exception::IllegalInstruction);
+    };
+
+    if cfg.vl == 0 {
+        return Ok(());
+    }
+
+    let mut lane = cfg.lane(cpu);
+    let mut acc = cfg.get(cpu, vs1, 0, eew);
+    for i in 0..cfg.vl {
+        if active(cpu, ops.vm, i) {
+            acc = op(acc, cfg.get(cpu, ops.vs2, i, cfg.sew), &mut lane);
+        }
+    }
+    cfg.set(cpu, ops.vd, 0, eew, acc);
+
+    Ok(())
+}
+
+/// The mask logical instructions, `vd.mask[i] = op(vs2.mask[i], vs1.mask[i])`
+fn mask_logical(
+    cpu: &mut RV64GC,
+    cfg: &Config,
+    vd: Reg,
+    vs2: Reg,
+    vs1: Reg,
+    op: fn(bool, bool) -> bool,
+) -> Result<(), Exception> {
+    maskwise(cpu, cfg, vd, true, |cpu, i| {
+        op(cpu.vector.mask_bit(vs2, i), cpu.vector.mask_bit(vs1, i))
+    });
+
+    Ok(())
+}
+
+/// `vmsbf`, `vmsif` and `vmsof`: `op(up to the first set bit of vs2, is the first set bit)`
+fn set_first(
+    cpu: &mut RV64GC,
+    cfg: &Config,
+    vd: Reg,
+    vs2: Reg,
+    vm: bool,
+    op: fn(bool, bool) -> bool,
+) -> Result<(), Exception> {
+    check_vstart(cfg)?;
+    check_mask(vd, vm)?;
+    if vd == vs2 {
+        return Err(Exception::IllegalInstruction);
+    }
+
+    let mut found = false;
+    maskwise(cpu, cfg, vd, vm, |cpu, i| {
+        let before = !found;
+        let first = before && cpu.vector.mask_bit(vs2, i);
+        found |= first;
+        op(before, first)
+    });
+
+    Ok(())
+}
+
+/// The offset or index a slide or gather takes from `rs1` or its unsigned immediate
+fn scalar_index(cpu: &RV64GC, src: VSrc) -> u64 {
+    match src {
+        VSrc::X(rs1) => cpu.registers[&rs1],
+        VSrc::I(uimm) => uimm as u64,
+        VSrc::V(_) | VSrc::F(_) => unreachable!("slides and gathers take x registers"),
+    }
+}
+
+/// `vslideup`/`vslidedown` by `rs1` or the immediate. Sliding up leaves the elements below
+/// the offset alone, sliding down reads zeros past VLMAX
+fn slide(cpu: &mut RV64GC, cfg: &Config, ops: VOperands, up: bool) -> Result<(), Exception> {
+    check_operands(&ops, cfg.lmul, cfg.lmul, cfg.lmul, false)?;
+
+    let offset = usize::try_from(scalar_index(cpu, ops.src)).unwrap_or(usize::MAX);
+    let vlmax = cfg.vlmax();
+    if up {
+        let cfg = Config {
+            vstart: cfg.vstart.max(offset),
+            ..*cfg
+        };
+        elementwise(cpu, &cfg, ops.vd, cfg.sew, ops.vm, |cpu, i| {
+            cfg.get(cpu, ops.vs2, i - offset, cfg.sew)
+        });
+    } else {
+        elementwise(cpu, cfg, ops.vd, cfg.sew, ops.vm, |cpu, i| {
+            match i.checked_add(offset) {
+                Some(src) if src < vlmax => cfg.get(cpu, ops.vs2, src, cfg.sew),
+                _ => 0,
+            }
+        });
+    }
+
+    Ok(())
+}
+
+/// `vslide1up`/`vslide1down` and their float versions, which shift in the scalar
+fn slide1(cpu: &mut RV64GC, cfg: &Config, ops: VOperands, up: bool) -> Result<(), Exception> {
+    check_operands(&ops, cfg.lmul, cfg.lmul, cfg.lmul, false)?;
+
+    let scalar = cfg.operand(cpu, ops.src, 0, cfg.sew);
+    elementwise(cpu, cfg, ops.vd, cfg.sew, ops.vm, |cpu, i| {
+        if up {
+            match i {
+                0 => scalar,
+                _ => cfg.get(cpu, ops.vs2, i - 1, cfg.sew),
+            }
+        } else if i + 1 < cfg.vl {
+            cfg.get(cpu, ops.vs2, i + 1, cfg.sew)
+        } else {
+            scalar
+        }
+    });
+
+    Ok(())
+}
+
+/// `vrgather`, `vd[i] = vs2[index]` or 0 past VLMAX. `vs1`'s indices are `index_eew` bytes
+/// wide, which is 2 for `vrgatherei16`
+fn gather(
+    cpu: &mut RV64GC,
+    cfg: &Config,
+    ops: VOperands,
+    index_eew: usize,
+) -> Result<(), Exception> {
+    check_operands(&ops, cfg.lmul, cfg.lmul, cfg.emul(index_eew), false)?;
+
+    let vlmax = cfg.vlmax() as u64;
+    elementwise(cpu, cfg, ops.vd, cfg.sew, ops.vm, |cpu, i| {
+        let index = match ops.src {
+            VSrc::V(vs1) => cfg.get(cpu, vs1, i, index_eew),
+            src => scalar_index(cpu, src),
+        };
+
+        if index < vlmax {
+            cfg.get(cpu, ops.vs2, index as usize, cfg.sew)
+        } else {
+            0
+        }
+    });
+
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy)]
+enum FloatOp {
+    Add,
+    Sub,
+    Rsub,
+    Mul,
+    Div,
+    Rdiv,
+    Min,
+    Max,
+    Sgnj,
+    Sgnjn,
+    Sgnjx,
+}
+
+impl FloatOp {
+    /// `a op b`, with `a` from `vs2` and `b` from `vs1`/`rs1`
+    fn apply<F: FloatExtends>(self, a: F, b: F, rm: RoundingMode, fcsr: &mut FCSR) -> F {
+        let sign = 1 << (F::EXP_BITS + F::FRAC_BITS);
+        let (a_bits, b_bits) = (a.to_raw(), b.to_raw());
+
+        match self {
+            FloatOp::Add => softfloat::add(a, b, rm, fcsr),
+            FloatOp::Sub => softfloat::sub(a, b, rm, fcsr),
+            FloatOp::Rsub => softfloat::sub(b, a, rm, fcsr),
+            FloatOp::Mul => softfloat::mul(a, b, rm, fcsr),
+            FloatOp::Div => softfloat::div(a, b, rm, fcsr),
+            FloatOp::Rdiv => softfloat::div(b, a, rm, fcsr),
+            FloatOp::Min => fcsr::min_max(a, b, false, fcsr),
+            FloatOp::Max => fcsr::min_max(a, b, true, fcsr),
+            FloatOp::Sgnj => F::from_raw(a_bits & !sign | b_bits & sign),
+            FloatOp::Sgnjn => F::from_raw(a_bits & !sign | !b_bits & sign),
+            FloatOp::Sgnjx => F::from_raw(a_bits ^ b_bits & sign),
+        }
+    }
+}
+
+/// The float multiply-adds, `op(vs1/rs1, vs2, vd)` like the integer ones
+#[derive(Debug, Clone, Copy)]
+enum FmaOp {
+    Macc,
+    Nmacc,
+    Msac,
+    Nmsac,
+    Madd,
+    Nmadd,
+    Msub,
+    Nmsub,
+}
+
+impl FmaOp {
+    fn apply<F: FloatExtends>(self, b: F, a: F, d: F, rm: RoundingMode, fcsr: &mut FCSR) -> F {
+        match self {
+            FmaOp::Macc => softfloat::mul_add(b, a, d, rm, fcsr),
+            FmaOp::Nmacc => softfloat::neg_mul_sub(b, a, d, rm, fcsr),
+            FmaOp::Msac => softfloat::mul_sub(b, a, d, rm, fcsr),
+            FmaOp::Nmsac => softfloat::neg_mul_add(b, a, d, rm, fcsr),
+            FmaOp::Madd => softfloat::mul_add(b, d, a, rm, fcsr),
+            FmaOp::Nmadd => softfloat::neg_mul_sub(b, d, a, rm, fcsr),
+            FmaOp::Msub => softfloat::mul_sub(b, d, a, rm, fcsr),
+            FmaOp::Nmsub => softfloat::neg_mul_add(b, d, a, rm, fcsr),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum FloatCmp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl FloatCmp {
+    /// Equality is quiet, like `feq`, and the orderings signaling, like `flt` and `fle`
+    fn apply<F: FloatExtends>(self, a: F, b: F, fcsr: &mut FCSR) -> bool {
+        use std::cmp::Ordering::*;
+
+        match self {
+            FloatCmp::Eq => fcsr::compare(a, b, false, fcsr) == Some(Equal),
+            FloatCmp::Ne => fcsr::compare(a, b, false, fcsr) != Some(Equal),
+            FloatCmp::Lt => fcsr::compare(a, b, true, fcsr) == Some(Less),
+            FloatCmp::Le => matches!(fcsr::compare(a, b, true, fcsr), Some(Less | Equal)),
+            FloatCmp::Gt => fcsr::compare(a, b, true, fcsr) == Some(Greater),
+            FloatCmp::Ge => matches!(fcsr::compare(a, b, true, fcsr), Some(Greater | Equal)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Unary {
+    Sqrt,
+    Rsqrt7,
+    Rec7,
+    Class,
+}
+
+/// `vd[i] = vs2[i] op src[i]`
+fn float_binary<F: FloatExtends>(
+    cpu: &mut RV64GC,
+    cfg: &Config,
+    ops: VOperands,
+    op: FloatOp,
+) -> Result<(), Exception> {
+    check_operands(&ops, cfg.lmul, cfg.lmul, cfg.lmul, false)?;
+
+    let eew = size_of::<F>();
+    let rm = cpu.fcsr.frm;
+    elementwise(cpu, cfg, ops.vd, eew, ops.vm, |cpu, i| {
+        let a = F::from_raw(cfg.get(cpu, ops.vs2, i, eew));
+        let b = F::from_raw(cfg.operand(cpu, ops.src, i, eew));
+        op.apply(a, b, rm, &mut cpu.fcsr).to_raw()
+    });
+
+    Ok(())
+}
+
+/// `vd[i] = op(src[i], vs2[i], vd[i])`
+fn float_multiply_add<F: FloatExtends>(
+    cpu: &mut RV64GC,
+    cfg: &Config,
+    ops: VOperands,
+    op: FmaOp,
+) -> Result<(), Exception> {
+    check_operands(&ops, cfg.lmul, cfg.lmul, cfg.lmul, false)?;
+
+    let eew = size_of::<F>();
+    let rm = cpu.fcsr.frm;
+    elementwise(cpu, cfg, ops.vd, eew, ops.vm, |cpu, i| {
+        let b = F::from_raw(cfg.operand(cpu, ops.src, i, eew));
+        let a = F::from_raw(cfg.get(cpu, ops.vs2, i, eew));
+        let d = F::from_raw(cfg.get(cpu, ops.vd, i, eew));
+        op.apply(b, a, d, rm, &mut cpu.fcsr).to_raw()
+    });
+
+    Ok(())
+}
+
+/// Widening float instructions go from singles to doubles
+fn check_float_widening(cfg: &Config) -> Result<(), Exception> {
+    if cfg.sew != 4 {
+        return Err(Exception::IllegalInstruction);
+    }
+
+    cfg.wide().map(|_| ())
+}
+
+/// Singles widen exactly, only signaling NaNs raise a flag
+fn widen(bits: u64, fcsr: &mut FCSR) -> f64 {
+    softfloat::convert(f32::from_raw(bits), RoundingMode::Rne, fcsr)
+}
+
+/// [`float_binary`] with double results, `vs2` is already double for the `.w` forms
+fn float_widening(
+    cpu: &mut RV64GC,
+    cfg: &Config,
+    ops: VOperands,
+    wide_vs2: bool,
+    op: FloatOp,
+) -> Result<(), Exception> {
+    check_float_widening(cfg)?;
+    let vs2_emul = if wide_vs2 { cfg.lmul + 1 } else { cfg.lmul };
+    check_operands(&ops, cfg.lmul + 1, vs2_emul, cfg.lmul, false)?;
+
+    let rm = cpu.fcsr.frm;
+    elementwise(cpu, cfg, ops.vd, 8, ops.vm, |cpu, i| {
+        let a = if wide_vs2 {
+            f64::from_raw(cfg.get(cpu, ops.vs2, i, 8))
+        } else {
+            widen(cfg.get(cpu, ops.vs2, i, 4), &mut cpu.fcsr)
+        };
+        let b = widen(cfg.operand(cpu, ops.src, i, 4), &mut cpu.fcsr);
+        op.apply(a, b, rm, &mut cpu.fcsr).to_raw()
+    });
+
+    Ok(())
+}
+
+/// [`float_multiply_add`] with a double `vd`
+fn float_widening_multiply_add(
+    cpu: &mut RV64GC,
+    cfg: &Config,
+    ops: VOperands,
+    op: FmaOp,
+) -> Result<(), Exception> {
+    check_float_widening(cfg)?;
+    check_operands(&ops, cfg.lmul + 1, cfg.lmul, cfg.lmul, false)?;
+
+    let rm = cpu.fcsr.frm;
+    elementwise(cpu, cfg, ops.vd, 8, ops.vm, |cpu, i| {
+        let b = widen(cfg.operand(cpu, ops.src, i, 4), &mut cpu.fcsr);
+        let a = widen(cfg.get(cpu, ops.vs2, i, 4), &mut cpu.fcsr);
+        let d = f64::from_raw(cfg.get(cpu, ops.vd, i, 8));
+        op.apply(b, a, d, rm, &mut cpu.fcsr).to_raw()
+    });
+
+    Ok(())
+}
+
+/// `vd.mask[i] = vs2[i] op src[i]`
+fn float_compare<F: FloatExtends>(
+    cpu: &mut RV64GC,
+    cfg: &Config,
+    ops: VOperands,
+    op: FloatCmp,
+) -> Result<(), Exception> {
+    check_operands(&ops, 0, cfg.lmul, cfg.lmul, true)?;
+
+    let eew = size_of::<F>();
+    maskwise(cpu, cfg, ops.vd, ops.vm, |cpu, i| {
+        let a = F::from_raw(cfg.get(cpu, ops.vs2, i, eew));
+        let b = F::from_raw(cfg.operand(cpu, ops.src, i, eew));
+        op.apply(a, b, &mut cpu.fcsr)
+    });
+
+    Ok(())
+}
+
+fn float_unary<F: FloatExtends>(
+    cpu: &mut RV64GC,
+    cfg: &Config,
+    vd: Reg,
+    vs2: Reg,
+    vm: bool,
+    op: Unary,
+) -> Result<(), Exception> {
+    check_group(vd, cfg.lmul)?;
+    check_group(vs2, cfg.lmul)?;
+    check_mask(vd, vm)?;
+
+    let eew = size_of::<F>();
+    let rm = cpu.fcsr.frm;
+    elementwise(cpu, cfg, vd, eew, vm, |cpu, i| {
+        let value = F::from_raw(cfg.get(cpu, vs2, i, eew));
+        match op {
+            Unary::Sqrt => softfloat::sqrt(value, rm, &mut cpu.fcsr).to_raw(),
+            Unary::Rsqrt7 => rsqrt7(value, &mut cpu.fcsr).to_raw(),
+            Unary::Rec7 => rec7(value, rm, &mut cpu.fcsr).to_raw(),
+            Unary::Class => fcsr::classify(value).into(),
+        }
+    });
+
+    Ok(())
+}
+
+/// The float reductions. They always go in element order, which is one of the orders the
+/// unordered sum may use
+fn float_reduce<F: FloatExtends>(
+    cpu: &mut RV64GC,
+    cfg: &Config,
+    ops: VOperands,
+    op: FloatOp,
+) -> Result<(), Exception> {
+    check_vstart(cfg)?;
+    check_group(ops.vs2, cfg.lmul)?;
+    let VSrc::V(vs1) = ops.src else {
+        return Err(Exception::IllegalInstruction);
+    };
+
+    if cfg.vl == 0 {
+        return Ok(());
+    }
+
+    let eew = size_of::<F>();
+    let rm = cpu.fcsr.frm;
+    let mut acc = F::from_raw(cfg.get(cpu, vs1, 0, eew));
+    for i in 0..cfg.vl {
+        if active(cpu, ops.vm, i) {
+            let value = F::from_raw(cfg.get(cpu, ops.vs2, i, eew));
+            acc = op.apply(acc, value, rm, &mut cpu.fcsr);
+        }
+    }
+    cfg.set(cpu, ops.vd, 0, eew, acc.to_raw());
+
+    Ok(())
+}
+
+/// `vfwredusum`/`vfwredosum`, singles summed into a double
+fn float_widening_reduce(cpu: &mut RV64GC, cfg: &Config, ops: VOperands) -> Result<(), Exception> {
+    if cfg.sew != 4 {
+        return Err(Exception::IllegalInstruction);
+    }
+
+    check_vstart(cfg)?;
+    check_group(ops.vs2, cfg.lmul)?;
+    let VSrc::V(vs1) = ops.src else {
+        return Err(Exception::IllegalInstruction);
+    };
+
+    if cfg.vl == 0 {
+        return Ok(());
+    }
+
+    let rm = cpu.fcsr.frm;
+    let mut acc = f64::from_raw(cfg.get(cpu, vs1, 0, 8));
+    for i in 0..cfg.vl {
+        if active(cpu, ops.vm, i) {
+            let value = widen(cfg.get(cpu, ops.vs2, i, 4), &mut cpu.fcsr);
+            acc = softfloat::add(acc, value, rm, &mut cpu.fcsr);
+        }
+    }
+    cfg.set(cpu, ops.vd, 0, 8, acc.to_raw());
+
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Num {
+    Float,
+    Int,
+    Uint,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Rounding {
+    /// The dynamic rounding mode
+    Frm,
+    Rtz,
+    /// Round to odd, only `vfncvt.rod.f.f.w` has it
+    Odd,
+}
+
+/// A conversion from `vs2`'s kind of number to `vd`'s, the `i32` is log2 of how much wider
+/// `vd`'s elements are
+#[derive(Debug, Clone, Copy)]
+struct Conversion(Num, Num, i32, Rounding);
+
+impl Conversion {
+    /// Converts `value` from `src` bytes to `dst` bytes
+    fn element(
+        &self,
+        value: u64,
+        src: usize,
+        dst: usize,
+        rm: RoundingMode,
+        fcsr: &mut FCSR,
+    ) -> u64 {
+        let Conversion(from, to, _, rounding) = *self;
+
+        match (from, to) {
+            (Num::Float, Num::Float) if src == 4 => widen(value, fcsr).to_raw(),
+            (Num::Float, Num::Float) if rounding == Rounding::Odd => {
+                // NOTE: Round to odd truncates, and sets the lowest bit when that was inexact
+                let flags = fcsr.fflags();
+                fcsr.set_fflags(0);
+                let narrowed: f32 =
+                    softfloat::convert(f64::from_raw(value), RoundingMode::Rtz, fcsr);
+                let inexact = fcsr.fflags() & FCSR::NX != 0;
+                fcsr.set_fflags(flags | fcsr.fflags());
+                narrowed.to_raw() | u64::from(inexact)
+            }
+            (Num::Float, Num::Float) => {
+                softfloat::convert::<f64, f32>(f64::from_raw(value), rm, fcsr).to_raw()
+            }
+            (Num::Float, int) => {
+                let float = if src == 4 {
+                    f64::from(f32::from_raw(value))
+                } else {
+                    f64::from_raw(value)
+                };
+                let bits = dst * 8;
+                let (min, max) = if int == Num::Int {
+                    (-(1 << (bits - 1)), (1 << (bits - 1)) - 1)
+                } else {
+                    (0, (1 << bits) - 1)
+                };
+                fcsr::to_integer(float, rm, min, max, fcsr) as u64
+            }
+            (int, _) => {
+                let int = if int == Num::Int {
+                    i128::from(sign_extend(value, src as u8 * 8))
+                } else {
+                    i128::from(value)
+                };
+                if dst == 4 {
+                    softfloat::from_int::<f32>(int, rm, fcsr).to_raw()
+                } else {
+                    softfloat::from_int::<f64>(int, rm, fcsr).to_raw()
+                }
+            }
+        }
+    }
+}
+
+/// `vfcvt`, `vfwcvt` and `vfncvt`
+fn convert(
+    cpu: &mut RV64GC,
+    cfg: &Config,
+    vd: Reg,
+    vs2: Reg,
+    vm: bool,
+    conversion: Conversion,
+) -> Result<(), Exception> {
+    let Conversion(from, to, scale, rounding) = conversion;
+    let (src, dst) = match scale {
+        0 => (cfg.sew, cfg.sew),
+        1 => (cfg.sew, cfg.wide()?),
+        _ => (cfg.wide()?, cfg.sew),
+    };
+
+    if (from == Num::Float && !matches!(src, 4 | 8)) || (to == Num::Float && !matches!(dst, 4 | 8))
+    {
+        return Err(Exception::IllegalInstruction);
+    }
+
+    check_group(vd, cfg.emul(dst))?;
+    check_group(vs2, cfg.emul(src))?;
+    check_mask(vd, vm)?;
+
+    let rm = match rounding {
+        Rounding::Frm => cpu.fcsr.frm,
+        Rounding::Rtz | Rounding::Odd => RoundingMode::Rtz,
+    };
+    elementwise(cpu, cfg, vd, dst, vm, |cpu, i| {
+        let value = cfg.get(cpu, vs2, i, src);
+        conversion.element(value, src, dst, rm, &mut cpu.fcsr)
+    });
+
+    Ok(())
+}
+
+/// `vfrec7`'s table, the 7 bit significand of 1/x for the top 7 bits of x's
+#[rustfmt::skip]
+const REC7: [u8; 128] = [
+    127, 125, 123, 121, 119, 117, 116, 114, 112, 110, 109, 107, 105, 104, 102, 100,
+    99, 97, 96, 94, 93, 91, 90, 88, 87, 85, 84, 83, 81, 80, 79, 77,
+    76, 75, 74, 72, 71, 70, 69, 68, 66, 65, 64, 63, 62, 61, 60, 59,
+    58, 57, 56, 55, 54, 53, 52, 51, 50, 49, 48, 47, 46, 45, 44, 43,
+    42, 41, 40, 40, 39, 38, 37, 36, 35, 35, 34, 33, 32, 31, 31, 30,
+    29, 28, 28, 27, 26, 25, 25, 24, 23, 23, 22, 21, 21, 20, 19, 19,
+    18, 17, 17, 16, 15, 15, 14, 14, 13, 12, 12, 11, 11, 10, 9, 9,
+    8, 8, 7, 7, 6, 5, 5, 4, 4, 3, 3, 2, 2, 1, 1, 0,
+];
+
+/// `vfrsqrt7`'s table, indexed by the low bit of x's exponent and the top 6 bits of its
+/// significand
+#[rustfmt::skip]
+const RSQRT7: [u8; 128] = [
+    52, 51, 50, 48, 47, 46, 44, 43, 42, 41, 40, 39, 38, 36, 35, 34,
+    33, 32, 31, 30, 30, 29, 28, 27, 26, 25, 24, 23, 23, 22, 21, 20,
+    19, 19, 18, 17, 16, 16, 15, 14, 14, 13, 12, 12, 11, 10, 10, 9,
+    9, 8, 7, 7, 6, 6, 5, 4, 4, 3, 3, 2, 2, 1, 1, 0,
+    127, 125, 123, 121, 119, 118, 116, 114, 113, 111, 109, 108, 106, 105, 103, 102,
+    100, 99, 97, 96, 95, 93, 92, 91, 90, 88, 87, 86, 85, 84, 83, 82,
+    80, 79, 78, 77, 76, 75, 74, 73, 72, 71, 70, 70, 69, 68, 67, 66,
+    65, 64, 63, 63, 62, 61, 60, 59, 59, 58, 57, 56, 56, 55, 54, 53,
+];
+
+/// The biased exponent and fraction of a finite nonzero `value`. Subnormals are normalized,
+/// their exponent goes to 0 or below
+fn normalize<F: FloatExtends>(value: F) -> (i64, u64) {
+    let bits = value.to_raw();
+    let frac_mask = (1 << F::FRAC_BITS) - 1;
+    let mut exp = (bits >> F::FRAC_BITS & ((1 << F::EXP_BITS) - 1)) as i64;
+    let mut sig = bits & frac_mask;
+
+    if exp == 0 {
+        while sig >> F::FRAC_BITS == 0 {
+            sig <<= 1;
+            exp -= 1;
+        }
+        sig &= frac_mask;
+        exp += 1;
+    }
+
+    (exp, sig)
+}
+
+/// `vfrec7`, 1/x to 7 bits
+fn rec7<F: FloatExtends>(value: F, rm: RoundingMode, fcsr: &mut FCSR) -> F {
+    let sign = value.to_raw() & 1 << (F::EXP_BITS + F::FRAC_BITS);
+    let infinity = ((1 << F::EXP_BITS) - 1) << F::FRAC_BITS;
+
+    if value.is_nan() {
+        if value.is_snan() {
+            fcsr.set_flag(FCSR::NV);
+        }
+        return F::CANONICAL_NAN;
+    }
+
+    if value.is_infinite() {
+        return F::from_raw(sign);
+    }
+
+    if value.is_zero() {
+        fcsr.set_flag(FCSR::DZ);
+        return F::from_raw(sign | infinity);
+    }
+
+    let (exp, sig) = normalize(value);
+    let bias = (1 << (F::EXP_BITS - 1)) - 1;
+
+    // NOTE: 1/x overflows for the tiniest subnormals, it's infinity or the largest finite
+    // value depending on which way it's rounded
+    if exp < -1 {
+        fcsr.set_flag(FCSR::OF | FCSR::NX);
+        let to_infinity = match rm {
+            RoundingMode::Rne | RoundingMode::Rmm => true,
+            RoundingMode::Rtz => false,
+            RoundingMode::Rdn => sign != 0,
+            RoundingMode::Rup => sign == 0,
+        };
+        return F::from_raw(sign | if to_infinity { infinity } else { infinity - 1 });
+    }
+
+    let mut out_exp = 2 * bias - 1 - exp;
+    let mut out_sig = u64::from(REC7[(sig >> (F::FRAC_BITS - 7)) as usize]) << (F::FRAC_BITS - 7);
+    if out_exp <= 0 {
+        out_sig = (out_sig | 1 << F::FRAC_BITS) >> (1 - out_exp);
+        out_exp = 0;
+    }
+
+    F::from_raw(sign | (out_exp as u64) << F::FRAC_BITS | out_sig)
+}
+
+/// `vfrsqrt7`, 1/sqrt(x) to 7 bits
+fn rsqrt7<F: FloatExtends>(value: F, fcsr: &mut FCSR) -> F {
+    let sign = value.to_raw() & 1 << (F::EXP_BITS + F::FRAC_BITS);
+    let infinity = ((1 << F::EXP_BITS) - 1) << F::FRAC_BITS;
+
+    if value.is_zero() {
+        fcsr.set_flag(FCSR::DZ);
+        return F::from_raw(sign | infinity);
+    }
+
+    if value.is_nan() || sign != 0 {
+        if value.is_snan() || !value.is_nan() {
+            fcsr.set_flag(FCSR::NV);
+        }
+        return F::CANONICAL_NAN;
+    }
+
+    if value.is_infinite() {
+        return F::from_raw(0);
+    }
+
+    let (exp, sig) = normalize(value);
+    let bias = (1 << (F::EXP_BITS - 1)) - 1;
+    let index = ((exp & 1) as u64) << 6 | sig >> (F::FRAC_BITS - 6);
+    let out_exp = (3 * bias - 1 - exp) / 2;
+
+    F::from_raw(
+        (out_exp as u64) << F::FRAC_BITS | u64::from(RSQRT7[index as usize]) << (F::FRAC_BITS - 7),
+    )
+}
+
+/// How a load or store finds each element
+#[derive(Debug, Clone, Copy)]
+enum Addressing {
+    /// One after the other
+    Unit,
+    /// `rs2` bytes apart
+    Strided(u64),
+    /// At the offsets in `vs2`
+    Indexed(Reg),
+}
+
+/// Where the fields of each element of a load or store are, in memory and in registers
+struct Access {
+    base: u64,
+    /// The width of the data, SEW for indexed accesses
+    eew: usize,
+    /// Registers in each field's group
+    regs: Reg,
+    mem: VMem,
+    addressing: Addressing,
+}
+
+impl Access {
+    /// Checks the register groups of a load or store to `vd`, or from `vs3`
+    fn new(
+        cpu: &RV64GC,
+        cfg: &Config,
+        vd: Reg,
+        rs1: Reg,
+        mem: VMem,
+        addressing: Addressing,
+    ) -> Result<Access, Exception> {
+        let (eew, emul) = match addressing {
+            Addressing::Indexed(vs2) => {
+                check_group(vs2, cfg.emul(usize::from(mem.eew / 8)))?;
+                (cfg.sew, cfg.lmul)
+            }
+            Addressing::Unit | Addressing::Strided(_) => {
+                let eew = usize::from(mem.eew / 8);
+                (eew, cfg.emul(eew))
+            }
+        };
+
+        check_group(vd, emul)?;
+        let regs = 1 << emul.max(0);
+        let nf = usize::from(mem.nf);
+        if nf * regs > 8 || usize::from(vd) + nf * regs > 32 {
+            return Err(Exception::IllegalInstruction);
+        }
+
+        Ok(Access {
+            base: cpu.registers[&rs1],
+            eew,
+            regs: regs as Reg,
+            mem,
+            addressing,
+        })
+    }
+
+    /// The address of field `field` of element `index`
+    fn address(&self, cpu: &RV64GC, cfg: &Config, index: usize, field: usize) -> u64 {
+        let field_offset = (field * self.eew) as u64;
+        let offset = match self.addressing {
+            Addressing::Unit => ((index * usize::from(self.mem.nf) + field) * self.eew) as u64,
+            Addressing::Strided(stride) => (index as u64)
+                .wrapping_mul(stride)
+                .wrapping_add(field_offset),
+            Addressing::Indexed(vs2) => {
+                let offset = cfg.get(cpu, vs2, index, usize::from(self.mem.eew / 8));
+                offset.wrapping_add(field_offset)
+            }
+        };
+
+        self.base.wrapping_add(offset)
+    }
+
+    /// The register group of field `field`
+    fn register(&self, vd: Reg, field: usize) -> Reg {
+        vd + field as Reg * self.regs
+    }
+}
+
+fn read_element(cpu: &RV64GC, addr: u64, eew: usize) -> Result<u64, Exception> {
+    let value = match eew {
+        1 => cpu.ram.read_byte(addr).map(u64::from),
+        2 => cpu.ram.read_halfword(addr),
+        4 => cpu.ram.read_word(addr).map(u64::from),
+        _ => cpu.ram.read_doubleword(addr),
+    };
+
+    value.map_err(Exception::load)
+}
+
+fn write_element(cpu: &mut RV64GC, addr: u64, eew: usize, value: u64) -> Result<(), Exception> {
+    let res = match eew {
+        1 => cpu.ram.write_byte(addr, value as u8),
+        2 => cpu.ram.write_halfword(addr, value),
+        4 => cpu.ram.write_word(addr, value as u32),
+        _ => cpu.ram.write_doubleword(addr, value),
+    };
+
+    res.map_err(Exception::store)
+}
+
+/// Unit-stride, strided and indexed loads, segments included. A fault leaves `vstart` at
+/// the element that faulted, except that fault-only-first loads only trap on element 0 and
+/// cut `vl` short for the later ones
+fn load(
+    cpu: &mut RV64GC,
+    cfg: &Config,
+    vd: Reg,
+    rs1: Reg,
+    mem: VMem,
+    addressing: Addressing,
+    fault_only_first: bool,
+) -> Result<(), Exception> {
+    let access = Access::new(cpu, cfg, vd, rs1, mem, addressing)?;
+    check_mask(vd, mem.vm)?;
+
+    for index in cfg.body() {
+        if !active(cpu, mem.vm, index) {
+            continue;
+        }
+
+        for field in 0..usize::from(mem.nf) {
+            let addr = access.address(cpu, cfg, index, field);
+            match read_element(cpu, addr, access.eew) {
+                Ok(value) => cfg.set(cpu, access.register(vd, field), index, access.eew, value),
+                Err(_) if fault_only_first && index > 0 => {
+                    cpu.vector.vl = index as u64;
+                    return Ok(());
+                }
+                Err(err) => {
+                    cpu.vector.vstart = index as u64;
+                    return Err(err);
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// The stores matching [`load`]
+fn store(
+    cpu: &mut RV64GC,
+    cfg: &Config,
+    vs3: Reg,
+    rs1: Reg,
+    mem: VMem,
+    addressing: Addressing,
+) -> Result<(), Exception> {
+    let access = Access::new(cpu, cfg, vs3, rs1, mem, addressing)?;
+
+    for index in cfg.body() {
+        if !active(cpu, mem.vm, index) {
+            continue;
+        }
+
+        for field in 0..usize::from(mem.nf) {
+            let addr = access.address(cpu, cfg, index, field);
+            let value = cfg.get(cpu, access.register(vs3, field), index, access.eew);
+            if let Err(err) = write_element(cpu, addr, access.eew, value) {
+                cpu.vector.vstart = index as u64;
+                return Err(err);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// `vlm.v`, the `ceil(vl / 8)` bytes of a mask
+fn load_mask(cpu: &mut RV64GC, cfg: &Config, vd: Reg, rs1: Reg) -> Result<(), Exception> {
+    let base = cpu.registers[&rs1];
+    for index in cfg.vstart..cfg.vl.div_ceil(8) {
+        match read_element(cpu, base.wrapping_add(index as u64), 1) {
+            Ok(value) => cfg.set(cpu, vd, index, 1, value),
+            Err(err) => {
+                cpu.vector.vstart = index as u64;
+                return Err(err);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// `vsm.v`
+fn store_mask(cpu: &mut RV64GC, cfg: &Config, vs3: Reg, rs1: Reg) -> Result<(), Exception> {
+    let base = cpu.registers[&rs1];
+    for index in cfg.vstart..cfg.vl.div_ceil(8) {
+        let value = cfg.get(cpu, vs3, index, 1);
+        if let Err(err) = write_element(cpu, base.wrapping_add(index as u64), 1, value) {
+            cpu.vector.vstart = index as u64;
+            return Err(err);
+        }
+    }
+
+    Ok(())
+}
+
+/// Whole register loads and stores move 1, 2, 4 or 8 registers, starting at a multiple of
+/// that
+fn check_whole(vd: Reg, mem: VMem) -> Result<(), Exception> {
+    if !mem.nf.is_power_of_two() || !vd.is_multiple_of(mem.nf) {
+        return Err(Exception::IllegalInstruction);
+    }
+
+    Ok(())
+}
+
+/// `vl<nf>re<eew>.v`
+fn load_whole(cpu: &mut RV64GC, vd: Reg, rs1: Reg, mem: VMem) -> Result<(), Exception> {
+    check_whole(vd, mem)?;
+
+    let vlenb = cpu.isa.vlenb();
+    let eew = usize::from(mem.eew / 8);
+    let base = cpu.registers[&rs1];
+    for index in cpu.vector.vstart as usize..usize::from(mem.nf) * vlenb / eew {
+        match read_element(cpu, base.wrapping_add((index * eew) as u64), eew) {
+            Ok(value) => cpu.vector.set_element(vlenb, vd, index, eew, value),
+            Err(err) => {
+                cpu.vector.vstart = index as u64;
+                return Err(err);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// `vs<nf>r.v`
+fn store_whole(cpu: &mut RV64GC, vs3: Reg, rs1: Reg, mem: VMem) -> Result<(), Exception> {
+    check_whole(vs3, mem)?;
+
+    let vlenb = cpu.isa.vlenb();
+    let base = cpu.registers[&rs1];
+    for index in cpu.vector.vstart as usize..usize::from(mem.nf) * vlenb {
+        let value = cpu.vector.element(vlenb, vs3, index, 1);
+        if let Err(err) = write_element(cpu, base.wrapping_add(index as u64), 1, value) {
+            cpu.vector.vstart = index as u64;
+            return Err(err);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ram::MemoryRegion;
+
+    const DATA: u64 = 0x1000;
+
+    fn cpu() -> RV64GC {
+        let mut cpu = RV64GC::new();
+        cpu.isa = crate::isa::Isa::RV64GCV_ZB;
+        cpu.ram
+            .add_region(MemoryRegion::new(DATA, 0x1000, vec![0; 0x1000]))
+            .unwrap();
+        cpu
+    }
+
+    fn run(cpu: &mut RV64GC, program: &[u32]) {
+        for &ins in program {
+            cpu.execute(ins).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_integer() {
+        let mut cpu = cpu();
+        for (i, value) in [1i32, -2, 3, 4].into_iter().enumerate() {
+            cpu.ram
+                .write_word(DATA + 4 * i as u64, value as u32)
+                .unwrap();
+        }
+        cpu.registers[&11] = 10;
+        cpu.registers[&12] = DATA;
+        cpu.registers[&13] = DATA + 0x100;
+        cpu.registers[&14] = 2;
+
+        run(
+            &mut cpu,
+            &[
+                0x0505f557, // vsetvli a0, a1, e32, m1, ta, mu
+                0x02066087, // vle32.v v1, (a2)
+                0x02108157, // vadd.vv v2, v1, v1
+                0x0206e127, // vse32.v v2, (a3)
+                0x021021d7, // vredsum.vs v3, v1, v0
+                0x6e174057, // vmslt.vx v0, v1, a4
+                0x00153157, // vadd.vi v2, v1, 10, v0.t
+                0x420827d7, // vcpop.m a5, v0
+            ],
+        );
+
+        // NOTE: VLEN is 128, so only 4 of the 10 elements asked for fit
+        assert_eq!(cpu.registers[&10], 4);
+        let stored: Vec<u32> = (0..4)
+            .map(|i| cpu.ram.read_word(DATA + 0x100 + 4 * i).unwrap())
+            .collect();
+        assert_eq!(stored, [2, -4i32 as u32, 6, 8]);
+
+        let vlenb = cpu.isa.vlenb();
+        assert_eq!(cpu.vector.element(vlenb, 3, 0, 4), 6);
+        assert_eq!(cpu.vector.register(0, vlenb)[0], 0b0011);
+        let v2: Vec<u64> = (0..4).map(|i| cpu.vector.element(vlenb, 2, i, 4)).collect();
+        assert_eq!(v2, [11, 8, 6, 8]);
+        assert_eq!(cpu.registers[&15], 2);
+    }
+
+    #[test]
+    fn test_configuration() {
+        let mut cpu = cpu();
+        cpu.isa = "rv64gcv_zvl256b".parse().unwrap();
+        cpu.registers[&11] = 100;
+
+        cpu.execute(0xc2202573).unwrap(); // csrr a0, vlenb
+        assert_eq!(cpu.registers[&10], 32);
+
+        cpu.execute(0x0505f557).unwrap(); // vsetvli a0, a1, e32, m1, ta, mu
+        assert_eq!(cpu.registers[&10], 8);
+
+        cpu.execute(0xc20025f3).unwrap(); // csrr a1, vl
+        assert_eq!(cpu.registers[&11], 8);
+
+        // NOTE: A reserved vtype sets vill, after which only vset{i}vl{i} works
+        cpu.registers[&12] = 1 << 8;
+        cpu.execute(0x80c5f557).unwrap(); // vsetvl a0, a1, a2
+        assert_eq!(cpu.vector.vtype, VTYPE_VILL);
+        assert_eq!(cpu.registers[&10], 0);
+        assert!(matches!(
+            cpu.execute(0x02108157), // vadd.vv v2, v1, v1
+            Err(Exception::IllegalInstruction)
+        ));
+
+        // NOTE: Without V the vector CSRs don't exist
+        cpu.isa = crate::isa::Isa::RV64GC_ZB;
+        assert!(matches!(
+            cpu.execute(0xc2202573),
+            Err(Exception::IllegalInstruction)
+        ));
+    }
+
+    #[test]
+    fn test_fixed_point_and_floats() {
+        let mut cpu = cpu();
+        cpu.registers[&11] = 10;
+        cpu.float_registers[&10] = 1.5f64.to_bits();
+
+        let vlenb = cpu.isa.vlenb();
+        for i in 0..8 {
+            cpu.vector.set_element(vlenb, 5, i, 1, 250);
+        }
+        cpu.vector
+            .set_element(vlenb, 10, 0, 8, (1.0 + 2f64.powi(-30)).to_bits());
+
+        run(
+            &mut cpu,
+            &[
+                0xcd827057, // vsetivli zero, 4, e64, m1, ta, ma
+                0x5e055257, // vfmv.v.f v4, fa0
+                0xb2421257, // vfmacc.vv v4, v4, v4
+                0x424015d7, // vfmv.f.s fa1, v4
+                0xcc047057, // vsetivli zero, 8, e8, m1, ta, ma
+                0x8255c2d7, // vsaddu.vx v5, v5, a1
+                0x3a513357, // vslideup.vi v6, v5, 2
+                0xcd027057, // vsetivli zero, 4, e32, m1, ta, ma
+                0x4aaa94d7, // vfncvt.rod.f.f.w v9, v10
+            ],
+        );
+
+        assert_eq!(f64::from_bits(cpu.float_registers[&11]), 3.75);
+        assert_eq!(cpu.vector.register(5, vlenb)[..8], [255; 8]);
+        assert!(cpu.vector.vxsat);
+        assert_eq!(
+            cpu.vector.register(6, vlenb)[..8],
+            [0, 0, 255, 255, 255, 255, 255, 255]
+        );
+
+        // NOTE: Rounding to odd sets the lowest bit of an inexact result
+        assert_eq!(cpu.vector.element(vlenb, 9, 0, 4), 0x3F80_0001);
+        assert_eq!(cpu.fcsr.fflags(), FCSR::NX);
+    }
+}
@@ -2,6 +2,7 @@ use std::{
     collections::HashSet,
     fs::File,
     io::{self, Read},
+    num::ParseIntError,
 };
 
 use layout::Flex;
@@ -15,7 +16,10 @@ use ratatui::{
     widgets::{Block, Borders, Cell, List, Padding, Paragraph, Row, Table, TableState},
     DefaultTerminal,
 };
-use riscvm_core::cpu::{RV64GCInstruction, RV64GC};
+use riscvm_core::{
+    cpu::{RV64GCInstruction, StepOutcome, RV64GC},
+    launch::LaunchConfig,
+};
 use tui_popup::Popup;
 use tui_prompts::{Prompt, State, TextPrompt, TextState};
 
@@ -59,9 +63,12 @@ fn main() -> io::Result<()> {
     };
 
     let mut buf = Vec::new();
-    File::open(path).unwrap().read_to_end(&mut buf).unwrap();
+    File::open(&path).unwrap().read_to_end(&mut buf).unwrap();
+
+    let mut config = LaunchConfig::new(std::env::args().skip(1).collect());
+    config.envp = std::env::vars().map(|(k, v)| format!("{k}={v}")).collect();
 
-    cpu.load_elf(buf).unwrap();
+    cpu.load_elf(buf, config).unwrap();
 
     let app = App::new();
 
@@ -91,6 +98,25 @@ fn update_ins_table(cpu: &RV64GC, ins_count: usize) -> Vec<(u64, RV64GCInstructi
     ins_vec
 }
 
+/// Steps the cpu once, returning a popup if the guest trapped or quit
+fn step_cpu<'a>(cpu: &mut RV64GC) -> Option<Popup<'a, Text<'a>>> {
+    match cpu.step() {
+        Ok(StepOutcome::Continue) => None,
+        Ok(StepOutcome::Halted) => Some(
+            Popup::new(
+                Text::from(format!("Program has quit! ({:?})", cpu.exit_reason())).centered(),
+            )
+            .title("CPU")
+            .style(Style::new().bg(Color::from_u32(0x3b3f63))),
+        ),
+        Err(trap) => Some(
+            Popup::new(Text::from(format!("{trap}")).centered())
+                .title("TRAP")
+                .style(Style::new().fg(Color::Red).bg(Color::from_u32(0x3b3f63))),
+        ),
+    }
+}
+
 fn jump_to_point(
     cpu: &mut RV64GC,
     app: &mut App,
@@ -104,7 +130,10 @@ fn jump_to_point(
     let selection = ins_vec[selected_ins];
 
     if cpu.registers[32] == selection.0 {
-        cpu.step();
+        if let Some(popup) = step_cpu(cpu) {
+            app.popup = popup;
+            app.show_popup = true;
+        }
         return;
     }
 
@@ -120,7 +149,20 @@ fn jump_to_point(
             return;
         }
 
-        cpu.step();
+        if let Some(popup) = step_cpu(cpu) {
+            app.popup = popup;
+            app.show_popup = true;
+            return;
+        }
+    }
+}
+
+/// Parses a hex address, `@` marks a link-time address that still needs the load bias added
+fn parse_addr(cpu: &RV64GC, s: &str) -> Result<u64, ParseIntError> {
+    match s.strip_prefix('@') {
+        Some(link_addr) => u64::from_str_radix(link_addr.trim_start_matches("0x"), 16)
+            .map(|addr| addr.wrapping_add(cpu.load_bias)),
+        None => u64::from_str_radix(s.trim_start_matches("0x"), 16),
     }
 }
 
@@ -158,7 +200,7 @@ fn handle_cmd<'a>(app: &mut App, cpu: &mut RV64GC, cmd: String) -> Popup<'a, Tex
                             .parse::<u8>()
                             .map(|i| cpu.registers[&i])
                     } else {
-                        u64::from_str_radix(str_addr.trim_start_matches("0x"), 16)
+                        parse_addr(cpu, str_addr)
                     };
 
                     let Ok(addr) = addr else {
@@ -181,6 +223,23 @@ fn handle_cmd<'a>(app: &mut App, cpu: &mut RV64GC, cmd: String) -> Popup<'a, Tex
             }
         }
 
+        "bias" => {
+            let interp = match cpu.interp_base {
+                Some(base) => format!("0x{base:016x}"),
+                None => "none".to_string(),
+            };
+
+            Popup::new(
+                Text::from(format!(
+                    "executable: 0x{:016x}\ninterpreter: {interp}",
+                    cpu.load_bias
+                ))
+                .centered(),
+            )
+            .title("LOAD BIAS")
+            .style(style)
+        }
+
         "reset" => {
             cpu.reset();
             Popup::new(Text::from("cpu has been reset!").centered())
@@ -205,10 +264,7 @@ fn handle_cmd<'a>(app: &mut App, cpu: &mut RV64GC, cmd: String) -> Popup<'a, Tex
                 _ => {}
             };
 
-            let Some(addr) = split_cmds
-                .get(2)
-                .and_then(|s| u64::from_str_radix(s.trim_start_matches("0x"), 16).ok())
-            else {
+            let Some(addr) = split_cmds.get(2).and_then(|s| parse_addr(cpu, s).ok()) else {
                 return err_popup("Missing address!");
             };
 
@@ -246,7 +302,9 @@ fn handle_cmd<'a>(app: &mut App, cpu: &mut RV64GC, cmd: String) -> Popup<'a, Tex
         "cont" | "c" => {
             if split_cmds.len() == 1 {
                 while !app.breakpoints.contains(&cpu.registers[32]) {
-                    cpu.step();
+                    if let Some(popup) = step_cpu(cpu) {
+                        return popup;
+                    }
                 }
 
                 return Popup::new(Text::from(format!(
@@ -270,11 +328,12 @@ fn handle_cmd<'a>(app: &mut App, cpu: &mut RV64GC, cmd: String) -> Popup<'a, Tex
 
                         let og_copy = cpu.registers[&reg_num];
                         while cpu.registers[&reg_num] == og_copy {
-                            cpu.step();
+                            if let Some(popup) = step_cpu(cpu) {
+                                return popup;
+                            }
                         }
                     } else {
-                        let Ok(addr) = u64::from_str_radix(str_addr.trim_start_matches("0x"), 16)
-                        else {
+                        let Ok(addr) = parse_addr(cpu, str_addr) else {
                             return err_popup("Invalid Address!");
                         };
 
@@ -283,20 +342,24 @@ fn handle_cmd<'a>(app: &mut App, cpu: &mut RV64GC, cmd: String) -> Popup<'a, Tex
                         };
 
                         while og_copy == cpu.ram.read_doubleword(addr).unwrap() {
-                            cpu.step();
+                            if let Some(popup) = step_cpu(cpu) {
+                                return popup;
+                            }
                         }
                     };
                     Popup::new(Text::from(format!("at {str_addr} now!")).centered()).style(style)
                 }
                 "to" => {
-                    let addr = u64::from_str_radix(str_addr.trim_start_matches("0x"), 16);
+                    let addr = parse_addr(cpu, str_addr);
 
                     let Ok(addr) = addr else {
                         return err_popup("Invalid address!\nPlease use a hex address");
                     };
 
                     while cpu.registers[32] != addr {
-                        cpu.step();
+                        if let Some(popup) = step_cpu(cpu) {
+                            return popup;
+                        }
                     }
 
                     Popup::new(Text::from(format!("at {str_addr} now!")).centered()).style(style)
@@ -406,7 +469,10 @@ fn run(mut term: DefaultTerminal, mut cpu: RV64GC, mut app: App) -> io::Result<(
                                 return Ok(());
                             }
                             KeyCode::Char('n') => {
-                                cpu.step();
+                                if let Some(popup) = step_cpu(&mut cpu) {
+                                    app.popup = popup;
+                                    app.show_popup = true;
+                                }
 
                                 table_state.select_first();
                             }
@@ -2,8 +2,30 @@ use std::io::Read;
 use tracing::level_filters::LevelFilter;
 use tracing_subscriber::filter::EnvFilter;
 
-use cpu::RV64GC;
+use cpu::{ExecutionMode, ExitReason, RV64GC};
+use exception::Trap;
+use isa::Isa;
+use launch::LaunchConfig;
 use riscvm_core::*;
+use syscalls::UnknownSyscallPolicy;
+
+const USAGE: &str = "Usage: riscvm [OPTIONS] <BINARY> [ARGS]...
+
+Options:
+  --sysroot DIR      Look up the interpreter and absolute guest paths in DIR first
+  --root DIR         Confine the guest's file system access to DIR, like a chroot
+  --read-only        Don't let the guest create, modify or remove host files
+  --pie-base ADDR    Load position independent executables at ADDR (hex)
+  --aslr             Randomize the executable and interpreter load addresses
+  --heap-limit BYTES How far brk can grow the heap (1 GB by default)
+  --isa ISA          Extensions the guest can use on top of rv64gc (the default), like
+                     rv64gc_zba_zbb_zbs or rv64gcv_zvl256b (zvl<N>b sets VLEN)
+  --clear-env        Don't pass the host environment to the guest
+  --env KEY=VALUE    Set an environment variable for the guest
+  --unknown-syscall POLICY
+                     enosys (default), trap or panic on syscalls riscvm doesn't implement
+  --exec MODE        step (default) through one instruction at a time, run translated
+                     blocks, or jit them (when built with the jit feature)";
 
 fn main() {
     tracing_subscriber::fmt()
@@ -13,12 +35,116 @@ fn main() {
         .without_time()
         .init();
 
-    if std::env::args().len() < 2 {
-        eprintln!("No binary specified!\n");
-        return;
-    }
+    let mut config = LaunchConfig {
+        envp: std::env::vars().map(|(k, v)| format!("{k}={v}")).collect(),
+        ..Default::default()
+    };
+
+    let mut unknown_syscall_policy = UnknownSyscallPolicy::default();
+    let mut execution_mode = ExecutionMode::default();
+    let mut isa = Isa::default();
+
+    let mut args = std::env::args().skip(1);
+    let file_path = loop {
+        match args.next().as_deref() {
+            Some("--clear-env") => config.envp.clear(),
+            Some("--env") => {
+                let Some(var) = args.next() else {
+                    eprintln!("--env expects KEY=VALUE\n");
+                    std::process::exit(2);
+                };
+
+                match var.split_once('=') {
+                    Some((key, value)) => config.set_env(key, value),
+                    None => config.set_env(&var, ""),
+                }
+            }
+            Some("--sysroot") => {
+                let Some(dir) = args.next() else {
+                    eprintln!("--sysroot expects a directory\n");
+                    std::process::exit(2);
+                };
+
+                config.sysroot = Some(dir.into());
+            }
+            Some("--root") => {
+                let Some(dir) = args.next() else {
+                    eprintln!("--root expects a directory\n");
+                    std::process::exit(2);
+                };
+
+                config.root = Some(dir.into());
+            }
+            Some("--read-only") => config.read_only = true,
+            Some("--pie-base") => {
+                let base = args
+                    .next()
+                    .and_then(|s| u64::from_str_radix(s.trim_start_matches("0x"), 16).ok());
+                let Some(base) = base.filter(|base| base.is_multiple_of(4096)) else {
+                    eprintln!("--pie-base expects a page aligned hex address\n");
+                    std::process::exit(2);
+                };
+
+                config.pie_base = base;
+            }
+            Some("--aslr") => config.randomize_base = true,
+            Some("--heap-limit") => {
+                let Some(limit) = args.next().and_then(|s| s.parse().ok()) else {
+                    eprintln!("--heap-limit expects a size in bytes\n");
+                    std::process::exit(2);
+                };
+
+                config.heap_limit = limit;
+            }
+            Some("--isa") => {
+                let Some(string) = args.next() else {
+                    eprintln!("--isa expects an ISA string like rv64gc_zba_zbb\n");
+                    std::process::exit(2);
+                };
+
+                isa = match string.parse() {
+                    Ok(isa) => isa,
+                    Err(e) => {
+                        eprintln!("--isa: {e}\n");
+                        std::process::exit(2);
+                    }
+                };
+            }
+            Some("--unknown-syscall") => {
+                unknown_syscall_policy = match args.next().as_deref() {
+                    Some("enosys") => UnknownSyscallPolicy::Enosys,
+                    Some("trap") => UnknownSyscallPolicy::Trap,
+                    Some("panic") => UnknownSyscallPolicy::Panic,
+                    _ => {
+                        eprintln!("--unknown-syscall expects enosys, trap or panic\n");
+                        std::process::exit(2);
+                    }
+                };
+            }
+            Some("--exec") => {
+                execution_mode = match args.next().as_deref() {
+                    Some("step") => ExecutionMode::Step,
+                    Some("blocks") => ExecutionMode::Blocks,
+                    #[cfg(all(feature = "jit", target_arch = "x86_64"))]
+                    Some("jit") => ExecutionMode::Jit,
+                    _ => {
+                        eprintln!("--exec expects step, blocks or jit\n");
+                        std::process::exit(2);
+                    }
+                };
+            }
+            Some(path) => break path.to_string(),
+            None => {
+                eprintln!("No binary specified!\n");
+                eprintln!("{USAGE}");
+                std::process::exit(2);
+            }
+        }
+    };
+
+    config.argv = std::iter::once(file_path.clone()).chain(args).collect();
+    config.execfn = Some(file_path.clone());
 
-    let file_path = std::env::args().nth(1).unwrap();
     let mut bin = Vec::new();
     std::fs::File::open(&file_path)
         .unwrap()
@@ -26,12 +152,112 @@ fn main() {
         .unwrap();
 
     let mut riscvm = RV64GC::new();
+    riscvm.unknown_syscall_policy = unknown_syscall_policy;
+    riscvm.execution_mode = execution_mode;
+    riscvm.isa = isa;
 
     if file_path.ends_with(".bin") {
         riscvm.load_bin(bin);
     } else {
-        riscvm.load_elf(bin).unwrap();
+        if let Err(e) = riscvm.load_elf(bin, config) {
+            eprintln!("riscvm: couldn't load {file_path}: {e}");
+            std::process::exit(1);
+        }
     }
 
-    riscvm.start();
+    std::process::exit(exit_status(riscvm.start()));
+}
+
+/// The status riscvm exits with, the guest's own if it exited
+fn exit_status(reason: ExitReason) -> i32 {
+    match reason {
+        ExitReason::Exited(code) => code,
+        ExitReason::Trapped(trap) => {
+            eprintln!("riscvm: guest trapped: {trap}");
+            128 + signal_for(&trap)
+        }
+        ExitReason::Breakpoint { pc } => {
+            eprintln!("riscvm: guest hit a breakpoint at {pc:#x}");
+            128 + SIGTRAP
+        }
+        ExitReason::BudgetExhausted => {
+            eprintln!("riscvm: guest ran out of instructions");
+            BUDGET_EXHAUSTED
+        }
+        ExitReason::Stopped => 0,
+    }
+}
+
+/// What `timeout(1)` exits with, so a guest that didn't finish isn't taken for a clean exit
+const BUDGET_EXHAUSTED: i32 = 124;
+
+const SIGILL: i32 = 4;
+const SIGTRAP: i32 = 5;
+const SIGBUS: i32 = 7;
+const SIGSEGV: i32 = 11;
+const SIGSYS: i32 = 31;
+
+/// The signal Linux would have killed the guest with, so shells see the usual `128 + n`
+fn signal_for(trap: &Trap) -> i32 {
+    match trap {
+        Trap::IllegalInstruction { .. } => SIGILL,
+        Trap::Breakpoint { .. } => SIGTRAP,
+        Trap::InstructionAddressMisaligned { .. }
+        | Trap::LoadAddressMisaligned { .. }
+        | Trap::StoreAddressMisaligned { .. } => SIGBUS,
+        Trap::InstructionAccessFault { .. }
+        | Trap::LoadAccessFault { .. }
+        | Trap::StoreAccessFault { .. } => SIGSEGV,
+        Trap::UnsupportedSyscall { .. } => SIGSYS,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exit_status() {
+        let (pc, addr) = (0x1000, 0x2000);
+        let cases = [
+            (ExitReason::Exited(0), 0),
+            (ExitReason::Exited(42), 42),
+            (ExitReason::Stopped, 0),
+            (ExitReason::BudgetExhausted, 124),
+            (ExitReason::Breakpoint { pc }, 128 + 5),
+            (ExitReason::Trapped(Trap::Breakpoint { pc }), 128 + 5),
+            (
+                ExitReason::Trapped(Trap::IllegalInstruction { pc, bits: 0 }),
+                128 + 4,
+            ),
+            (
+                ExitReason::Trapped(Trap::LoadAddressMisaligned { pc, addr }),
+                128 + 7,
+            ),
+            (
+                ExitReason::Trapped(Trap::StoreAddressMisaligned { pc, addr }),
+                128 + 7,
+            ),
+            (
+                ExitReason::Trapped(Trap::InstructionAccessFault { pc, addr }),
+                128 + 11,
+            ),
+            (
+                ExitReason::Trapped(Trap::LoadAccessFault { pc, addr }),
+                128 + 11,
+            ),
+            (
+                ExitReason::Trapped(Trap::StoreAccessFault { pc, addr }),
+                128 + 11,
+            ),
+            (
+                ExitReason::Trapped(Trap::UnsupportedSyscall { pc, number: 1000 }),
+                128 + 31,
+            ),
+        ];
+
+        for (reason, status) in cases {
+            assert_eq!(exit_status(reason), status, "{reason:?}");
+        }
+    }
 }